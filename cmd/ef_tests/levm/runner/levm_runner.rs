@@ -1,11 +1,11 @@
 use crate::{
     report::{EFTestReport, TestVector},
-    runner::{EFTestRunnerError, InternalError},
+    runner::{revm_runner::effective_gas_price, EFTestRunnerError, InternalError},
     types::EFTest,
     utils,
 };
 use ethrex_core::{
-    types::{code_hash, AccountInfo},
+    types::{AccountInfo, Fork},
     H256, U256,
 };
 use ethrex_levm::{
@@ -86,7 +86,6 @@ pub fn prepare_vm_for_tx(vector: &TestVector, test: &EFTest) -> Result<VM, EFTes
         Environment {
             origin: tx.sender,
             consumed_gas: U256::default(),
-            refunded_gas: U256::default(),
             gas_limit: tx.gas_limit,
             block_number: test.env.current_number,
             coinbase: test.env.current_coinbase,
@@ -94,14 +93,21 @@ pub fn prepare_vm_for_tx(vector: &TestVector, test: &EFTest) -> Result<VM, EFTes
             prev_randao: test.env.current_random,
             chain_id: U256::from(1729),
             base_fee_per_gas: test.env.current_base_fee.unwrap_or_default(),
-            gas_price: tx.gas_price.unwrap_or_default(),
+            gas_price: effective_gas_price(test, &tx),
             block_excess_blob_gas: test.env.current_excess_blob_gas,
             block_blob_gas_used: None,
             tx_blob_hashes: tx.blob_versioned_hashes.clone(),
             tx_max_priority_fee_per_gas: tx.max_priority_fee_per_gas,
             tx_max_fee_per_gas: tx.max_fee_per_gas,
             tx_max_fee_per_blob_gas: tx.max_fee_per_blob_gas,
+            tx_access_list: tx
+                .access_list
+                .iter()
+                .map(|item| (item.address, item.storage_keys.clone()))
+                .collect(),
             block_gas_limit: test.env.current_gas_limit,
+            fork: Fork::Cancun,
+            trace_transfers: false,
         },
         tx.value,
         tx.data.clone(),
@@ -114,7 +120,9 @@ pub fn prepare_vm_for_tx(vector: &TestVector, test: &EFTest) -> Result<VM, EFTes
 pub fn ensure_pre_state(evm: &VM, test: &EFTest) -> Result<(), EFTestRunnerError> {
     let world_state = &evm.db;
     for (address, pre_value) in &test.pre.0 {
-        let account = world_state.get_account_info(*address);
+        let account = world_state
+            .get_account_info(*address)
+            .map_err(|err| EFTestRunnerError::FailedToEnsurePreState(err.to_string()))?;
         ensure_pre_state_condition(
             account.nonce == pre_value.nonce.as_u64(),
             format!(
@@ -132,7 +140,9 @@ pub fn ensure_pre_state(evm: &VM, test: &EFTest) -> Result<(), EFTestRunnerError
         for (k, v) in &pre_value.storage {
             let mut key_bytes = [0u8; 32];
             k.to_big_endian(&mut key_bytes);
-            let storage_slot = world_state.get_storage_slot(*address, H256::from_slice(&key_bytes));
+            let storage_slot = world_state
+                .get_storage_slot(*address, H256::from_slice(&key_bytes))
+                .map_err(|err| EFTestRunnerError::FailedToEnsurePreState(err.to_string()))?;
             ensure_pre_state_condition(
                 &storage_slot == v,
                 format!(
@@ -231,7 +241,7 @@ pub fn get_state_transitions(execution_report: &TransactionReport) -> Vec<Accoun
             address: *address,
             removed: false,
             info: Some(AccountInfo {
-                code_hash: code_hash(&account.info.bytecode),
+                code_hash: account.info.code_hash,
                 balance: account.info.balance,
                 nonce: account.info.nonce,
             }),
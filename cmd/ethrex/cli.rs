@@ -47,8 +47,60 @@ pub fn cli() -> Command {
                 .long("authrpc.jwtsecret")
                 .default_value("jwt.hex")
                 .value_name("JWTSECRET_PATH")
+                .action(ArgAction::Set)
+                .help("Path of the JWT secret shared with the consensus client, which is reloaded when the file changes"),
+        )
+        .arg(
+            Arg::new("authrpc.jwt_clock_skew")
+                .long("authrpc.jwt-clock-skew")
+                .default_value("60")
+                .value_name("SECONDS")
+                .value_parser(clap::value_parser!(u64))
+                .help("Maximum difference between the iat claim of the JWT tokens and the local clock"),
+        )
+        .arg(
+            Arg::new("authrpc.jwt_log_claims")
+                .long("authrpc.jwt-log-claims")
+                .required(false)
+                .action(ArgAction::SetTrue)
+                .help("Log the id and clv claims the consensus client sends in its JWT tokens"),
+        )
+        .arg(
+            Arg::new("rpc.batch_request_limit")
+                .long("rpc.batch-request-limit")
+                .default_value("1000")
+                .value_name("LIMIT")
+                .value_parser(clap::value_parser!(usize))
+                .help("Maximum number of requests in a JSON-RPC batch"),
+        )
+        .arg(
+            Arg::new("graphql")
+                .long("graphql")
+                .required(false)
+                .action(ArgAction::SetTrue)
+                .help("Serve the EIP-1767 GraphQL API at /graphql on the HTTP server"),
+        )
+        .arg(
+            Arg::new("ipcpath")
+                .long("ipcpath")
+                .value_name("PATH")
+                .action(ArgAction::Set)
+                .help("Serve the JSON-RPC API, engine namespace included, on a unix socket at the given path"),
+        )
+        .arg(
+            Arg::new("metrics.addr")
+                .long("metrics.addr")
+                .default_value("localhost")
+                .value_name("ADDRESS")
                 .action(ArgAction::Set),
         )
+        .arg(
+            Arg::new("metrics.port")
+                .long("metrics.port")
+                .value_name("PORT")
+                .action(ArgAction::Set)
+                .help("Port of the Prometheus metrics endpoint, which is only served when set"),
+        )
         .arg(
             Arg::new("p2p.addr")
                 .long("p2p.addr")
@@ -102,7 +154,15 @@ pub fn cli() -> Command {
             Arg::new("import")
                 .long("import")
                 .required(false)
-                .value_name("CHAIN_RLP_PATH"),
+                .value_name("CHAIN_RLP_PATH")
+                .help("Path to a chain.rlp file, an era1 file or a directory of era1 files"),
+        )
+        .arg(
+            Arg::new("trusted_import")
+                .long("trusted-import")
+                .required(false)
+                .action(ArgAction::SetTrue)
+                .help("Treat imported blocks as coming from a trusted source and skip all of their validation: headers, gas, blobs, receipts and the state root"),
         )
         .arg(
             Arg::new("syncmode")
@@ -110,6 +170,53 @@ pub fn cli() -> Command {
                 .required(false)
                 .value_name("SYNC_MODE"),
         )
+        .arg(
+            Arg::new("evm")
+                .long("evm")
+                .required(false)
+                .default_value("levm")
+                .value_name("EVM_BACKEND")
+                .value_parser(["revm", "levm"])
+                .help("EVM implementation used to execute blocks and to serve eth_call, eth_estimateGas and eth_createAccessList"),
+        )
+        .arg(
+            Arg::new("evm_differential")
+                .long("evm-differential")
+                .required(false)
+                .action(ArgAction::SetTrue)
+                .help("Execute every added block with both revm and LEVM, saving the transactions where they diverge as EF state tests in <datadir>/evm-divergences"),
+        )
+        .arg(
+            Arg::new("signer")
+                .long("signer")
+                .required(false)
+                .action(ArgAction::SetTrue)
+                .help("Enable the local signer, serving eth_sign, eth_sendTransaction and the personal namespace over IPC with the accounts in <datadir>/keystore"),
+        )
+        .arg(
+            Arg::new("signer.password_file")
+                .long("signer.password-file")
+                .required(false)
+                .value_name("PASSWORD_FILE_PATH")
+                .requires("signer")
+                .help("File with one password per line, used to unlock the keystore accounts on startup"),
+        )
+        .arg(
+            Arg::new("signer.lightkdf")
+                .long("signer.lightkdf")
+                .required(false)
+                .action(ArgAction::SetTrue)
+                .requires("signer")
+                .help("Encrypt new keystore accounts with a cheaper scrypt cost, only meant for devnets"),
+        )
+        .arg(
+            Arg::new("signer.allow_insecure_unlock")
+                .long("signer.allow-insecure-unlock")
+                .required(false)
+                .action(ArgAction::SetTrue)
+                .requires("signer")
+                .help("Also serve the local signer over HTTP, letting anyone who reaches it use the unlocked accounts. Otherwise it's only served over IPC"),
+        )
         .arg(
             Arg::new("import_dir")
                 .long("import_dir")
@@ -124,4 +231,62 @@ pub fn cli() -> Command {
                     .action(ArgAction::Set),
             ),
         )
+        .subcommand(
+            Command::new("db")
+                .about("Database maintenance commands")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("migrate")
+                        .about("Upgrade the database to the current schema version")
+                        .arg(
+                            Arg::new("datadir")
+                                .long("datadir")
+                                .value_name("DATABASE_DIRECTORY")
+                                .action(ArgAction::Set),
+                        ),
+                ),
+        )
+        .subcommand(
+            Command::new("export")
+                .about("Export canonical blocks from the database")
+                .arg(
+                    Arg::new("datadir")
+                        .long("datadir")
+                        .value_name("DATABASE_DIRECTORY")
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("from")
+                        .long("from")
+                        .default_value("0")
+                        .value_name("BLOCK_NUMBER")
+                        .value_parser(clap::value_parser!(u64))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("to")
+                        .long("to")
+                        .required(false)
+                        .value_name("BLOCK_NUMBER")
+                        .value_parser(clap::value_parser!(u64))
+                        .help("Last block to export, defaults to the latest block")
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .default_value("rlp")
+                        .value_parser(["rlp", "era1"])
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("path")
+                        .required(true)
+                        .value_name("OUTPUT_PATH")
+                        .help(
+                            "Output file for the rlp format, output directory for the era1 format",
+                        )
+                        .action(ArgAction::Set),
+                ),
+        )
 }
@@ -1,33 +1,18 @@
-use anyhow::Error;
-use bytes::Bytes;
 use ethrex_core::types::{Block, Genesis};
-use ethrex_rlp::decode::RLPDecode as _;
-use std::{
-    fs::File,
-    io::{BufReader, Read as _},
-};
-pub fn jwtsecret_file(file: &mut File) -> Bytes {
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)
-        .expect("Failed to read jwt secret file");
-    if contents[0..2] == *"0x" {
-        contents = contents[2..contents.len()].to_string();
-    }
-    hex::decode(contents)
-        .expect("Secret should be hex encoded")
-        .into()
-}
-pub fn chain_file(file: File) -> Result<Vec<Block>, Error> {
-    let mut chain_rlp_reader = BufReader::new(file);
-    let mut buf = vec![];
-    chain_rlp_reader.read_to_end(&mut buf)?;
-    let mut blocks = Vec::new();
-    while !buf.is_empty() {
-        let (item, rest) = Block::decode_unfinished(&buf)?;
-        blocks.push(item);
-        buf = rest.to_vec();
-    }
-    Ok(blocks)
+use ethrex_rlp::{error::RLPDecodeError, stream::RLPStreamReader};
+use std::{fs::File, io::BufReader};
+
+/// Maximum size of an encoded block read from a file.
+/// The gas limit keeps blocks of current networks well below it, so bigger items are rejected
+/// before being loaded into memory.
+pub const MAX_BLOCK_SIZE: usize = 16 * 1024 * 1024;
+
+/// Returns an iterator over the blocks of a chain file.
+/// Blocks are decoded one at a time, so the whole file is never loaded into memory.
+pub fn chain_file(file: File) -> impl Iterator<Item = Result<Block, RLPDecodeError>> {
+    RLPStreamReader::new(BufReader::new(file))
+        .with_max_item_size(MAX_BLOCK_SIZE)
+        .items()
 }
 
 pub fn genesis_file(file: File) -> Result<Genesis, serde_json::Error> {
@@ -37,14 +22,17 @@ pub fn genesis_file(file: File) -> Result<Genesis, serde_json::Error> {
 
 #[cfg(test)]
 mod tests {
-    use crate::decode::chain_file;
+    use crate::decode::{chain_file, MAX_BLOCK_SIZE};
     use ethrex_core::H256;
-    use std::{fs::File, str::FromStr as _};
+    use ethrex_rlp::error::RLPDecodeError;
+    use std::{fs::File, io::Write, str::FromStr as _};
 
     #[test]
     fn decode_chain_file() {
         let file = File::open("../../test_data/chain.rlp").expect("Failed to open chain file");
-        let blocks = chain_file(file).expect("Failed to decode chain file");
+        let blocks: Vec<_> = chain_file(file)
+            .collect::<Result<_, _>>()
+            .expect("Failed to decode chain file");
         assert_eq!(20, blocks.len(), "There should be 20 blocks in chain file");
         assert_eq!(
             1,
@@ -72,4 +60,20 @@ mod tests {
             "Last block hash does not match"
         );
     }
+
+    #[test]
+    fn chain_file_rejects_oversized_blocks() {
+        let path =
+            std::env::temp_dir().join(format!("ethrex-oversized-{}.rlp", std::process::id()));
+        // List header announcing a payload one byte over the limit, without the payload
+        let mut header = vec![0xfb];
+        header.extend_from_slice(&(MAX_BLOCK_SIZE as u32 + 1).to_be_bytes());
+        File::create(&path).unwrap().write_all(&header).unwrap();
+
+        let mut blocks = chain_file(File::open(&path).unwrap());
+        let result = blocks.next().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(result, Err(RLPDecodeError::InvalidLength)));
+    }
 }
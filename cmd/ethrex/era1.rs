@@ -0,0 +1,457 @@
+//! Reading and writing of era1 archives.
+//! See <https://github.com/eth-clients/e2store-format-specs/blob/main/formats/era1.md>
+//!
+//! An era1 file is an e2store file with the following layout:
+//! `Version | (CompressedHeader | CompressedBody | CompressedReceipts | TotalDifficulty)* | Accumulator | BlockIndex`
+use crate::decode::MAX_BLOCK_SIZE;
+use anyhow::{bail, ensure, Context, Error};
+use bytes::BufMut;
+use ethrex_core::{
+    types::{Block, BlockBody, BlockHeader, Receipt, TxType},
+    H256, U256,
+};
+use ethrex_rlp::{
+    decode::RLPDecode,
+    encode::{encode_length, RLPEncode},
+    view::RlpView,
+};
+use sha2::{Digest, Sha256};
+use std::io::{Read, Write};
+
+/// Maximum amount of blocks an era1 file can hold, also the length of an epoch
+pub const MAX_ERA1_SIZE: u64 = 8192;
+
+const VERSION: u16 = 0x3265;
+const COMPRESSED_HEADER: u16 = 0x03;
+const COMPRESSED_BODY: u16 = 0x04;
+const COMPRESSED_RECEIPTS: u16 = 0x05;
+const TOTAL_DIFFICULTY: u16 = 0x06;
+const ACCUMULATOR: u16 = 0x07;
+const BLOCK_INDEX: u16 = 0x3266;
+
+/// Length of an e2store record header: type (2 bytes), length (4 bytes) and reserved (2 bytes)
+const HEADER_LENGTH: u64 = 8;
+/// Depth of the accumulator's merkle tree, log2(MAX_ERA1_SIZE)
+const ACCUMULATOR_DEPTH: usize = 13;
+
+/// A block as stored in an era1 archive
+#[derive(Debug, Clone, PartialEq)]
+pub struct Era1Block {
+    pub block: Block,
+    pub receipts: Vec<Receipt>,
+    pub total_difficulty: U256,
+}
+
+/// Writes blocks into an era1 archive.
+/// Blocks must be consecutive and at most [`MAX_ERA1_SIZE`] blocks can be written.
+pub struct Era1Writer<W: Write> {
+    writer: W,
+    written: u64,
+    starting_number: Option<u64>,
+    offsets: Vec<u64>,
+    header_records: Vec<(H256, U256)>,
+}
+
+impl<W: Write> Era1Writer<W> {
+    pub fn new(writer: W) -> Result<Self, Error> {
+        let mut era1_writer = Self {
+            writer,
+            written: 0,
+            starting_number: None,
+            offsets: Vec::new(),
+            header_records: Vec::new(),
+        };
+        era1_writer.write_record(VERSION, &[])?;
+        Ok(era1_writer)
+    }
+
+    pub fn add_block(
+        &mut self,
+        block: &Block,
+        receipts: &[Receipt],
+        total_difficulty: U256,
+    ) -> Result<(), Error> {
+        let number = block.header.number;
+        let starting_number = *self.starting_number.get_or_insert(number);
+        ensure!(
+            number == starting_number + self.offsets.len() as u64,
+            "Era1 blocks must be consecutive, got block {number} after {} blocks starting at {starting_number}",
+            self.offsets.len()
+        );
+        ensure!(
+            (self.offsets.len() as u64) < MAX_ERA1_SIZE,
+            "Era1 files can't hold more than {MAX_ERA1_SIZE} blocks"
+        );
+
+        self.offsets.push(self.written);
+        self.write_record(
+            COMPRESSED_HEADER,
+            &snappy_frame_compress(&block.header.encode_to_vec())?,
+        )?;
+        self.write_record(
+            COMPRESSED_BODY,
+            &snappy_frame_compress(&block.body.encode_to_vec())?,
+        )?;
+        self.write_record(
+            COMPRESSED_RECEIPTS,
+            &snappy_frame_compress(&encode_receipts(receipts))?,
+        )?;
+        self.write_record(TOTAL_DIFFICULTY, &u256_to_le_bytes(total_difficulty))?;
+        self.header_records.push((block.hash(), total_difficulty));
+        Ok(())
+    }
+
+    /// Writes the accumulator and block index records and returns the accumulator root
+    pub fn finish(mut self) -> Result<H256, Error> {
+        let Some(starting_number) = self.starting_number else {
+            bail!("Can't write an empty era1 file");
+        };
+        let accumulator_root = accumulator_root(&self.header_records);
+        self.write_record(ACCUMULATOR, accumulator_root.as_bytes())?;
+
+        // Offsets are relative to the start of the block index record
+        let index_position = self.written;
+        let mut index = Vec::with_capacity(16 + self.offsets.len() * 8);
+        index.put_u64_le(starting_number);
+        for offset in &self.offsets {
+            index.put_i64_le(*offset as i64 - index_position as i64);
+        }
+        index.put_u64_le(self.offsets.len() as u64);
+        self.write_record(BLOCK_INDEX, &index)?;
+        self.writer.flush()?;
+        Ok(accumulator_root)
+    }
+
+    fn write_record(&mut self, record_type: u16, data: &[u8]) -> Result<(), Error> {
+        let length = u32::try_from(data.len()).context("E2store record is too big")?;
+        let mut header = Vec::with_capacity(HEADER_LENGTH as usize);
+        header.put_u16_le(record_type);
+        header.put_u32_le(length);
+        header.put_u16_le(0);
+        self.writer.write_all(&header)?;
+        self.writer.write_all(data)?;
+        self.written += HEADER_LENGTH + data.len() as u64;
+        Ok(())
+    }
+}
+
+/// Reads all the blocks of an era1 archive.
+/// Checks that the block index is consistent with the stored blocks and that
+/// the accumulator matches the hashes and total difficulties of the blocks.
+pub fn read_era1(mut reader: impl Read) -> Result<Vec<Era1Block>, Error> {
+    let mut blocks = Vec::new();
+    let mut header = None;
+    let mut body = None;
+    let mut receipts = None;
+    let mut accumulator = None;
+    let mut index = None;
+    let mut read_version = false;
+
+    while let Some((record_type, data)) = read_record(&mut reader)? {
+        match record_type {
+            VERSION => read_version = true,
+            _ if !read_version => bail!("Era1 file must start with a version record"),
+            COMPRESSED_HEADER => {
+                header = Some(BlockHeader::decode(&snappy_frame_decompress(&data)?)?);
+            }
+            COMPRESSED_BODY => {
+                body = Some(BlockBody::decode(&snappy_frame_decompress(&data)?)?);
+            }
+            COMPRESSED_RECEIPTS => {
+                receipts = Some(decode_receipts(&snappy_frame_decompress(&data)?)?);
+            }
+            TOTAL_DIFFICULTY => {
+                let (Some(header), Some(body), Some(receipts)) =
+                    (header.take(), body.take(), receipts.take())
+                else {
+                    bail!("Incomplete block tuple in era1 file");
+                };
+                blocks.push(Era1Block {
+                    block: Block::new(header, body),
+                    receipts,
+                    total_difficulty: u256_from_le_bytes(&data)?,
+                });
+            }
+            ACCUMULATOR => {
+                ensure!(data.len() == 32, "Invalid accumulator record length");
+                accumulator = Some(H256::from_slice(&data));
+            }
+            BLOCK_INDEX => index = Some(data),
+            // Unknown records must be ignored
+            _ => {}
+        }
+    }
+
+    let accumulator = accumulator.context("Era1 file has no accumulator")?;
+    let index = index.context("Era1 file has no block index")?;
+    ensure!(
+        index.len() >= 16 && index.len() % 8 == 0,
+        "Invalid block index record length"
+    );
+    let starting_number = u64::from_le_bytes(index[..8].try_into()?);
+    let count = u64::from_le_bytes(index[index.len() - 8..].try_into()?);
+    ensure!(
+        count == blocks.len() as u64 && (index.len() - 16) / 8 == blocks.len(),
+        "Block index holds {count} entries but the era1 file has {} blocks",
+        blocks.len()
+    );
+    for (expected_number, era1_block) in (starting_number..).zip(&blocks) {
+        ensure!(
+            era1_block.block.header.number == expected_number,
+            "Expected block {expected_number} in era1 file, found {}",
+            era1_block.block.header.number
+        );
+    }
+
+    let header_records: Vec<_> = blocks
+        .iter()
+        .map(|era1_block| (era1_block.block.hash(), era1_block.total_difficulty))
+        .collect();
+    ensure!(
+        accumulator_root(&header_records) == accumulator,
+        "Era1 accumulator mismatch"
+    );
+    Ok(blocks)
+}
+
+/// Reads the next e2store record, returning `None` at the end of the input
+fn read_record(reader: &mut impl Read) -> Result<Option<(u16, Vec<u8>)>, Error> {
+    let mut header = [0; HEADER_LENGTH as usize];
+    let mut read = 0;
+    while read < header.len() {
+        match reader.read(&mut header[read..])? {
+            0 if read == 0 => return Ok(None),
+            0 => bail!("Unexpected end of e2store file"),
+            n => read += n,
+        }
+    }
+    let record_type = u16::from_le_bytes([header[0], header[1]]);
+    let length = u32::from_le_bytes([header[2], header[3], header[4], header[5]]);
+    ensure!(
+        header[6] == 0 && header[7] == 0,
+        "Reserved bytes of e2store record must be zero"
+    );
+    // Compressed blocks and receipts are the biggest records, check before allocating the data
+    ensure!(
+        length as usize <= MAX_BLOCK_SIZE,
+        "E2store record of {length} bytes is bigger than the {MAX_BLOCK_SIZE} bytes limit"
+    );
+    let mut data = vec![0; length as usize];
+    reader.read_exact(&mut data)?;
+    Ok(Some((record_type, data)))
+}
+
+/// Encodes receipts as in the `Receipts` network message: typed receipts are wrapped as byte strings
+fn encode_receipts(receipts: &[Receipt]) -> Vec<u8> {
+    let mut encoded_receipts = Vec::new();
+    for receipt in receipts {
+        match receipt.tx_type {
+            TxType::Legacy => receipt.encode(&mut encoded_receipts),
+            _ => receipt
+                .encode_to_vec()
+                .as_slice()
+                .encode(&mut encoded_receipts),
+        }
+    }
+    let mut buf = Vec::new();
+    encode_length(encoded_receipts.len(), &mut buf);
+    buf.extend(encoded_receipts);
+    buf
+}
+
+fn decode_receipts(rlp: &[u8]) -> Result<Vec<Receipt>, Error> {
+    let mut receipts = Vec::new();
+    for item in RlpView::new(rlp)?.iter()? {
+        let item = item?;
+        let receipt = if item.is_list() {
+            item.decode()?
+        } else {
+            Receipt::decode(item.as_bytes()?)?
+        };
+        receipts.push(receipt);
+    }
+    Ok(receipts)
+}
+
+fn snappy_frame_compress(data: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut encoder = snap::write::FrameEncoder::new(Vec::new());
+    encoder.write_all(data)?;
+    Ok(encoder.into_inner()?)
+}
+
+fn snappy_frame_decompress(data: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut decompressed = Vec::new();
+    snap::read::FrameDecoder::new(data)
+        .take(MAX_BLOCK_SIZE as u64 + 1)
+        .read_to_end(&mut decompressed)?;
+    ensure!(
+        decompressed.len() <= MAX_BLOCK_SIZE,
+        "Decompressed e2store record is bigger than the {MAX_BLOCK_SIZE} bytes limit"
+    );
+    Ok(decompressed)
+}
+
+fn u256_to_le_bytes(value: U256) -> [u8; 32] {
+    let mut bytes = [0; 32];
+    value.to_little_endian(&mut bytes);
+    bytes
+}
+
+fn u256_from_le_bytes(bytes: &[u8]) -> Result<U256, Error> {
+    ensure!(bytes.len() == 32, "Invalid total difficulty record length");
+    Ok(U256::from_little_endian(bytes))
+}
+
+/// Computes the SSZ `hash_tree_root` of a `List[HeaderRecord, MAX_ERA1_SIZE]`,
+/// where `HeaderRecord` is a container with a block hash and its total difficulty
+pub fn accumulator_root(header_records: &[(H256, U256)]) -> H256 {
+    let mut layer: Vec<[u8; 32]> = header_records
+        .iter()
+        .map(|(block_hash, total_difficulty)| {
+            sha256_pair(&block_hash.0, &u256_to_le_bytes(*total_difficulty))
+        })
+        .collect();
+
+    let mut zero_hash = [0; 32];
+    for _ in 0..ACCUMULATOR_DEPTH {
+        if layer.len() % 2 == 1 {
+            layer.push(zero_hash);
+        }
+        layer = layer
+            .chunks(2)
+            .map(|pair| sha256_pair(&pair[0], &pair[1]))
+            .collect();
+        zero_hash = sha256_pair(&zero_hash, &zero_hash);
+    }
+    let root = layer.first().copied().unwrap_or(zero_hash);
+
+    // Mix in the length of the list
+    let mut length = [0; 32];
+    length[..8].copy_from_slice(&(header_records.len() as u64).to_le_bytes());
+    H256(sha256_pair(&root, &length))
+}
+
+fn sha256_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode::chain_file;
+    use ethrex_core::{
+        types::{Log, TxType},
+        Address, Bytes,
+    };
+    use std::fs::File;
+
+    fn test_blocks() -> Vec<Era1Block> {
+        let file = File::open("../../test_data/chain.rlp").expect("Failed to open chain file");
+        chain_file(file)
+            .enumerate()
+            .map(|(i, block)| {
+                let log = Log {
+                    address: Address::repeat_byte(i as u8),
+                    topics: vec![H256::repeat_byte(1)],
+                    data: Bytes::from_static(b"data"),
+                };
+                Era1Block {
+                    block: block.unwrap(),
+                    receipts: vec![
+                        Receipt::new(TxType::Legacy, true, 21000, vec![]),
+                        Receipt::new(TxType::EIP1559, false, 42000, vec![log]),
+                    ],
+                    total_difficulty: U256::from(i + 1) * 131072,
+                }
+            })
+            .collect()
+    }
+
+    fn write_blocks(blocks: &[Era1Block]) -> (Vec<u8>, H256) {
+        let mut buf = Vec::new();
+        let mut writer = Era1Writer::new(&mut buf).unwrap();
+        for era1_block in blocks {
+            writer
+                .add_block(
+                    &era1_block.block,
+                    &era1_block.receipts,
+                    era1_block.total_difficulty,
+                )
+                .unwrap();
+        }
+        let root = writer.finish().unwrap();
+        (buf, root)
+    }
+
+    #[test]
+    fn era1_roundtrip() {
+        let blocks = test_blocks();
+        let (buf, root) = write_blocks(&blocks);
+        let header_records: Vec<_> = blocks
+            .iter()
+            .map(|era1_block| (era1_block.block.hash(), era1_block.total_difficulty))
+            .collect();
+        assert_eq!(root, accumulator_root(&header_records));
+
+        let decoded = read_era1(buf.as_slice()).unwrap();
+        assert_eq!(decoded, blocks);
+    }
+
+    #[test]
+    fn era1_rejects_accumulator_mismatch() {
+        let blocks = test_blocks();
+        let (mut buf, root) = write_blocks(&blocks);
+        // Corrupt the accumulator record, which precedes the block index record
+        let index_length = HEADER_LENGTH as usize + 16 + blocks.len() * 8;
+        let accumulator_start = buf.len() - index_length - 32;
+        assert_eq!(
+            &buf[accumulator_start..accumulator_start + 32],
+            root.as_bytes()
+        );
+        buf[accumulator_start] ^= 0xff;
+        assert!(read_era1(buf.as_slice()).is_err());
+    }
+
+    #[test]
+    fn era1_rejects_oversized_records() {
+        let mut buf = Vec::new();
+        Era1Writer::new(&mut buf).unwrap();
+        // Header of a record announcing more data than any block can hold
+        buf.extend_from_slice(&COMPRESSED_HEADER.to_le_bytes());
+        buf.extend_from_slice(&(MAX_BLOCK_SIZE as u32 + 1).to_le_bytes());
+        buf.extend_from_slice(&[0, 0]);
+        let error = read_era1(buf.as_slice()).unwrap_err();
+        assert!(error.to_string().contains("bigger than"));
+
+        let compressed = snappy_frame_compress(&vec![0; MAX_BLOCK_SIZE + 1]).unwrap();
+        assert!(snappy_frame_decompress(&compressed).is_err());
+    }
+
+    #[test]
+    fn era1_rejects_non_consecutive_blocks() {
+        let blocks = test_blocks();
+        let mut writer = Era1Writer::new(Vec::new()).unwrap();
+        writer
+            .add_block(&blocks[0].block, &[], blocks[0].total_difficulty)
+            .unwrap();
+        assert!(writer
+            .add_block(&blocks[2].block, &[], blocks[2].total_difficulty)
+            .is_err());
+    }
+
+    #[test]
+    fn accumulator_root_of_empty_list() {
+        // hash_tree_root of an empty list is the zero subtree root mixed with a zero length
+        let mut zero_hash = [0; 32];
+        for _ in 0..ACCUMULATOR_DEPTH {
+            zero_hash = sha256_pair(&zero_hash, &zero_hash);
+        }
+        assert_eq!(
+            accumulator_root(&[]),
+            H256(sha256_pair(&zero_hash, &[0; 32]))
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use bytes::Bytes;
 use directories::ProjectDirs;
-use ethrex_blockchain::{add_block, fork_choice::apply_fork_choice};
+use ethrex_blockchain::{EvmConfig, EvmEngine};
 use ethrex_core::{
     types::{Block, Genesis},
     H256,
@@ -9,15 +9,22 @@ use ethrex_net::{
     bootnode::BootNode, node_id_from_signing_key, peer_table, sync::SyncManager, types::Node,
 };
 use ethrex_rlp::decode::RLPDecode;
-use ethrex_storage::{EngineType, Store};
+use ethrex_rpc::{
+    authentication::{read_jwt_secret, JwtAuth},
+    signer::{
+        keystore::{LIGHT_SCRYPT_LOG_N, STANDARD_SCRYPT_LOG_N},
+        Signer,
+    },
+};
+use ethrex_storage::{EngineType, Store, STORE_SCHEMA_VERSION};
 use k256::ecdsa::SigningKey;
 use local_ip_address::local_ip;
 use std::{
     fs::{self, File},
     future::IntoFuture,
-    io,
+    io::{self, BufReader},
     net::{Ipv4Addr, SocketAddr, ToSocketAddrs},
-    path::Path,
+    path::{Path, PathBuf},
     str::FromStr as _,
     time::Duration,
 };
@@ -26,6 +33,9 @@ use tracing::{error, info, warn};
 use tracing_subscriber::{filter::Directive, EnvFilter, FmtSubscriber};
 mod cli;
 mod decode;
+mod era1;
+mod export;
+mod import;
 
 const DEFAULT_DATADIR: &str = "ethrex";
 #[tokio::main]
@@ -58,6 +68,53 @@ async fn main() {
         .finish();
     tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
 
+    if let Some(matches) = matches
+        .subcommand_matches("db")
+        .and_then(|matches| matches.subcommand_matches("migrate"))
+    {
+        let data_dir = matches
+            .get_one::<String>("datadir")
+            .map_or(set_datadir(DEFAULT_DATADIR), |datadir| set_datadir(datadir));
+        migrate_store(&data_dir);
+        return;
+    }
+
+    if let Some(matches) = matches.subcommand_matches("export") {
+        let data_dir = matches
+            .get_one::<String>("datadir")
+            .map_or(set_datadir(DEFAULT_DATADIR), |datadir| set_datadir(datadir));
+        let store = open_store(&data_dir);
+        let from = *matches
+            .get_one::<u64>("from")
+            .expect("from is used with a default value");
+        let to = match matches.get_one::<u64>("to") {
+            Some(to) => *to,
+            None => store
+                .get_latest_block_number()
+                .expect("Failed to read latest block number")
+                .expect("There are no blocks in the database"),
+        };
+        if from > to {
+            error!("Invalid block range, --from {from} is after --to {to}");
+            std::process::exit(1)
+        }
+        let path = matches.get_one::<String>("path").expect("path is required");
+        let format = matches
+            .get_one::<String>("format")
+            .expect("format is used with a default value");
+        info!("Exporting blocks {from} to {to} as {format} into {path}");
+        let result = match format.as_str() {
+            "era1" => export::export_blocks_era1(&store, from, to, Path::new(path)),
+            _ => export::export_blocks_rlp(&store, from, to, Path::new(path)),
+        };
+        if let Err(error) = result {
+            error!("Block export stopped: {error:#}");
+            std::process::exit(1)
+        }
+        info!("Exported {} blocks", to - from + 1);
+        return;
+    }
+
     let http_addr = matches
         .get_one::<String>("http.addr")
         .expect("http.addr is required");
@@ -73,6 +130,20 @@ async fn main() {
     let authrpc_jwtsecret = matches
         .get_one::<String>("authrpc.jwtsecret")
         .expect("authrpc.jwtsecret is required");
+    let batch_request_limit = *matches
+        .get_one::<usize>("rpc.batch_request_limit")
+        .expect("rpc.batch_request_limit has a default value");
+    let graphql = matches.get_flag("graphql");
+    let ipc_path = matches.get_one::<String>("ipcpath").map(PathBuf::from);
+    let metrics_addr = matches
+        .get_one::<String>("metrics.addr")
+        .expect("metrics.addr has a default value");
+    let metrics_socket_addr = matches
+        .get_one::<String>("metrics.port")
+        .map(|metrics_port| {
+            parse_socket_addr(metrics_addr, metrics_port)
+                .expect("Failed to parse metrics address and port")
+        });
 
     let tcp_addr = matches
         .get_one::<String>("p2p.addr")
@@ -120,25 +191,57 @@ async fn main() {
         info!("snap-sync not available, defaulting to full-sync");
     }
 
-    cfg_if::cfg_if! {
-        if #[cfg(feature = "redb")] {
-            let store = Store::new(&data_dir, EngineType::RedB).expect("Failed to create Store");
-        } else if #[cfg(feature = "libmdbx")] {
-            let store = Store::new(&data_dir, EngineType::Libmdbx).expect("Failed to create Store");
-        } else {
-            let store = Store::new(&data_dir, EngineType::InMemory).expect("Failed to create Store");
-        }
-    }
+    let engine = matches
+        .get_one::<String>("evm")
+        .expect("evm has a default value")
+        .parse::<EvmEngine>()
+        .expect("Invalid evm engine");
+    info!("Executing blocks with {engine}");
+    let evm = EvmConfig {
+        engine,
+        differential: matches.get_flag("evm_differential").then(|| {
+            let fixtures_dir = Path::new(&data_dir).join("evm-divergences");
+            info!(
+                "Comparing revm and LEVM on every block, divergences are saved to {}",
+                fixtures_dir.display()
+            );
+            fixtures_dir
+        }),
+    };
+
+    let store = open_store(&data_dir);
 
     let genesis = read_genesis_file(genesis_file_path);
     store
         .add_initial_state(genesis.clone())
         .expect("Failed to create genesis block");
 
-    if let Some(chain_rlp_path) = matches.get_one::<String>("import") {
-        info!("Importing blocks from chain file: {}", chain_rlp_path);
-        let blocks = read_chain_file(chain_rlp_path);
-        import_blocks(&store, &blocks);
+    let verify_imports = !matches.get_flag("trusted_import");
+    if let Some(import_path) = matches.get_one::<String>("import") {
+        let path = Path::new(import_path);
+        if path.is_dir() {
+            info!(
+                "Importing blocks from era1 files in directory: {}",
+                import_path
+            );
+            let mut era1_paths: Vec<_> = fs::read_dir(path)
+                .expect("Failed to read era1 directory")
+                .map(|entry| entry.expect("Failed to open file in directory").path())
+                .filter(|path| is_era1_file(path))
+                .collect();
+            era1_paths.sort();
+            let blocks = era1_paths
+                .into_iter()
+                .flat_map(|path| read_era1_file(&path));
+            import_blocks(&store, blocks, &evm, verify_imports);
+        } else if is_era1_file(path) {
+            info!("Importing blocks from era1 file: {}", import_path);
+            import_blocks(&store, read_era1_file(path), &evm, verify_imports);
+        } else {
+            info!("Importing blocks from chain file: {}", import_path);
+            let blocks = read_chain_file(import_path);
+            import_blocks(&store, blocks, &evm, verify_imports);
+        }
     }
 
     if let Some(blocks_path) = matches.get_one::<String>("import_dir") {
@@ -146,21 +249,37 @@ async fn main() {
             "Importing blocks from individual block files in directory: {}",
             blocks_path
         );
-        let mut blocks = vec![];
         let dir_reader = fs::read_dir(blocks_path).expect("Failed to read blocks directory");
-        for file_res in dir_reader {
+        let blocks = dir_reader.map(|file_res| {
             let file = file_res.expect("Failed to open file in directory");
             let path = file.path();
             let s = path
                 .to_str()
                 .expect("Path could not be converted into string");
-            blocks.push(read_block_file(s));
-        }
+            read_block_file(s)
+        });
 
-        import_blocks(&store, &blocks);
+        import_blocks(&store, blocks, &evm, verify_imports);
     }
 
-    let jwt_secret = read_jwtsecret_file(authrpc_jwtsecret);
+    let jwt_auth = JwtAuth::new(
+        read_jwtsecret_file(authrpc_jwtsecret),
+        Duration::from_secs(
+            *matches
+                .get_one::<u64>("authrpc.jwt_clock_skew")
+                .expect("authrpc.jwt_clock_skew is used with a default value"),
+        ),
+        matches.get_flag("authrpc.jwt_log_claims"),
+    );
+    jwt_auth.watch_secret_file(PathBuf::from(authrpc_jwtsecret));
+
+    let account_signer = matches.get_flag("signer").then(|| {
+        open_account_signer(
+            &data_dir,
+            matches.get_one::<String>("signer.password_file"),
+            matches.get_flag("signer.lightkdf"),
+        )
+    });
 
     // TODO Learn how should the key be created
     // https://github.com/lambdaclass/ethrex/issues/836
@@ -187,7 +306,7 @@ async fn main() {
     // Create Kademlia Table here so we can access it from rpc server (for syncing)
     let peer_table = peer_table(signer.clone());
     // Create SyncManager
-    let syncer = SyncManager::new(peer_table.clone(), snap_sync);
+    let syncer = SyncManager::new(peer_table.clone(), snap_sync, evm.clone());
 
     // TODO: Check every module starts properly.
     let tracker = TaskTracker::new();
@@ -195,9 +314,16 @@ async fn main() {
         http_socket_addr,
         authrpc_socket_addr,
         store.clone(),
-        jwt_secret,
+        jwt_auth,
         local_p2p_node,
         syncer,
+        peer_table.clone(),
+        evm,
+        batch_request_limit,
+        account_signer,
+        matches.get_flag("signer.allow_insecure_unlock"),
+        graphql,
+        ipc_path,
     )
     .into_future();
 
@@ -208,6 +334,16 @@ async fn main() {
 
     tracker.spawn(rpc_api);
 
+    if let Some(metrics_socket_addr) = metrics_socket_addr {
+        tracker.spawn(async move {
+            if let Err(error) =
+                ethrex_metrics::api::start_prometheus_metrics_api(metrics_socket_addr).await
+            {
+                error!("Metrics server failed: {error}");
+            }
+        });
+    }
+
     // We do not want to start the networking module if the l2 feature is enabled.
     cfg_if::cfg_if! {
         if #[cfg(feature = "l2")] {
@@ -250,9 +386,11 @@ async fn main() {
 }
 
 fn read_jwtsecret_file(jwt_secret_path: &str) -> Bytes {
-    match File::open(jwt_secret_path) {
-        Ok(mut file) => decode::jwtsecret_file(&mut file),
-        Err(_) => write_jwtsecret_file(jwt_secret_path),
+    let path = Path::new(jwt_secret_path);
+    if path.exists() {
+        read_jwt_secret(path).unwrap_or_else(|error| panic!("Invalid JWT secret file: {error}"))
+    } else {
+        write_jwtsecret_file(jwt_secret_path)
     }
 }
 
@@ -271,9 +409,52 @@ fn generate_jwt_secret() -> String {
     hex::encode(secret)
 }
 
-fn read_chain_file(chain_rlp_path: &str) -> Vec<Block> {
+fn open_account_signer(data_dir: &str, password_file: Option<&String>, light_kdf: bool) -> Signer {
+    let keystore_dir = Path::new(data_dir).join("keystore");
+    let scrypt_log_n = if light_kdf {
+        LIGHT_SCRYPT_LOG_N
+    } else {
+        STANDARD_SCRYPT_LOG_N
+    };
+    let signer = Signer::open(&keystore_dir, scrypt_log_n).expect("Failed to open the keystore");
+    info!(
+        "Local signer enabled with {} accounts from {}",
+        signer.accounts().len(),
+        keystore_dir.display()
+    );
+    if let Some(password_file) = password_file {
+        let passwords: Vec<String> = fs::read_to_string(password_file)
+            .expect("Failed to read the signer password file")
+            .lines()
+            .map(str::to_owned)
+            .collect();
+        let unlocked = signer.unlock_with_passwords(&passwords);
+        info!(
+            "Unlocked {} accounts with the password file",
+            unlocked.len()
+        );
+    }
+    signer
+}
+
+fn read_chain_file(chain_rlp_path: &str) -> impl Iterator<Item = Block> {
     let chain_file = std::fs::File::open(chain_rlp_path).expect("Failed to open chain rlp file");
-    decode::chain_file(chain_file).expect("Failed to decode chain rlp file")
+    decode::chain_file(chain_file).map(|block| block.expect("Failed to decode chain rlp file"))
+}
+
+fn is_era1_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == "era1")
+}
+
+fn read_era1_file(era1_path: &Path) -> Vec<Block> {
+    let era1_file = File::open(era1_path)
+        .unwrap_or_else(|_| panic!("Failed to open era1 file {}", era1_path.display()));
+    era1::read_era1(BufReader::new(era1_file))
+        .unwrap_or_else(|err| panic!("Failed to read era1 file {}: {err}", era1_path.display()))
+        .into_iter()
+        .map(|era1_block| era1_block.block)
+        .collect()
 }
 
 fn read_block_file(block_file_path: &str) -> Block {
@@ -321,42 +502,51 @@ fn set_datadir(datadir: &str) -> String {
         .to_owned()
 }
 
-fn import_blocks(store: &Store, blocks: &Vec<Block>) {
-    let size = blocks.len();
-    for block in blocks {
-        let hash = block.hash();
-        info!(
-            "Adding block {} with hash {:#x}.",
-            block.header.number, hash
-        );
-        let result = add_block(block, store);
-        if let Some(error) = result.err() {
-            warn!(
-                "Failed to add block {} with hash {:#x}: {}.",
-                block.header.number, hash, error
-            );
+fn engine_type() -> EngineType {
+    cfg_if::cfg_if! {
+        if #[cfg(feature = "redb")] {
+            EngineType::RedB
+        } else if #[cfg(feature = "libmdbx")] {
+            EngineType::Libmdbx
+        } else {
+            EngineType::InMemory
+        }
+    }
+}
+
+fn open_store(data_dir: &str) -> Store {
+    Store::new(data_dir, engine_type()).unwrap_or_else(|error| {
+        error!("Failed to open the store: {error}");
+        std::process::exit(1)
+    })
+}
+
+fn migrate_store(data_dir: &str) {
+    match Store::migrate(data_dir, engine_type()) {
+        Ok(previous_version) if previous_version == STORE_SCHEMA_VERSION => {
+            info!("Database is already at schema version {STORE_SCHEMA_VERSION}")
+        }
+        Ok(previous_version) => info!(
+            "Migrated database from schema version {previous_version} to {STORE_SCHEMA_VERSION}"
+        ),
+        Err(error) => {
+            error!("Failed to migrate the database: {error}");
+            std::process::exit(1)
         }
-        if store
-            .update_latest_block_number(block.header.number)
-            .is_err()
-        {
-            error!("Fatal: added block {} but could not update the block number -- aborting block import", block.header.number);
-            break;
-        };
-        if store
-            .set_canonical_block(block.header.number, hash)
-            .is_err()
-        {
-            error!(
-                "Fatal: added block {} but could not set it as canonical -- aborting block import",
-                block.header.number
-            );
-            break;
-        };
     }
-    if let Some(last_block) = blocks.last() {
-        let hash = last_block.hash();
-        apply_fork_choice(store, hash, hash, hash).unwrap();
+}
+
+fn import_blocks(
+    store: &Store,
+    blocks: impl IntoIterator<Item = Block>,
+    evm: &EvmConfig,
+    verify: bool,
+) {
+    match import::import_blocks(store, blocks, import::IMPORT_BATCH_SIZE, evm, verify) {
+        Ok(summary) => info!(
+            "Added {} blocks to blockchain, skipped {} already imported blocks",
+            summary.imported, summary.skipped
+        ),
+        Err(error) => error!("Block import stopped: {error:#}"),
     }
-    info!("Added {} blocks to blockchain", size);
 }
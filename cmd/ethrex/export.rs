@@ -0,0 +1,206 @@
+use crate::era1;
+use anyhow::Context;
+use ethrex_core::{
+    types::{Block, BlockNumber},
+    H256,
+};
+use ethrex_rlp::encode::RLPEncode;
+use ethrex_storage::Store;
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::Path,
+};
+use tracing::info;
+
+fn read_canonical_block(store: &Store, block_number: BlockNumber) -> anyhow::Result<Block> {
+    let header = store
+        .get_block_header(block_number)?
+        .with_context(|| format!("Block {block_number} is not in the database"))?;
+    let body = store
+        .get_block_body(block_number)?
+        .with_context(|| format!("Body of block {block_number} is not in the database"))?;
+    Ok(Block::new(header, body))
+}
+
+/// Exports blocks into a single file of consecutive RLP encoded blocks
+pub fn export_blocks_rlp(
+    store: &Store,
+    from: BlockNumber,
+    to: BlockNumber,
+    path: &Path,
+) -> anyhow::Result<()> {
+    let file = File::create(path).context("Failed to create output file")?;
+    let mut writer = BufWriter::new(file);
+    for block_number in from..=to {
+        let block = read_canonical_block(store, block_number)?;
+        writer
+            .write_all(&block.encode_to_vec())
+            .context("Failed to write block")?;
+    }
+    writer.flush().context("Failed to write blocks")
+}
+
+/// Exports blocks into one era1 file per epoch, named `<chain-id>-<epoch>-<accumulator root prefix>.era1`.
+///
+/// Every block needs its receipts and total difficulty, the export stops at the first block
+/// missing any of them, as the accumulator of its epoch can't be built.
+pub fn export_blocks_era1(
+    store: &Store,
+    from: BlockNumber,
+    to: BlockNumber,
+    dir: &Path,
+) -> anyhow::Result<()> {
+    fs::create_dir_all(dir).context("Failed to create output directory")?;
+    let chain_id = store.get_chain_config()?.chain_id;
+    let mut start = from;
+    while start <= to {
+        let epoch = start / era1::MAX_ERA1_SIZE;
+        let end = to.min((epoch + 1) * era1::MAX_ERA1_SIZE - 1);
+        let tmp_path = dir.join(format!("{chain_id}-{epoch:05}.era1.tmp"));
+        let accumulator_root = match write_era1_file(store, start, end, &tmp_path) {
+            Ok(accumulator_root) => accumulator_root,
+            Err(error) => {
+                let _ = fs::remove_file(&tmp_path);
+                return Err(error);
+            }
+        };
+        let era1_path = dir.join(format!(
+            "{chain_id}-{epoch:05}-{}.era1",
+            hex::encode(&accumulator_root[..4])
+        ));
+        fs::rename(tmp_path, &era1_path).context("Failed to write era1 file")?;
+        info!(
+            "Exported blocks {start} to {end} into {}",
+            era1_path.display()
+        );
+        start = end + 1;
+    }
+    Ok(())
+}
+
+/// Writes blocks `start..=end` of a single epoch into an era1 file, returning its accumulator root
+fn write_era1_file(
+    store: &Store,
+    start: BlockNumber,
+    end: BlockNumber,
+    path: &Path,
+) -> anyhow::Result<H256> {
+    let file = File::create(path).context("Failed to create era1 file")?;
+    let mut writer = era1::Era1Writer::new(BufWriter::new(file))?;
+    for block_number in start..=end {
+        let block = read_canonical_block(store, block_number)?;
+        let receipts = (0..block.body.transactions.len() as u64)
+            .map(|index| {
+                store.get_receipt(block_number, index)?.with_context(|| {
+                    format!("Receipt {index} of block {block_number} is not in the database")
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let total_difficulty = store
+            .get_block_total_difficulty(block.hash())?
+            .with_context(|| {
+                format!("Total difficulty of block {block_number} is not in the database")
+            })?;
+        writer
+            .add_block(&block, &receipts, total_difficulty)
+            .with_context(|| format!("Failed to write block {block_number} to era1 file"))?;
+    }
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        decode::{chain_file, genesis_file},
+        import::import_blocks,
+    };
+    use ethrex_blockchain::EvmConfig;
+    use ethrex_core::types::BlockBody;
+    use ethrex_storage::EngineType;
+    use std::path::PathBuf;
+
+    fn test_store_and_blocks() -> (Store, Vec<Block>) {
+        let store = Store::new("", EngineType::InMemory).unwrap();
+        let genesis =
+            genesis_file(File::open("../../test_data/genesis-execution-api.json").unwrap())
+                .unwrap();
+        store.add_initial_state(genesis).unwrap();
+        let blocks: Vec<Block> = chain_file(File::open("../../test_data/chain.rlp").unwrap())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        (store, blocks)
+    }
+
+    fn test_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("ethrex-export-{name}-{}", std::process::id()))
+    }
+
+    #[test]
+    fn rlp_export_round_trip() {
+        let (store, blocks) = test_store_and_blocks();
+        import_blocks(&store, blocks.clone(), 3, &EvmConfig::default(), true).unwrap();
+        let path = test_path("rlp");
+
+        export_blocks_rlp(&store, 1, 20, &path).unwrap();
+        let exported: Vec<Block> = chain_file(File::open(&path).unwrap())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(exported, blocks);
+    }
+
+    #[test]
+    fn era1_export_round_trip() {
+        let (store, blocks) = test_store_and_blocks();
+        import_blocks(&store, blocks.clone(), 3, &EvmConfig::default(), true).unwrap();
+        let dir = test_path("era1");
+
+        export_blocks_era1(&store, 1, 20, &dir).unwrap();
+        let files: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(files.len(), 1);
+        let exported = era1::read_era1(File::open(&files[0]).unwrap()).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(exported.len(), blocks.len());
+        for (exported, block) in exported.into_iter().zip(blocks) {
+            let block_number = block.header.number;
+            let receipts: Vec<_> = (0..block.body.transactions.len() as u64)
+                .map(|index| store.get_receipt(block_number, index).unwrap().unwrap())
+                .collect();
+            assert_eq!(
+                Some(exported.total_difficulty),
+                store.get_block_total_difficulty(block.hash()).unwrap()
+            );
+            assert_eq!(exported.receipts, receipts);
+            assert_eq!(exported.block, block);
+        }
+    }
+
+    #[test]
+    fn era1_export_stops_on_missing_total_difficulty() {
+        let (store, blocks) = test_store_and_blocks();
+        // Canonical block stored without its total difficulty
+        let block = Block::new(blocks[0].header.clone(), BlockBody::default());
+        let hash = block.hash();
+        store.add_block_header(hash, block.header).unwrap();
+        store.add_block_body(hash, block.body).unwrap();
+        store.set_canonical_block(1, hash).unwrap();
+        let dir = test_path("era1-missing-td");
+
+        let error = export_blocks_era1(&store, 1, 1, &dir).unwrap_err();
+        let files = fs::read_dir(&dir).unwrap().count();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            error.to_string(),
+            "Total difficulty of block 1 is not in the database"
+        );
+        assert_eq!(files, 0);
+    }
+}
@@ -0,0 +1,192 @@
+use anyhow::Context;
+use ethrex_blockchain::{add_block, add_trusted_block, fork_choice::apply_fork_choice, EvmConfig};
+use ethrex_core::types::{Block, BlockHash, BlockNumber};
+use ethrex_storage::Store;
+use std::time::Instant;
+use tracing::info;
+
+/// Amount of blocks whose canonical hashes and head are committed together
+pub const IMPORT_BATCH_SIZE: usize = 1024;
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    /// Blocks that were executed and added to the store
+    pub imported: usize,
+    /// Blocks that were already part of the canonical chain
+    pub skipped: usize,
+}
+
+/// Imports a sequence of consecutive blocks into the store.
+///
+/// Blocks are executed one by one, but the canonical chain and the latest block number are only
+/// updated once per batch, in a single transaction. If the import is interrupted, the store is
+/// left pointing at the last committed batch, and blocks that are already canonical are skipped
+/// when the import is run again.
+///
+/// Blocks are executed as set in the `evm` config. If `verify` is false, blocks are assumed to
+/// come from a trusted source and are added without pre and post execution validation, see
+/// [`add_trusted_block`].
+pub fn import_blocks(
+    store: &Store,
+    blocks: impl IntoIterator<Item = Block>,
+    batch_size: usize,
+    evm: &EvmConfig,
+    verify: bool,
+) -> anyhow::Result<ImportSummary> {
+    let mut summary = ImportSummary::default();
+    let mut batch = ImportBatch::new(batch_size);
+    let mut last_hash = None;
+
+    for block in blocks {
+        let number = block.header.number;
+        let hash = block.hash();
+        if store.get_canonical_block_hash(number)? == Some(hash) {
+            summary.skipped += 1;
+            continue;
+        }
+        let result = if verify {
+            add_block(&block, store, evm)
+        } else {
+            add_trusted_block(&block, store, evm)
+        };
+        if let Err(error) = result {
+            // Keep the blocks added so far, so the import can be resumed from this block
+            batch.commit(store)?;
+            return Err(error)
+                .with_context(|| format!("Failed to add block {number} with hash {hash:#x}"));
+        }
+        batch.push(number, hash, block.header.gas_used);
+        summary.imported += 1;
+        last_hash = Some(hash);
+        if batch.is_full() {
+            batch.commit(store)?;
+        }
+    }
+    batch.commit(store)?;
+
+    if let Some(hash) = last_hash {
+        apply_fork_choice(store, hash, hash, hash)
+            .map_err(|error| anyhow::anyhow!("Failed to apply fork choice: {error}"))?;
+    }
+    Ok(summary)
+}
+
+/// Blocks added to the store but not yet marked as canonical
+struct ImportBatch {
+    blocks: Vec<(BlockNumber, BlockHash)>,
+    capacity: usize,
+    gas_used: u64,
+    started_at: Instant,
+}
+
+impl ImportBatch {
+    fn new(capacity: usize) -> Self {
+        Self {
+            blocks: Vec::with_capacity(capacity),
+            capacity: capacity.max(1),
+            gas_used: 0,
+            started_at: Instant::now(),
+        }
+    }
+
+    fn push(&mut self, number: BlockNumber, hash: BlockHash, gas_used: u64) {
+        self.blocks.push((number, hash));
+        self.gas_used += gas_used;
+    }
+
+    fn is_full(&self) -> bool {
+        self.blocks.len() >= self.capacity
+    }
+
+    /// Marks the batch's blocks as canonical, logs the import rate and starts a new batch
+    fn commit(&mut self, store: &Store) -> anyhow::Result<()> {
+        let (Some((first, _)), Some((last, _))) = (self.blocks.first(), self.blocks.last()) else {
+            return Ok(());
+        };
+        store
+            .update_canonical_chain(&self.blocks, *last)
+            .with_context(|| format!("Failed to mark blocks {first}..={last} as canonical"))?;
+
+        let elapsed = self.started_at.elapsed().as_secs_f64().max(f64::EPSILON);
+        info!(
+            "Imported blocks {first}..={last} ({:.2} blocks/s, {:.2} Mgas/s)",
+            self.blocks.len() as f64 / elapsed,
+            self.gas_used as f64 / 1e6 / elapsed
+        );
+
+        self.blocks.clear();
+        self.gas_used = 0;
+        self.started_at = Instant::now();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode::{chain_file, genesis_file};
+    use ethrex_storage::EngineType;
+    use std::fs::File;
+
+    fn test_store_and_blocks() -> (Store, Vec<Block>) {
+        let store = Store::new("", EngineType::InMemory).unwrap();
+        let genesis =
+            genesis_file(File::open("../../test_data/genesis-execution-api.json").unwrap())
+                .unwrap();
+        store.add_initial_state(genesis).unwrap();
+        let blocks = chain_file(File::open("../../test_data/chain.rlp").unwrap())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        (store, blocks)
+    }
+
+    #[test]
+    fn import_commits_every_batch() {
+        let (store, blocks) = test_store_and_blocks();
+
+        let summary =
+            import_blocks(&store, blocks.clone(), 3, &EvmConfig::default(), true).unwrap();
+
+        assert_eq!(
+            summary,
+            ImportSummary {
+                imported: 20,
+                skipped: 0
+            }
+        );
+        assert_eq!(store.get_latest_block_number().unwrap(), Some(20));
+        for block in blocks {
+            assert_eq!(
+                store.get_canonical_block_hash(block.header.number).unwrap(),
+                Some(block.hash())
+            );
+        }
+    }
+
+    #[test]
+    fn import_resumes_from_last_canonical_block() {
+        let (store, blocks) = test_store_and_blocks();
+        import_blocks(&store, blocks[..7].to_vec(), 3, &EvmConfig::default(), true).unwrap();
+
+        let summary = import_blocks(&store, blocks, 3, &EvmConfig::default(), true).unwrap();
+
+        assert_eq!(
+            summary,
+            ImportSummary {
+                imported: 13,
+                skipped: 7
+            }
+        );
+        assert_eq!(store.get_latest_block_number().unwrap(), Some(20));
+    }
+
+    #[test]
+    fn import_keeps_blocks_added_before_a_failure() {
+        let (store, mut blocks) = test_store_and_blocks();
+        blocks[5].header.gas_used += 1;
+
+        assert!(import_blocks(&store, blocks, 3, &EvmConfig::default(), true).is_err());
+        assert_eq!(store.get_latest_block_number().unwrap(), Some(5));
+        assert_eq!(store.get_canonical_block_hash(6).unwrap(), None);
+    }
+}
@@ -0,0 +1,37 @@
+use ethrex_levm::errors::{DatabaseError, VMError};
+
+/// Errors that stop the tool. Each one exits with the same code geth's `evm` uses for it, so
+/// harnesses written for geth can tell them apart.
+#[derive(Debug, thiserror::Error)]
+pub enum EvmToolError {
+    #[error("EVM error: {0}")]
+    Evm(#[from] VMError),
+    #[error("Invalid configuration: {0}")]
+    Config(String),
+    #[error("Unsupported fork: {0}")]
+    UnsupportedFork(String),
+    #[error("Failed to parse {0}: {1}")]
+    Json(String, serde_json::Error),
+    #[error("Failed to access {0}: {1}")]
+    Io(String, std::io::Error),
+    #[error("Failed to decode transactions: {0}")]
+    Rlp(String),
+}
+
+impl EvmToolError {
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            EvmToolError::Evm(_) => 2,
+            EvmToolError::Config(_) | EvmToolError::UnsupportedFork(_) => 3,
+            EvmToolError::Json(_, _) => 10,
+            EvmToolError::Io(_, _) => 11,
+            EvmToolError::Rlp(_) => 12,
+        }
+    }
+}
+
+impl From<DatabaseError> for EvmToolError {
+    fn from(error: DatabaseError) -> Self {
+        EvmToolError::Evm(error.into())
+    }
+}
@@ -0,0 +1,43 @@
+//! Runs LEVM without the rest of the node, following the interface of geth's `evm` tool so it
+//! can be plugged into the harnesses built for it.
+
+mod error;
+mod state;
+mod statetest;
+mod t8n;
+mod types;
+
+use clap::{Parser, Subcommand};
+use std::process::ExitCode;
+
+#[derive(Debug, Parser)]
+#[command(name = "evm", about = "Runs LEVM on its own", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Executes a block's transactions on top of a state, like `evm t8n`
+    #[command(name = "t8n", visible_alias = "transition")]
+    T8n(t8n::T8nArgs),
+    /// Runs state tests, like `evm statetest`
+    #[command(name = "statetest")]
+    Statetest(statetest::StatetestArgs),
+}
+
+fn main() -> ExitCode {
+    let result = match Cli::parse().command {
+        Command::T8n(args) => t8n::run(args).map(|_| true),
+        Command::Statetest(args) => statetest::run(args),
+    };
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::from(err.exit_code())
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use crate::{
+    error::EvmToolError,
+    types::{Alloc, AllocAccount},
+};
+use bytes::Bytes;
+use ethrex_core::{
+    types::{AccountState, Fork, GenesisAccount, Log, TxKind},
+    Address, H256, U256,
+};
+use ethrex_levm::{
+    account::{Account, StorageSlot},
+    db::{AccountChange, BlockState, Db},
+    errors::{TransactionReport, VMError},
+    vm::VM,
+    Environment,
+};
+use ethrex_rlp::encode::RLPEncode;
+use ethrex_trie::Trie;
+use sha3::{Digest, Keccak256};
+use std::collections::HashMap;
+
+/// Parses a fork name as used by geth and the execution spec tests. LEVM implements the Cancun
+/// rules, and the EOF ones on Osaka, so only those two are accepted.
+pub fn parse_fork(name: &str) -> Result<Fork, EvmToolError> {
+    match name {
+        "Cancun" => Ok(Fork::Cancun),
+        "Osaka" => Ok(Fork::Osaka),
+        _ => Err(EvmToolError::UnsupportedFork(name.to_string())),
+    }
+}
+
+/// Block the transactions are executed in
+#[derive(Debug, Clone)]
+pub struct BlockContext {
+    pub coinbase: Address,
+    pub number: u64,
+    pub timestamp: u64,
+    pub gas_limit: u64,
+    pub prev_randao: Option<H256>,
+    pub base_fee_per_gas: u64,
+    pub excess_blob_gas: Option<u64>,
+    pub chain_id: u64,
+    pub fork: Fork,
+}
+
+impl BlockContext {
+    /// Environment with the block fields set, for a transaction sent by `origin`
+    pub fn environment(&self, origin: Address) -> Environment {
+        Environment {
+            block_number: self.number.into(),
+            coinbase: self.coinbase,
+            timestamp: self.timestamp.into(),
+            prev_randao: self.prev_randao,
+            chain_id: self.chain_id.into(),
+            base_fee_per_gas: self.base_fee_per_gas.into(),
+            block_excess_blob_gas: self.excess_blob_gas.map(U256::from),
+            block_gas_limit: self.gas_limit.into(),
+            fork: self.fork,
+            ..Environment::default_from_address(origin)
+        }
+    }
+}
+
+pub enum TxOutcome {
+    /// The transaction was executed and its changes committed to the block state
+    Included(TransactionReport),
+    /// The transaction is invalid, so it leaves the state untouched
+    Rejected(String),
+}
+
+/// Executes a transaction on top of the block state, committing its changes if it's valid
+pub fn run_transaction(
+    block_state: &mut BlockState,
+    to: TxKind,
+    env: Environment,
+    value: U256,
+    data: Bytes,
+) -> Result<TxOutcome, EvmToolError> {
+    let result = VM::new(
+        to,
+        env,
+        value,
+        data,
+        block_state.db(),
+        block_state.transaction_cache(),
+    )
+    .and_then(|mut vm| vm.transact());
+    match result {
+        Ok(report) => {
+            block_state.commit(report.new_state.clone())?;
+            Ok(TxOutcome::Included(report))
+        }
+        Err(VMError::TxValidation(err)) => Ok(TxOutcome::Rejected(err.to_string())),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// LEVM doesn't know the nonce of the transaction, so it's checked against the sender's before
+/// running it
+pub fn nonce_error(
+    block_state: &BlockState,
+    sender: Address,
+    tx_nonce: u64,
+) -> Result<Option<String>, EvmToolError> {
+    let state_nonce = block_state.account_info(sender)?.nonce;
+    Ok(match tx_nonce.cmp(&state_nonce) {
+        std::cmp::Ordering::Less => Some(format!(
+            "nonce too low: address {sender:#x}, tx: {tx_nonce} state: {state_nonce}"
+        )),
+        std::cmp::Ordering::Greater => Some(format!(
+            "nonce too high: address {sender:#x}, tx: {tx_nonce} state: {state_nonce}"
+        )),
+        std::cmp::Ordering::Equal => None,
+    })
+}
+
+/// Database holding the accounts of the allocation
+pub fn alloc_db(alloc: &Alloc, block_hashes: HashMap<u64, H256>) -> Db {
+    let accounts = alloc
+        .iter()
+        .map(|(address, account)| {
+            let storage = account
+                .storage
+                .iter()
+                .map(|(key, value)| {
+                    let slot = StorageSlot {
+                        original_value: *value,
+                        current_value: *value,
+                    };
+                    (u256_to_h256(*key), slot)
+                })
+                .collect();
+            let account = Account::new(
+                account.balance,
+                account.code.clone(),
+                account.nonce,
+                storage,
+            );
+            (*address, account)
+        })
+        .collect();
+    Db::new()
+        .with_accounts(accounts)
+        .with_block_hashes(block_hashes)
+}
+
+/// Applies the changes made by a block to the allocation, dropping the accounts it left empty
+/// (EIP-161)
+pub fn apply_changes(alloc: &mut Alloc, changes: Vec<AccountChange>) {
+    for change in changes {
+        let account = alloc.entry(change.address).or_default();
+        account.balance = change.info.balance;
+        account.nonce = change.info.nonce;
+        account.code = change.info.bytecode.clone();
+        for (key, value) in change.storage {
+            let key = U256::from_big_endian(key.as_bytes());
+            if value.is_zero() {
+                account.storage.remove(&key);
+            } else {
+                account.storage.insert(key, value);
+            }
+        }
+        if change.info.is_empty() && account.storage.is_empty() {
+            alloc.remove(&change.address);
+        }
+    }
+}
+
+pub fn state_root(alloc: &Alloc) -> H256 {
+    let iter = alloc.iter().map(|(address, account)| {
+        (
+            Keccak256::digest(address).to_vec(),
+            AccountState::from(&genesis_account(account)).encode_to_vec(),
+        )
+    });
+    Trie::compute_hash_from_unsorted_iter(iter)
+}
+
+/// Hash of the RLP encoding of the logs, as reported by the state tests
+pub fn logs_hash(logs: &[Log]) -> H256 {
+    H256::from_slice(&Keccak256::digest(logs.to_vec().encode_to_vec()))
+}
+
+fn genesis_account(account: &AllocAccount) -> GenesisAccount {
+    GenesisAccount {
+        code: account.code.clone(),
+        storage: account
+            .storage
+            .iter()
+            .map(|(key, value)| (u256_to_h256(*key), *value))
+            .collect(),
+        balance: account.balance,
+        nonce: account.nonce,
+    }
+}
+
+fn u256_to_h256(value: U256) -> H256 {
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    H256(bytes)
+}
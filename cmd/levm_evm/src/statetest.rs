@@ -0,0 +1,294 @@
+use crate::{
+    error::EvmToolError,
+    state::{
+        alloc_db, apply_changes, logs_hash, nonce_error, parse_fork, run_transaction, state_root,
+        BlockContext, TxOutcome,
+    },
+    types::{deser_opt_u64, Alloc, StateTestResult},
+};
+use bytes::Bytes;
+use clap::Args;
+use ethrex_core::{serde_utils, types::TxKind, Address, H256, U256};
+use ethrex_levm::db::BlockState;
+use secp256k1::{PublicKey, SecretKey, SECP256K1};
+use serde::{de::Error, Deserialize, Deserializer};
+use sha3::{Digest, Keccak256};
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::BufRead,
+    path::PathBuf,
+    sync::Arc,
+};
+
+/// Arguments of `evm statetest`
+#[derive(Debug, Args)]
+pub struct StatetestArgs {
+    /// State test files. If none is given, their paths are read from stdin, one per line.
+    paths: Vec<PathBuf>,
+    /// Only run the tests of this fork
+    #[arg(long = "statetest.fork")]
+    fork: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StateTest {
+    env: StateTestEnv,
+    pre: Alloc,
+    transaction: StateTestTransaction,
+    post: BTreeMap<String, Vec<PostState>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StateTestEnv {
+    current_coinbase: Address,
+    #[serde(deserialize_with = "serde_utils::u64::deser_hex_or_dec_str")]
+    current_gas_limit: u64,
+    #[serde(deserialize_with = "serde_utils::u64::deser_hex_or_dec_str")]
+    current_number: u64,
+    #[serde(deserialize_with = "serde_utils::u64::deser_hex_or_dec_str")]
+    current_timestamp: u64,
+    #[serde(default, deserialize_with = "deser_opt_u64")]
+    current_base_fee: Option<u64>,
+    #[serde(default)]
+    current_random: Option<H256>,
+    #[serde(default, deserialize_with = "deser_opt_u64")]
+    current_excess_blob_gas: Option<u64>,
+}
+
+/// Transaction of a state test, whose data, gas limit and value are picked by each post state
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StateTestTransaction {
+    #[serde(with = "serde_utils::bytes::vec")]
+    data: Vec<Bytes>,
+    gas_limit: Vec<U256>,
+    value: Vec<U256>,
+    #[serde(deserialize_with = "serde_utils::u64::deser_hex_or_dec_str")]
+    nonce: u64,
+    #[serde(default)]
+    gas_price: Option<U256>,
+    #[serde(default)]
+    max_fee_per_gas: Option<U256>,
+    #[serde(default)]
+    max_priority_fee_per_gas: Option<U256>,
+    #[serde(default)]
+    max_fee_per_blob_gas: Option<U256>,
+    #[serde(default)]
+    blob_versioned_hashes: Vec<H256>,
+    #[serde(default)]
+    access_lists: Vec<Option<Vec<AccessListItem>>>,
+    #[serde(default)]
+    sender: Option<Address>,
+    secret_key: H256,
+    #[serde(deserialize_with = "deser_tx_kind")]
+    to: TxKind,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AccessListItem {
+    address: Address,
+    storage_keys: Vec<H256>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PostState {
+    hash: H256,
+    logs: H256,
+    indexes: PostStateIndexes,
+    #[serde(default)]
+    expect_exception: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PostStateIndexes {
+    data: usize,
+    gas: usize,
+    value: usize,
+}
+
+/// Runs the tests of every file, printing their results. Fails if any of them doesn't pass.
+pub fn run(args: StatetestArgs) -> Result<bool, EvmToolError> {
+    let paths = if args.paths.is_empty() {
+        std::io::stdin()
+            .lock()
+            .lines()
+            .map(|line| {
+                line.map(PathBuf::from)
+                    .map_err(|err| EvmToolError::Io("stdin".to_string(), err))
+            })
+            .collect::<Result<Vec<_>, _>>()?
+    } else {
+        args.paths
+    };
+
+    let mut results = Vec::new();
+    for path in paths {
+        let input = std::fs::read_to_string(&path)
+            .map_err(|err| EvmToolError::Io(path.display().to_string(), err))?;
+        let tests: BTreeMap<String, StateTest> = serde_json::from_str(&input)
+            .map_err(|err| EvmToolError::Json(path.display().to_string(), err))?;
+        for (name, test) in tests {
+            for (fork_name, post_states) in &test.post {
+                if args.fork.as_ref().is_some_and(|fork| fork != fork_name) {
+                    continue;
+                }
+                for post_state in post_states {
+                    results.push(run_post_state(&name, &test, fork_name, post_state)?);
+                }
+            }
+        }
+    }
+
+    let all_passed = results.iter().all(|result| result.pass);
+    let output = serde_json::to_string_pretty(&results)
+        .map_err(|err| EvmToolError::Json("results".to_string(), err))?;
+    println!("{output}");
+    Ok(all_passed)
+}
+
+fn run_post_state(
+    name: &str,
+    test: &StateTest,
+    fork_name: &str,
+    post_state: &PostState,
+) -> Result<StateTestResult, EvmToolError> {
+    let failure = |error: String| StateTestResult {
+        name: name.to_string(),
+        pass: false,
+        state_root: H256::zero(),
+        fork: fork_name.to_string(),
+        error,
+    };
+    let fork = match parse_fork(fork_name) {
+        Ok(fork) => fork,
+        Err(err) => return Ok(failure(err.to_string())),
+    };
+    let tx = &test.transaction;
+    let indexes = &post_state.indexes;
+    let (Some(data), Some(gas_limit), Some(value)) = (
+        tx.data.get(indexes.data),
+        tx.gas_limit.get(indexes.gas),
+        tx.value.get(indexes.value),
+    ) else {
+        return Ok(failure("post state indexes out of range".to_string()));
+    };
+    let sender = match tx.sender {
+        Some(sender) => sender,
+        None => match address_from_secret_key(tx.secret_key) {
+            Some(sender) => sender,
+            None => return Ok(failure("invalid secret key".to_string())),
+        },
+    };
+
+    let context = BlockContext {
+        coinbase: test.env.current_coinbase,
+        number: test.env.current_number,
+        timestamp: test.env.current_timestamp,
+        gas_limit: test.env.current_gas_limit,
+        prev_randao: test.env.current_random,
+        base_fee_per_gas: test.env.current_base_fee.unwrap_or_default(),
+        excess_blob_gas: test.env.current_excess_blob_gas,
+        chain_id: 1,
+        fork,
+    };
+    let mut block_state = BlockState::new(Arc::new(alloc_db(&test.pre, HashMap::new())));
+
+    let mut logs = Vec::new();
+    let tx_error = match tx_gas_price(tx, context.base_fee_per_gas) {
+        Err(error) => Some(error),
+        Ok(gas_price) => match nonce_error(&block_state, sender, tx.nonce)? {
+            Some(error) => Some(error),
+            None => {
+                let mut env = context.environment(sender);
+                env.gas_limit = *gas_limit;
+                env.gas_price = gas_price;
+                env.tx_blob_hashes = tx.blob_versioned_hashes.clone();
+                env.tx_max_priority_fee_per_gas = tx.max_priority_fee_per_gas;
+                env.tx_max_fee_per_gas = tx.max_fee_per_gas;
+                env.tx_max_fee_per_blob_gas = tx.max_fee_per_blob_gas;
+                env.tx_access_list = tx
+                    .access_lists
+                    .get(indexes.data)
+                    .cloned()
+                    .flatten()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|item| (item.address, item.storage_keys))
+                    .collect();
+                match run_transaction(&mut block_state, tx.to.clone(), env, *value, data.clone())? {
+                    TxOutcome::Included(report) => {
+                        logs = report.logs;
+                        None
+                    }
+                    TxOutcome::Rejected(error) => Some(error),
+                }
+            }
+        },
+    };
+
+    let mut post_alloc = test.pre.clone();
+    apply_changes(&mut post_alloc, block_state.changes());
+    let state_root = state_root(&post_alloc);
+    let logs_hash = logs_hash(&logs);
+
+    let error = match (&tx_error, &post_state.expect_exception) {
+        (None, Some(exception)) => format!("expected exception {exception}"),
+        (Some(error), None) => format!("unexpected error: {error}"),
+        _ if state_root != post_state.hash => format!(
+            "post state root mismatch: got {state_root:#x}, want {:#x}",
+            post_state.hash
+        ),
+        _ if logs_hash != post_state.logs => format!(
+            "logs hash mismatch: got {logs_hash:#x}, want {:#x}",
+            post_state.logs
+        ),
+        _ => String::new(),
+    };
+    Ok(StateTestResult {
+        name: name.to_string(),
+        pass: error.is_empty(),
+        state_root,
+        fork: fork_name.to_string(),
+        error,
+    })
+}
+
+/// Gas price the transaction pays, or why it can't be included with the block's base fee
+fn tx_gas_price(tx: &StateTestTransaction, base_fee_per_gas: u64) -> Result<U256, String> {
+    let base_fee_per_gas = U256::from(base_fee_per_gas);
+    if let Some(gas_price) = tx.gas_price {
+        return Ok(gas_price);
+    }
+    let max_fee_per_gas = tx.max_fee_per_gas.unwrap_or_default();
+    if max_fee_per_gas < base_fee_per_gas {
+        return Err("max fee per gas less than block base fee".to_string());
+    }
+    let priority_fee = tx.max_priority_fee_per_gas.unwrap_or_default();
+    Ok(max_fee_per_gas.min(base_fee_per_gas.saturating_add(priority_fee)))
+}
+
+fn address_from_secret_key(secret_key: H256) -> Option<Address> {
+    let secret_key = SecretKey::from_slice(secret_key.as_bytes()).ok()?;
+    let public_key = PublicKey::from_secret_key(SECP256K1, &secret_key).serialize_uncompressed();
+    let hash = Keccak256::digest(public_key.get(1..)?);
+    Some(Address::from_slice(hash.get(12..)?))
+}
+
+/// The recipient is an empty string for contract creations
+fn deser_tx_kind<'de, D>(d: D) -> Result<TxKind, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let to = String::deserialize(d)?;
+    if to.is_empty() {
+        return Ok(TxKind::Create);
+    }
+    let address = hex::decode(to.trim_start_matches("0x")).map_err(D::Error::custom)?;
+    if address.len() != Address::len_bytes() {
+        return Err(D::Error::custom(format!("invalid address {to}")));
+    }
+    Ok(TxKind::Call(Address::from_slice(&address)))
+}
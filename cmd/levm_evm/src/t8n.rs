@@ -0,0 +1,498 @@
+use crate::{
+    error::EvmToolError,
+    state::{
+        alloc_db, apply_changes, logs_hash, nonce_error, parse_fork, run_transaction, state_root,
+        BlockContext, TxOutcome,
+    },
+    types::{
+        deser_opt_u64, parse_u64, Alloc, LogOutput, ReceiptOutput, RejectedTransaction, T8nResult,
+    },
+};
+use bytes::Bytes;
+use clap::Args;
+use ethrex_core::{
+    serde_utils,
+    types::{
+        calculate_base_fee_per_gas, compute_receipts_root, compute_transactions_root,
+        compute_withdrawals_root, Fork, Receipt, Transaction, TxKind, TxType, Withdrawal,
+        GWEI_TO_WEI,
+    },
+    Address, Bloom, H160, H256, U256,
+};
+use ethrex_levm::{
+    constants::{BLOB_GAS_PER_BLOB, MAX_BLOB_COUNT, TARGET_BLOB_GAS_PER_BLOCK},
+    db::BlockState,
+    vm::VM,
+};
+use ethrex_rlp::{decode::RLPDecode, encode::RLPEncode};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    io::Read,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+const STDIN: &str = "stdin";
+const STDOUT: &str = "stdout";
+const STDERR: &str = "stderr";
+
+/// Sender of the system call storing the parent beacon block root (EIP-4788)
+const SYSTEM_ADDRESS: Address = H160([
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xfe,
+]);
+const BEACON_ROOTS_ADDRESS: Address = H160([
+    0x00, 0x0f, 0x3d, 0xf6, 0xd7, 0x32, 0x80, 0x7e, 0xf1, 0x31, 0x9f, 0xb7, 0xb8, 0xbb, 0x85, 0x22,
+    0xd0, 0xbe, 0xac, 0x02,
+]);
+const SYSTEM_CALL_GAS_LIMIT: u64 = 30_000_000;
+
+/// Arguments of `evm t8n`. Inputs can be read from `stdin` and outputs written to `stdout` or
+/// `stderr`, in which case they are grouped in a single JSON object.
+#[derive(Debug, Args)]
+pub struct T8nArgs {
+    #[arg(long = "input.alloc", default_value = "alloc.json")]
+    input_alloc: String,
+    #[arg(long = "input.env", default_value = "env.json")]
+    input_env: String,
+    /// Signed transactions, as a JSON list or RLP encoded if the file ends in `.rlp`
+    #[arg(long = "input.txs", default_value = "txs.json")]
+    input_txs: String,
+    /// Directory the output files are written to
+    #[arg(long = "output.basedir", default_value = ".")]
+    output_basedir: PathBuf,
+    #[arg(long = "output.alloc", default_value = "alloc.json")]
+    output_alloc: String,
+    #[arg(long = "output.result", default_value = "result.json")]
+    output_result: String,
+    /// RLP encoding of the included transactions. Not written unless given.
+    #[arg(long = "output.body")]
+    output_body: Option<String>,
+    #[arg(long = "state.fork", default_value = "Cancun")]
+    fork: String,
+    #[arg(long = "state.chainid", default_value_t = 1)]
+    chain_id: u64,
+    /// Block reward paid to the coinbase, or -1 to pay none
+    #[arg(long = "state.reward", default_value_t = 0, allow_hyphen_values = true)]
+    reward: i64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct T8nEnv {
+    current_coinbase: Address,
+    #[serde(deserialize_with = "serde_utils::u64::deser_hex_or_dec_str")]
+    current_gas_limit: u64,
+    #[serde(deserialize_with = "serde_utils::u64::deser_hex_or_dec_str")]
+    current_number: u64,
+    #[serde(deserialize_with = "serde_utils::u64::deser_hex_or_dec_str")]
+    current_timestamp: u64,
+    #[serde(default)]
+    current_difficulty: Option<U256>,
+    #[serde(default)]
+    current_random: Option<H256>,
+    #[serde(default, deserialize_with = "deser_opt_u64")]
+    current_base_fee: Option<u64>,
+    #[serde(default, deserialize_with = "deser_opt_u64")]
+    parent_base_fee: Option<u64>,
+    #[serde(default, deserialize_with = "deser_opt_u64")]
+    parent_gas_used: Option<u64>,
+    #[serde(default, deserialize_with = "deser_opt_u64")]
+    parent_gas_limit: Option<u64>,
+    #[serde(default, deserialize_with = "deser_opt_u64")]
+    current_excess_blob_gas: Option<u64>,
+    #[serde(default, deserialize_with = "deser_opt_u64")]
+    parent_excess_blob_gas: Option<u64>,
+    #[serde(default, deserialize_with = "deser_opt_u64")]
+    parent_blob_gas_used: Option<u64>,
+    #[serde(default)]
+    parent_beacon_block_root: Option<H256>,
+    #[serde(default)]
+    withdrawals: Option<Vec<Withdrawal>>,
+    /// Hashes of previous blocks, by number
+    #[serde(default)]
+    block_hashes: HashMap<String, H256>,
+}
+
+/// All the inputs, when given together through stdin
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StdinInput {
+    alloc: Option<Alloc>,
+    env: Option<Value>,
+    txs: Option<Vec<Transaction>>,
+    txs_rlp: Option<String>,
+}
+
+pub fn run(args: T8nArgs) -> Result<(), EvmToolError> {
+    let fork = parse_fork(&args.fork)?;
+    let reads_stdin = [&args.input_alloc, &args.input_env, &args.input_txs]
+        .iter()
+        .any(|input| input.as_str() == STDIN);
+    let mut stdin_input = if reads_stdin {
+        parse_json(STDIN, &read_input(STDIN)?)?
+    } else {
+        StdinInput::default()
+    };
+
+    let alloc: Alloc = match args.input_alloc.as_str() {
+        STDIN => stdin_input.alloc.take().unwrap_or_default(),
+        path => parse_json(path, &read_input(path)?)?,
+    };
+    let env: T8nEnv = match args.input_env.as_str() {
+        STDIN => serde_json::from_value(stdin_input.env.take().unwrap_or_default())
+            .map_err(|err| EvmToolError::Json("env".to_string(), err))?,
+        path => parse_json(path, &read_input(path)?)?,
+    };
+    let txs = match args.input_txs.as_str() {
+        STDIN => match stdin_input.txs_rlp.take() {
+            Some(rlp) => decode_rlp_txs(&rlp)?,
+            None => stdin_input.txs.take().unwrap_or_default(),
+        },
+        path if path.ends_with(".rlp") => {
+            decode_rlp_txs(&parse_json::<String>(path, &read_input(path)?)?)?
+        }
+        path => parse_json(path, &read_input(path)?)?,
+    };
+
+    let (result, post_alloc, included_txs) = transition(&alloc, &env, &txs, fork, &args)?;
+
+    let mut outputs = Outputs::new(&args.output_basedir);
+    outputs.add("alloc", &args.output_alloc, to_json(&post_alloc)?)?;
+    outputs.add("result", &args.output_result, to_json(&result)?)?;
+    if let Some(output_body) = &args.output_body {
+        let body = format!("0x{}", hex::encode(included_txs.encode_to_vec()));
+        outputs.add("body", output_body, Value::String(body))?;
+    }
+    outputs.flush()
+}
+
+/// Executes the transactions on top of the allocation, returning the result, the resulting
+/// allocation and the transactions included in the block
+fn transition(
+    alloc: &Alloc,
+    env: &T8nEnv,
+    txs: &[Transaction],
+    fork: Fork,
+    args: &T8nArgs,
+) -> Result<(T8nResult, Alloc, Vec<Transaction>), EvmToolError> {
+    let base_fee_per_gas = base_fee_per_gas(env)?;
+    let excess_blob_gas = excess_blob_gas(env);
+    let block_hashes = env
+        .block_hashes
+        .iter()
+        .map(|(number, hash)| Ok((parse_u64(number).map_err(EvmToolError::Config)?, *hash)))
+        .collect::<Result<HashMap<u64, H256>, EvmToolError>>()?;
+    let context = BlockContext {
+        coinbase: env.current_coinbase,
+        number: env.current_number,
+        timestamp: env.current_timestamp,
+        gas_limit: env.current_gas_limit,
+        prev_randao: env.current_random,
+        base_fee_per_gas,
+        excess_blob_gas,
+        chain_id: args.chain_id,
+        fork,
+    };
+
+    let mut block_state = BlockState::new(Arc::new(alloc_db(alloc, block_hashes)));
+    if let Some(beacon_root) = env.parent_beacon_block_root {
+        let mut system_env = context.environment(SYSTEM_ADDRESS);
+        system_env.gas_limit = SYSTEM_CALL_GAS_LIMIT.into();
+        system_env.block_gas_limit = SYSTEM_CALL_GAS_LIMIT.into();
+        let mut vm = VM::new(
+            TxKind::Call(BEACON_ROOTS_ADDRESS),
+            system_env,
+            U256::zero(),
+            Bytes::copy_from_slice(beacon_root.as_bytes()),
+            block_state.db(),
+            block_state.transaction_cache(),
+        )?;
+        let report = vm.execute_system_call()?;
+        block_state.commit(report.new_state)?;
+    }
+
+    let max_blob_gas_per_block = u64::try_from(MAX_BLOB_COUNT)
+        .unwrap_or(u64::MAX)
+        .saturating_mul(BLOB_GAS_PER_BLOB.low_u64());
+    let mut gas_used = 0u64;
+    let mut blob_gas_used = 0u64;
+    let mut included_txs = Vec::new();
+    let mut receipts = Vec::new();
+    let mut receipt_outputs = Vec::new();
+    let mut rejected = Vec::new();
+    let mut logs = Vec::new();
+    for (index, tx) in txs.iter().enumerate() {
+        let tx_blob_gas = u64::try_from(tx.blob_versioned_hashes().len())
+            .unwrap_or(u64::MAX)
+            .saturating_mul(BLOB_GAS_PER_BLOB.low_u64());
+        let sender = tx.sender();
+        let rejection = if tx
+            .chain_id()
+            .is_some_and(|chain_id| chain_id != args.chain_id)
+        {
+            Some(format!(
+                "invalid chain id: have {}, want {}",
+                tx.chain_id().unwrap_or_default(),
+                args.chain_id
+            ))
+        } else if tx.gas_limit() > env.current_gas_limit.saturating_sub(gas_used) {
+            Some("gas limit reached".to_string())
+        } else if tx_blob_gas > max_blob_gas_per_block.saturating_sub(blob_gas_used) {
+            Some("blob gas limit reached".to_string())
+        } else {
+            nonce_error(&block_state, sender, tx.nonce())?
+        };
+        if let Some(error) = rejection {
+            rejected.push(RejectedTransaction { index, error });
+            continue;
+        }
+        let Some(gas_price) = tx.effective_gas_price(Some(base_fee_per_gas)) else {
+            rejected.push(RejectedTransaction {
+                index,
+                error: "max fee per gas less than block base fee".to_string(),
+            });
+            continue;
+        };
+
+        let mut tx_env = context.environment(sender);
+        tx_env.gas_limit = tx.gas_limit().into();
+        tx_env.gas_price = gas_price.into();
+        tx_env.tx_blob_hashes = tx.blob_versioned_hashes();
+        tx_env.tx_max_priority_fee_per_gas = tx.max_priority_fee().map(U256::from);
+        tx_env.tx_max_fee_per_gas = tx.max_fee_per_gas().map(U256::from);
+        tx_env.tx_max_fee_per_blob_gas = tx.max_fee_per_blob_gas();
+        tx_env.tx_access_list = tx.access_list();
+
+        let report = match run_transaction(
+            &mut block_state,
+            tx.to(),
+            tx_env,
+            tx.value(),
+            tx.data().clone(),
+        )? {
+            TxOutcome::Included(report) => report,
+            TxOutcome::Rejected(error) => {
+                rejected.push(RejectedTransaction { index, error });
+                continue;
+            }
+        };
+
+        gas_used = gas_used.saturating_add(report.gas_used);
+        blob_gas_used = blob_gas_used.saturating_add(tx_blob_gas);
+        let receipt = Receipt::new(
+            tx.tx_type(),
+            report.is_success(),
+            gas_used,
+            report.logs.clone(),
+        );
+        let transaction_index = u64::try_from(included_txs.len()).unwrap_or(u64::MAX);
+        let transaction_hash = tx.compute_hash();
+        let log_outputs = report
+            .logs
+            .iter()
+            .enumerate()
+            .map(|(position, log)| LogOutput {
+                address: log.address,
+                topics: log.topics.clone(),
+                data: log.data.clone(),
+                block_number: env.current_number,
+                transaction_hash,
+                transaction_index,
+                block_hash: H256::zero(),
+                log_index: u64::try_from(logs.len().saturating_add(position)).unwrap_or(u64::MAX),
+                removed: false,
+            })
+            .collect();
+        receipt_outputs.push(ReceiptOutput {
+            tx_type: tx_type_number(tx.tx_type()),
+            root: Bytes::new(),
+            status: u64::from(report.is_success()),
+            cumulative_gas_used: gas_used,
+            logs_bloom: receipt.bloom,
+            logs: log_outputs,
+            transaction_hash,
+            contract_address: report.created_address.unwrap_or_default(),
+            gas_used: report.gas_used,
+            block_hash: H256::zero(),
+            transaction_index,
+        });
+        logs.extend(report.logs);
+        receipts.push(receipt);
+        included_txs.push(tx.clone());
+    }
+
+    if let Some(withdrawals) = &env.withdrawals {
+        for withdrawal in withdrawals
+            .iter()
+            .filter(|withdrawal| withdrawal.amount > 0)
+        {
+            let amount = U256::from(withdrawal.amount).saturating_mul(U256::from(GWEI_TO_WEI));
+            block_state.increase_account_balance(withdrawal.address, amount)?;
+        }
+    }
+    if let Ok(reward) = u64::try_from(args.reward) {
+        if reward > 0 {
+            block_state.increase_account_balance(env.current_coinbase, U256::from(reward))?;
+        }
+    }
+
+    let mut post_alloc = alloc.clone();
+    apply_changes(&mut post_alloc, block_state.changes());
+
+    let mut logs_bloom = Bloom::zero();
+    for receipt in &receipts {
+        logs_bloom.accrue_bloom(&receipt.bloom);
+    }
+    let result = T8nResult {
+        state_root: state_root(&post_alloc),
+        tx_root: compute_transactions_root(&included_txs),
+        receipts_root: compute_receipts_root(&receipts),
+        logs_hash: logs_hash(&logs),
+        logs_bloom,
+        receipts: receipt_outputs,
+        rejected,
+        current_difficulty: env.current_difficulty,
+        gas_used,
+        current_base_fee: base_fee_per_gas,
+        withdrawals_root: env
+            .withdrawals
+            .as_ref()
+            .map(|withdrawals| compute_withdrawals_root(withdrawals)),
+        current_excess_blob_gas: excess_blob_gas,
+        blob_gas_used: excess_blob_gas.map(|_| blob_gas_used),
+    };
+    Ok((result, post_alloc, included_txs))
+}
+
+/// Base fee of the block, either given or computed from its parent's (EIP-1559)
+fn base_fee_per_gas(env: &T8nEnv) -> Result<u64, EvmToolError> {
+    if let Some(base_fee) = env.current_base_fee {
+        return Ok(base_fee);
+    }
+    match (
+        env.parent_base_fee,
+        env.parent_gas_used,
+        env.parent_gas_limit,
+    ) {
+        (Some(parent_base_fee), Some(parent_gas_used), Some(parent_gas_limit)) => {
+            calculate_base_fee_per_gas(
+                env.current_gas_limit,
+                parent_gas_limit,
+                parent_gas_used,
+                parent_base_fee,
+            )
+            .ok_or_else(|| {
+                EvmToolError::Config(format!(
+                    "invalid gas limit {} for a parent gas limit of {parent_gas_limit}",
+                    env.current_gas_limit
+                ))
+            })
+        }
+        _ => Err(EvmToolError::Config(
+            "currentBaseFee or the parent fields to compute it are required".to_string(),
+        )),
+    }
+}
+
+/// Excess blob gas of the block, either given or computed from its parent's (EIP-4844)
+fn excess_blob_gas(env: &T8nEnv) -> Option<u64> {
+    env.current_excess_blob_gas.or_else(|| {
+        let parent_excess_blob_gas = env.parent_excess_blob_gas?;
+        let parent_blob_gas_used = env.parent_blob_gas_used?;
+        Some(
+            parent_excess_blob_gas
+                .saturating_add(parent_blob_gas_used)
+                .saturating_sub(TARGET_BLOB_GAS_PER_BLOCK.low_u64()),
+        )
+    })
+}
+
+fn tx_type_number(tx_type: TxType) -> u64 {
+    match tx_type {
+        TxType::Legacy => 0x00,
+        TxType::EIP2930 => 0x01,
+        TxType::EIP1559 => 0x02,
+        TxType::EIP4844 => 0x03,
+        TxType::Privileged => 0x7e,
+    }
+}
+
+/// Transactions given as the hex encoding of their RLP list
+fn decode_rlp_txs(rlp: &str) -> Result<Vec<Transaction>, EvmToolError> {
+    let bytes = hex::decode(rlp.trim_start_matches("0x"))
+        .map_err(|err| EvmToolError::Rlp(err.to_string()))?;
+    Vec::<Transaction>::decode(&bytes).map_err(|err| EvmToolError::Rlp(err.to_string()))
+}
+
+fn read_input(path: &str) -> Result<String, EvmToolError> {
+    let mut input = String::new();
+    let result = if path == STDIN {
+        std::io::stdin().read_to_string(&mut input).map(|_| ())
+    } else {
+        std::fs::read_to_string(path).map(|file| input = file)
+    };
+    result
+        .map(|_| input)
+        .map_err(|err| EvmToolError::Io(path.to_string(), err))
+}
+
+fn parse_json<T: DeserializeOwned>(name: &str, input: &str) -> Result<T, EvmToolError> {
+    serde_json::from_str(input).map_err(|err| EvmToolError::Json(name.to_string(), err))
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> Result<Value, EvmToolError> {
+    serde_json::to_value(value).map_err(|err| EvmToolError::Json("output".to_string(), err))
+}
+
+/// Writes each output to its file, or groups it with the others sent to stdout or stderr
+struct Outputs<'a> {
+    basedir: &'a Path,
+    stdout: serde_json::Map<String, Value>,
+    stderr: serde_json::Map<String, Value>,
+}
+
+impl<'a> Outputs<'a> {
+    fn new(basedir: &'a Path) -> Self {
+        Self {
+            basedir,
+            stdout: serde_json::Map::new(),
+            stderr: serde_json::Map::new(),
+        }
+    }
+
+    fn add(&mut self, name: &str, target: &str, value: Value) -> Result<(), EvmToolError> {
+        match target {
+            STDOUT => {
+                self.stdout.insert(name.to_string(), value);
+            }
+            STDERR => {
+                self.stderr.insert(name.to_string(), value);
+            }
+            file => {
+                let path = self.basedir.join(file);
+                let contents = serde_json::to_string_pretty(&value)
+                    .map_err(|err| EvmToolError::Json(name.to_string(), err))?;
+                std::fs::write(&path, contents)
+                    .map_err(|err| EvmToolError::Io(path.display().to_string(), err))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn flush(self) -> Result<(), EvmToolError> {
+        if !self.stdout.is_empty() {
+            let output = serde_json::to_string_pretty(&self.stdout)
+                .map_err(|err| EvmToolError::Json(STDOUT.to_string(), err))?;
+            println!("{output}");
+        }
+        if !self.stderr.is_empty() {
+            let output = serde_json::to_string_pretty(&self.stderr)
+                .map_err(|err| EvmToolError::Json(STDERR.to_string(), err))?;
+            eprintln!("{output}");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,141 @@
+use bytes::Bytes;
+use ethrex_core::{serde_utils, Address, Bloom, H256, U256};
+use serde::{de::Error, Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+
+/// Accounts of a state, in the format of geth's `alloc.json`
+pub type Alloc = BTreeMap<Address, AllocAccount>;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AllocAccount {
+    #[serde(
+        default,
+        skip_serializing_if = "Bytes::is_empty",
+        with = "serde_utils::bytes"
+    )]
+    pub code: Bytes,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub storage: BTreeMap<U256, U256>,
+    #[serde(default)]
+    pub balance: U256,
+    #[serde(
+        default,
+        skip_serializing_if = "is_zero",
+        with = "serde_utils::u64::hex_str"
+    )]
+    pub nonce: u64,
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+/// Result of a state transition, in the format of geth's `result.json`
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct T8nResult {
+    pub state_root: H256,
+    pub tx_root: H256,
+    pub receipts_root: H256,
+    pub logs_hash: H256,
+    pub logs_bloom: Bloom,
+    pub receipts: Vec<ReceiptOutput>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rejected: Vec<RejectedTransaction>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_difficulty: Option<U256>,
+    #[serde(with = "serde_utils::u64::hex_str")]
+    pub gas_used: u64,
+    #[serde(with = "serde_utils::u64::hex_str")]
+    pub current_base_fee: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub withdrawals_root: Option<H256>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        with = "serde_utils::u64::hex_str_opt"
+    )]
+    pub current_excess_blob_gas: Option<u64>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        with = "serde_utils::u64::hex_str_opt"
+    )]
+    pub blob_gas_used: Option<u64>,
+}
+
+/// A transaction left out of the block, along with the reason why
+#[derive(Debug, Serialize)]
+pub struct RejectedTransaction {
+    pub index: usize,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceiptOutput {
+    #[serde(rename = "type", with = "serde_utils::u64::hex_str")]
+    pub tx_type: u64,
+    #[serde(with = "serde_utils::bytes")]
+    pub root: Bytes,
+    #[serde(with = "serde_utils::u64::hex_str")]
+    pub status: u64,
+    #[serde(with = "serde_utils::u64::hex_str")]
+    pub cumulative_gas_used: u64,
+    pub logs_bloom: Bloom,
+    pub logs: Vec<LogOutput>,
+    pub transaction_hash: H256,
+    pub contract_address: Address,
+    #[serde(with = "serde_utils::u64::hex_str")]
+    pub gas_used: u64,
+    pub block_hash: H256,
+    #[serde(with = "serde_utils::u64::hex_str")]
+    pub transaction_index: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogOutput {
+    pub address: Address,
+    pub topics: Vec<H256>,
+    #[serde(with = "serde_utils::bytes")]
+    pub data: Bytes,
+    #[serde(with = "serde_utils::u64::hex_str")]
+    pub block_number: u64,
+    pub transaction_hash: H256,
+    #[serde(with = "serde_utils::u64::hex_str")]
+    pub transaction_index: u64,
+    pub block_hash: H256,
+    #[serde(with = "serde_utils::u64::hex_str")]
+    pub log_index: u64,
+    pub removed: bool,
+}
+
+/// Outcome of a state test for one fork and transaction, as reported by geth's `evm statetest`
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateTestResult {
+    pub name: String,
+    pub pass: bool,
+    pub state_root: H256,
+    pub fork: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub error: String,
+}
+
+/// Deserializes an optional number given as a hex or decimal string
+pub fn deser_opt_u64<'de, D>(d: D) -> Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(d)?
+        .map(|value| parse_u64(&value).map_err(D::Error::custom))
+        .transpose()
+}
+
+/// Parses a number given as a hex or decimal string
+pub fn parse_u64(value: &str) -> Result<u64, String> {
+    match value.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => value.parse(),
+    }
+    .map_err(|err| format!("invalid number {value}: {err}"))
+}
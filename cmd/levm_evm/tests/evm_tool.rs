@@ -0,0 +1,196 @@
+#![allow(clippy::indexing_slicing)]
+#![allow(clippy::unwrap_used)]
+
+use ethrex_core::{
+    types::{EIP1559Transaction, Genesis, GenesisAccount, Signable, Transaction, TxKind},
+    Address, H256, U256,
+};
+use ethrex_rlp::encode::RLPEncode;
+use secp256k1::SecretKey;
+use serde_json::{json, Value};
+use std::{collections::HashMap, path::PathBuf, process::Command, str::FromStr};
+
+const SECRET_KEY: &str = "45a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d8";
+const SENDER: &str = "0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b";
+const RECIPIENT: &str = "0x1000000000000000000000000000000000000000";
+const COINBASE: &str = "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba";
+const EMPTY_LOGS_HASH: &str = "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347";
+const INITIAL_BALANCE: u64 = 1_000_000_000_000_000_000;
+
+fn address(address: &str) -> Address {
+    Address::from_str(address).unwrap()
+}
+
+fn work_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("levm-evm-tool-{name}-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// State root after the sender transfers 1 wei to the recipient paying 1 wei per gas
+fn transfer_state_root() -> H256 {
+    let account = |balance: U256, nonce: u64| GenesisAccount {
+        code: Default::default(),
+        storage: HashMap::new(),
+        balance,
+        nonce,
+    };
+    let sender_balance = U256::from(INITIAL_BALANCE)
+        .checked_sub(U256::from(21_001))
+        .unwrap();
+    let genesis = Genesis {
+        alloc: HashMap::from([
+            (address(SENDER), account(sender_balance, 1)),
+            (address(RECIPIENT), account(U256::one(), 0)),
+            (address(COINBASE), account(U256::from(21_000), 0)),
+        ]),
+        ..Default::default()
+    };
+    genesis.compute_state_root()
+}
+
+fn transfer(nonce: u64) -> Transaction {
+    let secret_key = SecretKey::from_slice(&hex::decode(SECRET_KEY).unwrap()).unwrap();
+    Transaction::EIP1559Transaction(EIP1559Transaction {
+        chain_id: 1,
+        nonce,
+        max_priority_fee_per_gas: 1,
+        max_fee_per_gas: 1,
+        gas_limit: 21_000,
+        to: TxKind::Call(address(RECIPIENT)),
+        value: U256::one(),
+        ..Default::default()
+    })
+    .sign(&secret_key)
+}
+
+#[test]
+fn t8n_executes_the_block_transactions() {
+    let dir = work_dir("t8n");
+    let alloc = json!({ SENDER: { "balance": format!("{INITIAL_BALANCE:#x}") } });
+    let env = json!({
+        "currentCoinbase": COINBASE,
+        "currentGasLimit": "0x1000000",
+        "currentNumber": "0x1",
+        "currentTimestamp": "0x1000",
+        "currentRandom": H256::zero(),
+        "currentBaseFee": "0x0",
+        "currentExcessBlobGas": "0x0",
+        "parentBeaconBlockRoot": H256::zero(),
+        "withdrawals": [],
+    });
+    // The second transaction reuses the nonce of the first one
+    let txs = vec![transfer(0), transfer(0)];
+    std::fs::write(dir.join("alloc.json"), alloc.to_string()).unwrap();
+    std::fs::write(dir.join("env.json"), env.to_string()).unwrap();
+    std::fs::write(
+        dir.join("txs.rlp"),
+        json!(format!("0x{}", hex::encode(txs.encode_to_vec()))).to_string(),
+    )
+    .unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_evm"))
+        .current_dir(&dir)
+        .args([
+            "t8n",
+            "--input.alloc=alloc.json",
+            "--input.env=env.json",
+            "--input.txs=txs.rlp",
+            "--output.result=result.json",
+            "--output.alloc=out_alloc.json",
+            "--state.fork=Cancun",
+        ])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let result: Value =
+        serde_json::from_str(&std::fs::read_to_string(dir.join("result.json")).unwrap()).unwrap();
+    assert_eq!(
+        result["stateRoot"],
+        json!(format!("{:#x}", transfer_state_root()))
+    );
+    assert_eq!(result["gasUsed"], json!("0x5208"));
+    assert_eq!(result["logsHash"], json!(EMPTY_LOGS_HASH));
+    assert_eq!(result["receipts"].as_array().unwrap().len(), 1);
+    assert_eq!(result["receipts"][0]["status"], json!("0x1"));
+    assert_eq!(
+        result["receipts"][0]["transactionHash"],
+        json!(format!("{:#x}", txs[0].compute_hash()))
+    );
+    assert_eq!(result["rejected"][0]["index"], json!(1));
+    assert!(result["rejected"][0]["error"]
+        .as_str()
+        .unwrap()
+        .starts_with("nonce too low"));
+
+    let post_alloc: Value =
+        serde_json::from_str(&std::fs::read_to_string(dir.join("out_alloc.json")).unwrap())
+            .unwrap();
+    assert_eq!(post_alloc[RECIPIENT]["balance"], json!("0x1"));
+    assert_eq!(post_alloc[SENDER]["nonce"], json!("0x1"));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn statetest_checks_the_post_state() {
+    let dir = work_dir("statetest");
+    let test = |hash: H256| {
+        json!({
+            "transfer": {
+                "env": {
+                    "currentCoinbase": COINBASE,
+                    "currentGasLimit": "0x1000000",
+                    "currentNumber": "0x1",
+                    "currentTimestamp": "0x1000",
+                    "currentRandom": H256::zero(),
+                    "currentBaseFee": "0x0",
+                    "currentExcessBlobGas": "0x0",
+                },
+                "pre": { SENDER: { "balance": format!("{INITIAL_BALANCE:#x}") } },
+                "transaction": {
+                    "data": ["0x"],
+                    "gasLimit": ["0x5208"],
+                    "value": ["0x1"],
+                    "nonce": "0x0",
+                    "gasPrice": "0x1",
+                    "secretKey": format!("0x{SECRET_KEY}"),
+                    "to": RECIPIENT,
+                },
+                "post": {
+                    "Cancun": [{
+                        "hash": hash,
+                        "logs": EMPTY_LOGS_HASH,
+                        "indexes": { "data": 0, "gas": 0, "value": 0 },
+                    }],
+                },
+            }
+        })
+    };
+    let run = |test: Value| {
+        let path = dir.join("test.json");
+        std::fs::write(&path, test.to_string()).unwrap();
+        let output = Command::new(env!("CARGO_BIN_EXE_evm"))
+            .arg("statetest")
+            .arg(&path)
+            .output()
+            .unwrap();
+        let results: Value = serde_json::from_slice(&output.stdout).unwrap();
+        (output.status.success(), results[0].clone())
+    };
+
+    let (success, result) = run(test(transfer_state_root()));
+    assert!(success);
+    assert_eq!(result["pass"], json!(true));
+
+    let (success, result) = run(test(H256::zero()));
+    assert!(!success);
+    assert_eq!(result["pass"], json!(false));
+    assert!(result["error"]
+        .as_str()
+        .unwrap()
+        .starts_with("post state root mismatch"));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -3,19 +3,48 @@ pub mod error;
 pub mod fork_choice;
 pub mod mempool;
 pub mod payload;
+pub mod simulate;
 mod smoke_test;
 
 use constants::{GAS_PER_BLOB, MAX_BLOB_GAS_PER_BLOCK, MAX_BLOB_NUMBER_PER_BLOCK};
 use error::{ChainError, InvalidBlockError};
 use ethrex_core::types::{
     validate_block_header, validate_cancun_header_fields, validate_no_cancun_header_fields, Block,
-    BlockHash, BlockHeader, BlockNumber, EIP4844Transaction, Receipt, Transaction,
+    BlockHash, BlockHeader, BlockNumber, ChainConfig, EIP4844Transaction, Receipt, Transaction,
 };
 use ethrex_core::H256;
+use ethrex_metrics::chain::{observe_block_import, BlockImportStage};
 
 use ethrex_storage::error::StoreError;
-use ethrex_storage::Store;
-use ethrex_vm::{evm_state, execute_block, spec_id, EvmState, SpecId};
+use ethrex_storage::{Store, WriteBatch};
+use ethrex_vm::{differential::compare_block_execution, spec_id, Evm, SpecId};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+use tracing::{info, warn};
+
+pub use ethrex_vm::EvmEngine;
+
+/// Configures how blocks are executed when they are added to the chain
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct EvmConfig {
+    /// Engine whose results are stored
+    pub engine: EvmEngine,
+    /// When set, every block is also executed with both revm and LEVM before being added, and the
+    /// first transaction where they diverge is saved to this directory as an EF state test
+    pub differential: Option<PathBuf>,
+}
+
+impl From<EvmEngine> for EvmConfig {
+    fn from(engine: EvmEngine) -> Self {
+        EvmConfig {
+            engine,
+            differential: None,
+        }
+    }
+}
 
 //TODO: Implement a struct Chain or BlockChain to encapsulate
 //functionality and canonical chain state and config
@@ -25,10 +54,30 @@ use ethrex_vm::{evm_state, execute_block, spec_id, EvmState, SpecId};
 /// canonical chain/head. Fork choice needs to be updated for that in a separate step.
 ///
 /// Performs pre and post execution validation, and updates the database with the post state.
-#[cfg(not(feature = "levm"))]
-pub fn add_block(block: &Block, storage: &Store) -> Result<(), ChainError> {
-    use ethrex_vm::get_state_transitions;
+/// The block is executed as set in the given EVM config.
+pub fn add_block(block: &Block, storage: &Store, evm: &EvmConfig) -> Result<(), ChainError> {
+    add_block_inner(block, storage, evm, true)
+}
 
+/// Adds a block coming from a trusted source (such as a chain file exported by this node)
+/// to the store, same as [`add_block`] but skipping pre and post execution validation.
+///
+/// The block is still executed and its post-state root is computed and stored, as it is needed
+/// to execute its children, but it is not checked against the one in the block header.
+pub fn add_trusted_block(
+    block: &Block,
+    storage: &Store,
+    evm: &EvmConfig,
+) -> Result<(), ChainError> {
+    add_block_inner(block, storage, evm, false)
+}
+
+fn add_block_inner(
+    block: &Block,
+    storage: &Store,
+    evm: &EvmConfig,
+    validate: bool,
+) -> Result<(), ChainError> {
     let block_hash = block.header.compute_block_hash();
 
     // Validate if it can be the new head and find the parent
@@ -37,74 +86,83 @@ pub fn add_block(block: &Block, storage: &Store) -> Result<(), ChainError> {
         storage.add_pending_block(block.clone())?;
         return Err(ChainError::ParentNotFound);
     };
-    let mut state = evm_state(storage.clone(), block.header.parent_hash);
+    // Compare both engines before executing the block, so divergences are saved even when the
+    // configured engine fails to execute it
+    if let Some(fixtures_dir) = &evm.differential {
+        report_evm_divergences(block, storage, fixtures_dir);
+    }
+
+    let mut evm = Evm::new(evm.engine, storage.clone(), block.header.parent_hash);
 
     // Validate the block pre-execution
-    validate_block(block, &parent_header, &state)?;
+    if validate {
+        validate_block(block, &parent_header, &evm.chain_config()?)?;
+    }
 
-    let receipts = execute_block(block, &mut state)?;
+    let execution_start = Instant::now();
+    let (receipts, account_updates) = evm.execute_block(block)?;
+    observe_block_import(BlockImportStage::Execution, execution_start.elapsed());
 
-    validate_gas_used(&receipts, &block.header)?;
+    if validate {
+        validate_gas_used(&receipts, &block.header)?;
+    }
 
-    let account_updates = get_state_transitions(&mut state);
+    // All of the block's data is written within a single transaction, so a failure
+    // never leaves a partially stored block behind
+    let mut batch = WriteBatch::new();
 
     // Apply the account updates over the last block's state and compute the new state root
-    let new_state_root = state
-        .database()
-        .ok_or(ChainError::StoreError(StoreError::MissingStore))?
-        .apply_account_updates(block.header.parent_hash, &account_updates)?
+    let state_root_start = Instant::now();
+    let new_state_root = storage
+        .apply_account_updates_to_batch(&mut batch, block.header.parent_hash, &account_updates)?
         .ok_or(ChainError::ParentStateNotFound)?;
+    observe_block_import(BlockImportStage::StateRoot, state_root_start.elapsed());
 
     // Check state root matches the one in block header after execution
-    validate_state_root(&block.header, new_state_root)?;
+    if validate {
+        validate_state_root(&block.header, new_state_root)?;
+    }
 
-    store_block(storage, block.clone())?;
-    store_receipts(storage, receipts, block_hash)?;
+    let db_write_start = Instant::now();
+    storage.add_block_to_batch(&mut batch, block.clone())?;
+    batch.add_receipts(block_hash, receipts);
+    storage.write_batch(batch)?;
+    observe_block_import(BlockImportStage::DbWrite, db_write_start.elapsed());
 
     Ok(())
 }
 
-/// Adds a new block to the store. It may or may not be canonical, as long as its ancestry links
-/// with the canonical chain and its parent's post-state is calculated. It doesn't modify the
-/// canonical chain/head. Fork choice needs to be updated for that in a separate step.
-///
-/// Performs pre and post execution validation, and updates the database with the post state.
-#[cfg(feature = "levm")]
-pub fn add_block(block: &Block, storage: &Store) -> Result<(), ChainError> {
-    let block_hash = block.header.compute_block_hash();
-
-    // Validate if it can be the new head and find the parent
-    let Ok(parent_header) = find_parent_header(&block.header, storage) else {
-        // If the parent is not present, we store it as pending.
-        storage.add_pending_block(block.clone())?;
-        return Err(ChainError::ParentNotFound);
+/// Executes the block with both revm and LEVM, logging the first transaction where they diverge
+/// and saving it as an EF state test in the given directory
+fn report_evm_divergences(block: &Block, storage: &Store, fixtures_dir: &Path) {
+    let number = block.header.number;
+    let divergence = match compare_block_execution(block, storage) {
+        Ok(Some(divergence)) => divergence,
+        Ok(None) => return,
+        Err(error) => {
+            warn!("Failed to compare revm and LEVM on block {number}: {error}");
+            return;
+        }
     };
-    let mut state = evm_state(storage.clone(), block.header.parent_hash);
-
-    // Validate the block pre-execution
-    validate_block(block, &parent_header, &state)?;
-
-    let (receipts, account_updates) = execute_block(block, &mut state)?;
-
-    // Note: these is commented because it is still being used in development.
-    // dbg!(&account_updates);
-
-    validate_gas_used(&receipts, &block.header)?;
-
-    // Apply the account updates over the last block's state and compute the new state root
-    let new_state_root = state
-        .database()
-        .ok_or(ChainError::StoreError(StoreError::MissingStore))?
-        .apply_account_updates(block.header.parent_hash, &account_updates)?
-        .ok_or(ChainError::ParentStateNotFound)?;
-
-    // Check state root matches the one in block header after execution
-    validate_state_root(&block.header, new_state_root)?;
-
-    store_block(storage, block.clone())?;
-    store_receipts(storage, receipts, block_hash)?;
-
-    Ok(())
+    warn!(
+        "revm and LEVM diverge on transaction {} ({:#x}) of block {number}: {}",
+        divergence.tx_index,
+        divergence.tx_hash,
+        divergence.mismatches.join("; ")
+    );
+    let fixture_path = fixtures_dir.join(format!(
+        "block_{number}_tx_{}_{:#x}.json",
+        divergence.tx_index, divergence.tx_hash
+    ));
+    match fs::create_dir_all(fixtures_dir)
+        .and_then(|_| fs::write(&fixture_path, format!("{:#}", divergence.fixture)))
+    {
+        Ok(()) => info!("Saved divergence fixture to {}", fixture_path.display()),
+        Err(error) => warn!(
+            "Failed to save divergence fixture to {}: {error}",
+            fixture_path.display()
+        ),
+    }
 }
 
 /// Stores block and header in the database
@@ -170,25 +228,21 @@ pub fn find_parent_header(
 pub fn validate_block(
     block: &Block,
     parent_header: &BlockHeader,
-    state: &EvmState,
+    chain_config: &ChainConfig,
 ) -> Result<(), ChainError> {
-    let spec = spec_id(
-        &state.chain_config().map_err(ChainError::from)?,
-        block.header.timestamp,
-    );
+    let spec = spec_id(chain_config, block.header.timestamp);
 
     // Verify initial header validity against parent
     validate_block_header(&block.header, parent_header).map_err(InvalidBlockError::from)?;
 
-    match spec {
-        SpecId::CANCUN => validate_cancun_header_fields(&block.header, parent_header)
-            .map_err(InvalidBlockError::from)?,
-        _other_specs => {
-            validate_no_cancun_header_fields(&block.header).map_err(InvalidBlockError::from)?
-        }
+    if spec.is_enabled_in(SpecId::CANCUN) {
+        validate_cancun_header_fields(&block.header, parent_header)
+            .map_err(InvalidBlockError::from)?
+    } else {
+        validate_no_cancun_header_fields(&block.header).map_err(InvalidBlockError::from)?
     };
 
-    if spec == SpecId::CANCUN {
+    if spec.is_enabled_in(SpecId::CANCUN) {
         verify_blob_gas_usage(block)?
     }
     Ok(())
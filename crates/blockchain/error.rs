@@ -36,6 +36,28 @@ pub enum InvalidBlockError {
     InvalidTransaction(String),
 }
 
+#[derive(Debug, thiserror::Error)]
+pub enum SimulationError {
+    #[error("Block number {number} is not greater than the parent's {parent}")]
+    BlockNumberNotIncreasing { number: u64, parent: u64 },
+    #[error("Block timestamp {timestamp} is not greater than the parent's {parent}")]
+    TimestampNotIncreasing { timestamp: u64, parent: u64 },
+    #[error("Too many blocks, at most {0} can be simulated")]
+    TooManyBlocks(u64),
+    #[error("Block gas limit reached, the call needs {gas} gas and {remaining} are left")]
+    BlockGasLimitReached { gas: u64, remaining: u64 },
+    #[error("Nonce too low, the call has nonce {call} and the account {account}")]
+    NonceTooLow { call: u64, account: u64 },
+    #[error("Nonce too high, the call has nonce {call} and the account {account}")]
+    NonceTooHigh { call: u64, account: u64 },
+    #[error("Max fee per gas {max_fee} is lower than the block base fee {base_fee}")]
+    BaseFeeTooLow { max_fee: u64, base_fee: u64 },
+    #[error("DB error: {0}")]
+    StoreError(#[from] StoreError),
+    #[error("EVM error: {0}")]
+    EvmError(#[from] EvmError),
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum MempoolError {
     #[error("No block header")]
@@ -137,6 +137,7 @@ pub fn apply_fork_choice(
         store.update_safe_block_number(safe.header.number)?;
     }
     store.update_latest_block_number(head.number)?;
+    ethrex_metrics::chain::set_head(head.number, head.timestamp);
 
     Ok(head)
 }
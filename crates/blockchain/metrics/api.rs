@@ -0,0 +1,37 @@
+use std::net::SocketAddr;
+
+use axum::{
+    http::{header::CONTENT_TYPE, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use prometheus::TEXT_FORMAT;
+use tokio::net::TcpListener;
+use tracing::info;
+
+use crate::{gather_metrics, MetricsError};
+
+/// Serves the node's metrics at `/metrics` until Ctrl+C is received
+pub async fn start_prometheus_metrics_api(addr: SocketAddr) -> Result<(), MetricsError> {
+    let router = Router::new().route("/metrics", get(get_metrics));
+    let listener = TcpListener::bind(addr).await?;
+    info!("Starting metrics server at {addr}");
+    axum::serve(listener, router)
+        .with_graceful_shutdown(shutdown_signal())
+        .await?;
+    Ok(())
+}
+
+async fn get_metrics() -> Response {
+    match gather_metrics() {
+        Ok(metrics) => ([(CONTENT_TYPE, TEXT_FORMAT)], metrics).into_response(),
+        Err(error) => (StatusCode::INTERNAL_SERVER_ERROR, error.to_string()).into_response(),
+    }
+}
+
+async fn shutdown_signal() {
+    tokio::signal::ctrl_c()
+        .await
+        .expect("failed to install Ctrl+C handler");
+}
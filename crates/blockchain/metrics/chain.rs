@@ -0,0 +1,119 @@
+use std::{
+    sync::LazyLock,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use prometheus::{Gauge, HistogramVec, IntGauge};
+
+use crate::{gauge_value, histogram_vec, int_gauge, register};
+
+static HEAD_NUMBER: LazyLock<IntGauge> = LazyLock::new(|| {
+    int_gauge(
+        "ethrex_chain_head_number",
+        "Number of the head of the canonical chain",
+    )
+});
+
+static HEAD_TIMESTAMP: LazyLock<IntGauge> = LazyLock::new(|| {
+    int_gauge(
+        "ethrex_chain_head_timestamp_seconds",
+        "Timestamp of the head of the canonical chain",
+    )
+});
+
+static HEAD_LAG: LazyLock<Gauge> = LazyLock::new(|| {
+    register(
+        Gauge::new(
+            "ethrex_chain_head_lag_seconds",
+            "Seconds elapsed since the timestamp of the head of the canonical chain",
+        )
+        .expect("Metric options are valid"),
+    )
+});
+
+static BLOCK_IMPORT: LazyLock<HistogramVec> = LazyLock::new(|| {
+    histogram_vec(
+        "ethrex_block_import_seconds",
+        "Time spent importing blocks, by stage",
+        &["stage"],
+        vec![
+            0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+        ],
+    )
+});
+
+/// Stages a block goes through when it's added to the chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockImportStage {
+    /// Running the block's transactions
+    Execution,
+    /// Applying the account updates to the state trie and computing its root
+    StateRoot,
+    /// Writing the block, its receipts and its state to the database
+    DbWrite,
+}
+
+impl BlockImportStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BlockImportStage::Execution => "execution",
+            BlockImportStage::StateRoot => "state_root",
+            BlockImportStage::DbWrite => "db_write",
+        }
+    }
+}
+
+/// Records a new head of the canonical chain
+pub fn set_head(number: u64, timestamp: u64) {
+    HEAD_NUMBER.set(gauge_value(number));
+    HEAD_TIMESTAMP.set(gauge_value(timestamp));
+    update_head_lag();
+}
+
+/// Records the time a block spent on one of the stages of its import
+pub fn observe_block_import(stage: BlockImportStage, duration: Duration) {
+    BLOCK_IMPORT
+        .with_label_values(&[stage.as_str()])
+        .observe(duration.as_secs_f64());
+}
+
+/// Updates the head lag to the current time, so it keeps growing while no new head is set
+pub(crate) fn update_head_lag() {
+    let timestamp = HEAD_TIMESTAMP.get();
+    if timestamp == 0 {
+        // No head was set yet
+        return;
+    }
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64();
+    HEAD_LAG.set((now - timestamp as f64).max(0.0));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gather_metrics;
+
+    #[test]
+    fn head_lag_is_measured_from_the_head_timestamp() {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        set_head(7, now.as_secs() - 30);
+        assert_eq!(HEAD_NUMBER.get(), 7);
+        assert!((30.0..60.0).contains(&HEAD_LAG.get()));
+
+        let metrics = gather_metrics().unwrap();
+        assert!(metrics.contains("ethrex_chain_head_number 7"));
+        assert!(metrics.contains("ethrex_chain_head_lag_seconds"));
+    }
+
+    #[test]
+    fn block_import_is_split_by_stage() {
+        observe_block_import(BlockImportStage::Execution, Duration::from_millis(20));
+        observe_block_import(BlockImportStage::DbWrite, Duration::from_millis(3));
+        let metrics = gather_metrics().unwrap();
+        assert!(metrics.contains("ethrex_block_import_seconds_count{stage=\"execution\"} 1"));
+        assert!(metrics.contains("ethrex_block_import_seconds_count{stage=\"db_write\"} 1"));
+    }
+}
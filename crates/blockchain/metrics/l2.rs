@@ -0,0 +1,49 @@
+use std::sync::LazyLock;
+
+use prometheus::IntGauge;
+
+use crate::{gauge_value, int_gauge};
+
+static PROPOSER_HEAD: LazyLock<IntGauge> = LazyLock::new(|| {
+    int_gauge(
+        "ethrex_l2_proposer_head_block",
+        "Block the L2 proposer last started producing blocks on top of",
+    )
+});
+
+static COMMITTER_LAST_COMMITTED: LazyLock<IntGauge> = LazyLock::new(|| {
+    int_gauge(
+        "ethrex_l2_committer_last_committed_block",
+        "Last L2 block committed to the L1",
+    )
+});
+
+static PROVER_SERVER_LAST_VERIFIED: LazyLock<IntGauge> = LazyLock::new(|| {
+    int_gauge(
+        "ethrex_l2_prover_server_last_verified_block",
+        "Last L2 block whose proof was verified on the L1",
+    )
+});
+
+static PROVER_SERVER_LAST_PROVED: LazyLock<IntGauge> = LazyLock::new(|| {
+    int_gauge(
+        "ethrex_l2_prover_server_last_proved_block",
+        "Last L2 block whose proof was sent to the L1 by the prover server",
+    )
+});
+
+pub fn set_proposer_head(block_number: u64) {
+    PROPOSER_HEAD.set(gauge_value(block_number));
+}
+
+pub fn set_last_committed_block(block_number: u64) {
+    COMMITTER_LAST_COMMITTED.set(gauge_value(block_number));
+}
+
+pub fn set_last_verified_block(block_number: u64) {
+    PROVER_SERVER_LAST_VERIFIED.set(gauge_value(block_number));
+}
+
+pub fn set_last_proved_block(block_number: u64) {
+    PROVER_SERVER_LAST_PROVED.set(gauge_value(block_number));
+}
@@ -0,0 +1,23 @@
+use std::sync::LazyLock;
+
+use prometheus::IntGaugeVec;
+
+use crate::int_gauge_vec;
+
+static TRANSACTIONS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    int_gauge_vec(
+        "ethrex_mempool_transactions",
+        "Transactions in the mempool, by type",
+        &["type"],
+    )
+});
+
+/// Records a transaction of the given type being added to the mempool
+pub fn transaction_added(tx_type: &str) {
+    TRANSACTIONS.with_label_values(&[tx_type]).inc();
+}
+
+/// Records a transaction of the given type being removed from the mempool
+pub fn transaction_removed(tx_type: &str) {
+    TRANSACTIONS.with_label_values(&[tx_type]).dec();
+}
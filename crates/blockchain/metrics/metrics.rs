@@ -0,0 +1,68 @@
+//! Prometheus metrics of the node.
+//!
+//! Every metric is registered in a single registry the first time it's used, and served in
+//! Prometheus' text format by [`api::start_prometheus_metrics_api`].
+
+pub mod api;
+pub mod chain;
+pub mod l2;
+pub mod mempool;
+pub mod p2p;
+pub mod rpc;
+pub mod store;
+
+use std::sync::LazyLock;
+
+use prometheus::{
+    core::Collector, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts,
+    Registry, TextEncoder,
+};
+
+static REGISTRY: LazyLock<Registry> = LazyLock::new(Registry::new);
+
+#[derive(Debug, thiserror::Error)]
+pub enum MetricsError {
+    #[error("Prometheus error: {0}")]
+    Prometheus(#[from] prometheus::Error),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Returns every metric of the node in Prometheus' text format
+pub fn gather_metrics() -> Result<String, MetricsError> {
+    chain::update_head_lag();
+    let encoder = TextEncoder::new();
+    Ok(encoder.encode_to_string(&REGISTRY.gather())?)
+}
+
+// Metrics are only created by their statics, with constant names and options, so neither
+// building nor registering them can fail
+
+fn register<M: Collector + Clone + 'static>(metric: M) -> M {
+    REGISTRY
+        .register(Box::new(metric.clone()))
+        .expect("Metric names are unique");
+    metric
+}
+
+fn int_gauge(name: &str, help: &str) -> IntGauge {
+    register(IntGauge::new(name, help).expect("Metric options are valid"))
+}
+
+fn int_gauge_vec(name: &str, help: &str, labels: &[&str]) -> IntGaugeVec {
+    register(IntGaugeVec::new(Opts::new(name, help), labels).expect("Metric options are valid"))
+}
+
+fn int_counter_vec(name: &str, help: &str, labels: &[&str]) -> IntCounterVec {
+    register(IntCounterVec::new(Opts::new(name, help), labels).expect("Metric options are valid"))
+}
+
+fn histogram_vec(name: &str, help: &str, labels: &[&str], buckets: Vec<f64>) -> HistogramVec {
+    let opts = HistogramOpts::new(name, help).buckets(buckets);
+    register(HistogramVec::new(opts, labels).expect("Metric options are valid"))
+}
+
+/// Converts block numbers to the values of integer gauges
+fn gauge_value(value: u64) -> i64 {
+    i64::try_from(value).unwrap_or(i64::MAX)
+}
@@ -0,0 +1,68 @@
+use std::sync::LazyLock;
+
+use prometheus::{IntCounterVec, IntGaugeVec};
+
+use crate::{int_counter_vec, int_gauge_vec};
+
+static PEERS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    int_gauge_vec(
+        "ethrex_p2p_peers",
+        "Connected peers, by capability they share with the node",
+        &["capability"],
+    )
+});
+
+static MESSAGES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    int_counter_vec(
+        "ethrex_rlpx_messages_total",
+        "RLPx messages sent to and received from peers, by type",
+        &["direction", "message"],
+    )
+});
+
+/// Records a peer connecting with the given capabilities
+pub fn peer_connected(capabilities: &[&str]) {
+    for capability in capabilities {
+        PEERS.with_label_values(&[capability]).inc();
+    }
+}
+
+/// Records a peer with the given capabilities disconnecting
+pub fn peer_disconnected(capabilities: &[&str]) {
+    for capability in capabilities {
+        PEERS.with_label_values(&[capability]).dec();
+    }
+}
+
+/// Records a message sent to a peer
+pub fn message_sent(message: &str) {
+    MESSAGES.with_label_values(&["out", message]).inc();
+}
+
+/// Records a message received from a peer
+pub fn message_received(message: &str) {
+    MESSAGES.with_label_values(&["in", message]).inc();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peers_are_counted_per_capability() {
+        peer_connected(&["test_eth", "test_snap"]);
+        peer_connected(&["test_eth"]);
+        peer_disconnected(&["test_eth", "test_snap"]);
+        assert_eq!(PEERS.with_label_values(&["test_eth"]).get(), 1);
+        assert_eq!(PEERS.with_label_values(&["test_snap"]).get(), 0);
+    }
+
+    #[test]
+    fn messages_are_counted_per_direction() {
+        message_sent("test:Ping");
+        message_received("test:Ping");
+        message_received("test:Ping");
+        assert_eq!(MESSAGES.with_label_values(&["out", "test:Ping"]).get(), 1);
+        assert_eq!(MESSAGES.with_label_values(&["in", "test:Ping"]).get(), 2);
+    }
+}
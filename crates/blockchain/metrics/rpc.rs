@@ -0,0 +1,34 @@
+use std::{sync::LazyLock, time::Duration};
+
+use prometheus::{HistogramVec, IntCounterVec};
+
+use crate::{histogram_vec, int_counter_vec};
+
+static REQUEST_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    histogram_vec(
+        "ethrex_rpc_request_duration_seconds",
+        "Time spent handling JSON-RPC requests, by method",
+        &["method"],
+        vec![
+            0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0,
+        ],
+    )
+});
+
+static REQUEST_ERRORS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    int_counter_vec(
+        "ethrex_rpc_request_errors_total",
+        "JSON-RPC requests that returned an error, by method",
+        &["method"],
+    )
+});
+
+/// Records a handled request of the given method, and whether it failed
+pub fn record_request(method: &str, duration: Duration, failed: bool) {
+    REQUEST_DURATION
+        .with_label_values(&[method])
+        .observe(duration.as_secs_f64());
+    if failed {
+        REQUEST_ERRORS.with_label_values(&[method]).inc();
+    }
+}
@@ -0,0 +1,54 @@
+use std::{sync::LazyLock, time::Instant};
+
+use prometheus::HistogramVec;
+
+use crate::histogram_vec;
+
+static OPERATION_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    histogram_vec(
+        "ethrex_store_operation_seconds",
+        "Time spent on store engine reads and writes, by operation",
+        &["kind", "operation"],
+        vec![
+            0.00001, 0.00005, 0.0001, 0.0005, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0,
+        ],
+    )
+});
+
+/// Runs a read of the store engine, recording the time it took
+pub fn time_read<T>(operation: &str, read: impl FnOnce() -> T) -> T {
+    time_operation("read", operation, read)
+}
+
+/// Runs a write to the store engine, recording the time it took
+pub fn time_write<T>(operation: &str, write: impl FnOnce() -> T) -> T {
+    time_operation("write", operation, write)
+}
+
+fn time_operation<T>(kind: &str, operation: &str, run: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let result = run();
+    OPERATION_DURATION
+        .with_label_values(&[kind, operation])
+        .observe(start.elapsed().as_secs_f64());
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operations_are_timed_by_kind() {
+        assert_eq!(time_read("test_account", || 1), 1);
+        time_write("test_batch", || ());
+        time_write("test_batch", || ());
+        let count = |kind, operation| {
+            OPERATION_DURATION
+                .with_label_values(&[kind, operation])
+                .get_sample_count()
+        };
+        assert_eq!(count("read", "test_account"), 1);
+        assert_eq!(count("write", "test_batch"), 2);
+    }
+}
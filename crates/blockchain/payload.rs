@@ -1,6 +1,7 @@
 use std::{
     cmp::{min, Ordering},
     collections::HashMap,
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 use ethrex_core::{
@@ -13,11 +14,8 @@ use ethrex_core::{
     Address, Bloom, Bytes, H256, U256,
 };
 use ethrex_rlp::encode::RLPEncode;
-use ethrex_storage::{error::StoreError, Store};
-use ethrex_vm::{
-    beacon_root_contract_call, evm_state, execute_tx, get_state_transitions, process_withdrawals,
-    spec_id, EvmError, EvmState, SpecId,
-};
+use ethrex_storage::{error::StoreError, AccountUpdate, Store, WriteBatch};
+use ethrex_vm::{Evm, EvmEngine, EvmError};
 use sha3::{Digest, Keccak256};
 
 use crate::{
@@ -69,7 +67,21 @@ pub fn create_payload(args: &BuildPayloadArgs, storage: &Store) -> Result<Block,
         .ok_or_else(|| ChainError::ParentNotFound)?;
     let chain_config = storage.get_chain_config()?;
     let gas_limit = calc_gas_limit(parent_block.gas_limit, DEFAULT_BUILDER_GAS_CEIL);
+    Ok(create_payload_from_parent(
+        args,
+        &parent_block,
+        &chain_config,
+        gas_limit,
+    ))
+}
 
+/// Creates a new payload on top of the given parent block, with the given gas limit
+pub fn create_payload_from_parent(
+    args: &BuildPayloadArgs,
+    parent_block: &BlockHeader,
+    chain_config: &ChainConfig,
+    gas_limit: u64,
+) -> Block {
     let header = BlockHeader {
         parent_hash: args.parent,
         ommers_hash: *DEFAULT_OMMERS_HASH,
@@ -113,7 +125,7 @@ pub fn create_payload(args: &BuildPayloadArgs, storage: &Store) -> Result<Block,
     };
 
     // Delay applying withdrawals until the payload is requested and built
-    Ok(Block::new(header, body))
+    Block::new(header, body)
 }
 
 fn calc_gas_limit(parent_gas_limit: u64, desired_limit: u64) -> u64 {
@@ -147,16 +159,18 @@ fn calc_excess_blob_gas(parent_excess_blob_gas: u64, parent_blob_gas_used: u64)
 
 pub struct PayloadBuildContext<'a> {
     pub payload: &'a mut Block,
-    pub evm_state: &'a mut EvmState,
+    pub evm: &'a mut Evm,
     pub remaining_gas: u64,
     pub receipts: Vec<Receipt>,
     pub block_value: U256,
     base_fee_per_blob_gas: U256,
     pub blobs_bundle: BlobsBundle,
+    /// Whether the transactions added to the payload are removed from the mempool
+    pub pull_from_mempool: bool,
 }
 
 impl<'a> PayloadBuildContext<'a> {
-    fn new(payload: &'a mut Block, evm_state: &'a mut EvmState) -> Self {
+    pub fn new(payload: &'a mut Block, evm: &'a mut Evm) -> Self {
         PayloadBuildContext {
             remaining_gas: payload.header.gas_limit,
             receipts: vec![],
@@ -165,8 +179,9 @@ impl<'a> PayloadBuildContext<'a> {
                 payload.header.excess_blob_gas.unwrap_or_default(),
             )),
             payload,
-            evm_state,
+            evm,
             blobs_bundle: BlobsBundle::default(),
+            pull_from_mempool: true,
         }
     }
 }
@@ -181,11 +196,11 @@ impl<'a> PayloadBuildContext<'a> {
     }
 
     fn store(&self) -> Option<&Store> {
-        self.evm_state.database()
+        self.evm.store()
     }
 
     fn chain_config(&self) -> Result<ChainConfig, EvmError> {
-        self.evm_state.chain_config()
+        self.evm.chain_config()
     }
 
     fn base_fee_per_gas(&self) -> Option<u64> {
@@ -193,28 +208,91 @@ impl<'a> PayloadBuildContext<'a> {
     }
 }
 
-/// Completes the payload building process, return the block value
+/// Completes the payload building process with the given EVM engine, return the block value
 pub fn build_payload(
     payload: &mut Block,
     store: &Store,
+    evm: EvmEngine,
 ) -> Result<(BlobsBundle, U256), ChainError> {
     debug!("Building payload");
-    let mut evm_state = evm_state(store.clone(), payload.header.parent_hash);
-    let mut context = PayloadBuildContext::new(payload, &mut evm_state);
+    let mut evm = Evm::new(evm, store.clone(), payload.header.parent_hash);
+    let mut context = PayloadBuildContext::new(payload, &mut evm);
     apply_withdrawals(&mut context)?;
     fill_transactions(&mut context)?;
     finalize_payload(&mut context)?;
     Ok((context.blobs_bundle, context.block_value))
 }
 
+/// Builds the block served for the `pending` tag: a block on top of the given head holding the
+/// mempool transactions that fit in it. As it's not meant to be proposed, its transactions are
+/// kept in the mempool.
+pub fn build_pending_block(
+    head: &BlockHeader,
+    store: &Store,
+    evm: EvmEngine,
+) -> Result<(Block, Vec<AccountUpdate>), ChainError> {
+    let chain_config = store.get_chain_config()?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let timestamp = now.max(head.timestamp + 1);
+    let args = BuildPayloadArgs {
+        parent: head.compute_block_hash(),
+        timestamp,
+        fee_recipient: Address::zero(),
+        random: H256::zero(),
+        withdrawals: Vec::new(),
+        beacon_root: chain_config
+            .is_cancun_activated(timestamp)
+            .then_some(H256::zero()),
+        version: 3,
+    };
+    let mut payload = create_payload(&args, store)?;
+    let mut evm = Evm::new(evm, store.clone(), args.parent);
+    let mut context = PayloadBuildContext::new(&mut payload, &mut evm);
+    context.pull_from_mempool = false;
+    apply_withdrawals(&mut context)?;
+    fill_transactions(&mut context)?;
+    // The block is rebuilt often and never imported, so its trie nodes are added to a batch
+    // that is never written. Its state is kept in memory instead, as account updates
+    let account_updates = context.evm.get_state_transitions();
+    context.payload.header.state_root = store
+        .apply_account_updates_to_batch(&mut WriteBatch::new(), args.parent, &account_updates)?
+        .unwrap_or_default();
+    fill_payload_header(&mut context);
+    Ok((payload, account_updates))
+}
+
+/// Rebuilds the pending block if the head or the mempool changed since it was last built
+pub fn update_pending_block(store: &Store, evm: EvmEngine) -> Result<(), ChainError> {
+    let Some(head_number) = store.get_latest_block_number()? else {
+        return Ok(());
+    };
+    let Some(head) = store.get_block_header(head_number)? else {
+        return Ok(());
+    };
+    // Read before building, so changes made meanwhile trigger another build
+    let mempool_generation = store.get_mempool_generation();
+    if let Some((block, built_generation)) = store.get_current_pending_block()? {
+        if block.header.parent_hash == head.compute_block_hash()
+            && built_generation == mempool_generation
+        {
+            return Ok(());
+        }
+    }
+    let (block, account_updates) = build_pending_block(&head, store, evm)?;
+    store.set_current_pending_block(block, account_updates, mempool_generation)?;
+    Ok(())
+}
+
 pub fn apply_withdrawals(context: &mut PayloadBuildContext) -> Result<(), EvmError> {
     // Apply withdrawals & call beacon root contract, and obtain the new state root
-    let spec_id = spec_id(&context.chain_config()?, context.payload.header.timestamp);
-    if context.payload.header.parent_beacon_block_root.is_some() && spec_id == SpecId::CANCUN {
-        beacon_root_contract_call(context.evm_state, &context.payload.header, spec_id)?;
-    }
+    context
+        .evm
+        .beacon_root_contract_call(&context.payload.header)?;
     let withdrawals = context.payload.body.withdrawals.clone().unwrap_or_default();
-    process_withdrawals(context.evm_state, &withdrawals)?;
+    context.evm.process_withdrawals(&withdrawals)?;
     Ok(())
 }
 
@@ -309,12 +387,14 @@ pub fn fill_transactions(context: &mut PayloadBuildContext) -> Result<(), ChainE
             // Pull transaction from the mempool
             debug!("Ignoring replay-protected transaction: {}", tx_hash);
             txs.pop();
-            mempool::remove_transaction(
-                &head_tx.tx.compute_hash(),
-                context
-                    .store()
-                    .ok_or(ChainError::StoreError(StoreError::MissingStore))?,
-            )?;
+            if context.pull_from_mempool {
+                mempool::remove_transaction(
+                    &head_tx.tx.compute_hash(),
+                    context
+                        .store()
+                        .ok_or(ChainError::StoreError(StoreError::MissingStore))?,
+                )?;
+            }
             continue;
         }
         // Execute tx
@@ -322,12 +402,14 @@ pub fn fill_transactions(context: &mut PayloadBuildContext) -> Result<(), ChainE
             Ok(receipt) => {
                 txs.shift()?;
                 // Pull transaction from the mempool
-                mempool::remove_transaction(
-                    &head_tx.tx.compute_hash(),
-                    context
-                        .store()
-                        .ok_or(ChainError::StoreError(StoreError::MissingStore))?,
-                )?;
+                if context.pull_from_mempool {
+                    mempool::remove_transaction(
+                        &head_tx.tx.compute_hash(),
+                        context
+                            .store()
+                            .ok_or(ChainError::StoreError(StoreError::MissingStore))?,
+                    )?;
+                }
                 receipt
             }
             // Ignore following txs from sender
@@ -396,15 +478,7 @@ fn apply_plain_transaction(
     head: &HeadTransaction,
     context: &mut PayloadBuildContext,
 ) -> Result<Receipt, ChainError> {
-    let result = execute_tx(
-        &head.tx,
-        &context.payload.header,
-        context.evm_state,
-        spec_id(
-            &context.chain_config().map_err(ChainError::from)?,
-            context.payload.header.timestamp,
-        ),
-    )?;
+    let result = context.evm.execute_tx(&head.tx, &context.payload.header)?;
     context.remaining_gas = context.remaining_gas.saturating_sub(result.gas_used());
     context.block_value += U256::from(result.gas_used()) * head.tip;
     let receipt = Receipt::new(
@@ -417,7 +491,7 @@ fn apply_plain_transaction(
 }
 
 fn finalize_payload(context: &mut PayloadBuildContext) -> Result<(), StoreError> {
-    let account_updates = get_state_transitions(context.evm_state);
+    let account_updates = context.evm.get_state_transitions();
     // Note: This is commented because it is still being used in development.
     // dbg!(&account_updates);
     context.payload.header.state_root = context
@@ -425,11 +499,21 @@ fn finalize_payload(context: &mut PayloadBuildContext) -> Result<(), StoreError>
         .ok_or(StoreError::MissingStore)?
         .apply_account_updates(context.parent_hash(), &account_updates)?
         .unwrap_or_default();
+    fill_payload_header(context);
+    Ok(())
+}
+
+/// Sets the header fields that depend on the transactions included in the payload
+pub fn fill_payload_header(context: &mut PayloadBuildContext) {
     context.payload.header.transactions_root =
         compute_transactions_root(&context.payload.body.transactions);
     context.payload.header.receipts_root = compute_receipts_root(&context.receipts);
+    let mut logs_bloom = Bloom::zero();
+    for receipt in &context.receipts {
+        logs_bloom.accrue_bloom(&receipt.bloom);
+    }
+    context.payload.header.logs_bloom = logs_bloom;
     context.payload.header.gas_used = context.payload.header.gas_limit - context.remaining_gas;
-    Ok(())
 }
 
 /// A struct representing suitable mempool transactions waiting to be included in a block
@@ -0,0 +1,311 @@
+use ethrex_core::{
+    types::{
+        Block, BlockHash, BlockHeader, ChainConfig, EIP1559Transaction, EIP2930Transaction,
+        EIP4844Transaction, GenericTransaction, LegacyTransaction, Receipt, Transaction, TxKind,
+    },
+    Address, H256,
+};
+use ethrex_storage::{AccountUpdate, Store, WriteBatch};
+use ethrex_vm::{
+    BlockOverrides, Evm, EvmEngine, ExecutionResult, SimulationConfig, StateOverride,
+    TRANSFER_LOG_EMITTER,
+};
+
+use crate::{
+    error::SimulationError,
+    payload::{
+        apply_withdrawals, create_payload_from_parent, fill_payload_header, BuildPayloadArgs,
+        PayloadBuildContext,
+    },
+};
+
+/// Maximum number of blocks a simulation can go through after its base block, counting the
+/// empty ones filling the gaps between the requested block numbers
+pub const MAX_SIMULATED_BLOCKS: u64 = 256;
+/// Seconds between simulated blocks whose timestamp isn't overridden
+const SIMULATED_BLOCK_TIME: u64 = 12;
+
+/// Calls run in a simulated block, along with the overrides applied before running them
+#[derive(Debug, Clone, Default)]
+pub struct BlockStateCalls {
+    pub block_overrides: BlockOverrides,
+    pub state_overrides: StateOverride,
+    pub calls: Vec<GenericTransaction>,
+}
+
+/// Call executed in a simulated block
+#[derive(Debug)]
+pub struct SimulatedCall {
+    /// Unsigned transaction made from the call, as included in the block
+    pub transaction: Transaction,
+    pub sender: Address,
+    pub result: ExecutionResult,
+}
+
+/// Block built by a simulation, with the results of its calls
+#[derive(Debug)]
+pub struct SimulatedBlock {
+    pub block: Block,
+    pub calls: Vec<SimulatedCall>,
+    pub receipts: Vec<Receipt>,
+}
+
+/// Simulates a chain of blocks on top of `base`, each one running its calls on the state left
+/// by the previous ones. Blocks are built as payloads are, but nothing is stored.
+pub fn simulate_blocks(
+    base: &BlockHeader,
+    blocks: &[BlockStateCalls],
+    config: SimulationConfig,
+    store: &Store,
+    engine: EvmEngine,
+) -> Result<Vec<SimulatedBlock>, SimulationError> {
+    let base_hash = base.compute_block_hash();
+    let mut simulator = Simulator {
+        store,
+        chain_config: store.get_chain_config()?,
+        config,
+        evm: Evm::new(engine, store.clone(), base_hash),
+        base_hash,
+        account_updates: Vec::new(),
+    };
+    let mut parent = base.clone();
+    let mut simulated_blocks = Vec::new();
+    for block_calls in blocks {
+        let number = block_calls
+            .block_overrides
+            .number
+            .unwrap_or(parent.number.saturating_add(1));
+        if number <= parent.number {
+            return Err(SimulationError::BlockNumberNotIncreasing {
+                number,
+                parent: parent.number,
+            });
+        }
+        if number.saturating_sub(base.number) > MAX_SIMULATED_BLOCKS {
+            return Err(SimulationError::TooManyBlocks(MAX_SIMULATED_BLOCKS));
+        }
+        // The blocks skipped by the requested number are simulated without calls
+        while parent.number.saturating_add(1) < number {
+            let simulated = simulator.simulate_block(&parent, &BlockStateCalls::default())?;
+            parent = simulated.block.header.clone();
+            simulated_blocks.push(simulated);
+        }
+        let simulated = simulator.simulate_block(&parent, block_calls)?;
+        parent = simulated.block.header.clone();
+        simulated_blocks.push(simulated);
+    }
+    Ok(simulated_blocks)
+}
+
+struct Simulator<'a> {
+    store: &'a Store,
+    chain_config: ChainConfig,
+    config: SimulationConfig,
+    /// Holds the state left by the blocks simulated so far
+    evm: Evm,
+    base_hash: BlockHash,
+    /// Account updates made since the base block, which the state root of every simulated
+    /// block is computed from
+    account_updates: Vec<AccountUpdate>,
+}
+
+impl Simulator<'_> {
+    fn simulate_block(
+        &mut self,
+        parent: &BlockHeader,
+        block_calls: &BlockStateCalls,
+    ) -> Result<SimulatedBlock, SimulationError> {
+        let overrides = &block_calls.block_overrides;
+        let timestamp = overrides
+            .time
+            .unwrap_or(parent.timestamp.saturating_add(SIMULATED_BLOCK_TIME));
+        if timestamp <= parent.timestamp {
+            return Err(SimulationError::TimestampNotIncreasing {
+                timestamp,
+                parent: parent.timestamp,
+            });
+        }
+        let args = BuildPayloadArgs {
+            parent: parent.compute_block_hash(),
+            timestamp,
+            fee_recipient: overrides.fee_recipient.unwrap_or_default(),
+            random: overrides.prev_randao.unwrap_or_default(),
+            withdrawals: Vec::new(),
+            beacon_root: self
+                .chain_config
+                .is_cancun_activated(timestamp)
+                .then_some(H256::zero()),
+            version: 0,
+        };
+        let mut block =
+            create_payload_from_parent(&args, parent, &self.chain_config, parent.gas_limit);
+        overrides.apply(&mut block.header);
+        let override_updates = self
+            .evm
+            .apply_state_overrides(&block_calls.state_overrides)?;
+        self.account_updates.extend(override_updates);
+
+        let mut context = PayloadBuildContext::new(&mut block, &mut self.evm);
+        apply_withdrawals(&mut context)?;
+        let mut calls = Vec::new();
+        for call in &block_calls.calls {
+            let call = prepare_call(call, &mut context, self.config, self.chain_config.chain_id)?;
+            let result =
+                context
+                    .evm
+                    .execute_tx_from_generic(&call, &context.payload.header, self.config)?;
+            context.remaining_gas = context.remaining_gas.saturating_sub(result.gas_used());
+            let transaction = call_transaction(&call);
+            // Traced transfers are only reported with the calls, they aren't part of the receipt
+            let logs = result
+                .logs()
+                .into_iter()
+                .filter(|log| !self.config.trace_transfers || log.address != TRANSFER_LOG_EMITTER)
+                .collect();
+            context.receipts.push(Receipt::new(
+                transaction.tx_type(),
+                result.is_success(),
+                context.payload.header.gas_limit - context.remaining_gas,
+                logs,
+            ));
+            context.payload.body.transactions.push(transaction.clone());
+            calls.push(SimulatedCall {
+                transaction,
+                sender: call.from,
+                result,
+            });
+        }
+
+        self.account_updates
+            .extend(context.evm.get_state_transitions());
+        // The trie nodes of simulated blocks are added to a batch that is never written
+        context.payload.header.state_root = self
+            .store
+            .apply_account_updates_to_batch(
+                &mut WriteBatch::new(),
+                self.base_hash,
+                &self.account_updates,
+            )?
+            .unwrap_or_default();
+        fill_payload_header(&mut context);
+        let receipts = std::mem::take(&mut context.receipts);
+        Ok(SimulatedBlock {
+            block,
+            calls,
+            receipts,
+        })
+    }
+}
+
+/// Fills in the fields missing from a call, and checks it can be included in the block
+fn prepare_call(
+    call: &GenericTransaction,
+    context: &mut PayloadBuildContext,
+    config: SimulationConfig,
+    chain_id: u64,
+) -> Result<GenericTransaction, SimulationError> {
+    let mut call = call.clone();
+    let gas = call.gas.unwrap_or(context.remaining_gas);
+    if gas > context.remaining_gas {
+        return Err(SimulationError::BlockGasLimitReached {
+            gas,
+            remaining: context.remaining_gas,
+        });
+    }
+    call.gas = Some(gas);
+    call.chain_id.get_or_insert(chain_id);
+
+    let account_nonce = context.evm.get_account_info(call.from)?.nonce;
+    match call.nonce {
+        None => call.nonce = Some(account_nonce),
+        Some(nonce) if config.validation && nonce < account_nonce => {
+            return Err(SimulationError::NonceTooLow {
+                call: nonce,
+                account: account_nonce,
+            })
+        }
+        Some(nonce) if config.validation && nonce > account_nonce => {
+            return Err(SimulationError::NonceTooHigh {
+                call: nonce,
+                account: account_nonce,
+            })
+        }
+        Some(_) => {}
+    }
+
+    if config.validation {
+        let base_fee = context.payload.header.base_fee_per_gas.unwrap_or_default();
+        let max_fee = match call.gas_price {
+            0 => call.max_fee_per_gas.unwrap_or_default(),
+            gas_price => gas_price,
+        };
+        if max_fee < base_fee {
+            return Err(SimulationError::BaseFeeTooLow { max_fee, base_fee });
+        }
+    }
+    Ok(call)
+}
+
+/// Unsigned transaction made from a call, whose type is chosen from the fields it sets
+fn call_transaction(call: &GenericTransaction) -> Transaction {
+    let nonce = call.nonce.unwrap_or_default();
+    let gas = call.gas.unwrap_or_default();
+    let chain_id = call.chain_id.unwrap_or_default();
+    let access_list: Vec<_> = call
+        .access_list
+        .iter()
+        .map(|entry| (entry.address, entry.storage_keys.clone()))
+        .collect();
+    match call.to {
+        TxKind::Call(to) if !call.blob_versioned_hashes.is_empty() => {
+            Transaction::EIP4844Transaction(EIP4844Transaction {
+                chain_id,
+                nonce,
+                max_priority_fee_per_gas: call.max_priority_fee_per_gas.unwrap_or_default(),
+                max_fee_per_gas: call.max_fee_per_gas.unwrap_or_default(),
+                gas,
+                to,
+                value: call.value,
+                data: call.input.clone(),
+                access_list,
+                max_fee_per_blob_gas: call.max_fee_per_blob_gas.unwrap_or_default(),
+                blob_versioned_hashes: call.blob_versioned_hashes.clone(),
+                ..Default::default()
+            })
+        }
+        _ if call.gas_price != 0 && access_list.is_empty() => {
+            Transaction::LegacyTransaction(LegacyTransaction {
+                nonce,
+                gas_price: call.gas_price,
+                gas,
+                to: call.to.clone(),
+                value: call.value,
+                data: call.input.clone(),
+                ..Default::default()
+            })
+        }
+        _ if call.gas_price != 0 => Transaction::EIP2930Transaction(EIP2930Transaction {
+            chain_id,
+            nonce,
+            gas_price: call.gas_price,
+            gas_limit: gas,
+            to: call.to.clone(),
+            value: call.value,
+            data: call.input.clone(),
+            access_list,
+            ..Default::default()
+        }),
+        _ => Transaction::EIP1559Transaction(EIP1559Transaction {
+            chain_id,
+            nonce,
+            max_priority_fee_per_gas: call.max_priority_fee_per_gas.unwrap_or_default(),
+            max_fee_per_gas: call.max_fee_per_gas.unwrap_or_default(),
+            gas_limit: gas,
+            to: call.to.clone(),
+            value: call.value,
+            data: call.input.clone(),
+            access_list,
+            ..Default::default()
+        }),
+    }
+}
@@ -7,14 +7,21 @@ mod blockchain_integration_test {
         error::{ChainError, InvalidForkChoice},
         fork_choice::apply_fork_choice,
         is_canonical, latest_canonical_block_hash,
-        payload::{build_payload, create_payload, BuildPayloadArgs},
+        payload::{build_payload, create_payload, update_pending_block, BuildPayloadArgs},
+        EvmConfig, EvmEngine,
     };
 
     use ethrex_core::{
-        types::{Block, BlockHeader},
-        H160, H256,
+        types::{
+            AccountInfo, Block, BlockHeader, EIP1559Transaction, MempoolTransaction, Signable,
+            Transaction, TxKind,
+        },
+        H160, H256, U256,
     };
-    use ethrex_storage::{EngineType, Store};
+    use ethrex_rlp::encode::RLPEncode;
+    use ethrex_storage::{AccountUpdate, EngineType, Store};
+    use secp256k1::SecretKey;
+    use sha3::{Digest, Keccak256};
 
     #[test]
     fn test_small_to_long_reorg() {
@@ -26,7 +33,7 @@ mod blockchain_integration_test {
         // Add first block. We'll make it canonical.
         let block_1a = new_block(&store, &genesis_header);
         let hash_1a = block_1a.hash();
-        add_block(&block_1a, &store).unwrap();
+        add_block(&block_1a, &store, &EvmConfig::default()).unwrap();
         store.set_canonical_block(1, hash_1a).unwrap();
         let retrieved_1a = store.get_block_header(1).unwrap().unwrap();
 
@@ -36,7 +43,7 @@ mod blockchain_integration_test {
         // Add second block at height 1. Will not be canonical.
         let block_1b = new_block(&store, &genesis_header);
         let hash_1b = block_1b.hash();
-        add_block(&block_1b, &store).expect("Could not add block 1b.");
+        add_block(&block_1b, &store, &EvmConfig::default()).expect("Could not add block 1b.");
         let retrieved_1b = store.get_block_header_by_hash(hash_1b).unwrap().unwrap();
 
         assert_ne!(retrieved_1a, retrieved_1b);
@@ -45,7 +52,7 @@ mod blockchain_integration_test {
         // Add a third block at height 2, child to the non canonical block.
         let block_2 = new_block(&store, &block_1b.header);
         let hash_2 = block_2.hash();
-        add_block(&block_2, &store).expect("Could not add block 2.");
+        add_block(&block_2, &store, &EvmConfig::default()).expect("Could not add block 2.");
         let retrieved_2 = store.get_block_header_by_hash(hash_2).unwrap();
 
         assert!(retrieved_2.is_some());
@@ -75,14 +82,14 @@ mod blockchain_integration_test {
         // Build a single valid block.
         let block_1 = new_block(&store, &genesis_header);
         let hash_1 = block_1.header.compute_block_hash();
-        add_block(&block_1, &store).unwrap();
+        add_block(&block_1, &store, &EvmConfig::default()).unwrap();
         apply_fork_choice(&store, hash_1, H256::zero(), H256::zero()).unwrap();
 
         // Build a child, then change its parent, making it effectively a pending block.
         let mut block_2 = new_block(&store, &block_1.header);
         block_2.header.parent_hash = H256::random();
         let hash_2 = block_2.header.compute_block_hash();
-        let result = add_block(&block_2, &store);
+        let result = add_block(&block_2, &store, &EvmConfig::default());
         assert!(matches!(result, Err(ChainError::ParentNotFound)));
 
         // block 2 should now be pending.
@@ -105,7 +112,7 @@ mod blockchain_integration_test {
         // Add first block. Not canonical.
         let block_1a = new_block(&store, &genesis_header);
         let hash_1a = block_1a.hash();
-        add_block(&block_1a, &store).unwrap();
+        add_block(&block_1a, &store, &EvmConfig::default()).unwrap();
         let retrieved_1a = store.get_block_header_by_hash(hash_1a).unwrap().unwrap();
 
         assert!(!is_canonical(&store, 1, hash_1a).unwrap());
@@ -113,7 +120,7 @@ mod blockchain_integration_test {
         // Add second block at height 1. Canonical.
         let block_1b = new_block(&store, &genesis_header);
         let hash_1b = block_1b.hash();
-        add_block(&block_1b, &store).expect("Could not add block 1b.");
+        add_block(&block_1b, &store, &EvmConfig::default()).expect("Could not add block 1b.");
         apply_fork_choice(&store, hash_1b, genesis_hash, genesis_hash).unwrap();
         let retrieved_1b = store.get_block_header(1).unwrap().unwrap();
 
@@ -125,7 +132,7 @@ mod blockchain_integration_test {
         // Add a third block at height 2, child to the canonical one.
         let block_2 = new_block(&store, &block_1b.header);
         let hash_2 = block_2.hash();
-        add_block(&block_2, &store).expect("Could not add block 2.");
+        add_block(&block_2, &store, &EvmConfig::default()).expect("Could not add block 2.");
         apply_fork_choice(&store, hash_2, genesis_hash, genesis_hash).unwrap();
         let retrieved_2 = store.get_block_header_by_hash(hash_2).unwrap();
         assert_eq!(latest_canonical_block_hash(&store).unwrap(), hash_2);
@@ -160,12 +167,12 @@ mod blockchain_integration_test {
         // Add block at height 1.
         let block_1 = new_block(&store, &genesis_header);
         let hash_1 = block_1.hash();
-        add_block(&block_1, &store).expect("Could not add block 1b.");
+        add_block(&block_1, &store, &EvmConfig::default()).expect("Could not add block 1b.");
 
         // Add child at height 2.
         let block_2 = new_block(&store, &block_1.header);
         let hash_2 = block_2.hash();
-        add_block(&block_2, &store).expect("Could not add block 2.");
+        add_block(&block_2, &store, &EvmConfig::default()).expect("Could not add block 2.");
 
         assert!(!is_canonical(&store, 1, hash_1).unwrap());
         assert!(!is_canonical(&store, 2, hash_2).unwrap());
@@ -201,12 +208,12 @@ mod blockchain_integration_test {
 
         // Add block at height 1.
         let block_1 = new_block(&store, &genesis_header);
-        add_block(&block_1, &store).expect("Could not add block 1b.");
+        add_block(&block_1, &store, &EvmConfig::default()).expect("Could not add block 1b.");
 
         // Add child at height 2.
         let block_2 = new_block(&store, &block_1.header);
         let hash_2 = block_2.hash();
-        add_block(&block_2, &store).expect("Could not add block 2.");
+        add_block(&block_2, &store, &EvmConfig::default()).expect("Could not add block 2.");
 
         assert_eq!(latest_canonical_block_hash(&store).unwrap(), genesis_hash);
 
@@ -218,7 +225,7 @@ mod blockchain_integration_test {
         // Add a new, non canonical block, starting from genesis.
         let block_1b = new_block(&store, &genesis_header);
         let hash_b = block_1b.hash();
-        add_block(&block_1b, &store).expect("Could not add block b.");
+        add_block(&block_1b, &store, &EvmConfig::default()).expect("Could not add block b.");
 
         // The latest block should be the same.
         assert_eq!(latest_canonical_block_hash(&store).unwrap(), hash_2);
@@ -230,6 +237,148 @@ mod blockchain_integration_test {
         assert_eq!(latest_canonical_block_hash(&store).unwrap(), hash_b);
     }
 
+    #[test]
+    fn pending_block_holds_mempool_transactions() {
+        let store = test_store();
+        let private_key = SecretKey::from_slice(&[0x11; 32]).unwrap();
+        let recipient = H160::random();
+        let chain_id = store.get_chain_config().unwrap().chain_id;
+        let tx = Transaction::EIP1559Transaction(EIP1559Transaction {
+            chain_id,
+            nonce: 0,
+            max_priority_fee_per_gas: 1_000_000_000,
+            max_fee_per_gas: 10_000_000_000,
+            gas_limit: 21_000,
+            to: TxKind::Call(recipient),
+            value: U256::from(1_000),
+            ..Default::default()
+        })
+        .sign(&private_key);
+        let sender = tx.sender();
+        let funded_head = fund_accounts(&store, &[(sender, U256::from(10).pow(U256::from(18)))]);
+        let funded_head_hash = funded_head.compute_block_hash();
+
+        let tx_hash = tx.compute_hash();
+        store
+            .add_transaction_to_pool(tx_hash, MempoolTransaction::new(tx.clone(), sender))
+            .unwrap();
+        update_pending_block(&store, EvmEngine::default()).unwrap();
+
+        let (pending_block, _) = store.get_current_pending_block().unwrap().unwrap();
+        assert_eq!(pending_block.header.parent_hash, funded_head_hash);
+        assert_eq!(pending_block.body.transactions, vec![tx]);
+        // The transaction stays in the mempool, as the pending block isn't proposed
+        assert!(store
+            .get_pool_transaction_hashes()
+            .unwrap()
+            .contains(&tx_hash));
+        let recipient_info = store
+            .get_account_info_by_hash(pending_block.hash(), recipient)
+            .unwrap()
+            .unwrap();
+        assert_eq!(recipient_info.balance, U256::from(1_000));
+        // Its state is only kept in memory, the trie isn't written to the DB
+        assert!(store.state_trie(pending_block.hash()).unwrap().is_none());
+
+        // Nothing changed, so the pending block isn't rebuilt
+        update_pending_block(&store, EvmEngine::default()).unwrap();
+        let (rebuilt_block, _) = store.get_current_pending_block().unwrap().unwrap();
+        assert_eq!(rebuilt_block.hash(), pending_block.hash());
+    }
+
+    #[test]
+    fn levm_removes_accounts_emptied_by_a_block() {
+        let store = test_store();
+        let private_key = SecretKey::from_slice(&[0x11; 32]).unwrap();
+        let beneficiary = H160::repeat_byte(0xbb);
+        // Initcode that destroys the contract right away, sending its balance to the beneficiary
+        let mut initcode = vec![0x73];
+        initcode.extend_from_slice(beneficiary.as_bytes());
+        initcode.push(0xff);
+        let tx = Transaction::EIP1559Transaction(EIP1559Transaction {
+            chain_id: store.get_chain_config().unwrap().chain_id,
+            nonce: 0,
+            max_priority_fee_per_gas: 1_000_000_000,
+            max_fee_per_gas: 10_000_000_000,
+            gas_limit: 200_000,
+            to: TxKind::Create,
+            data: initcode.into(),
+            ..Default::default()
+        })
+        .sign(&private_key);
+        let sender = tx.sender();
+        let mut encoded = Vec::new();
+        (sender, 0u64).encode(&mut encoded);
+        let contract = H160::from_slice(&Keccak256::digest(encoded)[12..]);
+
+        // The contract's address already holds a balance, so the block empties an account in
+        // the state
+        let head = fund_accounts(
+            &store,
+            &[
+                (sender, U256::from(10).pow(U256::from(18))),
+                (contract, U256::from(1_000)),
+            ],
+        );
+        store
+            .add_transaction_to_pool(
+                tx.compute_hash(),
+                MempoolTransaction::new(tx.clone(), sender),
+            )
+            .unwrap();
+        let args = BuildPayloadArgs {
+            parent: head.compute_block_hash(),
+            timestamp: head.timestamp + 12,
+            fee_recipient: H160::random(),
+            random: H256::random(),
+            withdrawals: Vec::new(),
+            beacon_root: Some(H256::random()),
+            version: 1,
+        };
+        let mut block = create_payload(&args, &store).unwrap();
+        build_payload(&mut block, &store, EvmEngine::LEVM).unwrap();
+        assert_eq!(block.body.transactions, vec![tx]);
+
+        // revm checks the state root computed with LEVM when adding the block
+        add_block(&block, &store, &EvmConfig::from(EvmEngine::REVM)).unwrap();
+        assert!(store
+            .get_account_info_by_hash(block.hash(), contract)
+            .unwrap()
+            .is_none());
+        let beneficiary_info = store
+            .get_account_info_by_hash(block.hash(), beneficiary)
+            .unwrap()
+            .unwrap();
+        assert_eq!(beneficiary_info.balance, U256::from(1_000));
+    }
+
+    // Replaces the genesis with one where the given accounts hold a balance
+    fn fund_accounts(store: &Store, accounts: &[(H160, U256)]) -> BlockHeader {
+        let head = store.get_block_header(0).unwrap().unwrap();
+        let updates: Vec<_> = accounts
+            .iter()
+            .map(|(address, balance)| {
+                let mut update = AccountUpdate::new(*address);
+                update.info = Some(AccountInfo {
+                    balance: *balance,
+                    ..Default::default()
+                });
+                update
+            })
+            .collect();
+        let mut funded_head = head.clone();
+        funded_head.state_root = store
+            .apply_account_updates(head.compute_block_hash(), &updates)
+            .unwrap()
+            .unwrap();
+        let funded_head_hash = funded_head.compute_block_hash();
+        store
+            .add_block_header(funded_head_hash, funded_head.clone())
+            .unwrap();
+        store.set_canonical_block(0, funded_head_hash).unwrap();
+        funded_head
+    }
+
     fn new_block(store: &Store, parent: &BlockHeader) -> Block {
         let args = BuildPayloadArgs {
             parent: parent.compute_block_hash(),
@@ -242,7 +391,7 @@ mod blockchain_integration_test {
         };
 
         let mut block = create_payload(&args, store).unwrap();
-        build_payload(&mut block, store).unwrap();
+        build_payload(&mut block, store, EvmEngine::default()).unwrap();
         block
     }
 
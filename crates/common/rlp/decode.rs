@@ -27,6 +27,37 @@ pub trait RLPDecode: Sized {
     }
 }
 
+/// Trait for decoding RLP encoded slices of data into values that borrow from the input.
+/// Unlike [`RLPDecode`], implementors don't copy the payload, so large byte strings can be
+/// inspected without allocating.
+/// See [`RlpView`](crate::view::RlpView) for lazily decoding nested lists.
+pub trait RLPDecodeRef<'a>: Sized {
+    fn decode_unfinished_ref(rlp: &'a [u8]) -> Result<(Self, &'a [u8]), RLPDecodeError>;
+
+    fn decode_ref(rlp: &'a [u8]) -> Result<Self, RLPDecodeError> {
+        let (decoded, remaining) = Self::decode_unfinished_ref(rlp)?;
+        if !remaining.is_empty() {
+            return Err(RLPDecodeError::InvalidLength);
+        }
+
+        Ok(decoded)
+    }
+}
+
+impl<'a> RLPDecodeRef<'a> for &'a [u8] {
+    fn decode_unfinished_ref(rlp: &'a [u8]) -> Result<(Self, &'a [u8]), RLPDecodeError> {
+        decode_bytes(rlp)
+    }
+}
+
+impl<'a> RLPDecodeRef<'a> for &'a str {
+    fn decode_unfinished_ref(rlp: &'a [u8]) -> Result<(Self, &'a [u8]), RLPDecodeError> {
+        let (str_bytes, rest) = decode_bytes(rlp)?;
+        let value = std::str::from_utf8(str_bytes).map_err(|_| RLPDecodeError::MalformedData)?;
+        Ok((value, rest))
+    }
+}
+
 impl RLPDecode for bool {
     #[inline(always)]
     fn decode_unfinished(buf: &[u8]) -> Result<(Self, &[u8]), RLPDecodeError> {
@@ -655,6 +686,21 @@ mod tests {
         assert_eq!(decoded, expected);
     }
 
+    #[test]
+    fn test_decode_ref() {
+        let rlp = vec![RLP_NULL + 3, b'd', b'o', b'g'];
+        let decoded = <&str>::decode_ref(&rlp).unwrap();
+        assert_eq!(decoded, "dog");
+        // The decoded value points into the input buffer
+        assert_eq!(decoded.as_ptr(), rlp[1..].as_ptr());
+
+        let decoded = <&[u8]>::decode_ref(&rlp).unwrap();
+        assert_eq!(decoded, b"dog");
+
+        let rlp = vec![RLP_EMPTY_LIST + 1, 0x01];
+        assert!(<&[u8]>::decode_ref(&rlp).is_err());
+    }
+
     #[test]
     fn test_decode_lists() {
         // empty list
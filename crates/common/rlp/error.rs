@@ -15,6 +15,8 @@ pub enum RLPDecodeError {
     UnexpectedString,
     #[error("InvalidCompression")]
     InvalidCompression(#[from] snap::Error),
+    #[error("IoError: {0}")]
+    IoError(#[from] std::io::Error),
     #[error("{0}")]
     Custom(String),
 }
@@ -2,4 +2,6 @@ pub mod constants;
 pub mod decode;
 pub mod encode;
 pub mod error;
+pub mod stream;
 pub mod structs;
+pub mod view;
@@ -0,0 +1,209 @@
+use super::{
+    constants::RLP_EMPTY_LIST,
+    decode::{static_left_pad, RLPDecode},
+    error::RLPDecodeError,
+};
+use std::{
+    io::{ErrorKind, Read},
+    marker::PhantomData,
+};
+
+/// # Streaming RLP reader
+///
+/// Reads a sequence of concatenated RLP items (such as a `chain.rlp` file) from any
+/// [`Read`] implementor, one item at a time.
+/// Only the item being decoded is kept in memory, and its buffer is reused for the next one,
+/// so arbitrarily long streams can be decoded in constant memory.
+///
+/// # Examples
+///
+/// ```
+/// # use ethrex_rlp::stream::RLPStreamReader;
+/// // 1, "dog", [2, 3]
+/// let bytes: &[u8] = &[0x01, 0x83, b'd', b'o', b'g', 0xc2, 0x02, 0x03];
+/// let mut reader = RLPStreamReader::new(bytes);
+///
+/// assert_eq!(reader.decode_next::<u8>().unwrap(), Some(1));
+/// assert_eq!(reader.decode_next::<String>().unwrap(), Some("dog".to_string()));
+/// assert_eq!(reader.decode_next::<Vec<u8>>().unwrap(), Some(vec![2, 3]));
+/// assert_eq!(reader.decode_next::<u8>().unwrap(), None);
+/// ```
+#[derive(Debug)]
+pub struct RLPStreamReader<R> {
+    reader: R,
+    buf: Vec<u8>,
+    max_item_size: Option<usize>,
+}
+
+impl<R: Read> RLPStreamReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buf: Vec::new(),
+            max_item_size: None,
+        }
+    }
+
+    /// Sets the maximum size (prefix included) an item may have.
+    /// Bigger items are rejected before reading their payload, which protects against
+    /// allocating huge buffers when reading from untrusted sources.
+    pub fn with_max_item_size(mut self, max_item_size: usize) -> Self {
+        self.max_item_size = Some(max_item_size);
+        self
+    }
+
+    /// Reads the next item, including its prefix, without decoding it.
+    /// Returns `None` if the stream ended exactly at an item boundary.
+    pub fn next_item(&mut self) -> Result<Option<&[u8]>, RLPDecodeError> {
+        let mut prefix = [0; 1];
+        loop {
+            match self.reader.read(&mut prefix) {
+                Ok(0) => return Ok(None),
+                Ok(_) => break,
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into()),
+            }
+        }
+        let first_byte = prefix[0];
+        self.buf.clear();
+        self.buf.push(first_byte);
+
+        let payload_length = match first_byte {
+            0..=0x7F => 0,
+            0x80..=0xB7 => (first_byte - 0x80) as usize,
+            0xB8..=0xBF => self.read_length((first_byte - 0xB7) as usize)?,
+            RLP_EMPTY_LIST..=0xF7 => (first_byte - RLP_EMPTY_LIST) as usize,
+            0xF8..=0xFF => self.read_length((first_byte - 0xF7) as usize)?,
+        };
+
+        let header_length = self.buf.len();
+        let item_length = header_length
+            .checked_add(payload_length)
+            .ok_or(RLPDecodeError::InvalidLength)?;
+        if self
+            .max_item_size
+            .is_some_and(|max_item_size| item_length > max_item_size)
+        {
+            return Err(RLPDecodeError::InvalidLength);
+        }
+        self.buf.resize(item_length, 0);
+        self.reader.read_exact(&mut self.buf[header_length..])?;
+        Ok(Some(&self.buf))
+    }
+
+    /// Reads and decodes the next item.
+    /// Returns `None` if the stream ended exactly at an item boundary.
+    pub fn decode_next<T: RLPDecode>(&mut self) -> Result<Option<T>, RLPDecodeError> {
+        self.next_item()?.map(T::decode).transpose()
+    }
+
+    /// Turns the reader into an iterator over decoded items of type `T`
+    pub fn items<T: RLPDecode>(self) -> RLPStreamIter<R, T> {
+        RLPStreamIter {
+            reader: self,
+            done: false,
+            _item: PhantomData,
+        }
+    }
+
+    /// Reads the big-endian length that follows a long-form prefix and appends it to the buffer
+    fn read_length(&mut self, length_of_length: usize) -> Result<usize, RLPDecodeError> {
+        let mut length_bytes = [0; 8];
+        let length_bytes = length_bytes
+            .get_mut(..length_of_length)
+            .ok_or(RLPDecodeError::InvalidLength)?;
+        self.reader.read_exact(length_bytes)?;
+        self.buf.extend_from_slice(length_bytes);
+        Ok(usize::from_be_bytes(static_left_pad(length_bytes)?))
+    }
+}
+
+/// Iterator over the decoded items of a stream, see [`RLPStreamReader::items`].
+/// Iteration stops after the first error.
+#[derive(Debug)]
+pub struct RLPStreamIter<R, T> {
+    reader: RLPStreamReader<R>,
+    done: bool,
+    _item: PhantomData<T>,
+}
+
+impl<R: Read, T: RLPDecode> Iterator for RLPStreamIter<R, T> {
+    type Item = Result<T, RLPDecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let next = self.reader.decode_next().transpose();
+        if !matches!(next, Some(Ok(_))) {
+            self.done = true;
+        }
+        next
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encode::RLPEncode;
+
+    #[test]
+    fn test_stream_short_and_long_items() {
+        let items = vec![
+            "a".to_string(),
+            "".to_string(),
+            "b".repeat(55),
+            "c".repeat(56),
+            "d".repeat(1024),
+        ];
+        let mut buf = Vec::new();
+        for item in &items {
+            item.encode(&mut buf);
+        }
+        let decoded: Vec<String> = RLPStreamReader::new(buf.as_slice())
+            .items()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(decoded, items);
+    }
+
+    #[test]
+    fn test_stream_long_lists() {
+        let items = vec![vec![1u64; 3], vec![], vec![u64::MAX; 100]];
+        let mut buf = Vec::new();
+        for item in &items {
+            item.encode(&mut buf);
+        }
+        let mut reader = RLPStreamReader::new(buf.as_slice());
+        let mut decoded = Vec::new();
+        while let Some(item) = reader.decode_next::<Vec<u64>>().unwrap() {
+            decoded.push(item);
+        }
+        assert_eq!(decoded, items);
+    }
+
+    #[test]
+    fn test_stream_next_item_returns_raw_item() {
+        let buf = vec![0xc2, 0x01, 0x02, 0x7f];
+        let mut reader = RLPStreamReader::new(buf.as_slice());
+        assert_eq!(reader.next_item().unwrap(), Some(&buf[..3]));
+        assert_eq!(reader.next_item().unwrap(), Some(&buf[3..]));
+        assert_eq!(reader.next_item().unwrap(), None);
+    }
+
+    #[test]
+    fn test_stream_truncated_item() {
+        let buf = vec![0x83, b'd', b'o'];
+        let mut iter = RLPStreamReader::new(buf.as_slice()).items::<String>();
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_stream_max_item_size() {
+        let mut buf = Vec::new();
+        "a".repeat(100).encode(&mut buf);
+        let mut reader = RLPStreamReader::new(buf.as_slice()).with_max_item_size(64);
+        assert!(reader.next_item().is_err());
+    }
+}
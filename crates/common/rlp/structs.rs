@@ -1,5 +1,5 @@
 use super::{
-    decode::{decode_rlp_item, get_item_with_prefix, RLPDecode},
+    decode::{decode_rlp_item, get_item_with_prefix, RLPDecode, RLPDecodeRef},
     encode::{encode_length, RLPEncode},
     error::RLPDecodeError,
 };
@@ -65,6 +65,21 @@ impl<'a> Decoder<'a> {
         };
         Ok((field, updated_self))
     }
+
+    /// Same as [`decode_field`](Self::decode_field), but the decoded field borrows from the input
+    pub fn decode_field_ref<T: RLPDecodeRef<'a>>(
+        self,
+        name: &str,
+    ) -> Result<(T, Self), RLPDecodeError> {
+        let (field, rest) = <T as RLPDecodeRef<'a>>::decode_unfinished_ref(self.payload)
+            .map_err(|err| field_decode_error::<T>(name, err))?;
+        let updated_self = Self {
+            payload: rest,
+            ..self
+        };
+        Ok((field, updated_self))
+    }
+
     /// Returns the next field without decoding it, i.e. the payload bytes including its prefix.
     pub fn get_encoded_item(self) -> Result<(Vec<u8>, Self), RLPDecodeError> {
         match get_item_with_prefix(self.payload) {
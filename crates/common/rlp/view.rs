@@ -0,0 +1,215 @@
+use super::{
+    decode::{decode_rlp_item, get_item_with_prefix, RLPDecode, RLPDecodeRef},
+    error::RLPDecodeError,
+};
+
+/// # Lazy RLP view
+///
+/// A borrowed view over a single RLP item. Creating a view only parses the item's prefix,
+/// the payload is left untouched until it is requested, so nested items can be inspected
+/// (or skipped) without allocating or decoding them.
+///
+/// # Examples
+///
+/// ```
+/// # use ethrex_rlp::view::RlpView;
+/// // ["cat", "dog"]
+/// let bytes = [0xc8, 0x83, b'c', b'a', b't', 0x83, b'd', b'o', b'g'];
+/// let view = RlpView::new(&bytes).unwrap();
+///
+/// assert!(view.is_list());
+/// assert_eq!(view.item_count().unwrap(), 2);
+/// assert_eq!(view.at(1).unwrap().as_bytes().unwrap(), b"dog");
+/// assert_eq!(view.at(0).unwrap().decode::<String>().unwrap(), "cat");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RlpView<'a> {
+    raw: &'a [u8],
+    payload: &'a [u8],
+    is_list: bool,
+}
+
+impl<'a> RlpView<'a> {
+    /// Creates a view over `buf`, which must contain exactly one RLP item.
+    pub fn new(buf: &'a [u8]) -> Result<Self, RLPDecodeError> {
+        let (view, rest) = Self::new_unfinished(buf)?;
+        if !rest.is_empty() {
+            return Err(RLPDecodeError::InvalidLength);
+        }
+        Ok(view)
+    }
+
+    /// Creates a view over the first RLP item in `buf` and returns the remaining bytes after it.
+    pub fn new_unfinished(buf: &'a [u8]) -> Result<(Self, &'a [u8]), RLPDecodeError> {
+        let (raw, rest) = get_item_with_prefix(buf)?;
+        let (is_list, payload, _) = decode_rlp_item(raw)?;
+        Ok((
+            Self {
+                raw,
+                payload,
+                is_list,
+            },
+            rest,
+        ))
+    }
+
+    /// Returns true if the item is a list
+    pub fn is_list(&self) -> bool {
+        self.is_list
+    }
+
+    /// Returns the item's payload, without its prefix
+    pub fn payload(&self) -> &'a [u8] {
+        self.payload
+    }
+
+    /// Returns the whole encoded item, including its prefix
+    pub fn as_raw(&self) -> &'a [u8] {
+        self.raw
+    }
+
+    /// Returns the payload of a string item.
+    /// Fails if the item is a list.
+    pub fn as_bytes(&self) -> Result<&'a [u8], RLPDecodeError> {
+        if self.is_list {
+            return Err(RLPDecodeError::UnexpectedList);
+        }
+        Ok(self.payload)
+    }
+
+    /// Returns an iterator over the elements of a list item.
+    /// Fails if the item is not a list.
+    pub fn iter(&self) -> Result<RlpListIter<'a>, RLPDecodeError> {
+        if !self.is_list {
+            return Err(RLPDecodeError::UnexpectedString);
+        }
+        Ok(RlpListIter {
+            remaining: self.payload,
+        })
+    }
+
+    /// Returns the amount of elements in a list item, without decoding them
+    pub fn item_count(&self) -> Result<usize, RLPDecodeError> {
+        self.iter()?
+            .try_fold(0, |count, item| item.map(|_| count + 1))
+    }
+
+    /// Returns a view over the element at `index` of a list item
+    pub fn at(&self, index: usize) -> Result<RlpView<'a>, RLPDecodeError> {
+        self.iter()?
+            .nth(index)
+            .ok_or(RLPDecodeError::InvalidLength)?
+    }
+
+    /// Decodes the item into an owned value
+    pub fn decode<T: RLPDecode>(&self) -> Result<T, RLPDecodeError> {
+        T::decode(self.raw)
+    }
+
+    /// Decodes the item into a value borrowing from the underlying buffer
+    pub fn decode_ref<T: RLPDecodeRef<'a>>(&self) -> Result<T, RLPDecodeError> {
+        T::decode_ref(self.raw)
+    }
+}
+
+impl<'a> RLPDecodeRef<'a> for RlpView<'a> {
+    fn decode_unfinished_ref(rlp: &'a [u8]) -> Result<(Self, &'a [u8]), RLPDecodeError> {
+        Self::new_unfinished(rlp)
+    }
+}
+
+/// Iterator over the elements of a list item, see [`RlpView::iter`]
+#[derive(Debug, Clone)]
+pub struct RlpListIter<'a> {
+    remaining: &'a [u8],
+}
+
+impl<'a> Iterator for RlpListIter<'a> {
+    type Item = Result<RlpView<'a>, RLPDecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining.is_empty() {
+            return None;
+        }
+        match RlpView::new_unfinished(self.remaining) {
+            Ok((item, rest)) => {
+                self.remaining = rest;
+                Some(Ok(item))
+            }
+            Err(err) => {
+                // Stop iterating after a malformed item
+                self.remaining = &[];
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{constants::RLP_EMPTY_LIST, encode::RLPEncode};
+
+    #[test]
+    fn test_view_string() {
+        let rlp = vec![0x83, b'd', b'o', b'g'];
+        let view = RlpView::new(&rlp).unwrap();
+        assert!(!view.is_list());
+        assert_eq!(view.as_bytes().unwrap(), b"dog");
+        assert_eq!(view.as_raw(), rlp.as_slice());
+        assert!(view.iter().is_err());
+    }
+
+    #[test]
+    fn test_view_nested_list() {
+        // [[1, 2], "cat", []]
+        let rlp = vec![
+            0xc8,
+            RLP_EMPTY_LIST + 2,
+            0x01,
+            0x02,
+            0x83,
+            b'c',
+            b'a',
+            b't',
+            RLP_EMPTY_LIST,
+        ];
+        let view = RlpView::new(&rlp).unwrap();
+        assert_eq!(view.item_count().unwrap(), 3);
+        assert_eq!(view.at(0).unwrap().decode::<Vec<u8>>().unwrap(), vec![1, 2]);
+        assert_eq!(view.at(1).unwrap().decode_ref::<&str>().unwrap(), "cat");
+        assert_eq!(view.at(2).unwrap().item_count().unwrap(), 0);
+        assert!(view.at(3).is_err());
+    }
+
+    #[test]
+    fn test_view_matches_owned_decoding() {
+        let value = vec![
+            vec!["foo".to_string(), "bar".to_string()],
+            vec!["a".repeat(60)],
+        ];
+        let rlp = value.encode_to_vec();
+        let view = RlpView::new(&rlp).unwrap();
+        let strings: Vec<Vec<&str>> = view
+            .iter()
+            .unwrap()
+            .map(|list| {
+                list.unwrap()
+                    .iter()
+                    .unwrap()
+                    .map(|item| item.unwrap().decode_ref().unwrap())
+                    .collect()
+            })
+            .collect();
+        assert_eq!(strings, value);
+    }
+
+    #[test]
+    fn test_view_rejects_trailing_bytes() {
+        let rlp = vec![0x01, 0x02];
+        assert!(RlpView::new(&rlp).is_err());
+        let (view, rest) = RlpView::new_unfinished(&rlp).unwrap();
+        assert_eq!(view.as_bytes().unwrap(), &[0x01]);
+        assert_eq!(rest, &[0x02]);
+    }
+}
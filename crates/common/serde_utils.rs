@@ -254,6 +254,35 @@ pub mod bytes {
             serialize_vec_of_hex_encodables(value, serializer)
         }
     }
+
+    pub mod opt {
+        use serde::Serialize;
+
+        use super::*;
+
+        pub fn deserialize<'de, D>(d: D) -> Result<Option<Bytes>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            Option::<String>::deserialize(d)?
+                .map(|value| {
+                    hex::decode(value.trim_start_matches("0x"))
+                        .map(Bytes::from)
+                        .map_err(|e| D::Error::custom(e.to_string()))
+                })
+                .transpose()
+        }
+
+        pub fn serialize<S>(value: &Option<Bytes>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            Option::<String>::serialize(
+                &value.as_ref().map(|bytes| format!("0x{:x}", bytes)),
+                serializer,
+            )
+        }
+    }
 }
 
 /// Serializes to and deserializes from 0x prefixed hex string
@@ -78,6 +78,7 @@ pub struct ChainConfig {
     pub shanghai_time: Option<u64>,
     pub cancun_time: Option<u64>,
     pub prague_time: Option<u64>,
+    pub osaka_time: Option<u64>,
     pub verkle_time: Option<u64>,
 
     /// Amount of total difficulty reached by the network that triggers the consensus upgrade.
@@ -87,11 +88,14 @@ pub struct ChainConfig {
     pub terminal_total_difficulty_passed: bool,
 }
 
-#[derive(Debug, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
 pub enum Fork {
+    #[default]
     Paris = 0,
     Shanghai = 1,
     Cancun = 2,
+    /// Brings the EVM Object Format (EIP-7692)
+    Osaka = 3,
 }
 
 impl ChainConfig {
@@ -104,6 +108,10 @@ impl ChainConfig {
         self.cancun_time.is_some_and(|time| time <= block_timestamp)
     }
 
+    pub fn is_osaka_activated(&self, block_timestamp: u64) -> bool {
+        self.osaka_time.is_some_and(|time| time <= block_timestamp)
+    }
+
     pub fn is_istanbul_activated(&self, block_number: BlockNumber) -> bool {
         self.istanbul_block.is_some_and(|num| num <= block_number)
     }
@@ -113,7 +121,9 @@ impl ChainConfig {
     }
 
     pub fn get_fork(&self, block_timestamp: u64) -> Fork {
-        if self.is_cancun_activated(block_timestamp) {
+        if self.is_osaka_activated(block_timestamp) {
+            Fork::Osaka
+        } else if self.is_cancun_activated(block_timestamp) {
             Fork::Cancun
         } else if self.is_shanghai_activated(block_timestamp) {
             Fork::Shanghai
@@ -149,6 +159,7 @@ impl ChainConfig {
             self.shanghai_time,
             self.cancun_time,
             self.prague_time,
+            self.osaka_time,
             self.verkle_time,
         ];
 
@@ -86,6 +86,7 @@ impl Committer {
             let block_number_to_fetch = if last_committed_block == u64::MAX {
                 0
             } else {
+                ethrex_metrics::l2::set_last_committed_block(last_committed_block);
                 last_committed_block + 1
             };
 
@@ -145,6 +146,7 @@ impl Committer {
                     .await
                 {
                     Ok(commit_tx_hash) => {
+                        ethrex_metrics::l2::set_last_committed_block(block_to_commit.header.number);
                         info!("Sent commitment to block {head_block_hash:#x}, with transaction hash {commit_tx_hash:#x}");
                     }
                     Err(error) => {
@@ -85,6 +85,7 @@ impl Proposer {
             let current_block_number = store
                 .get_latest_block_number()?
                 .ok_or(ProposerError::StorageDataIsNone)?;
+            ethrex_metrics::l2::set_proposer_head(current_block_number);
             store
                 .get_canonical_block_hash(current_block_number)?
                 .ok_or(ProposerError::StorageDataIsNone)?
@@ -213,6 +213,7 @@ impl ProverServer {
         let last_verified_block = if last_verified_block == u64::MAX {
             0
         } else {
+            ethrex_metrics::l2::set_last_verified_block(last_verified_block);
             last_verified_block
         };
 
@@ -446,6 +447,7 @@ impl ProverServer {
             .await?;
 
         info!("Sent proof for block {block_number}, with transaction hash {verify_tx_hash:#x}");
+        ethrex_metrics::l2::set_last_proved_block(block_number);
 
         Ok(verify_tx_hash)
     }
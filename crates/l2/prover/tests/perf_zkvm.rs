@@ -2,7 +2,7 @@
 use std::path::Path;
 use tracing::info;
 
-use ethrex_blockchain::add_block;
+use ethrex_blockchain::{add_block, EvmConfig};
 use ethrex_prover_lib::prover::Prover;
 use ethrex_storage::{EngineType, Store};
 use ethrex_vm::execution_db::ExecutionDB;
@@ -29,7 +29,7 @@ async fn test_performance_zkvm() {
     info!("Number of blocks to insert: {}", blocks.len());
 
     for block in &blocks {
-        add_block(block, &store).unwrap();
+        add_block(block, &store, &EvmConfig::default()).unwrap();
     }
     let block_to_prove = blocks.last().unwrap();
 
@@ -16,7 +16,8 @@ fn main() {
     let mut state = EvmState::from(db.clone());
 
     // Validate the block pre-execution
-    validate_block(&block, &parent_block_header, &state).expect("invalid block");
+    let chain_config = state.chain_config().expect("failed to get chain config");
+    validate_block(&block, &parent_block_header, &chain_config).expect("invalid block");
 
     // Validate the initial state
     let (mut state_trie, mut storage_tries) = db
@@ -1,9 +1,9 @@
 #![allow(clippy::unwrap_used)]
 #![allow(clippy::expect_used)]
 
-use ethrex_blockchain::add_block;
+use ethrex_blockchain::{add_block, EvmConfig};
 use ethrex_core::types::{Block, Genesis};
-use ethrex_rlp::{decode::RLPDecode, encode::RLPEncode};
+use ethrex_rlp::{encode::RLPEncode, stream::RLPStreamReader};
 use ethrex_storage::{EngineType, Store};
 use ethrex_vm::execution_db::ExecutionDB;
 use tracing::info;
@@ -11,7 +11,7 @@ use zkvm_interface::io::ProgramInput;
 
 use std::{
     fs::File,
-    io::{BufReader, Read as _, Write},
+    io::{BufReader, Write},
     path::PathBuf,
 };
 
@@ -73,7 +73,7 @@ pub fn generate_program_input(
     let store = Store::new("memory", EngineType::InMemory)?;
     store.add_initial_state(genesis)?;
     for block in chain {
-        add_block(&block, &store)?;
+        add_block(&block, &store, &EvmConfig::default())?;
     }
 
     let parent_block_header = store
@@ -92,15 +92,9 @@ pub fn generate_program_input(
 
 // From cmd/ethrex/decode.rs
 fn _chain_file(file: File) -> Result<Vec<Block>, Box<dyn std::error::Error>> {
-    let mut chain_rlp_reader = BufReader::new(file);
-    let mut buf = vec![];
-    chain_rlp_reader.read_to_end(&mut buf)?;
-    let mut blocks = Vec::new();
-    while !buf.is_empty() {
-        let (item, rest) = Block::decode_unfinished(&buf)?;
-        blocks.push(item);
-        buf = rest.to_vec();
-    }
+    let blocks = RLPStreamReader::new(BufReader::new(file))
+        .items()
+        .collect::<Result<_, _>>()?;
     Ok(blocks)
 }
 
@@ -271,6 +271,15 @@ impl KademliaTable {
         }
     }
 
+    /// Returns the number of peers with an active connection
+    pub fn count_connected_peers(&self) -> usize {
+        self.buckets
+            .iter()
+            .flat_map(|bucket| bucket.peers.iter())
+            .filter(|peer| peer.channels.is_some())
+            .count()
+    }
+
     /// TODO: Randomly select peer
     pub fn get_peer(&self) -> Option<PeerData> {
         self.get_least_recently_pinged_peers(1).pop()
@@ -48,8 +48,12 @@ use tokio::{
     time::{sleep, Instant},
 };
 use tracing::{debug, error};
+
+/// Version of the eth protocol spoken with peers
+pub const SUPPORTED_ETH_VERSION: u8 = 68;
+
 const CAP_P2P: (Capability, u8) = (Capability::P2p, 5);
-const CAP_ETH: (Capability, u8) = (Capability::Eth, 68);
+const CAP_ETH: (Capability, u8) = (Capability::Eth, SUPPORTED_ETH_VERSION);
 const CAP_SNAP: (Capability, u8) = (Capability::Snap, 1);
 const SUPPORTED_CAPABILITIES: [(Capability, u8); 3] = [CAP_P2P, CAP_ETH, CAP_SNAP];
 const PERIODIC_TASKS_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15);
@@ -164,13 +168,26 @@ impl<S: AsyncWrite + AsyncRead + std::marker::Unpin> RLPxConnection<S> {
                     .await;
             };
             table.lock().await.set_channels(node_id, peer_channels);
-            if let Err(e) = self.handle_peer_conn(sender, receiver).await {
+            let capabilities = self.shared_capabilities();
+            ethrex_metrics::p2p::peer_connected(&capabilities);
+            let result = self.handle_peer_conn(sender, receiver).await;
+            ethrex_metrics::p2p::peer_disconnected(&capabilities);
+            if let Err(e) = result {
                 self.peer_conn_failed("Error during RLPx connection", e, table)
                     .await;
             }
         }
     }
 
+    /// Names of the capabilities advertised by the peer that are also supported by the node
+    fn shared_capabilities(&self) -> Vec<&'static str> {
+        self.capabilities
+            .iter()
+            .filter(|cap| SUPPORTED_CAPABILITIES.contains(cap))
+            .map(|(capability, _)| capability.name())
+            .collect()
+    }
+
     async fn peer_conn_failed(
         &mut self,
         error_text: &str,
@@ -596,6 +613,7 @@ impl<S: AsyncWrite + AsyncRead + std::marker::Unpin> RLPxConnection<S> {
             let mut frame_buffer = vec![];
             message.encode(&mut frame_buffer)?;
             frame::write(frame_buffer, state, &mut self.stream).await?;
+            ethrex_metrics::p2p::message_sent(&message.to_string());
             Ok(())
         } else {
             Err(RLPxError::InvalidState())
@@ -606,7 +624,9 @@ impl<S: AsyncWrite + AsyncRead + std::marker::Unpin> RLPxConnection<S> {
         if let RLPxConnectionState::Established(state) = &mut self.state {
             let frame_data = frame::read(state, &mut self.stream).await?;
             let (msg_id, msg_data): (u8, _) = RLPDecode::decode_unfinished(&frame_data)?;
-            Ok(rlpx::Message::decode(msg_id, msg_data)?)
+            let message = rlpx::Message::decode(msg_id, msg_data)?;
+            ethrex_metrics::p2p::message_received(&message.to_string());
+            Ok(message)
         } else {
             Err(RLPxError::InvalidState())
         }
@@ -22,16 +22,22 @@ pub(crate) enum Capability {
     Snap,
 }
 
-impl RLPEncode for Capability {
-    fn encode(&self, buf: &mut dyn BufMut) {
+impl Capability {
+    pub(crate) fn name(&self) -> &'static str {
         match self {
-            Self::P2p => "p2p".encode(buf),
-            Self::Eth => "eth".encode(buf),
-            Self::Snap => "snap".encode(buf),
+            Self::P2p => "p2p",
+            Self::Eth => "eth",
+            Self::Snap => "snap",
         }
     }
 }
 
+impl RLPEncode for Capability {
+    fn encode(&self, buf: &mut dyn BufMut) {
+        self.name().encode(buf)
+    }
+}
+
 impl RLPDecode for Capability {
     fn decode_unfinished(rlp: &[u8]) -> Result<(Self, &[u8]), RLPDecodeError> {
         let (cap_string, rest) = String::decode_unfinished(rlp)?;
@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use ethrex_blockchain::error::ChainError;
+use ethrex_blockchain::{error::ChainError, EvmConfig};
 use ethrex_core::{
     types::{Block, BlockHash, BlockHeader},
     H256,
@@ -19,11 +19,17 @@ pub struct SyncManager {
     #[allow(unused)]
     snap_mode: bool,
     peers: Arc<Mutex<KademliaTable>>,
+    /// How the downloaded blocks are executed
+    evm: EvmConfig,
 }
 
 impl SyncManager {
-    pub fn new(peers: Arc<Mutex<KademliaTable>>, snap_mode: bool) -> Self {
-        Self { snap_mode, peers }
+    pub fn new(peers: Arc<Mutex<KademliaTable>>, snap_mode: bool, evm: EvmConfig) -> Self {
+        Self {
+            snap_mode,
+            peers,
+            evm,
+        }
     }
 
     /// Starts a sync cycle, updating the state with all blocks between the current head and the sync head
@@ -69,6 +75,7 @@ impl SyncManager {
             all_block_headers,
             self.peers.clone(),
             store.clone(),
+            self.evm.clone(),
         ))
         .await
         {
@@ -94,6 +101,7 @@ impl SyncManager {
         Self {
             snap_mode: false,
             peers: dummy_peer_table,
+            evm: EvmConfig::default(),
         }
     }
 }
@@ -105,6 +113,7 @@ async fn download_and_run_blocks(
     mut block_headers: Vec<BlockHeader>,
     peers: Arc<Mutex<KademliaTable>>,
     store: Store,
+    evm: EvmConfig,
 ) -> Result<(), ChainError> {
     loop {
         let peer = peers.lock().await.get_peer_channels().await;
@@ -118,13 +127,15 @@ async fn download_and_run_blocks(
                 let header = block_headers.remove(0);
                 let hash = block_hashes.remove(0);
                 let number = header.number;
+                let timestamp = header.timestamp;
                 let block = Block::new(header, body);
-                if let Err(error) = ethrex_blockchain::add_block(&block, &store) {
+                if let Err(error) = ethrex_blockchain::add_block(&block, &store, &evm) {
                     warn!("Failed to add block during FullSync: {error}");
                     return Err(error);
                 }
                 store.set_canonical_block(number, hash)?;
                 store.update_latest_block_number(number)?;
+                ethrex_metrics::chain::set_head(number, timestamp);
             }
             debug!("Executed & stored {} blocks", block_bodies_len);
             // Check if we need to ask for another batch
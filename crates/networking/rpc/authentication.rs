@@ -4,30 +4,165 @@ use axum_extra::{
     TypedHeader,
 };
 use bytes::Bytes;
-use jsonwebtoken::{decode, Algorithm, DecodingKey, TokenData, Validation};
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
 use serde::{Deserialize, Serialize};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tracing::{debug, info, warn};
+
+/// How far the "iat" claim of a token may be from the local clock unless configured otherwise
+pub const DEFAULT_JWT_CLOCK_SKEW: Duration = Duration::from_secs(60);
+
+/// How often the secret file is checked for changes
+const SECRET_RELOAD_INTERVAL: Duration = Duration::from_secs(5);
 
-#[derive(Debug, Deserialize)]
+/// Id and client version claims the consensus client identifies itself with
+type ClientClaims = (Option<String>, Option<String>);
+
+#[derive(Debug, Clone, Deserialize)]
 pub enum AuthenticationError {
     InvalidIssuedAtClaim,
     TokenDecodingError,
     MissingAuthentication,
 }
 
-pub fn authenticate(
-    secret: &Bytes,
-    auth_header: Option<TypedHeader<Authorization<Bearer>>>,
-) -> Result<(), RpcErr> {
-    match auth_header {
-        Some(TypedHeader(auth_header)) => {
-            let token = auth_header.token();
-            validate_jwt_authentication(token, secret).map_err(RpcErr::AuthenticationError)
+/// Authentication of the engine API requests, which the consensus client signs with the shared
+/// secret
+#[derive(Debug, Clone)]
+pub struct JwtAuth {
+    secret: Arc<RwLock<Bytes>>,
+    clock_skew: Duration,
+    /// Whether the id and clv claims the consensus client sends are logged
+    log_claims: bool,
+    /// Id and clv claims of the last accepted token, so they are only logged when they change
+    last_claims: Arc<Mutex<Option<ClientClaims>>>,
+}
+
+impl Default for JwtAuth {
+    fn default() -> Self {
+        Self::new(Bytes::new(), DEFAULT_JWT_CLOCK_SKEW, false)
+    }
+}
+
+impl JwtAuth {
+    pub fn new(secret: Bytes, clock_skew: Duration, log_claims: bool) -> Self {
+        Self {
+            secret: Arc::new(RwLock::new(secret)),
+            clock_skew,
+            log_claims,
+            last_claims: Default::default(),
+        }
+    }
+
+    pub fn authenticate(
+        &self,
+        auth_header: Option<TypedHeader<Authorization<Bearer>>>,
+    ) -> Result<(), RpcErr> {
+        let result = match auth_header {
+            Some(TypedHeader(auth_header)) => {
+                let secret = self
+                    .secret
+                    .read()
+                    .map(|secret| secret.clone())
+                    .unwrap_or_default();
+                validate_jwt_authentication(auth_header.token(), &secret, self.clock_skew)
+            }
+            None => Err(AuthenticationError::MissingAuthentication),
+        };
+        match result {
+            Ok(claims) => {
+                self.log_claims(claims);
+                Ok(())
+            }
+            Err(error) => {
+                warn!("Rejected an authenticated RPC request: {error:?}");
+                Err(RpcErr::AuthenticationError(error))
+            }
+        }
+    }
+
+    fn log_claims(&self, claims: Claims) {
+        if !self.log_claims {
+            return;
+        }
+        let Ok(mut last_claims) = self.last_claims.lock() else {
+            return;
+        };
+        let claims = (claims.id, claims.clv);
+        if last_claims.as_ref() != Some(&claims) {
+            info!(
+                "Authenticated consensus client, id: {}, clv: {}",
+                claims.0.as_deref().unwrap_or("none"),
+                claims.1.as_deref().unwrap_or("none")
+            );
+            *last_claims = Some(claims);
         }
-        None => Err(RpcErr::AuthenticationError(
-            AuthenticationError::MissingAuthentication,
-        )),
     }
+
+    /// Reloads the secret whenever the file it was read from changes, so it can be rotated
+    /// without restarting the node
+    pub fn watch_secret_file(&self, path: PathBuf) {
+        let auth = self.clone();
+        tokio::task::spawn(async move {
+            let mut last_modified = modified_at(&path);
+            let mut interval = tokio::time::interval(SECRET_RELOAD_INTERVAL);
+            loop {
+                interval.tick().await;
+                auth.reload_secret_if_modified(&path, &mut last_modified);
+            }
+        });
+    }
+
+    fn reload_secret_if_modified(&self, path: &Path, last_modified: &mut Option<SystemTime>) {
+        let modified = modified_at(path);
+        if modified == *last_modified {
+            return;
+        }
+        *last_modified = modified;
+        // A secret that can't be read is most likely being written, the current one is kept
+        // until the file holds a valid one
+        match read_jwt_secret(path) {
+            Ok(secret) => match self.secret.write() {
+                Ok(mut current) => {
+                    if *current != secret {
+                        info!("Reloaded the JWT secret from {}", path.display());
+                        *current = secret;
+                    }
+                }
+                Err(error) => warn!("Failed to reload the JWT secret: {error}"),
+            },
+            Err(error) => {
+                warn!(
+                    "Failed to reload the JWT secret from {}: {error}",
+                    path.display()
+                )
+            }
+        }
+    }
+}
+
+fn modified_at(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Reads a hex encoded secret, optionally 0x prefixed, from the file
+pub fn read_jwt_secret(path: &Path) -> Result<Bytes, String> {
+    let contents = std::fs::read_to_string(path).map_err(|error| error.to_string())?;
+    let contents = contents.trim();
+    let secret = hex::decode(contents.strip_prefix("0x").unwrap_or(contents))
+        .map_err(|error| format!("the secret should be hex encoded, {error}"))?;
+    if secret.len() != 32 {
+        return Err(format!(
+            "the secret should be 32 bytes, not {}",
+            secret.len()
+        ));
+    }
+    Ok(secret.into())
 }
 
 // JWT claims struct
@@ -39,28 +174,109 @@ struct Claims {
 }
 
 /// Authenticates bearer jwt to check that authrpc calls are sent by the consensus layer
-pub fn validate_jwt_authentication(token: &str, secret: &Bytes) -> Result<(), AuthenticationError> {
+fn validate_jwt_authentication(
+    token: &str,
+    secret: &Bytes,
+    clock_skew: Duration,
+) -> Result<Claims, AuthenticationError> {
     let decoding_key = DecodingKey::from_secret(secret);
     let mut validation = Validation::new(Algorithm::HS256);
     validation.validate_exp = false;
     validation.set_required_spec_claims(&["iat"]);
     match decode::<Claims>(token, &decoding_key, &validation) {
         Ok(token_data) => {
-            if invalid_issued_at_claim(token_data) {
+            if invalid_issued_at_claim(&token_data.claims, clock_skew) {
                 Err(AuthenticationError::InvalidIssuedAtClaim)
             } else {
-                Ok(())
+                debug!(
+                    "Authenticated request with iat {}, id {:?} and clv {:?}",
+                    token_data.claims.iat, token_data.claims.id, token_data.claims.clv
+                );
+                Ok(token_data.claims)
             }
         }
         Err(_) => Err(AuthenticationError::TokenDecodingError),
     }
 }
 
-/// Checks that the "iat" timestamp in the claim is less than 60 seconds from now
-fn invalid_issued_at_claim(token_data: TokenData<Claims>) -> bool {
+/// Checks that the "iat" timestamp in the claim is within the allowed clock skew from now
+fn invalid_issued_at_claim(claims: &Claims, clock_skew: Duration) -> bool {
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap()
-        .as_secs() as usize;
-    (now as isize - token_data.claims.iat as isize).abs() > 60
+        .as_secs();
+    now.abs_diff(claims.iat as u64) > clock_skew.as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+
+    fn token(secret: &[u8], issued_ago: u64) -> TypedHeader<Authorization<Bearer>> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let claims = Claims {
+            iat: (now - issued_ago) as usize,
+            id: Some("lighthouse".to_owned()),
+            clv: Some("v6.0.0".to_owned()),
+        };
+        let token = encode(
+            &Header::new(Algorithm::HS256),
+            &claims,
+            &EncodingKey::from_secret(secret),
+        )
+        .unwrap();
+        TypedHeader(Authorization::bearer(&token).unwrap())
+    }
+
+    #[test]
+    fn issued_at_claim_is_checked_against_the_clock_skew() {
+        let secret = [0x11; 32];
+        let auth = JwtAuth::new(Bytes::from(secret.to_vec()), DEFAULT_JWT_CLOCK_SKEW, true);
+        assert!(auth.authenticate(Some(token(&secret, 10))).is_ok());
+        assert!(matches!(
+            auth.authenticate(Some(token(&secret, 120))),
+            Err(RpcErr::AuthenticationError(
+                AuthenticationError::InvalidIssuedAtClaim
+            ))
+        ));
+
+        let auth = JwtAuth::new(Bytes::from(secret.to_vec()), Duration::from_secs(300), true);
+        assert!(auth.authenticate(Some(token(&secret, 120))).is_ok());
+        assert!(matches!(
+            auth.authenticate(None),
+            Err(RpcErr::AuthenticationError(
+                AuthenticationError::MissingAuthentication
+            ))
+        ));
+    }
+
+    #[test]
+    fn secret_is_reloaded_when_its_file_changes() {
+        let path = std::env::temp_dir().join(format!("ethrex-jwt-{}.hex", std::process::id()));
+        let (old_secret, new_secret) = ([0x11; 32], [0x22; 32]);
+        std::fs::write(&path, hex::encode(old_secret)).unwrap();
+        let auth = JwtAuth::new(
+            read_jwt_secret(&path).unwrap(),
+            DEFAULT_JWT_CLOCK_SKEW,
+            false,
+        );
+        assert!(auth.authenticate(Some(token(&old_secret, 0))).is_ok());
+
+        let mut last_modified = None;
+        std::fs::write(&path, format!("0x{}\n", hex::encode(new_secret))).unwrap();
+        auth.reload_secret_if_modified(&path, &mut last_modified);
+        assert!(auth.authenticate(Some(token(&new_secret, 0))).is_ok());
+        assert!(auth.authenticate(Some(token(&old_secret, 0))).is_err());
+
+        // Invalid secrets are ignored
+        let mut last_modified = None;
+        std::fs::write(&path, "not a secret").unwrap();
+        auth.reload_secret_if_modified(&path, &mut last_modified);
+        assert!(auth.authenticate(Some(token(&new_secret, 0))).is_ok());
+        std::fs::remove_file(path).unwrap();
+    }
 }
@@ -0,0 +1,152 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use serde_json::Value;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::utils::RpcErr;
+
+/// Maximum number of requests running on the blocking pool at the same time unless
+/// configured otherwise
+pub const DEFAULT_BLOCKING_TASKS_LIMIT: usize = 64;
+
+/// Timeout and maximum number of concurrent requests of the methods limited by the pool
+const METHOD_LIMITS: [(&str, Duration, usize); 10] = [
+    ("eth_call", Duration::from_secs(5), 32),
+    ("eth_estimateGas", Duration::from_secs(10), 16),
+    ("eth_createAccessList", Duration::from_secs(10), 16),
+    ("eth_simulateV1", Duration::from_secs(10), 8),
+    ("eth_getLogs", Duration::from_secs(30), 8),
+    ("eth_getFilterChanges", Duration::from_secs(30), 8),
+    ("eth_getFilterLogs", Duration::from_secs(30), 8),
+    ("eth_getProof", Duration::from_secs(10), 16),
+    ("eth_feeHistory", Duration::from_secs(10), 16),
+    ("graphql", Duration::from_secs(30), 8),
+];
+
+#[derive(Debug)]
+struct MethodLimit {
+    timeout: Duration,
+    permits: Arc<Semaphore>,
+}
+
+/// Runs the CPU and DB heavy requests on tokio's blocking threads, so they don't stall the
+/// executor threads the engine API and p2p tasks run on.
+///
+/// Requests of limited methods wait for a permit of their method and one of the pool, and
+/// fail once their method's timeout is reached. The others, like the engine API ones, are
+/// never queued behind them.
+#[derive(Debug, Clone)]
+pub struct BlockingPool {
+    permits: Arc<Semaphore>,
+    methods: Arc<HashMap<&'static str, MethodLimit>>,
+}
+
+impl BlockingPool {
+    pub fn new(max_tasks: usize) -> Self {
+        Self::with_limits(max_tasks, METHOD_LIMITS)
+    }
+
+    fn with_limits(
+        max_tasks: usize,
+        limits: impl IntoIterator<Item = (&'static str, Duration, usize)>,
+    ) -> Self {
+        let methods = limits
+            .into_iter()
+            .map(|(method, timeout, max_requests)| {
+                let permits = Arc::new(Semaphore::new(max_requests));
+                (method, MethodLimit { timeout, permits })
+            })
+            .collect();
+        Self {
+            permits: Arc::new(Semaphore::new(max_tasks)),
+            methods: Arc::new(methods),
+        }
+    }
+
+    /// Runs the work of a request of the given method on a blocking thread
+    pub async fn run<F>(&self, method: &str, work: F) -> Result<Value, RpcErr>
+    where
+        F: FnOnce() -> Result<Value, RpcErr> + Send + 'static,
+    {
+        let Some(limit) = self.methods.get(method) else {
+            return spawn(work, Vec::new()).await;
+        };
+        let limited = async {
+            let method_permit = acquire(&limit.permits).await?;
+            let pool_permit = acquire(&self.permits).await?;
+            spawn(work, vec![method_permit, pool_permit]).await
+        };
+        tokio::time::timeout(limit.timeout, limited)
+            .await
+            .map_err(|_| RpcErr::Timeout(method.to_string()))?
+    }
+}
+
+async fn acquire(semaphore: &Arc<Semaphore>) -> Result<OwnedSemaphorePermit, RpcErr> {
+    semaphore
+        .clone()
+        .acquire_owned()
+        .await
+        .map_err(|error| RpcErr::Internal(error.to_string()))
+}
+
+/// Spawns the work, which keeps the permits until it's done even if its request times out
+async fn spawn<F>(work: F, permits: Vec<OwnedSemaphorePermit>) -> Result<Value, RpcErr>
+where
+    F: FnOnce() -> Result<Value, RpcErr> + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || {
+        let _permits = permits;
+        work()
+    })
+    .await
+    .map_err(|error| RpcErr::Internal(error.to_string()))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread::sleep;
+
+    const TIMEOUT: Duration = Duration::from_millis(200);
+
+    fn slow_work() -> Result<Value, RpcErr> {
+        sleep(TIMEOUT * 2);
+        Ok(Value::Bool(true))
+    }
+
+    #[tokio::test]
+    async fn limited_method_times_out() {
+        let pool = BlockingPool::with_limits(4, [("test_slow", TIMEOUT, 4)]);
+        let result = pool.run("test_slow", slow_work).await;
+        assert!(matches!(result, Err(RpcErr::Timeout(_))));
+        // Methods without limits are never timed out
+        let result = pool.run("test_unlimited", slow_work).await;
+        assert_eq!(result.unwrap(), Value::Bool(true));
+    }
+
+    #[tokio::test]
+    async fn limited_method_waits_for_running_requests() {
+        let pool = BlockingPool::with_limits(4, [("test_slow", TIMEOUT * 10, 1)]);
+        let first = pool.run("test_slow", slow_work);
+        let second = pool.run("test_slow", slow_work);
+        let start = std::time::Instant::now();
+        let (first, second) = tokio::join!(first, second);
+        assert!(first.is_ok() && second.is_ok());
+        // The second request only started once the first one was done
+        assert!(start.elapsed() >= TIMEOUT * 4);
+    }
+
+    #[tokio::test]
+    async fn timed_out_work_keeps_its_permit() {
+        let pool = BlockingPool::with_limits(1, [("test_slow", TIMEOUT, 1)]);
+        let slower_work = || {
+            sleep(TIMEOUT * 4);
+            Ok(Value::Null)
+        };
+        assert!(pool.run("test_slow", slower_work).await.is_err());
+        // The first request is still running, so this one can't get a permit in time
+        let result = pool.run("test_slow", || Ok(Value::Null)).await;
+        assert!(matches!(result, Err(RpcErr::Timeout(_))));
+    }
+}
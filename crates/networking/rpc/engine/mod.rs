@@ -7,6 +7,15 @@ use serde_json::{json, Value};
 
 pub type ExchangeCapabilitiesRequest = Vec<String>;
 
+/// Engine API methods served by the node, which are the capabilities it exchanges with the
+/// consensus client
+pub const CAPABILITIES: [&str; 4] = [
+    "engine_forkchoiceUpdatedV3",
+    "engine_getPayloadV3",
+    "engine_newPayloadV3",
+    "engine_exchangeTransitionConfigurationV1",
+];
+
 impl From<ExchangeCapabilitiesRequest> for RpcRequest {
     fn from(val: ExchangeCapabilitiesRequest) -> Self {
         RpcRequest {
@@ -30,7 +39,7 @@ impl RpcHandler for ExchangeCapabilitiesRequest {
             })
     }
 
-    fn handle(&self, _context: RpcApiContext) -> Result<Value, RpcErr> {
-        Ok(json!(*self))
+    async fn handle(&self, _context: RpcApiContext) -> Result<Value, RpcErr> {
+        Ok(json!(CAPABILITIES))
     }
 }
@@ -56,7 +56,7 @@ impl RpcHandler for NewPayloadV3Request {
         })
     }
 
-    fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
         let storage = &context.storage;
 
         let block_hash = self.payload.block_hash;
@@ -116,7 +116,7 @@ impl RpcHandler for NewPayloadV3Request {
 
         // Execute and store the block
         info!("Executing payload with block hash: {block_hash:#x}");
-        let payload_status = match add_block(&block, storage) {
+        let payload_status = match add_block(&block, storage, &context.evm) {
             Err(ChainError::ParentNotFound) => Ok(PayloadStatus::syncing()),
             // Under the current implementation this is not possible: we always calculate the state
             // transition of any new payload as long as the parent is present. If we received the
@@ -190,7 +190,7 @@ impl RpcHandler for GetPayloadV3Request {
         Ok(GetPayloadV3Request { payload_id })
     }
 
-    fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
         info!("Requested payload with id: {:#018x}", self.payload_id);
         let Some(mut payload) = context.storage.get_payload(self.payload_id)? else {
             return Err(RpcErr::UnknownPayload(format!(
@@ -198,8 +198,9 @@ impl RpcHandler for GetPayloadV3Request {
                 self.payload_id
             )));
         };
-        let (blobs_bundle, block_value) = build_payload(&mut payload, &context.storage)
-            .map_err(|err| RpcErr::Internal(err.to_string()))?;
+        let (blobs_bundle, block_value) =
+            build_payload(&mut payload, &context.storage, context.evm.engine)
+                .map_err(|err| RpcErr::Internal(err.to_string()))?;
         serde_json::to_value(ExecutionPayloadResponse {
             execution_payload: ExecutionPayloadV3::from_block(payload),
             block_value,
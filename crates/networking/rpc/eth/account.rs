@@ -48,13 +48,13 @@ impl RpcHandler for GetBalanceRequest {
             block: BlockIdentifierOrHash::parse(params[1].clone(), 1)?,
         })
     }
-    fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
         info!(
             "Requested balance of account {} at block {}",
             self.address, self.block
         );
 
-        let Some(block_number) = self.block.resolve_block_number(&context.storage)? else {
+        let Some(block_hash) = self.block.resolve_block_hash(&context.storage)? else {
             return Err(RpcErr::Internal(
                 "Could not resolve block number".to_owned(),
             )); // Should we return Null here?
@@ -62,7 +62,7 @@ impl RpcHandler for GetBalanceRequest {
 
         let account = context
             .storage
-            .get_account_info(block_number, self.address)?;
+            .get_account_info_by_hash(block_hash, self.address)?;
         let balance = account.map(|acc| acc.balance).unwrap_or_default();
 
         serde_json::to_value(format!("{:#x}", balance))
@@ -83,7 +83,7 @@ impl RpcHandler for GetCodeRequest {
             block: BlockIdentifierOrHash::parse(params[1].clone(), 1)?,
         })
     }
-    fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
         info!(
             "Requested code of account {} at block {}",
             self.address, self.block
@@ -119,7 +119,7 @@ impl RpcHandler for GetStorageAtRequest {
             block: BlockIdentifierOrHash::parse(params[2].clone(), 2)?,
         })
     }
-    fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
         info!(
             "Requested storage sot {} of account {} at block {}",
             self.storage_slot, self.address, self.block
@@ -154,32 +154,28 @@ impl RpcHandler for GetTransactionCountRequest {
             block: BlockIdentifierOrHash::parse(params[1].clone(), 1)?,
         })
     }
-    fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
         info!(
             "Requested nonce of account {} at block {}",
             self.address, self.block
         );
 
-        // If the tag is Pending, we need to get the nonce from the mempool
-        let pending_nonce = if self.block == BlockTag::Pending {
-            mempool::get_nonce(&self.address, &context.storage)?
-        } else {
-            None
+        let Some(block_hash) = self.block.resolve_block_hash(&context.storage)? else {
+            return serde_json::to_value("0x0")
+                .map_err(|error| RpcErr::Internal(error.to_string()));
         };
+        let nonce = context
+            .storage
+            .get_account_info_by_hash(block_hash, self.address)?
+            .map(|account| account.nonce)
+            .unwrap_or_default();
 
-        let nonce = match pending_nonce {
-            Some(nonce) => nonce,
-            None => {
-                let Some(block_number) = self.block.resolve_block_number(&context.storage)? else {
-                    return serde_json::to_value("0x0")
-                        .map_err(|error| RpcErr::Internal(error.to_string()));
-                };
-
-                context
-                    .storage
-                    .get_nonce_by_account_address(block_number, self.address)?
-                    .unwrap_or_default()
-            }
+        // Mempool transactions that didn't fit in the pending block still count for its nonce
+        let nonce = if self.block == BlockTag::Pending {
+            mempool::get_nonce(&self.address, &context.storage)?
+                .map_or(nonce, |pending_nonce| pending_nonce.max(nonce))
+        } else {
+            nonce
         };
 
         serde_json::to_value(format!("0x{:x}", nonce))
@@ -204,7 +200,7 @@ impl RpcHandler for GetProofRequest {
         })
     }
 
-    fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
         let storage = &context.storage;
         info!(
             "Requested proof for account {} at block {} with storage keys: {:?}",
@@ -40,6 +40,15 @@ pub struct GetBlockReceiptsRequest {
 }
 
 #[derive(Clone, Debug)]
+pub struct GetUncleCountRequest {
+    pub block: BlockIdentifierOrHash,
+}
+
+pub struct GetUncleByBlockAndIndexRequest {
+    pub block: BlockIdentifierOrHash,
+    pub uncle_index: usize,
+}
+
 pub struct GetRawHeaderRequest {
     pub block: BlockIdentifier,
 }
@@ -68,9 +77,25 @@ impl RpcHandler for GetBlockByNumberRequest {
             hydrated: serde_json::from_value(params[1].clone())?,
         })
     }
-    fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
         let storage = &context.storage;
         info!("Requested block with number: {}", self.block);
+        if let Some(pending_block) = self.block.resolve_pending_block(storage)? {
+            let hash = pending_block.hash();
+            // TODO (#307): Remove TotalDifficulty.
+            // The pending block adds no difficulty, so it has the one of its parent
+            let total_difficulty =
+                storage.get_block_total_difficulty(pending_block.header.parent_hash)?;
+            let block = RpcBlock::build(
+                pending_block.header,
+                pending_block.body,
+                hash,
+                self.hydrated,
+                total_difficulty.unwrap_or(U256::zero()),
+            );
+            return serde_json::to_value(&block)
+                .map_err(|error| RpcErr::Internal(error.to_string()));
+        }
         let block_number = match self.block.resolve_block_number(storage)? {
             Some(block_number) => block_number,
             _ => return Ok(Value::Null),
@@ -110,7 +135,7 @@ impl RpcHandler for GetBlockByHashRequest {
             hydrated: serde_json::from_value(params[1].clone())?,
         })
     }
-    fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
         let storage = &context.storage;
         info!("Requested block with hash: {:#x}", self.block);
         let block_number = match storage.get_block_number(self.block)? {
@@ -151,7 +176,7 @@ impl RpcHandler for GetBlockTransactionCountRequest {
         })
     }
 
-    fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
         info!(
             "Requested transaction count for block with number: {}",
             self.block
@@ -184,7 +209,7 @@ impl RpcHandler for GetBlockReceiptsRequest {
         })
     }
 
-    fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
         let storage = &context.storage;
         info!("Requested receipts for block with number: {}", self.block);
         let block_number = match self.block.resolve_block_number(storage)? {
@@ -204,6 +229,74 @@ impl RpcHandler for GetBlockReceiptsRequest {
     }
 }
 
+impl RpcHandler for GetUncleCountRequest {
+    fn parse(params: &Option<Vec<Value>>) -> Result<GetUncleCountRequest, RpcErr> {
+        let params = params
+            .as_ref()
+            .ok_or(RpcErr::BadParams("No params provided".to_owned()))?;
+        if params.len() != 1 {
+            return Err(RpcErr::BadParams("Expected 1 param".to_owned()));
+        };
+        Ok(GetUncleCountRequest {
+            block: BlockIdentifierOrHash::parse(params[0].clone(), 0)?,
+        })
+    }
+
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+        info!("Requested uncle count for block: {}", self.block);
+        let block_number = match self.block.resolve_block_number(&context.storage)? {
+            Some(block_number) => block_number,
+            _ => return Ok(Value::Null),
+        };
+        let block_body = match context.storage.get_block_body(block_number)? {
+            Some(block_body) => block_body,
+            _ => return Ok(Value::Null),
+        };
+        serde_json::to_value(format!("{:#x}", block_body.ommers.len()))
+            .map_err(|error| RpcErr::Internal(error.to_string()))
+    }
+}
+
+impl RpcHandler for GetUncleByBlockAndIndexRequest {
+    fn parse(params: &Option<Vec<Value>>) -> Result<GetUncleByBlockAndIndexRequest, RpcErr> {
+        let params = params
+            .as_ref()
+            .ok_or(RpcErr::BadParams("No params provided".to_owned()))?;
+        if params.len() != 2 {
+            return Err(RpcErr::BadParams("Expected 2 params".to_owned()));
+        };
+        let index_as_string: String = serde_json::from_value(params[1].clone())?;
+        Ok(GetUncleByBlockAndIndexRequest {
+            block: BlockIdentifierOrHash::parse(params[0].clone(), 0)?,
+            uncle_index: usize::from_str_radix(index_as_string.trim_start_matches("0x"), 16)
+                .map_err(|error| RpcErr::BadParams(error.to_string()))?,
+        })
+    }
+
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+        info!(
+            "Requested uncle at index: {} of block: {}",
+            self.uncle_index, self.block
+        );
+        let block_number = match self.block.resolve_block_number(&context.storage)? {
+            Some(block_number) => block_number,
+            _ => return Ok(Value::Null),
+        };
+        let block_body = match context.storage.get_block_body(block_number)? {
+            Some(block_body) => block_body,
+            _ => return Ok(Value::Null),
+        };
+        let uncle = match block_body.ommers.into_iter().nth(self.uncle_index) {
+            Some(uncle) => uncle,
+            _ => return Ok(Value::Null),
+        };
+        // Uncles are returned as blocks without transactions, as their bodies are not known
+        let hash = uncle.compute_block_hash();
+        let block = RpcBlock::build(uncle, BlockBody::empty(), hash, false, U256::zero());
+        serde_json::to_value(&block).map_err(|error| RpcErr::Internal(error.to_string()))
+    }
+}
+
 impl RpcHandler for GetRawHeaderRequest {
     fn parse(params: &Option<Vec<Value>>) -> Result<GetRawHeaderRequest, RpcErr> {
         let params = params
@@ -217,7 +310,7 @@ impl RpcHandler for GetRawHeaderRequest {
         })
     }
 
-    fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
         info!(
             "Requested raw header for block with identifier: {}",
             self.block
@@ -250,7 +343,7 @@ impl RpcHandler for GetRawBlockRequest {
         })
     }
 
-    fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
         info!("Requested raw block: {}", self.block);
         let block_number = match self.block.resolve_block_number(&context.storage)? {
             Some(block_number) => block_number,
@@ -283,7 +376,7 @@ impl RpcHandler for GetRawReceipts {
         })
     }
 
-    fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
         let storage = &context.storage;
         let block_number = match self.block.resolve_block_number(storage)? {
             Some(block_number) => block_number,
@@ -308,7 +401,7 @@ impl RpcHandler for BlockNumberRequest {
         Ok(Self {})
     }
 
-    fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
         info!("Requested latest block number");
         match context.storage.get_latest_block_number() {
             Ok(Some(block_number)) => serde_json::to_value(format!("{:#x}", block_number))
@@ -324,7 +417,7 @@ impl RpcHandler for GetBlobBaseFee {
         Ok(Self {})
     }
 
-    fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
         info!("Requested blob gas price");
         match context.storage.get_latest_block_number() {
             Ok(Some(block_number)) => {
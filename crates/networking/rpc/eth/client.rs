@@ -1,3 +1,5 @@
+use ethrex_core::Address;
+use ethrex_net::rlpx::connection::SUPPORTED_ETH_VERSION;
 use serde_json::Value;
 use tracing::info;
 
@@ -9,7 +11,7 @@ impl RpcHandler for ChainId {
         Ok(Self {})
     }
 
-    fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
         info!("Requested chain id");
         let chain_spec = context
             .storage
@@ -26,7 +28,68 @@ impl RpcHandler for Syncing {
         Ok(Self {})
     }
 
-    fn handle(&self, _context: RpcApiContext) -> Result<Value, RpcErr> {
+    async fn handle(&self, _context: RpcApiContext) -> Result<Value, RpcErr> {
         Ok(Value::Bool(false))
     }
 }
+
+pub struct Accounts;
+impl RpcHandler for Accounts {
+    fn parse(_params: &Option<Vec<Value>>) -> Result<Self, RpcErr> {
+        Ok(Self {})
+    }
+
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+        // The node only manages accounts when the local signer is enabled
+        let accounts = context
+            .signer
+            .map(|signer| signer.accounts())
+            .unwrap_or_default();
+        serde_json::to_value(accounts).map_err(|error| RpcErr::Internal(error.to_string()))
+    }
+}
+
+pub struct Coinbase;
+impl RpcHandler for Coinbase {
+    fn parse(_params: &Option<Vec<Value>>) -> Result<Self, RpcErr> {
+        Ok(Self {})
+    }
+
+    async fn handle(&self, _context: RpcApiContext) -> Result<Value, RpcErr> {
+        // Since the merge the fee recipient is set by the consensus client on each payload
+        serde_json::to_value(Address::zero()).map_err(|error| RpcErr::Internal(error.to_string()))
+    }
+}
+
+pub struct Mining;
+impl RpcHandler for Mining {
+    fn parse(_params: &Option<Vec<Value>>) -> Result<Self, RpcErr> {
+        Ok(Self {})
+    }
+
+    async fn handle(&self, _context: RpcApiContext) -> Result<Value, RpcErr> {
+        Ok(Value::Bool(false))
+    }
+}
+
+pub struct Hashrate;
+impl RpcHandler for Hashrate {
+    fn parse(_params: &Option<Vec<Value>>) -> Result<Self, RpcErr> {
+        Ok(Self {})
+    }
+
+    async fn handle(&self, _context: RpcApiContext) -> Result<Value, RpcErr> {
+        Ok(Value::String("0x0".to_owned()))
+    }
+}
+
+pub struct ProtocolVersion;
+impl RpcHandler for ProtocolVersion {
+    fn parse(_params: &Option<Vec<Value>>) -> Result<Self, RpcErr> {
+        Ok(Self {})
+    }
+
+    async fn handle(&self, _context: RpcApiContext) -> Result<Value, RpcErr> {
+        Ok(Value::String(format!("{:#x}", SUPPORTED_ETH_VERSION)))
+    }
+}
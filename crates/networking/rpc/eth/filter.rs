@@ -2,11 +2,11 @@
 // - Manually testing the behaviour deploying contracts on the Sepolia test network.
 // - Go-Ethereum, specifically: https://github.com/ethereum/go-ethereum/blob/368e16f39d6c7e5cce72a92ec289adbfbaed4854/eth/filters/filter.go
 // - Ethereum's reference: https://ethereum.org/en/developers/docs/apis/json-rpc/#eth_newfilter
-use ethrex_core::types::BlockNumber;
+use ethrex_core::{types::BlockNumber, H256};
 use ethrex_storage::Store;
 use std::{
-    collections::HashMap,
-    sync::{Arc, Mutex},
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex, MutexGuard},
     time::{Duration, Instant},
 };
 use tracing::error;
@@ -32,12 +32,7 @@ pub struct NewFilterRequest {
 /// - filter_duration: represents how many *seconds* filter can last,
 ///   if any filter is older than this, it will be removed.
 pub fn clean_outdated_filters(filters: ActiveFilters, filter_duration: Duration) {
-    let mut active_filters_guard = filters.lock().unwrap_or_else(|mut poisoned_guard| {
-        error!("THREAD CRASHED WITH MUTEX TAKEN; SYSTEM MIGHT BE UNSTABLE");
-        **poisoned_guard.get_mut() = HashMap::new();
-        filters.clear_poison();
-        poisoned_guard.into_inner()
-    });
+    let mut active_filters_guard = lock_filters(&filters);
 
     // Keep only filters that have not expired.
     active_filters_guard
@@ -47,14 +42,65 @@ pub fn clean_outdated_filters(filters: ActiveFilters, filter_duration: Duration)
 pub type ActiveFilters = Arc<Mutex<HashMap<u64, (Instant, PollableFilter)>>>;
 
 #[derive(Debug, Clone)]
-pub struct PollableFilter {
-    /// Last block number from when this
-    /// filter was requested or created.
-    /// i.e. if this filter is requested,
-    /// the log will be applied from this
-    /// block number up to the latest one.
-    pub last_block_number: BlockNumber,
-    pub filter_data: LogsFilter,
+pub enum PollableFilter {
+    /// Polls the logs matching the filter
+    Logs {
+        /// Last block number from when this
+        /// filter was requested or created.
+        /// i.e. if this filter is requested,
+        /// the log will be applied from this
+        /// block number up to the latest one.
+        last_block_number: BlockNumber,
+        filter_data: LogsFilter,
+    },
+    /// Polls the hashes of the new canonical blocks
+    Blocks {
+        /// Latest block when this filter was requested or created
+        last_block_number: BlockNumber,
+    },
+    /// Polls the hashes of the transactions added to the mempool
+    PendingTransactions {
+        /// Transactions in the mempool when this filter was requested or created
+        known_transactions: HashSet<H256>,
+    },
+}
+
+/// Locks the active filters, clearing them if a thread panicked while holding the lock
+fn lock_filters(
+    filters: &ActiveFilters,
+) -> MutexGuard<'_, HashMap<u64, (Instant, PollableFilter)>> {
+    filters.lock().unwrap_or_else(|mut poisoned_guard| {
+        error!("THREAD CRASHED WITH MUTEX TAKEN; SYSTEM MIGHT BE UNSTABLE");
+        **poisoned_guard.get_mut() = HashMap::new();
+        filters.clear_poison();
+        poisoned_guard.into_inner()
+    })
+}
+
+/// Adds the filter to the active ones, returning its hex encoded id
+fn install_filter(filters: &ActiveFilters, filter: PollableFilter) -> Value {
+    let id: u64 = random();
+    let timestamp = Instant::now();
+    lock_filters(filters).insert(id, (timestamp, filter));
+    json!(format!("0x{:x}", id))
+}
+
+fn latest_block_number(storage: &Store) -> Result<BlockNumber, RpcErr> {
+    storage.get_latest_block_number()?.ok_or_else(|| {
+        error!("Latest block number was requested but it does not exist");
+        RpcErr::Internal("Failed to create filter".to_string())
+    })
+}
+
+/// Parses the params of the requests taking only a filter id
+fn parse_filter_id(params: &Option<Vec<Value>>) -> Result<u64, RpcErr> {
+    match params.as_deref() {
+        Some([param]) => parse_json_hex(param).map_err(|_err| RpcErr::BadHexFormat(0)),
+        Some(_) => Err(RpcErr::BadParams(
+            "Expected an array with a single hex encoded id".to_string(),
+        )),
+        None => Err(RpcErr::MissingParam("0".to_string())),
+    }
 }
 
 impl NewFilterRequest {
@@ -85,30 +131,12 @@ impl NewFilterRequest {
             return Err(RpcErr::BadParams("Invalid block range".to_string()));
         }
 
-        let Some(last_block_number) = storage.get_latest_block_number()? else {
-            error!("Latest block number was requested but it does not exist");
-            return Err(RpcErr::Internal("Failed to create filter".to_string()));
+        let last_block_number = latest_block_number(&storage)?;
+        let filter = PollableFilter::Logs {
+            last_block_number,
+            filter_data: self.request_data.clone(),
         };
-        let id: u64 = random();
-        let timestamp = Instant::now();
-        let mut active_filters_guard = filters.lock().unwrap_or_else(|mut poisoned_guard| {
-            error!("THREAD CRASHED WITH MUTEX TAKEN; SYSTEM MIGHT BE UNSTABLE");
-            **poisoned_guard.get_mut() = HashMap::new();
-            filters.clear_poison();
-            poisoned_guard.into_inner()
-        });
-        active_filters_guard.insert(
-            id,
-            (
-                timestamp,
-                PollableFilter {
-                    last_block_number,
-                    filter_data: self.request_data.clone(),
-                },
-            ),
-        );
-        let as_hex = json!(format!("0x{:x}", id));
-        Ok(as_hex)
+        Ok(install_filter(&filters, filter))
     }
 
     pub fn stateful_call(
@@ -121,22 +149,44 @@ impl NewFilterRequest {
     }
 }
 
+pub struct NewBlockFilterRequest;
+
+impl NewBlockFilterRequest {
+    pub fn stateful_call(
+        _req: &RpcRequest,
+        storage: Store,
+        filters: ActiveFilters,
+    ) -> Result<Value, RpcErr> {
+        let filter = PollableFilter::Blocks {
+            last_block_number: latest_block_number(&storage)?,
+        };
+        Ok(install_filter(&filters, filter))
+    }
+}
+
+pub struct NewPendingTransactionFilterRequest;
+
+impl NewPendingTransactionFilterRequest {
+    pub fn stateful_call(
+        _req: &RpcRequest,
+        storage: Store,
+        filters: ActiveFilters,
+    ) -> Result<Value, RpcErr> {
+        let filter = PollableFilter::PendingTransactions {
+            known_transactions: storage.get_pool_transaction_hashes()?,
+        };
+        Ok(install_filter(&filters, filter))
+    }
+}
+
 pub struct DeleteFilterRequest {
     pub id: u64,
 }
 
 impl DeleteFilterRequest {
     pub fn parse(params: &Option<Vec<serde_json::Value>>) -> Result<Self, RpcErr> {
-        match params.as_deref() {
-            Some([param]) => {
-                let id = parse_json_hex(param).map_err(|_err| RpcErr::BadHexFormat(0))?;
-                Ok(DeleteFilterRequest { id })
-            }
-            Some(_) => Err(RpcErr::BadParams(
-                "Expected an array with a single hex encoded id".to_string(),
-            )),
-            None => Err(RpcErr::MissingParam("0".to_string())),
-        }
+        let id = parse_filter_id(params)?;
+        Ok(DeleteFilterRequest { id })
     }
 
     pub fn handle(
@@ -144,13 +194,7 @@ impl DeleteFilterRequest {
         _storage: ethrex_storage::Store,
         filters: ActiveFilters,
     ) -> Result<serde_json::Value, crate::utils::RpcErr> {
-        let mut active_filters_guard = filters.lock().unwrap_or_else(|mut poisoned_guard| {
-            error!("THREAD CRASHED WITH MUTEX TAKEN; SYSTEM MIGHT BE UNSTABLE");
-            **poisoned_guard.get_mut() = HashMap::new();
-            filters.clear_poison();
-            poisoned_guard.into_inner()
-        });
-        match active_filters_guard.remove(&self.id) {
+        match lock_filters(&filters).remove(&self.id) {
             Some(_) => Ok(true.into()),
             None => Ok(false.into()),
         }
@@ -172,81 +216,110 @@ pub struct FilterChangesRequest {
 
 impl FilterChangesRequest {
     pub fn parse(params: &Option<Vec<serde_json::Value>>) -> Result<Self, RpcErr> {
-        match params.as_deref() {
-            Some([param]) => {
-                let id = parse_json_hex(param).map_err(|_err| RpcErr::BadHexFormat(0))?;
-                Ok(FilterChangesRequest { id })
-            }
-            Some(_) => Err(RpcErr::BadParams(
-                "Expected an array with a single hex encoded id".to_string(),
-            )),
-            None => Err(RpcErr::MissingParam("0".to_string())),
-        }
+        let id = parse_filter_id(params)?;
+        Ok(FilterChangesRequest { id })
     }
     pub fn handle(
         &self,
         storage: ethrex_storage::Store,
         filters: ActiveFilters,
     ) -> Result<serde_json::Value, crate::utils::RpcErr> {
-        let Some(latest_block_num) = storage.get_latest_block_number()? else {
-            error!("Latest block number was requested but it does not exist");
-            return Err(RpcErr::Internal("Failed to create filter".to_string()));
+        let latest_block_num = latest_block_number(&storage)?;
+        let mut active_filters_guard = lock_filters(&filters);
+        let Some((timestamp, filter)) = active_filters_guard.get_mut(&self.id) else {
+            return Err(RpcErr::BadParams(
+                "No matching filter for given id".to_string(),
+            ));
         };
-        let mut active_filters_guard = filters.lock().unwrap_or_else(|mut poisoned_guard| {
-            error!("THREAD CRASHED WITH MUTEX TAKEN; SYSTEM MIGHT BE UNSTABLE");
-            **poisoned_guard.get_mut() = HashMap::new();
-            filters.clear_poison();
-            poisoned_guard.into_inner()
-        });
-        if let Some((timestamp, filter)) = active_filters_guard.get_mut(&self.id) {
-            // We'll only get changes for a filter that either has a block
-            // range for upcoming blocks, or for the 'latest' tag.
-            let valid_block_range = match filter.filter_data.to_block {
-                BlockIdentifier::Tag(BlockTag::Latest) => true,
-                BlockIdentifier::Number(block_num) if block_num >= latest_block_num => true,
-                _ => false,
-            };
-            // This filter has a valid block range, so here's what we'll do:
-            // - Update the filter's timestamp and block number from the last poll.
-            // - Do the query to fetch logs in range last_block_number..=to_block for
-            //   this filter.
-            if valid_block_range {
-                // Since the filter was polled, updated its timestamp, so
-                // it does not expire.
-                *timestamp = Instant::now();
-                // Update this filter so the current query
-                // starts from the last polled block.
-                filter.filter_data.from_block = BlockIdentifier::Number(filter.last_block_number);
-                filter.last_block_number = latest_block_num;
-                let mut filter = filter.clone();
-                filter.filter_data.to_block = BlockIdentifier::Number(latest_block_num);
+        // Since the filter was polled, updated its timestamp, so
+        // it does not expire.
+        *timestamp = Instant::now();
+        match filter {
+            PollableFilter::Logs {
+                last_block_number,
+                filter_data,
+            } => {
+                // We'll only get changes for a filter that either has a block
+                // range for upcoming blocks, or for the 'latest' tag.
+                let valid_block_range = match filter_data.to_block {
+                    BlockIdentifier::Tag(BlockTag::Latest) => true,
+                    BlockIdentifier::Number(block_num) if block_num >= latest_block_num => true,
+                    _ => false,
+                };
+                if !valid_block_range {
+                    return Ok(json!([]));
+                }
+                // This filter has a valid block range, so here's what we'll do:
+                // - Update the filter's block number from the last poll.
+                // - Do the query to fetch logs in range last_block_number..=latest for
+                //   this filter, keeping its own range for eth_getFilterLogs.
+                let mut filter_data = filter_data.clone();
+                filter_data.from_block = BlockIdentifier::Number(*last_block_number);
+                filter_data.to_block = BlockIdentifier::Number(latest_block_num);
+                *last_block_number = latest_block_num;
                 // Drop the lock early to process this filter's query
                 // and not keep the lock more than we should.
                 drop(active_filters_guard);
-                let logs = fetch_logs_with_filter(&filter.filter_data, storage)?;
+                let logs = fetch_logs_with_filter(&filter_data, storage)?;
                 serde_json::to_value(logs).map_err(|error| {
                     tracing::error!("Log filtering request failed with: {error}");
                     RpcErr::Internal("Failed to filter logs".to_string())
                 })
-            } else {
-                serde_json::to_value(Vec::<u8>::new()).map_err(|error| {
-                    tracing::error!("Log filtering request failed with: {error}");
-                    RpcErr::Internal("Failed to filter logs".to_string())
-                })
             }
-        } else {
-            Err(RpcErr::BadParams(
-                "No matching filter for given id".to_string(),
-            ))
+            PollableFilter::Blocks { last_block_number } => {
+                let from = *last_block_number + 1;
+                *last_block_number = latest_block_num.max(*last_block_number);
+                drop(active_filters_guard);
+                let mut block_hashes = Vec::new();
+                for number in from..=latest_block_num {
+                    if let Some(hash) = storage.get_canonical_block_hash(number)? {
+                        block_hashes.push(hash);
+                    }
+                }
+                Ok(json!(block_hashes))
+            }
+            PollableFilter::PendingTransactions { known_transactions } => {
+                let pool_transactions = storage.get_pool_transaction_hashes()?;
+                let new_transactions: Vec<H256> = pool_transactions
+                    .difference(known_transactions)
+                    .copied()
+                    .collect();
+                *known_transactions = pool_transactions;
+                Ok(json!(new_transactions))
+            }
         }
     }
-    pub fn stateful_call(
-        req: &RpcRequest,
-        storage: ethrex_storage::Store,
-        filters: ActiveFilters,
-    ) -> Result<serde_json::Value, crate::utils::RpcErr> {
-        let request = Self::parse(&req.params)?;
-        request.handle(storage, filters)
+}
+
+pub struct FilterLogsRequest {
+    pub id: u64,
+}
+
+impl FilterLogsRequest {
+    pub fn parse(params: &Option<Vec<serde_json::Value>>) -> Result<Self, RpcErr> {
+        let id = parse_filter_id(params)?;
+        Ok(FilterLogsRequest { id })
+    }
+
+    /// Returns every log matching a log filter, unlike polling it only returns the new ones
+    pub fn handle(&self, storage: Store, filters: ActiveFilters) -> Result<Value, RpcErr> {
+        let filter_data = {
+            let mut active_filters_guard = lock_filters(&filters);
+            match active_filters_guard.get_mut(&self.id) {
+                Some((timestamp, PollableFilter::Logs { filter_data, .. })) => {
+                    *timestamp = Instant::now();
+                    filter_data.clone()
+                }
+                // Only log filters have logs
+                _ => {
+                    return Err(RpcErr::BadParams(
+                        "No matching filter for given id".to_string(),
+                    ))
+                }
+            }
+        };
+        let logs = fetch_logs_with_filter(&filter_data, storage)?;
+        serde_json::to_value(logs).map_err(|error| RpcErr::Internal(error.to_string()))
     }
 }
 
@@ -261,27 +334,38 @@ mod tests {
 
     use super::ActiveFilters;
     use crate::{
+        blocking_pool::{BlockingPool, DEFAULT_BLOCKING_TASKS_LIMIT},
         eth::{
             filter::PollableFilter,
             logs::{AddressFilter, LogsFilter, TopicFilter},
         },
         map_http_requests,
         utils::test_utils::{self, start_test_api},
-        RpcApiContext, FILTER_DURATION,
+        RpcApiContext, DEFAULT_BATCH_REQUEST_LIMIT, FILTER_DURATION,
     };
     use crate::{
         types::block_identifier::BlockIdentifier,
-        utils::{test_utils::example_p2p_node, RpcRequest},
+        utils::{
+            test_utils::{example_p2p_node, example_peer_table},
+            RpcErr, RpcRequest,
+        },
+    };
+    use ethrex_blockchain::EvmConfig;
+    use ethrex_core::{
+        types::{
+            Block, BlockBody, BlockHeader, EIP1559Transaction, Genesis, MempoolTransaction,
+            Transaction,
+        },
+        Address,
     };
-    use ethrex_core::types::Genesis;
     use ethrex_net::sync::SyncManager;
     use ethrex_storage::{EngineType, Store};
 
     use serde_json::{json, Value};
     use test_utils::TEST_GENESIS;
 
-    #[test]
-    fn filter_request_smoke_test_valid_params() {
+    #[tokio::test]
+    async fn filter_request_smoke_test_valid_params() {
         let filter_req_params = json!(
                 {
                     "fromBlock": "0x1",
@@ -301,27 +385,21 @@ mod tests {
                 ,"id":1
         });
         let filters = Arc::new(Mutex::new(HashMap::new()));
-        let id = run_new_filter_request_test(raw_json.clone(), filters.clone());
+        let id = run_new_filter_request_test(raw_json.clone(), filters.clone()).await;
         let filters = filters.lock().unwrap();
         assert!(filters.len() == 1);
-        let (_, filter) = filters.clone().get(&id).unwrap().clone();
-        assert!(matches!(
-            filter.filter_data.from_block,
-            BlockIdentifier::Number(1)
-        ));
-        assert!(matches!(
-            filter.filter_data.to_block,
-            BlockIdentifier::Number(2)
-        ));
-        assert!(filter.filter_data.address_filters.is_none());
-        assert!(matches!(
-            &filter.filter_data.topics[..],
-            [TopicFilter::Topic(_)]
-        ));
+        let (_, PollableFilter::Logs { filter_data, .. }) = filters.get(&id).unwrap().clone()
+        else {
+            panic!("Expected a log filter");
+        };
+        assert!(matches!(filter_data.from_block, BlockIdentifier::Number(1)));
+        assert!(matches!(filter_data.to_block, BlockIdentifier::Number(2)));
+        assert!(filter_data.address_filters.is_none());
+        assert!(matches!(&filter_data.topics[..], [TopicFilter::Topic(_)]));
     }
 
-    #[test]
-    fn filter_request_smoke_test_valid_null_topics_null_addr() {
+    #[tokio::test]
+    async fn filter_request_smoke_test_valid_null_topics_null_addr() {
         let raw_json = json!(
         {
             "jsonrpc":"2.0",
@@ -338,24 +416,21 @@ mod tests {
                 ,"id":1
         });
         let filters = Arc::new(Mutex::new(HashMap::new()));
-        let id = run_new_filter_request_test(raw_json.clone(), filters.clone());
+        let id = run_new_filter_request_test(raw_json.clone(), filters.clone()).await;
         let filters = filters.lock().unwrap();
         assert!(filters.len() == 1);
-        let (_, filter) = filters.clone().get(&id).unwrap().clone();
-        assert!(matches!(
-            filter.filter_data.from_block,
-            BlockIdentifier::Number(1)
-        ));
-        assert!(matches!(
-            filter.filter_data.to_block,
-            BlockIdentifier::Number(255)
-        ));
-        assert!(filter.filter_data.address_filters.is_none());
-        assert!(matches!(&filter.filter_data.topics[..], []));
+        let (_, PollableFilter::Logs { filter_data, .. }) = filters.get(&id).unwrap().clone()
+        else {
+            panic!("Expected a log filter");
+        };
+        assert!(matches!(filter_data.from_block, BlockIdentifier::Number(1)));
+        assert!(matches!(filter_data.to_block, BlockIdentifier::Number(255)));
+        assert!(filter_data.address_filters.is_none());
+        assert!(matches!(&filter_data.topics[..], []));
     }
 
-    #[test]
-    fn filter_request_smoke_test_valid_addr_topic_null() {
+    #[tokio::test]
+    async fn filter_request_smoke_test_valid_addr_topic_null() {
         let raw_json = json!(
         {
             "jsonrpc":"2.0",
@@ -372,28 +447,25 @@ mod tests {
                 ,"id":1
         });
         let filters = Arc::new(Mutex::new(HashMap::new()));
-        let id = run_new_filter_request_test(raw_json.clone(), filters.clone());
+        let id = run_new_filter_request_test(raw_json.clone(), filters.clone()).await;
         let filters = filters.lock().unwrap();
         assert!(filters.len() == 1);
-        let (_, filter) = filters.clone().get(&id).unwrap().clone();
-        assert!(matches!(
-            filter.filter_data.from_block,
-            BlockIdentifier::Number(1)
-        ));
-        assert!(matches!(
-            filter.filter_data.to_block,
-            BlockIdentifier::Number(255)
-        ));
+        let (_, PollableFilter::Logs { filter_data, .. }) = filters.get(&id).unwrap().clone()
+        else {
+            panic!("Expected a log filter");
+        };
+        assert!(matches!(filter_data.from_block, BlockIdentifier::Number(1)));
+        assert!(matches!(filter_data.to_block, BlockIdentifier::Number(255)));
         assert!(matches!(
-            filter.filter_data.address_filters.unwrap(),
+            filter_data.address_filters.unwrap(),
             AddressFilter::Many(_)
         ));
-        assert!(matches!(&filter.filter_data.topics[..], []));
+        assert!(matches!(&filter_data.topics[..], []));
     }
 
-    #[test]
+    #[tokio::test]
     #[should_panic]
-    fn filter_request_smoke_test_invalid_block_range() {
+    async fn filter_request_smoke_test_invalid_block_range() {
         let raw_json = json!(
         {
             "jsonrpc":"2.0",
@@ -409,12 +481,12 @@ mod tests {
             ]
                 ,"id":1
         });
-        run_new_filter_request_test(raw_json.clone(), Default::default());
+        run_new_filter_request_test(raw_json.clone(), Default::default()).await;
     }
 
-    #[test]
+    #[tokio::test]
     #[should_panic]
-    fn filter_request_smoke_test_from_block_missing() {
+    async fn filter_request_smoke_test_from_block_missing() {
         let raw_json = json!(
         {
             "jsonrpc":"2.0",
@@ -431,20 +503,26 @@ mod tests {
                 ,"id":1
         });
         let filters = Arc::new(Mutex::new(HashMap::new()));
-        run_new_filter_request_test(raw_json.clone(), filters.clone());
+        run_new_filter_request_test(raw_json.clone(), filters.clone()).await;
     }
 
-    fn run_new_filter_request_test(
+    async fn run_new_filter_request_test(
         json_req: serde_json::Value,
         filters_pointer: ActiveFilters,
     ) -> u64 {
         let context = RpcApiContext {
             storage: Store::new("in-mem", EngineType::InMemory)
                 .expect("Fatal: could not create in memory test db"),
-            jwt_secret: Default::default(),
+            jwt_auth: Default::default(),
             local_p2p_node: example_p2p_node(),
             active_filters: filters_pointer.clone(),
             syncer: Arc::new(TokioMutex::new(SyncManager::dummy())),
+            peer_table: example_peer_table(),
+            evm: EvmConfig::default(),
+            batch_request_limit: DEFAULT_BATCH_REQUEST_LIMIT,
+            blocking_pool: BlockingPool::new(DEFAULT_BLOCKING_TASKS_LIMIT),
+            signer: None,
+            allow_insecure_unlock: false,
         };
         let request: RpcRequest = serde_json::from_value(json_req).expect("Test json is incorrect");
         let genesis_config: Genesis =
@@ -454,7 +532,10 @@ mod tests {
             .storage
             .add_initial_state(genesis_config)
             .expect("Fatal: could not add test genesis in test");
-        let response = map_http_requests(&request, context).unwrap().to_string();
+        let response = map_http_requests(&request, context)
+            .await
+            .unwrap()
+            .to_string();
         let trimmed_id = response.trim().trim_matches('"');
         assert!(trimmed_id.starts_with("0x"));
         let hex = trimmed_id.trim_start_matches("0x");
@@ -463,8 +544,8 @@ mod tests {
         parsed.unwrap()
     }
 
-    #[test]
-    fn install_filter_removed_correctly_test() {
+    #[tokio::test]
+    async fn install_filter_removed_correctly_test() {
         let uninstall_filter_req: RpcRequest = serde_json::from_value(json!(
         {
             "jsonrpc":"2.0",
@@ -480,7 +561,7 @@ mod tests {
             0xFF,
             (
                 Instant::now(),
-                PollableFilter {
+                PollableFilter::Logs {
                     last_block_number: 0,
                     filter_data: LogsFilter {
                         from_block: BlockIdentifier::Number(1),
@@ -495,12 +576,20 @@ mod tests {
         let context = RpcApiContext {
             storage: Store::new("in-mem", EngineType::InMemory).unwrap(),
             local_p2p_node: example_p2p_node(),
-            jwt_secret: Default::default(),
+            jwt_auth: Default::default(),
             active_filters: active_filters.clone(),
             syncer: Arc::new(TokioMutex::new(SyncManager::dummy())),
+            peer_table: example_peer_table(),
+            evm: EvmConfig::default(),
+            batch_request_limit: DEFAULT_BATCH_REQUEST_LIMIT,
+            blocking_pool: BlockingPool::new(DEFAULT_BLOCKING_TASKS_LIMIT),
+            signer: None,
+            allow_insecure_unlock: false,
         };
 
-        map_http_requests(&uninstall_filter_req, context).unwrap();
+        map_http_requests(&uninstall_filter_req, context)
+            .await
+            .unwrap();
 
         assert!(
             active_filters.clone().lock().unwrap().len() == 0,
@@ -508,16 +597,22 @@ mod tests {
         );
     }
 
-    #[test]
-    fn removing_non_existing_filter_returns_false() {
+    #[tokio::test]
+    async fn removing_non_existing_filter_returns_false() {
         let active_filters = Arc::new(Mutex::new(HashMap::new()));
 
         let context = RpcApiContext {
             storage: Store::new("in-mem", EngineType::InMemory).unwrap(),
             local_p2p_node: example_p2p_node(),
             active_filters: active_filters.clone(),
-            jwt_secret: Default::default(),
+            jwt_auth: Default::default(),
             syncer: Arc::new(TokioMutex::new(SyncManager::dummy())),
+            peer_table: example_peer_table(),
+            evm: EvmConfig::default(),
+            batch_request_limit: DEFAULT_BATCH_REQUEST_LIMIT,
+            blocking_pool: BlockingPool::new(DEFAULT_BLOCKING_TASKS_LIMIT),
+            signer: None,
+            allow_insecure_unlock: false,
         };
         let uninstall_filter_req: RpcRequest = serde_json::from_value(json!(
         {
@@ -530,7 +625,9 @@ mod tests {
                 ,"id":1
         }))
         .expect("Json for test is not a valid request");
-        let res = map_http_requests(&uninstall_filter_req, context).unwrap();
+        let res = map_http_requests(&uninstall_filter_req, context)
+            .await
+            .unwrap();
         assert!(matches!(res, serde_json::Value::Bool(false)));
     }
 
@@ -609,4 +706,103 @@ mod tests {
 
         server_handle.abort();
     }
+
+    fn filter_test_context(active_filters: ActiveFilters) -> RpcApiContext {
+        let storage = Store::new("in-mem", EngineType::InMemory).unwrap();
+        let genesis: Genesis = serde_json::from_str(TEST_GENESIS).unwrap();
+        storage.add_initial_state(genesis).unwrap();
+        RpcApiContext {
+            storage,
+            local_p2p_node: example_p2p_node(),
+            jwt_auth: Default::default(),
+            active_filters,
+            syncer: Arc::new(TokioMutex::new(SyncManager::dummy())),
+            peer_table: example_peer_table(),
+            evm: EvmConfig::default(),
+            batch_request_limit: DEFAULT_BATCH_REQUEST_LIMIT,
+            blocking_pool: BlockingPool::new(DEFAULT_BLOCKING_TASKS_LIMIT),
+            signer: None,
+            allow_insecure_unlock: false,
+        }
+    }
+
+    async fn call(context: &RpcApiContext, method: &str, params: Value) -> Value {
+        let request: RpcRequest = serde_json::from_value(json!(
+            {"jsonrpc": "2.0", "method": method, "params": params, "id": 1}
+        ))
+        .unwrap();
+        map_http_requests(&request, context.clone()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn block_filter_returns_new_block_hashes() {
+        let context = filter_test_context(Default::default());
+        let id = call(&context, "eth_newBlockFilter", json!([])).await;
+
+        let storage = &context.storage;
+        let genesis_hash = storage.get_canonical_block_hash(0).unwrap().unwrap();
+        let header = BlockHeader {
+            number: 1,
+            parent_hash: genesis_hash,
+            ..Default::default()
+        };
+        let hash = header.compute_block_hash();
+        storage
+            .add_block(Block::new(header, BlockBody::empty()))
+            .unwrap();
+        storage.set_canonical_block(1, hash).unwrap();
+        storage.update_latest_block_number(1).unwrap();
+
+        let changes = call(&context, "eth_getFilterChanges", json!([id])).await;
+        assert_eq!(changes, json!([hash]));
+        // Blocks are only reported once
+        let changes = call(&context, "eth_getFilterChanges", json!([id])).await;
+        assert_eq!(changes, json!([]));
+    }
+
+    #[tokio::test]
+    async fn pending_transaction_filter_returns_new_transaction_hashes() {
+        let context = filter_test_context(Default::default());
+        let id = call(&context, "eth_newPendingTransactionFilter", json!([])).await;
+
+        let tx = Transaction::EIP1559Transaction(EIP1559Transaction {
+            nonce: 1,
+            ..Default::default()
+        });
+        let hash = tx.compute_hash();
+        context
+            .storage
+            .add_transaction_to_pool(hash, MempoolTransaction::new(tx, Address::zero()))
+            .unwrap();
+
+        let changes = call(&context, "eth_getFilterChanges", json!([id])).await;
+        assert_eq!(changes, json!([hash]));
+        let changes = call(&context, "eth_getFilterChanges", json!([id])).await;
+        assert_eq!(changes, json!([]));
+    }
+
+    #[tokio::test]
+    async fn filter_logs_rejects_non_log_filters() {
+        let context = filter_test_context(Default::default());
+        let id = call(&context, "eth_newBlockFilter", json!([])).await;
+        let request: RpcRequest = serde_json::from_value(json!(
+            {"jsonrpc": "2.0", "method": "eth_getFilterLogs", "params": [id], "id": 1}
+        ))
+        .unwrap();
+        let result = map_http_requests(&request, context).await;
+        assert!(matches!(result, Err(RpcErr::BadParams(_))));
+    }
+
+    #[tokio::test]
+    async fn filter_logs_returns_logs_of_log_filters() {
+        let context = filter_test_context(Default::default());
+        let id = call(
+            &context,
+            "eth_newFilter",
+            json!([{"fromBlock": "0x0", "toBlock": "latest", "address": null, "topics": null}]),
+        )
+        .await;
+        let logs = call(&context, "eth_getFilterLogs", json!([id])).await;
+        assert_eq!(logs, json!([]));
+    }
 }
@@ -1,4 +1,5 @@
 use ethrex_blockchain::constants::MIN_GAS_LIMIT;
+use ethrex_core::types::Transaction;
 use tracing::error;
 
 use crate::utils::RpcErr;
@@ -13,11 +14,16 @@ use serde_json::Value;
 #[derive(Debug, Clone)]
 pub struct GasPrice;
 
+#[derive(Debug, Clone)]
+pub struct MaxPriorityFee;
+
 // TODO: Maybe these constants should be some kind of config.
 // How many transactions to take as a price sample from a block.
 const TXS_SAMPLE_SIZE: usize = 3;
 // How many blocks we'll go back to calculate the estimate.
 const BLOCK_RANGE_LOWER_BOUND_DEC: u64 = 20;
+// Priority fee suggested when there are no recent transactions to sample, 1 gwei.
+const DEFAULT_MAX_PRIORITY_FEE: u64 = 1_000_000_000;
 
 impl RpcHandler for GasPrice {
     fn parse(_: &Option<Vec<Value>>) -> Result<Self, RpcErr> {
@@ -40,54 +46,14 @@ impl RpcHandler for GasPrice {
     // we can look into more sophisticated estimation methods, if needed.
     /// Estimate Gas Price based on already accepted transactions,
     /// as per the spec, this will be returned in wei.
-    fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
-        let Some(latest_block_number) = context.storage.get_latest_block_number()? else {
-            error!("FATAL: LATEST BLOCK NUMBER IS MISSING");
-            return Err(RpcErr::Internal("Error calculating gas price".to_string()));
-        };
-        let block_range_lower_bound =
-            latest_block_number.saturating_sub(BLOCK_RANGE_LOWER_BOUND_DEC);
-        // These are the blocks we'll use to estimate the price.
-        let block_range = block_range_lower_bound..=latest_block_number;
-        if block_range.is_empty() {
-            error!(
-                "Calculated block range from block {} \
-                    up to block {} for gas price estimation is empty",
-                block_range_lower_bound, latest_block_number
-            );
-            return Err(RpcErr::Internal("Error calculating gas price".to_string()));
-        }
-        let mut results = vec![];
-        // TODO: Estimating gas price involves querying multiple blocks
-        // and doing some calculations with each of them, let's consider
-        // caching this result, also we can have a specific DB method
-        // that returns a block range to not query them one-by-one.
-        for block_num in block_range {
-            let Some(block_body) = context.storage.get_block_body(block_num)? else {
-                error!("Block body for block number {block_num} is missing but is below the latest known block!");
-                return Err(RpcErr::Internal(
-                    "Error calculating gas price: missing data".to_string(),
-                ));
-            };
-            let mut gas_price_samples = block_body
-                .transactions
-                .into_iter()
-                .map(|tx| tx.gas_price())
-                .collect::<Vec<u64>>();
-            gas_price_samples.sort();
-            results.extend(gas_price_samples.into_iter().take(TXS_SAMPLE_SIZE));
-        }
-        results.sort();
-
-        let sample_gas = match results.get(results.len() / 2) {
-            Some(gas) => *gas,
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+        let sample_gas = match sample_recent_transactions(&context, |tx, _| Some(tx.gas_price()))? {
+            Some(gas) => gas,
             None => {
                 // If we don't have enough samples, we'll return the base fee or the min gas limit as a default.
-                context
-                    .storage
-                    .get_block_header(latest_block_number)
-                    .ok()
-                    .flatten()
+                let latest_block_number = context.storage.get_latest_block_number()?;
+                latest_block_number
+                    .and_then(|number| context.storage.get_block_header(number).ok().flatten())
                     .and_then(|header| header.base_fee_per_gas)
                     .unwrap_or(MIN_GAS_LIMIT)
             }
@@ -98,15 +64,88 @@ impl RpcHandler for GasPrice {
     }
 }
 
+impl RpcHandler for MaxPriorityFee {
+    fn parse(_: &Option<Vec<Value>>) -> Result<Self, RpcErr> {
+        Ok(MaxPriorityFee {})
+    }
+
+    /// Estimate the priority fee with the same sampling used for the gas price,
+    /// but over the tips the transactions actually paid on top of their block's base fee
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+        let sample_tip =
+            sample_recent_transactions(&context, |tx, base_fee| tx.effective_gas_tip(base_fee))?
+                .unwrap_or(DEFAULT_MAX_PRIORITY_FEE);
+
+        let tip_as_hex = format!("0x{:x}", sample_tip);
+        Ok(serde_json::Value::String(tip_as_hex))
+    }
+}
+
+/// Samples the lowest values of the transactions included in the latest blocks
+/// and returns the median of them, if there's any.
+/// The closure receives each transaction along with the base fee of its block.
+fn sample_recent_transactions<F>(context: &RpcApiContext, sample: F) -> Result<Option<u64>, RpcErr>
+where
+    F: Fn(&Transaction, Option<u64>) -> Option<u64>,
+{
+    let Some(latest_block_number) = context.storage.get_latest_block_number()? else {
+        error!("FATAL: LATEST BLOCK NUMBER IS MISSING");
+        return Err(RpcErr::Internal("Error calculating gas price".to_string()));
+    };
+    let block_range_lower_bound = latest_block_number.saturating_sub(BLOCK_RANGE_LOWER_BOUND_DEC);
+    // These are the blocks we'll use to estimate the price.
+    let block_range = block_range_lower_bound..=latest_block_number;
+    if block_range.is_empty() {
+        error!(
+            "Calculated block range from block {} \
+                up to block {} for gas price estimation is empty",
+            block_range_lower_bound, latest_block_number
+        );
+        return Err(RpcErr::Internal("Error calculating gas price".to_string()));
+    }
+    let mut results = vec![];
+    // TODO: Estimating gas price involves querying multiple blocks
+    // and doing some calculations with each of them, let's consider
+    // caching this result, also we can have a specific DB method
+    // that returns a block range to not query them one-by-one.
+    for block_num in block_range {
+        let (Some(block_header), Some(block_body)) = (
+            context.storage.get_block_header(block_num)?,
+            context.storage.get_block_body(block_num)?,
+        ) else {
+            error!("Block {block_num} is missing but is below the latest known block!");
+            return Err(RpcErr::Internal(
+                "Error calculating gas price: missing data".to_string(),
+            ));
+        };
+        let mut samples = block_body
+            .transactions
+            .iter()
+            .filter_map(|tx| sample(tx, block_header.base_fee_per_gas))
+            .collect::<Vec<u64>>();
+        samples.sort();
+        results.extend(samples.into_iter().take(TXS_SAMPLE_SIZE));
+    }
+    results.sort();
+
+    Ok(results.get(results.len() / 2).copied())
+}
+
 #[cfg(test)]
 mod tests {
-    use super::GasPrice;
+    use super::{GasPrice, MaxPriorityFee};
     use crate::{
+        blocking_pool::{BlockingPool, DEFAULT_BLOCKING_TASKS_LIMIT},
         map_http_requests,
-        utils::{parse_json_hex, test_utils::example_p2p_node, RpcRequest},
-        RpcApiContext, RpcHandler,
+        utils::{
+            parse_json_hex,
+            test_utils::{example_p2p_node, example_peer_table},
+            RpcRequest,
+        },
+        RpcApiContext, RpcHandler, DEFAULT_BATCH_REQUEST_LIMIT,
     };
     use bytes::Bytes;
+    use ethrex_blockchain::EvmConfig;
     use ethrex_core::{
         types::{
             Block, BlockBody, BlockHeader, EIP1559Transaction, Genesis, LegacyTransaction,
@@ -208,8 +247,8 @@ mod tests {
         store.add_initial_state(genesis).unwrap();
         store
     }
-    #[test]
-    fn test_for_legacy_txs() {
+    #[tokio::test]
+    async fn test_for_legacy_txs() {
         let context = default_context();
         for block_num in 1..100 {
             let mut txs = vec![];
@@ -235,13 +274,13 @@ mod tests {
                 .unwrap();
         }
         let gas_price = GasPrice {};
-        let response = gas_price.handle(context).unwrap();
+        let response = gas_price.handle(context).await.unwrap();
         let parsed_result = parse_json_hex(&response).unwrap();
         assert_eq!(parsed_result, 2000000000);
     }
 
-    #[test]
-    fn test_for_eip_1559_txs() {
+    #[tokio::test]
+    async fn test_for_eip_1559_txs() {
         let context = default_context();
         for block_num in 1..100 {
             let mut txs = vec![];
@@ -266,12 +305,12 @@ mod tests {
                 .unwrap();
         }
         let gas_price = GasPrice {};
-        let response = gas_price.handle(context).unwrap();
+        let response = gas_price.handle(context).await.unwrap();
         let parsed_result = parse_json_hex(&response).unwrap();
         assert_eq!(parsed_result, 2000000000);
     }
-    #[test]
-    fn test_with_mixed_transactions() {
+    #[tokio::test]
+    async fn test_with_mixed_transactions() {
         let context = default_context();
         for block_num in 1..100 {
             let txs = vec![
@@ -298,12 +337,12 @@ mod tests {
                 .unwrap();
         }
         let gas_price = GasPrice {};
-        let response = gas_price.handle(context).unwrap();
+        let response = gas_price.handle(context).await.unwrap();
         let parsed_result = parse_json_hex(&response).unwrap();
         assert_eq!(parsed_result, 2000000000);
     }
-    #[test]
-    fn test_with_not_enough_blocks_or_transactions() {
+    #[tokio::test]
+    async fn test_with_not_enough_blocks_or_transactions() {
         let context = default_context();
         for block_num in 1..10 {
             let txs = vec![legacy_tx_for_test(1)];
@@ -325,22 +364,22 @@ mod tests {
                 .unwrap();
         }
         let gas_price = GasPrice {};
-        let response = gas_price.handle(context).unwrap();
+        let response = gas_price.handle(context).await.unwrap();
         let parsed_result = parse_json_hex(&response).unwrap();
         assert_eq!(parsed_result, 1000000000);
     }
-    #[test]
-    fn test_with_no_blocks_but_genesis() {
+    #[tokio::test]
+    async fn test_with_no_blocks_but_genesis() {
         let context = default_context();
         let gas_price = GasPrice {};
         // genesis base fee is 1_000_000_000
         let expected_gas_price = 1_000_000_000;
-        let response = gas_price.handle(context).unwrap();
+        let response = gas_price.handle(context).await.unwrap();
         let parsed_result = parse_json_hex(&response).unwrap();
         assert_eq!(parsed_result, expected_gas_price);
     }
-    #[test]
-    fn request_smoke_test() {
+    #[tokio::test]
+    async fn request_smoke_test() {
         let raw_json = json!(
         {
             "jsonrpc":"2.0",
@@ -371,14 +410,57 @@ mod tests {
                 .update_latest_block_number(block_num)
                 .unwrap();
         }
-        let response = map_http_requests(&request, context).unwrap();
+        let response = map_http_requests(&request, context).await.unwrap();
         assert_eq!(response, expected_response)
     }
 
+    #[tokio::test]
+    async fn max_priority_fee_for_eip_1559_txs() {
+        let context = default_context();
+        for block_num in 1..100 {
+            let mut txs = vec![];
+            for nonce in 1..=3 {
+                txs.push(eip1559_tx_for_test(nonce));
+            }
+            let block_body = BlockBody {
+                transactions: txs,
+                ommers: Default::default(),
+                withdrawals: Default::default(),
+            };
+            let block_header = test_header(block_num);
+            let block = Block::new(block_header.clone(), block_body);
+            context.storage.add_block(block).unwrap();
+            context
+                .storage
+                .set_canonical_block(block_num, block_header.compute_block_hash())
+                .unwrap();
+            context
+                .storage
+                .update_latest_block_number(block_num)
+                .unwrap();
+        }
+        let response = MaxPriorityFee {}.handle(context).await.unwrap();
+        let parsed_result = parse_json_hex(&response).unwrap();
+        // Without a base fee the whole tip cap is paid, the sampled median is the one of nonce 2
+        assert_eq!(parsed_result, 4_000_000_000_000_000_000);
+    }
+
+    #[tokio::test]
+    async fn max_priority_fee_without_transactions() {
+        let request = RpcRequest {
+            method: "eth_maxPriorityFeePerGas".to_string(),
+            ..Default::default()
+        };
+        let response = map_http_requests(&request, default_context())
+            .await
+            .unwrap();
+        assert_eq!(response, json!("0x3b9aca00"));
+    }
+
     fn default_context() -> RpcApiContext {
         RpcApiContext {
             storage: setup_store(),
-            jwt_secret: Default::default(),
+            jwt_auth: Default::default(),
             local_p2p_node: Node {
                 ip: std::net::IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
                 udp_port: Default::default(),
@@ -387,6 +469,12 @@ mod tests {
             },
             active_filters: Default::default(),
             syncer: Arc::new(Mutex::new(SyncManager::dummy())),
+            peer_table: example_peer_table(),
+            evm: EvmConfig::default(),
+            batch_request_limit: DEFAULT_BATCH_REQUEST_LIMIT,
+            blocking_pool: BlockingPool::new(DEFAULT_BLOCKING_TASKS_LIMIT),
+            signer: None,
+            allow_insecure_unlock: false,
         }
     }
 }
@@ -5,4 +5,6 @@ pub(crate) mod fee_market;
 pub(crate) mod filter;
 pub(crate) mod gas_price;
 pub(crate) mod logs;
+pub(crate) mod sign;
+pub(crate) mod simulate;
 pub(crate) mod transaction;
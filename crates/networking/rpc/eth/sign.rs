@@ -0,0 +1,259 @@
+use bytes::Bytes;
+use ethrex_blockchain::mempool;
+use ethrex_core::{
+    types::{EIP1559Transaction, EIP2930Transaction, GenericTransaction, Signable, Transaction},
+    Address,
+};
+use ethrex_vm::StateOverride;
+use serde_json::{json, Value};
+use tracing::info;
+
+use crate::{
+    eth::{
+        account::GetTransactionCountRequest, gas_price::MaxPriorityFee,
+        transaction::EstimateGasRequest,
+    },
+    signer::{sign_message, Signer},
+    types::block_identifier::{BlockIdentifier, BlockIdentifierOrHash, BlockTag},
+    utils::{parse_json_hex, RpcErr},
+    RpcApiContext, RpcHandler,
+};
+
+pub struct SignRequest {
+    pub address: Address,
+    pub message: Bytes,
+    /// Only given by personal_sign, the account must be unlocked otherwise
+    pub password: Option<String>,
+}
+
+pub struct SignTransactionRequest {
+    pub transaction: GenericTransaction,
+    /// Only given by personal_signTransaction, the account must be unlocked otherwise
+    pub password: Option<String>,
+}
+
+pub struct SendTransactionRequest {
+    pub transaction: GenericTransaction,
+    /// Only given by personal_sendTransaction, the account must be unlocked otherwise
+    pub password: Option<String>,
+}
+
+impl RpcHandler for SignRequest {
+    fn parse(params: &Option<Vec<Value>>) -> Result<Self, RpcErr> {
+        let params = params
+            .as_ref()
+            .ok_or(RpcErr::BadParams("No params provided".to_owned()))?;
+        if params.len() != 2 {
+            return Err(RpcErr::BadParams(format!(
+                "Expected two params and {} were provided",
+                params.len()
+            )));
+        };
+        Ok(SignRequest {
+            address: serde_json::from_value(params[0].clone())?,
+            message: parse_hex_bytes(&params[1], 1)?,
+            password: None,
+        })
+    }
+
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+        info!("Requested signature of account {:#x}", self.address);
+        let secret_key = signer(&context)?.secret_key(self.address, self.password.as_deref())?;
+        let signature = sign_message(&secret_key, &self.message);
+        Ok(Value::String(format!("0x{}", hex::encode(signature))))
+    }
+}
+
+impl RpcHandler for SignTransactionRequest {
+    fn parse(params: &Option<Vec<Value>>) -> Result<Self, RpcErr> {
+        Ok(SignTransactionRequest {
+            transaction: parse_transaction(params)?,
+            password: None,
+        })
+    }
+
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+        info!(
+            "Requested to sign a transaction of account {:#x}",
+            self.transaction.from
+        );
+        let transaction =
+            sign_transaction(&self.transaction, self.password.as_deref(), &context).await?;
+        Ok(json!({
+            "raw": format!("0x{}", hex::encode(transaction.encode_canonical_to_vec())),
+            "tx": transaction,
+        }))
+    }
+}
+
+impl RpcHandler for SendTransactionRequest {
+    fn parse(params: &Option<Vec<Value>>) -> Result<Self, RpcErr> {
+        Ok(SendTransactionRequest {
+            transaction: parse_transaction(params)?,
+            password: None,
+        })
+    }
+
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+        info!(
+            "Requested to send a transaction of account {:#x}",
+            self.transaction.from
+        );
+        let transaction =
+            sign_transaction(&self.transaction, self.password.as_deref(), &context).await?;
+        let hash = mempool::add_transaction(transaction, &context.storage)?;
+        serde_json::to_value(format!("{:#x}", hash))
+            .map_err(|error| RpcErr::Internal(error.to_string()))
+    }
+}
+
+/// The local signer, which only exists when the node was started with it enabled
+pub(crate) fn signer(context: &RpcApiContext) -> Result<&Signer, RpcErr> {
+    context
+        .signer
+        .as_ref()
+        .ok_or(RpcErr::Signer("The local signer is disabled".to_owned()))
+}
+
+pub(crate) fn parse_hex_bytes(value: &Value, arg_index: u64) -> Result<Bytes, RpcErr> {
+    let hex_str = serde_json::from_value::<String>(value.clone())?;
+    let hex_str = hex_str
+        .strip_prefix("0x")
+        .ok_or(RpcErr::BadHexFormat(arg_index))?;
+    let bytes = hex::decode(hex_str).map_err(|error| RpcErr::BadParams(error.to_string()))?;
+    Ok(Bytes::from(bytes))
+}
+
+fn parse_transaction(params: &Option<Vec<Value>>) -> Result<GenericTransaction, RpcErr> {
+    let params = params
+        .as_ref()
+        .ok_or(RpcErr::BadParams("No params provided".to_owned()))?;
+    if params.len() != 1 {
+        return Err(RpcErr::BadParams(format!(
+            "Expected one param and {} were provided",
+            params.len()
+        )));
+    };
+    Ok(serde_json::from_value(params[0].clone())?)
+}
+
+/// Fills the fields the request left out and signs the transaction with the key of its sender
+pub(crate) async fn sign_transaction(
+    transaction: &GenericTransaction,
+    password: Option<&str>,
+    context: &RpcApiContext,
+) -> Result<Transaction, RpcErr> {
+    // Get the key first, so locked accounts fail before estimating anything
+    let secret_key = signer(context)?.secret_key(transaction.from, password)?;
+    let transaction = fill_transaction(transaction.clone(), context).await?;
+    Ok(transaction.sign(&secret_key))
+}
+
+/// Builds the transaction to sign, taking the missing nonce, fees and gas limit from the
+/// pending nonce of the sender, the fee estimations and eth_estimateGas.
+/// Transactions stating a gas price are built as EIP-2930 ones, as legacy transactions would
+/// be signed without replay protection, the others as EIP-1559 ones.
+async fn fill_transaction(
+    mut transaction: GenericTransaction,
+    context: &RpcApiContext,
+) -> Result<Transaction, RpcErr> {
+    if !transaction.blob_versioned_hashes.is_empty() || !transaction.blobs.is_empty() {
+        return Err(RpcErr::BadParams(
+            "Blob transactions must be sent through eth_sendRawTransaction".to_owned(),
+        ));
+    }
+    let chain_id = context.storage.get_chain_config()?.chain_id;
+    if let Some(tx_chain_id) = transaction.chain_id.filter(|id| *id != chain_id) {
+        return Err(RpcErr::BadParams(format!(
+            "Chain id {tx_chain_id} doesn't match the one of the node, {chain_id}"
+        )));
+    }
+
+    if transaction.nonce.is_none() {
+        let nonce = GetTransactionCountRequest {
+            address: transaction.from,
+            block: BlockIdentifierOrHash::Identifier(BlockIdentifier::Tag(BlockTag::Pending)),
+        }
+        .handle(context.clone())
+        .await?;
+        transaction.nonce = Some(parse_json_hex(&nonce).map_err(RpcErr::Internal)?);
+    }
+
+    let uses_gas_price = transaction.gas_price != 0
+        && transaction.max_fee_per_gas.is_none()
+        && transaction.max_priority_fee_per_gas.is_none();
+    if !uses_gas_price {
+        let max_priority_fee_per_gas = match transaction.max_priority_fee_per_gas {
+            Some(fee) => fee,
+            None => parse_json_hex(&MaxPriorityFee.handle(context.clone()).await?)
+                .map_err(RpcErr::Internal)?,
+        };
+        if transaction.max_fee_per_gas.is_none() {
+            let base_fee = BlockIdentifier::Tag(BlockTag::Latest)
+                .resolve_block_header(&context.storage)?
+                .and_then(|header| header.base_fee_per_gas)
+                .unwrap_or_default();
+            // Leave room for the base fee to double before the transaction is included
+            transaction.max_fee_per_gas = Some(
+                base_fee
+                    .saturating_mul(2)
+                    .saturating_add(max_priority_fee_per_gas),
+            );
+        }
+        transaction.max_priority_fee_per_gas = Some(max_priority_fee_per_gas);
+    }
+
+    let gas_limit = match transaction.gas {
+        Some(gas_limit) => gas_limit,
+        None => {
+            // The nonce is left out, as the sender's previous transactions may still be in the
+            // mempool and the estimation runs on the latest block
+            let estimation = EstimateGasRequest {
+                transaction: GenericTransaction {
+                    nonce: None,
+                    ..transaction.clone()
+                },
+                block: None,
+                state_overrides: StateOverride::default(),
+                block_overrides: None,
+            }
+            .handle(context.clone())
+            .await?;
+            parse_json_hex(&estimation).map_err(RpcErr::Internal)?
+        }
+    };
+
+    let nonce = transaction.nonce.unwrap_or_default();
+    let access_list = transaction
+        .access_list
+        .into_iter()
+        .map(|entry| (entry.address, entry.storage_keys))
+        .collect();
+    let transaction = if uses_gas_price {
+        Transaction::EIP2930Transaction(EIP2930Transaction {
+            chain_id,
+            nonce,
+            gas_price: transaction.gas_price,
+            gas_limit,
+            to: transaction.to,
+            value: transaction.value,
+            data: transaction.input,
+            access_list,
+            ..Default::default()
+        })
+    } else {
+        Transaction::EIP1559Transaction(EIP1559Transaction {
+            chain_id,
+            nonce,
+            max_priority_fee_per_gas: transaction.max_priority_fee_per_gas.unwrap_or_default(),
+            max_fee_per_gas: transaction.max_fee_per_gas.unwrap_or_default(),
+            gas_limit,
+            to: transaction.to,
+            value: transaction.value,
+            data: transaction.input,
+            access_list,
+            ..Default::default()
+        })
+    };
+    Ok(transaction)
+}
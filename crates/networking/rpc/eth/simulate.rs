@@ -0,0 +1,214 @@
+use ethrex_blockchain::simulate::{simulate_blocks, BlockStateCalls, SimulatedBlock};
+use ethrex_core::{serde_utils, types::GenericTransaction, Bytes, U256};
+use ethrex_vm::{BlockOverrides, ExecutionResult, SimulationConfig, StateOverride};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::info;
+
+use crate::{
+    types::{
+        block::{BlockBodyWrapper, FullBlockBody, OnlyHashesBlockBody, RpcBlock},
+        block_identifier::BlockIdentifier,
+        receipt::{RpcLog, RpcLogInfo},
+    },
+    utils::{RpcErr, RpcErrorMetadata},
+    RpcApiContext, RpcHandler,
+};
+
+pub struct SimulateRequest {
+    payload: SimulationPayload,
+    block: Option<BlockIdentifier>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SimulationPayload {
+    block_state_calls: Vec<BlockStateCallsParam>,
+    #[serde(default)]
+    trace_transfers: bool,
+    #[serde(default)]
+    validation: bool,
+    #[serde(default)]
+    return_full_transactions: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BlockStateCallsParam {
+    #[serde(default)]
+    block_overrides: Option<BlockOverrides>,
+    #[serde(default)]
+    state_overrides: Option<StateOverride>,
+    #[serde(default)]
+    calls: Vec<GenericTransaction>,
+}
+
+impl From<BlockStateCallsParam> for BlockStateCalls {
+    fn from(param: BlockStateCallsParam) -> Self {
+        BlockStateCalls {
+            block_overrides: param.block_overrides.unwrap_or_default(),
+            state_overrides: param.state_overrides.unwrap_or_default(),
+            calls: param.calls,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SimulatedBlockResult {
+    #[serde(flatten)]
+    block: RpcBlock,
+    calls: Vec<CallResult>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CallResult {
+    #[serde(with = "serde_utils::bytes")]
+    return_data: Bytes,
+    logs: Vec<RpcLog>,
+    #[serde(with = "serde_utils::u64::hex_str")]
+    gas_used: u64,
+    #[serde(with = "serde_utils::bool")]
+    status: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<RpcErrorMetadata>,
+}
+
+impl RpcHandler for SimulateRequest {
+    fn parse(params: &Option<Vec<Value>>) -> Result<SimulateRequest, RpcErr> {
+        let params = params
+            .as_ref()
+            .ok_or(RpcErr::BadParams("No params provided".to_owned()))?;
+        if params.is_empty() || params.len() > 2 {
+            return Err(RpcErr::BadParams(format!(
+                "Expected one or two params and {} were provided",
+                params.len()
+            )));
+        }
+        let payload: SimulationPayload = serde_json::from_value(params[0].clone())?;
+        for block_calls in &payload.block_state_calls {
+            for (address, account_override) in block_calls.state_overrides.iter().flatten() {
+                account_override
+                    .validate(*address)
+                    .map_err(|error| RpcErr::BadParams(error.to_string()))?;
+            }
+        }
+        let block = match params.get(1) {
+            // Differentiate between missing and bad block param
+            Some(value) => Some(BlockIdentifier::parse(value.clone(), 1)?),
+            None => None,
+        };
+        Ok(SimulateRequest { payload, block })
+    }
+
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+        let block = self.block.clone().unwrap_or_default();
+        info!("Requested simulation on block: {}", block);
+        let Some(header) = block.resolve_block_header(&context.storage)? else {
+            // Block not found
+            return Ok(Value::Null);
+        };
+        let total_difficulty = context
+            .storage
+            .get_block_total_difficulty(header.compute_block_hash())?
+            .unwrap_or(U256::zero());
+        let blocks: Vec<BlockStateCalls> = self
+            .payload
+            .block_state_calls
+            .iter()
+            .cloned()
+            .map(BlockStateCalls::from)
+            .collect();
+        let config = SimulationConfig {
+            validation: self.payload.validation,
+            trace_transfers: self.payload.trace_transfers,
+        };
+        let simulated_blocks = simulate_blocks(
+            &header,
+            &blocks,
+            config,
+            &context.storage,
+            context.evm.engine,
+        )?;
+        let results: Vec<SimulatedBlockResult> = simulated_blocks
+            .into_iter()
+            .map(|simulated| {
+                simulated_block_result(
+                    simulated,
+                    self.payload.return_full_transactions,
+                    total_difficulty,
+                )
+            })
+            .collect();
+        serde_json::to_value(results).map_err(|error| RpcErr::Internal(error.to_string()))
+    }
+}
+
+fn simulated_block_result(
+    simulated: SimulatedBlock,
+    full_transactions: bool,
+    total_difficulty: U256,
+) -> SimulatedBlockResult {
+    let SimulatedBlock { block, calls, .. } = simulated;
+    let hash = block.header.compute_block_hash();
+    let number = block.header.number;
+
+    let mut log_index = 0;
+    let mut call_results = Vec::new();
+    for (index, call) in calls.iter().enumerate() {
+        let transaction_hash = call.transaction.compute_hash();
+        let mut logs = Vec::new();
+        for log in call.result.logs() {
+            logs.push(RpcLog {
+                log: RpcLogInfo::from(log),
+                log_index,
+                removed: false,
+                transaction_hash,
+                transaction_index: index as u64,
+                block_hash: hash,
+                block_number: number,
+            });
+            log_index += 1;
+        }
+        let error = match &call.result {
+            ExecutionResult::Success { .. } => None,
+            ExecutionResult::Revert { output, .. } => Some(RpcErr::Revert {
+                data: format!("0x{:#x}", output),
+            }),
+            ExecutionResult::Halt { reason, gas_used } => Some(RpcErr::Halt {
+                reason: reason.clone(),
+                gas_used: *gas_used,
+            }),
+        };
+        call_results.push(CallResult {
+            return_data: call.result.output(),
+            logs,
+            gas_used: call.result.gas_used(),
+            status: call.result.is_success(),
+            error: error.map(RpcErrorMetadata::from),
+        });
+    }
+
+    let body_wrapper = if full_transactions {
+        let senders = calls.iter().map(|call| call.sender).collect();
+        BlockBodyWrapper::Full(FullBlockBody::from_body_with_senders(
+            block.body.clone(),
+            senders,
+            number,
+            hash,
+        ))
+    } else {
+        BlockBodyWrapper::OnlyHashes(OnlyHashesBlockBody::from_body(block.body.clone()))
+    };
+    SimulatedBlockResult {
+        block: RpcBlock::build_with_body(
+            block.header,
+            block.body,
+            hash,
+            body_wrapper,
+            total_difficulty,
+        ),
+        calls: call_results,
+    }
+}
@@ -8,15 +8,18 @@ use crate::{
     RpcApiContext, RpcHandler,
 };
 use ethrex_core::{
-    types::{AccessListEntry, BlockHash, BlockHeader, BlockNumber, GenericTransaction, TxKind},
-    H256, U256,
+    types::{
+        AccessListEntry, AccountInfo, BlockHash, BlockHeader, GenericTransaction, TxKind,
+        EMPTY_KECCACK_HASH,
+    },
+    Address, H256, U256,
 };
 
 use ethrex_blockchain::mempool;
 use ethrex_rlp::encode::RLPEncode;
-use ethrex_storage::Store;
+use ethrex_storage::{error::StoreError, Store};
 
-use ethrex_vm::{evm_state, ExecutionResult, SpecId};
+use ethrex_vm::{AccountOverride, BlockOverrides, Evm, EvmEngine, ExecutionResult, StateOverride};
 use serde::Serialize;
 
 use serde_json::Value;
@@ -29,6 +32,8 @@ pub const TRANSACTION_GAS: u64 = 21_000; // Per transaction not creating a contr
 pub struct CallRequest {
     transaction: GenericTransaction,
     block: Option<BlockIdentifier>,
+    state_overrides: StateOverride,
+    block_overrides: Option<BlockOverrides>,
 }
 
 pub struct GetTransactionByBlockNumberAndIndexRequest {
@@ -56,6 +61,8 @@ pub struct CreateAccessListRequest {
 pub struct EstimateGasRequest {
     pub transaction: GenericTransaction,
     pub block: Option<BlockIdentifier>,
+    pub state_overrides: StateOverride,
+    pub block_overrides: Option<BlockOverrides>,
 }
 
 pub struct GetRawTransaction {
@@ -80,9 +87,9 @@ impl RpcHandler for CallRequest {
         if params.is_empty() {
             return Err(RpcErr::BadParams("No params provided".to_owned()));
         }
-        if params.len() > 2 {
+        if params.len() > 4 {
             return Err(RpcErr::BadParams(format!(
-                "Expected one or two params and {} were provided",
+                "Expected between one and four params and {} were provided",
                 params.len()
             )));
         }
@@ -91,21 +98,29 @@ impl RpcHandler for CallRequest {
             Some(value) => Some(BlockIdentifier::parse(value.clone(), 1)?),
             None => None,
         };
+        let (state_overrides, block_overrides) = parse_overrides(params)?;
         Ok(CallRequest {
             transaction: serde_json::from_value(params[0].clone())?,
             block,
+            state_overrides,
+            block_overrides,
         })
     }
-    fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
         let block = self.block.clone().unwrap_or_default();
         info!("Requested call on block: {}", block);
-        let header = match block.resolve_block_header(&context.storage)? {
-            Some(header) => header,
+        let Some(header) = block.resolve_block_header(&context.storage)? else {
             // Block not found
-            _ => return Ok(Value::Null),
+            return Ok(Value::Null);
         };
+        let simulation = Simulation::new(header, &self.state_overrides, &self.block_overrides);
         // Run transaction
-        let result = simulate_tx(&self.transaction, &header, context.storage, SpecId::CANCUN)?;
+        let result = simulate_tx(
+            &self.transaction,
+            &simulation,
+            context.storage,
+            context.evm.engine,
+        )?;
         serde_json::to_value(format!("0x{:#x}", result.output()))
             .map_err(|error| RpcErr::Internal(error.to_string()))
     }
@@ -132,7 +147,7 @@ impl RpcHandler for GetTransactionByBlockNumberAndIndexRequest {
         })
     }
 
-    fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
         info!(
             "Requested transaction at index: {} of block with number: {}",
             self.transaction_index, self.block,
@@ -183,7 +198,7 @@ impl RpcHandler for GetTransactionByBlockHashAndIndexRequest {
                 .map_err(|error| RpcErr::BadParams(error.to_string()))?,
         })
     }
-    fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
         info!(
             "Requested transaction at index: {} of block with hash: {:#x}",
             self.transaction_index, self.block,
@@ -221,7 +236,7 @@ impl RpcHandler for GetTransactionByHashRequest {
             transaction_hash: serde_json::from_value(params[0].clone())?,
         })
     }
-    fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
         let storage = &context.storage;
         info!(
             "Requested transaction with hash: {:#x}",
@@ -260,7 +275,7 @@ impl RpcHandler for GetTransactionReceiptRequest {
             transaction_hash: serde_json::from_value(params[0].clone())?,
         })
     }
-    fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
         let storage = &context.storage;
         info!(
             "Requested receipt for transaction {:#x}",
@@ -306,7 +321,7 @@ impl RpcHandler for CreateAccessListRequest {
             block,
         })
     }
-    fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
         let block = self.block.clone().unwrap_or_default();
         info!("Requested access list creation for tx on block: {}", block);
         let block_number = match block.resolve_block_number(&context.storage)? {
@@ -319,37 +334,38 @@ impl RpcHandler for CreateAccessListRequest {
             _ => return Ok(Value::Null),
         };
         // Run transaction and obtain access list
-        let (gas_used, access_list, error) = match ethrex_vm::create_access_list(
-            &self.transaction,
-            &header,
-            &mut evm_state(context.storage, header.compute_block_hash()),
-            SpecId::CANCUN,
-        )? {
-            (
-                ExecutionResult::Success {
-                    reason: _,
-                    gas_used,
-                    gas_refunded: _,
-                    logs: _,
-                    output: _,
-                },
-                access_list,
-            ) => (gas_used, access_list, None),
-            (
-                ExecutionResult::Revert {
+        let mut evm = Evm::new(
+            context.evm.engine,
+            context.storage,
+            header.compute_block_hash(),
+        );
+        let (gas_used, access_list, error) =
+            match evm.create_access_list(&self.transaction, &header)? {
+                (
+                    ExecutionResult::Success {
+                        reason: _,
+                        gas_used,
+                        gas_refunded: _,
+                        logs: _,
+                        output: _,
+                    },
+                    access_list,
+                ) => (gas_used, access_list, None),
+                (
+                    ExecutionResult::Revert {
+                        gas_used,
+                        output: _,
+                    },
+                    access_list,
+                ) => (
                     gas_used,
-                    output: _,
-                },
-                access_list,
-            ) => (
-                gas_used,
-                access_list,
-                Some("Transaction Reverted".to_string()),
-            ),
-            (ExecutionResult::Halt { reason, gas_used }, access_list) => {
-                (gas_used, access_list, Some(reason))
-            }
-        };
+                    access_list,
+                    Some("Transaction Reverted".to_string()),
+                ),
+                (ExecutionResult::Halt { reason, gas_used }, access_list) => {
+                    (gas_used, access_list, Some(reason))
+                }
+            };
         let result = AccessListResult {
             access_list: access_list
                 .into_iter()
@@ -388,7 +404,7 @@ impl RpcHandler for GetRawTransaction {
         })
     }
 
-    fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
         let tx = context
             .storage
             .get_transaction_by_hash(self.transaction_hash)?;
@@ -411,9 +427,9 @@ impl RpcHandler for EstimateGasRequest {
         if params.is_empty() {
             return Err(RpcErr::BadParams("No params provided".to_owned()));
         }
-        if params.len() > 2 {
+        if params.len() > 4 {
             return Err(RpcErr::BadParams(format!(
-                "Expected one or two params and {} were provided",
+                "Expected between one and four params and {} were provided",
                 params.len()
             )));
         }
@@ -422,26 +438,37 @@ impl RpcHandler for EstimateGasRequest {
             Some(value) => Some(BlockIdentifier::parse(value.clone(), 1)?),
             None => None,
         };
+        let (state_overrides, block_overrides) = parse_overrides(params)?;
         Ok(EstimateGasRequest {
             transaction: serde_json::from_value(params[0].clone())?,
             block,
+            state_overrides,
+            block_overrides,
         })
     }
-    fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
         let storage = &context.storage;
         let block = self.block.clone().unwrap_or_default();
         info!("Requested estimate on block: {}", block);
-        let block_header = match block.resolve_block_header(storage)? {
-            Some(header) => header,
+        let Some(block_header) = block.resolve_block_header(storage)? else {
             // Block not found
-            _ => return Ok(Value::Null),
+            return Ok(Value::Null);
         };
+        let simulation =
+            Simulation::new(block_header, &self.state_overrides, &self.block_overrides);
+        let block_header = &simulation.header;
 
         let transaction = match self.transaction.nonce {
             Some(_nonce) => self.transaction.clone(),
             None => {
-                let transaction_nonce = storage
-                    .get_nonce_by_account_address(block_header.number, self.transaction.from)?;
+                let transaction_nonce = match simulation.account_override(self.transaction.from) {
+                    Some(AccountOverride {
+                        nonce: Some(nonce), ..
+                    }) => Some(*nonce),
+                    _ => simulation
+                        .account_info(storage, self.transaction.from)?
+                        .map(|account| account.nonce),
+                };
 
                 let mut cloned_transaction = self.transaction.clone();
                 cloned_transaction.nonce = transaction_nonce;
@@ -449,20 +476,25 @@ impl RpcHandler for EstimateGasRequest {
             }
         };
 
-        let spec_id = ethrex_vm::spec_id(&storage.get_chain_config()?, block_header.timestamp);
-
         // If the transaction is a plain value transfer, short circuit estimation.
         if let TxKind::Call(address) = transaction.to {
-            let account_info = storage.get_account_info(block_header.number, address)?;
-            let code = account_info.map(|info| storage.get_account_code(info.code_hash));
-            if code.is_none() {
+            let has_code = match simulation.account_override(address) {
+                Some(AccountOverride {
+                    code: Some(code), ..
+                }) => !code.is_empty(),
+                _ => {
+                    let account_info = simulation.account_info(storage, address)?;
+                    account_info.is_some_and(|info| info.code_hash != *EMPTY_KECCACK_HASH)
+                }
+            };
+            if !has_code {
                 let mut value_transfer_transaction = transaction.clone();
                 value_transfer_transaction.gas = Some(TRANSACTION_GAS);
                 let result: Result<ExecutionResult, RpcErr> = simulate_tx(
                     &value_transfer_transaction,
-                    &block_header,
+                    &simulation,
                     storage.clone(),
-                    spec_id,
+                    context.evm.engine,
                 );
                 if let Ok(ExecutionResult::Success { .. }) = result {
                     return serde_json::to_value(format!("{:#x}", TRANSACTION_GAS))
@@ -478,18 +510,19 @@ impl RpcHandler for EstimateGasRequest {
         };
 
         if transaction.gas_price != 0 {
-            highest_gas_limit = recap_with_account_balances(
-                highest_gas_limit,
-                &transaction,
-                storage,
-                block_header.number,
-            )?;
+            highest_gas_limit =
+                recap_with_account_balances(highest_gas_limit, &transaction, storage, &simulation)?;
         }
 
         // Check whether the execution is possible
         let mut transaction = transaction.clone();
         transaction.gas = Some(highest_gas_limit);
-        let result = simulate_tx(&transaction, &block_header, storage.clone(), spec_id)?;
+        let result = simulate_tx(
+            &transaction,
+            &simulation,
+            storage.clone(),
+            context.evm.engine,
+        )?;
 
         let gas_used = result.gas_used();
         let gas_refunded = result.gas_refunded();
@@ -512,7 +545,12 @@ impl RpcHandler for EstimateGasRequest {
             }
             transaction.gas = Some(middle_gas_limit);
 
-            let result = simulate_tx(&transaction, &block_header, storage.clone(), spec_id);
+            let result = simulate_tx(
+                &transaction,
+                &simulation,
+                storage.clone(),
+                context.evm.engine,
+            );
             if let Ok(ExecutionResult::Success { .. }) = result {
                 highest_gas_limit = middle_gas_limit;
             } else {
@@ -530,29 +568,88 @@ fn recap_with_account_balances(
     highest_gas_limit: u64,
     transaction: &GenericTransaction,
     storage: &Store,
-    block_number: BlockNumber,
+    simulation: &Simulation,
 ) -> Result<u64, RpcErr> {
-    let account_balance = storage
-        .get_account_info(block_number, transaction.from)?
-        .map(|acc| acc.balance)
-        .unwrap_or_default();
+    let account_balance = match simulation.account_override(transaction.from) {
+        Some(AccountOverride {
+            balance: Some(balance),
+            ..
+        }) => *balance,
+        _ => simulation
+            .account_info(storage, transaction.from)?
+            .map(|acc| acc.balance)
+            .unwrap_or_default(),
+    };
     let account_gas =
         account_balance.saturating_sub(transaction.value) / U256::from(transaction.gas_price);
     Ok(highest_gas_limit.min(account_gas.as_u64()))
 }
 
+/// Block a call is simulated on top of, along with the overrides requested for it
+struct Simulation<'a> {
+    /// Block whose post-state the call is executed on
+    block: BlockHeader,
+    /// Header the call is executed with, which has the block overrides applied
+    header: BlockHeader,
+    state_overrides: &'a StateOverride,
+}
+
+impl<'a> Simulation<'a> {
+    fn new(
+        block: BlockHeader,
+        state_overrides: &'a StateOverride,
+        block_overrides: &Option<BlockOverrides>,
+    ) -> Self {
+        let mut header = block.clone();
+        if let Some(block_overrides) = block_overrides {
+            block_overrides.apply(&mut header);
+        }
+        Self {
+            block,
+            header,
+            state_overrides,
+        }
+    }
+
+    fn account_override(&self, address: Address) -> Option<&AccountOverride> {
+        self.state_overrides.get(&address)
+    }
+
+    /// Reads the account from the block's post-state, found by hash as it may be the pending block
+    fn account_info(
+        &self,
+        storage: &Store,
+        address: Address,
+    ) -> Result<Option<AccountInfo>, StoreError> {
+        storage.get_account_info_by_hash(self.block.compute_block_hash(), address)
+    }
+}
+
+/// Parses the optional state and block overrides that follow the block param of calls
+fn parse_overrides(params: &[Value]) -> Result<(StateOverride, Option<BlockOverrides>), RpcErr> {
+    let state_overrides: StateOverride = match params.get(2) {
+        Some(Value::Null) | None => StateOverride::default(),
+        Some(value) => serde_json::from_value(value.clone())?,
+    };
+    for (address, account_override) in &state_overrides {
+        account_override
+            .validate(*address)
+            .map_err(|error| RpcErr::BadParams(error.to_string()))?;
+    }
+    let block_overrides = match params.get(3) {
+        Some(Value::Null) | None => None,
+        Some(value) => Some(serde_json::from_value(value.clone())?),
+    };
+    Ok((state_overrides, block_overrides))
+}
+
 fn simulate_tx(
     transaction: &GenericTransaction,
-    block_header: &BlockHeader,
+    simulation: &Simulation,
     storage: Store,
-    spec_id: SpecId,
+    evm: EvmEngine,
 ) -> Result<ExecutionResult, RpcErr> {
-    match ethrex_vm::simulate_tx_from_generic(
-        transaction,
-        block_header,
-        &mut evm_state(storage, block_header.compute_block_hash()),
-        spec_id,
-    )? {
+    match execute_simulation(transaction, simulation, storage, evm)? {
         ExecutionResult::Revert {
             gas_used: _,
             output,
@@ -564,6 +661,30 @@ fn simulate_tx(
     }
 }
 
+/// Runs the call on top of the block without overrides, returning its result even if it
+/// reverted or halted
+pub(crate) fn call_on_block(
+    transaction: &GenericTransaction,
+    block: BlockHeader,
+    storage: Store,
+    evm: EvmEngine,
+) -> Result<ExecutionResult, RpcErr> {
+    let state_overrides = StateOverride::default();
+    let simulation = Simulation::new(block, &state_overrides, &None);
+    execute_simulation(transaction, &simulation, storage, evm)
+}
+
+fn execute_simulation(
+    transaction: &GenericTransaction,
+    simulation: &Simulation,
+    storage: Store,
+    evm: EvmEngine,
+) -> Result<ExecutionResult, RpcErr> {
+    let mut evm = Evm::new(evm, storage, simulation.block.compute_block_hash());
+    evm.apply_state_overrides(simulation.state_overrides)?;
+    Ok(evm.simulate_tx_from_generic(transaction, &simulation.header)?)
+}
+
 impl RpcHandler for SendRawTransactionRequest {
     fn parse(params: &Option<Vec<Value>>) -> Result<SendRawTransactionRequest, RpcErr> {
         let params = params
@@ -587,7 +708,7 @@ impl RpcHandler for SendRawTransactionRequest {
 
         Ok(transaction)
     }
-    fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
         let hash = if let SendRawTransactionRequest::EIP4844(wrapped_blob_tx) = self {
             mempool::add_blob_transaction(
                 wrapped_blob_tx.tx.clone(),
@@ -0,0 +1,366 @@
+// GraphQL interface of EIP-1767: https://eips.ethereum.org/EIPS/eip-1767
+// The schema follows the one served by Go-Ethereum, specifically:
+// https://github.com/ethereum/go-ethereum/blob/master/graphql/schema.go
+//
+// Queries are executed by a small executor over the parsed document instead of a GraphQL
+// framework, as the schema is fixed and only needs fields, arguments, variables, fragments and
+// the skip and include directives. Introspection isn't supported.
+mod schema;
+
+use std::collections::HashMap;
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use graphql_parser::query::{
+    parse_query, Definition, Directive, Field, FragmentDefinition, OperationDefinition, Selection,
+    SelectionSet, TypeCondition, Value as GraphQLValue,
+};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use tracing::debug;
+
+use crate::{utils::RpcErrorMetadata, RpcApiContext};
+use schema::{Arguments, Node, Resolved};
+
+/// Name the GraphQL requests are limited by on the blocking pool
+const GRAPHQL_METHOD: &str = "graphql";
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphQLRequest {
+    query: String,
+    #[serde(default)]
+    operation_name: Option<String>,
+    #[serde(default)]
+    variables: Option<Map<String, Value>>,
+}
+
+pub async fn handle_graphql_request(
+    State(service_context): State<RpcApiContext>,
+    body: String,
+) -> Response {
+    let request: GraphQLRequest = match serde_json::from_str(&body) {
+        Ok(request) => request,
+        Err(error) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(error_response(error.to_string())),
+            )
+                .into_response()
+        }
+    };
+    // Queries may go through many blocks and run calls, so they run on the blocking pool
+    let blocking_pool = service_context.blocking_pool.clone();
+    let response = blocking_pool
+        .run(GRAPHQL_METHOD, move || {
+            Ok(execute(&request, &service_context))
+        })
+        .await
+        .unwrap_or_else(|error| error_response(RpcErrorMetadata::from(error).message));
+    Json(response).into_response()
+}
+
+/// Executes the requested operation of the document, returning the GraphQL response
+pub fn execute(request: &GraphQLRequest, context: &RpcApiContext) -> Value {
+    let document = match parse_query::<&str>(&request.query) {
+        Ok(document) => document,
+        Err(error) => return error_response(error.to_string()),
+    };
+    let mut operations = Vec::new();
+    let mut fragments = HashMap::new();
+    for definition in &document.definitions {
+        match definition {
+            Definition::Operation(operation) => operations.push(operation),
+            Definition::Fragment(fragment) => {
+                fragments.insert(fragment.name, fragment);
+            }
+        }
+    }
+    let operation = match (&request.operation_name, operations.as_slice()) {
+        (None, [operation]) => *operation,
+        (None, _) => {
+            return error_response(
+                "An operation name is required when the document has several operations",
+            )
+        }
+        (Some(name), _) => {
+            match operations
+                .iter()
+                .find(|operation| operation_name(operation) == Some(name.as_str()))
+            {
+                Some(operation) => *operation,
+                None => return error_response(format!("Unknown operation named {name}")),
+            }
+        }
+    };
+    let (root, variable_definitions, selection_set) = match operation {
+        OperationDefinition::SelectionSet(selection_set) => (Node::Query, &[][..], selection_set),
+        OperationDefinition::Query(query) => (
+            Node::Query,
+            &query.variable_definitions[..],
+            &query.selection_set,
+        ),
+        OperationDefinition::Mutation(mutation) => (
+            Node::Mutation,
+            &mutation.variable_definitions[..],
+            &mutation.selection_set,
+        ),
+        OperationDefinition::Subscription(_) => {
+            return error_response("Subscriptions are not supported")
+        }
+    };
+
+    let mut executor = Executor {
+        context,
+        fragments,
+        variables: request.variables.clone().unwrap_or_default(),
+        errors: Vec::new(),
+    };
+    for definition in variable_definitions {
+        if executor.variables.contains_key(definition.name) {
+            continue;
+        }
+        if let Some(default_value) = &definition.default_value {
+            let default_value = executor.value(default_value);
+            executor
+                .variables
+                .insert(definition.name.to_string(), default_value);
+        }
+    }
+    let data = executor.resolve_selection_sets(&root, &[selection_set], &mut Vec::new());
+
+    let mut response = json!({ "data": data });
+    if !executor.errors.is_empty() {
+        response["errors"] = Value::Array(executor.errors);
+    }
+    response
+}
+
+fn operation_name<'a>(operation: &OperationDefinition<'a, &'a str>) -> Option<&'a str> {
+    match operation {
+        OperationDefinition::SelectionSet(_) => None,
+        OperationDefinition::Query(query) => query.name,
+        OperationDefinition::Mutation(mutation) => mutation.name,
+        OperationDefinition::Subscription(subscription) => subscription.name,
+    }
+}
+
+fn error_response(message: impl Into<String>) -> Value {
+    json!({ "errors": [{ "message": message.into() }] })
+}
+
+/// Strings of the parsed document, borrowed from the request
+type Text<'a> = &'a str;
+
+struct Executor<'a> {
+    context: &'a RpcApiContext,
+    fragments: HashMap<&'a str, &'a FragmentDefinition<'a, Text<'a>>>,
+    variables: Map<String, Value>,
+    errors: Vec<Value>,
+}
+
+impl<'a> Executor<'a> {
+    /// Resolves the fields selected on the node, grouping the ones sharing a response key
+    fn resolve_selection_sets(
+        &mut self,
+        node: &Node,
+        selection_sets: &[&'a SelectionSet<'a, Text<'a>>],
+        path: &mut Vec<Value>,
+    ) -> Value {
+        let mut fields: Vec<(&'a str, Vec<&'a Field<'a, Text<'a>>>)> = Vec::new();
+        for selection_set in selection_sets {
+            self.collect_fields(
+                node.type_name(),
+                selection_set,
+                &mut fields,
+                &mut Vec::new(),
+            );
+        }
+        let mut object = Map::new();
+        for (key, fields) in fields {
+            path.push(Value::String(key.to_string()));
+            let value = self.resolve_field(node, &fields, path);
+            path.pop();
+            object.insert(key.to_string(), value);
+        }
+        Value::Object(object)
+    }
+
+    fn collect_fields(
+        &mut self,
+        type_name: &str,
+        selection_set: &'a SelectionSet<'a, Text<'a>>,
+        fields: &mut Vec<(&'a str, Vec<&'a Field<'a, Text<'a>>>)>,
+        visited_fragments: &mut Vec<&'a str>,
+    ) {
+        for selection in &selection_set.items {
+            match selection {
+                Selection::Field(field) => {
+                    if !self.is_included(&field.directives) {
+                        continue;
+                    }
+                    let key = field.alias.unwrap_or(field.name);
+                    match fields.iter_mut().find(|(existing, _)| *existing == key) {
+                        Some((_, same_key_fields)) => same_key_fields.push(field),
+                        None => fields.push((key, vec![field])),
+                    }
+                }
+                Selection::FragmentSpread(spread) => {
+                    if !self.is_included(&spread.directives)
+                        || visited_fragments.contains(&spread.fragment_name)
+                    {
+                        continue;
+                    }
+                    visited_fragments.push(spread.fragment_name);
+                    let Some(fragment) = self.fragments.get(spread.fragment_name).copied() else {
+                        self.error(format!("Unknown fragment {}", spread.fragment_name), &[]);
+                        continue;
+                    };
+                    if applies_to(&fragment.type_condition, type_name) {
+                        self.collect_fields(
+                            type_name,
+                            &fragment.selection_set,
+                            fields,
+                            visited_fragments,
+                        );
+                    }
+                }
+                Selection::InlineFragment(fragment) => {
+                    let applies = fragment
+                        .type_condition
+                        .as_ref()
+                        .map_or(true, |condition| applies_to(condition, type_name));
+                    if applies && self.is_included(&fragment.directives) {
+                        self.collect_fields(
+                            type_name,
+                            &fragment.selection_set,
+                            fields,
+                            visited_fragments,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Resolves a field of the node, leaving it null and recording the error when it fails
+    fn resolve_field(
+        &mut self,
+        node: &Node,
+        fields: &[&'a Field<'a, Text<'a>>],
+        path: &mut Vec<Value>,
+    ) -> Value {
+        let field = fields[0];
+        if field.name == "__typename" {
+            return Value::String(node.type_name().to_string());
+        }
+        let arguments = Arguments::new(
+            field
+                .arguments
+                .iter()
+                .map(|(name, value)| (name.to_string(), self.value(value)))
+                .collect(),
+        );
+        let selection_sets: Vec<_> = fields.iter().map(|field| &field.selection_set).collect();
+        match node.resolve(field.name, &arguments, self.context) {
+            Ok(Resolved::Value(value)) => value,
+            Ok(Resolved::Node(child)) => self.resolve_child(&child, field, &selection_sets, path),
+            Ok(Resolved::List(children)) => Value::Array(
+                children
+                    .iter()
+                    .enumerate()
+                    .map(|(index, child)| {
+                        path.push(Value::from(index));
+                        let value = self.resolve_child(child, field, &selection_sets, path);
+                        path.pop();
+                        value
+                    })
+                    .collect(),
+            ),
+            Err(error) => {
+                debug!("GraphQL field {} failed: {error:?}", field.name);
+                self.error(RpcErrorMetadata::from(error).message, path);
+                Value::Null
+            }
+        }
+    }
+
+    fn resolve_child(
+        &mut self,
+        child: &Node,
+        field: &'a Field<'a, Text<'a>>,
+        selection_sets: &[&'a SelectionSet<'a, Text<'a>>],
+        path: &mut Vec<Value>,
+    ) -> Value {
+        if selection_sets
+            .iter()
+            .all(|selection_set| selection_set.items.is_empty())
+        {
+            self.error(
+                format!(
+                    "Field {} of type {} must have a selection of subfields",
+                    field.name,
+                    child.type_name()
+                ),
+                path,
+            );
+            return Value::Null;
+        }
+        self.resolve_selection_sets(child, selection_sets, path)
+    }
+
+    /// Whether the selection is kept by its skip and include directives
+    fn is_included(&self, directives: &[Directive<'a, Text<'a>>]) -> bool {
+        directives.iter().all(|directive| {
+            let condition = directive
+                .arguments
+                .iter()
+                .find(|(name, _)| *name == "if")
+                .map(|(_, value)| self.value(value));
+            match directive.name {
+                "skip" => condition != Some(Value::Bool(true)),
+                "include" => condition != Some(Value::Bool(false)),
+                _ => true,
+            }
+        })
+    }
+
+    /// Converts an argument of the document to JSON, replacing the variables it uses
+    fn value(&self, value: &GraphQLValue<'a, Text<'a>>) -> Value {
+        match value {
+            GraphQLValue::Variable(name) => self.variables.get(*name).cloned().unwrap_or_default(),
+            GraphQLValue::Int(number) => number.as_i64().map(Value::from).unwrap_or_default(),
+            GraphQLValue::Float(number) => Value::from(*number),
+            GraphQLValue::String(string) => Value::String(string.clone()),
+            GraphQLValue::Boolean(boolean) => Value::Bool(*boolean),
+            GraphQLValue::Null => Value::Null,
+            GraphQLValue::Enum(name) => Value::String(name.to_string()),
+            GraphQLValue::List(values) => {
+                Value::Array(values.iter().map(|value| self.value(value)).collect())
+            }
+            GraphQLValue::Object(fields) => Value::Object(
+                fields
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), self.value(value)))
+                    .collect(),
+            ),
+        }
+    }
+
+    fn error(&mut self, message: String, path: &[Value]) {
+        let mut error = json!({ "message": message });
+        if !path.is_empty() {
+            error["path"] = Value::Array(path.to_vec());
+        }
+        self.errors.push(error);
+    }
+}
+
+fn applies_to<'a>(condition: &TypeCondition<'a, Text<'a>>, type_name: &str) -> bool {
+    let TypeCondition::On(condition) = condition;
+    *condition == type_name
+}
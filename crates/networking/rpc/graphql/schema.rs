@@ -0,0 +1,1030 @@
+use bytes::Bytes;
+use ethrex_core::{
+    types::{
+        calculate_base_fee_per_gas, AccessListEntry, Block, BlockBody, BlockHash, BlockHeader,
+        BlockNumber, GenericTransaction, Transaction, TxKind, Withdrawal,
+    },
+    Address, BigEndianHash, H256, U256,
+};
+use ethrex_rlp::encode::RLPEncode;
+use ethrex_storage::Store;
+use ethrex_vm::StateOverride;
+use serde_json::{Map, Value};
+use std::future::Future;
+use tokio::runtime::Handle;
+
+use crate::{
+    eth::{
+        block::get_all_block_rpc_receipts,
+        gas_price::{GasPrice, MaxPriorityFee},
+        logs::{fetch_logs_with_filter, AddressFilter, LogsFilter, TopicFilter},
+        transaction::{call_on_block, EstimateGasRequest},
+    },
+    types::{
+        block_identifier::{BlockIdentifier, BlockTag},
+        receipt::{RpcLog, RpcReceipt},
+        transaction::SendRawTransactionRequest,
+    },
+    utils::{parse_json_hex, RpcErr},
+    RpcApiContext, RpcHandler,
+};
+
+/// Most blocks a single `blocks` field can return, so a query can't load the whole chain
+const MAX_BLOCKS_RANGE: u64 = 1024;
+
+/// Objects of the schema, which resolve the fields selected on them
+pub enum Node {
+    Query,
+    Mutation,
+    Pending,
+    Block(Box<BlockNode>),
+    Transaction(Box<TransactionNode>),
+    Log(Box<RpcLog>),
+    Account(AccountNode),
+    AccessTuple(AccessListEntry),
+    CallResult(CallResultNode),
+    Withdrawal(Withdrawal),
+}
+
+/// What a field resolves to, the objects in it are resolved with the field's selection
+pub enum Resolved {
+    Value(Value),
+    Node(Node),
+    List(Vec<Node>),
+}
+
+impl From<Option<Resolved>> for Resolved {
+    fn from(resolved: Option<Resolved>) -> Self {
+        resolved.unwrap_or(Resolved::Value(Value::Null))
+    }
+}
+
+impl From<Node> for Resolved {
+    fn from(node: Node) -> Self {
+        Resolved::Node(node)
+    }
+}
+
+pub struct BlockNode {
+    block: Block,
+}
+
+pub struct TransactionNode {
+    transaction: Transaction,
+    hash: H256,
+    /// Block number, block hash and index of the transaction, unless it's still in the mempool
+    location: Option<(BlockNumber, BlockHash, u64)>,
+}
+
+pub struct AccountNode {
+    address: Address,
+    /// Block whose post-state the account is read from
+    block_hash: BlockHash,
+}
+
+pub struct CallResultNode {
+    data: Bytes,
+    gas_used: u64,
+    status: u64,
+}
+
+impl Node {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Node::Query => "Query",
+            Node::Mutation => "Mutation",
+            Node::Pending => "Pending",
+            Node::Block(_) => "Block",
+            Node::Transaction(_) => "Transaction",
+            Node::Log(_) => "Log",
+            Node::Account(_) => "Account",
+            Node::AccessTuple(_) => "AccessTuple",
+            Node::CallResult(_) => "CallResult",
+            Node::Withdrawal(_) => "Withdrawal",
+        }
+    }
+
+    pub fn resolve(
+        &self,
+        field: &str,
+        args: &Arguments,
+        context: &RpcApiContext,
+    ) -> Result<Resolved, RpcErr> {
+        let resolved = match self {
+            Node::Query => resolve_query(field, args, context),
+            Node::Mutation => resolve_mutation(field, args, context),
+            Node::Pending => resolve_pending(field, args, context),
+            Node::Block(block) => block.resolve(field, args, context),
+            Node::Transaction(transaction) => transaction.resolve(field, args, context),
+            Node::Log(log) => resolve_log(log, field, args, context),
+            Node::Account(account) => account.resolve(field, args, &context.storage),
+            Node::AccessTuple(entry) => Ok(match field {
+                "address" => address(entry.address),
+                "storageKeys" => list(entry.storage_keys.iter().copied().map(bytes32)),
+                _ => None,
+            }),
+            Node::CallResult(result) => Ok(match field {
+                "data" => bytes(&result.data),
+                "gasUsed" => long(result.gas_used),
+                "status" => long(result.status),
+                _ => None,
+            }),
+            Node::Withdrawal(withdrawal) => Ok(match field {
+                "index" => long(withdrawal.index),
+                "validator" => long(withdrawal.validator_index),
+                "address" => address(withdrawal.address),
+                "amount" => long(withdrawal.amount),
+                _ => None,
+            }),
+        }?;
+        resolved.ok_or_else(|| {
+            RpcErr::BadParams(format!(
+                "Unknown field {field} on type {}",
+                self.type_name()
+            ))
+        })
+    }
+}
+
+// Resolvers return `None` for the fields the type doesn't have, and `Some(Resolved)` holding a
+// null value for the ones that have no value.
+
+fn resolve_query(
+    field: &str,
+    args: &Arguments,
+    context: &RpcApiContext,
+) -> Result<Option<Resolved>, RpcErr> {
+    let storage = &context.storage;
+    Ok(match field {
+        "block" => {
+            let block = match (args.long("number")?, args.bytes32("hash")?) {
+                (Some(_), Some(_)) => {
+                    return Err(RpcErr::BadParams(
+                        "Only one of number and hash can be given".to_owned(),
+                    ))
+                }
+                (_, Some(hash)) => storage.get_block_by_hash(hash)?,
+                (number, None) => {
+                    let block = number
+                        .map(BlockIdentifier::Number)
+                        .unwrap_or(BlockIdentifier::Tag(BlockTag::Latest));
+                    canonical_block(&block, storage)?
+                }
+            };
+            optional_node(block.map(BlockNode::node))
+        }
+        "blocks" => {
+            let from = args.required("from", Arguments::long)?;
+            let to = match args.long("to")? {
+                Some(to) => to,
+                None => latest_header(storage)?.number,
+            };
+            if to.saturating_sub(from) >= MAX_BLOCKS_RANGE {
+                return Err(RpcErr::BadParams(format!(
+                    "At most {MAX_BLOCKS_RANGE} blocks can be queried at once"
+                )));
+            }
+            let mut blocks = Vec::new();
+            for number in from..=to {
+                match canonical_block(&BlockIdentifier::Number(number), storage)? {
+                    Some(block) => blocks.push(BlockNode::node(block)),
+                    None => break,
+                }
+            }
+            list_nodes(blocks.into_iter())
+        }
+        "pending" => Some(Node::Pending.into()),
+        "transaction" => {
+            let hash = args.required("hash", Arguments::bytes32)?;
+            optional_node(TransactionNode::find(hash, storage)?.map(TransactionNode::node))
+        }
+        "logs" => {
+            let filter = args.required("filter", Arguments::object)?;
+            let block = |name| -> Result<BlockIdentifier, RpcErr> {
+                Ok(filter
+                    .long(name)?
+                    .map(BlockIdentifier::Number)
+                    .unwrap_or(BlockIdentifier::Tag(BlockTag::Latest)))
+            };
+            let (addresses, topics) = log_criteria(&filter)?;
+            let filter = LogsFilter {
+                from_block: block("fromBlock")?,
+                to_block: block("toBlock")?,
+                address_filters: (!addresses.is_empty()).then_some(AddressFilter::Many(addresses)),
+                topics: topics
+                    .into_iter()
+                    .map(|topics| match topics.as_slice() {
+                        [] => TopicFilter::Topic(None),
+                        _ => TopicFilter::Topics(topics.into_iter().map(Some).collect()),
+                    })
+                    .collect(),
+            };
+            let logs = fetch_logs_with_filter(&filter, storage.clone())?;
+            list_nodes(logs.into_iter().map(log_node))
+        }
+        // Both handlers already return hex quantities
+        "gasPrice" => Some(Resolved::Value(block_on(GasPrice.handle(context.clone()))?)),
+        "maxPriorityFeePerGas" => Some(Resolved::Value(block_on(
+            MaxPriorityFee.handle(context.clone()),
+        )?)),
+        // The node doesn't report sync progress, as eth_syncing
+        "syncing" => null(),
+        "chainID" => big_int(storage.get_chain_config()?.chain_id),
+        _ => None,
+    })
+}
+
+fn resolve_mutation(
+    field: &str,
+    args: &Arguments,
+    context: &RpcApiContext,
+) -> Result<Option<Resolved>, RpcErr> {
+    Ok(match field {
+        "sendRawTransaction" => {
+            let data = args.required("data", Arguments::bytes)?;
+            let params = Some(vec![Value::String(format!("0x{}", hex::encode(data)))]);
+            let request = SendRawTransactionRequest::parse(&params)?;
+            Some(Resolved::Value(block_on(request.handle(context.clone()))?))
+        }
+        _ => None,
+    })
+}
+
+fn resolve_pending(
+    field: &str,
+    args: &Arguments,
+    context: &RpcApiContext,
+) -> Result<Option<Resolved>, RpcErr> {
+    let pending = BlockIdentifier::Tag(BlockTag::Pending);
+    Ok(match field {
+        "transactionCount" => long(mempool_transactions(&context.storage)?.len() as u64),
+        "transactions" => list_nodes(
+            mempool_transactions(&context.storage)?
+                .into_iter()
+                .map(|transaction| TransactionNode::node(TransactionNode::pending(transaction))),
+        ),
+        "account" => {
+            let header = block_header(&pending, &context.storage)?;
+            Some(
+                AccountNode::node(
+                    args.required("address", Arguments::address)?,
+                    header.compute_block_hash(),
+                )
+                .into(),
+            )
+        }
+        "call" => Some(call(
+            args,
+            block_header(&pending, &context.storage)?,
+            context,
+        )?),
+        "estimateGas" => Some(estimate_gas(args, pending, context)?),
+        _ => None,
+    })
+}
+
+impl BlockNode {
+    fn node(block: Block) -> Node {
+        Node::Block(Box::new(BlockNode { block }))
+    }
+
+    fn resolve(
+        &self,
+        field: &str,
+        args: &Arguments,
+        context: &RpcApiContext,
+    ) -> Result<Option<Resolved>, RpcErr> {
+        let storage = &context.storage;
+        let header = &self.block.header;
+        let body = &self.block.body;
+        Ok(match field {
+            "number" => long(header.number),
+            "hash" => bytes32(self.block.hash()),
+            "parent" => optional_node(
+                (header.number != 0)
+                    .then(|| storage.get_block_by_hash(header.parent_hash))
+                    .transpose()?
+                    .flatten()
+                    .map(BlockNode::node),
+            ),
+            "nonce" => bytes(&header.nonce.to_be_bytes()),
+            "transactionsRoot" => bytes32(header.transactions_root),
+            "transactionCount" => long(body.transactions.len() as u64),
+            "stateRoot" => bytes32(header.state_root),
+            "receiptsRoot" => bytes32(header.receipts_root),
+            "miner" => Some(
+                AccountNode::node(
+                    header.coinbase,
+                    state_block(args, self.block.hash(), storage)?,
+                )
+                .into(),
+            ),
+            "extraData" => bytes(&header.extra_data),
+            "gasLimit" => long(header.gas_limit),
+            "gasUsed" => long(header.gas_used),
+            "baseFeePerGas" => optional(header.base_fee_per_gas.map(big_int)),
+            "nextBaseFeePerGas" => optional(
+                header
+                    .base_fee_per_gas
+                    .and_then(|base_fee| {
+                        calculate_base_fee_per_gas(
+                            header.gas_limit,
+                            header.gas_limit,
+                            header.gas_used,
+                            base_fee,
+                        )
+                    })
+                    .map(big_int),
+            ),
+            "timestamp" => long(header.timestamp),
+            "logsBloom" => bytes(header.logs_bloom.as_bytes()),
+            "mixHash" => bytes32(header.prev_randao),
+            "difficulty" => big_int(header.difficulty),
+            "totalDifficulty" => big_int(
+                storage
+                    .get_block_total_difficulty(self.block.hash())?
+                    .unwrap_or_default(),
+            ),
+            "ommerCount" => long(body.ommers.len() as u64),
+            "ommers" => list_nodes(body.ommers.iter().map(ommer_node)),
+            "ommerAt" => {
+                let index = args.required("index", Arguments::long)?;
+                optional_node(body.ommers.get(index as usize).map(ommer_node))
+            }
+            "ommerHash" => bytes32(header.ommers_hash),
+            "transactions" => list_nodes(self.transactions()),
+            "transactionAt" => {
+                let index = args.required("index", Arguments::long)?;
+                optional_node(self.transactions().nth(index as usize))
+            }
+            "logs" => {
+                let filter = args.required("filter", Arguments::object)?;
+                let (addresses, topics) = log_criteria(&filter)?;
+                let receipts = get_all_block_rpc_receipts(
+                    header.number,
+                    header.clone(),
+                    body.clone(),
+                    storage,
+                )?;
+                list_nodes(
+                    receipts
+                        .into_iter()
+                        .flat_map(|receipt| receipt.logs)
+                        .filter(|log| log_matches(log, &addresses, &topics))
+                        .map(log_node),
+                )
+            }
+            "account" => Some(
+                AccountNode::node(
+                    args.required("address", Arguments::address)?,
+                    self.block.hash(),
+                )
+                .into(),
+            ),
+            "call" => Some(call(args, header.clone(), context)?),
+            "estimateGas" => Some(estimate_gas(
+                args,
+                BlockIdentifier::Number(header.number),
+                context,
+            )?),
+            "rawHeader" => bytes(&header.encode_to_vec()),
+            "raw" => bytes(&self.block.encode_to_vec()),
+            "withdrawalsRoot" => optional(header.withdrawals_root.map(bytes32)),
+            "withdrawals" => match &body.withdrawals {
+                Some(withdrawals) => list_nodes(withdrawals.iter().cloned().map(Node::Withdrawal)),
+                None => null(),
+            },
+            "blobGasUsed" => optional(header.blob_gas_used.map(long)),
+            "excessBlobGas" => optional(header.excess_blob_gas.map(long)),
+            _ => None,
+        })
+    }
+
+    fn transactions(&self) -> impl Iterator<Item = Node> + '_ {
+        let (number, hash) = (self.block.header.number, self.block.hash());
+        self.block
+            .body
+            .transactions
+            .iter()
+            .enumerate()
+            .map(move |(index, transaction)| {
+                TransactionNode::node(TransactionNode {
+                    transaction: transaction.clone(),
+                    hash: transaction.compute_hash(),
+                    location: Some((number, hash, index as u64)),
+                })
+            })
+    }
+}
+
+fn ommer_node(header: &BlockHeader) -> Node {
+    BlockNode::node(Block::new(header.clone(), BlockBody::default()))
+}
+
+impl TransactionNode {
+    fn node(transaction: TransactionNode) -> Node {
+        Node::Transaction(Box::new(transaction))
+    }
+
+    fn pending(transaction: Transaction) -> Self {
+        TransactionNode {
+            hash: transaction.compute_hash(),
+            transaction,
+            location: None,
+        }
+    }
+
+    /// Looks the transaction up in the chain first and in the mempool then
+    fn find(hash: H256, storage: &Store) -> Result<Option<Self>, RpcErr> {
+        if let Some((number, block_hash, index)) = storage.get_transaction_location(hash)? {
+            return Ok(storage.get_transaction_by_location(block_hash, index)?.map(
+                |transaction| TransactionNode {
+                    transaction,
+                    hash,
+                    location: Some((number, block_hash, index)),
+                },
+            ));
+        }
+        Ok(mempool_transactions(storage)?
+            .into_iter()
+            .find(|transaction| transaction.compute_hash() == hash)
+            .map(TransactionNode::pending))
+    }
+
+    fn resolve(
+        &self,
+        field: &str,
+        args: &Arguments,
+        context: &RpcApiContext,
+    ) -> Result<Option<Resolved>, RpcErr> {
+        let storage = &context.storage;
+        let transaction = &self.transaction;
+        Ok(match field {
+            "hash" => bytes32(self.hash),
+            "nonce" => long(transaction.nonce()),
+            "index" => optional(self.location.map(|(_, _, index)| long(index))),
+            "from" => Some(
+                AccountNode::node(transaction.sender(), latest_state_block(args, storage)?).into(),
+            ),
+            "to" => match transaction.to() {
+                TxKind::Call(to) => {
+                    Some(AccountNode::node(to, latest_state_block(args, storage)?).into())
+                }
+                TxKind::Create => null(),
+            },
+            "value" => big_int(transaction.value()),
+            "gasPrice" => {
+                let price = match self.base_fee(storage)? {
+                    Some(base_fee) => transaction.effective_gas_price(Some(base_fee)),
+                    None => None,
+                };
+                big_int(price.unwrap_or(transaction.gas_price()))
+            }
+            "maxFeePerGas" => optional(transaction.max_fee_per_gas().map(big_int)),
+            "maxPriorityFeePerGas" => optional(transaction.max_priority_fee().map(big_int)),
+            "maxFeePerBlobGas" => optional(transaction.max_fee_per_blob_gas().map(big_int)),
+            "effectiveTip" => optional(
+                self.base_fee(storage)?
+                    .and_then(|base_fee| transaction.effective_gas_tip(Some(base_fee)))
+                    .map(big_int),
+            ),
+            "gas" => long(transaction.gas_limit()),
+            "inputData" => bytes(transaction.data()),
+            "block" => optional_node(
+                self.location
+                    .map(|(_, block_hash, _)| storage.get_block_by_hash(block_hash))
+                    .transpose()?
+                    .flatten()
+                    .map(BlockNode::node),
+            ),
+            "status" => optional(
+                self.receipt(storage)?
+                    .map(|receipt| long(receipt.receipt.status as u64)),
+            ),
+            "gasUsed" => optional(
+                self.receipt(storage)?
+                    .map(|receipt| long(receipt.tx_info.gas_used)),
+            ),
+            "cumulativeGasUsed" => optional(
+                self.receipt(storage)?
+                    .map(|receipt| long(receipt.receipt.cumulative_gas_used)),
+            ),
+            "effectiveGasPrice" => optional(
+                self.receipt(storage)?
+                    .map(|receipt| big_int(receipt.tx_info.effective_gas_price)),
+            ),
+            "blobGasUsed" => optional(
+                self.receipt(storage)?
+                    .and_then(|receipt| receipt.tx_info.blob_gas_used.map(long)),
+            ),
+            "blobGasPrice" => optional(
+                self.receipt(storage)?
+                    .and_then(|receipt| receipt.tx_info.blob_gas_price.map(big_int)),
+            ),
+            "createdContract" => {
+                let block_hash = latest_state_block(args, storage)?;
+                optional_node(
+                    self.receipt(storage)?
+                        .and_then(|receipt| receipt.tx_info.contract_address)
+                        .map(|contract| AccountNode::node(contract, block_hash)),
+                )
+            }
+            "logs" => match self.receipt(storage)? {
+                Some(receipt) => list_nodes(receipt.logs.into_iter().map(log_node)),
+                None => null(),
+            },
+            "r" => big_int(signature(transaction).0),
+            "s" => big_int(signature(transaction).1),
+            "v" => big_int(signature(transaction).2),
+            "yParity" => optional(signature(transaction).3.map(big_int)),
+            "type" => long(transaction.tx_type() as u64),
+            "accessList" => match transaction {
+                Transaction::LegacyTransaction(_) => null(),
+                _ => list_nodes(
+                    transaction
+                        .access_list()
+                        .iter()
+                        .map(|entry| Node::AccessTuple(entry.into())),
+                ),
+            },
+            "raw" => bytes(&transaction.encode_canonical_to_vec()),
+            "rawReceipt" => match self.location {
+                Some((number, _, index)) => optional(
+                    storage
+                        .get_receipt(number, index)?
+                        .map(|receipt| bytes(&receipt.encode_to_vec())),
+                ),
+                None => null(),
+            },
+            "blobVersionedHashes" => match transaction {
+                Transaction::EIP4844Transaction(transaction) => list(
+                    transaction
+                        .blob_versioned_hashes
+                        .iter()
+                        .copied()
+                        .map(bytes32),
+                ),
+                _ => null(),
+            },
+            _ => None,
+        })
+    }
+
+    /// Base fee of the block holding the transaction, if it was included in one
+    fn base_fee(&self, storage: &Store) -> Result<Option<u64>, RpcErr> {
+        let Some((_, block_hash, _)) = self.location else {
+            return Ok(None);
+        };
+        Ok(storage
+            .get_block_header_by_hash(block_hash)?
+            .and_then(|header| header.base_fee_per_gas))
+    }
+
+    fn receipt(&self, storage: &Store) -> Result<Option<RpcReceipt>, RpcErr> {
+        let Some((number, block_hash, index)) = self.location else {
+            return Ok(None);
+        };
+        let Some(block) = storage.get_block_by_hash(block_hash)? else {
+            return Ok(None);
+        };
+        let receipts = get_all_block_rpc_receipts(number, block.header, block.body, storage)?;
+        Ok(receipts.into_iter().nth(index as usize))
+    }
+}
+
+/// r, s, v and y parity of the transaction, which only typed transactions have
+fn signature(transaction: &Transaction) -> (U256, U256, U256, Option<U256>) {
+    let (r, s, y_parity) = match transaction {
+        Transaction::LegacyTransaction(transaction) => {
+            return (transaction.r, transaction.s, transaction.v, None)
+        }
+        Transaction::EIP2930Transaction(transaction) => (
+            transaction.signature_r,
+            transaction.signature_s,
+            transaction.signature_y_parity,
+        ),
+        Transaction::EIP1559Transaction(transaction) => (
+            transaction.signature_r,
+            transaction.signature_s,
+            transaction.signature_y_parity,
+        ),
+        Transaction::EIP4844Transaction(transaction) => (
+            transaction.signature_r,
+            transaction.signature_s,
+            transaction.signature_y_parity,
+        ),
+        Transaction::PrivilegedL2Transaction(transaction) => (
+            transaction.signature_r,
+            transaction.signature_s,
+            transaction.signature_y_parity,
+        ),
+    };
+    let y_parity = U256::from(y_parity as u8);
+    (r, s, y_parity, Some(y_parity))
+}
+
+fn log_node(log: RpcLog) -> Node {
+    Node::Log(Box::new(log))
+}
+
+fn resolve_log(
+    log: &RpcLog,
+    field: &str,
+    args: &Arguments,
+    context: &RpcApiContext,
+) -> Result<Option<Resolved>, RpcErr> {
+    let storage = &context.storage;
+    Ok(match field {
+        "index" => long(log.log_index),
+        "account" => {
+            Some(AccountNode::node(log.log.address, latest_state_block(args, storage)?).into())
+        }
+        "topics" => list(log.log.topics.iter().copied().map(bytes32)),
+        "data" => bytes(&log.log.data),
+        "transaction" => {
+            let transaction = storage
+                .get_transaction_by_location(log.block_hash, log.transaction_index)?
+                .ok_or(RpcErr::Internal(
+                    "Could not get the transaction of the log".to_owned(),
+                ))?;
+            Some(
+                TransactionNode::node(TransactionNode {
+                    transaction,
+                    hash: log.transaction_hash,
+                    location: Some((log.block_number, log.block_hash, log.transaction_index)),
+                })
+                .into(),
+            )
+        }
+        _ => None,
+    })
+}
+
+/// Whether the log is emitted by one of the addresses and has the topics of the criteria,
+/// where an empty set of topics matches any topic in its position
+fn log_matches(log: &RpcLog, addresses: &[Address], topics: &[Vec<H256>]) -> bool {
+    if !addresses.is_empty() && !addresses.contains(&log.log.address) {
+        return false;
+    }
+    topics.iter().enumerate().all(|(position, topics)| {
+        topics.is_empty()
+            || log
+                .log
+                .topics
+                .get(position)
+                .is_some_and(|topic| topics.contains(topic))
+    })
+}
+
+/// Addresses and topics of the log filter criteria
+fn log_criteria(filter: &Arguments) -> Result<(Vec<Address>, Vec<Vec<H256>>), RpcErr> {
+    let addresses = filter
+        .list("addresses")?
+        .unwrap_or_default()
+        .iter()
+        .map(|address| parse_address(address).ok_or_else(|| bad_argument("addresses")))
+        .collect::<Result<_, _>>()?;
+    let topics = filter
+        .list("topics")?
+        .unwrap_or_default()
+        .iter()
+        .map(|topics| {
+            topics
+                .as_array()
+                .ok_or_else(|| bad_argument("topics"))?
+                .iter()
+                .map(|topic| parse_bytes32(topic).ok_or_else(|| bad_argument("topics")))
+                .collect()
+        })
+        .collect::<Result<_, _>>()?;
+    Ok((addresses, topics))
+}
+
+impl AccountNode {
+    fn node(address: Address, block_hash: BlockHash) -> Node {
+        Node::Account(AccountNode {
+            address,
+            block_hash,
+        })
+    }
+
+    fn resolve(
+        &self,
+        field: &str,
+        args: &Arguments,
+        storage: &Store,
+    ) -> Result<Option<Resolved>, RpcErr> {
+        // Accounts missing from the state are shown as empty ones
+        let account_info = || {
+            storage
+                .get_account_info_by_hash(self.block_hash, self.address)
+                .map(Option::unwrap_or_default)
+        };
+        Ok(match field {
+            "address" => address(self.address),
+            "balance" => big_int(account_info()?.balance),
+            "transactionCount" => long(account_info()?.nonce),
+            "code" => {
+                let code = storage
+                    .get_account_code(account_info()?.code_hash)?
+                    .unwrap_or_default();
+                bytes(&code)
+            }
+            "storage" => {
+                let slot = args.required("slot", Arguments::bytes32)?;
+                let value = storage
+                    .get_storage_at_hash(self.block_hash, self.address, slot)?
+                    .unwrap_or_default();
+                bytes32(H256::from_uint(&value))
+            }
+            _ => None,
+        })
+    }
+}
+
+/// Runs the call data of the arguments on top of the block, returning the result even if the
+/// call reverted
+fn call(
+    args: &Arguments,
+    header: BlockHeader,
+    context: &RpcApiContext,
+) -> Result<Resolved, RpcErr> {
+    let transaction = call_data(args)?;
+    let result = call_on_block(
+        &transaction,
+        header,
+        context.storage.clone(),
+        context.evm.engine,
+    )?;
+    Ok(Resolved::Node(Node::CallResult(CallResultNode {
+        data: result.output(),
+        gas_used: result.gas_used(),
+        status: result.is_success() as u64,
+    })))
+}
+
+fn estimate_gas(
+    args: &Arguments,
+    block: BlockIdentifier,
+    context: &RpcApiContext,
+) -> Result<Resolved, RpcErr> {
+    let request = EstimateGasRequest {
+        transaction: call_data(args)?,
+        block: Some(block),
+        state_overrides: StateOverride::default(),
+        block_overrides: None,
+    };
+    let estimation = block_on(request.handle(context.clone()))?;
+    let gas = parse_json_hex(&estimation).map_err(RpcErr::Internal)?;
+    Ok(long(gas).into())
+}
+
+/// The CallData input of calls and gas estimations
+fn call_data(args: &Arguments) -> Result<GenericTransaction, RpcErr> {
+    let data = args.required("data", Arguments::object)?;
+    let fee = |name| -> Result<Option<u64>, RpcErr> {
+        data.big_int(name)?
+            .map(|fee| u64::try_from(fee).map_err(|_| bad_argument(name)))
+            .transpose()
+    };
+    let access_list = data
+        .list("accessList")?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|entry| {
+            let entry = match entry {
+                Value::Object(entry) => Arguments::new(entry),
+                _ => return Err(bad_argument("accessList")),
+            };
+            Ok(AccessListEntry {
+                address: entry.required("address", Arguments::address)?,
+                storage_keys: entry
+                    .list("storageKeys")?
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|key| parse_bytes32(key).ok_or_else(|| bad_argument("storageKeys")))
+                    .collect::<Result<_, _>>()?,
+            })
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(GenericTransaction {
+        from: data.address("from")?.unwrap_or_default(),
+        to: data
+            .address("to")?
+            .map(TxKind::Call)
+            .unwrap_or(TxKind::Create),
+        gas: data.long("gas")?,
+        gas_price: fee("gasPrice")?.unwrap_or_default(),
+        max_fee_per_gas: fee("maxFeePerGas")?,
+        max_priority_fee_per_gas: fee("maxPriorityFeePerGas")?,
+        value: data.big_int("value")?.unwrap_or_default(),
+        input: data.bytes("data")?.unwrap_or_default(),
+        access_list,
+        ..Default::default()
+    })
+}
+
+/// Block whose post-state accounts are read from, given by the optional block argument
+fn state_block(args: &Arguments, default: BlockHash, storage: &Store) -> Result<BlockHash, RpcErr> {
+    let Some(number) = args.long("block")? else {
+        return Ok(default);
+    };
+    storage
+        .get_canonical_block_hash(number)?
+        .ok_or(RpcErr::BadParams(format!("Unknown block {number}")))
+}
+
+/// Same as [`state_block`], reading the latest state when no block is given
+fn latest_state_block(args: &Arguments, storage: &Store) -> Result<BlockHash, RpcErr> {
+    state_block(args, latest_header(storage)?.compute_block_hash(), storage)
+}
+
+fn canonical_block(block: &BlockIdentifier, storage: &Store) -> Result<Option<Block>, RpcErr> {
+    let Some(number) = block.resolve_block_number(storage)? else {
+        return Ok(None);
+    };
+    let (Some(header), Some(body)) = (
+        storage.get_block_header(number)?,
+        storage.get_block_body(number)?,
+    ) else {
+        return Ok(None);
+    };
+    Ok(Some(Block::new(header, body)))
+}
+
+fn block_header(block: &BlockIdentifier, storage: &Store) -> Result<BlockHeader, RpcErr> {
+    block.resolve_block_header(storage)?.ok_or(RpcErr::Internal(
+        "Could not get the block header".to_owned(),
+    ))
+}
+
+fn latest_header(storage: &Store) -> Result<BlockHeader, RpcErr> {
+    block_header(&BlockIdentifier::Tag(BlockTag::Latest), storage)
+}
+
+fn mempool_transactions(storage: &Store) -> Result<Vec<Transaction>, RpcErr> {
+    Ok(storage
+        .filter_pool_transactions(&|_| true)?
+        .into_values()
+        .flatten()
+        .map(Transaction::from)
+        .collect())
+}
+
+/// Runs the future of an eth handler, the executor already runs on a blocking thread
+fn block_on<F: Future>(future: F) -> F::Output {
+    Handle::current().block_on(future)
+}
+
+// Scalars of the schema. Long and BigInt values are hex quantities, as geth serves them, the
+// others are hex strings of fixed (Bytes32, Address) or any (Bytes) length.
+
+fn long(value: u64) -> Option<Resolved> {
+    Some(Resolved::Value(Value::String(format!("{value:#x}"))))
+}
+
+fn big_int(value: impl Into<U256>) -> Option<Resolved> {
+    Some(Resolved::Value(Value::String(format!(
+        "{:#x}",
+        value.into()
+    ))))
+}
+
+fn bytes32(value: H256) -> Option<Resolved> {
+    Some(Resolved::Value(Value::String(format!("{value:#x}"))))
+}
+
+fn address(value: Address) -> Option<Resolved> {
+    Some(Resolved::Value(Value::String(format!("{value:#x}"))))
+}
+
+fn bytes(value: &[u8]) -> Option<Resolved> {
+    Some(Resolved::Value(Value::String(format!(
+        "0x{}",
+        hex::encode(value)
+    ))))
+}
+
+fn null() -> Option<Resolved> {
+    Some(Resolved::Value(Value::Null))
+}
+
+fn optional(value: Option<Option<Resolved>>) -> Option<Resolved> {
+    Some(value.flatten().into())
+}
+
+fn list(values: impl Iterator<Item = Option<Resolved>>) -> Option<Resolved> {
+    Some(Resolved::Value(Value::Array(
+        values
+            .flatten()
+            .map(|value| match value {
+                Resolved::Value(value) => value,
+                _ => Value::Null,
+            })
+            .collect(),
+    )))
+}
+
+fn optional_node(node: Option<Node>) -> Option<Resolved> {
+    Some(node.map(Resolved::Node).into())
+}
+
+fn list_nodes(nodes: impl Iterator<Item = Node>) -> Option<Resolved> {
+    Some(Resolved::List(nodes.collect()))
+}
+
+/// Arguments of a field, with the variables of the request already replaced
+pub struct Arguments(Map<String, Value>);
+
+impl Arguments {
+    pub fn new(arguments: Map<String, Value>) -> Self {
+        Self(arguments)
+    }
+
+    fn parse<T>(&self, name: &str, parser: fn(&Value) -> Option<T>) -> Result<Option<T>, RpcErr> {
+        match self.0.get(name) {
+            None | Some(Value::Null) => Ok(None),
+            Some(value) => parser(value).map(Some).ok_or_else(|| bad_argument(name)),
+        }
+    }
+
+    fn required<T>(
+        &self,
+        name: &str,
+        getter: fn(&Self, &str) -> Result<Option<T>, RpcErr>,
+    ) -> Result<T, RpcErr> {
+        getter(self, name)?.ok_or_else(|| RpcErr::MissingParam(name.to_owned()))
+    }
+
+    fn long(&self, name: &str) -> Result<Option<u64>, RpcErr> {
+        self.parse(name, parse_long)
+    }
+
+    fn big_int(&self, name: &str) -> Result<Option<U256>, RpcErr> {
+        self.parse(name, parse_big_int)
+    }
+
+    fn address(&self, name: &str) -> Result<Option<Address>, RpcErr> {
+        self.parse(name, parse_address)
+    }
+
+    fn bytes32(&self, name: &str) -> Result<Option<H256>, RpcErr> {
+        self.parse(name, parse_bytes32)
+    }
+
+    fn bytes(&self, name: &str) -> Result<Option<Bytes>, RpcErr> {
+        self.parse(name, parse_bytes)
+    }
+
+    fn list(&self, name: &str) -> Result<Option<Vec<Value>>, RpcErr> {
+        self.parse(name, |value| value.as_array().cloned())
+    }
+
+    fn object(&self, name: &str) -> Result<Option<Arguments>, RpcErr> {
+        self.parse(name, |value| value.as_object().cloned().map(Arguments))
+    }
+}
+
+fn bad_argument(name: &str) -> RpcErr {
+    RpcErr::BadParams(format!("Invalid value for argument {name}"))
+}
+
+/// Longs are given as numbers or as hex or decimal strings
+fn parse_long(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(number) => number.as_u64(),
+        Value::String(string) => match string.strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(hex, 16).ok(),
+            None => string.parse().ok(),
+        },
+        _ => None,
+    }
+}
+
+fn parse_big_int(value: &Value) -> Option<U256> {
+    match value {
+        Value::Number(number) => number.as_u64().map(U256::from),
+        Value::String(string) => match string.strip_prefix("0x") {
+            Some(hex) => U256::from_str_radix(hex, 16).ok(),
+            None => U256::from_dec_str(string).ok(),
+        },
+        _ => None,
+    }
+}
+
+fn parse_address(value: &Value) -> Option<Address> {
+    parse_bytes(value)
+        .filter(|bytes| bytes.len() == Address::len_bytes())
+        .map(|bytes| Address::from_slice(&bytes))
+}
+
+fn parse_bytes32(value: &Value) -> Option<H256> {
+    parse_bytes(value)
+        .filter(|bytes| bytes.len() == H256::len_bytes())
+        .map(|bytes| H256::from_slice(&bytes))
+}
+
+fn parse_bytes(value: &Value) -> Option<Bytes> {
+    let hex = value.as_str()?.strip_prefix("0x")?;
+    hex::decode(hex).ok().map(Bytes::from)
+}
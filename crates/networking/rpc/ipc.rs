@@ -0,0 +1,149 @@
+use std::{
+    fs::{DirBuilder, Permissions},
+    io::{Error, ErrorKind},
+    os::unix::fs::{DirBuilderExt, FileTypeExt, PermissionsExt},
+    path::Path,
+};
+
+use serde_json::{Deserializer, Value};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{UnixListener, UnixStream},
+};
+use tracing::{debug, info};
+
+use crate::{
+    handle_body, map_ipc_requests, rpc_response,
+    utils::{RpcErr, RpcRequestId},
+    RpcApiContext,
+};
+
+/// Largest request a connection may send, bigger ones get an error and the connection closed
+const MAX_REQUEST_SIZE: usize = 32 * 1024 * 1024;
+
+/// Serves the JSON-RPC API on a unix socket at the given path. Each connection sends a stream
+/// of requests and batches, without delimiters, and gets a response per line. As it serves the
+/// engine and personal namespaces, only the owner can connect to the socket.
+pub async fn serve_ipc(path: &Path, context: RpcApiContext) -> std::io::Result<()> {
+    remove_stale_socket(path)?;
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    std::fs::create_dir_all(parent)?;
+    // The socket is created according to the umask, usually letting anyone connect. It's bound
+    // in a directory only the owner can enter and moved into place once its permissions are set
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let private_dir = parent.join(format!(".{file_name}.{}", std::process::id()));
+    DirBuilder::new().mode(0o700).create(&private_dir)?;
+    let bound_path = private_dir.join("socket");
+    let listener = UnixListener::bind(&bound_path).and_then(|listener| {
+        std::fs::set_permissions(&bound_path, Permissions::from_mode(0o600))?;
+        std::fs::rename(&bound_path, path)?;
+        Ok(listener)
+    });
+    let _ = std::fs::remove_file(&bound_path);
+    std::fs::remove_dir(&private_dir)?;
+    let listener = listener?;
+    info!("Starting IPC server at {}", path.display());
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let context = context.clone();
+        tokio::task::spawn(async move {
+            if let Err(error) = handle_connection(stream, context).await {
+                debug!("IPC connection closed: {error}");
+            }
+        });
+    }
+}
+
+/// Removes the socket left at the path by a node that stopped, as it would make the bind fail.
+/// Fails if the path holds something else, or a socket a running node listens on.
+fn remove_stale_socket(path: &Path) -> std::io::Result<()> {
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return Ok(());
+    };
+    if !metadata.file_type().is_socket() {
+        return Err(Error::new(
+            ErrorKind::AlreadyExists,
+            format!("{} exists and isn't a socket", path.display()),
+        ));
+    }
+    match std::os::unix::net::UnixStream::connect(path) {
+        Ok(_) => Err(Error::new(
+            ErrorKind::AddrInUse,
+            format!("{} is used by a running node", path.display()),
+        )),
+        Err(error) if error.kind() == ErrorKind::ConnectionRefused => std::fs::remove_file(path),
+        Err(error) => Err(error),
+    }
+}
+
+async fn handle_connection(mut stream: UnixStream, context: RpcApiContext) -> std::io::Result<()> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0; 8192];
+    loop {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            return Ok(());
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+
+        let (bodies, parse_error) = read_bodies(&mut buffer);
+        for body in bodies {
+            let batch_request_limit = context.batch_request_limit;
+            let response = handle_body(body, batch_request_limit, |req| {
+                let context = context.clone();
+                async move { map_ipc_requests(&req, context).await }
+            })
+            .await;
+            if let Some(response) = response {
+                write_response(&mut stream, &response).await?;
+            }
+        }
+        // The stream can't be resynchronized after malformed input, so the connection is closed
+        let error = match parse_error {
+            Some(error) => error,
+            None if buffer.len() > MAX_REQUEST_SIZE => RpcErr::InvalidRequest(format!(
+                "request too large, the limit is {MAX_REQUEST_SIZE} bytes"
+            )),
+            None => continue,
+        };
+        let response = rpc_response(RpcRequestId::Null, Err(error)).0;
+        return write_response(&mut stream, &response).await;
+    }
+}
+
+/// Takes the complete JSON values at the start of the buffer, leaving an incomplete one there
+/// until the rest of it is read
+fn read_bodies(buffer: &mut Vec<u8>) -> (Vec<Value>, Option<RpcErr>) {
+    let mut bodies = Vec::new();
+    let mut parse_error = None;
+    let mut stream = Deserializer::from_slice(buffer).into_iter::<Value>();
+    let mut consumed = 0;
+    loop {
+        match stream.next() {
+            Some(Ok(body)) => {
+                bodies.push(body);
+                consumed = stream.byte_offset();
+            }
+            Some(Err(error)) if error.is_eof() => break,
+            Some(Err(error)) => {
+                parse_error = Some(RpcErr::ParseError(error.to_string()));
+                break;
+            }
+            None => {
+                consumed = stream.byte_offset();
+                break;
+            }
+        }
+    }
+    buffer.drain(..consumed);
+    (bodies, parse_error)
+}
+
+async fn write_response(stream: &mut UnixStream, response: &Value) -> std::io::Result<()> {
+    let mut response = serde_json::to_vec(response)?;
+    response.push(b'\n');
+    stream.write_all(&response).await
+}
@@ -0,0 +1,18 @@
+use serde_json::Value;
+
+use crate::{utils::RpcErr, RpcApiContext};
+
+pub fn version(context: RpcApiContext) -> Result<Value, RpcErr> {
+    let chain_config = context.storage.get_chain_config()?;
+    // Unlike eth_chainId, the network id is returned as a decimal string
+    Ok(Value::String(chain_config.chain_id.to_string()))
+}
+
+pub async fn peer_count(context: RpcApiContext) -> Result<Value, RpcErr> {
+    let peer_count = context.peer_table.lock().await.count_connected_peers();
+    Ok(Value::String(format!("{:#x}", peer_count)))
+}
+
+pub fn listening() -> Result<Value, RpcErr> {
+    Ok(Value::Bool(true))
+}
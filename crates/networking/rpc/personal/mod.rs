@@ -0,0 +1,205 @@
+use std::time::Duration;
+
+use ethrex_core::Address;
+use secp256k1::SecretKey;
+use serde_json::Value;
+use tracing::info;
+
+use crate::{
+    eth::sign::{
+        parse_hex_bytes, signer, SendTransactionRequest, SignRequest, SignTransactionRequest,
+    },
+    signer::recover_message_signer,
+    utils::RpcErr,
+    RpcApiContext, RpcHandler,
+};
+
+/// How long personal_unlockAccount unlocks an account for when no duration is given, as geth
+const DEFAULT_UNLOCK_DURATION: Duration = Duration::from_secs(300);
+
+pub struct NewAccountRequest {
+    pub password: String,
+}
+
+pub struct ImportRawKeyRequest {
+    pub secret_key: SecretKey,
+    pub password: String,
+}
+
+pub struct UnlockAccountRequest {
+    pub address: Address,
+    pub password: String,
+    /// Unlocked until the node stops when zero
+    pub duration: Duration,
+}
+
+pub struct LockAccountRequest {
+    pub address: Address,
+}
+
+pub struct EcRecoverRequest {
+    pub message: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+pub fn list_accounts(context: RpcApiContext) -> Result<Value, RpcErr> {
+    serde_json::to_value(signer(&context)?.accounts())
+        .map_err(|error| RpcErr::Internal(error.to_string()))
+}
+
+impl RpcHandler for NewAccountRequest {
+    fn parse(params: &Option<Vec<Value>>) -> Result<Self, RpcErr> {
+        let params = expect_params(params, 1)?;
+        Ok(NewAccountRequest {
+            password: serde_json::from_value(params[0].clone())?,
+        })
+    }
+
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+        let address = signer(&context)?.new_account(&self.password)?;
+        info!("Created account {address:#x}");
+        serde_json::to_value(address).map_err(|error| RpcErr::Internal(error.to_string()))
+    }
+}
+
+impl RpcHandler for ImportRawKeyRequest {
+    fn parse(params: &Option<Vec<Value>>) -> Result<Self, RpcErr> {
+        let params = expect_params(params, 2)?;
+        let secret_key: String = serde_json::from_value(params[0].clone())?;
+        let secret_key = hex::decode(secret_key.trim_start_matches("0x"))
+            .map_err(|error| RpcErr::BadParams(error.to_string()))?;
+        Ok(ImportRawKeyRequest {
+            secret_key: SecretKey::from_slice(&secret_key)
+                .map_err(|error| RpcErr::BadParams(error.to_string()))?,
+            password: serde_json::from_value(params[1].clone())?,
+        })
+    }
+
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+        let address = signer(&context)?.import_key(&self.secret_key, &self.password)?;
+        info!("Imported account {address:#x}");
+        serde_json::to_value(address).map_err(|error| RpcErr::Internal(error.to_string()))
+    }
+}
+
+impl RpcHandler for UnlockAccountRequest {
+    fn parse(params: &Option<Vec<Value>>) -> Result<Self, RpcErr> {
+        let params = params
+            .as_ref()
+            .ok_or(RpcErr::BadParams("No params provided".to_owned()))?;
+        if !(2..=3).contains(&params.len()) {
+            return Err(RpcErr::BadParams(format!(
+                "Expected two or three params and {} were provided",
+                params.len()
+            )));
+        }
+        let duration = match params.get(2) {
+            Some(Value::Null) | None => DEFAULT_UNLOCK_DURATION,
+            Some(seconds) => Duration::from_secs(serde_json::from_value(seconds.clone())?),
+        };
+        Ok(UnlockAccountRequest {
+            address: serde_json::from_value(params[0].clone())?,
+            password: serde_json::from_value(params[1].clone())?,
+            duration,
+        })
+    }
+
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+        info!("Requested to unlock account {:#x}", self.address);
+        let duration = (!self.duration.is_zero()).then_some(self.duration);
+        signer(&context)?.unlock(self.address, &self.password, duration)?;
+        Ok(Value::Bool(true))
+    }
+}
+
+impl RpcHandler for LockAccountRequest {
+    fn parse(params: &Option<Vec<Value>>) -> Result<Self, RpcErr> {
+        let params = expect_params(params, 1)?;
+        Ok(LockAccountRequest {
+            address: serde_json::from_value(params[0].clone())?,
+        })
+    }
+
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+        signer(&context)?.lock(self.address)?;
+        Ok(Value::Bool(true))
+    }
+}
+
+impl RpcHandler for EcRecoverRequest {
+    fn parse(params: &Option<Vec<Value>>) -> Result<Self, RpcErr> {
+        let params = expect_params(params, 2)?;
+        Ok(EcRecoverRequest {
+            message: parse_hex_bytes(&params[0], 0)?.to_vec(),
+            signature: parse_hex_bytes(&params[1], 1)?.to_vec(),
+        })
+    }
+
+    async fn handle(&self, _context: RpcApiContext) -> Result<Value, RpcErr> {
+        let address = recover_message_signer(&self.message, &self.signature)?;
+        serde_json::to_value(address).map_err(|error| RpcErr::Internal(error.to_string()))
+    }
+}
+
+/// personal_sign, which takes the message first and the password of the account last
+pub struct PersonalSignRequest(SignRequest);
+
+impl RpcHandler for PersonalSignRequest {
+    fn parse(params: &Option<Vec<Value>>) -> Result<Self, RpcErr> {
+        let params = expect_params(params, 3)?;
+        Ok(PersonalSignRequest(SignRequest {
+            message: parse_hex_bytes(&params[0], 0)?,
+            address: serde_json::from_value(params[1].clone())?,
+            password: Some(serde_json::from_value(params[2].clone())?),
+        }))
+    }
+
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+        self.0.handle(context).await
+    }
+}
+
+pub struct PersonalSignTransactionRequest(SignTransactionRequest);
+
+impl RpcHandler for PersonalSignTransactionRequest {
+    fn parse(params: &Option<Vec<Value>>) -> Result<Self, RpcErr> {
+        let params = expect_params(params, 2)?;
+        Ok(PersonalSignTransactionRequest(SignTransactionRequest {
+            transaction: serde_json::from_value(params[0].clone())?,
+            password: Some(serde_json::from_value(params[1].clone())?),
+        }))
+    }
+
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+        self.0.handle(context).await
+    }
+}
+
+pub struct PersonalSendTransactionRequest(SendTransactionRequest);
+
+impl RpcHandler for PersonalSendTransactionRequest {
+    fn parse(params: &Option<Vec<Value>>) -> Result<Self, RpcErr> {
+        let params = expect_params(params, 2)?;
+        Ok(PersonalSendTransactionRequest(SendTransactionRequest {
+            transaction: serde_json::from_value(params[0].clone())?,
+            password: Some(serde_json::from_value(params[1].clone())?),
+        }))
+    }
+
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+        self.0.handle(context).await
+    }
+}
+
+fn expect_params(params: &Option<Vec<Value>>, count: usize) -> Result<&Vec<Value>, RpcErr> {
+    let params = params
+        .as_ref()
+        .ok_or(RpcErr::BadParams("No params provided".to_owned()))?;
+    if params.len() != count {
+        return Err(RpcErr::BadParams(format!(
+            "Expected {count} params and {} were provided",
+            params.len()
+        )));
+    }
+    Ok(params)
+}
@@ -1,10 +1,15 @@
-use crate::authentication::authenticate;
-use axum::{routing::post, Json, Router};
+use crate::authentication::JwtAuth;
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::post,
+    Json, Router,
+};
 use axum_extra::{
     headers::{authorization::Bearer, Authorization},
     TypedHeader,
 };
-use bytes::Bytes;
+use blocking_pool::{BlockingPool, DEFAULT_BLOCKING_TASKS_LIMIT};
 use engine::{
     exchange_transition_config::ExchangeTransitionConfigV1Req,
     fork_choice::ForkChoiceUpdatedV3,
@@ -19,67 +24,113 @@ use eth::{
     block::{
         BlockNumberRequest, GetBlobBaseFee, GetBlockByHashRequest, GetBlockByNumberRequest,
         GetBlockReceiptsRequest, GetBlockTransactionCountRequest, GetRawBlockRequest,
-        GetRawHeaderRequest, GetRawReceipts,
+        GetRawHeaderRequest, GetRawReceipts, GetUncleByBlockAndIndexRequest, GetUncleCountRequest,
     },
-    client::{ChainId, Syncing},
+    client::{Accounts, ChainId, Coinbase, Hashrate, Mining, ProtocolVersion, Syncing},
     fee_market::FeeHistoryRequest,
-    filter::{self, ActiveFilters, DeleteFilterRequest, FilterChangesRequest, NewFilterRequest},
-    gas_price::GasPrice,
+    filter::{
+        self, ActiveFilters, DeleteFilterRequest, FilterChangesRequest, FilterLogsRequest,
+        NewBlockFilterRequest, NewFilterRequest, NewPendingTransactionFilterRequest,
+    },
+    gas_price::{GasPrice, MaxPriorityFee},
     logs::LogsFilter,
+    sign::{SendTransactionRequest, SignRequest, SignTransactionRequest},
+    simulate::SimulateRequest,
     transaction::{
         CallRequest, CreateAccessListRequest, EstimateGasRequest, GetRawTransaction,
         GetTransactionByBlockHashAndIndexRequest, GetTransactionByBlockNumberAndIndexRequest,
         GetTransactionByHashRequest, GetTransactionReceiptRequest,
     },
 };
-use ethrex_net::sync::SyncManager;
+use ethrex_net::{sync::SyncManager, KademliaTable};
+use personal::{
+    EcRecoverRequest, ImportRawKeyRequest, LockAccountRequest, NewAccountRequest,
+    PersonalSendTransactionRequest, PersonalSignRequest, PersonalSignTransactionRequest,
+    UnlockAccountRequest,
+};
+use serde::Deserialize;
 use serde_json::Value;
 use std::{
     collections::HashMap,
-    future::IntoFuture,
+    future::{Future, IntoFuture},
     net::SocketAddr,
+    path::PathBuf,
     sync::{Arc, Mutex},
-    time::Duration,
+    time::{Duration, Instant},
 };
-use tokio::{net::TcpListener, sync::Mutex as TokioMutex};
-use tracing::info;
+use tokio::{net::TcpListener, runtime::Handle, sync::Mutex as TokioMutex};
+use tracing::{debug, error, info};
 use types::transaction::SendRawTransactionRequest;
 use utils::{
     RpcErr, RpcErrorMetadata, RpcErrorResponse, RpcNamespace, RpcRequest, RpcRequestId,
     RpcSuccessResponse,
 };
 mod admin;
-mod authentication;
+pub mod authentication;
+mod blocking_pool;
 pub mod engine;
 mod eth;
+mod graphql;
+mod ipc;
+mod net;
+mod personal;
+pub mod signer;
 pub mod types;
 pub mod utils;
 mod web3;
 
 use axum::extract::State;
+use ethrex_blockchain::{payload::update_pending_block, EvmConfig};
 use ethrex_net::types::Node;
 use ethrex_storage::Store;
+use signer::Signer;
 
 #[derive(Debug, Clone)]
 pub struct RpcApiContext {
     storage: Store,
-    jwt_secret: Bytes,
+    jwt_auth: JwtAuth,
     local_p2p_node: Node,
     active_filters: ActiveFilters,
     syncer: Arc<TokioMutex<SyncManager>>,
+    peer_table: Arc<TokioMutex<KademliaTable>>,
+    evm: EvmConfig,
+    batch_request_limit: usize,
+    blocking_pool: BlockingPool,
+    /// Only set when the node was started with the local signer enabled
+    signer: Option<Signer>,
+    /// Whether the local signer is also served over HTTP, where anyone reaching the endpoint can
+    /// use its unlocked accounts. It's always served over IPC
+    allow_insecure_unlock: bool,
 }
 
 trait RpcHandler: Sized {
     fn parse(params: &Option<Vec<Value>>) -> Result<Self, RpcErr>;
 
-    fn call(req: &RpcRequest, context: RpcApiContext) -> Result<Value, RpcErr> {
+    async fn call(req: &RpcRequest, context: RpcApiContext) -> Result<Value, RpcErr> {
         let request = Self::parse(&req.params)?;
-        request.handle(context)
+        request.handle(context).await
     }
 
-    fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr>;
+    /// Handles the request on the blocking pool, for the methods doing CPU or DB heavy work
+    async fn call_blocking(req: &RpcRequest, context: RpcApiContext) -> Result<Value, RpcErr>
+    where
+        Self: Send + 'static,
+    {
+        let request = Self::parse(&req.params)?;
+        let blocking_pool = context.blocking_pool.clone();
+        blocking_pool
+            .run(&req.method, move || {
+                Handle::current().block_on(request.handle(context))
+            })
+            .await
+    }
+
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr>;
 }
 
+/// Maximum number of requests a batch can hold unless configured otherwise
+pub const DEFAULT_BATCH_REQUEST_LIMIT: usize = 1000;
+
 const FILTER_DURATION: Duration = {
     if cfg!(test) {
         Duration::from_secs(1)
@@ -88,23 +139,41 @@ const FILTER_DURATION: Duration = {
     }
 };
 
+/// How often the pending block is checked against the head and the mempool, to rebuild it
+/// when either of them changed
+const PENDING_BLOCK_UPDATE_INTERVAL: Duration = Duration::from_millis(250);
+
+#[allow(clippy::too_many_arguments)]
 pub async fn start_api(
     http_addr: SocketAddr,
     authrpc_addr: SocketAddr,
     storage: Store,
-    jwt_secret: Bytes,
+    jwt_auth: JwtAuth,
     local_p2p_node: Node,
     syncer: SyncManager,
+    peer_table: Arc<TokioMutex<KademliaTable>>,
+    evm: EvmConfig,
+    batch_request_limit: usize,
+    signer: Option<Signer>,
+    allow_insecure_unlock: bool,
+    graphql: bool,
+    ipc_path: Option<PathBuf>,
 ) {
     // TODO: Refactor how filters are handled,
     // filters are used by the filters endpoints (eth_newFilter, eth_getFilterChanges, ...etc)
     let active_filters = Arc::new(Mutex::new(HashMap::new()));
     let service_context = RpcApiContext {
         storage: storage.clone(),
-        jwt_secret,
+        jwt_auth,
         local_p2p_node,
         active_filters: active_filters.clone(),
         syncer: Arc::new(TokioMutex::new(syncer)),
+        peer_table,
+        evm,
+        batch_request_limit,
+        blocking_pool: BlockingPool::new(DEFAULT_BLOCKING_TASKS_LIMIT),
+        signer,
+        allow_insecure_unlock,
     };
 
     // Periodically clean up the active filters for the filters endpoints.
@@ -119,11 +188,39 @@ pub async fn start_api(
         }
     });
 
-    let http_router = Router::new()
-        .route("/", post(handle_http_request))
-        .with_state(service_context.clone());
+    // Keep the pending block built on top of the head with the transactions of the mempool
+    let pending_storage = storage.clone();
+    let evm_engine = service_context.evm.engine;
+    tokio::task::spawn(async move {
+        let mut interval = tokio::time::interval(PENDING_BLOCK_UPDATE_INTERVAL);
+        loop {
+            interval.tick().await;
+            let storage = pending_storage.clone();
+            let result =
+                tokio::task::spawn_blocking(move || update_pending_block(&storage, evm_engine))
+                    .await;
+            if let Ok(Err(error)) = result {
+                debug!("Failed to update the pending block: {error}");
+            }
+        }
+    });
+
+    let mut http_router = Router::new().route("/", post(handle_http_request));
+    if graphql {
+        http_router = http_router.route("/graphql", post(graphql::handle_graphql_request));
+    }
+    let http_router = http_router.with_state(service_context.clone());
     let http_listener = TcpListener::bind(http_addr).await.unwrap();
 
+    if let Some(ipc_path) = ipc_path {
+        let context = service_context.clone();
+        tokio::task::spawn(async move {
+            if let Err(error) = ipc::serve_ipc(&ipc_path, context).await {
+                error!("IPC server at {} failed: {error}", ipc_path.display());
+            }
+        });
+    }
+
     let authrpc_router = Router::new()
         .route("/", post(handle_authrpc_request))
         .with_state(service_context);
@@ -137,6 +234,9 @@ pub async fn start_api(
         .into_future();
 
     info!("Starting HTTP server at {http_addr}");
+    if graphql {
+        info!("Serving GraphQL at http://{http_addr}/graphql");
+    }
     info!("Starting Auth-RPC server at {}", authrpc_addr);
 
     let _ = tokio::try_join!(authrpc_server, http_server)
@@ -152,131 +252,355 @@ async fn shutdown_signal() {
 pub async fn handle_http_request(
     State(service_context): State<RpcApiContext>,
     body: String,
-) -> Json<Value> {
-    let req: RpcRequest = serde_json::from_str(&body).unwrap();
-    let res = map_http_requests(&req, service_context);
-    rpc_response(req.id, res)
+) -> Response {
+    let batch_request_limit = service_context.batch_request_limit;
+    handle_request_body(&body, batch_request_limit, |req| {
+        let context = service_context.clone();
+        async move { map_http_requests(&req, context).await }
+    })
+    .await
 }
 
 pub async fn handle_authrpc_request(
     State(service_context): State<RpcApiContext>,
     auth_header: Option<TypedHeader<Authorization<Bearer>>>,
     body: String,
-) -> Json<Value> {
-    let req: RpcRequest = serde_json::from_str(&body).unwrap();
-    match authenticate(&service_context.jwt_secret, auth_header) {
-        Err(error) => rpc_response(req.id, Err(error)),
-        Ok(()) => {
-            // Proceed with the request
-            let res = map_authrpc_requests(&req, service_context);
-            rpc_response(req.id, res)
+) -> Response {
+    let authentication = service_context.jwt_auth.authenticate(auth_header);
+    let batch_request_limit = service_context.batch_request_limit;
+    handle_request_body(&body, batch_request_limit, |req| {
+        let authentication = authentication.clone();
+        let context = service_context.clone();
+        async move {
+            // Proceed with the request only if it was authenticated
+            authentication?;
+            map_authrpc_requests(&req, context).await
+        }
+    })
+    .await
+}
+
+/// Handles a JSON-RPC body sent over HTTP, answering with an empty response when it gets none
+async fn handle_request_body<F, Fut>(body: &str, batch_request_limit: usize, handler: F) -> Response
+where
+    F: Fn(RpcRequest) -> Fut,
+    Fut: Future<Output = Result<Value, RpcErr>>,
+{
+    let body: Value = match serde_json::from_str(body) {
+        Ok(body) => body,
+        Err(error) => {
+            return rpc_response(
+                RpcRequestId::Null,
+                Err(RpcErr::ParseError(error.to_string())),
+            )
+            .into_response()
+        }
+    };
+    match handle_body(body, batch_request_limit, handler).await {
+        Some(response) => Json(response).into_response(),
+        None => StatusCode::OK.into_response(),
+    }
+}
+
+/// Handles a JSON-RPC body, which holds either a single request or a batch of them.
+/// Notifications (requests without an id) are handled but get no response, so a body made
+/// only of notifications gets none.
+async fn handle_body<F, Fut>(body: Value, batch_request_limit: usize, handler: F) -> Option<Value>
+where
+    F: Fn(RpcRequest) -> Fut,
+    Fut: Future<Output = Result<Value, RpcErr>>,
+{
+    match body {
+        Value::Array(requests) if requests.is_empty() => Some(
+            rpc_response(
+                RpcRequestId::Null,
+                Err(RpcErr::InvalidRequest("empty batch".to_string())),
+            )
+            .0,
+        ),
+        Value::Array(requests) if requests.len() > batch_request_limit => Some(
+            rpc_response(
+                RpcRequestId::Null,
+                Err(RpcErr::InvalidRequest(format!(
+                    "batch too large, the limit is {batch_request_limit} requests"
+                ))),
+            )
+            .0,
+        ),
+        Value::Array(requests) => {
+            let mut responses = Vec::new();
+            for request in requests {
+                responses.extend(handle_request(request, &handler).await);
+            }
+            (!responses.is_empty()).then_some(Value::Array(responses))
         }
+        request => handle_request(request, &handler).await,
     }
 }
 
+/// Handles a single request of a body, returning its response unless it's a notification
+async fn handle_request<F, Fut>(request: Value, handler: &F) -> Option<Value>
+where
+    F: Fn(RpcRequest) -> Fut,
+    Fut: Future<Output = Result<Value, RpcErr>>,
+{
+    let is_notification = request.as_object().is_some_and(|r| !r.contains_key("id"));
+    let mut req = match parse_request(&request) {
+        Ok(req) => req,
+        Err(error) => {
+            // The id of an invalid request is still echoed back when it can be read
+            let id = request
+                .get("id")
+                .and_then(|id| serde_json::from_value(id.clone()).ok())
+                .unwrap_or(RpcRequestId::Null);
+            return Some(rpc_response(id, Err(error)).0);
+        }
+    };
+    let id = std::mem::replace(&mut req.id, RpcRequestId::Null);
+    let method = req.method.clone();
+    let start = Instant::now();
+    let res = handler(req).await;
+    // Unknown methods share a label, so requests can't create metrics at will
+    let method = match &res {
+        Err(RpcErr::MethodNotFound(_)) => "unknown",
+        _ => &method,
+    };
+    ethrex_metrics::rpc::record_request(method, start.elapsed(), res.is_err());
+    (!is_notification).then(|| rpc_response(id, res).0)
+}
+
+fn parse_request(request: &Value) -> Result<RpcRequest, RpcErr> {
+    let req = RpcRequest::deserialize(request)
+        .map_err(|error| RpcErr::InvalidRequest(error.to_string()))?;
+    if req.jsonrpc != "2.0" {
+        return Err(RpcErr::InvalidRequest(format!(
+            "unsupported jsonrpc version {}",
+            req.jsonrpc
+        )));
+    }
+    Ok(req)
+}
+
 /// Handle requests that can come from either clients or other users
-pub fn map_http_requests(req: &RpcRequest, context: RpcApiContext) -> Result<Value, RpcErr> {
+pub async fn map_http_requests(req: &RpcRequest, context: RpcApiContext) -> Result<Value, RpcErr> {
+    // Anyone reaching the endpoint could sign with the unlocked accounts
+    if context.signer.is_some() && !context.allow_insecure_unlock && uses_local_signer(req) {
+        return Err(RpcErr::Signer(
+            "The local signer is only served over IPC unless the node runs with --signer.allow-insecure-unlock"
+                .to_owned(),
+        ));
+    }
+    map_client_requests(req, context).await
+}
+
+/// Handle the requests of the namespaces served to users, whatever the transport
+async fn map_client_requests(req: &RpcRequest, context: RpcApiContext) -> Result<Value, RpcErr> {
     match req.namespace() {
-        Ok(RpcNamespace::Eth) => map_eth_requests(req, context),
-        Ok(RpcNamespace::Admin) => map_admin_requests(req, context),
-        Ok(RpcNamespace::Debug) => map_debug_requests(req, context),
-        Ok(RpcNamespace::Web3) => map_web3_requests(req, context),
+        Ok(RpcNamespace::Eth) => map_eth_requests(req, context).await,
+        Ok(RpcNamespace::Admin) => map_admin_requests(req, context).await,
+        Ok(RpcNamespace::Debug) => map_debug_requests(req, context).await,
+        Ok(RpcNamespace::Web3) => map_web3_requests(req, context).await,
+        Ok(RpcNamespace::Net) => map_net_requests(req, context).await,
+        Ok(RpcNamespace::Personal) => map_personal_requests(req, context).await,
         _ => Err(RpcErr::MethodNotFound(req.method.clone())),
     }
 }
 
+/// Handle requests sent through the IPC socket, which only the node's user can reach, so it
+/// serves the engine namespace without authentication along with the others
+pub async fn map_ipc_requests(req: &RpcRequest, context: RpcApiContext) -> Result<Value, RpcErr> {
+    match req.namespace() {
+        Ok(RpcNamespace::Engine) => map_engine_requests(req, context).await,
+        _ => map_client_requests(req, context).await,
+    }
+}
+
+/// Methods using the accounts of the local signer, which sign with the unlocked ones without
+/// asking for their password
+fn uses_local_signer(req: &RpcRequest) -> bool {
+    matches!(req.namespace(), Ok(RpcNamespace::Personal))
+        || matches!(
+            req.method.as_str(),
+            "eth_sign" | "eth_signTransaction" | "eth_sendTransaction"
+        )
+}
+
 /// Handle requests from consensus client
-pub fn map_authrpc_requests(req: &RpcRequest, context: RpcApiContext) -> Result<Value, RpcErr> {
+pub async fn map_authrpc_requests(
+    req: &RpcRequest,
+    context: RpcApiContext,
+) -> Result<Value, RpcErr> {
     match req.namespace() {
-        Ok(RpcNamespace::Engine) => map_engine_requests(req, context),
-        Ok(RpcNamespace::Eth) => map_eth_requests(req, context),
+        Ok(RpcNamespace::Engine) => map_engine_requests(req, context).await,
+        Ok(RpcNamespace::Eth) if !uses_local_signer(req) => map_eth_requests(req, context).await,
         _ => Err(RpcErr::MethodNotFound(req.method.clone())),
     }
 }
 
-pub fn map_eth_requests(req: &RpcRequest, context: RpcApiContext) -> Result<Value, RpcErr> {
+pub async fn map_eth_requests(req: &RpcRequest, context: RpcApiContext) -> Result<Value, RpcErr> {
     match req.method.as_str() {
-        "eth_chainId" => ChainId::call(req, context),
-        "eth_syncing" => Syncing::call(req, context),
-        "eth_getBlockByNumber" => GetBlockByNumberRequest::call(req, context),
-        "eth_getBlockByHash" => GetBlockByHashRequest::call(req, context),
-        "eth_getBalance" => GetBalanceRequest::call(req, context),
-        "eth_getCode" => GetCodeRequest::call(req, context),
-        "eth_getStorageAt" => GetStorageAtRequest::call(req, context),
+        "eth_chainId" => ChainId::call(req, context).await,
+        "eth_syncing" => Syncing::call(req, context).await,
+        "eth_getBlockByNumber" => GetBlockByNumberRequest::call(req, context).await,
+        "eth_getBlockByHash" => GetBlockByHashRequest::call(req, context).await,
+        "eth_getBalance" => GetBalanceRequest::call(req, context).await,
+        "eth_getCode" => GetCodeRequest::call(req, context).await,
+        "eth_getStorageAt" => GetStorageAtRequest::call(req, context).await,
         "eth_getBlockTransactionCountByNumber" => {
-            GetBlockTransactionCountRequest::call(req, context)
+            GetBlockTransactionCountRequest::call(req, context).await
+        }
+        "eth_getBlockTransactionCountByHash" => {
+            GetBlockTransactionCountRequest::call(req, context).await
+        }
+        "eth_getUncleCountByBlockNumber" => GetUncleCountRequest::call(req, context).await,
+        "eth_getUncleCountByBlockHash" => GetUncleCountRequest::call(req, context).await,
+        "eth_getUncleByBlockNumberAndIndex" => {
+            GetUncleByBlockAndIndexRequest::call(req, context).await
+        }
+        "eth_getUncleByBlockHashAndIndex" => {
+            GetUncleByBlockAndIndexRequest::call(req, context).await
         }
-        "eth_getBlockTransactionCountByHash" => GetBlockTransactionCountRequest::call(req, context),
         "eth_getTransactionByBlockNumberAndIndex" => {
-            GetTransactionByBlockNumberAndIndexRequest::call(req, context)
+            GetTransactionByBlockNumberAndIndexRequest::call(req, context).await
         }
         "eth_getTransactionByBlockHashAndIndex" => {
-            GetTransactionByBlockHashAndIndexRequest::call(req, context)
-        }
-        "eth_getBlockReceipts" => GetBlockReceiptsRequest::call(req, context),
-        "eth_getTransactionByHash" => GetTransactionByHashRequest::call(req, context),
-        "eth_getTransactionReceipt" => GetTransactionReceiptRequest::call(req, context),
-        "eth_createAccessList" => CreateAccessListRequest::call(req, context),
-        "eth_blockNumber" => BlockNumberRequest::call(req, context),
-        "eth_call" => CallRequest::call(req, context),
-        "eth_blobBaseFee" => GetBlobBaseFee::call(req, context),
-        "eth_getTransactionCount" => GetTransactionCountRequest::call(req, context),
-        "eth_feeHistory" => FeeHistoryRequest::call(req, context),
-        "eth_estimateGas" => EstimateGasRequest::call(req, context),
-        "eth_getLogs" => LogsFilter::call(req, context),
+            GetTransactionByBlockHashAndIndexRequest::call(req, context).await
+        }
+        "eth_getBlockReceipts" => GetBlockReceiptsRequest::call(req, context).await,
+        "eth_getTransactionByHash" => GetTransactionByHashRequest::call(req, context).await,
+        "eth_getTransactionReceipt" => GetTransactionReceiptRequest::call(req, context).await,
+        "eth_createAccessList" => CreateAccessListRequest::call_blocking(req, context).await,
+        "eth_blockNumber" => BlockNumberRequest::call(req, context).await,
+        "eth_call" => CallRequest::call_blocking(req, context).await,
+        "eth_blobBaseFee" => GetBlobBaseFee::call(req, context).await,
+        "eth_getTransactionCount" => GetTransactionCountRequest::call(req, context).await,
+        "eth_feeHistory" => FeeHistoryRequest::call_blocking(req, context).await,
+        "eth_estimateGas" => EstimateGasRequest::call_blocking(req, context).await,
+        "eth_simulateV1" => SimulateRequest::call_blocking(req, context).await,
+        "eth_getLogs" => LogsFilter::call_blocking(req, context).await,
         "eth_newFilter" => {
             NewFilterRequest::stateful_call(req, context.storage, context.active_filters)
         }
+        "eth_newBlockFilter" => {
+            NewBlockFilterRequest::stateful_call(req, context.storage, context.active_filters)
+        }
+        "eth_newPendingTransactionFilter" => NewPendingTransactionFilterRequest::stateful_call(
+            req,
+            context.storage,
+            context.active_filters,
+        ),
         "eth_uninstallFilter" => {
             DeleteFilterRequest::stateful_call(req, context.storage, context.active_filters)
         }
         "eth_getFilterChanges" => {
-            FilterChangesRequest::stateful_call(req, context.storage, context.active_filters)
+            let request = FilterChangesRequest::parse(&req.params)?;
+            context
+                .blocking_pool
+                .run(&req.method, move || {
+                    request.handle(context.storage, context.active_filters)
+                })
+                .await
         }
-        "eth_sendRawTransaction" => SendRawTransactionRequest::call(req, context),
-        "eth_getProof" => GetProofRequest::call(req, context),
-        "eth_gasPrice" => GasPrice::call(req, context),
+        "eth_getFilterLogs" => {
+            let request = FilterLogsRequest::parse(&req.params)?;
+            context
+                .blocking_pool
+                .run(&req.method, move || {
+                    request.handle(context.storage, context.active_filters)
+                })
+                .await
+        }
+        "eth_sendRawTransaction" => SendRawTransactionRequest::call(req, context).await,
+        "eth_sign" => SignRequest::call(req, context).await,
+        "eth_signTransaction" => SignTransactionRequest::call_blocking(req, context).await,
+        "eth_sendTransaction" => SendTransactionRequest::call_blocking(req, context).await,
+        "eth_getProof" => GetProofRequest::call_blocking(req, context).await,
+        "eth_gasPrice" => GasPrice::call(req, context).await,
+        "eth_maxPriorityFeePerGas" => MaxPriorityFee::call(req, context).await,
+        "eth_accounts" => Accounts::call(req, context).await,
+        "eth_coinbase" => Coinbase::call(req, context).await,
+        "eth_mining" => Mining::call(req, context).await,
+        "eth_hashrate" => Hashrate::call(req, context).await,
+        "eth_protocolVersion" => ProtocolVersion::call(req, context).await,
         unknown_eth_method => Err(RpcErr::MethodNotFound(unknown_eth_method.to_owned())),
     }
 }
 
-pub fn map_debug_requests(req: &RpcRequest, context: RpcApiContext) -> Result<Value, RpcErr> {
+pub async fn map_debug_requests(req: &RpcRequest, context: RpcApiContext) -> Result<Value, RpcErr> {
     match req.method.as_str() {
-        "debug_getRawHeader" => GetRawHeaderRequest::call(req, context),
-        "debug_getRawBlock" => GetRawBlockRequest::call(req, context),
-        "debug_getRawTransaction" => GetRawTransaction::call(req, context),
-        "debug_getRawReceipts" => GetRawReceipts::call(req, context),
+        "debug_getRawHeader" => GetRawHeaderRequest::call(req, context).await,
+        "debug_getRawBlock" => GetRawBlockRequest::call(req, context).await,
+        "debug_getRawTransaction" => GetRawTransaction::call(req, context).await,
+        "debug_getRawReceipts" => GetRawReceipts::call(req, context).await,
         unknown_debug_method => Err(RpcErr::MethodNotFound(unknown_debug_method.to_owned())),
     }
 }
 
-pub fn map_engine_requests(req: &RpcRequest, context: RpcApiContext) -> Result<Value, RpcErr> {
+pub async fn map_engine_requests(
+    req: &RpcRequest,
+    context: RpcApiContext,
+) -> Result<Value, RpcErr> {
     match req.method.as_str() {
-        "engine_exchangeCapabilities" => ExchangeCapabilitiesRequest::call(req, context),
-        "engine_forkchoiceUpdatedV3" => ForkChoiceUpdatedV3::call(req, context),
-        "engine_newPayloadV3" => NewPayloadV3Request::call(req, context),
+        "engine_exchangeCapabilities" => ExchangeCapabilitiesRequest::call(req, context).await,
+        "engine_forkchoiceUpdatedV3" => ForkChoiceUpdatedV3::call_blocking(req, context).await,
+        "engine_newPayloadV3" => NewPayloadV3Request::call_blocking(req, context).await,
         "engine_exchangeTransitionConfigurationV1" => {
-            ExchangeTransitionConfigV1Req::call(req, context)
+            ExchangeTransitionConfigV1Req::call(req, context).await
         }
-        "engine_getPayloadV3" => GetPayloadV3Request::call(req, context),
+        "engine_getPayloadV3" => GetPayloadV3Request::call_blocking(req, context).await,
         unknown_engine_method => Err(RpcErr::MethodNotFound(unknown_engine_method.to_owned())),
     }
 }
 
-pub fn map_admin_requests(req: &RpcRequest, context: RpcApiContext) -> Result<Value, RpcErr> {
+pub async fn map_admin_requests(req: &RpcRequest, context: RpcApiContext) -> Result<Value, RpcErr> {
     match req.method.as_str() {
         "admin_nodeInfo" => admin::node_info(context.storage, context.local_p2p_node),
         unknown_admin_method => Err(RpcErr::MethodNotFound(unknown_admin_method.to_owned())),
     }
 }
 
-pub fn map_web3_requests(req: &RpcRequest, context: RpcApiContext) -> Result<Value, RpcErr> {
+pub async fn map_web3_requests(req: &RpcRequest, context: RpcApiContext) -> Result<Value, RpcErr> {
     match req.method.as_str() {
         "web3_clientVersion" => web3::client_version(req, context.storage),
         unknown_web3_method => Err(RpcErr::MethodNotFound(unknown_web3_method.to_owned())),
     }
 }
 
+pub async fn map_net_requests(req: &RpcRequest, context: RpcApiContext) -> Result<Value, RpcErr> {
+    match req.method.as_str() {
+        "net_version" => net::version(context),
+        "net_peerCount" => net::peer_count(context).await,
+        "net_listening" => net::listening(),
+        unknown_net_method => Err(RpcErr::MethodNotFound(unknown_net_method.to_owned())),
+    }
+}
+
+pub async fn map_personal_requests(
+    req: &RpcRequest,
+    context: RpcApiContext,
+) -> Result<Value, RpcErr> {
+    // The namespace is only served when the local signer is enabled
+    if context.signer.is_none() {
+        return Err(RpcErr::MethodNotFound(req.method.clone()));
+    }
+    match req.method.as_str() {
+        "personal_listAccounts" => personal::list_accounts(context),
+        "personal_newAccount" => NewAccountRequest::call_blocking(req, context).await,
+        "personal_importRawKey" => ImportRawKeyRequest::call_blocking(req, context).await,
+        "personal_unlockAccount" => UnlockAccountRequest::call_blocking(req, context).await,
+        "personal_lockAccount" => LockAccountRequest::call(req, context).await,
+        "personal_sign" => PersonalSignRequest::call_blocking(req, context).await,
+        "personal_ecRecover" => EcRecoverRequest::call(req, context).await,
+        "personal_signTransaction" => {
+            PersonalSignTransactionRequest::call_blocking(req, context).await
+        }
+        "personal_sendTransaction" => {
+            PersonalSendTransactionRequest::call_blocking(req, context).await
+        }
+        unknown_personal_method => Err(RpcErr::MethodNotFound(unknown_personal_method.to_owned())),
+    }
+}
+
 fn rpc_response<E>(id: RpcRequestId, res: Result<Value, E>) -> Json<Value>
 where
     E: Into<RpcErrorMetadata>,
@@ -304,11 +628,21 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::utils::test_utils::example_p2p_node;
-    use ethrex_core::types::{ChainConfig, Genesis};
-    use ethrex_storage::EngineType;
+    use crate::utils::test_utils::{example_p2p_node, example_peer_table};
+    use bytes::Bytes;
+    use ethrex_core::{
+        types::{
+            code_hash, AccountInfo, Block, BlockHeader, ChainConfig, EIP1559Transaction, Genesis,
+            GenesisAccount, Signable, Transaction, TxKind, TxType,
+        },
+        Address, U256,
+    };
+    use ethrex_storage::{AccountUpdate, EngineType, WriteBatch};
+    use ethrex_vm::EvmEngine;
+    use serde_json::json;
     use std::fs::File;
     use std::io::BufReader;
+    use std::str::FromStr;
 
     // Maps string rpc response to RpcSuccessResponse as serde Value
     // This is used to avoid failures due to field order and allow easier string comparisons for responses
@@ -316,8 +650,8 @@ mod tests {
         serde_json::to_value(serde_json::from_str::<RpcSuccessResponse>(str).unwrap()).unwrap()
     }
 
-    #[test]
-    fn admin_nodeinfo_request() {
+    #[tokio::test]
+    async fn admin_nodeinfo_request() {
         let body = r#"{"jsonrpc":"2.0", "method":"admin_nodeInfo", "params":[], "id":1}"#;
         let request: RpcRequest = serde_json::from_str(body).unwrap();
         let local_p2p_node = example_p2p_node();
@@ -327,14 +661,20 @@ mod tests {
         let context = RpcApiContext {
             local_p2p_node,
             storage,
-            jwt_secret: Default::default(),
+            jwt_auth: Default::default(),
             active_filters: Default::default(),
             syncer: Arc::new(TokioMutex::new(SyncManager::dummy())),
+            peer_table: example_peer_table(),
+            evm: EvmConfig::default(),
+            batch_request_limit: DEFAULT_BATCH_REQUEST_LIMIT,
+            blocking_pool: BlockingPool::new(DEFAULT_BLOCKING_TASKS_LIMIT),
+            signer: None,
+            allow_insecure_unlock: false,
         };
-        let result = map_http_requests(&request, context);
+        let result = map_http_requests(&request, context).await;
         let rpc_response = rpc_response(request.id, result);
         let expected_response = to_rpc_response_success_value(
-            r#"{"jsonrpc":"2.0","id":1,"result":{"enode":"enode://d860a01f9722d78051619d1e2351aba3f43f943f6f00718d1b9baa4101932a1f5011f16bb2b1bb35db20d6fe28fa0bf09636d26a87d31de9ec6203eeedb1f666@127.0.0.1:30303","id":"d860a01f9722d78051619d1e2351aba3f43f943f6f00718d1b9baa4101932a1f5011f16bb2b1bb35db20d6fe28fa0bf09636d26a87d31de9ec6203eeedb1f666","ip":"127.0.0.1","name":"ethrex/0.1.0/rust1.81","ports":{"discovery":30303,"listener":30303},"protocols":{"eth":{"chainId":3151908,"homesteadBlock":0,"daoForkBlock":null,"daoForkSupport":false,"eip150Block":0,"eip155Block":0,"eip158Block":0,"byzantiumBlock":0,"constantinopleBlock":0,"petersburgBlock":0,"istanbulBlock":0,"muirGlacierBlock":null,"berlinBlock":0,"londonBlock":0,"arrowGlacierBlock":null,"grayGlacierBlock":null,"mergeNetsplitBlock":0,"shanghaiTime":0,"cancunTime":0,"pragueTime":1718232101,"verkleTime":null,"terminalTotalDifficulty":0,"terminalTotalDifficultyPassed":true}}}}"#,
+            r#"{"jsonrpc":"2.0","id":1,"result":{"enode":"enode://d860a01f9722d78051619d1e2351aba3f43f943f6f00718d1b9baa4101932a1f5011f16bb2b1bb35db20d6fe28fa0bf09636d26a87d31de9ec6203eeedb1f666@127.0.0.1:30303","id":"d860a01f9722d78051619d1e2351aba3f43f943f6f00718d1b9baa4101932a1f5011f16bb2b1bb35db20d6fe28fa0bf09636d26a87d31de9ec6203eeedb1f666","ip":"127.0.0.1","name":"ethrex/0.1.0/rust1.81","ports":{"discovery":30303,"listener":30303},"protocols":{"eth":{"chainId":3151908,"homesteadBlock":0,"daoForkBlock":null,"daoForkSupport":false,"eip150Block":0,"eip155Block":0,"eip158Block":0,"byzantiumBlock":0,"constantinopleBlock":0,"petersburgBlock":0,"istanbulBlock":0,"muirGlacierBlock":null,"berlinBlock":0,"londonBlock":0,"arrowGlacierBlock":null,"grayGlacierBlock":null,"mergeNetsplitBlock":0,"shanghaiTime":0,"cancunTime":0,"pragueTime":1718232101,"osakaTime":null,"verkleTime":null,"terminalTotalDifficulty":0,"terminalTotalDifficultyPassed":true}}}}"#,
         );
         assert_eq!(rpc_response.to_string(), expected_response.to_string())
     }
@@ -347,8 +687,8 @@ mod tests {
         serde_json::from_reader(reader).expect("Failed to deserialize genesis file")
     }
 
-    #[test]
-    fn create_access_list_simple_transfer() {
+    #[tokio::test]
+    async fn create_access_list_simple_transfer() {
         // Create Request
         // Request taken from https://github.com/ethereum/execution-apis/blob/main/tests/eth_createAccessList/create-al-value-transfer.io
         let body = r#"{"jsonrpc":"2.0","id":1,"method":"eth_createAccessList","params":[{"from":"0x0c2c51a0990aee1d73c1228de158688341557508","nonce":"0x0","to":"0x0100000000000000000000000000000000000000","value":"0xa"},"0x00"]}"#;
@@ -365,11 +705,17 @@ mod tests {
         let context = RpcApiContext {
             local_p2p_node,
             storage,
-            jwt_secret: Default::default(),
+            jwt_auth: Default::default(),
             active_filters: Default::default(),
             syncer: Arc::new(TokioMutex::new(SyncManager::dummy())),
+            peer_table: example_peer_table(),
+            evm: EvmConfig::default(),
+            batch_request_limit: DEFAULT_BATCH_REQUEST_LIMIT,
+            blocking_pool: BlockingPool::new(DEFAULT_BLOCKING_TASKS_LIMIT),
+            signer: None,
+            allow_insecure_unlock: false,
         };
-        let result = map_http_requests(&request, context);
+        let result = map_http_requests(&request, context).await;
         let response = rpc_response(request.id, result);
         let expected_response = to_rpc_response_success_value(
             r#"{"jsonrpc":"2.0","id":1,"result":{"accessList":[],"gasUsed":"0x5208"}}"#,
@@ -377,8 +723,8 @@ mod tests {
         assert_eq!(response.to_string(), expected_response.to_string());
     }
 
-    #[test]
-    fn create_access_list_create() {
+    #[tokio::test]
+    async fn create_access_list_create() {
         // Create Request
         // Request taken from https://github.com/ethereum/execution-apis/blob/main/tests/eth_createAccessList/create-al-contract.io
         let body = r#"{"jsonrpc":"2.0","id":1,"method":"eth_createAccessList","params":[{"from":"0x0c2c51a0990aee1d73c1228de158688341557508","gas":"0xea60","gasPrice":"0x44103f2","input":"0x010203040506","nonce":"0x0","to":"0x7dcd17433742f4c0ca53122ab541d0ba67fc27df"},"0x00"]}"#;
@@ -395,11 +741,17 @@ mod tests {
         let context = RpcApiContext {
             local_p2p_node,
             storage,
-            jwt_secret: Default::default(),
+            jwt_auth: Default::default(),
             active_filters: Default::default(),
             syncer: Arc::new(TokioMutex::new(SyncManager::dummy())),
+            peer_table: example_peer_table(),
+            evm: EvmConfig::default(),
+            batch_request_limit: DEFAULT_BATCH_REQUEST_LIMIT,
+            blocking_pool: BlockingPool::new(DEFAULT_BLOCKING_TASKS_LIMIT),
+            signer: None,
+            allow_insecure_unlock: false,
         };
-        let result = map_http_requests(&request, context);
+        let result = map_http_requests(&request, context).await;
         let response =
             serde_json::from_value::<RpcSuccessResponse>(rpc_response(request.id, result).0)
                 .expect("Request failed");
@@ -415,6 +767,440 @@ mod tests {
         )
     }
 
+    // Context over the execution-apis genesis, running calls with the given engine
+    fn execution_api_context(engine: EvmEngine) -> RpcApiContext {
+        let storage =
+            Store::new("temp.db", EngineType::InMemory).expect("Failed to create test DB");
+        storage
+            .add_initial_state(read_execution_api_genesis_file())
+            .expect("Failed to add genesis block to DB");
+        RpcApiContext {
+            local_p2p_node: example_p2p_node(),
+            storage,
+            jwt_auth: Default::default(),
+            active_filters: Default::default(),
+            syncer: Arc::new(TokioMutex::new(SyncManager::dummy())),
+            peer_table: example_peer_table(),
+            evm: EvmConfig::from(engine),
+            batch_request_limit: DEFAULT_BATCH_REQUEST_LIMIT,
+            blocking_pool: BlockingPool::new(DEFAULT_BLOCKING_TASKS_LIMIT),
+            signer: None,
+            allow_insecure_unlock: false,
+        }
+    }
+
+    async fn call_with_engines(body: &str) -> Vec<Result<Value, RpcErr>> {
+        let mut results = Vec::new();
+        for engine in [EvmEngine::REVM, EvmEngine::LEVM] {
+            let request: RpcRequest = serde_json::from_str(body).unwrap();
+            results.push(map_http_requests(&request, execution_api_context(engine)).await);
+        }
+        results
+    }
+
+    // Returns the second storage slot of the called account
+    const SLOAD_CODE: &str = "0x60015460005260206000f3";
+    // Returns the block number
+    const NUMBER_CODE: &str = "0x4360005260206000f3";
+    const OVERRIDDEN_ADDRESS: &str = "0x00000000000000000000000000000000000000aa";
+    // Has its slots 1 to 3 set in the genesis
+    const CONTRACT_WITH_STORAGE: &str = "0x8bebc8ba651aee624937e7d897853ac30c95a067";
+
+    fn word(value: u64) -> String {
+        format!("0x{value:064x}")
+    }
+
+    #[tokio::test]
+    async fn call_with_state_overrides() {
+        async fn call(account_override: String) -> Vec<Value> {
+            let body = format!(
+                r#"{{"jsonrpc":"2.0","id":1,"method":"eth_call","params":[{{"to":"{CONTRACT_WITH_STORAGE}"}},"latest",{{"{CONTRACT_WITH_STORAGE}":{account_override}}}]}}"#
+            );
+            call_with_engines(&body)
+                .await
+                .into_iter()
+                .map(|result| result.unwrap())
+                .collect()
+        }
+        let both_engines = |value: u64| vec![Value::String(word(value)); 2];
+        assert_eq!(
+            call(format!(r#"{{"code":"{SLOAD_CODE}"}}"#)).await,
+            both_engines(1)
+        );
+        assert_eq!(
+            call(format!(
+                r#"{{"code":"{SLOAD_CODE}","stateDiff":{{"{}":"{}"}}}}"#,
+                word(1),
+                word(42)
+            ))
+            .await,
+            both_engines(42)
+        );
+        // Slots left out of a state diff keep their value
+        assert_eq!(
+            call(format!(
+                r#"{{"code":"{SLOAD_CODE}","stateDiff":{{"{}":"{}"}}}}"#,
+                word(2),
+                word(42)
+            ))
+            .await,
+            both_engines(1)
+        );
+        // While the ones left out of a full state are cleared
+        assert_eq!(
+            call(format!(
+                r#"{{"code":"{SLOAD_CODE}","state":{{"{}":"{}"}}}}"#,
+                word(2),
+                word(42)
+            ))
+            .await,
+            both_engines(0)
+        );
+    }
+
+    #[tokio::test]
+    async fn call_with_state_and_state_diff_is_rejected() {
+        let body = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"eth_call","params":[{{"to":"{OVERRIDDEN_ADDRESS}"}},"latest",{{"{OVERRIDDEN_ADDRESS}":{{"state":{{}},"stateDiff":{{}}}}}}]}}"#
+        );
+        for result in call_with_engines(&body).await {
+            assert!(matches!(result, Err(RpcErr::BadParams(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn call_with_block_overrides() {
+        let body = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"eth_call","params":[{{"to":"{OVERRIDDEN_ADDRESS}"}},"latest",{{"{OVERRIDDEN_ADDRESS}":{{"code":"{NUMBER_CODE}"}}}},{{"number":"0x1234"}}]}}"#
+        );
+        for result in call_with_engines(&body).await {
+            assert_eq!(result.unwrap(), Value::String(word(0x1234)));
+        }
+    }
+
+    #[tokio::test]
+    async fn estimate_gas_with_balance_override() {
+        // The sender has no funds unless its balance is overridden
+        let sender = "0x00000000000000000000000000000000000000bb";
+        let transaction = format!(
+            r#"{{"from":"{sender}","to":"{OVERRIDDEN_ADDRESS}","value":"0xde0b6b3a7640000","gasPrice":"0x3b9aca00"}}"#
+        );
+        let body = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"eth_estimateGas","params":[{transaction},"latest"]}}"#
+        );
+        for result in call_with_engines(&body).await {
+            assert!(result.is_err());
+        }
+        let body = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"eth_estimateGas","params":[{transaction},"latest",{{"{sender}":{{"balance":"0xffffffffffffffffffff"}}}}]}}"#
+        );
+        for result in call_with_engines(&body).await {
+            assert_eq!(result.unwrap(), Value::String("0x5208".to_string()));
+        }
+    }
+
+    #[tokio::test]
+    async fn pending_tag_is_served_from_the_pending_block() {
+        // The pending block funds a sender and deploys code returning the block number
+        let sender = "0x00000000000000000000000000000000000000bb";
+        let pending_state = |storage: &Store, head: &BlockHeader| {
+            let mut funded = AccountUpdate::new(Address::from_str(sender).unwrap());
+            funded.info = Some(AccountInfo {
+                balance: U256::from(10).pow(U256::from(18)),
+                nonce: 1,
+                ..Default::default()
+            });
+            let code = Bytes::from(hex::decode(&NUMBER_CODE[2..]).unwrap());
+            let mut contract = AccountUpdate::new(Address::from_str(OVERRIDDEN_ADDRESS).unwrap());
+            contract.info = Some(AccountInfo {
+                code_hash: code_hash(&code),
+                ..Default::default()
+            });
+            contract.code = Some(code);
+            // Like the node does, the state is only kept in memory
+            let account_updates = vec![funded, contract];
+            let state_root = storage
+                .apply_account_updates_to_batch(
+                    &mut WriteBatch::new(),
+                    head.compute_block_hash(),
+                    &account_updates,
+                )
+                .unwrap()
+                .unwrap();
+            (state_root, account_updates)
+        };
+        let mut contexts = Vec::new();
+        for engine in [EvmEngine::REVM, EvmEngine::LEVM] {
+            let context = execution_api_context(engine);
+            let head_number = context.storage.get_latest_block_number().unwrap().unwrap();
+            let head = context
+                .storage
+                .get_block_header(head_number)
+                .unwrap()
+                .unwrap();
+            let (state_root, account_updates) = pending_state(&context.storage, &head);
+            let pending_header = BlockHeader {
+                parent_hash: head.compute_block_hash(),
+                number: head.number + 1,
+                timestamp: head.timestamp + 12,
+                state_root,
+                ..head.clone()
+            };
+            let pending_block = Block::new(pending_header, Default::default());
+            context
+                .storage
+                .set_current_pending_block(pending_block, account_updates, 0)
+                .unwrap();
+            contexts.push((context, head.number));
+        }
+
+        for (context, head_number) in contexts {
+            let call = |method: &str, params: String| {
+                let body =
+                    format!(r#"{{"jsonrpc":"2.0","id":1,"method":"{method}","params":{params}}}"#);
+                let request: RpcRequest = serde_json::from_str(&body).unwrap();
+                let context = context.clone();
+                async move { map_http_requests(&request, context).await }
+            };
+            for (tag, balance, nonce) in [
+                ("latest", "0x0", "0x0"),
+                ("pending", "0xde0b6b3a7640000", "0x1"),
+            ] {
+                let result = call("eth_getBalance", format!(r#"["{sender}", "{tag}"]"#)).await;
+                assert_eq!(result.unwrap(), Value::String(balance.to_string()));
+                let result = call(
+                    "eth_getTransactionCount",
+                    format!(r#"["{sender}", "{tag}"]"#),
+                )
+                .await;
+                assert_eq!(result.unwrap(), Value::String(nonce.to_string()));
+            }
+
+            let result = call("eth_getBlockByNumber", r#"["pending", false]"#.to_string()).await;
+            assert_eq!(
+                result.unwrap()["number"],
+                Value::String(format!("{:#x}", head_number + 1))
+            );
+            let result = call(
+                "eth_call",
+                format!(r#"[{{"to":"{OVERRIDDEN_ADDRESS}"}}, "pending"]"#),
+            )
+            .await;
+            assert_eq!(result.unwrap(), Value::String(word(head_number + 1)));
+
+            // The sender can only pay for the transfer on the pending block
+            let transaction = format!(
+                r#"{{"from":"{sender}","to":"{SIMULATION_SENDER}","value":"0x1","gasPrice":"0x3b9aca00"}}"#
+            );
+            let result = call("eth_estimateGas", format!(r#"[{transaction}, "latest"]"#)).await;
+            assert!(result.is_err());
+            let result = call("eth_estimateGas", format!(r#"[{transaction}, "pending"]"#)).await;
+            assert_eq!(result.unwrap(), Value::String("0x5208".to_string()));
+        }
+    }
+
+    const SIMULATION_SENDER: &str = "0x00000000000000000000000000000000000000bb";
+    const SIMULATION_RECIPIENT: &str = "0x00000000000000000000000000000000000000cc";
+
+    async fn simulate_with_engines(payload: &str) -> Vec<Result<Value, RpcErr>> {
+        let body = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"eth_simulateV1","params":[{payload},"latest"]}}"#
+        );
+        call_with_engines(&body).await
+    }
+
+    // Block state calls sending 0x100 wei from the funded sender to the recipient
+    fn simulated_transfer() -> String {
+        format!(
+            r#"{{"stateOverrides":{{"{SIMULATION_SENDER}":{{"balance":"0xffffffffffffffffffff"}}}},"calls":[{{"from":"{SIMULATION_SENDER}","to":"{SIMULATION_RECIPIENT}","value":"0x100"}}]}}"#
+        )
+    }
+
+    #[tokio::test]
+    async fn simulate_carries_state_across_blocks() {
+        // Returns the balance of the recipient
+        let balance_code = format!("0x73{}3160005260206000f3", &SIMULATION_RECIPIENT[2..]);
+        let payload = format!(
+            r#"{{"blockStateCalls":[{},{{"stateOverrides":{{"{OVERRIDDEN_ADDRESS}":{{"code":"{balance_code}"}}}},"calls":[{{"to":"{OVERRIDDEN_ADDRESS}"}}]}}]}}"#,
+            simulated_transfer()
+        );
+        for result in simulate_with_engines(&payload).await {
+            let blocks = result.unwrap();
+            assert_eq!(blocks.as_array().unwrap().len(), 2);
+            assert_eq!(blocks[0]["number"], "0x1");
+            assert_eq!(blocks[1]["number"], "0x2");
+            assert_eq!(blocks[1]["parentHash"], blocks[0]["hash"]);
+            assert_eq!(blocks[0]["calls"][0]["status"], "0x1");
+            assert_eq!(blocks[0]["transactions"].as_array().unwrap().len(), 1);
+            assert_eq!(blocks[1]["calls"][0]["returnData"], word(0x100));
+        }
+    }
+
+    #[tokio::test]
+    async fn simulate_traces_transfers() {
+        let payload = format!(
+            r#"{{"blockStateCalls":[{}],"traceTransfers":true}}"#,
+            simulated_transfer()
+        );
+        for result in simulate_with_engines(&payload).await {
+            let logs = result.unwrap()[0]["calls"][0]["logs"].clone();
+            assert_eq!(logs.as_array().unwrap().len(), 1);
+            assert_eq!(
+                logs[0]["address"],
+                "0xeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee"
+            );
+            assert_eq!(
+                logs[0]["topics"][0],
+                "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+            );
+            assert_eq!(logs[0]["data"], word(0x100));
+        }
+        // Transfers aren't reported unless requested
+        let payload = format!(r#"{{"blockStateCalls":[{}]}}"#, simulated_transfer());
+        for result in simulate_with_engines(&payload).await {
+            assert_eq!(result.unwrap()[0]["calls"][0]["logs"], Value::Array(vec![]));
+        }
+    }
+
+    #[tokio::test]
+    async fn simulate_with_validation_checks_nonces() {
+        let payload = format!(
+            r#"{{"blockStateCalls":[{{"calls":[{{"from":"{SIMULATION_SENDER}","to":"{SIMULATION_RECIPIENT}","nonce":"0x5"}}]}}],"validation":true}}"#
+        );
+        for result in simulate_with_engines(&payload).await {
+            assert!(matches!(
+                result,
+                Err(RpcErr::Simulation { code: -38011, .. })
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn simulate_fills_skipped_blocks_and_rejects_decreasing_numbers() {
+        let payload = r#"{"blockStateCalls":[{"blockOverrides":{"number":"0x3"}}]}"#;
+        for result in simulate_with_engines(payload).await {
+            let blocks = result.unwrap();
+            let numbers: Vec<_> = blocks
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|block| block["number"].clone())
+                .collect();
+            assert_eq!(numbers, vec!["0x1", "0x2", "0x3"]);
+        }
+        let payload = r#"{"blockStateCalls":[{"blockOverrides":{"number":"0x2"}},{"blockOverrides":{"number":"0x2"}}]}"#;
+        for result in simulate_with_engines(payload).await {
+            assert!(matches!(
+                result,
+                Err(RpcErr::Simulation { code: -38020, .. })
+            ));
+        }
+    }
+
+    // Handles the test_echo method only, returning the params it was called with
+    async fn echo_handler(req: RpcRequest) -> Result<Value, RpcErr> {
+        match req.method.as_str() {
+            "test_echo" => Ok(serde_json::to_value(&req.params)?),
+            unknown_method => Err(RpcErr::MethodNotFound(unknown_method.to_owned())),
+        }
+    }
+
+    // Handles a body with the echo handler, returning its response or null if it's empty
+    async fn handle_echo_body(body: &str, batch_request_limit: usize) -> Value {
+        let response = handle_request_body(body, batch_request_limit, echo_handler).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        if body.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(&body).unwrap()
+        }
+    }
+
+    #[tokio::test]
+    async fn malformed_body_returns_parse_error() {
+        let response = handle_echo_body(r#"{"jsonrpc":"2.0","method":"#, 10).await;
+        assert_eq!(response["error"]["code"], -32700);
+        assert_eq!(response["id"], Value::Null);
+    }
+
+    #[tokio::test]
+    async fn invalid_request_returns_invalid_request_error() {
+        // The method is missing
+        let response = handle_echo_body(r#"{"jsonrpc":"2.0","id":7}"#, 10).await;
+        assert_eq!(response["error"]["code"], -32600);
+        assert_eq!(response["id"], 7);
+
+        let response =
+            handle_echo_body(r#"{"jsonrpc":"1.0","id":7,"method":"test_echo"}"#, 10).await;
+        assert_eq!(response["error"]["code"], -32600);
+
+        let response = handle_echo_body("1", 10).await;
+        assert_eq!(response["error"]["code"], -32600);
+        assert_eq!(response["id"], Value::Null);
+    }
+
+    #[tokio::test]
+    async fn batch_returns_a_response_per_request() {
+        let body = r#"[
+            {"jsonrpc":"2.0","id":1,"method":"test_echo","params":[1]},
+            {"jsonrpc":"2.0","method":"test_echo","params":[2]},
+            {"jsonrpc":"2.0","id":"three","method":"test_unknown"},
+            {"foo":"bar"}
+        ]"#;
+        let response = handle_echo_body(body, 10).await;
+        let responses = response.as_array().unwrap();
+        // The notification gets no response
+        assert_eq!(responses.len(), 3);
+        assert_eq!(responses[0]["id"], 1);
+        assert_eq!(responses[0]["result"], serde_json::json!([1]));
+        assert_eq!(responses[1]["id"], "three");
+        assert_eq!(responses[1]["error"]["code"], -32601);
+        assert_eq!(responses[2]["id"], Value::Null);
+        assert_eq!(responses[2]["error"]["code"], -32600);
+    }
+
+    #[tokio::test]
+    async fn notifications_get_no_response() {
+        let notification = r#"{"jsonrpc":"2.0","method":"test_echo","params":[1]}"#;
+        assert_eq!(handle_echo_body(notification, 10).await, Value::Null);
+        let batch = format!("[{notification},{notification}]");
+        assert_eq!(handle_echo_body(&batch, 10).await, Value::Null);
+    }
+
+    #[tokio::test]
+    async fn request_with_null_id_gets_a_response() {
+        let body = r#"{"jsonrpc":"2.0","id":null,"method":"test_echo","params":[1]}"#;
+        let response = handle_echo_body(body, 10).await;
+        assert_eq!(response["id"], Value::Null);
+        assert_eq!(response["result"], serde_json::json!([1]));
+    }
+
+    #[tokio::test]
+    async fn empty_or_too_large_batch_is_rejected() {
+        let response = handle_echo_body("[]", 10).await;
+        assert_eq!(response["error"]["code"], -32600);
+
+        let request = r#"{"jsonrpc":"2.0","id":1,"method":"test_echo"}"#;
+        let response = handle_echo_body(&format!("[{request},{request}]"), 1).await;
+        assert_eq!(response["error"]["code"], -32600);
+        assert_eq!(response["id"], Value::Null);
+    }
+
+    #[tokio::test]
+    async fn requests_are_recorded_in_metrics() {
+        handle_echo_body(r#"{"jsonrpc":"2.0","id":1,"method":"test_echo"}"#, 10).await;
+        handle_echo_body(r#"{"jsonrpc":"2.0","id":1,"method":"test_missing"}"#, 10).await;
+        let metrics = ethrex_metrics::gather_metrics().unwrap();
+        assert!(
+            metrics.contains(r#"ethrex_rpc_request_duration_seconds_count{method="test_echo"}"#)
+        );
+        assert!(metrics.contains(r#"ethrex_rpc_request_errors_total{method="unknown"}"#));
+        assert!(!metrics.contains("test_missing"));
+    }
+
     fn example_chain_config() -> ChainConfig {
         ChainConfig {
             chain_id: 3151908_u64,
@@ -437,4 +1223,453 @@ mod tests {
             ..Default::default()
         }
     }
+
+    #[tokio::test]
+    async fn net_and_client_methods() {
+        let context = execution_api_context(EvmEngine::REVM);
+        let chain_id = context.storage.get_chain_config().unwrap().chain_id;
+        let expected = [
+            ("net_version", "[]", Value::String(chain_id.to_string())),
+            ("net_peerCount", "[]", Value::String("0x0".to_string())),
+            ("net_listening", "[]", Value::Bool(true)),
+            ("eth_accounts", "[]", Value::Array(vec![])),
+            ("eth_mining", "[]", Value::Bool(false)),
+            ("eth_hashrate", "[]", Value::String("0x0".to_string())),
+            (
+                "eth_protocolVersion",
+                "[]",
+                Value::String("0x44".to_string()),
+            ),
+            (
+                "eth_coinbase",
+                "[]",
+                Value::String(format!("{:#x}", ethrex_core::Address::zero())),
+            ),
+            (
+                "eth_getUncleCountByBlockNumber",
+                r#"["0x0"]"#,
+                Value::String("0x0".to_string()),
+            ),
+            (
+                "eth_getUncleByBlockNumberAndIndex",
+                r#"["0x0", "0x0"]"#,
+                Value::Null,
+            ),
+        ];
+        for (method, params, expected) in expected {
+            let body =
+                format!(r#"{{"jsonrpc":"2.0","id":1,"method":"{method}","params":{params}}}"#);
+            let request: RpcRequest = serde_json::from_str(&body).unwrap();
+            let result = map_http_requests(&request, context.clone()).await;
+            assert_eq!(result.unwrap(), expected, "unexpected result for {method}");
+        }
+    }
+
+    #[tokio::test]
+    async fn local_signer_sends_transactions_of_its_accounts() {
+        let secret_key = secp256k1::SecretKey::from_slice(&[0x22; 32]).unwrap();
+        let sender = signer::keystore::address_of(&secret_key);
+        let mut genesis = read_execution_api_genesis_file();
+        genesis.alloc.insert(
+            sender,
+            GenesisAccount {
+                code: Bytes::new(),
+                storage: Default::default(),
+                balance: U256::from(10).pow(U256::from(18)),
+                nonce: 0,
+            },
+        );
+        let storage =
+            Store::new("temp.db", EngineType::InMemory).expect("Failed to create test DB");
+        storage
+            .add_initial_state(genesis)
+            .expect("Failed to add genesis block to DB");
+        let keystore_dir =
+            std::env::temp_dir().join(format!("ethrex-rpc-keystore-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&keystore_dir);
+        let signer = Signer::open(&keystore_dir, signer::keystore::LIGHT_SCRYPT_LOG_N).unwrap();
+        let context = RpcApiContext {
+            storage: storage.clone(),
+            signer: Some(signer),
+            ..execution_api_context(EvmEngine::REVM)
+        };
+        let call = |method: &str, params: String| {
+            let body =
+                format!(r#"{{"jsonrpc":"2.0","id":1,"method":"{method}","params":{params}}}"#);
+            let request: RpcRequest = serde_json::from_str(&body).unwrap();
+            let context = context.clone();
+            async move { map_ipc_requests(&request, context).await }
+        };
+        let sender_value = serde_json::to_value(sender).unwrap();
+
+        assert_eq!(
+            call("eth_accounts", "[]".to_string()).await.unwrap(),
+            json!([])
+        );
+        let result = call(
+            "personal_importRawKey",
+            format!(
+                r#"["{}", "password"]"#,
+                hex::encode(secret_key.secret_bytes())
+            ),
+        )
+        .await;
+        assert_eq!(result.unwrap(), sender_value);
+        assert_eq!(
+            call("eth_accounts", "[]".to_string()).await.unwrap(),
+            json!([sender_value])
+        );
+
+        // The account must be unlocked to sign without its password
+        let transaction =
+            format!(r#"{{"from":"{sender:#x}","to":"{OVERRIDDEN_ADDRESS}","value":"0x1"}}"#);
+        let result = call("eth_sendTransaction", format!("[{transaction}]")).await;
+        assert!(matches!(result, Err(RpcErr::Signer(_))));
+        let result = call(
+            "personal_unlockAccount",
+            format!(r#"["{sender:#x}", "password", 0]"#),
+        )
+        .await;
+        assert_eq!(result.unwrap(), Value::Bool(true));
+
+        let signature = call("eth_sign", format!(r#"["{sender:#x}", "0x68656c6c6f"]"#))
+            .await
+            .unwrap();
+        let result = call(
+            "personal_ecRecover",
+            format!(r#"["0x68656c6c6f", {signature}]"#),
+        )
+        .await;
+        assert_eq!(result.unwrap(), sender_value);
+
+        // Nonces follow the transactions already sent to the mempool
+        for nonce in 0..2 {
+            let hash = call("eth_sendTransaction", format!("[{transaction}]"))
+                .await
+                .unwrap();
+            let pool = storage.filter_pool_transactions(&|_| true).unwrap();
+            let sent = pool[&sender]
+                .iter()
+                .find(|tx| Value::String(format!("{:#x}", tx.compute_hash())) == hash)
+                .expect("Transaction wasn't added to the mempool");
+            assert_eq!(sent.nonce(), nonce);
+            assert_eq!(sent.sender(), sender);
+            assert_eq!(sent.tx_type(), TxType::EIP1559);
+        }
+
+        // A password signs without unlocking the account
+        call("personal_lockAccount", format!(r#"["{sender:#x}"]"#))
+            .await
+            .unwrap();
+        let result = call(
+            "personal_signTransaction",
+            format!(r#"[{transaction}, "password"]"#),
+        )
+        .await;
+        assert_eq!(result.unwrap()["tx"]["nonce"], json!("0x2"));
+        std::fs::remove_dir_all(keystore_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn local_signer_is_only_served_over_http_when_allowed() {
+        let keystore_dir =
+            std::env::temp_dir().join(format!("ethrex-rpc-http-keystore-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&keystore_dir);
+        let signer = Signer::open(&keystore_dir, signer::keystore::LIGHT_SCRYPT_LOG_N).unwrap();
+        let context = RpcApiContext {
+            signer: Some(signer),
+            ..execution_api_context(EvmEngine::REVM)
+        };
+        let request = |method: &str, params: &str| -> RpcRequest {
+            let body =
+                format!(r#"{{"jsonrpc":"2.0","id":1,"method":"{method}","params":{params}}}"#);
+            serde_json::from_str(&body).unwrap()
+        };
+        let transaction = format!(r#"[{{"from":"{OVERRIDDEN_ADDRESS}","value":"0x1"}}]"#);
+        for (method, params) in [
+            ("personal_listAccounts", "[]"),
+            (
+                "personal_unlockAccount",
+                r#"["0x00000000000000000000000000000000000000aa", "password", 0]"#,
+            ),
+            ("eth_sendTransaction", transaction.as_str()),
+        ] {
+            let result = map_http_requests(&request(method, params), context.clone()).await;
+            assert!(matches!(result, Err(RpcErr::Signer(_))), "{method}");
+            let result = map_authrpc_requests(&request(method, params), context.clone()).await;
+            assert!(matches!(result, Err(RpcErr::MethodNotFound(_))), "{method}");
+        }
+        // The accounts can still be listed
+        let result = map_http_requests(&request("eth_accounts", "[]"), context.clone()).await;
+        assert_eq!(result.unwrap(), json!([]));
+
+        let context = RpcApiContext {
+            allow_insecure_unlock: true,
+            ..context
+        };
+        let result = map_http_requests(&request("personal_listAccounts", "[]"), context).await;
+        assert_eq!(result.unwrap(), json!([]));
+        std::fs::remove_dir_all(keystore_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn personal_namespace_needs_the_local_signer() {
+        let context = execution_api_context(EvmEngine::REVM);
+        let body = r#"{"jsonrpc":"2.0","id":1,"method":"personal_listAccounts","params":[]}"#;
+        let request: RpcRequest = serde_json::from_str(body).unwrap();
+        let result = map_http_requests(&request, context).await;
+        assert!(matches!(result, Err(RpcErr::MethodNotFound(_))));
+    }
+
+    async fn graphql(context: &RpcApiContext, query: &str, variables: Value) -> Value {
+        let body = json!({ "query": query, "variables": variables }).to_string();
+        let response = graphql::handle_graphql_request(State(context.clone()), body).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn graphql_queries_blocks_accounts_and_calls() {
+        let context = execution_api_context(EvmEngine::REVM);
+        let query = r#"
+            query Genesis($number: Long!, $slot: Bytes32!) {
+                block(number: $number) {
+                    number
+                    parent { number }
+                    transactionCount
+                    ...state
+                }
+                missing: block(number: 100) { number }
+                chainID
+            }
+            fragment state on Block {
+                __typename
+                contract: account(address: "0x8bebc8ba651aee624937e7d897853ac30c95a067") {
+                    balance
+                    transactionCount
+                    storage(slot: $slot)
+                }
+                call(data: { to: "0x00000000000000000000000000000000000000aa" }) { data status }
+                estimateGas(data: { to: "0x00000000000000000000000000000000000000aa" })
+                withdrawals @skip(if: true) { index }
+            }
+        "#;
+        let response = graphql(&context, query, json!({ "number": 0, "slot": word(2) })).await;
+        assert_eq!(
+            response,
+            json!({
+                "data": {
+                    "block": {
+                        "number": "0x0",
+                        "parent": null,
+                        "transactionCount": "0x0",
+                        "__typename": "Block",
+                        "contract": {
+                            "balance": "0x1",
+                            "transactionCount": "0x1",
+                            "storage": word(2),
+                        },
+                        "call": { "data": "0x", "status": "0x1" },
+                        "estimateGas": "0x5208",
+                    },
+                    "missing": null,
+                    "chainID": "0xc72dd9d5e883e",
+                }
+            })
+        );
+
+        // Failing fields are left null and reported along with their path
+        let response = graphql(&context, "{ block { number unknown } }", json!({})).await;
+        assert_eq!(
+            response["data"],
+            json!({ "block": { "number": "0x0", "unknown": null } })
+        );
+        assert_eq!(response["errors"][0]["path"], json!(["block", "unknown"]));
+
+        // Block ranges are capped
+        let response = graphql(
+            &context,
+            "{ blocks(from: 0, to: 1024) { number } }",
+            json!({}),
+        )
+        .await;
+        assert_eq!(response["data"], json!({ "blocks": null }));
+        assert_eq!(response["errors"][0]["path"], json!(["blocks"]));
+    }
+
+    #[tokio::test]
+    async fn graphql_sends_raw_transactions() {
+        let secret_key = secp256k1::SecretKey::from_slice(&[0x33; 32]).unwrap();
+        let sender = signer::keystore::address_of(&secret_key);
+        let mut genesis = read_execution_api_genesis_file();
+        genesis.alloc.insert(
+            sender,
+            GenesisAccount {
+                code: Bytes::new(),
+                storage: Default::default(),
+                balance: U256::from(10).pow(U256::from(18)),
+                nonce: 0,
+            },
+        );
+        let storage =
+            Store::new("temp.db", EngineType::InMemory).expect("Failed to create test DB");
+        storage
+            .add_initial_state(genesis.clone())
+            .expect("Failed to add genesis block to DB");
+        let context = RpcApiContext {
+            storage,
+            ..execution_api_context(EvmEngine::REVM)
+        };
+        let transaction = Transaction::EIP1559Transaction(EIP1559Transaction {
+            chain_id: genesis.config.chain_id,
+            max_priority_fee_per_gas: 1_000_000_000,
+            max_fee_per_gas: 10_000_000_000,
+            gas_limit: 21_000,
+            to: TxKind::Call(Address::from_str(OVERRIDDEN_ADDRESS).unwrap()),
+            value: U256::from(1),
+            ..Default::default()
+        })
+        .sign(&secret_key);
+        let hash = format!("{:#x}", transaction.compute_hash());
+
+        let response = graphql(
+            &context,
+            "mutation Send($data: Bytes!) { sendRawTransaction(data: $data) }",
+            json!({ "data": format!("0x{}", hex::encode(transaction.encode_canonical_to_vec())) }),
+        )
+        .await;
+        assert_eq!(response, json!({ "data": { "sendRawTransaction": hash } }));
+
+        let query = r#"
+            query Pending($hash: Bytes32!) {
+                pending { transactionCount }
+                transaction(hash: $hash) {
+                    hash
+                    index
+                    from { address }
+                    ... on Transaction { value status }
+                }
+            }
+        "#;
+        let response = graphql(&context, query, json!({ "hash": hash })).await;
+        assert_eq!(
+            response,
+            json!({
+                "data": {
+                    "pending": { "transactionCount": "0x1" },
+                    "transaction": {
+                        "hash": hash,
+                        "index": null,
+                        "from": { "address": format!("{sender:#x}") },
+                        "value": "0x1",
+                        "status": null,
+                    },
+                }
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn exchanged_capabilities_are_served() {
+        let context = execution_api_context(EvmEngine::REVM);
+        let body = r#"{"jsonrpc":"2.0","id":1,"method":"engine_exchangeCapabilities","params":[["engine_newPayloadV1"]]}"#;
+        let request: RpcRequest = serde_json::from_str(body).unwrap();
+        let result = map_engine_requests(&request, context.clone()).await;
+        assert_eq!(result.unwrap(), json!(engine::CAPABILITIES));
+        for method in engine::CAPABILITIES {
+            let request = RpcRequest {
+                method: method.to_string(),
+                ..Default::default()
+            };
+            let result = map_engine_requests(&request, context.clone()).await;
+            assert!(
+                !matches!(result, Err(RpcErr::MethodNotFound(_))),
+                "{method}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn ipc_serves_streamed_requests_of_every_namespace() {
+        use std::os::unix::fs::PermissionsExt;
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+        use tokio::net::UnixStream;
+
+        let path = std::env::temp_dir().join(format!("ethrex-{}.ipc", std::process::id()));
+        let context = execution_api_context(EvmEngine::REVM);
+        let server_path = path.clone();
+        tokio::task::spawn(async move { ipc::serve_ipc(&server_path, context).await });
+        let mut stream = loop {
+            if let Ok(stream) = UnixStream::connect(&path).await {
+                break stream;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        };
+        // Only the owner can connect
+        let permissions = std::fs::metadata(&path).unwrap().permissions();
+        assert_eq!(permissions.mode() & 0o777, 0o600);
+
+        // A request split across writes, followed by a batch in the same write
+        let request = r#"{"jsonrpc":"2.0","id":1,"method":"eth_chainId","params":[]}"#;
+        let (start, end) = request.split_at(20);
+        stream.write_all(start.as_bytes()).await.unwrap();
+        stream.flush().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let batch = r#"[{"jsonrpc":"2.0","id":2,"method":"engine_exchangeCapabilities","params":[[]]},{"jsonrpc":"2.0","id":3,"method":"net_version","params":[]}]"#;
+        stream
+            .write_all(format!("{end}\n{batch}").as_bytes())
+            .await
+            .unwrap();
+
+        let mut lines = BufReader::new(stream).lines();
+        let response: Value =
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert_eq!(response["id"], json!(1));
+        assert_eq!(response["result"], json!("0xc72dd9d5e883e"));
+        let responses: Value =
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert_eq!(responses[0]["id"], json!(2));
+        assert_eq!(responses[0]["result"], json!(engine::CAPABILITIES));
+        assert_eq!(responses[1]["id"], json!(3));
+        assert_eq!(responses[1]["result"], json!("3503995874084926"));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn ipc_only_replaces_stale_sockets() {
+        use std::os::unix::net::{UnixListener, UnixStream};
+
+        let context = execution_api_context(EvmEngine::REVM);
+        let path = std::env::temp_dir().join(format!("ethrex-{}.not-ipc", std::process::id()));
+        std::fs::write(&path, "not a socket").unwrap();
+        assert!(ipc::serve_ipc(&path, context.clone()).await.is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "not a socket");
+        std::fs::remove_file(path).unwrap();
+
+        // The socket of a running node is left alone
+        let path = std::env::temp_dir().join(format!("ethrex-{}.live-ipc", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let live_listener = UnixListener::bind(&path).unwrap();
+        let error = ipc::serve_ipc(&path, context.clone()).await.unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::AddrInUse);
+        assert!(UnixStream::connect(&path).is_ok());
+
+        // Once the node stops, its socket is replaced
+        drop(live_listener);
+        let server_path = path.clone();
+        tokio::task::spawn(async move { ipc::serve_ipc(&server_path, context).await });
+        let mut connected = false;
+        for _ in 0..100 {
+            if tokio::net::UnixStream::connect(&path).await.is_ok() {
+                connected = true;
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(connected);
+        std::fs::remove_file(path).unwrap();
+    }
 }
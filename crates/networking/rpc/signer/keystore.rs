@@ -0,0 +1,336 @@
+//! Encrypted key files in the Web3 Secret Storage format (version 3), the one geth and most
+//! wallets use.
+//! See: https://ethereum.org/en/developers/docs/data-structures-and-encoding/web3-secret-storage/
+
+use aes::cipher::{KeyIvInit, StreamCipher};
+use ethrex_core::{Address, H256};
+use rand::RngCore;
+use secp256k1::{PublicKey, SecretKey, SECP256K1};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use sha3::{Digest, Keccak256};
+
+use super::SignerError;
+
+type Aes128Ctr = ctr::Ctr128BE<aes::Aes128>;
+
+const KEYSTORE_VERSION: u64 = 3;
+const CIPHER: &str = "aes-128-ctr";
+const DERIVED_KEY_LEN: usize = 32;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+
+/// Scrypt cost used for new key files, the same geth uses by default
+pub const STANDARD_SCRYPT_LOG_N: u8 = 18;
+/// Scrypt cost for devnets and tests, where key files don't need to resist brute forcing
+pub const LIGHT_SCRYPT_LOG_N: u8 = 12;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct KeyFile {
+    /// Lowercase hex without the 0x prefix, it isn't required by the format
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    // Older versions of geth wrote the field capitalized
+    #[serde(alias = "Crypto")]
+    pub crypto: CryptoParams,
+    pub id: String,
+    pub version: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CryptoParams {
+    pub cipher: String,
+    pub ciphertext: String,
+    pub cipherparams: CipherParams,
+    pub kdf: String,
+    pub kdfparams: KdfParams,
+    pub mac: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CipherParams {
+    pub iv: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum KdfParams {
+    Scrypt {
+        dklen: usize,
+        n: u64,
+        p: u32,
+        r: u32,
+        salt: String,
+    },
+    Pbkdf2 {
+        c: u32,
+        dklen: usize,
+        prf: String,
+        salt: String,
+    },
+}
+
+impl KeyFile {
+    /// Encrypts the key with the password, deriving the encryption key with scrypt
+    pub fn encrypt(
+        secret_key: &SecretKey,
+        password: &str,
+        scrypt_log_n: u8,
+    ) -> Result<KeyFile, SignerError> {
+        let mut rng = rand::thread_rng();
+        let mut salt = [0u8; 32];
+        rng.fill_bytes(&mut salt);
+        let mut iv = [0u8; 16];
+        rng.fill_bytes(&mut iv);
+
+        let kdfparams = KdfParams::Scrypt {
+            dklen: DERIVED_KEY_LEN,
+            n: 1 << scrypt_log_n,
+            p: SCRYPT_P,
+            r: SCRYPT_R,
+            salt: hex::encode(salt),
+        };
+        let derived_key = derive_key(password, &kdfparams)?;
+
+        let mut ciphertext = secret_key.secret_bytes().to_vec();
+        Aes128Ctr::new(derived_key[..16].into(), &iv.into()).apply_keystream(&mut ciphertext);
+        let mac = compute_mac(&derived_key, &ciphertext);
+
+        Ok(KeyFile {
+            address: Some(hex::encode(address_of(secret_key))),
+            crypto: CryptoParams {
+                cipher: CIPHER.to_owned(),
+                ciphertext: hex::encode(ciphertext),
+                cipherparams: CipherParams {
+                    iv: hex::encode(iv),
+                },
+                kdf: kdf_name(&kdfparams).to_owned(),
+                kdfparams,
+                mac: hex::encode(mac),
+            },
+            id: random_uuid(&mut rng),
+            version: KEYSTORE_VERSION,
+        })
+    }
+
+    /// Decrypts the key, failing with `WrongPassword` when the MAC doesn't match the one
+    /// derived from the password
+    pub fn decrypt(&self, password: &str) -> Result<SecretKey, SignerError> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(SignerError::InvalidKeyFile(format!(
+                "unsupported version {}",
+                self.version
+            )));
+        }
+        let crypto = &self.crypto;
+        if crypto.cipher != CIPHER {
+            return Err(SignerError::InvalidKeyFile(format!(
+                "unsupported cipher {}",
+                crypto.cipher
+            )));
+        }
+        if crypto.kdf != kdf_name(&crypto.kdfparams) {
+            return Err(SignerError::InvalidKeyFile(format!(
+                "kdf {} doesn't match its params",
+                crypto.kdf
+            )));
+        }
+
+        let derived_key = derive_key(password, &crypto.kdfparams)?;
+        let mut ciphertext = decode_hex(&crypto.ciphertext)?;
+        let mac = decode_hex(&crypto.mac)?;
+        if compute_mac(&derived_key, &ciphertext).as_bytes() != mac.as_slice() {
+            return Err(SignerError::WrongPassword);
+        }
+
+        let iv: [u8; 16] = decode_hex(&crypto.cipherparams.iv)?
+            .try_into()
+            .map_err(|_| SignerError::InvalidKeyFile("iv must be 16 bytes long".to_owned()))?;
+        Aes128Ctr::new(derived_key[..16].into(), &iv.into()).apply_keystream(&mut ciphertext);
+        SecretKey::from_slice(&ciphertext)
+            .map_err(|error| SignerError::InvalidKeyFile(error.to_string()))
+    }
+
+    /// The address stated in the file, if any, which can only be trusted after decrypting it
+    pub fn address(&self) -> Option<Address> {
+        let address = self.address.as_ref()?;
+        let bytes = hex::decode(address.trim_start_matches("0x")).ok()?;
+        (bytes.len() == Address::len_bytes()).then(|| Address::from_slice(&bytes))
+    }
+}
+
+pub fn address_of(secret_key: &SecretKey) -> Address {
+    let public_key = PublicKey::from_secret_key(SECP256K1, secret_key);
+    let hash = Keccak256::digest(&public_key.serialize_uncompressed()[1..]);
+    Address::from_slice(&hash[12..])
+}
+
+fn kdf_name(kdfparams: &KdfParams) -> &'static str {
+    match kdfparams {
+        KdfParams::Scrypt { .. } => "scrypt",
+        KdfParams::Pbkdf2 { .. } => "pbkdf2",
+    }
+}
+
+fn derive_key(password: &str, kdfparams: &KdfParams) -> Result<Vec<u8>, SignerError> {
+    match kdfparams {
+        KdfParams::Scrypt {
+            dklen,
+            n,
+            p,
+            r,
+            salt,
+        } => {
+            if *dklen < DERIVED_KEY_LEN || !n.is_power_of_two() {
+                return Err(SignerError::InvalidKeyFile(
+                    "invalid scrypt params".to_owned(),
+                ));
+            }
+            let params = scrypt::Params::new(n.trailing_zeros() as u8, *r, *p, *dklen)
+                .map_err(|error| SignerError::InvalidKeyFile(error.to_string()))?;
+            let mut derived_key = vec![0; *dklen];
+            scrypt::scrypt(
+                password.as_bytes(),
+                &decode_hex(salt)?,
+                &params,
+                &mut derived_key,
+            )
+            .map_err(|error| SignerError::InvalidKeyFile(error.to_string()))?;
+            Ok(derived_key)
+        }
+        KdfParams::Pbkdf2 {
+            c,
+            dklen,
+            prf,
+            salt,
+        } => {
+            if prf != "hmac-sha256" {
+                return Err(SignerError::InvalidKeyFile(format!(
+                    "unsupported pbkdf2 prf {prf}"
+                )));
+            }
+            if *dklen < DERIVED_KEY_LEN {
+                return Err(SignerError::InvalidKeyFile(
+                    "invalid pbkdf2 params".to_owned(),
+                ));
+            }
+            let mut derived_key = vec![0; *dklen];
+            pbkdf2::pbkdf2_hmac::<Sha256>(
+                password.as_bytes(),
+                &decode_hex(salt)?,
+                *c,
+                &mut derived_key,
+            );
+            Ok(derived_key)
+        }
+    }
+}
+
+fn compute_mac(derived_key: &[u8], ciphertext: &[u8]) -> H256 {
+    let mut hasher = Keccak256::new();
+    hasher.update(&derived_key[16..32]);
+    hasher.update(ciphertext);
+    H256::from_slice(&hasher.finalize())
+}
+
+fn decode_hex(value: &str) -> Result<Vec<u8>, SignerError> {
+    hex::decode(value).map_err(|error| SignerError::InvalidKeyFile(error.to_string()))
+}
+
+/// Random (version 4) UUID, used as the id of new key files
+fn random_uuid(rng: &mut impl RngCore) -> String {
+    let mut bytes = [0u8; 16];
+    rng.fill_bytes(&mut bytes);
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = hex::encode(bytes);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    // Test vector of the Web3 Secret Storage definition
+    const PBKDF2_KEY_FILE: &str = r#"{
+        "crypto": {
+            "cipher": "aes-128-ctr",
+            "cipherparams": { "iv": "6087dab2f9fdbbfaddc31a909735c1e6" },
+            "ciphertext": "5318b4d5bcd28de64ee5559e671353e16f075ecae9f99c7a79a38af5f869aa46",
+            "kdf": "pbkdf2",
+            "kdfparams": {
+                "c": 262144,
+                "dklen": 32,
+                "prf": "hmac-sha256",
+                "salt": "ae3cd4e7013836a3df6bd7241b12db061dbe2c6785853cce422d148a624ce0bd"
+            },
+            "mac": "517ead924a9d0dc3124507e3393d175ce3ff7c1e96529c6c555ce9e51205e9b2"
+        },
+        "id": "3198bc9c-6672-5ab3-d995-4942343ae5b6",
+        "version": 3
+    }"#;
+
+    // Same key with geth's light scrypt params, as the definition's vector uses r = 1 along with
+    // a cost RFC 7914 doesn't allow
+    const SCRYPT_KEY_FILE: &str = r#"{
+        "crypto": {
+            "cipher": "aes-128-ctr",
+            "cipherparams": { "iv": "83dbcc02d8ccb40e466191a123791e0e" },
+            "ciphertext": "3b4309355ad643f2b15cfb6a83a7f6f328e7a6459a56ab8c6e25a89c8f43eb80",
+            "kdf": "scrypt",
+            "kdfparams": {
+                "dklen": 32,
+                "n": 4096,
+                "p": 1,
+                "r": 8,
+                "salt": "ab0c7876052600dd703518d6fc3fe8984592145b591fc8fb5c6d43190334ba19"
+            },
+            "mac": "994d83f6bfb7e6e3aa95980f72b6ad87db9d352789d0f2e433cf777425db3a42"
+        },
+        "id": "3198bc9c-6672-5ab3-d995-4942343ae5b6",
+        "version": 3
+    }"#;
+
+    const SECRET_KEY: &str = "7a28b5ba57c53603b0b07b56bba752f7784bf506fa95edc395f5cf6c7514fe9d";
+
+    #[test]
+    fn decrypt_pbkdf2_key_file() {
+        let key_file: KeyFile = serde_json::from_str(PBKDF2_KEY_FILE).unwrap();
+        let secret_key = key_file.decrypt("testpassword").unwrap();
+        assert_eq!(secret_key, SecretKey::from_str(SECRET_KEY).unwrap());
+    }
+
+    #[test]
+    fn decrypt_scrypt_key_file() {
+        let key_file: KeyFile = serde_json::from_str(SCRYPT_KEY_FILE).unwrap();
+        let secret_key = key_file.decrypt("testpassword").unwrap();
+        assert_eq!(secret_key, SecretKey::from_str(SECRET_KEY).unwrap());
+    }
+
+    #[test]
+    fn decrypt_with_wrong_password() {
+        let key_file: KeyFile = serde_json::from_str(PBKDF2_KEY_FILE).unwrap();
+        assert!(matches!(
+            key_file.decrypt("wrongpassword"),
+            Err(SignerError::WrongPassword)
+        ));
+    }
+
+    #[test]
+    fn encrypted_key_file_roundtrip() {
+        let secret_key = SecretKey::from_str(SECRET_KEY).unwrap();
+        let key_file = KeyFile::encrypt(&secret_key, "password", LIGHT_SCRYPT_LOG_N).unwrap();
+        let json = serde_json::to_string(&key_file).unwrap();
+        let key_file: KeyFile = serde_json::from_str(&json).unwrap();
+        assert_eq!(key_file.address(), Some(address_of(&secret_key)));
+        assert_eq!(key_file.decrypt("password").unwrap(), secret_key);
+    }
+}
@@ -0,0 +1,381 @@
+//! Local signer, holding the accounts of the keystore directory so the node can sign messages
+//! and transactions on their behalf. Only meant for devnets and faucets, it's disabled unless
+//! the node is started with `--signer`.
+
+pub mod keystore;
+
+use std::{
+    collections::BTreeMap,
+    fs::{self, DirBuilder, OpenOptions},
+    io::Write,
+    os::unix::fs::{DirBuilderExt, OpenOptionsExt},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use bytes::Bytes;
+use ethrex_core::{Address, H256};
+use keystore::{address_of, KeyFile};
+use secp256k1::{
+    ecdsa::{RecoverableSignature, RecoveryId},
+    Message, SecretKey, SECP256K1,
+};
+use sha3::{Digest, Keccak256};
+use tracing::warn;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SignerError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid key file: {0}")]
+    InvalidKeyFile(String),
+    #[error("Could not decrypt key with given password")]
+    WrongPassword,
+    #[error("Unknown account {0:#x}")]
+    UnknownAccount(Address),
+    #[error("Account {0:#x} is locked")]
+    AccountLocked(Address),
+    #[error("Account {0:#x} already exists")]
+    AccountExists(Address),
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Signer {
+    keystore_dir: PathBuf,
+    scrypt_log_n: u8,
+    accounts: Arc<Mutex<BTreeMap<Address, Account>>>,
+}
+
+#[derive(Debug)]
+struct Account {
+    key_file: PathBuf,
+    unlocked: Option<UnlockedKey>,
+}
+
+#[derive(Debug)]
+struct UnlockedKey {
+    secret_key: SecretKey,
+    /// Unlocked until the node stops when not set
+    expires_at: Option<Instant>,
+}
+
+impl Signer {
+    /// Loads the accounts of the keystore directory, creating it if missing. Files that aren't
+    /// key files or don't state their address are skipped. New key files are encrypted with
+    /// a scrypt cost of `2^scrypt_log_n`.
+    pub fn open(keystore_dir: &Path, scrypt_log_n: u8) -> Result<Self, SignerError> {
+        // Like the key files, the directory is only accessible to the owner
+        DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(keystore_dir)?;
+        let mut accounts = BTreeMap::new();
+        for entry in fs::read_dir(keystore_dir)? {
+            let path = entry?.path();
+            if !path.is_file() {
+                continue;
+            }
+            match read_key_file(&path).map(|key_file| key_file.address()) {
+                Ok(Some(address)) => {
+                    accounts.insert(
+                        address,
+                        Account {
+                            key_file: path,
+                            unlocked: None,
+                        },
+                    );
+                }
+                Ok(None) => warn!("Skipping key file without address {}", path.display()),
+                Err(error) => warn!("Skipping {}: {error}", path.display()),
+            }
+        }
+        Ok(Self {
+            keystore_dir: keystore_dir.to_path_buf(),
+            scrypt_log_n,
+            accounts: Arc::new(Mutex::new(accounts)),
+        })
+    }
+
+    /// Addresses of the accounts in the keystore, locked or not
+    pub fn accounts(&self) -> Vec<Address> {
+        self.lock_accounts().keys().copied().collect()
+    }
+
+    /// Unlocks every account that can be decrypted with one of the passwords until the node
+    /// stops, returning the unlocked addresses
+    pub fn unlock_with_passwords(&self, passwords: &[String]) -> Vec<Address> {
+        let mut unlocked = vec![];
+        for address in self.accounts() {
+            let unlocked_with = passwords
+                .iter()
+                .find(|password| self.unlock(address, password, None).is_ok());
+            match unlocked_with {
+                Some(_) => unlocked.push(address),
+                None => warn!("Couldn't unlock account {address:#x} with the password file"),
+            }
+        }
+        unlocked
+    }
+
+    /// Generates a new key, storing it encrypted with the password in the keystore
+    pub fn new_account(&self, password: &str) -> Result<Address, SignerError> {
+        let (secret_key, _) = SECP256K1.generate_keypair(&mut rand::thread_rng());
+        self.import_key(&secret_key, password)
+    }
+
+    /// Stores the key encrypted with the password in the keystore
+    pub fn import_key(
+        &self,
+        secret_key: &SecretKey,
+        password: &str,
+    ) -> Result<Address, SignerError> {
+        let address = address_of(secret_key);
+        if self.lock_accounts().contains_key(&address) {
+            return Err(SignerError::AccountExists(address));
+        }
+        let key_file = KeyFile::encrypt(secret_key, password, self.scrypt_log_n)?;
+        let json = serde_json::to_vec(&key_file)
+            .map_err(|error| SignerError::InvalidKeyFile(error.to_string()))?;
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let path = self
+            .keystore_dir
+            .join(format!("UTC--{timestamp}--{}", hex::encode(address)));
+        // Readable only by the owner, as anyone with the file can try to guess the password
+        OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&path)?
+            .write_all(&json)?;
+        self.lock_accounts().insert(
+            address,
+            Account {
+                key_file: path,
+                unlocked: None,
+            },
+        );
+        Ok(address)
+    }
+
+    /// Keeps the decrypted key of the account in memory for the given duration, or until the
+    /// node stops if there's none
+    pub fn unlock(
+        &self,
+        address: Address,
+        password: &str,
+        duration: Option<Duration>,
+    ) -> Result<(), SignerError> {
+        let secret_key = self.decrypt(address, password)?;
+        let mut accounts = self.lock_accounts();
+        let account = accounts
+            .get_mut(&address)
+            .ok_or(SignerError::UnknownAccount(address))?;
+        account.unlocked = Some(UnlockedKey {
+            secret_key,
+            expires_at: duration.map(|duration| Instant::now() + duration),
+        });
+        Ok(())
+    }
+
+    /// Drops the decrypted key of the account
+    pub fn lock(&self, address: Address) -> Result<(), SignerError> {
+        let mut accounts = self.lock_accounts();
+        let account = accounts
+            .get_mut(&address)
+            .ok_or(SignerError::UnknownAccount(address))?;
+        account.unlocked = None;
+        Ok(())
+    }
+
+    /// Key of the account, decrypted with the password if given, or else the one kept by
+    /// unlocking it
+    pub fn secret_key(
+        &self,
+        address: Address,
+        password: Option<&str>,
+    ) -> Result<SecretKey, SignerError> {
+        if let Some(password) = password {
+            return self.decrypt(address, password);
+        }
+        let mut accounts = self.lock_accounts();
+        let account = accounts
+            .get_mut(&address)
+            .ok_or(SignerError::UnknownAccount(address))?;
+        match &account.unlocked {
+            Some(UnlockedKey {
+                expires_at: Some(expires_at),
+                ..
+            }) if *expires_at <= Instant::now() => {
+                account.unlocked = None;
+                Err(SignerError::AccountLocked(address))
+            }
+            Some(unlocked) => Ok(unlocked.secret_key),
+            None => Err(SignerError::AccountLocked(address)),
+        }
+    }
+
+    fn decrypt(&self, address: Address, password: &str) -> Result<SecretKey, SignerError> {
+        let path = self
+            .lock_accounts()
+            .get(&address)
+            .map(|account| account.key_file.clone())
+            .ok_or(SignerError::UnknownAccount(address))?;
+        // Decrypting is slow on purpose, so it's done without holding the accounts lock
+        let secret_key = read_key_file(&path)?.decrypt(password)?;
+        if address_of(&secret_key) != address {
+            return Err(SignerError::InvalidKeyFile(format!(
+                "{} holds the key of another address",
+                path.display()
+            )));
+        }
+        Ok(secret_key)
+    }
+
+    fn lock_accounts(&self) -> std::sync::MutexGuard<'_, BTreeMap<Address, Account>> {
+        // The lock can't be poisoned as no code holding it panics
+        self.accounts
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+    }
+}
+
+fn read_key_file(path: &Path) -> Result<KeyFile, SignerError> {
+    let json = fs::read(path)?;
+    serde_json::from_slice(&json).map_err(|error| SignerError::InvalidKeyFile(error.to_string()))
+}
+
+/// Hash signed by eth_sign and personal_sign, which prefixes the message as EIP-191 states so
+/// signatures can't be replayed as transactions
+pub fn message_hash(message: &[u8]) -> H256 {
+    let mut hasher = Keccak256::new();
+    hasher.update(format!("\x19Ethereum Signed Message:\n{}", message.len()));
+    hasher.update(message);
+    H256::from_slice(&hasher.finalize())
+}
+
+/// Signs the message as EIP-191 states, returning the signature as r, s and v, with v being
+/// 27 or 28
+pub fn sign_message(secret_key: &SecretKey, message: &[u8]) -> Bytes {
+    let digest = Message::from_digest(message_hash(message).0);
+    let (recovery_id, signature) = SECP256K1
+        .sign_ecdsa_recoverable(&digest, secret_key)
+        .serialize_compact();
+    let mut signature = signature.to_vec();
+    signature.push(27 + recovery_id.to_i32() as u8);
+    Bytes::from(signature)
+}
+
+/// Recovers the address that signed the message with `sign_message`
+pub fn recover_message_signer(message: &[u8], signature: &[u8]) -> Result<Address, SignerError> {
+    let [compact @ .., v] = signature else {
+        return Err(SignerError::InvalidSignature("empty signature".to_owned()));
+    };
+    if compact.len() != 64 || !(*v == 27 || *v == 28) {
+        return Err(SignerError::InvalidSignature(
+            "expected 65 bytes with v being 27 or 28".to_owned(),
+        ));
+    }
+    let recovery_id = RecoveryId::from_i32((*v - 27) as i32)
+        .map_err(|error| SignerError::InvalidSignature(error.to_string()))?;
+    let signature = RecoverableSignature::from_compact(compact, recovery_id)
+        .map_err(|error| SignerError::InvalidSignature(error.to_string()))?;
+    let digest = Message::from_digest(message_hash(message).0);
+    let public_key = SECP256K1
+        .recover_ecdsa(&digest, &signature)
+        .map_err(|error| SignerError::InvalidSignature(error.to_string()))?;
+    let hash = Keccak256::digest(&public_key.serialize_uncompressed()[1..]);
+    Ok(Address::from_slice(&hash[12..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keystore::LIGHT_SCRYPT_LOG_N;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn temp_keystore(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("ethrex-keystore-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn accounts_are_loaded_from_the_keystore() {
+        let dir = temp_keystore("load");
+        let signer = Signer::open(&dir, LIGHT_SCRYPT_LOG_N).unwrap();
+        let address = signer.new_account("password").unwrap();
+        // Only the owner can access the keystore and read the keys
+        assert_eq!(
+            fs::metadata(&dir).unwrap().permissions().mode() & 0o777,
+            0o700
+        );
+        let key_file = fs::read_dir(&dir).unwrap().next().unwrap().unwrap().path();
+        assert_eq!(
+            fs::metadata(key_file).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+        fs::write(dir.join("README"), "not a key file").unwrap();
+
+        let signer = Signer::open(&dir, LIGHT_SCRYPT_LOG_N).unwrap();
+        assert_eq!(signer.accounts(), vec![address]);
+        let unlocked = signer.unlock_with_passwords(&["wrong".to_owned(), "password".to_owned()]);
+        assert_eq!(unlocked, vec![address]);
+        assert!(signer.secret_key(address, None).is_ok());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn accounts_lock_and_unlock() {
+        let dir = temp_keystore("unlock");
+        let signer = Signer::open(&dir, LIGHT_SCRYPT_LOG_N).unwrap();
+        let address = signer.new_account("password").unwrap();
+        assert!(matches!(
+            signer.secret_key(address, None),
+            Err(SignerError::AccountLocked(_))
+        ));
+        assert!(matches!(
+            signer.unlock(address, "wrong", None),
+            Err(SignerError::WrongPassword)
+        ));
+        // A password allows using the key without unlocking the account
+        assert!(signer.secret_key(address, Some("password")).is_ok());
+
+        signer
+            .unlock(address, "password", Some(Duration::ZERO))
+            .unwrap();
+        assert!(matches!(
+            signer.secret_key(address, None),
+            Err(SignerError::AccountLocked(_))
+        ));
+        signer.unlock(address, "password", None).unwrap();
+        assert!(signer.secret_key(address, None).is_ok());
+        signer.lock(address).unwrap();
+        assert!(matches!(
+            signer.secret_key(address, None),
+            Err(SignerError::AccountLocked(_))
+        ));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn signed_message_recovers_its_signer() {
+        let secret_key = SecretKey::from_slice(&[0x11; 32]).unwrap();
+        let signature = sign_message(&secret_key, b"hello");
+        assert_eq!(signature.len(), 65);
+        assert_eq!(
+            recover_message_signer(b"hello", &signature).unwrap(),
+            address_of(&secret_key)
+        );
+        assert_ne!(
+            recover_message_signer(b"bye", &signature).unwrap(),
+            address_of(&secret_key)
+        );
+    }
+}
@@ -2,7 +2,7 @@ use super::transaction::RpcTransaction;
 use ethrex_core::{
     serde_utils,
     types::{Block, BlockBody, BlockHash, BlockHeader, BlockNumber, Withdrawal},
-    H256, U256,
+    Address, H256, U256,
 };
 use ethrex_rlp::encode::RLPEncode;
 
@@ -52,19 +52,23 @@ impl RpcBlock {
         full_transactions: bool,
         total_difficulty: U256,
     ) -> RpcBlock {
-        let size = Block::new(header.clone(), body.clone())
-            .encode_to_vec()
-            .len();
         let body_wrapper = if full_transactions {
-            BlockBodyWrapper::Full(FullBlockBody::from_body(body, header.number, hash))
+            BlockBodyWrapper::Full(FullBlockBody::from_body(body.clone(), header.number, hash))
         } else {
-            BlockBodyWrapper::OnlyHashes(OnlyHashesBlockBody {
-                transactions: body.transactions.iter().map(|t| t.compute_hash()).collect(),
-                uncles: body.ommers,
-                withdrawals: body.withdrawals.unwrap_or_default(),
-            })
+            BlockBodyWrapper::OnlyHashes(OnlyHashesBlockBody::from_body(body.clone()))
         };
+        Self::build_with_body(header, body, hash, body_wrapper, total_difficulty)
+    }
 
+    /// Builds the block with an already built body, whose transactions may not be signed
+    pub fn build_with_body(
+        header: BlockHeader,
+        body: BlockBody,
+        hash: H256,
+        body_wrapper: BlockBodyWrapper,
+        total_difficulty: U256,
+    ) -> RpcBlock {
+        let size = Block::new(header.clone(), body).encode_to_vec().len();
         RpcBlock {
             hash,
             total_difficulty,
@@ -80,11 +84,22 @@ impl FullBlockBody {
         body: BlockBody,
         block_number: BlockNumber,
         block_hash: BlockHash,
+    ) -> FullBlockBody {
+        let senders = body.transactions.iter().map(|tx| tx.sender()).collect();
+        Self::from_body_with_senders(body, senders, block_number, block_hash)
+    }
+
+    pub fn from_body_with_senders(
+        body: BlockBody,
+        senders: Vec<Address>,
+        block_number: BlockNumber,
+        block_hash: BlockHash,
     ) -> FullBlockBody {
         let mut transactions = Vec::new();
-        for (index, tx) in body.transactions.iter().enumerate() {
-            transactions.push(RpcTransaction::build(
+        for (index, (tx, sender)) in body.transactions.iter().zip(senders).enumerate() {
+            transactions.push(RpcTransaction::build_with_sender(
                 tx.clone(),
+                sender,
                 block_number,
                 block_hash,
                 index,
@@ -97,6 +112,16 @@ impl FullBlockBody {
         }
     }
 }
+
+impl OnlyHashesBlockBody {
+    pub fn from_body(body: BlockBody) -> OnlyHashesBlockBody {
+        OnlyHashesBlockBody {
+            transactions: body.transactions.iter().map(|t| t.compute_hash()).collect(),
+            uncles: body.ommers,
+            withdrawals: body.withdrawals.unwrap_or_default(),
+        }
+    }
+}
 #[cfg(test)]
 mod test {
 
@@ -1,6 +1,6 @@
 use std::{fmt::Display, str::FromStr};
 
-use ethrex_core::types::{BlockHash, BlockHeader, BlockNumber};
+use ethrex_core::types::{Block, BlockHash, BlockHeader, BlockNumber};
 use ethrex_storage::{error::StoreError, Store};
 use serde::Deserialize;
 use serde_json::Value;
@@ -77,11 +77,31 @@ impl BlockIdentifier {
     }
 
     pub fn resolve_block_header(&self, storage: &Store) -> Result<Option<BlockHeader>, StoreError> {
+        if let Some(pending_block) = self.resolve_pending_block(storage)? {
+            return Ok(Some(pending_block.header));
+        }
         match self.resolve_block_number(storage)? {
             Some(block_number) => storage.get_block_header(block_number),
             _ => Ok(None),
         }
     }
+
+    /// Returns the block built from the mempool if the identifier is the pending tag.
+    /// It's only returned when built on top of the current head, otherwise callers fall back to
+    /// the latest block until it's rebuilt.
+    pub fn resolve_pending_block(&self, storage: &Store) -> Result<Option<Block>, StoreError> {
+        if !matches!(self, BlockIdentifier::Tag(BlockTag::Pending)) {
+            return Ok(None);
+        }
+        let Some((pending_block, _)) = storage.get_current_pending_block()? else {
+            return Ok(None);
+        };
+        let head_hash = match storage.get_latest_block_number()? {
+            Some(head_number) => storage.get_canonical_block_hash(head_number)?,
+            None => None,
+        };
+        Ok((head_hash == Some(pending_block.header.parent_hash)).then_some(pending_block))
+    }
 }
 
 impl BlockIdentifierOrHash {
@@ -93,6 +113,23 @@ impl BlockIdentifierOrHash {
         }
     }
 
+    /// Resolves the hash of the block, which for the pending tag may be the one of the pending
+    /// block, so its state can be read even though it's not part of the chain
+    pub fn resolve_block_hash(&self, storage: &Store) -> Result<Option<BlockHash>, StoreError> {
+        match self {
+            BlockIdentifierOrHash::Hash(block_hash) => Ok(Some(*block_hash)),
+            BlockIdentifierOrHash::Identifier(id) => {
+                if let Some(pending_block) = id.resolve_pending_block(storage)? {
+                    return Ok(Some(pending_block.hash()));
+                }
+                match id.resolve_block_number(storage)? {
+                    Some(block_number) => storage.get_canonical_block_hash(block_number),
+                    None => Ok(None),
+                }
+            }
+        }
+    }
+
     pub fn parse(serde_value: Value, arg_index: u64) -> Result<BlockIdentifierOrHash, RpcErr> {
         // Parse as BlockHash
         if let Some(block_hash) = serde_json::from_value::<String>(serde_value.clone())
@@ -3,11 +3,13 @@ use ethrex_vm::EvmError;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use crate::authentication::AuthenticationError;
-use ethrex_blockchain::error::MempoolError;
+use crate::{authentication::AuthenticationError, signer::SignerError};
+use ethrex_blockchain::error::{MempoolError, SimulationError};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub enum RpcErr {
+    ParseError(String),
+    InvalidRequest(String),
     MethodNotFound(String),
     WrongParam(String),
     BadParams(String),
@@ -22,11 +24,24 @@ pub enum RpcErr {
     InvalidForkChoiceState(String),
     InvalidPayloadAttributes(String),
     UnknownPayload(String),
+    Simulation { code: i32, message: String },
+    Timeout(String),
+    Signer(String),
 }
 
 impl From<RpcErr> for RpcErrorMetadata {
     fn from(value: RpcErr) -> Self {
         match value {
+            RpcErr::ParseError(context) => RpcErrorMetadata {
+                code: -32700,
+                data: None,
+                message: format!("Parse error: {context}"),
+            },
+            RpcErr::InvalidRequest(context) => RpcErrorMetadata {
+                code: -32600,
+                data: None,
+                message: format!("Invalid request: {context}"),
+            },
             RpcErr::MethodNotFound(bad_method) => RpcErrorMetadata {
                 code: -32601,
                 data: None,
@@ -116,6 +131,21 @@ impl From<RpcErr> for RpcErrorMetadata {
                 data: None,
                 message: format!("Unknown payload: {context}"),
             },
+            RpcErr::Simulation { code, message } => RpcErrorMetadata {
+                code,
+                data: None,
+                message,
+            },
+            RpcErr::Timeout(method) => RpcErrorMetadata {
+                code: -32002,
+                data: None,
+                message: format!("Request timed out: {method}"),
+            },
+            RpcErr::Signer(context) => RpcErrorMetadata {
+                code: -32000,
+                data: None,
+                message: context,
+            },
         }
     }
 }
@@ -137,12 +167,34 @@ impl From<MempoolError> for RpcErr {
     }
 }
 
+impl From<SimulationError> for RpcErr {
+    fn from(err: SimulationError) -> Self {
+        let code = match err {
+            SimulationError::NonceTooLow { .. } => -38010,
+            SimulationError::NonceTooHigh { .. } => -38011,
+            SimulationError::BaseFeeTooLow { .. } => -38012,
+            SimulationError::BlockGasLimitReached { .. } => -38015,
+            SimulationError::BlockNumberNotIncreasing { .. } => -38020,
+            SimulationError::TimestampNotIncreasing { .. } => -38021,
+            SimulationError::TooManyBlocks(_) => -38026,
+            SimulationError::EvmError(_) => -32000,
+            SimulationError::StoreError(err) => return Self::Internal(err.to_string()),
+        };
+        Self::Simulation {
+            code,
+            message: err.to_string(),
+        }
+    }
+}
+
 pub enum RpcNamespace {
     Engine,
     Eth,
     Admin,
     Debug,
     Web3,
+    Net,
+    Personal,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -150,16 +202,23 @@ pub enum RpcNamespace {
 pub enum RpcRequestId {
     Number(u64),
     String(String),
+    /// Used when the id of the request is null or couldn't be read
+    Null,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct RpcRequest {
+    #[serde(default = "null_request_id")]
     pub id: RpcRequestId,
     pub jsonrpc: String,
     pub method: String,
     pub params: Option<Vec<Value>>,
 }
 
+fn null_request_id() -> RpcRequestId {
+    RpcRequestId::Null
+}
+
 impl RpcRequest {
     pub fn namespace(&self) -> Result<RpcNamespace, RpcErr> {
         let mut parts = self.method.split('_');
@@ -170,6 +229,8 @@ impl RpcRequest {
                 "admin" => Ok(RpcNamespace::Admin),
                 "debug" => Ok(RpcNamespace::Debug),
                 "web3" => Ok(RpcNamespace::Web3),
+                "net" => Ok(RpcNamespace::Net),
+                "personal" => Ok(RpcNamespace::Personal),
                 _ => Err(RpcErr::MethodNotFound(self.method.clone())),
             }
         } else {
@@ -218,6 +279,15 @@ impl From<StoreError> for RpcErr {
     }
 }
 
+impl From<SignerError> for RpcErr {
+    fn from(value: SignerError) -> Self {
+        match value {
+            SignerError::Io(error) => RpcErr::Internal(error.to_string()),
+            other => RpcErr::Signer(other.to_string()),
+        }
+    }
+}
+
 impl From<EvmError> for RpcErr {
     fn from(value: EvmError) -> Self {
         RpcErr::Vm(value.to_string())
@@ -244,13 +314,15 @@ pub fn parse_json_hex(hex: &serde_json::Value) -> Result<u64, String> {
 
 #[cfg(test)]
 pub mod test_utils {
-    use std::{net::SocketAddr, str::FromStr};
+    use std::{net::SocketAddr, str::FromStr, sync::Arc};
 
+    use ethrex_blockchain::EvmConfig;
     use ethrex_core::H512;
-    use ethrex_net::{sync::SyncManager, types::Node};
+    use ethrex_net::{sync::SyncManager, types::Node, KademliaTable};
     use ethrex_storage::{EngineType, Store};
+    use tokio::sync::Mutex;
 
-    use crate::start_api;
+    use crate::{start_api, DEFAULT_BATCH_REQUEST_LIMIT};
 
     pub const TEST_GENESIS: &str = include_str!("../../../test_data/genesis-l1.json");
     pub fn example_p2p_node() -> Node {
@@ -263,6 +335,10 @@ pub mod test_utils {
         }
     }
 
+    pub fn example_peer_table() -> Arc<Mutex<KademliaTable>> {
+        Arc::new(Mutex::new(KademliaTable::new(example_p2p_node().node_id)))
+    }
+
     // Util to start an api for testing on ports 8500 and 8501,
     // mostly for when hive is missing some endpoints to test
     // like eth_uninstallFilter.
@@ -283,15 +359,22 @@ pub mod test_utils {
             .add_initial_state(serde_json::from_str(TEST_GENESIS).unwrap())
             .expect("Failed to build test genesis");
 
-        let jwt_secret = Default::default();
+        let jwt_auth = Default::default();
         let local_p2p_node = example_p2p_node();
         start_api(
             http_addr,
             authrpc_addr,
             storage,
-            jwt_secret,
+            jwt_auth,
             local_p2p_node,
             SyncManager::dummy(),
+            example_peer_table(),
+            EvmConfig::default(),
+            DEFAULT_BATCH_REQUEST_LIMIT,
+            None,
+            false,
+            false,
+            None,
         )
         .await;
     }
@@ -5,7 +5,7 @@ use ethrex_core::types::{
 };
 use std::{fmt::Debug, panic::RefUnwindSafe};
 
-use crate::error::StoreError;
+use crate::{error::StoreError, write_batch::WriteBatch};
 use ethrex_trie::Trie;
 
 pub trait StoreEngine: Debug + Send + Sync + RefUnwindSafe {
@@ -214,6 +214,27 @@ pub trait StoreEngine: Debug + Send + Sync + RefUnwindSafe {
     // Unsets canonical block for a block number.
     fn unset_canonical_block(&self, number: BlockNumber) -> Result<(), StoreError>;
 
+    // Sets the canonical hashes for the given block numbers and updates the latest block number,
+    // all within a single transaction.
+    fn update_canonical_chain(
+        &self,
+        canonical_blocks: &[(BlockNumber, BlockHash)],
+        latest_block_number: BlockNumber,
+    ) -> Result<(), StoreError>;
+
+    // Obtain the version of the DB layout, None if it was never set
+    fn get_schema_version(&self) -> Result<Option<u64>, StoreError>;
+
+    // Set the version of the DB layout
+    fn set_schema_version(&self, version: u64) -> Result<(), StoreError>;
+
+    // Upgrades the DB layout from `from_version` to the next version and records the new version,
+    // all within a single transaction
+    fn migrate_schema(&self, from_version: u64) -> Result<(), StoreError>;
+
+    // Applies all the writes in the batch within a single transaction
+    fn write_batch(&self, batch: WriteBatch) -> Result<(), StoreError>;
+
     fn add_payload(&self, payload_id: u64, block: Block) -> Result<(), StoreError>;
 
     fn get_payload(&self, payload_id: u64) -> Result<Option<Block>, StoreError>;
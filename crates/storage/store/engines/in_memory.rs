@@ -1,4 +1,7 @@
-use crate::error::StoreError;
+use crate::{
+    error::StoreError,
+    write_batch::{WriteBatch, WriteOp},
+};
 use bytes::Bytes;
 use ethereum_types::{H256, U256};
 use ethrex_core::types::{
@@ -50,6 +53,7 @@ struct ChainData {
     // TODO (#307): Remove TotalDifficulty.
     latest_total_difficulty: Option<U256>,
     pending_block_number: Option<BlockNumber>,
+    schema_version: Option<u64>,
 }
 
 impl Store {
@@ -340,6 +344,98 @@ impl StoreEngine for Store {
         Ok(())
     }
 
+    fn update_canonical_chain(
+        &self,
+        canonical_blocks: &[(BlockNumber, BlockHash)],
+        latest_block_number: BlockNumber,
+    ) -> Result<(), StoreError> {
+        let mut store = self.inner();
+        store
+            .canonical_hashes
+            .extend(canonical_blocks.iter().copied());
+        store
+            .chain_data
+            .latest_block_number
+            .replace(latest_block_number);
+        Ok(())
+    }
+
+    fn get_schema_version(&self) -> Result<Option<u64>, StoreError> {
+        Ok(self.inner().chain_data.schema_version)
+    }
+
+    fn set_schema_version(&self, version: u64) -> Result<(), StoreError> {
+        self.inner().chain_data.schema_version.replace(version);
+        Ok(())
+    }
+
+    fn migrate_schema(&self, from_version: u64) -> Result<(), StoreError> {
+        // The in-memory store doesn't outlive the process, so there is no layout to upgrade
+        self.set_schema_version(from_version + 1)
+    }
+
+    fn write_batch(&self, batch: WriteBatch) -> Result<(), StoreError> {
+        // Holding the lock for the whole batch keeps other readers from seeing it half-applied
+        let mut store = self.inner();
+        for op in batch.ops {
+            match op {
+                WriteOp::BlockHeader(hash, header) => {
+                    store.headers.insert(hash, header);
+                }
+                WriteOp::BlockBody(hash, body) => {
+                    store.bodies.insert(hash, body);
+                }
+                WriteOp::BlockNumber(hash, number) => {
+                    store.block_numbers.insert(hash, number);
+                }
+                WriteOp::BlockTotalDifficulty(hash, total_difficulty) => {
+                    store
+                        .block_total_difficulties
+                        .insert(hash, total_difficulty);
+                }
+                WriteOp::LatestTotalDifficulty(total_difficulty) => {
+                    store
+                        .chain_data
+                        .latest_total_difficulty
+                        .replace(total_difficulty);
+                }
+                WriteOp::TransactionLocation {
+                    transaction_hash,
+                    block_number,
+                    block_hash,
+                    index,
+                } => store
+                    .transaction_locations
+                    .entry(transaction_hash)
+                    .or_default()
+                    .push((block_number, block_hash, index)),
+                WriteOp::Receipt(hash, index, receipt) => {
+                    store
+                        .receipts
+                        .entry(hash)
+                        .or_default()
+                        .insert(index, receipt);
+                }
+                WriteOp::AccountCode(code_hash, code) => {
+                    store.account_codes.insert(code_hash, code);
+                }
+                WriteOp::StateTrieNode(key, node) => {
+                    store.state_trie_nodes.lock().unwrap().insert(key, node);
+                }
+                WriteOp::StorageTrieNode(hashed_address, key, node) => {
+                    store
+                        .storage_trie_nodes
+                        .entry(hashed_address)
+                        .or_default()
+                        .lock()
+                        .unwrap()
+                        .insert(key, node);
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn add_payload(&self, payload_id: u64, block: Block) -> Result<(), StoreError> {
         self.inner().payloads.insert(payload_id, block);
         Ok(())
@@ -5,6 +5,7 @@ use crate::rlp::{
     AccountCodeHashRLP, AccountCodeRLP, BlockBodyRLP, BlockHashRLP, BlockHeaderRLP, BlockRLP,
     BlockTotalDifficultyRLP, ReceiptRLP, Rlp, TransactionHashRLP, TupleRLP,
 };
+use crate::write_batch::{WriteBatch, WriteOp};
 use anyhow::Result;
 use bytes::Bytes;
 use ethereum_types::{H256, U256};
@@ -13,7 +14,7 @@ use ethrex_core::types::{
 };
 use ethrex_rlp::decode::RLPDecode;
 use ethrex_rlp::encode::RLPEncode;
-use ethrex_trie::{LibmdbxDupsortTrieDB, LibmdbxTrieDB, Trie};
+use ethrex_trie::{db::node_hash_to_fixed_size, LibmdbxDupsortTrieDB, LibmdbxTrieDB, Trie};
 use libmdbx::orm::{Decodable, Encodable, Table};
 use libmdbx::{
     dupsort,
@@ -350,6 +351,85 @@ impl StoreEngine for Store {
             .map(|o| o.map(|hash_rlp| hash_rlp.to()))
     }
 
+    fn get_schema_version(&self) -> Result<Option<u64>, StoreError> {
+        match self.read::<ChainData>(ChainDataIndex::SchemaVersion)? {
+            None => Ok(None),
+            Some(ref rlp) => RLPDecode::decode(rlp)
+                .map(Some)
+                .map_err(|_| StoreError::DecodeError),
+        }
+    }
+
+    fn set_schema_version(&self, version: u64) -> Result<(), StoreError> {
+        self.write::<ChainData>(ChainDataIndex::SchemaVersion, version.encode_to_vec())
+    }
+
+    fn migrate_schema(&self, from_version: u64) -> Result<(), StoreError> {
+        let txn = self
+            .db
+            .begin_readwrite()
+            .map_err(StoreError::LibmdbxError)?;
+        match from_version {
+            // Version 1 only started recording the schema version, the layout is unchanged
+            0 => {}
+            _ => return Err(StoreError::MissingMigration(from_version)),
+        }
+        txn.upsert::<ChainData>(
+            ChainDataIndex::SchemaVersion,
+            (from_version + 1).encode_to_vec(),
+        )
+        .map_err(StoreError::LibmdbxError)?;
+        txn.commit().map_err(StoreError::LibmdbxError)
+    }
+
+    fn write_batch(&self, batch: WriteBatch) -> Result<(), StoreError> {
+        let txn = self
+            .db
+            .begin_readwrite()
+            .map_err(StoreError::LibmdbxError)?;
+        for op in batch.ops {
+            let written = match op {
+                WriteOp::BlockHeader(hash, header) => {
+                    txn.upsert::<Headers>(hash.into(), header.into())
+                }
+                WriteOp::BlockBody(hash, body) => txn.upsert::<Bodies>(hash.into(), body.into()),
+                WriteOp::BlockNumber(hash, number) => {
+                    txn.upsert::<BlockNumbers>(hash.into(), number)
+                }
+                WriteOp::BlockTotalDifficulty(hash, total_difficulty) => {
+                    txn.upsert::<BlockTotalDifficulties>(hash.into(), total_difficulty.into())
+                }
+                WriteOp::LatestTotalDifficulty(total_difficulty) => txn.upsert::<ChainData>(
+                    ChainDataIndex::LatestTotalDifficulty,
+                    total_difficulty.encode_to_vec(),
+                ),
+                WriteOp::TransactionLocation {
+                    transaction_hash,
+                    block_number,
+                    block_hash,
+                    index,
+                } => txn.upsert::<TransactionLocations>(
+                    transaction_hash.into(),
+                    (block_number, block_hash, index).into(),
+                ),
+                WriteOp::Receipt(hash, index, receipt) => {
+                    txn.upsert::<Receipts>((hash, index).into(), receipt.into())
+                }
+                WriteOp::AccountCode(code_hash, code) => {
+                    txn.upsert::<AccountCodes>(code_hash.into(), code.into())
+                }
+                WriteOp::StateTrieNode(key, node) => txn.upsert::<StateTrieNodes>(key, node),
+                WriteOp::StorageTrieNode(hashed_address, key, node) => txn
+                    .upsert::<StorageTriesNodes>(
+                        (hashed_address.0, node_hash_to_fixed_size(key)),
+                        node,
+                    ),
+            };
+            written.map_err(StoreError::LibmdbxError)?;
+        }
+        txn.commit().map_err(StoreError::LibmdbxError)
+    }
+
     fn add_payload(&self, payload_id: u64, block: Block) -> Result<(), StoreError> {
         self.write::<Payloads>(payload_id, block.into())
     }
@@ -409,6 +489,27 @@ impl StoreEngine for Store {
             .map_err(StoreError::LibmdbxError)
     }
 
+    fn update_canonical_chain(
+        &self,
+        canonical_blocks: &[(BlockNumber, BlockHash)],
+        latest_block_number: BlockNumber,
+    ) -> Result<(), StoreError> {
+        let txn = self
+            .db
+            .begin_readwrite()
+            .map_err(StoreError::LibmdbxError)?;
+        for (number, hash) in canonical_blocks {
+            txn.upsert::<CanonicalBlockHashes>(*number, (*hash).into())
+                .map_err(StoreError::LibmdbxError)?;
+        }
+        txn.upsert::<ChainData>(
+            ChainDataIndex::LatestBlockNumber,
+            latest_block_number.encode_to_vec(),
+        )
+        .map_err(StoreError::LibmdbxError)?;
+        txn.commit().map_err(StoreError::LibmdbxError)
+    }
+
     fn add_pending_block(&self, block: Block) -> std::result::Result<(), StoreError> {
         self.write::<PendingBlocks>(block.header.compute_block_hash().into(), block.into())
     }
@@ -9,12 +9,13 @@ use ethrex_core::{
 use ethrex_rlp::decode::RLPDecode;
 use ethrex_rlp::encode::RLPEncode;
 use ethrex_trie::{
-    db::{redb::RedBTrie, redb_multitable::RedBMultiTableTrieDB},
+    db::{node_hash_to_fixed_size, redb::RedBTrie, redb_multitable::RedBMultiTableTrieDB},
     Trie,
 };
 use redb::{AccessGuard, Database, Key, MultimapTableDefinition, TableDefinition, TypeName, Value};
 
 use crate::rlp::{BlockRLP, BlockTotalDifficultyRLP, Rlp, TransactionHashRLP};
+use crate::write_batch::{WriteBatch, WriteOp};
 use crate::{
     error::StoreError,
     rlp::{
@@ -25,8 +26,8 @@ use crate::{
 
 use super::{api::StoreEngine, utils::ChainDataIndex};
 
-const STATE_TRIE_NODES_TABLE: TableDefinition<&[u8], &[u8]> =
-    TableDefinition::new("StateTrieNodes");
+// Same table the state trie reads its nodes from, see `RedBTrie`
+const STATE_TRIE_NODES_TABLE: TableDefinition<&[u8], &[u8]> = TableDefinition::new("Trie");
 const BLOCK_NUMBERS_TABLE: TableDefinition<BlockHashRLP, BlockNumber> =
     TableDefinition::new("BlockNumbers");
 const BLOCK_TOTAL_DIFFICULTIES_TABLE: TableDefinition<BlockHashRLP, BlockTotalDifficultyRLP> =
@@ -528,6 +529,139 @@ impl StoreEngine for RedBStore {
         self.delete(CANONICAL_BLOCK_HASHES_TABLE, number)
     }
 
+    fn update_canonical_chain(
+        &self,
+        canonical_blocks: &[(BlockNumber, BlockHash)],
+        latest_block_number: BlockNumber,
+    ) -> Result<(), StoreError> {
+        let write_txn = self.db.begin_write()?;
+        {
+            let mut canonical_hashes = write_txn.open_table(CANONICAL_BLOCK_HASHES_TABLE)?;
+            for (number, hash) in canonical_blocks {
+                canonical_hashes.insert(*number, <H256 as Into<BlockHashRLP>>::into(*hash))?;
+            }
+            write_txn.open_table(CHAIN_DATA_TABLE)?.insert(
+                ChainDataIndex::LatestBlockNumber,
+                latest_block_number.encode_to_vec(),
+            )?;
+        }
+        write_txn.commit()?;
+
+        Ok(())
+    }
+
+    fn get_schema_version(&self) -> Result<Option<u64>, StoreError> {
+        match self.read(CHAIN_DATA_TABLE, ChainDataIndex::SchemaVersion)? {
+            None => Ok(None),
+            Some(ref rlp) => RLPDecode::decode(&rlp.value())
+                .map(Some)
+                .map_err(|_| StoreError::DecodeError),
+        }
+    }
+
+    fn set_schema_version(&self, version: u64) -> Result<(), StoreError> {
+        self.write(
+            CHAIN_DATA_TABLE,
+            ChainDataIndex::SchemaVersion,
+            version.encode_to_vec(),
+        )
+    }
+
+    fn migrate_schema(&self, from_version: u64) -> Result<(), StoreError> {
+        let write_txn = self.db.begin_write()?;
+        match from_version {
+            // Version 1 only started recording the schema version, the layout is unchanged
+            0 => {}
+            _ => return Err(StoreError::MissingMigration(from_version)),
+        }
+        write_txn.open_table(CHAIN_DATA_TABLE)?.insert(
+            ChainDataIndex::SchemaVersion,
+            (from_version + 1).encode_to_vec(),
+        )?;
+        write_txn.commit()?;
+
+        Ok(())
+    }
+
+    fn write_batch(&self, batch: WriteBatch) -> Result<(), StoreError> {
+        let write_txn = self.db.begin_write()?;
+        for op in batch.ops {
+            match op {
+                WriteOp::BlockHeader(hash, header) => {
+                    write_txn.open_table(HEADERS_TABLE)?.insert(
+                        <H256 as Into<BlockHashRLP>>::into(hash),
+                        <BlockHeader as Into<BlockHeaderRLP>>::into(header),
+                    )?;
+                }
+                WriteOp::BlockBody(hash, body) => {
+                    write_txn.open_table(BLOCK_BODIES_TABLE)?.insert(
+                        <H256 as Into<BlockHashRLP>>::into(hash),
+                        <BlockBody as Into<BlockBodyRLP>>::into(body),
+                    )?;
+                }
+                WriteOp::BlockNumber(hash, number) => {
+                    write_txn
+                        .open_table(BLOCK_NUMBERS_TABLE)?
+                        .insert(<H256 as Into<BlockHashRLP>>::into(hash), number)?;
+                }
+                WriteOp::BlockTotalDifficulty(hash, total_difficulty) => {
+                    write_txn
+                        .open_table(BLOCK_TOTAL_DIFFICULTIES_TABLE)?
+                        .insert(
+                            <H256 as Into<BlockHashRLP>>::into(hash),
+                            <U256 as Into<Rlp<U256>>>::into(total_difficulty),
+                        )?;
+                }
+                WriteOp::LatestTotalDifficulty(total_difficulty) => {
+                    write_txn.open_table(CHAIN_DATA_TABLE)?.insert(
+                        ChainDataIndex::LatestTotalDifficulty,
+                        total_difficulty.encode_to_vec(),
+                    )?;
+                }
+                WriteOp::TransactionLocation {
+                    transaction_hash,
+                    block_number,
+                    block_hash,
+                    index,
+                } => {
+                    write_txn
+                        .open_multimap_table(TRANSACTION_LOCATIONS_TABLE)?
+                        .insert(
+                            <H256 as Into<TransactionHashRLP>>::into(transaction_hash),
+                            <(u64, H256, u64) as Into<Rlp<(BlockNumber, BlockHash, Index)>>>::into(
+                                (block_number, block_hash, index),
+                            ),
+                        )?;
+                }
+                WriteOp::Receipt(hash, index, receipt) => {
+                    write_txn.open_table(RECEIPTS_TABLE)?.insert(
+                        <(H256, u64) as Into<TupleRLP<BlockHash, Index>>>::into((hash, index)),
+                        <Receipt as Into<ReceiptRLP>>::into(receipt),
+                    )?;
+                }
+                WriteOp::AccountCode(code_hash, code) => {
+                    write_txn.open_table(ACCOUNT_CODES_TABLE)?.insert(
+                        <H256 as Into<AccountCodeHashRLP>>::into(code_hash),
+                        <bytes::Bytes as Into<AccountCodeRLP>>::into(code),
+                    )?;
+                }
+                WriteOp::StateTrieNode(key, node) => {
+                    write_txn
+                        .open_table(STATE_TRIE_NODES_TABLE)?
+                        .insert(&*key, &*node)?;
+                }
+                WriteOp::StorageTrieNode(hashed_address, key, node) => {
+                    write_txn
+                        .open_multimap_table(STORAGE_TRIE_NODES_TABLE)?
+                        .insert((hashed_address.0, node_hash_to_fixed_size(key)), &*node)?;
+                }
+            }
+        }
+        write_txn.commit()?;
+
+        Ok(())
+    }
+
     fn add_payload(&self, payload_id: u64, block: Block) -> Result<(), StoreError> {
         self.write(
             PAYLOADS_TABLE,
@@ -38,4 +38,10 @@ pub enum StoreError {
     Trie(#[from] TrieError),
     #[error("missing store: is an execution DB being used instead?")]
     MissingStore,
+    #[error("DB schema version {found} is outdated, the current version is {expected}. Run `ethrex db migrate` to upgrade it")]
+    OutdatedSchemaVersion { found: u64, expected: u64 },
+    #[error("DB schema version {found} is not supported, the latest supported version is {expected}. The DB was created by a newer release")]
+    UnsupportedSchemaVersion { found: u64, expected: u64 },
+    #[error("No migration available from DB schema version {0}")]
+    MissingMigration(u64),
 }
@@ -17,14 +17,21 @@ use ethrex_rlp::encode::RLPEncode;
 use ethrex_trie::Trie;
 use serde::{Deserialize, Serialize};
 use sha3::{Digest as _, Keccak256};
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
-use std::sync::{Arc, Mutex};
+use std::sync::{
+    atomic::{AtomicU64, Ordering as AtomicOrdering},
+    Arc, Mutex,
+};
 use tracing::info;
 
 mod engines;
 pub mod error;
 mod rlp;
+mod write_batch;
+
+pub use write_batch::WriteBatch;
 
 #[derive(Debug, Clone)]
 pub struct Store {
@@ -32,8 +39,25 @@ pub struct Store {
     engine: Arc<dyn StoreEngine>,
     pub mempool: Arc<Mutex<HashMap<H256, MempoolTransaction>>>,
     pub blobs_bundle_pool: Arc<Mutex<HashMap<H256, BlobsBundle>>>,
+    /// Increased on every change to the mempool, so the pending block can tell if it's outdated
+    mempool_generation: Arc<AtomicU64>,
+    /// Block built on top of the head with the mempool transactions. Unlike the pending blocks
+    /// received through the engine API, it's only kept in memory, along with its state.
+    current_pending_block: Arc<Mutex<Option<CurrentPendingBlock>>>,
+}
+
+#[derive(Debug, Clone)]
+struct CurrentPendingBlock {
+    block: Block,
+    hash: BlockHash,
+    mempool_generation: u64,
+    /// Changes the block makes to its parent's state, by address. As they aren't written to
+    /// the DB, the block's state is read from them and its parent's
+    account_updates: PendingAccountUpdates,
 }
 
+type PendingAccountUpdates = Arc<HashMap<Address, AccountUpdate>>;
+
 #[allow(dead_code)]
 #[derive(Debug, Clone, Copy)]
 pub enum EngineType {
@@ -44,7 +68,7 @@ pub enum EngineType {
     RedB,
 }
 
-#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AccountUpdate {
     pub address: Address,
     pub removed: bool,
@@ -74,8 +98,33 @@ impl AccountUpdate {
     }
 }
 
+/// Version of the DB layout written by this release.
+/// It must be bumped whenever a table's layout or encoding changes, adding a migration from the
+/// previous version to each engine's `migrate_schema`.
+pub const STORE_SCHEMA_VERSION: u64 = 1;
+
+/// Schema versions whose migration to the next version leaves the layout unchanged.
+/// These migrations are run when the store is opened, the rest need `ethrex db migrate`.
+const LAYOUT_PRESERVING_MIGRATIONS: &[u64] = &[0];
+
 impl Store {
+    /// Opens the store, failing if its DB was written with a different schema version.
+    /// Outdated DBs are upgraded if none of the pending migrations change the layout.
     pub fn new(path: &str, engine_type: EngineType) -> Result<Self, StoreError> {
+        let store = Self::open(path, engine_type)?;
+        store.prepare_schema_version()?;
+        Ok(store)
+    }
+
+    /// Opens the store and upgrades its DB to the current schema version.
+    /// Returns the schema version the DB had before the migration.
+    pub fn migrate(path: &str, engine_type: EngineType) -> Result<u64, StoreError> {
+        let store = Self::open(path, engine_type)?;
+        store.stamp_empty_schema_version()?;
+        store.migrate_schema()
+    }
+
+    fn open(path: &str, engine_type: EngineType) -> Result<Self, StoreError> {
         info!("Starting storage engine ({engine_type:?})");
         let store = match engine_type {
             #[cfg(feature = "libmdbx")]
@@ -83,23 +132,94 @@ impl Store {
                 engine: Arc::new(LibmdbxStore::new(path)?),
                 mempool: Arc::new(Mutex::new(HashMap::new())),
                 blobs_bundle_pool: Arc::new(Mutex::new(HashMap::new())),
+                mempool_generation: Arc::new(AtomicU64::new(0)),
+                current_pending_block: Arc::new(Mutex::new(None)),
             },
             EngineType::InMemory => Self {
                 engine: Arc::new(InMemoryStore::new()),
                 mempool: Arc::new(Mutex::new(HashMap::new())),
                 blobs_bundle_pool: Arc::new(Mutex::new(HashMap::new())),
+                mempool_generation: Arc::new(AtomicU64::new(0)),
+                current_pending_block: Arc::new(Mutex::new(None)),
             },
             #[cfg(feature = "redb")]
             EngineType::RedB => Self {
                 engine: Arc::new(RedBStore::new()?),
                 mempool: Arc::new(Mutex::new(HashMap::new())),
                 blobs_bundle_pool: Arc::new(Mutex::new(HashMap::new())),
+                mempool_generation: Arc::new(AtomicU64::new(0)),
+                current_pending_block: Arc::new(Mutex::new(None)),
             },
         };
         info!("Started store engine");
         Ok(store)
     }
 
+    /// Returns the schema version of the DB.
+    /// DBs created before the schema version started being recorded are at version 0.
+    pub fn get_schema_version(&self) -> Result<u64, StoreError> {
+        Ok(self.engine.get_schema_version()?.unwrap_or_default())
+    }
+
+    /// Records the current schema version in an empty DB, as there is nothing to migrate
+    fn stamp_empty_schema_version(&self) -> Result<(), StoreError> {
+        if self.engine.get_schema_version()?.is_none()
+            && self.engine.get_latest_block_number()?.is_none()
+        {
+            self.engine.set_schema_version(STORE_SCHEMA_VERSION)?;
+        }
+        Ok(())
+    }
+
+    /// Gets the DB ready to be used by this release: empty DBs are set to the current schema
+    /// version and outdated DBs are migrated if the layout is unchanged, anything else fails
+    fn prepare_schema_version(&self) -> Result<(), StoreError> {
+        self.stamp_empty_schema_version()?;
+        let version = self.get_schema_version()?;
+        if version < STORE_SCHEMA_VERSION
+            && (version..STORE_SCHEMA_VERSION)
+                .all(|version| LAYOUT_PRESERVING_MIGRATIONS.contains(&version))
+        {
+            self.migrate_schema()?;
+        }
+        self.check_schema_version()
+    }
+
+    fn check_schema_version(&self) -> Result<(), StoreError> {
+        let found = self.get_schema_version()?;
+        match found.cmp(&STORE_SCHEMA_VERSION) {
+            Ordering::Equal => Ok(()),
+            Ordering::Less => Err(StoreError::OutdatedSchemaVersion {
+                found,
+                expected: STORE_SCHEMA_VERSION,
+            }),
+            Ordering::Greater => Err(StoreError::UnsupportedSchemaVersion {
+                found,
+                expected: STORE_SCHEMA_VERSION,
+            }),
+        }
+    }
+
+    /// Runs the migrations needed to bring the DB up to the current schema version, one version
+    /// at a time. Each step is committed on its own, so an interrupted migration can be resumed.
+    fn migrate_schema(&self) -> Result<u64, StoreError> {
+        let initial_version = self.get_schema_version()?;
+        if initial_version > STORE_SCHEMA_VERSION {
+            return Err(StoreError::UnsupportedSchemaVersion {
+                found: initial_version,
+                expected: STORE_SCHEMA_VERSION,
+            });
+        }
+        for version in initial_version..STORE_SCHEMA_VERSION {
+            info!(
+                "Migrating DB from schema version {version} to {}",
+                version + 1
+            );
+            self.engine.migrate_schema(version)?;
+        }
+        Ok(initial_version)
+    }
+
     pub fn get_account_info(
         &self,
         block_number: BlockNumber,
@@ -116,19 +236,30 @@ impl Store {
         block_hash: BlockHash,
         address: Address,
     ) -> Result<Option<AccountInfo>, StoreError> {
-        let Some(state_trie) = self.state_trie(block_hash)? else {
-            return Ok(None);
-        };
-        let hashed_address = hash_address(&address);
-        let Some(encoded_state) = state_trie.get(&hashed_address)? else {
-            return Ok(None);
-        };
-        let account_state = AccountState::decode(&encoded_state)?;
-        Ok(Some(AccountInfo {
-            code_hash: account_state.code_hash,
-            balance: account_state.balance,
-            nonce: account_state.nonce,
-        }))
+        if let Some((parent_hash, account_updates)) = self.pending_state(block_hash)? {
+            return match account_updates.get(&address) {
+                Some(update) if update.removed => Ok(None),
+                Some(AccountUpdate {
+                    info: Some(info), ..
+                }) => Ok(Some(info.clone())),
+                _ => self.get_account_info_by_hash(parent_hash, address),
+            };
+        }
+        ethrex_metrics::store::time_read("account_info", || {
+            let Some(state_trie) = self.state_trie(block_hash)? else {
+                return Ok(None);
+            };
+            let hashed_address = hash_address(&address);
+            let Some(encoded_state) = state_trie.get(&hashed_address)? else {
+                return Ok(None);
+            };
+            let account_state = AccountState::decode(&encoded_state)?;
+            Ok(Some(AccountInfo {
+                code_hash: account_state.code_hash,
+                balance: account_state.balance,
+                nonce: account_state.nonce,
+            }))
+        })
     }
 
     pub fn add_block_header(
@@ -143,21 +274,37 @@ impl Store {
         &self,
         block_number: BlockNumber,
     ) -> Result<Option<BlockHeader>, StoreError> {
-        self.engine.get_block_header(block_number)
+        ethrex_metrics::store::time_read("block_header", || {
+            self.engine.get_block_header(block_number)
+        })
     }
 
     pub fn get_block_header_by_hash(
         &self,
         block_hash: BlockHash,
     ) -> Result<Option<BlockHeader>, StoreError> {
-        self.engine.get_block_header_by_hash(block_hash)
+        if let Some(header) = ethrex_metrics::store::time_read("block_header", || {
+            self.engine.get_block_header_by_hash(block_hash)
+        })? {
+            return Ok(Some(header));
+        }
+        // The current pending block isn't stored, but it's served like any other
+        Ok(self
+            .current_pending_block
+            .lock()
+            .map_err(|error| StoreError::Custom(error.to_string()))?
+            .as_ref()
+            .filter(|pending| pending.hash == block_hash)
+            .map(|pending| pending.block.header.clone()))
     }
 
     pub fn get_block_body_by_hash(
         &self,
         block_hash: BlockHash,
     ) -> Result<Option<BlockBody>, StoreError> {
-        self.engine.get_block_body_by_hash(block_hash)
+        ethrex_metrics::store::time_read("block_body", || {
+            self.engine.get_block_body_by_hash(block_hash)
+        })
     }
 
     pub fn add_block_body(
@@ -172,7 +319,7 @@ impl Store {
         &self,
         block_number: BlockNumber,
     ) -> Result<Option<BlockBody>, StoreError> {
-        self.engine.get_block_body(block_number)
+        ethrex_metrics::store::time_read("block_body", || self.engine.get_block_body(block_number))
     }
 
     pub fn add_pending_block(&self, block: Block) -> Result<(), StoreError> {
@@ -249,7 +396,12 @@ impl Store {
             .mempool
             .lock()
             .map_err(|error| StoreError::Custom(error.to_string()))?;
-        mempool.insert(hash, transaction);
+        let tx_type = transaction.tx_type();
+        if mempool.insert(hash, transaction).is_none() {
+            ethrex_metrics::mempool::transaction_added(&format!("{tx_type:?}"));
+        }
+        self.mempool_generation
+            .fetch_add(1, AtomicOrdering::Relaxed);
 
         Ok(())
     }
@@ -294,12 +446,85 @@ impl Store {
                     .remove(&tx.compute_hash());
             }
 
+            ethrex_metrics::mempool::transaction_removed(&format!("{:?}", tx.tx_type()));
             mempool.remove(hash);
+            self.mempool_generation
+                .fetch_add(1, AtomicOrdering::Relaxed);
         };
 
         Ok(())
     }
 
+    /// Returns the hashes of every transaction in the pool
+    pub fn get_pool_transaction_hashes(&self) -> Result<HashSet<H256>, StoreError> {
+        let mempool = self
+            .mempool
+            .lock()
+            .map_err(|error| StoreError::Custom(error.to_string()))?;
+        Ok(mempool.keys().copied().collect())
+    }
+
+    /// Returns a number that changes every time a transaction is added to or removed from the pool
+    pub fn get_mempool_generation(&self) -> u64 {
+        self.mempool_generation.load(AtomicOrdering::Relaxed)
+    }
+
+    /// Replaces the block served for the `pending` tag, built from the given mempool generation.
+    /// The account updates are the changes it makes to its parent's state, they are kept in
+    /// memory so the block's state can be read without writing it to the DB
+    pub fn set_current_pending_block(
+        &self,
+        block: Block,
+        account_updates: Vec<AccountUpdate>,
+        mempool_generation: u64,
+    ) -> Result<(), StoreError> {
+        let account_updates = account_updates
+            .into_iter()
+            .map(|update| (update.address, update))
+            .collect();
+        *self
+            .current_pending_block
+            .lock()
+            .map_err(|error| StoreError::Custom(error.to_string()))? = Some(CurrentPendingBlock {
+            hash: block.hash(),
+            block,
+            mempool_generation,
+            account_updates: Arc::new(account_updates),
+        });
+        Ok(())
+    }
+
+    /// Returns the block served for the `pending` tag along with the mempool generation it
+    /// was built from, if it was built at all
+    pub fn get_current_pending_block(&self) -> Result<Option<(Block, u64)>, StoreError> {
+        Ok(self
+            .current_pending_block
+            .lock()
+            .map_err(|error| StoreError::Custom(error.to_string()))?
+            .as_ref()
+            .map(|pending| (pending.block.clone(), pending.mempool_generation)))
+    }
+
+    /// Returns the parent hash and the account updates of the current pending block, if the
+    /// given hash is the one of that block
+    fn pending_state(
+        &self,
+        block_hash: BlockHash,
+    ) -> Result<Option<(BlockHash, PendingAccountUpdates)>, StoreError> {
+        Ok(self
+            .current_pending_block
+            .lock()
+            .map_err(|error| StoreError::Custom(error.to_string()))?
+            .as_ref()
+            .filter(|pending| pending.hash == block_hash)
+            .map(|pending| {
+                (
+                    pending.block.header.parent_hash,
+                    pending.account_updates.clone(),
+                )
+            }))
+    }
+
     /// Applies the filter and returns a set of suitable transactions from the mempool.
     /// These transactions will be grouped by sender and sorted by nonce
     pub fn filter_pool_transactions(
@@ -330,7 +555,25 @@ impl Store {
     }
 
     pub fn get_account_code(&self, code_hash: H256) -> Result<Option<Bytes>, StoreError> {
-        self.engine.get_account_code(code_hash)
+        if let Some(code) = ethrex_metrics::store::time_read("account_code", || {
+            self.engine.get_account_code(code_hash)
+        })? {
+            return Ok(Some(code));
+        }
+        // Contracts deployed by the current pending block only have their code in memory
+        let current_pending_block = self
+            .current_pending_block
+            .lock()
+            .map_err(|error| StoreError::Custom(error.to_string()))?;
+        Ok(current_pending_block.as_ref().and_then(|pending| {
+            pending.account_updates.values().find_map(|update| {
+                update
+                    .info
+                    .as_ref()
+                    .filter(|info| info.code_hash == code_hash)
+                    .and(update.code.clone())
+            })
+        }))
     }
 
     pub fn get_code_by_account_address(
@@ -376,6 +619,22 @@ impl Store {
         &self,
         block_hash: BlockHash,
         account_updates: &[AccountUpdate],
+    ) -> Result<Option<H256>, StoreError> {
+        let mut batch = WriteBatch::new();
+        let state_root =
+            self.apply_account_updates_to_batch(&mut batch, block_hash, account_updates)?;
+        self.write_batch(batch)?;
+        Ok(state_root)
+    }
+
+    /// Applies account updates over the state of the given block and returns the new state root.
+    /// Instead of being written to the DB, the new trie nodes and account codes are added to
+    /// `batch`, so they can be committed along with the rest of the block's data.
+    pub fn apply_account_updates_to_batch(
+        &self,
+        batch: &mut WriteBatch,
+        block_hash: BlockHash,
+        account_updates: &[AccountUpdate],
     ) -> Result<Option<H256>, StoreError> {
         let Some(mut state_trie) = self.state_trie(block_hash)? else {
             return Ok(None);
@@ -398,15 +657,15 @@ impl Store {
                     account_state.code_hash = info.code_hash;
                     // Store updated code in DB
                     if let Some(code) = &update.code {
-                        self.add_account_code(info.code_hash, code.clone())?;
+                        batch.add_account_code(info.code_hash, code.clone());
                     }
                 }
                 // Store the added storage in the account's storage trie and compute its new root
                 if !update.added_storage.is_empty() {
-                    let mut storage_trie = self.engine.open_storage_trie(
-                        H256::from_slice(&hashed_address),
-                        account_state.storage_root,
-                    );
+                    let hashed_address = H256::from_slice(&hashed_address);
+                    let mut storage_trie = self
+                        .engine
+                        .open_storage_trie(hashed_address, account_state.storage_root);
                     for (storage_key, storage_value) in &update.added_storage {
                         let hashed_key = hash_key(storage_key);
                         if storage_value.is_zero() {
@@ -415,12 +674,16 @@ impl Store {
                             storage_trie.insert(hashed_key, storage_value.encode_to_vec())?;
                         }
                     }
-                    account_state.storage_root = storage_trie.hash()?;
+                    let (storage_root, storage_nodes) = storage_trie.collect_changes();
+                    batch.add_storage_trie_nodes(hashed_address, storage_nodes);
+                    account_state.storage_root = storage_root;
                 }
                 state_trie.insert(hashed_address, account_state.encode_to_vec())?;
             }
         }
-        Ok(Some(state_trie.hash()?))
+        let (state_root, state_nodes) = state_trie.collect_changes();
+        batch.add_state_trie_nodes(state_nodes);
+        Ok(Some(state_root))
     }
 
     /// Adds all genesis accounts and returns the genesis block's state_root
@@ -474,39 +737,52 @@ impl Store {
         self.engine.get_receipt(block_number, index)
     }
 
+    /// Stores the block's header, body, number, total difficulty and transaction locations
+    /// within a single transaction
     pub fn add_block(&self, block: Block) -> Result<(), StoreError> {
-        // TODO Maybe add both in a single tx?
+        let mut batch = WriteBatch::new();
+        self.add_block_to_batch(&mut batch, block)?;
+        self.write_batch(batch)
+    }
+
+    /// Adds the writes performed by [`Store::add_block`] to `batch`.
+    /// The block's total difficulty is computed from the latest one stored in the DB, so a batch
+    /// should not contain more than one block.
+    pub fn add_block_to_batch(
+        &self,
+        batch: &mut WriteBatch,
+        block: Block,
+    ) -> Result<(), StoreError> {
         let header = block.header;
         let number = header.number;
         let latest_total_difficulty = self.get_latest_total_difficulty()?;
         let block_total_difficulty =
             latest_total_difficulty.unwrap_or(U256::zero()) + header.difficulty;
         let hash = header.compute_block_hash();
-        self.add_transaction_locations(&block.body.transactions, number, hash)?;
-        self.add_block_body(hash, block.body)?;
-        self.add_block_header(hash, header)?;
-        self.add_block_number(hash, number)?;
-        self.add_block_total_difficulty(hash, block_total_difficulty)?;
-        self.update_latest_total_difficulty(block_total_difficulty)
-    }
-
-    fn add_transaction_locations(
-        &self,
-        transactions: &[Transaction],
-        block_number: BlockNumber,
-        block_hash: BlockHash,
-    ) -> Result<(), StoreError> {
-        for (index, transaction) in transactions.iter().enumerate() {
-            self.add_transaction_location(
+        for (index, transaction) in block.body.transactions.iter().enumerate() {
+            batch.add_transaction_location(
                 transaction.compute_hash(),
-                block_number,
-                block_hash,
+                number,
+                hash,
                 index as Index,
-            )?;
+            );
         }
+        batch.add_block_body(hash, block.body);
+        batch.add_block_header(hash, header);
+        batch.add_block_number(hash, number);
+        batch.add_block_total_difficulty(hash, block_total_difficulty);
+        batch.update_latest_total_difficulty(block_total_difficulty);
         Ok(())
     }
 
+    /// Commits all the writes in `batch` within a single transaction
+    pub fn write_batch(&self, batch: WriteBatch) -> Result<(), StoreError> {
+        if batch.is_empty() {
+            return Ok(());
+        }
+        ethrex_metrics::store::time_write("batch", || self.engine.write_batch(batch))
+    }
+
     pub fn add_initial_state(&self, genesis: Genesis) -> Result<(), StoreError> {
         info!("Storing initial state from genesis");
 
@@ -581,14 +857,26 @@ impl Store {
         address: Address,
         storage_key: H256,
     ) -> Result<Option<U256>, StoreError> {
-        let Some(storage_trie) = self.storage_trie(block_hash, address)? else {
-            return Ok(None);
-        };
-        let hashed_key = hash_key(&storage_key);
-        storage_trie
-            .get(&hashed_key)?
-            .map(|rlp| U256::decode(&rlp).map_err(StoreError::RLPDecode))
-            .transpose()
+        if let Some((parent_hash, account_updates)) = self.pending_state(block_hash)? {
+            let update = account_updates.get(&address);
+            if update.is_some_and(|update| update.removed) {
+                return Ok(None);
+            }
+            return match update.and_then(|update| update.added_storage.get(&storage_key)) {
+                Some(value) => Ok((!value.is_zero()).then_some(*value)),
+                None => self.get_storage_at_hash(parent_hash, address, storage_key),
+            };
+        }
+        ethrex_metrics::store::time_read("storage", || {
+            let Some(storage_trie) = self.storage_trie(block_hash, address)? else {
+                return Ok(None);
+            };
+            let hashed_key = hash_key(&storage_key);
+            storage_trie
+                .get(&hashed_key)?
+                .map(|rlp| U256::decode(&rlp).map_err(StoreError::RLPDecode))
+                .transpose()
+        })
     }
 
     pub fn set_chain_config(&self, chain_config: &ChainConfig) -> Result<(), StoreError> {
@@ -677,9 +965,21 @@ impl Store {
         self.engine.unset_canonical_block(number)
     }
 
+    /// Marks the given blocks as canonical and sets `latest_block_number` as the latest block.
+    /// Either all of the updates are committed or none of them are.
+    pub fn update_canonical_chain(
+        &self,
+        canonical_blocks: &[(BlockNumber, BlockHash)],
+        latest_block_number: BlockNumber,
+    ) -> Result<(), StoreError> {
+        self.engine
+            .update_canonical_chain(canonical_blocks, latest_block_number)
+    }
+
     // Obtain the storage trie for the given block
     pub fn state_trie(&self, block_hash: BlockHash) -> Result<Option<Trie>, StoreError> {
-        let Some(header) = self.get_block_header_by_hash(block_hash)? else {
+        // The trie of the current pending block isn't stored, so it's not looked up
+        let Some(header) = self.engine.get_block_header_by_hash(block_hash)? else {
             return Ok(None);
         };
         Ok(Some(self.engine.open_state_trie(header.state_root)))
@@ -952,6 +1252,23 @@ mod tests {
         test_store_suite(EngineType::RedB);
     }
 
+    #[test]
+    fn test_unversioned_store_is_upgraded_when_opened() {
+        // A DB with blocks but no schema version, as written before the version was recorded
+        let store = Store::open("", EngineType::InMemory).unwrap();
+        store.update_latest_block_number(0).unwrap();
+
+        // Reading the version doesn't record it
+        assert_eq!(store.get_schema_version().unwrap(), 0);
+        assert_eq!(store.engine.get_schema_version().unwrap(), None);
+
+        store.prepare_schema_version().unwrap();
+        assert_eq!(
+            store.engine.get_schema_version().unwrap(),
+            Some(STORE_SCHEMA_VERSION)
+        );
+    }
+
     // Creates an empty store, runs the test and then removes the store (if needed)
     fn run_test(test_func: &dyn Fn(Store), engine_type: EngineType) {
         // Remove preexistent DBs in case of a failed previous test
@@ -976,6 +1293,9 @@ mod tests {
         run_test(&test_store_block_receipt, engine_type);
         run_test(&test_store_account_code, engine_type);
         run_test(&test_store_block_tags, engine_type);
+        run_test(&test_update_canonical_chain, engine_type);
+        run_test(&test_write_batch, engine_type);
+        run_test(&test_schema_version, engine_type);
         run_test(&test_chain_config_storage, engine_type);
         run_test(&test_genesis_block, engine_type);
         run_test(&test_filter_mempool_transactions, engine_type);
@@ -1025,6 +1345,105 @@ mod tests {
         assert_eq!(stored_body, block_body);
     }
 
+    fn test_schema_version(store: Store) {
+        // A new DB starts at the current version
+        assert_eq!(
+            store.engine.get_schema_version().unwrap(),
+            Some(STORE_SCHEMA_VERSION)
+        );
+        assert_eq!(store.get_schema_version().unwrap(), STORE_SCHEMA_VERSION);
+        store.check_schema_version().unwrap();
+
+        store
+            .engine
+            .set_schema_version(STORE_SCHEMA_VERSION + 1)
+            .unwrap();
+        assert!(matches!(
+            store.check_schema_version(),
+            Err(StoreError::UnsupportedSchemaVersion { .. })
+        ));
+        assert!(store.migrate_schema().is_err());
+
+        store.engine.set_schema_version(0).unwrap();
+        assert!(matches!(
+            store.check_schema_version(),
+            Err(StoreError::OutdatedSchemaVersion { .. })
+        ));
+        assert_eq!(store.migrate_schema().unwrap(), 0);
+        assert_eq!(store.get_schema_version().unwrap(), STORE_SCHEMA_VERSION);
+        store.check_schema_version().unwrap();
+    }
+
+    fn test_write_batch(store: Store) {
+        // Start from a parent block with an empty state
+        let (parent_header, body) = create_block_for_testing();
+        let parent_header = BlockHeader {
+            state_root: *EMPTY_TRIE_HASH,
+            ..parent_header
+        };
+        let parent_hash = parent_header.compute_block_hash();
+        store
+            .add_block_header(parent_hash, parent_header.clone())
+            .unwrap();
+
+        let address = Address::repeat_byte(1);
+        let storage_key = H256::repeat_byte(2);
+        let code = Bytes::from("kiwi");
+        let mut update = AccountUpdate::new(address);
+        update.info = Some(AccountInfo {
+            code_hash: code_hash(&code),
+            balance: U256::from(10),
+            nonce: 1,
+        });
+        update.code = Some(code.clone());
+        update.added_storage.insert(storage_key, U256::from(3));
+
+        let mut batch = WriteBatch::new();
+        let state_root = store
+            .apply_account_updates_to_batch(&mut batch, parent_hash, &[update])
+            .unwrap()
+            .unwrap();
+        let block = Block::new(
+            BlockHeader {
+                parent_hash,
+                number: parent_header.number + 1,
+                state_root,
+                ..parent_header
+            },
+            body,
+        );
+        let hash = block.hash();
+        let number = block.header.number;
+        store.add_block_to_batch(&mut batch, block).unwrap();
+        let receipt = Receipt::new(TxType::EIP1559, true, 21000, vec![]);
+        batch.add_receipts(hash, vec![receipt.clone()]);
+
+        // Nothing is written until the batch is committed
+        assert!(store.get_block_header_by_hash(hash).unwrap().is_none());
+        assert!(store.get_account_code(code_hash(&code)).unwrap().is_none());
+
+        store.write_batch(batch).unwrap();
+        store.set_canonical_block(number, hash).unwrap();
+
+        assert_eq!(store.get_block_number(hash).unwrap(), Some(number));
+        assert_eq!(store.get_receipt(number, 0).unwrap(), Some(receipt));
+        assert_eq!(
+            store.get_account_code(code_hash(&code)).unwrap(),
+            Some(code)
+        );
+        let info = store
+            .get_account_info_by_hash(hash, address)
+            .unwrap()
+            .unwrap();
+        assert_eq!(info.balance, U256::from(10));
+        assert_eq!(
+            store
+                .get_storage_at_hash(hash, address, storage_key)
+                .unwrap(),
+            Some(U256::from(3))
+        );
+    }
+
     fn create_block_for_testing() -> (BlockHeader, BlockBody) {
         let block_header = BlockHeader {
             parent_hash: H256::from_str(
@@ -1196,6 +1615,17 @@ mod tests {
         assert_eq!(pending_block_number, stored_pending_block_number);
     }
 
+    fn test_update_canonical_chain(store: Store) {
+        let canonical_blocks: Vec<_> = (1..=5).map(|number| (number, H256::random())).collect();
+
+        store.update_canonical_chain(&canonical_blocks, 5).unwrap();
+
+        for (number, hash) in canonical_blocks {
+            assert_eq!(store.get_canonical_block_hash(number).unwrap(), Some(hash));
+        }
+        assert_eq!(store.get_latest_block_number().unwrap(), Some(5));
+    }
+
     fn test_chain_config_storage(store: Store) {
         let chain_config = example_chain_config();
         store.set_chain_config(&chain_config).unwrap();
@@ -0,0 +1,121 @@
+use bytes::Bytes;
+use ethereum_types::{H256, U256};
+use ethrex_core::types::{BlockBody, BlockHash, BlockHeader, BlockNumber, Index, Receipt};
+
+/// A set of writes that are committed to the DB within a single transaction, so either all of
+/// them are applied or none of them are. See [`Store::write_batch`](crate::Store::write_batch).
+#[derive(Debug, Default)]
+pub struct WriteBatch {
+    pub(crate) ops: Vec<WriteOp>,
+}
+
+/// A single write operation, mirroring the `add_*` methods of the store engine
+#[derive(Debug)]
+pub(crate) enum WriteOp {
+    BlockHeader(BlockHash, BlockHeader),
+    BlockBody(BlockHash, BlockBody),
+    BlockNumber(BlockHash, BlockNumber),
+    // TODO (#307): Remove TotalDifficulty.
+    BlockTotalDifficulty(BlockHash, U256),
+    // TODO (#307): Remove TotalDifficulty.
+    LatestTotalDifficulty(U256),
+    TransactionLocation {
+        transaction_hash: H256,
+        block_number: BlockNumber,
+        block_hash: BlockHash,
+        index: Index,
+    },
+    Receipt(BlockHash, Index, Receipt),
+    AccountCode(H256, Bytes),
+    /// A state trie node, keyed by its hash
+    StateTrieNode(Vec<u8>, Vec<u8>),
+    /// A storage trie node of the account with the given hashed address, keyed by its hash
+    StorageTrieNode(H256, Vec<u8>, Vec<u8>),
+}
+
+impl WriteBatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    pub fn add_block_header(&mut self, block_hash: BlockHash, block_header: BlockHeader) {
+        self.ops
+            .push(WriteOp::BlockHeader(block_hash, block_header));
+    }
+
+    pub fn add_block_body(&mut self, block_hash: BlockHash, block_body: BlockBody) {
+        self.ops.push(WriteOp::BlockBody(block_hash, block_body));
+    }
+
+    pub fn add_block_number(&mut self, block_hash: BlockHash, block_number: BlockNumber) {
+        self.ops
+            .push(WriteOp::BlockNumber(block_hash, block_number));
+    }
+
+    pub fn add_block_total_difficulty(&mut self, block_hash: BlockHash, total_difficulty: U256) {
+        self.ops
+            .push(WriteOp::BlockTotalDifficulty(block_hash, total_difficulty));
+    }
+
+    pub fn update_latest_total_difficulty(&mut self, total_difficulty: U256) {
+        self.ops
+            .push(WriteOp::LatestTotalDifficulty(total_difficulty));
+    }
+
+    pub fn add_transaction_location(
+        &mut self,
+        transaction_hash: H256,
+        block_number: BlockNumber,
+        block_hash: BlockHash,
+        index: Index,
+    ) {
+        self.ops.push(WriteOp::TransactionLocation {
+            transaction_hash,
+            block_number,
+            block_hash,
+            index,
+        });
+    }
+
+    pub fn add_receipt(&mut self, block_hash: BlockHash, index: Index, receipt: Receipt) {
+        self.ops.push(WriteOp::Receipt(block_hash, index, receipt));
+    }
+
+    /// Adds the receipts of a block, in transaction order
+    pub fn add_receipts(&mut self, block_hash: BlockHash, receipts: Vec<Receipt>) {
+        for (index, receipt) in receipts.into_iter().enumerate() {
+            self.add_receipt(block_hash, index as Index, receipt);
+        }
+    }
+
+    pub fn add_account_code(&mut self, code_hash: H256, code: Bytes) {
+        self.ops.push(WriteOp::AccountCode(code_hash, code));
+    }
+
+    /// Adds the nodes collected from a state trie, see [`ethrex_trie::Trie::collect_changes`]
+    pub(crate) fn add_state_trie_nodes(&mut self, nodes: Vec<(Vec<u8>, Vec<u8>)>) {
+        self.ops.extend(
+            nodes
+                .into_iter()
+                .map(|(key, node)| WriteOp::StateTrieNode(key, node)),
+        );
+    }
+
+    /// Adds the nodes collected from an account's storage trie,
+    /// see [`ethrex_trie::Trie::collect_changes`]
+    pub(crate) fn add_storage_trie_nodes(
+        &mut self,
+        hashed_address: H256,
+        nodes: Vec<(Vec<u8>, Vec<u8>)>,
+    ) {
+        self.ops.extend(
+            nodes
+                .into_iter()
+                .map(|(key, node)| WriteOp::StorageTrieNode(hashed_address, key, node)),
+        );
+    }
+}
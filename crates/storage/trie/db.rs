@@ -9,6 +9,8 @@ pub mod redb;
 pub mod redb_multitable;
 mod utils;
 
+pub use utils::node_hash_to_fixed_size;
+
 use crate::error::TrieError;
 
 pub trait TrieDB {
@@ -49,30 +49,41 @@ impl TrieState {
     /// Commits cache changes to DB and clears it
     /// Only writes nodes that follow the root's canonical trie
     pub fn commit(&mut self, root: &NodeHash) -> Result<(), TrieError> {
-        self.commit_node(root)?;
-        self.cache.clear();
+        for (key, node) in self.take_changes(root) {
+            self.db.put(key, node)?;
+        }
         Ok(())
     }
 
-    // Writes a node and its children into the DB
-    fn commit_node(&mut self, node_hash: &NodeHash) -> Result<(), TrieError> {
+    /// Removes the nodes that follow the root's canonical trie from the cache and returns them
+    /// encoded and keyed by their hash, ready to be written into the DB. Clears the cache.
+    /// Children are returned before their parents.
+    pub fn take_changes(&mut self, root: &NodeHash) -> Vec<(Vec<u8>, Vec<u8>)> {
+        let mut changes = Vec::new();
+        self.take_node(root, &mut changes);
+        self.cache.clear();
+        changes
+    }
+
+    // Takes a node and its children out of the cache
+    fn take_node(&mut self, node_hash: &NodeHash, changes: &mut Vec<(Vec<u8>, Vec<u8>)>) {
         let Some(node) = self.cache.remove(node_hash) else {
             // If the node is not in the cache then it means it is already stored in the DB
-            return Ok(());
+            return;
         };
-        // Commit children (if any)
+        // Take children (if any)
         match &node {
             Node::Branch(n) => {
                 for child in n.choices.iter() {
                     if child.is_valid() {
-                        self.commit_node(child)?;
+                        self.take_node(child, changes);
                     }
                 }
             }
-            Node::Extension(n) => self.commit_node(&n.child)?,
+            Node::Extension(n) => self.take_node(&n.child, changes),
             Node::Leaf(_) => {}
         }
-        // Commit self
-        self.db.put(node_hash.into(), node.encode_to_vec())
+        // Take self
+        changes.push((node_hash.into(), node.encode_to_vec()))
     }
 }
@@ -138,6 +138,17 @@ impl Trie {
             .unwrap_or(*EMPTY_TRIE_HASH))
     }
 
+    /// Return the hash of the trie's root node along with the nodes that [`Trie::hash`] would
+    /// commit, without writing them to the DB.
+    /// Used to write the changes of several tries within a single DB transaction.
+    pub fn collect_changes(&mut self) -> (H256, Vec<(Vec<u8>, NodeRLP)>) {
+        let changes = match self.root {
+            Some(ref root) => self.state.take_changes(root),
+            None => Vec::new(),
+        };
+        (self.hash_no_commit(), changes)
+    }
+
     /// Return the hash of the trie's root node.
     /// Returns keccak(RLP_NULL) if the trie is empty
     pub fn hash_no_commit(&self) -> H256 {
@@ -668,6 +679,28 @@ mod test {
         );
     }
 
+    #[test]
+    fn collect_changes_matches_committed_nodes() {
+        use std::{collections::HashMap, sync::Mutex};
+
+        let mut trie = Trie::new_temp();
+        let mut collected = Trie::new_temp();
+        for i in 0..50u8 {
+            trie.insert(vec![i; 32], vec![i; 40]).unwrap();
+            collected.insert(vec![i; 32], vec![i; 40]).unwrap();
+        }
+
+        let (root, changes) = collected.collect_changes();
+        assert_eq!(root, trie.hash().unwrap());
+
+        // Nothing was written, so the collected nodes must be enough to rebuild the trie
+        let map = Arc::new(Mutex::new(changes.into_iter().collect::<HashMap<_, _>>()));
+        let rebuilt = Trie::open(Box::new(InMemoryTrieDB::new(map)), root);
+        for i in 0..50u8 {
+            assert_eq!(rebuilt.get(&vec![i; 32]).unwrap(), Some(vec![i; 40]));
+        }
+    }
+
     #[cfg(feature = "libmdbx")]
     #[test]
     fn get_old_state() {
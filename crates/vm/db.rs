@@ -1,4 +1,9 @@
-use ethrex_core::{types::BlockHash, Address as CoreAddress, H256 as CoreH256};
+use bytes::Bytes;
+use ethrex_core::{
+    types::{BlockHash, EMPTY_KECCACK_HASH},
+    Address as CoreAddress, H256 as CoreH256, U256 as CoreU256,
+};
+use ethrex_levm::{db::Database as LevmDatabase, errors::DatabaseError as LevmDatabaseError};
 use ethrex_storage::{error::StoreError, Store};
 use revm::primitives::{
     AccountInfo as RevmAccountInfo, Address as RevmAddress, Bytecode as RevmBytecode,
@@ -10,45 +15,55 @@ pub struct StoreWrapper {
     pub block_hash: BlockHash,
 }
 
-cfg_if::cfg_if! {
-    if #[cfg(feature = "levm")] {
-        use ethrex_core::{U256 as CoreU256};
-        use ethrex_levm::db::Database as LevmDatabase;
-
-        impl LevmDatabase for StoreWrapper {
-            fn get_account_info(&self, address: CoreAddress) -> ethrex_levm::account::AccountInfo {
-                let acc_info = self
-                    .store
-                    .get_account_info_by_hash(self.block_hash, address)
-                    .unwrap()
-                    .unwrap_or_default();
-
-                let acc_code = self
-                    .store
-                    .get_account_code(acc_info.code_hash)
-                    .unwrap()
-                    .unwrap_or_default();
+impl LevmDatabase for StoreWrapper {
+    fn get_account_info(
+        &self,
+        address: CoreAddress,
+    ) -> Result<ethrex_levm::account::AccountInfo, LevmDatabaseError> {
+        let acc_info = self
+            .store
+            .get_account_info_by_hash(self.block_hash, address)
+            .map_err(|err| LevmDatabaseError::Custom(err.to_string()))?
+            .unwrap_or_default();
 
-                ethrex_levm::account::AccountInfo {
-                    balance: acc_info.balance,
-                    nonce: acc_info.nonce,
-                    bytecode: acc_code,
-                }
-            }
+        // Accounts without code don't need their code to be stored
+        let acc_code = if acc_info.code_hash == *EMPTY_KECCACK_HASH {
+            Bytes::new()
+        } else {
+            self.store
+                .get_account_code(acc_info.code_hash)
+                .map_err(|err| LevmDatabaseError::Custom(err.to_string()))?
+                .ok_or_else(|| {
+                    LevmDatabaseError::Custom(format!("No code for hash {:#x}", acc_info.code_hash))
+                })?
+        };
 
-            fn get_storage_slot(&self, address: CoreAddress, key: CoreH256) -> CoreU256 {
-                self.store
-                    .get_storage_at_hash(self.block_hash, address, key)
-                    .unwrap()
-                    .unwrap_or_default()
-            }
+        Ok(ethrex_levm::account::AccountInfo {
+            balance: acc_info.balance,
+            nonce: acc_info.nonce,
+            bytecode: acc_code,
+            code_hash: acc_info.code_hash,
+        })
+    }
 
-            fn get_block_hash(&self, block_number: u64) -> Option<CoreH256> {
-                let a = self.store.get_block_header(block_number).unwrap();
+    fn get_storage_slot(
+        &self,
+        address: CoreAddress,
+        key: CoreH256,
+    ) -> Result<CoreU256, LevmDatabaseError> {
+        Ok(self
+            .store
+            .get_storage_at_hash(self.block_hash, address, key)
+            .map_err(|err| LevmDatabaseError::Custom(err.to_string()))?
+            .unwrap_or_default())
+    }
 
-                a.map(|a| CoreH256::from(a.compute_block_hash().0))
-            }
-        }
+    fn get_block_hash(&self, block_number: u64) -> Result<Option<CoreH256>, LevmDatabaseError> {
+        Ok(self
+            .store
+            .get_block_header(block_number)
+            .map_err(|err| LevmDatabaseError::Custom(err.to_string()))?
+            .map(|header| CoreH256::from(header.compute_block_hash().0)))
     }
 }
 
@@ -0,0 +1,453 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::Arc,
+};
+
+use ethrex_core::{
+    types::{Block, BlockHeader, Genesis, GenesisAccount, Transaction, TxKind},
+    Address, H256, U256,
+};
+use ethrex_levm::{
+    db::{cache, BlockState, CacheDB},
+    errors::{DatabaseError, TransactionReport},
+};
+use ethrex_rlp::encode::RLPEncode;
+use ethrex_storage::{AccountUpdate, Store};
+use serde_json::{json, Map, Value};
+use sha3::{Digest, Keccak256};
+
+use crate::{
+    account_updates_levm, beacon_root_contract_call, beacon_root_contract_call_levm,
+    db::StoreWrapper, errors::EvmError, evm_state, execute_tx, get_state_transitions, run_tx_levm,
+    spec_id, ExecutionResult, SpecId,
+};
+
+/// A transaction whose execution differs between revm and LEVM
+#[derive(Debug)]
+pub struct Divergence {
+    pub tx_index: usize,
+    pub tx_hash: H256,
+    /// Every difference found between both executions
+    pub mismatches: Vec<String>,
+    /// EF state test that reproduces the transaction over its pre-state, expecting revm's result
+    pub fixture: Value,
+}
+
+/// Executes the transactions of a block one by one with both revm and LEVM, over the post-state of
+/// its parent, and compares their receipts, gas used, logs and account updates.
+///
+/// Returns the first divergent transaction, as the following ones would be executed over
+/// different states by each engine.
+pub fn compare_block_execution(
+    block: &Block,
+    store: &Store,
+) -> Result<Option<Divergence>, EvmError> {
+    let header = &block.header;
+    let chain_config = store.get_chain_config()?;
+    let spec_id = spec_id(&chain_config, header.timestamp);
+    let mut revm_state = evm_state(store.clone(), header.parent_hash);
+    let mut block_state = BlockState::new(Arc::new(StoreWrapper {
+        store: store.clone(),
+        block_hash: header.parent_hash,
+    }));
+
+    cfg_if::cfg_if! {
+        if #[cfg(not(feature = "l2"))] {
+            if header.parent_beacon_block_root.is_some() && spec_id.is_enabled_in(SpecId::CANCUN) {
+                beacon_root_contract_call(&mut revm_state, header, spec_id)?;
+                beacon_root_contract_call_levm(&mut block_state, header)?;
+                // Only the changes made by each transaction are compared
+                get_state_transitions(&mut revm_state);
+            }
+        }
+    }
+
+    for (tx_index, tx) in block.body.transactions.iter().enumerate() {
+        let revm_result = execute_tx(tx, header, &mut revm_state, spec_id);
+        let revm_updates = get_state_transitions(&mut revm_state);
+        let levm_result = match run_tx_levm(
+            tx,
+            header,
+            &block_state,
+            chain_config.get_fork(header.timestamp),
+        ) {
+            // Failing to read the state is not a divergence
+            Err(error) if error.is_internal() => return Err(error.into()),
+            result => result,
+        };
+        let levm_updates = match &levm_result {
+            Ok(report) => account_updates_levm(block_state.transaction_changes(&report.new_state)?),
+            Err(_) => Vec::new(),
+        };
+
+        let mismatches = match (&revm_result, &levm_result) {
+            (Ok(revm_result), Ok(levm_report)) => {
+                compare_executions(revm_result, levm_report, &revm_updates, &levm_updates)
+            }
+            (Ok(_), Err(error)) => vec![format!("LEVM rejected the transaction: {error}")],
+            (Err(error), Ok(_)) => vec![format!("revm rejected the transaction: {error}")],
+            (Err(_), Err(_)) => Vec::new(),
+        };
+        if !mismatches.is_empty() {
+            let pre_state = pre_state(
+                tx,
+                header,
+                &block_state,
+                levm_result.as_ref().ok(),
+                &revm_updates,
+            )?;
+            let fixture = state_test_fixture(
+                block,
+                tx_index,
+                tx,
+                pre_state,
+                &revm_result,
+                &revm_updates,
+                &mismatches,
+                &format!("{:?}", chain_config.get_fork(header.timestamp)),
+            );
+            return Ok(Some(Divergence {
+                tx_index,
+                tx_hash: tx.compute_hash(),
+                mismatches,
+                fixture,
+            }));
+        }
+        if let Ok(report) = levm_result {
+            block_state.commit(report.new_state)?;
+        }
+    }
+    Ok(None)
+}
+
+fn compare_executions(
+    revm_result: &ExecutionResult,
+    levm_report: &TransactionReport,
+    revm_updates: &[AccountUpdate],
+    levm_updates: &[AccountUpdate],
+) -> Vec<String> {
+    let mut mismatches = Vec::new();
+    if revm_result.is_success() != levm_report.is_success() {
+        mismatches.push(format!(
+            "Result mismatch: revm {revm_result:?}, LEVM {:?}",
+            levm_report.result
+        ));
+    }
+    if revm_result.gas_used() != levm_report.gas_used {
+        mismatches.push(format!(
+            "Gas used mismatch: revm {}, LEVM {}",
+            revm_result.gas_used(),
+            levm_report.gas_used
+        ));
+    }
+    if revm_result.logs() != levm_report.logs {
+        mismatches.push(format!(
+            "Logs mismatch: revm {:?}, LEVM {:?}",
+            revm_result.logs(),
+            levm_report.logs
+        ));
+    }
+
+    let revm_updates = relevant_updates(revm_updates);
+    let levm_updates = relevant_updates(levm_updates);
+    let addresses: BTreeSet<&Address> = revm_updates.keys().chain(levm_updates.keys()).collect();
+    for address in addresses {
+        let revm_update = revm_updates.get(address);
+        let levm_update = levm_updates.get(address);
+        if revm_update != levm_update {
+            mismatches.push(format!(
+                "Account update mismatch for {address:#x}: revm {revm_update:?}, LEVM {levm_update:?}"
+            ));
+        }
+    }
+    mismatches
+}
+
+// Indexes the updates by address, leaving out the ones that don't change anything. revm reports
+// an empty code for created accounts, which is the same as not updating it.
+fn relevant_updates(updates: &[AccountUpdate]) -> BTreeMap<Address, AccountUpdate> {
+    updates
+        .iter()
+        .map(|update| AccountUpdate {
+            code: update.code.clone().filter(|code| !code.is_empty()),
+            ..update.clone()
+        })
+        .filter(|update| {
+            update.removed
+                || update.info.is_some()
+                || update.code.is_some()
+                || !update.added_storage.is_empty()
+        })
+        .map(|update| (update.address, update))
+        .collect()
+}
+
+// Reads the accounts involved in the transaction, as they were before it was executed
+fn pre_state(
+    tx: &Transaction,
+    header: &BlockHeader,
+    block_state: &BlockState,
+    levm_report: Option<&TransactionReport>,
+    revm_updates: &[AccountUpdate],
+) -> Result<BTreeMap<Address, GenesisAccount>, DatabaseError> {
+    let mut slots: BTreeMap<Address, BTreeSet<H256>> = BTreeMap::new();
+    for address in [tx.sender(), header.coinbase] {
+        slots.entry(address).or_default();
+    }
+    if let TxKind::Call(to) = tx.to() {
+        slots.entry(to).or_default();
+    }
+    for (address, account) in levm_report
+        .map(|report| &report.new_state)
+        .into_iter()
+        .flatten()
+    {
+        slots
+            .entry(*address)
+            .or_default()
+            .extend(account.storage.keys());
+    }
+    for update in revm_updates {
+        slots
+            .entry(update.address)
+            .or_default()
+            .extend(update.added_storage.keys());
+    }
+
+    let db = block_state.db();
+    let cache: CacheDB = block_state.transaction_cache();
+    let mut pre_state = BTreeMap::new();
+    for (address, keys) in slots {
+        let cached_account = cache::get_account(&cache, &address);
+        let info = match cached_account {
+            Some(account) => account.info.clone(),
+            None => db.get_account_info(address)?,
+        };
+        let mut storage = HashMap::new();
+        for key in keys {
+            let value = match cached_account.and_then(|account| account.storage.get(&key)) {
+                Some(slot) => slot.current_value,
+                None => db.get_storage_slot(address, key)?,
+            };
+            if !value.is_zero() {
+                storage.insert(key, value);
+            }
+        }
+        if info.is_empty() && storage.is_empty() {
+            continue;
+        }
+        pre_state.insert(
+            address,
+            GenesisAccount {
+                code: info.bytecode,
+                storage,
+                balance: info.balance,
+                nonce: info.nonce,
+            },
+        );
+    }
+    Ok(pre_state)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn state_test_fixture(
+    block: &Block,
+    tx_index: usize,
+    tx: &Transaction,
+    pre_state: BTreeMap<Address, GenesisAccount>,
+    revm_result: &Result<ExecutionResult, EvmError>,
+    revm_updates: &[AccountUpdate],
+    mismatches: &[String],
+    fork: &str,
+) -> Value {
+    let header = &block.header;
+    let test_name = format!("block_{}_tx_{tx_index}", header.number);
+
+    let mut env = Map::new();
+    env.insert(
+        "currentCoinbase".into(),
+        json!(format!("{:#x}", header.coinbase)),
+    );
+    env.insert(
+        "currentDifficulty".into(),
+        json!(format!("{:#x}", header.difficulty)),
+    );
+    env.insert(
+        "currentGasLimit".into(),
+        json!(format!("{:#x}", header.gas_limit)),
+    );
+    env.insert(
+        "currentNumber".into(),
+        json!(format!("{:#x}", header.number)),
+    );
+    env.insert(
+        "currentTimestamp".into(),
+        json!(format!("{:#x}", header.timestamp)),
+    );
+    env.insert(
+        "currentRandom".into(),
+        json!(format!("{:#x}", header.prev_randao)),
+    );
+    if let Some(base_fee) = header.base_fee_per_gas {
+        env.insert("currentBaseFee".into(), json!(format!("{base_fee:#x}")));
+    }
+    if let Some(excess_blob_gas) = header.excess_blob_gas {
+        env.insert(
+            "currentExcessBlobGas".into(),
+            json!(format!("{excess_blob_gas:#x}")),
+        );
+    }
+
+    let pre: Map<String, Value> = pre_state
+        .iter()
+        .map(|(address, account)| {
+            let storage: Map<String, Value> = account
+                .storage
+                .iter()
+                .map(|(key, value)| {
+                    (
+                        format!("{:#x}", U256::from_big_endian(key.as_bytes())),
+                        json!(format!("{value:#x}")),
+                    )
+                })
+                .collect();
+            (
+                format!("{address:#x}"),
+                json!({
+                    "balance": format!("{:#x}", account.balance),
+                    "code": format!("0x{}", hex::encode(&account.code)),
+                    "nonce": format!("{:#x}", account.nonce),
+                    "storage": storage,
+                }),
+            )
+        })
+        .collect();
+
+    let mut transaction = Map::new();
+    transaction.insert(
+        "data".into(),
+        json!([format!("0x{}", hex::encode(tx.data()))]),
+    );
+    transaction.insert("gasLimit".into(), json!([format!("{:#x}", tx.gas_limit())]));
+    transaction.insert("value".into(), json!([format!("{:#x}", tx.value())]));
+    transaction.insert("nonce".into(), json!(format!("{:#x}", tx.nonce())));
+    transaction.insert("sender".into(), json!(format!("{:#x}", tx.sender())));
+    // The sender's key is unknown, the transaction can only be replayed from its sender
+    transaction.insert("secretKey".into(), json!(format!("{:#x}", H256::zero())));
+    transaction.insert(
+        "to".into(),
+        json!(match tx.to() {
+            TxKind::Call(to) => format!("{to:#x}"),
+            TxKind::Create => String::new(),
+        }),
+    );
+    match tx.max_fee_per_gas() {
+        Some(max_fee_per_gas) => {
+            transaction.insert(
+                "maxFeePerGas".into(),
+                json!(format!("{max_fee_per_gas:#x}")),
+            );
+            transaction.insert(
+                "maxPriorityFeePerGas".into(),
+                json!(format!("{:#x}", tx.max_priority_fee().unwrap_or_default())),
+            );
+        }
+        None => {
+            transaction.insert("gasPrice".into(), json!(format!("{:#x}", tx.gas_price())));
+        }
+    }
+    if let Some(max_fee_per_blob_gas) = tx.max_fee_per_blob_gas() {
+        transaction.insert(
+            "maxFeePerBlobGas".into(),
+            json!(format!("{max_fee_per_blob_gas:#x}")),
+        );
+        let blob_versioned_hashes: Vec<String> = tx
+            .blob_versioned_hashes()
+            .iter()
+            .map(|hash| format!("{hash:#x}"))
+            .collect();
+        transaction.insert("blobVersionedHashes".into(), json!(blob_versioned_hashes));
+    }
+    let access_list: Vec<Value> = tx
+        .access_list()
+        .iter()
+        .map(|(address, keys)| {
+            let keys: Vec<String> = keys.iter().map(|key| format!("{key:#x}")).collect();
+            json!({ "address": format!("{address:#x}"), "storageKeys": keys })
+        })
+        .collect();
+    if !access_list.is_empty() {
+        transaction.insert("accessLists".into(), json!([access_list]));
+    }
+
+    // The expected post-state is the one reached by revm
+    let mut post_state: HashMap<Address, GenesisAccount> = pre_state.into_iter().collect();
+    for update in revm_updates {
+        if update.removed {
+            post_state.remove(&update.address);
+            continue;
+        }
+        let account = post_state
+            .entry(update.address)
+            .or_insert_with(|| GenesisAccount {
+                code: Default::default(),
+                storage: HashMap::new(),
+                balance: U256::zero(),
+                nonce: 0,
+            });
+        if let Some(info) = &update.info {
+            account.balance = info.balance;
+            account.nonce = info.nonce;
+        }
+        if let Some(code) = &update.code {
+            account.code = code.clone();
+        }
+        account.storage.extend(update.added_storage.clone());
+    }
+    let post_state_root = Genesis {
+        alloc: post_state,
+        ..Default::default()
+    }
+    .compute_state_root();
+    let logs = match revm_result {
+        Ok(result) => result.logs(),
+        Err(_) => Vec::new(),
+    };
+    let logs_hash = H256::from_slice(&Keccak256::digest(logs.encode_to_vec()));
+
+    let mut post_value = Map::new();
+    post_value.insert("hash".into(), json!(format!("{post_state_root:#x}")));
+    post_value.insert("logs".into(), json!(format!("{logs_hash:#x}")));
+    post_value.insert("indexes".into(), json!({ "data": 0, "gas": 0, "value": 0 }));
+    post_value.insert(
+        "txbytes".into(),
+        json!(format!("0x{}", hex::encode(tx.encode_canonical_to_vec()))),
+    );
+    if let Err(error) = revm_result {
+        post_value.insert("expectException".into(), json!(error.to_string()));
+    }
+
+    json!({
+        test_name: {
+            "_info": {
+                "comment": format!(
+                    "Transaction {tx_index} of block {} ({:#x}), where revm and LEVM diverge: {}",
+                    header.number,
+                    block.hash(),
+                    mismatches.join("; ")
+                ),
+                "filling-rpc-server": "ethrex",
+                "filling-tool-version": "ethrex --evm-differential",
+                "generatedTestHash": format!("{:#x}", tx.compute_hash()),
+                "lllcversion": "",
+                "solidity": "",
+                "source": "",
+                "sourceHash": format!("{:#x}", H256::zero()),
+            },
+            "env": env,
+            "pre": pre,
+            "transaction": transaction,
+            "post": { fork: [post_value] },
+        }
+    })
+}
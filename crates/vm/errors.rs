@@ -1,5 +1,6 @@
 use ethereum_types::{H160, H256};
 use ethrex_core::types::BlockHash;
+use ethrex_levm::errors::{DatabaseError as LevmDatabaseError, InternalError, VMError};
 use ethrex_storage::error::StoreError;
 use ethrex_trie::TrieError;
 use revm::primitives::{
@@ -17,6 +18,8 @@ pub enum EvmError {
     DB(#[from] StoreError),
     #[error("Execution DB error: {0}")]
     ExecutionDB(#[from] ExecutionDBError),
+    #[error("LEVM DB error: {0}")]
+    LevmDatabase(#[from] LevmDatabaseError),
     #[error("{0}")]
     Custom(String),
     #[error("{0}")]
@@ -104,3 +107,13 @@ impl From<RevmError<ExecutionDBError>> for EvmError {
         }
     }
 }
+
+impl From<VMError> for EvmError {
+    fn from(value: VMError) -> Self {
+        match value {
+            VMError::Internal(InternalError::Database(err)) => EvmError::LevmDatabase(err),
+            VMError::Internal(err) => EvmError::Custom(err.to_string()),
+            err => EvmError::Transaction(err.to_string()),
+        }
+    }
+}
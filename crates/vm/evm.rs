@@ -0,0 +1,286 @@
+use std::{fmt, str::FromStr, sync::Arc};
+
+use ethrex_core::{
+    types::{
+        AccountInfo, Block, BlockHash, BlockHeader, ChainConfig, GenericTransaction, Receipt,
+        Transaction, Withdrawal,
+    },
+    Address, H256, U256,
+};
+use ethrex_levm::db::BlockState;
+use ethrex_storage::{AccountUpdate, Store};
+
+use crate::{
+    beacon_root_contract_call, beacon_root_contract_call_levm, create_access_list,
+    create_access_list_levm, db::StoreWrapper, errors::EvmError, evm_state, execute_block,
+    execute_block_levm, execute_tx, execute_tx_from_generic, execute_tx_from_generic_levm,
+    execute_tx_levm, get_state_transitions, get_state_transitions_levm, overrides,
+    process_withdrawals, process_withdrawals_levm, simulate_tx_from_generic,
+    simulate_tx_from_generic_levm, spec_id, AccessList, EvmState, ExecutionResult, RevmAddress,
+    SimulationConfig, SpecId, StateOverride,
+};
+use revm::Database;
+
+/// Implementation of the EVM used to execute transactions
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum EvmEngine {
+    REVM,
+    #[default]
+    LEVM,
+}
+
+impl FromStr for EvmEngine {
+    type Err = EvmError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "revm" => Ok(EvmEngine::REVM),
+            "levm" => Ok(EvmEngine::LEVM),
+            _ => Err(EvmError::Custom(format!(
+                "Unknown evm engine {s}, expected revm or levm"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for EvmEngine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvmEngine::REVM => write!(f, "revm"),
+            EvmEngine::LEVM => write!(f, "levm"),
+        }
+    }
+}
+
+/// Executes transactions on top of the post-state of a stored block, with the engine chosen when
+/// it was created.
+///
+/// Changes made by executed transactions are kept in memory until they are collected with
+/// [`Evm::get_state_transitions`], while simulated transactions leave the state untouched.
+pub enum Evm {
+    REVM {
+        state: EvmState,
+    },
+    LEVM {
+        store: Store,
+        block_state: BlockState,
+    },
+}
+
+impl Evm {
+    /// Creates an EVM over the post-state of the block with the given hash
+    pub fn new(engine: EvmEngine, store: Store, block_hash: BlockHash) -> Self {
+        match engine {
+            EvmEngine::REVM => Evm::REVM {
+                state: evm_state(store, block_hash),
+            },
+            EvmEngine::LEVM => Evm::LEVM {
+                block_state: BlockState::new(Arc::new(StoreWrapper {
+                    store: store.clone(),
+                    block_hash,
+                })),
+                store,
+            },
+        }
+    }
+
+    pub fn engine(&self) -> EvmEngine {
+        match self {
+            Evm::REVM { .. } => EvmEngine::REVM,
+            Evm::LEVM { .. } => EvmEngine::LEVM,
+        }
+    }
+
+    /// Get a reference to inner `Store` database
+    pub fn store(&self) -> Option<&Store> {
+        match self {
+            Evm::REVM { state } => state.database(),
+            Evm::LEVM { store, .. } => Some(store),
+        }
+    }
+
+    /// Gets the stored chain config
+    pub fn chain_config(&self) -> Result<ChainConfig, EvmError> {
+        match self {
+            Evm::REVM { state } => state.chain_config(),
+            Evm::LEVM { store, .. } => Ok(store.get_chain_config()?),
+        }
+    }
+
+    /// Executes all transactions in a block, returning their receipts along with the account
+    /// updates made by the block
+    pub fn execute_block(
+        &mut self,
+        block: &Block,
+    ) -> Result<(Vec<Receipt>, Vec<AccountUpdate>), EvmError> {
+        match self {
+            Evm::REVM { state } => {
+                let receipts = execute_block(block, state)?;
+                Ok((receipts, get_state_transitions(state)))
+            }
+            Evm::LEVM { store, block_state } => {
+                let receipts = execute_block_levm(block, block_state, &store.get_chain_config()?)?;
+                Ok((receipts, get_state_transitions_levm(block_state)))
+            }
+        }
+    }
+
+    /// Executes a transaction on top of the previously executed ones, keeping its changes
+    pub fn execute_tx(
+        &mut self,
+        tx: &Transaction,
+        header: &BlockHeader,
+    ) -> Result<ExecutionResult, EvmError> {
+        match self {
+            Evm::REVM { state } => {
+                let spec_id = spec_id(&state.chain_config()?, header.timestamp);
+                execute_tx(tx, header, state, spec_id)
+            }
+            Evm::LEVM { store, block_state } => {
+                let fork = store.get_chain_config()?.get_fork(header.timestamp);
+                Ok(execute_tx_levm(tx, header, block_state, fork)?.into())
+            }
+        }
+    }
+
+    /// Calls the eip4788 beacon block root system contract if the block requires it
+    pub fn beacon_root_contract_call(&mut self, header: &BlockHeader) -> Result<(), EvmError> {
+        let spec_id = spec_id(&self.chain_config()?, header.timestamp);
+        if header.parent_beacon_block_root.is_none() || !spec_id.is_enabled_in(SpecId::CANCUN) {
+            return Ok(());
+        }
+        match self {
+            Evm::REVM { state } => {
+                beacon_root_contract_call(state, header, spec_id)?;
+            }
+            Evm::LEVM { block_state, .. } => {
+                beacon_root_contract_call_levm(block_state, header)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Processes a block's withdrawals, updating the account balances in the state
+    pub fn process_withdrawals(&mut self, withdrawals: &[Withdrawal]) -> Result<(), EvmError> {
+        match self {
+            Evm::REVM { state } => Ok(process_withdrawals(state, withdrawals)?),
+            Evm::LEVM { block_state, .. } => process_withdrawals_levm(block_state, withdrawals),
+        }
+    }
+
+    /// Returns the account updates made by the executed transactions
+    /// Doesn't update the DB
+    pub fn get_state_transitions(&mut self) -> Vec<AccountUpdate> {
+        match self {
+            Evm::REVM { state } => get_state_transitions(state),
+            Evm::LEVM { block_state, .. } => get_state_transitions_levm(block_state),
+        }
+    }
+
+    /// Overrides accounts on top of the current state without touching the store, so the
+    /// transactions simulated afterwards see them. Meant for simulations only, as the overridden
+    /// accounts aren't reported as state transitions: the account updates setting them are
+    /// returned instead.
+    pub fn apply_state_overrides(
+        &mut self,
+        overrides: &StateOverride,
+    ) -> Result<Vec<AccountUpdate>, EvmError> {
+        match self {
+            Evm::REVM { state } => overrides::apply_state_overrides(state, overrides)?,
+            Evm::LEVM { block_state, .. } => {
+                overrides::apply_state_overrides_levm(block_state, overrides)?
+            }
+        }
+        let mut account_updates = Vec::new();
+        for (address, account_override) in overrides {
+            let info = self.get_account_info(*address)?;
+            account_updates.extend(account_override.account_updates(*address, info));
+        }
+        Ok(account_updates)
+    }
+
+    /// Gets an account as seen by the next transaction executed
+    pub fn get_account_info(&mut self, address: Address) -> Result<AccountInfo, EvmError> {
+        match self {
+            Evm::REVM { state } => {
+                let address = RevmAddress::from_slice(address.as_bytes());
+                let info = match state {
+                    EvmState::Store(db) => db.basic(address)?,
+                    EvmState::Execution(db) => db.basic(address)?,
+                };
+                Ok(info
+                    .map(|info| AccountInfo {
+                        code_hash: H256::from(info.code_hash.0),
+                        balance: U256::from_little_endian(info.balance.as_le_slice()),
+                        nonce: info.nonce,
+                    })
+                    .unwrap_or_default())
+            }
+            Evm::LEVM { block_state, .. } => {
+                let info = block_state.account_info(address)?;
+                Ok(AccountInfo {
+                    code_hash: info.code_hash,
+                    balance: info.balance,
+                    nonce: info.nonce,
+                })
+            }
+        }
+    }
+
+    /// Executes a single GenericTransaction, doesn't commit the result or perform state transitions
+    pub fn simulate_tx_from_generic(
+        &mut self,
+        tx: &GenericTransaction,
+        header: &BlockHeader,
+    ) -> Result<ExecutionResult, EvmError> {
+        match self {
+            Evm::REVM { state } => {
+                let spec_id = spec_id(&state.chain_config()?, header.timestamp);
+                simulate_tx_from_generic(tx, header, state, spec_id)
+            }
+            Evm::LEVM { store, block_state } => {
+                simulate_tx_from_generic_levm(tx, header, block_state, &store.get_chain_config()?)
+            }
+        }
+    }
+
+    /// Executes a GenericTransaction on top of the previous ones, keeping its changes so the
+    /// following transactions see them. Used to run the calls of multi-block simulations.
+    pub fn execute_tx_from_generic(
+        &mut self,
+        tx: &GenericTransaction,
+        header: &BlockHeader,
+        config: SimulationConfig,
+    ) -> Result<ExecutionResult, EvmError> {
+        match self {
+            Evm::REVM { state } => {
+                let spec_id = spec_id(&state.chain_config()?, header.timestamp);
+                execute_tx_from_generic(tx, header, state, spec_id, config)
+            }
+            Evm::LEVM { store, block_state } => execute_tx_from_generic_levm(
+                tx,
+                header,
+                block_state,
+                &store.get_chain_config()?,
+                config,
+            ),
+        }
+    }
+
+    /// Runs the transaction and returns the access list and estimated gas use (when running the tx with said access list)
+    pub fn create_access_list(
+        &mut self,
+        tx: &GenericTransaction,
+        header: &BlockHeader,
+    ) -> Result<(ExecutionResult, AccessList), EvmError> {
+        match self {
+            Evm::REVM { state } => {
+                let spec_id = spec_id(&state.chain_config()?, header.timestamp);
+                create_access_list(tx, header, state, spec_id)
+            }
+            Evm::LEVM { store, block_state } => {
+                create_access_list_levm(tx, header, block_state, &store.get_chain_config()?)
+            }
+        }
+    }
+}
@@ -1,9 +1,14 @@
 use std::collections::HashMap;
 
+use bytes::Bytes;
 use ethereum_types::H160;
 use ethrex_core::{
-    types::{AccountState, Block, ChainConfig},
-    H256,
+    types::{AccountState, Block, ChainConfig, EMPTY_KECCACK_HASH},
+    H256, U256,
+};
+use ethrex_levm::{
+    account::AccountInfo as LevmAccountInfo, db::Database as LevmDatabase,
+    errors::DatabaseError as LevmDatabaseError,
 };
 use ethrex_rlp::encode::RLPEncode;
 use ethrex_storage::{hash_address, hash_key, Store};
@@ -234,3 +239,44 @@ impl DatabaseRef for ExecutionDB {
             .ok_or(ExecutionDBError::BlockHashNotFound(number))
     }
 }
+
+impl LevmDatabase for ExecutionDB {
+    fn get_account_info(&self, address: H160) -> Result<LevmAccountInfo, LevmDatabaseError> {
+        let address = RevmAddress::from_slice(address.as_bytes());
+        let Some(account_state) = self.accounts.get(&address) else {
+            return Ok(LevmAccountInfo::default());
+        };
+        let bytecode = if account_state.code_hash == *EMPTY_KECCACK_HASH {
+            Bytes::new()
+        } else {
+            self.code_by_hash_ref(RevmB256::from_slice(account_state.code_hash.as_bytes()))?
+                .original_bytes()
+                .0
+        };
+        Ok(LevmAccountInfo {
+            balance: account_state.balance,
+            nonce: account_state.nonce,
+            bytecode,
+            code_hash: account_state.code_hash,
+        })
+    }
+
+    fn get_storage_slot(&self, address: H160, key: H256) -> Result<U256, LevmDatabaseError> {
+        let value = self.storage_ref(
+            RevmAddress::from_slice(address.as_bytes()),
+            RevmU256::from_be_bytes(key.0),
+        )?;
+        Ok(U256::from_big_endian(&value.to_be_bytes_vec()))
+    }
+
+    fn get_block_hash(&self, block_number: u64) -> Result<Option<H256>, LevmDatabaseError> {
+        let hash = self.block_hash_ref(block_number)?;
+        Ok(Some(H256::from_slice(hash.as_slice())))
+    }
+}
+
+impl From<ExecutionDBError> for LevmDatabaseError {
+    fn from(value: ExecutionDBError) -> Self {
+        LevmDatabaseError::Custom(value.to_string())
+    }
+}
@@ -1,6 +1,7 @@
 use bytes::Bytes;
 use ethrex_core::Address;
 use ethrex_core::{types::Log, H256};
+use ethrex_levm::errors::{TransactionReport, TxResult, VMError};
 use revm::primitives::result::Output as RevmOutput;
 use revm::primitives::result::SuccessReason as RevmSuccessReason;
 use revm::primitives::ExecutionResult as RevmExecutionResult;
@@ -88,6 +89,35 @@ impl From<RevmExecutionResult> for ExecutionResult {
     }
 }
 
+impl From<TransactionReport> for ExecutionResult {
+    fn from(report: TransactionReport) -> Self {
+        match report.result {
+            TxResult::Success => ExecutionResult::Success {
+                reason: if report.output.is_empty() {
+                    SuccessReason::Stop
+                } else {
+                    SuccessReason::Return
+                },
+                gas_used: report.gas_used,
+                gas_refunded: report.gas_refunded,
+                logs: report.logs,
+                output: match report.created_address {
+                    Some(address) => Output::Create(report.output, Some(address)),
+                    None => Output::Call(report.output),
+                },
+            },
+            TxResult::Revert(VMError::RevertOpcode) => ExecutionResult::Revert {
+                gas_used: report.gas_used,
+                output: report.output,
+            },
+            TxResult::Revert(error) => ExecutionResult::Halt {
+                reason: error.to_string(),
+                gas_used: report.gas_used,
+            },
+        }
+    }
+}
+
 impl ExecutionResult {
     pub fn is_success(&self) -> bool {
         matches!(self, ExecutionResult::Success { .. })
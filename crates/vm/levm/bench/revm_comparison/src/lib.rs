@@ -1,5 +1,5 @@
 use bytes::Bytes;
-use ethrex_levm::{call_frame::CallFrame, errors::TxResult, utils::new_vm_with_bytecode};
+use ethrex_levm::{call_frame::CallFrame, errors::TxResult, utils::new_vm_with_bytecode, vm::VM};
 use revm::{
     db::BenchmarkDB,
     primitives::{address, Bytecode, TransactTo},
@@ -11,24 +11,41 @@ pub const FIBONACCI_BYTECODE: &str =
     "5f355f60015b8215601a578181019150909160019003916005565b9150505f5260205ff3";
 pub const FACTORIAL_BYTECODE: &str =
     "5f355f60015b8215601b57906001018091029160019003916005565b9150505f5260205ff3";
+/// Writes n storage slots, calling itself with no calldata after each write. The nested calls
+/// return right away, so most of the work is setting up and finishing call frames while the
+/// state of the transaction grows.
+pub const STORAGE_CALLS_BYTECODE: &str =
+    "5f355b8015601b578080555f5f5f5f5f305af150600190036002565b00";
+/// Mints n tokens to the caller and transfers them one at a time to addresses n down to 1, the
+/// way an ERC-20 `transfer` does: balances live in a mapping at slot 0, each transfer checks the
+/// sender's balance, updates both balances and emits a `Transfer` event. Returns the balance of
+/// address 1.
+pub const ERC20_TRANSFERS_BYTECODE: &str = "5f35335f528060405f20555b8015606757335f5260405f20805460018110607757600190039055805f5260405f208054600101905560015f5280337fddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef60205fa360019003600b565b60015f5260405f20545f5260205ff35b5f5ffd";
+/// Makes n swaps against a constant product pool, like a Uniswap V2 pair: the reserves live in
+/// slots 0 and 1, each swap alternates its direction, prices the output with the 0.3% fee,
+/// updates both reserves and emits a `Swap` event. Returns both reserves.
+pub const UNISWAP_SWAPS_BYTECODE: &str = "5f35683635c9adc5dea000005f55683635c9adc5dea000006001555b8015608757806001168054816001185466038d7ea4c680006103e502808202836103e802820190049050805f529003826001185566038d7ea4c680000190557fd78ad95fa46c994b6551d0da85fc275fe613ce37657fb8d5e3d130840159d82260205fa160019003601b565b5f545f5260015460205260405ff3";
+
+// The program is deployed as the called contract, so it can call itself
+fn new_levm(bytecode: &Bytes, calldata: &Bytes) -> (VM, CallFrame) {
+    let mut vm = new_vm_with_bytecode(bytecode.clone()).unwrap();
+    let mut call_frame = vm.call_frames.pop().unwrap();
+    call_frame.calldata = calldata.clone();
+    (vm, call_frame)
+}
 
 pub fn run_with_levm(program: &str, runs: usize, number_of_iterations: u32) {
     let bytecode = Bytes::from(hex::decode(program).unwrap());
-    let mut call_frame = CallFrame::new_from_bytecode(bytecode);
     let mut calldata = vec![0x00; 32];
     calldata[28..32].copy_from_slice(&number_of_iterations.to_be_bytes());
-    call_frame.calldata = Bytes::from(calldata);
+    let calldata = Bytes::from(calldata);
 
     for _ in 0..runs - 1 {
-        let mut vm = new_vm_with_bytecode(Bytes::new()).unwrap();
-        *vm.current_call_frame_mut().unwrap() = call_frame.clone();
-        let mut current_call_frame = vm.call_frames.pop().unwrap();
+        let (mut vm, mut current_call_frame) = new_levm(&bytecode, &calldata);
         let tx_report = black_box(vm.execute(&mut current_call_frame).unwrap());
         assert!(tx_report.result == TxResult::Success);
     }
-    let mut vm = new_vm_with_bytecode(Bytes::new()).unwrap();
-    *vm.current_call_frame_mut().unwrap() = call_frame.clone();
-    let mut current_call_frame = vm.call_frames.pop().unwrap();
+    let (mut vm, mut current_call_frame) = new_levm(&bytecode, &calldata);
     let tx_report = black_box(vm.execute(&mut current_call_frame).unwrap());
     assert!(tx_report.result == TxResult::Success);
 
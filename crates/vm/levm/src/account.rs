@@ -5,14 +5,42 @@ use keccak_hash::keccak;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AccountInfo {
     pub balance: U256,
     pub bytecode: Bytes,
+    /// Hash of `bytecode`, kept along with it so the code isn't hashed every time it runs. Use
+    /// [`AccountInfo::set_bytecode`] to change the code of an existing account.
+    pub code_hash: H256,
     pub nonce: u64,
 }
 
+impl Default for AccountInfo {
+    fn default() -> Self {
+        Self {
+            balance: U256::zero(),
+            bytecode: Bytes::new(),
+            code_hash: EMPTY_CODE_HASH,
+            nonce: 0,
+        }
+    }
+}
+
 impl AccountInfo {
+    pub fn new(balance: U256, bytecode: Bytes, nonce: u64) -> Self {
+        Self {
+            balance,
+            code_hash: code_hash(&bytecode),
+            bytecode,
+            nonce,
+        }
+    }
+
+    pub fn set_bytecode(&mut self, bytecode: Bytes) {
+        self.code_hash = code_hash(&bytecode);
+        self.bytecode = bytecode;
+    }
+
     pub fn is_empty(&self) -> bool {
         self.balance.is_zero() && self.nonce == 0 && self.bytecode.is_empty()
     }
@@ -22,10 +50,14 @@ impl AccountInfo {
     }
 
     pub fn bytecode_hash(&self) -> H256 {
-        keccak(self.bytecode.as_ref()).0.into()
+        self.code_hash
     }
 }
 
+pub fn code_hash(bytecode: &Bytes) -> H256 {
+    keccak(bytecode.as_ref()).0.into()
+}
+
 #[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Account {
     pub info: AccountInfo,
@@ -55,11 +87,7 @@ impl Account {
         storage: HashMap<H256, StorageSlot>,
     ) -> Self {
         Self {
-            info: AccountInfo {
-                balance,
-                bytecode,
-                nonce,
-            },
+            info: AccountInfo::new(balance, bytecode, nonce),
             storage,
         }
     }
@@ -82,7 +110,7 @@ impl Account {
     }
 
     pub fn with_bytecode(mut self, bytecode: Bytes) -> Self {
-        self.info.bytecode = bytecode;
+        self.info.set_bytecode(bytecode);
         self
     }
 
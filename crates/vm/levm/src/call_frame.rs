@@ -1,15 +1,16 @@
 use crate::{
+    code::{AnalyzedCode, DEFAULT_DECODE_OPCODES},
     constants::STACK_LIMIT,
+    eof::EofContainer,
     errors::{InternalError, VMError},
     memory::Memory,
     opcodes::Opcode,
 };
 use bytes::Bytes;
-use ethrex_core::{types::Log, Address, U256};
-use std::collections::HashMap;
+use ethrex_core::{Address, U256};
+use std::sync::Arc;
 
 /// [EIP-1153]: https://eips.ethereum.org/EIPS/eip-1153#reference-implementation
-pub type TransientStorage = HashMap<(Address, U256), U256>;
 
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct Stack {
@@ -63,8 +64,8 @@ pub struct CallFrame {
     pub to: Address,
     /// Address of the code to execute. Usually the same as `to`, but can be different
     pub code_address: Address,
-    /// Bytecode to execute
-    pub bytecode: Bytes,
+    /// Code to execute, analyzed once and shared by every frame running it
+    pub code: Arc<AnalyzedCode>,
     pub msg_value: U256,
     pub stack: Stack, // max 1024 in the future
     pub memory: Memory,
@@ -77,15 +78,23 @@ pub struct CallFrame {
     pub sub_return_data_offset: usize,
     pub sub_return_data_size: usize,
     pub is_static: bool,
-    pub transient_storage: TransientStorage,
-    pub logs: Vec<Log>,
     pub depth: usize,
+    /// Whether the code runs as an EOF container, in which case `pc` is a position in the
+    /// whole container
+    pub is_eof: bool,
+    /// Whether the code is the initcode of an EOF creation, the only code RETURNCONTRACT can
+    /// finish
+    pub is_eof_initcode: bool,
+    /// EOF code section being executed
+    pub code_section: usize,
+    /// Code sections and positions to go back to with RETF, pushed by CALLF (EIP-4750)
+    pub return_stack: Vec<(usize, usize)>,
 }
 
 impl CallFrame {
     pub fn new_from_bytecode(bytecode: Bytes) -> Self {
         Self {
-            bytecode,
+            code: Arc::new(AnalyzedCode::new(&bytecode, DEFAULT_DECODE_OPCODES)),
             gas_limit: U256::MAX,
             ..Default::default()
         }
@@ -96,7 +105,7 @@ impl CallFrame {
         msg_sender: Address,
         to: Address,
         code_address: Address,
-        bytecode: Bytes,
+        code: Arc<AnalyzedCode>,
         msg_value: U256,
         calldata: Bytes,
         is_static: bool,
@@ -109,7 +118,7 @@ impl CallFrame {
             msg_sender,
             to,
             code_address,
-            bytecode,
+            code,
             msg_value,
             calldata,
             is_static,
@@ -119,8 +128,13 @@ impl CallFrame {
         }
     }
 
+    /// Bytecode being executed
+    pub fn bytecode(&self) -> &Bytes {
+        self.code.bytecode()
+    }
+
     pub fn next_opcode(&mut self) -> Result<Option<Opcode>, VMError> {
-        let opcode = self.opcode_at(self.pc);
+        let opcode = self.code.opcode_at(self.pc);
         self.increment_pc()?;
         Ok(opcode)
     }
@@ -141,30 +155,69 @@ impl CallFrame {
         self.pc
     }
 
+    /// EOF container being executed
+    pub fn eof(&self) -> Result<&EofContainer, VMError> {
+        self.code
+            .eof()
+            .filter(|_| self.is_eof)
+            .ok_or(VMError::InvalidOpcode)
+    }
+
+    /// Immediate bytes of the opcode being executed, starting `offset` bytes after the opcode
+    pub fn immediate(&self, offset: usize, size: usize) -> Result<&[u8], VMError> {
+        self.code
+            .push_data(
+                self.pc
+                    .checked_add(offset)
+                    .ok_or(VMError::Internal(InternalError::PCOverflowed))?,
+                size,
+            )
+            .ok_or(VMError::Internal(InternalError::SlicingError))
+    }
+
+    pub fn immediate_u8(&self, offset: usize) -> Result<u8, VMError> {
+        match *self.immediate(offset, 1)? {
+            [byte] => Ok(byte),
+            _ => Err(VMError::Internal(InternalError::SlicingError)),
+        }
+    }
+
+    pub fn immediate_u16(&self, offset: usize) -> Result<u16, VMError> {
+        match *self.immediate(offset, 2)? {
+            [high, low] => Ok(u16::from_be_bytes([high, low])),
+            _ => Err(VMError::Internal(InternalError::SlicingError)),
+        }
+    }
+
+    /// Moves `pc` by `offset` bytes from `from`, for the relative jumps of EOF (EIP-4200)
+    pub fn relative_jump(&mut self, from: usize, offset: i16) -> Result<(), VMError> {
+        self.pc = from
+            .checked_add_signed(isize::from(offset))
+            .ok_or(VMError::InvalidJump)?;
+        Ok(())
+    }
+
+    /// Starts running the EOF code section at `index`
+    pub fn enter_code_section(&mut self, index: usize) -> Result<(), VMError> {
+        let section = self
+            .eof()?
+            .code_section(index)
+            .ok_or(VMError::InvalidJump)?;
+        self.code_section = index;
+        self.pc = section.start;
+        Ok(())
+    }
+
     /// Jump to the given address, returns false if the jump position wasn't a JUMPDEST
     pub fn jump(&mut self, jump_address: U256) -> Result<bool, VMError> {
         let jump_address_usize = jump_address
             .try_into()
             .map_err(|_err| VMError::VeryLargeNumber)?;
 
-        if !self.valid_jump(jump_address_usize) {
+        if !self.code.is_jumpdest(jump_address_usize) {
             return Ok(false);
         }
         self.pc = jump_address_usize;
         Ok(true)
     }
-
-    fn valid_jump(&self, jump_address: usize) -> bool {
-        self.opcode_at(jump_address)
-            .map(|opcode| opcode.eq(&Opcode::JUMPDEST))
-            .is_some_and(|is_jumpdest| is_jumpdest)
-    }
-
-    fn opcode_at(&self, offset: usize) -> Option<Opcode> {
-        self.bytecode
-            .get(offset)
-            .copied()
-            .map(Opcode::try_from)?
-            .ok()
-    }
 }
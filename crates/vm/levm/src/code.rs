@@ -0,0 +1,214 @@
+use crate::{
+    eof::{is_eof, EofContainer},
+    opcodes::Opcode,
+};
+use bytes::Bytes;
+use ethrex_core::H256;
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock, PoisonError, RwLock},
+};
+
+/// Zeros appended to the code, so a PUSH32 at its end reads its data without bounds checks and
+/// running past the end reads STOP
+pub const CODE_PADDING: usize = 33;
+
+/// Number of code hashes kept by the shared cache before it starts over
+pub const DEFAULT_CODE_CACHE_CAPACITY: usize = 4096;
+/// Whether the shared cache decodes opcodes up front. Off since it didn't make a measurable
+/// difference in the `revm_comparison` benchmarks, while doubling the memory of each analysis.
+pub const DEFAULT_DECODE_OPCODES: bool = false;
+
+static SHARED_CODE_CACHE: LazyLock<CodeCache> =
+    LazyLock::new(|| CodeCache::new(DEFAULT_CODE_CACHE_CAPACITY, DEFAULT_DECODE_OPCODES));
+
+/// Bytecode along with what the VM needs to run it, derived once per code instead of on every
+/// call.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AnalyzedCode {
+    /// Original code, sharing its memory with `padded_code`
+    bytecode: Bytes,
+    /// The code followed by [`CODE_PADDING`] zeros
+    padded_code: Bytes,
+    /// Bit `pc % 64` of word `pc / 64` is set if `pc` is a JUMPDEST that isn't PUSH data
+    jumpdests: Box<[u64]>,
+    /// Opcode at each position of the padded code, `None` for bytes that aren't opcodes. Only
+    /// present if the code was analyzed with `decode_opcodes`.
+    opcodes: Option<Box<[Option<Opcode>]>>,
+    /// The decoded container if the code is EOF. Whether it runs as EOF depends on the fork.
+    eof: Option<EofContainer>,
+}
+
+impl AnalyzedCode {
+    /// Analyzes the code, decoding every opcode up front if `decode_opcodes` is set. Decoding
+    /// trades memory for not converting each byte when it runs.
+    pub fn new(bytecode: &Bytes, decode_opcodes: bool) -> Self {
+        let code_len = bytecode.len();
+        let mut padded_code = Vec::with_capacity(code_len.saturating_add(CODE_PADDING));
+        padded_code.extend_from_slice(bytecode);
+        padded_code.resize(code_len.saturating_add(CODE_PADDING), 0);
+        let padded_code = Bytes::from(padded_code);
+
+        let mut jumpdests = vec![0u64; code_len.div_ceil(64)].into_boxed_slice();
+        let mut pc = 0;
+        while let Some(&byte) = bytecode.get(pc) {
+            if byte == u8::from(Opcode::JUMPDEST) {
+                if let Some(word) = jumpdests.get_mut(pc / 64) {
+                    *word |= 1u64.checked_shl(bit_index(pc)).unwrap_or_default();
+                }
+            }
+            pc = pc.saturating_add(push_data_size(byte)).saturating_add(1);
+        }
+
+        let opcodes = decode_opcodes.then(|| {
+            padded_code
+                .iter()
+                .map(|byte| Opcode::try_from(*byte).ok())
+                .collect()
+        });
+
+        // Deployed EOF code was validated on creation, so this only fails for code that was put
+        // in the state some other way, which then runs as legacy code
+        let eof = is_eof(bytecode)
+            .then(|| EofContainer::decode(bytecode.clone()).ok())
+            .flatten();
+
+        Self {
+            bytecode: padded_code.slice(..code_len),
+            padded_code,
+            jumpdests,
+            opcodes,
+            eof,
+        }
+    }
+
+    pub fn bytecode(&self) -> &Bytes {
+        &self.bytecode
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytecode.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytecode.is_empty()
+    }
+
+    pub fn eof(&self) -> Option<&EofContainer> {
+        self.eof.as_ref()
+    }
+
+    pub fn has_decoded_opcodes(&self) -> bool {
+        self.opcodes.is_some()
+    }
+
+    /// Opcode at the given position, `None` if the byte isn't an opcode or it's past the padding
+    pub fn opcode_at(&self, pc: usize) -> Option<Opcode> {
+        match &self.opcodes {
+            Some(opcodes) => opcodes.get(pc).copied().flatten(),
+            None => Opcode::try_from(*self.padded_code.get(pc)?).ok(),
+        }
+    }
+
+    /// Whether the position is a JUMPDEST opcode, as opposed to a 0x5b byte in PUSH data
+    pub fn is_jumpdest(&self, pc: usize) -> bool {
+        self.jumpdests
+            .get(pc / 64)
+            .is_some_and(|word| word.checked_shr(bit_index(pc)).unwrap_or_default() & 1 == 1)
+    }
+
+    /// `size` bytes of the code starting at `pc`, read from the padded code so the data of a
+    /// PUSH truncated by the end of the code is completed with zeros
+    pub fn push_data(&self, pc: usize, size: usize) -> Option<&[u8]> {
+        self.padded_code.get(pc..pc.checked_add(size)?)
+    }
+}
+
+// Index of the bit of `pc` in its jumpdests word, always lower than 64
+fn bit_index(pc: usize) -> u32 {
+    u32::try_from(pc % 64).unwrap_or_default()
+}
+
+// Bytes of data following the opcode, which are skipped when looking for jump destinations
+fn push_data_size(byte: u8) -> usize {
+    if (u8::from(Opcode::PUSH1)..=u8::from(Opcode::PUSH32)).contains(&byte) {
+        usize::from(byte.saturating_sub(u8::from(Opcode::PUSH0)))
+    } else {
+        0
+    }
+}
+
+/// Analyses of the code of accounts by code hash, shared by every call frame and transaction
+/// running the same code.
+///
+/// Once it holds `capacity` analyses it's emptied and starts over, which keeps its memory bounded
+/// without tracking how recently each code ran.
+#[derive(Debug)]
+pub struct CodeCache {
+    analyses: RwLock<HashMap<H256, Arc<AnalyzedCode>>>,
+    capacity: usize,
+    decode_opcodes: bool,
+}
+
+impl CodeCache {
+    pub fn new(capacity: usize, decode_opcodes: bool) -> Self {
+        Self {
+            analyses: RwLock::new(HashMap::new()),
+            capacity,
+            decode_opcodes,
+        }
+    }
+
+    /// Cache used by the VM, shared by every VM in the process
+    pub fn shared() -> &'static CodeCache {
+        &SHARED_CODE_CACHE
+    }
+
+    /// Analysis of the code with the given hash, analyzing it if it isn't cached yet
+    pub fn get_or_analyze(&self, code_hash: H256, bytecode: &Bytes) -> Arc<AnalyzedCode> {
+        if let Some(analysis) = self
+            .analyses
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&code_hash)
+        {
+            return analysis.clone();
+        }
+
+        let analysis = Arc::new(AnalyzedCode::new(bytecode, self.decode_opcodes));
+        let mut analyses = self
+            .analyses
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        if analyses.len() >= self.capacity {
+            analyses.clear();
+        }
+        analyses.insert(code_hash, analysis.clone());
+        analysis
+    }
+
+    pub fn contains(&self, code_hash: &H256) -> bool {
+        self.analyses
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains_key(code_hash)
+    }
+
+    pub fn len(&self) -> usize {
+        self.analyses
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        self.analyses
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+}
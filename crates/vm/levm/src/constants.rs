@@ -1,4 +1,4 @@
-use ethrex_core::{H256, U256};
+use ethrex_core::{H160, H256, U256};
 
 pub const WORD_SIZE_IN_BYTES: U256 = U256([32, 0, 0, 0]);
 pub const WORD_SIZE_IN_BYTES_USIZE: usize = 32;
@@ -8,9 +8,21 @@ pub const REVERT_FOR_CALL: i32 = 0;
 pub const HALT_FOR_CALL: i32 = 2;
 pub const SUCCESS_FOR_RETURN: i32 = 1;
 pub const REVERT_FOR_CREATE: i32 = 0;
+// Results pushed by EXTCALL, EXTDELEGATECALL and EXTSTATICCALL (EIP-7069)
+pub const SUCCESS_FOR_EXT_CALL: i32 = 0;
+pub const REVERT_FOR_EXT_CALL: i32 = 1;
+pub const FAILURE_FOR_EXT_CALL: i32 = 2;
 pub const WORD_SIZE: usize = 32;
 
 pub const STACK_LIMIT: usize = 1024;
+/// Maximum depth of nested calls and creations, the initial call frame has depth 0
+pub const MAX_CALL_DEPTH: usize = 1024;
+
+/// Precompiled contracts live at the addresses from 0x01 up to this one
+pub const LAST_PRECOMPILE_ADDRESS: u64 = 0x0a;
+/// The refund applied at the end of a transaction is at most its gas used divided by this
+/// (EIP-3529)
+pub const MAX_REFUND_QUOTIENT: u64 = 5;
 
 pub const GAS_REFUND_DENOMINATOR: u64 = 5;
 
@@ -21,6 +33,14 @@ pub const EMPTY_CODE_HASH: H256 = H256([
 
 pub const MEMORY_EXPANSION_QUOTIENT: usize = 512;
 
+// Ether transfers are reported as logs of this address when they are traced, following the
+// format of ERC-20 Transfer(address,address,uint256) events
+pub const TRANSFER_LOG_EMITTER: H160 = H160([0xee; 20]);
+pub const TRANSFER_EVENT_TOPIC: H256 = H256([
+    0xdd, 0xf2, 0x52, 0xad, 0x1b, 0xe2, 0xc8, 0x9b, 0x69, 0xc2, 0xb0, 0x68, 0xfc, 0x37, 0x8d, 0xaa,
+    0x95, 0x2b, 0xa7, 0xf1, 0x63, 0xc4, 0xa1, 0x16, 0x28, 0xf5, 0x5a, 0x4d, 0xf5, 0x23, 0xb3, 0xef,
+]);
+
 // Transaction costs in gas (in wei)
 pub const TX_BASE_COST: U256 = U256([21000, 0, 0, 0]);
 
@@ -46,7 +66,7 @@ pub const TARGET_BLOB_GAS_PER_BLOCK: U256 = U256([393216, 0, 0, 0]); // TARGET_B
 pub const MIN_BASE_FEE_PER_BLOB_GAS: U256 = U256([1, 0, 0, 0]);
 pub const BLOB_BASE_FEE_UPDATE_FRACTION: U256 = U256([3338477, 0, 0, 0]);
 pub const MAX_BLOB_COUNT: usize = 6;
-pub const VALID_BLOB_PREFIXES: [u8; 2] = [0x01, 0x02];
+pub const BLOB_GAS_PER_BLOB: U256 = U256([131072, 0, 0, 0]);
 
 // Block constants
 pub const LAST_AVAILABLE_BLOCK_LIMIT: U256 = U256([256, 0, 0, 0]);
@@ -0,0 +1,201 @@
+use super::{cache, CacheDB, Database};
+use crate::{
+    account::{Account, AccountInfo},
+    errors::{DatabaseError, VMError},
+};
+use ethrex_core::{Address, H256, U256};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    sync::Arc,
+};
+
+/// State changes made by the transactions of a block, on top of the state of its parent block.
+///
+/// Each transaction is executed with the cache returned by [`BlockState::transaction_cache`], so
+/// it sees the changes made by the previous transactions of the block, and its resulting state is
+/// merged back with [`BlockState::commit`]. Once the whole block is executed,
+/// [`BlockState::changes`] returns the accounts modified by the block.
+#[derive(Clone)]
+pub struct BlockState {
+    db: Arc<dyn Database>,
+    /// Info of the touched accounts before the block was executed
+    initial_accounts: HashMap<Address, AccountInfo>,
+    /// Current state of the touched accounts. The `original_value` of each storage slot is its
+    /// value before the block was executed.
+    accounts: CacheDB,
+}
+
+/// An account modified by a block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountChange {
+    pub address: Address,
+    /// Account info after the block was executed
+    pub info: AccountInfo,
+    /// Whether the balance, nonce or code changed
+    pub info_changed: bool,
+    pub code_changed: bool,
+    /// Storage slots whose value changed, along with their new value
+    pub storage: HashMap<H256, U256>,
+}
+
+impl BlockState {
+    /// Creates an empty block state on top of the given database
+    pub fn new(db: Arc<dyn Database>) -> Self {
+        Self {
+            db,
+            initial_accounts: HashMap::new(),
+            accounts: CacheDB::new(),
+        }
+    }
+
+    /// Database holding the state of the parent block
+    pub fn db(&self) -> Arc<dyn Database> {
+        self.db.clone()
+    }
+
+    /// Returns the cache a transaction should be executed with.
+    ///
+    /// It contains the accounts touched by the previous transactions of the block, with the
+    /// `original_value` of their storage slots set to the value at the start of the transaction.
+    pub fn transaction_cache(&self) -> CacheDB {
+        let mut cache = self.accounts.clone();
+        for account in cache.values_mut() {
+            for slot in account.storage.values_mut() {
+                slot.original_value = slot.current_value;
+            }
+        }
+        cache
+    }
+
+    /// Current info of an account, including the changes made by the block so far
+    pub fn account_info(&self, address: Address) -> Result<AccountInfo, DatabaseError> {
+        match cache::get_account(&self.accounts, &address) {
+            Some(account) => Ok(account.info.clone()),
+            None => self.db.get_account_info(address),
+        }
+    }
+
+    /// Merges the state left by an executed transaction into the block state
+    pub fn commit(&mut self, new_state: CacheDB) -> Result<(), DatabaseError> {
+        for (address, account) in new_state {
+            self.track_account(address)?;
+            match cache::get_account_mut(&mut self.accounts, &address) {
+                Some(block_account) => {
+                    block_account.info = account.info;
+                    for (key, slot) in account.storage {
+                        block_account
+                            .storage
+                            .entry(key)
+                            .and_modify(|block_slot| block_slot.current_value = slot.current_value)
+                            .or_insert(slot);
+                    }
+                }
+                None => {
+                    cache::insert_account(&mut self.accounts, address, account);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Increases an account's balance outside of any transaction, as done for withdrawals
+    pub fn increase_account_balance(
+        &mut self,
+        address: Address,
+        increase: U256,
+    ) -> Result<(), VMError> {
+        self.track_account(address)?;
+        let account = match self.accounts.entry(address) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(Account::from(self.db.get_account_info(address)?)),
+        };
+        account.info.balance = account
+            .info
+            .balance
+            .checked_add(increase)
+            .ok_or(VMError::BalanceOverflow)?;
+        Ok(())
+    }
+
+    /// Returns the accounts whose info or storage was changed by the block
+    pub fn changes(&self) -> Vec<AccountChange> {
+        self.accounts
+            .iter()
+            .filter_map(|(address, account)| {
+                let initial_info = self.initial_accounts.get(address)?;
+                account_change(*address, account, initial_info)
+            })
+            .collect()
+    }
+
+    /// Returns the accounts changed by a single transaction, given the state it left after being
+    /// executed with [`BlockState::transaction_cache`]. It must be called before that state is
+    /// committed.
+    pub fn transaction_changes(
+        &self,
+        new_state: &CacheDB,
+    ) -> Result<Vec<AccountChange>, DatabaseError> {
+        let mut changes = Vec::new();
+        for (address, account) in new_state {
+            let previous_info = match cache::get_account(&self.accounts, address) {
+                Some(previous_account) => previous_account.info.clone(),
+                None => self.db.get_account_info(*address)?,
+            };
+            changes.extend(account_change(*address, account, &previous_info));
+        }
+        Ok(changes)
+    }
+
+    // Records the info an account had before the block, the first time it is touched
+    fn track_account(&mut self, address: Address) -> Result<(), DatabaseError> {
+        if let Entry::Vacant(entry) = self.initial_accounts.entry(address) {
+            entry.insert(self.db.get_account_info(address)?);
+        }
+        Ok(())
+    }
+}
+
+// Compares an account against its previous info. As the `original_value` of each slot is its
+// value before the changes, only the storage needs the account itself.
+fn account_change(
+    address: Address,
+    account: &Account,
+    previous_info: &AccountInfo,
+) -> Option<AccountChange> {
+    let storage: HashMap<H256, U256> = account
+        .storage
+        .iter()
+        .filter(|(_, slot)| slot.current_value != slot.original_value)
+        .map(|(key, slot)| (*key, slot.current_value))
+        .collect();
+    let info_changed = account.info != *previous_info;
+    if !info_changed && storage.is_empty() {
+        return None;
+    }
+    Some(AccountChange {
+        address,
+        info: account.info.clone(),
+        info_changed,
+        code_changed: account.info.code_hash != previous_info.code_hash,
+        storage,
+    })
+}
+
+/// Reads see the changes made by the block so far, so a block state can be the database of
+/// another one
+impl Database for BlockState {
+    fn get_account_info(&self, address: Address) -> Result<AccountInfo, DatabaseError> {
+        self.account_info(address)
+    }
+
+    fn get_storage_slot(&self, address: Address, key: H256) -> Result<U256, DatabaseError> {
+        match cache::get_account(&self.accounts, &address).and_then(|a| a.storage.get(&key)) {
+            Some(slot) => Ok(slot.current_value),
+            None => self.db.get_storage_slot(address, key),
+        }
+    }
+
+    fn get_block_hash(&self, block_number: u64) -> Result<Option<H256>, DatabaseError> {
+        self.db.get_block_hash(block_number)
+    }
+}
@@ -1,14 +1,23 @@
-use crate::account::{Account, AccountInfo, StorageSlot};
+use crate::{
+    account::{Account, AccountInfo, StorageSlot},
+    errors::DatabaseError,
+};
 use ethrex_core::{Address, H256, U256};
 use std::collections::HashMap;
 
+pub mod block_state;
 pub mod cache;
+pub use block_state::{AccountChange, BlockState};
 pub use cache::CacheDB;
 
+/// State the VM executes on top of.
+///
+/// Accounts and storage slots that don't exist are returned with their default values, while
+/// failing to read them (e.g. because a trie node is missing) is an error.
 pub trait Database {
-    fn get_account_info(&self, address: Address) -> AccountInfo;
-    fn get_storage_slot(&self, address: Address, key: H256) -> U256;
-    fn get_block_hash(&self, block_number: u64) -> Option<H256>;
+    fn get_account_info(&self, address: Address) -> Result<AccountInfo, DatabaseError>;
+    fn get_storage_slot(&self, address: Address, key: H256) -> Result<U256, DatabaseError>;
+    fn get_block_hash(&self, block_number: u64) -> Result<Option<H256>, DatabaseError>;
 }
 
 #[derive(Debug, Default)]
@@ -50,26 +59,28 @@ impl Db {
 }
 
 impl Database for Db {
-    fn get_account_info(&self, address: Address) -> AccountInfo {
-        self.accounts
+    fn get_account_info(&self, address: Address) -> Result<AccountInfo, DatabaseError> {
+        Ok(self
+            .accounts
             .get(&address)
             .unwrap_or(&Account::default())
             .info
-            .clone()
+            .clone())
     }
 
-    fn get_storage_slot(&self, address: Address, key: H256) -> U256 {
+    fn get_storage_slot(&self, address: Address, key: H256) -> Result<U256, DatabaseError> {
         // both `original_value` and `current_value` should work here because they have the same values on Db
-        self.accounts
+        Ok(self
+            .accounts
             .get(&address)
             .unwrap_or(&Account::default())
             .storage
             .get(&key)
             .unwrap_or(&StorageSlot::default())
-            .original_value
+            .original_value)
     }
 
-    fn get_block_hash(&self, block_number: u64) -> Option<H256> {
-        self.block_hashes.get(&block_number).cloned()
+    fn get_block_hash(&self, block_number: u64) -> Result<Option<H256>, DatabaseError> {
+        Ok(self.block_hashes.get(&block_number).cloned())
     }
 }
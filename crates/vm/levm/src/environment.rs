@@ -1,5 +1,5 @@
 use crate::constants::TX_BASE_COST;
-use ethrex_core::{Address, H256, U256};
+use ethrex_core::{types::Fork, Address, H256, U256};
 
 #[derive(Debug, Default, Clone)]
 pub struct Environment {
@@ -7,7 +7,6 @@ pub struct Environment {
     /// this execution.
     pub origin: Address,
     pub consumed_gas: U256,
-    pub refunded_gas: U256,
     pub gas_limit: U256,
     pub block_number: U256,
     pub coinbase: Address,
@@ -22,7 +21,12 @@ pub struct Environment {
     pub tx_max_priority_fee_per_gas: Option<U256>,
     pub tx_max_fee_per_gas: Option<U256>,
     pub tx_max_fee_per_blob_gas: Option<U256>,
+    pub tx_access_list: Vec<(Address, Vec<H256>)>,
     pub block_gas_limit: U256,
+    /// Fork whose rules apply to the transaction. EOF code only runs from Osaka on.
+    pub fork: Fork,
+    /// Whether ether transfers are recorded as logs, as done by `eth_simulateV1`
+    pub trace_transfers: bool,
 }
 
 impl Environment {
@@ -30,7 +34,6 @@ impl Environment {
         Self {
             origin,
             consumed_gas: TX_BASE_COST,
-            refunded_gas: U256::default(),
             gas_limit: U256::MAX,
             block_number: Default::default(),
             coinbase: Default::default(),
@@ -45,7 +48,10 @@ impl Environment {
             tx_max_priority_fee_per_gas: Default::default(),
             tx_max_fee_per_gas: Default::default(),
             tx_max_fee_per_blob_gas: Default::default(),
+            tx_access_list: Default::default(),
             block_gas_limit: Default::default(),
+            fork: Default::default(),
+            trace_transfers: false,
         }
     }
 }
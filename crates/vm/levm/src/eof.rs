@@ -0,0 +1,905 @@
+//! EVM Object Format containers (EIP-7692): decoding of the container layout (EIP-3540) and
+//! validation of its code before it's deployed (EIP-3670, EIP-4200, EIP-4750, EIP-5450,
+//! EIP-6206, EIP-7480, EIP-663, EIP-7620 and EIP-7069).
+
+use crate::opcodes::Opcode;
+use bytes::Bytes;
+use ethrex_core::H256;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// Bytes every EOF container starts with. Legacy code can't start with 0xEF since EIP-3541.
+pub const EOF_MAGIC: [u8; 2] = [0xEF, 0x00];
+pub const EOF_VERSION: u8 = 0x01;
+/// keccak256(0xEF00), the code hash legacy code sees for accounts with EOF code
+pub const EOF_MAGIC_HASH: H256 = H256([
+    0x9d, 0xbf, 0x36, 0x48, 0xdb, 0x82, 0x10, 0x55, 0x2e, 0x9c, 0x4f, 0x75, 0xc6, 0xa1, 0xc3, 0x05,
+    0x7c, 0x0c, 0xa4, 0x32, 0x04, 0x3b, 0xd6, 0x48, 0xbe, 0x15, 0xfe, 0x7b, 0xe0, 0x56, 0x46, 0xf5,
+]);
+
+const KIND_TERMINATOR: u8 = 0x00;
+const KIND_TYPES: u8 = 0x01;
+const KIND_CODE: u8 = 0x02;
+const KIND_CONTAINER: u8 = 0x03;
+const KIND_DATA: u8 = 0x04;
+
+pub const MAX_CODE_SECTIONS: usize = 1024;
+pub const MAX_CONTAINER_SECTIONS: usize = 256;
+/// Maximum number of CALLF frames a call frame can have at once
+pub const MAX_RETURN_STACK_HEIGHT: usize = 1024;
+pub const MAX_STACK_HEIGHT: u16 = 0x03FF;
+pub const MAX_INPUTS: u8 = 0x7F;
+/// Outputs of a code section that never returns to its caller
+pub const NON_RETURNING_FUNCTION: u8 = 0x80;
+/// Size of the entry of each code section in the types section
+const TYPE_SIZE: usize = 4;
+/// Stack limit as a signed number, for the stack height bounds of the validation
+const MAX_STACK_ITEMS: i32 = 1024;
+
+/// Reasons for an EOF container to be rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error, Serialize, Deserialize)]
+pub enum EofError {
+    // Header
+    #[error("Container is shorter than its header")]
+    MissingInput,
+    #[error("Invalid EOF magic")]
+    InvalidMagic,
+    #[error("Invalid EOF version")]
+    InvalidVersion,
+    #[error("Invalid types section kind")]
+    InvalidTypesKind,
+    #[error("Types section size isn't a multiple of 4")]
+    InvalidTypesSize,
+    #[error("Invalid code section kind")]
+    InvalidCodeKind,
+    #[error("Container has no code sections")]
+    NoCodeSections,
+    #[error("Container has too many code sections")]
+    TooManyCodeSections,
+    #[error("Number of code sections doesn't match the types section")]
+    TypesCodeMismatch,
+    #[error("Container has no subcontainers")]
+    NoContainerSections,
+    #[error("Container has too many subcontainers")]
+    TooManyContainerSections,
+    #[error("Section of size zero")]
+    ZeroSizeSection,
+    #[error("Invalid data section kind")]
+    InvalidDataKind,
+    #[error("Missing header terminator")]
+    MissingTerminator,
+    // Body
+    #[error("Container is shorter than its sections")]
+    MissingBody,
+    #[error("Container is longer than its sections")]
+    DanglingData,
+    #[error("Data section is shorter than declared")]
+    DataNotFilled,
+    #[error("Invalid code section type")]
+    InvalidTypeSection,
+    #[error("First code section must take no inputs and not return")]
+    InvalidFirstTypeSection,
+    // Code
+    #[error("Unknown opcode")]
+    UnknownOpcode,
+    #[error("Opcode isn't allowed in EOF code")]
+    OpcodeDisabled,
+    #[error("Instruction is unreachable")]
+    UnreachableInstruction,
+    #[error("Code ends in the middle of an instruction")]
+    MissingImmediateBytes,
+    #[error("Jump into the immediate of an instruction")]
+    JumpIntoImmediate,
+    #[error("Jump out of the code section")]
+    JumpOutOfBounds,
+    #[error("Code section doesn't end with a terminating instruction")]
+    MissingTerminatingInstruction,
+    #[error("Code section index out of bounds")]
+    InvalidCodeSectionIndex,
+    #[error("Subcontainer index out of bounds")]
+    InvalidContainerIndex,
+    #[error("CALLF to a non-returning code section")]
+    CallfToNonReturning,
+    #[error("JUMPF to a code section with more outputs")]
+    JumpfInsufficientOutputs,
+    #[error("Stack is higher than the outputs of the code section")]
+    StackHigherThanOutputs,
+    #[error("DATALOADN out of the data section")]
+    DataLoadOutOfBounds,
+    #[error("Stack underflow")]
+    StackUnderflow,
+    #[error("Stack overflow")]
+    StackOverflow,
+    #[error("Stack heights don't match at a backward jump")]
+    StackHeightMismatch,
+    #[error("Max stack height doesn't match the types section")]
+    MaxStackHeightMismatch,
+    #[error("Code section returning flag doesn't match its type")]
+    ReturningMismatch,
+    #[error("Code section is unreachable")]
+    UnreachableCodeSection,
+    #[error("Subcontainer is never used")]
+    UnreachableContainer,
+    #[error("Container used both as initcode and as runtime code")]
+    IncompatibleContainerKind,
+}
+
+/// How a container is used, which decides how its code may finish
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContainerKind {
+    /// Run by a creation, finishing with RETURNCONTRACT
+    Initcode,
+    /// Deployed as account code, finishing with RETURN or STOP
+    Runtime,
+}
+
+/// Entry of a code section in the types section
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TypeSection {
+    pub inputs: u8,
+    pub outputs: u8,
+    pub max_stack_height: u16,
+}
+
+impl TypeSection {
+    pub fn is_non_returning(&self) -> bool {
+        self.outputs == NON_RETURNING_FUNCTION
+    }
+}
+
+/// A decoded EOF container. Sections are kept as ranges of the raw container, so the program
+/// counter of EOF code is a position in the whole container like it is for legacy code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EofContainer {
+    raw: Bytes,
+    types: Vec<TypeSection>,
+    code_sections: Vec<Range<usize>>,
+    container_sections: Vec<Range<usize>>,
+    /// Position of the data section, which runs until the end of the container
+    data_offset: usize,
+    /// Data size declared in the header, which can be bigger than the data of subcontainers
+    data_size: u16,
+    /// Position of the data size in the header, rewritten by RETURNCONTRACT
+    data_size_index: usize,
+}
+
+/// Whether the code is an EOF container, as opposed to legacy code
+pub fn is_eof(code: &[u8]) -> bool {
+    code.starts_with(&EOF_MAGIC)
+}
+
+// Header fields, with the sizes of the sections as declared
+struct Header {
+    size: usize,
+    types_size: usize,
+    code_sizes: Vec<usize>,
+    container_sizes: Vec<usize>,
+    data_size: u16,
+    data_size_index: usize,
+}
+
+impl Header {
+    // Size of every section but the data
+    fn partial_body_size(&self) -> usize {
+        self.code_sizes
+            .iter()
+            .chain(&self.container_sizes)
+            .fold(self.types_size, |total, size| total.saturating_add(*size))
+    }
+
+    fn container_size(&self) -> usize {
+        self.size
+            .saturating_add(self.partial_body_size())
+            .saturating_add(usize::from(self.data_size))
+    }
+}
+
+// Reads the header fields in order
+struct Reader<'a> {
+    input: &'a [u8],
+    position: usize,
+}
+
+impl Reader<'_> {
+    fn u8(&mut self) -> Result<u8, EofError> {
+        let byte = *self
+            .input
+            .get(self.position)
+            .ok_or(EofError::MissingInput)?;
+        self.position = self.position.saturating_add(1);
+        Ok(byte)
+    }
+
+    fn u16(&mut self) -> Result<u16, EofError> {
+        Ok(u16::from_be_bytes([self.u8()?, self.u8()?]))
+    }
+
+    // Number of sections followed by their sizes, none of them zero
+    fn section_sizes(
+        &mut self,
+        max_sections: usize,
+        too_many: EofError,
+    ) -> Result<Vec<usize>, EofError> {
+        let count = usize::from(self.u16()?);
+        if count > max_sections {
+            return Err(too_many);
+        }
+        (0..count)
+            .map(|_| match self.u16()? {
+                0 => Err(EofError::ZeroSizeSection),
+                size => Ok(usize::from(size)),
+            })
+            .collect()
+    }
+}
+
+fn decode_header(raw: &[u8]) -> Result<Header, EofError> {
+    let mut reader = Reader {
+        input: raw,
+        position: 0,
+    };
+    if [reader.u8()?, reader.u8()?] != EOF_MAGIC {
+        return Err(EofError::InvalidMagic);
+    }
+    if reader.u8()? != EOF_VERSION {
+        return Err(EofError::InvalidVersion);
+    }
+
+    if reader.u8()? != KIND_TYPES {
+        return Err(EofError::InvalidTypesKind);
+    }
+    let types_size = usize::from(reader.u16()?);
+    if types_size % TYPE_SIZE != 0 {
+        return Err(EofError::InvalidTypesSize);
+    }
+
+    if reader.u8()? != KIND_CODE {
+        return Err(EofError::InvalidCodeKind);
+    }
+    let code_sizes = reader.section_sizes(MAX_CODE_SECTIONS, EofError::TooManyCodeSections)?;
+    if code_sizes.is_empty() {
+        return Err(EofError::NoCodeSections);
+    }
+    if code_sizes.len() != types_size / TYPE_SIZE {
+        return Err(EofError::TypesCodeMismatch);
+    }
+
+    let mut kind = reader.u8()?;
+    let mut container_sizes = Vec::new();
+    if kind == KIND_CONTAINER {
+        container_sizes =
+            reader.section_sizes(MAX_CONTAINER_SECTIONS, EofError::TooManyContainerSections)?;
+        if container_sizes.is_empty() {
+            return Err(EofError::NoContainerSections);
+        }
+        kind = reader.u8()?;
+    }
+    if kind != KIND_DATA {
+        return Err(EofError::InvalidDataKind);
+    }
+    let data_size_index = reader.position;
+    let data_size = reader.u16()?;
+
+    if reader.u8()? != KIND_TERMINATOR {
+        return Err(EofError::MissingTerminator);
+    }
+
+    Ok(Header {
+        size: reader.position,
+        types_size,
+        code_sizes,
+        container_sizes,
+        data_size,
+        data_size_index,
+    })
+}
+
+// Consecutive ranges of the given sizes, starting at `start`
+fn section_ranges(start: &mut usize, sizes: &[usize]) -> Vec<Range<usize>> {
+    sizes
+        .iter()
+        .map(|size| {
+            let section_start = *start;
+            *start = start.saturating_add(*size);
+            section_start..*start
+        })
+        .collect()
+}
+
+impl EofContainer {
+    /// Decodes a container, allowing its data section to be shorter than declared as it can be
+    /// for subcontainers that are deployed with auxiliary data.
+    pub fn decode(raw: Bytes) -> Result<Self, EofError> {
+        let header = decode_header(&raw)?;
+        Self::decode_body(raw, header)
+    }
+
+    /// Decodes a container followed by more bytes, as in the data of a creation transaction.
+    /// Returns the container, whose data section must be complete, and the bytes after it.
+    pub fn decode_with_trailing_data(mut raw: Bytes) -> Result<(Self, Bytes), EofError> {
+        let header = decode_header(&raw)?;
+        let container_size = header.container_size();
+        if container_size > raw.len() {
+            return Err(EofError::MissingInput);
+        }
+        let trailing_data = raw.split_off(container_size);
+        Ok((Self::decode_body(raw, header)?, trailing_data))
+    }
+
+    fn decode_body(raw: Bytes, header: Header) -> Result<Self, EofError> {
+        let partial_size = header.size.saturating_add(header.partial_body_size());
+        if raw.len() < partial_size {
+            return Err(EofError::MissingBody);
+        }
+        if raw.len() > header.container_size() {
+            return Err(EofError::DanglingData);
+        }
+
+        let types = raw
+            .get(header.size..header.size.saturating_add(header.types_size))
+            .ok_or(EofError::MissingBody)?
+            .chunks_exact(TYPE_SIZE)
+            .map(|entry| match *entry {
+                [inputs, outputs, height_high, height_low] => {
+                    let max_stack_height = u16::from_be_bytes([height_high, height_low]);
+                    if inputs > MAX_INPUTS
+                        || outputs > NON_RETURNING_FUNCTION
+                        || max_stack_height > MAX_STACK_HEIGHT
+                        || u16::from(inputs) > max_stack_height
+                    {
+                        return Err(EofError::InvalidTypeSection);
+                    }
+                    Ok(TypeSection {
+                        inputs,
+                        outputs,
+                        max_stack_height,
+                    })
+                }
+                _ => Err(EofError::InvalidTypeSection),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut start = header.size.saturating_add(header.types_size);
+        let code_sections = section_ranges(&mut start, &header.code_sizes);
+        let container_sections = section_ranges(&mut start, &header.container_sizes);
+
+        Ok(Self {
+            raw,
+            types,
+            code_sections,
+            container_sections,
+            data_offset: start,
+            data_size: header.data_size,
+            data_size_index: header.data_size_index,
+        })
+    }
+
+    pub fn raw(&self) -> &Bytes {
+        &self.raw
+    }
+
+    pub fn len(&self) -> usize {
+        self.raw.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.raw.is_empty()
+    }
+
+    pub fn type_section(&self, index: usize) -> Option<&TypeSection> {
+        self.types.get(index)
+    }
+
+    pub fn code_section_count(&self) -> usize {
+        self.code_sections.len()
+    }
+
+    /// Position of the code section within the container
+    pub fn code_section(&self, index: usize) -> Option<Range<usize>> {
+        self.code_sections.get(index).cloned()
+    }
+
+    pub fn container_count(&self) -> usize {
+        self.container_sections.len()
+    }
+
+    pub fn container(&self, index: usize) -> Option<Bytes> {
+        let range = self.container_sections.get(index)?;
+        (range.end <= self.raw.len()).then(|| self.raw.slice(range.clone()))
+    }
+
+    /// Data section as present in the container
+    pub fn data(&self) -> &[u8] {
+        self.raw.get(self.data_offset..).unwrap_or_default()
+    }
+
+    /// Data size declared in the header
+    pub fn declared_data_size(&self) -> u16 {
+        self.data_size
+    }
+
+    pub fn is_data_filled(&self) -> bool {
+        self.data().len() == usize::from(self.data_size)
+    }
+
+    /// Position of the data size in the header
+    pub fn data_size_index(&self) -> usize {
+        self.data_size_index
+    }
+
+    /// Validates the container and every subcontainer in it, the container being used as
+    /// `kind`.
+    pub fn validate(&self, kind: ContainerKind) -> Result<(), EofError> {
+        if !self.is_data_filled() {
+            return Err(EofError::DataNotFilled);
+        }
+
+        let mut pending = vec![(self.clone(), kind)];
+        while let Some((container, kind)) = pending.pop() {
+            let subcontainer_kinds = container.validate_code(kind)?;
+            for (index, kind) in subcontainer_kinds.into_iter().enumerate() {
+                let raw = container
+                    .container(index)
+                    .ok_or(EofError::InvalidContainerIndex)?;
+                pending.push((Self::decode(raw)?, kind));
+            }
+        }
+        Ok(())
+    }
+
+    // Validates the code sections of this container, returning the kind of each subcontainer
+    fn validate_code(&self, kind: ContainerKind) -> Result<Vec<ContainerKind>, EofError> {
+        let first_type = self.types.first().ok_or(EofError::NoCodeSections)?;
+        if first_type.inputs != 0 || !first_type.is_non_returning() {
+            return Err(EofError::InvalidFirstTypeSection);
+        }
+
+        let mut tracker = AccessTracker {
+            kind: Some(kind),
+            reached_sections: vec![false; self.code_sections.len()],
+            pending_sections: vec![0],
+            container_kinds: vec![None; self.container_sections.len()],
+        };
+        if let Some(reached) = tracker.reached_sections.first_mut() {
+            *reached = true;
+        }
+        while let Some(index) = tracker.pending_sections.pop() {
+            self.validate_code_section(index, &mut tracker)?;
+        }
+
+        if tracker.reached_sections.contains(&false) {
+            return Err(EofError::UnreachableCodeSection);
+        }
+        if tracker.kind == Some(ContainerKind::Initcode) && !self.is_data_filled() {
+            return Err(EofError::DataNotFilled);
+        }
+        tracker
+            .container_kinds
+            .into_iter()
+            .map(|kind| kind.ok_or(EofError::UnreachableContainer))
+            .collect()
+    }
+
+    fn validate_code_section(
+        &self,
+        index: usize,
+        tracker: &mut AccessTracker,
+    ) -> Result<(), EofError> {
+        let code = self
+            .code_section(index)
+            .and_then(|range| self.raw.get(range))
+            .ok_or(EofError::MissingBody)?;
+        let this_type = *self
+            .types
+            .get(index)
+            .ok_or(EofError::InvalidCodeSectionIndex)?;
+        let code_len = i64::try_from(code.len()).map_err(|_| EofError::MissingBody)?;
+
+        let mut instructions = vec![InstructionInfo::default(); code.len()];
+        let mut after_terminating = false;
+        let mut next_smallest = i32::from(this_type.inputs);
+        let mut next_biggest = i32::from(this_type.inputs);
+        let mut is_returning = false;
+
+        let mut pc = 0;
+        while let Some(&byte) = code.get(pc) {
+            let info = opcode_info(byte).ok_or(EofError::UnknownOpcode)?;
+            if info.disabled {
+                return Err(EofError::OpcodeDisabled);
+            }
+
+            let instruction = instructions
+                .get_mut(pc)
+                .ok_or(EofError::MissingImmediateBytes)?;
+            if after_terminating {
+                // Only reachable through forward jumps, which already set its stack heights
+                if !instruction.is_jump_target {
+                    return Err(EofError::UnreachableInstruction);
+                }
+            } else {
+                instruction.smallest = instruction.smallest.min(next_smallest);
+                instruction.biggest = instruction.biggest.max(next_biggest);
+            }
+            let InstructionInfo {
+                smallest, biggest, ..
+            } = *instruction;
+            after_terminating = info.terminating;
+
+            let immediate = |offset: usize| {
+                code.get(pc.saturating_add(1).saturating_add(offset))
+                    .copied()
+                    .ok_or(EofError::MissingImmediateBytes)
+            };
+            let immediate_u16 = |offset: usize| -> Result<u16, EofError> {
+                Ok(u16::from_be_bytes([
+                    immediate(offset)?,
+                    immediate(offset.saturating_add(1))?,
+                ]))
+            };
+
+            let mut immediate_size = usize::from(info.immediate_size);
+            let mut io_diff = i32::from(info.outputs).saturating_sub(i32::from(info.inputs));
+            let mut requirement = i32::from(info.inputs);
+            let mut jump_offsets = Vec::new();
+
+            let opcode = Opcode::try_from(byte).map_err(|_| EofError::UnknownOpcode)?;
+            match opcode {
+                Opcode::RJUMP | Opcode::RJUMPI => {
+                    jump_offsets.push(i16::from_be_bytes(immediate_u16(0)?.to_be_bytes()));
+                }
+                Opcode::RJUMPV => {
+                    let cases = usize::from(immediate(0)?).saturating_add(1);
+                    immediate_size = immediate_size.saturating_add(cases.saturating_mul(2));
+                    for case in 0..cases {
+                        let offset = immediate_u16(case.saturating_mul(2).saturating_add(1))?;
+                        jump_offsets.push(i16::from_be_bytes(offset.to_be_bytes()));
+                    }
+                }
+                Opcode::CALLF => {
+                    let target_index = usize::from(immediate_u16(0)?);
+                    let target = self
+                        .types
+                        .get(target_index)
+                        .ok_or(EofError::InvalidCodeSectionIndex)?;
+                    if target.is_non_returning() {
+                        return Err(EofError::CallfToNonReturning);
+                    }
+                    requirement = i32::from(target.inputs);
+                    io_diff = i32::from(target.outputs).saturating_sub(requirement);
+                    // The inputs are part of the max stack height of the called section
+                    if biggest
+                        .saturating_sub(requirement)
+                        .saturating_add(i32::from(target.max_stack_height))
+                        > MAX_STACK_ITEMS
+                    {
+                        return Err(EofError::StackOverflow);
+                    }
+                    tracker.reach_section(target_index);
+                }
+                Opcode::JUMPF => {
+                    let target_index = usize::from(immediate_u16(0)?);
+                    let target = self
+                        .types
+                        .get(target_index)
+                        .ok_or(EofError::InvalidCodeSectionIndex)?;
+                    if biggest
+                        .saturating_sub(i32::from(target.inputs))
+                        .saturating_add(i32::from(target.max_stack_height))
+                        > MAX_STACK_ITEMS
+                    {
+                        return Err(EofError::StackOverflow);
+                    }
+                    tracker.reach_section(target_index);
+
+                    if target.is_non_returning() {
+                        requirement = i32::from(target.inputs);
+                    } else {
+                        // The target returns to this section's caller with its outputs
+                        is_returning = true;
+                        if this_type.outputs < target.outputs {
+                            return Err(EofError::JumpfInsufficientOutputs);
+                        }
+                        requirement = i32::from(this_type.outputs)
+                            .saturating_add(i32::from(target.inputs))
+                            .saturating_sub(i32::from(target.outputs));
+                        if biggest > requirement {
+                            return Err(EofError::StackHigherThanOutputs);
+                        }
+                        if biggest.saturating_add(requirement) > MAX_STACK_ITEMS {
+                            return Err(EofError::StackOverflow);
+                        }
+                    }
+                }
+                Opcode::RETF => {
+                    is_returning = true;
+                    requirement = i32::from(this_type.outputs);
+                    if biggest > requirement {
+                        return Err(EofError::StackHigherThanOutputs);
+                    }
+                }
+                Opcode::EOFCREATE => {
+                    let container_index = usize::from(immediate(0)?);
+                    tracker.set_container_kind(container_index, ContainerKind::Initcode)?;
+                }
+                Opcode::RETURNCONTRACT => {
+                    let container_index = usize::from(immediate(0)?);
+                    tracker.set_kind(ContainerKind::Initcode)?;
+                    tracker.set_container_kind(container_index, ContainerKind::Runtime)?;
+                }
+                Opcode::RETURN | Opcode::STOP => {
+                    tracker.set_kind(ContainerKind::Runtime)?;
+                }
+                Opcode::DATALOADN => {
+                    let offset = usize::from(immediate_u16(0)?);
+                    let data_size = usize::from(self.data_size);
+                    if data_size < 32 || offset > data_size.saturating_sub(32) {
+                        return Err(EofError::DataLoadOutOfBounds);
+                    }
+                }
+                Opcode::DUPN => {
+                    requirement = i32::from(immediate(0)?).saturating_add(1);
+                }
+                Opcode::SWAPN => {
+                    requirement = i32::from(immediate(0)?).saturating_add(2);
+                }
+                Opcode::EXCHANGE => {
+                    let (n, m) = exchange_positions(immediate(0)?);
+                    requirement = i32::from(n).saturating_add(i32::from(m)).saturating_add(1);
+                }
+                _ => {}
+            }
+
+            // The immediate bytes can't be jumped into
+            if pc.saturating_add(immediate_size) >= code.len() {
+                return Err(EofError::MissingImmediateBytes);
+            }
+            for position in 1..=immediate_size {
+                if let Some(immediate) = instructions.get_mut(pc.saturating_add(position)) {
+                    if immediate.is_jump_target {
+                        return Err(EofError::JumpIntoImmediate);
+                    }
+                    immediate.is_immediate = true;
+                }
+            }
+
+            if requirement > smallest {
+                return Err(EofError::StackUnderflow);
+            }
+            next_smallest = smallest.saturating_add(io_diff);
+            next_biggest = biggest.saturating_add(io_diff);
+
+            // Jumps are relative to the next instruction
+            let next_pc = pc.saturating_add(immediate_size).saturating_add(1);
+            let next_pc = i64::try_from(next_pc).map_err(|_| EofError::JumpOutOfBounds)?;
+            for offset in jump_offsets {
+                let target = next_pc.saturating_add(i64::from(offset));
+                if target < 0 || target >= code_len {
+                    return Err(EofError::JumpOutOfBounds);
+                }
+                let target = usize::try_from(target).map_err(|_| EofError::JumpOutOfBounds)?;
+                let target_instruction = instructions
+                    .get_mut(target)
+                    .ok_or(EofError::JumpOutOfBounds)?;
+                if target_instruction.is_immediate {
+                    return Err(EofError::JumpIntoImmediate);
+                }
+                target_instruction.is_jump_target = true;
+
+                if target <= pc {
+                    // Stack heights must be the same however the instruction is reached
+                    if target_instruction.smallest != next_smallest
+                        || target_instruction.biggest != next_biggest
+                    {
+                        return Err(EofError::StackHeightMismatch);
+                    }
+                } else {
+                    target_instruction.smallest = target_instruction.smallest.min(next_smallest);
+                    target_instruction.biggest = target_instruction.biggest.max(next_biggest);
+                }
+            }
+
+            pc = pc.saturating_add(immediate_size).saturating_add(1);
+        }
+
+        if is_returning == this_type.is_non_returning() {
+            return Err(EofError::ReturningMismatch);
+        }
+        if !after_terminating {
+            return Err(EofError::MissingTerminatingInstruction);
+        }
+        let max_stack_height = instructions
+            .iter()
+            .map(|instruction| instruction.biggest)
+            .fold(0, i32::max);
+        if max_stack_height != i32::from(this_type.max_stack_height) {
+            return Err(EofError::MaxStackHeightMismatch);
+        }
+        Ok(())
+    }
+}
+
+/// Depths of the items swapped by EXCHANGE, counting from the top item after it
+pub fn exchange_positions(immediate: u8) -> (u8, u8) {
+    (
+        (immediate >> 4).saturating_add(1),
+        (immediate & 0x0F).saturating_add(1),
+    )
+}
+
+// Code sections and subcontainers reached while validating a container
+struct AccessTracker {
+    /// How the container is used, learned from its code if the caller doesn't know it
+    kind: Option<ContainerKind>,
+    reached_sections: Vec<bool>,
+    /// Reached code sections that haven't been validated yet
+    pending_sections: Vec<usize>,
+    container_kinds: Vec<Option<ContainerKind>>,
+}
+
+impl AccessTracker {
+    fn reach_section(&mut self, index: usize) {
+        if let Some(reached) = self.reached_sections.get_mut(index) {
+            if !*reached {
+                *reached = true;
+                self.pending_sections.push(index);
+            }
+        }
+    }
+
+    fn set_kind(&mut self, kind: ContainerKind) -> Result<(), EofError> {
+        if *self.kind.get_or_insert(kind) != kind {
+            return Err(EofError::IncompatibleContainerKind);
+        }
+        Ok(())
+    }
+
+    fn set_container_kind(&mut self, index: usize, kind: ContainerKind) -> Result<(), EofError> {
+        let container_kind = self
+            .container_kinds
+            .get_mut(index)
+            .ok_or(EofError::InvalidContainerIndex)?;
+        if *container_kind.get_or_insert(kind) != kind {
+            return Err(EofError::IncompatibleContainerKind);
+        }
+        Ok(())
+    }
+}
+
+// Stack heights an instruction can be reached with
+#[derive(Debug, Clone, Copy)]
+struct InstructionInfo {
+    is_immediate: bool,
+    is_jump_target: bool,
+    smallest: i32,
+    biggest: i32,
+}
+
+impl Default for InstructionInfo {
+    fn default() -> Self {
+        Self {
+            is_immediate: false,
+            is_jump_target: false,
+            smallest: i32::MAX,
+            biggest: i32::MIN,
+        }
+    }
+}
+
+// What the validation needs to know about an opcode
+#[derive(Debug, Clone, Copy, Default)]
+struct OpcodeInfo {
+    inputs: u8,
+    outputs: u8,
+    immediate_size: u8,
+    terminating: bool,
+    disabled: bool,
+}
+
+const fn io(inputs: u8, outputs: u8) -> OpcodeInfo {
+    OpcodeInfo {
+        inputs,
+        outputs,
+        immediate_size: 0,
+        terminating: false,
+        disabled: false,
+    }
+}
+
+const fn with_immediate(immediate_size: u8, info: OpcodeInfo) -> OpcodeInfo {
+    OpcodeInfo {
+        immediate_size,
+        ..info
+    }
+}
+
+const fn terminating(info: OpcodeInfo) -> OpcodeInfo {
+    OpcodeInfo {
+        terminating: true,
+        ..info
+    }
+}
+
+const DISABLED: OpcodeInfo = OpcodeInfo {
+    inputs: 0,
+    outputs: 0,
+    immediate_size: 0,
+    terminating: false,
+    disabled: true,
+};
+
+fn opcode_info(byte: u8) -> Option<OpcodeInfo> {
+    let opcode = Opcode::try_from(byte).ok()?;
+    let info = match opcode {
+        Opcode::STOP => terminating(io(0, 0)),
+        Opcode::ADDMOD | Opcode::MULMOD => io(3, 1),
+        Opcode::ISZERO | Opcode::NOT => io(1, 1),
+        op if (Opcode::ADD..=Opcode::SAR).contains(&op) => io(2, 1),
+        Opcode::KECCAK256 => io(2, 1),
+        Opcode::CODESIZE
+        | Opcode::CODECOPY
+        | Opcode::EXTCODESIZE
+        | Opcode::EXTCODECOPY
+        | Opcode::EXTCODEHASH
+        | Opcode::JUMP
+        | Opcode::JUMPI
+        | Opcode::PC
+        | Opcode::GAS
+        | Opcode::CREATE
+        | Opcode::CALL
+        | Opcode::CALLCODE
+        | Opcode::DELEGATECALL
+        | Opcode::CREATE2
+        | Opcode::STATICCALL
+        | Opcode::SELFDESTRUCT => DISABLED,
+        Opcode::BALANCE | Opcode::CALLDATALOAD | Opcode::BLOCKHASH | Opcode::BLOBHASH => io(1, 1),
+        Opcode::CALLDATACOPY | Opcode::RETURNDATACOPY | Opcode::MCOPY => io(3, 0),
+        op if (Opcode::ADDRESS..=Opcode::BLOBBASEFEE).contains(&op) => io(0, 1),
+        Opcode::POP => io(1, 0),
+        Opcode::MLOAD | Opcode::SLOAD | Opcode::TLOAD => io(1, 1),
+        Opcode::MSTORE | Opcode::MSTORE8 | Opcode::SSTORE | Opcode::TSTORE => io(2, 0),
+        Opcode::MSIZE | Opcode::PUSH0 => io(0, 1),
+        Opcode::JUMPDEST => io(0, 0),
+        op if (Opcode::PUSH1..=Opcode::PUSH32).contains(&op) => {
+            with_immediate(byte.saturating_sub(u8::from(Opcode::PUSH0)), io(0, 1))
+        }
+        op if (Opcode::DUP1..=Opcode::DUP16).contains(&op) => {
+            let n = byte
+                .saturating_sub(u8::from(Opcode::DUP1))
+                .saturating_add(1);
+            io(n, n.saturating_add(1))
+        }
+        op if (Opcode::SWAP1..=Opcode::SWAP16).contains(&op) => {
+            let n = byte
+                .saturating_sub(u8::from(Opcode::SWAP1))
+                .saturating_add(2);
+            io(n, n)
+        }
+        op if (Opcode::LOG0..=Opcode::LOG4).contains(&op) => io(
+            byte.saturating_sub(u8::from(Opcode::LOG0))
+                .saturating_add(2),
+            0,
+        ),
+        Opcode::DATALOAD => io(1, 1),
+        Opcode::DATALOADN => with_immediate(2, io(0, 1)),
+        Opcode::DATASIZE => io(0, 1),
+        Opcode::DATACOPY => io(3, 0),
+        Opcode::RJUMP => terminating(with_immediate(2, io(0, 0))),
+        Opcode::RJUMPI => with_immediate(2, io(1, 0)),
+        // The jump table after the max index is handled by the validation
+        Opcode::RJUMPV => with_immediate(1, io(1, 0)),
+        Opcode::CALLF => with_immediate(2, io(0, 0)),
+        Opcode::RETF => terminating(io(0, 0)),
+        Opcode::JUMPF => terminating(with_immediate(2, io(0, 0))),
+        Opcode::DUPN => with_immediate(1, io(0, 1)),
+        Opcode::SWAPN | Opcode::EXCHANGE => with_immediate(1, io(0, 0)),
+        Opcode::EOFCREATE => with_immediate(1, io(4, 1)),
+        Opcode::RETURNCONTRACT => terminating(with_immediate(1, io(2, 0))),
+        Opcode::RETURN | Opcode::REVERT => terminating(io(2, 0)),
+        Opcode::RETURNDATALOAD => io(1, 1),
+        Opcode::EXTCALL => io(4, 1),
+        Opcode::EXTDELEGATECALL | Opcode::EXTSTATICCALL => io(3, 1),
+        Opcode::INVALID => terminating(io(0, 0)),
+        _ => return None,
+    };
+    Some(info)
+}
@@ -1,4 +1,4 @@
-use crate::account::Account;
+use crate::{account::Account, eof::EofError};
 use bytes::Bytes;
 use ethrex_core::{types::Log, Address};
 use serde::{Deserialize, Serialize};
@@ -70,6 +70,18 @@ pub enum VMError {
     NonceOverflow,
     #[error("Nonce underflowed")]
     NonceUnderflow,
+    #[error("Invalid EOF container: {0}")]
+    InvalidEof(#[from] EofError),
+    #[error("Return Stack Overflow")]
+    ReturnStackOverflow,
+    #[error("Return Stack Underflow")]
+    ReturnStackUnderflow,
+    #[error("Invalid Call Target")]
+    InvalidCallTarget,
+    #[error("Auxiliary Data Too Large")]
+    AuxDataTooLarge,
+    #[error("Auxiliary Data Too Small")]
+    AuxDataTooSmall,
     // OutOfGas
     #[error("Out Of Gas")]
     OutOfGas(#[from] OutOfGasError),
@@ -174,6 +186,21 @@ pub enum InternalError {
     ExcessBlobGasShouldNotBeNone,
     #[error("Error in utils file")]
     UtilsError,
+    #[error("Database access error: {0}")]
+    Database(#[from] DatabaseError),
+}
+
+/// Errors returned by a [`Database`](crate::db::Database) when it can't read the state
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error, Serialize, Deserialize)]
+pub enum DatabaseError {
+    #[error("{0}")]
+    Custom(String),
+}
+
+impl From<DatabaseError> for VMError {
+    fn from(error: DatabaseError) -> Self {
+        VMError::Internal(InternalError::Database(error))
+    }
 }
 
 #[derive(Debug, Clone)]
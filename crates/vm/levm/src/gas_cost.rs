@@ -1,12 +1,12 @@
 use crate::{
     call_frame::CallFrame,
-    constants::{WORD_SIZE, WORD_SIZE_IN_BYTES},
+    constants::{TX_BASE_COST, WORD_SIZE, WORD_SIZE_IN_BYTES},
     errors::{InternalError, OutOfGasError, VMError},
     memory, StorageSlot,
 };
 use bytes::Bytes;
 /// Contains the gas costs of the EVM instructions (in wei)
-use ethrex_core::U256;
+use ethrex_core::{Address, H256, U256};
 
 // Opcodes cost
 pub const ADD: U256 = U256([3, 0, 0, 0]);
@@ -87,6 +87,23 @@ pub const GASPRICE: U256 = U256([2, 0, 0, 0]);
 pub const SELFDESTRUCT_STATIC: U256 = U256([5000, 0, 0, 0]);
 pub const SELFDESTRUCT_DYNAMIC: U256 = U256([25000, 0, 0, 0]);
 
+// EOF opcodes cost (EIP-7692)
+pub const DATALOAD: U256 = U256([4, 0, 0, 0]);
+pub const DATALOADN: U256 = U256([3, 0, 0, 0]);
+pub const DATASIZE: U256 = U256([2, 0, 0, 0]);
+pub const DATACOPY_STATIC: U256 = U256([3, 0, 0, 0]);
+pub const DATACOPY_DYNAMIC_BASE: U256 = U256([3, 0, 0, 0]);
+pub const RJUMP: U256 = U256([2, 0, 0, 0]);
+pub const RJUMPI: U256 = U256([4, 0, 0, 0]);
+pub const RJUMPV: U256 = U256([4, 0, 0, 0]);
+pub const CALLF: U256 = U256([5, 0, 0, 0]);
+pub const RETF: U256 = U256([3, 0, 0, 0]);
+pub const JUMPF: U256 = U256([5, 0, 0, 0]);
+pub const EXCHANGE: U256 = U256([3, 0, 0, 0]);
+pub const RETURNDATALOAD: U256 = U256([3, 0, 0, 0]);
+pub const EOFCREATE_STATIC: U256 = U256([32000, 0, 0, 0]);
+pub const EOFCREATE_DYNAMIC_BASE: U256 = KECCAK25_DYNAMIC_BASE;
+
 pub const DEFAULT_STATIC: U256 = U256::zero();
 pub const DEFAULT_COLD_DYNAMIC: U256 = U256([2600, 0, 0, 0]);
 pub const DEFAULT_WARM_DYNAMIC: U256 = U256([100, 0, 0, 0]);
@@ -99,7 +116,9 @@ pub const SSTORE_STATIC: U256 = U256::zero();
 pub const SSTORE_COLD_DYNAMIC: U256 = U256([2100, 0, 0, 0]);
 pub const SSTORE_DEFAULT_DYNAMIC: U256 = U256([100, 0, 0, 0]);
 pub const SSTORE_STORAGE_CREATION: U256 = U256([20000, 0, 0, 0]);
-pub const SSTORE_STORAGE_MODIFICATION: U256 = U256([5000, 0, 0, 0]);
+// Resetting a slot costs 5000 minus the cold access cost, which is charged apart (EIP-2929)
+pub const SSTORE_STORAGE_MODIFICATION: U256 = U256([2900, 0, 0, 0]);
+pub const SSTORE_CLEARS_SCHEDULE: U256 = U256([4800, 0, 0, 0]);
 
 pub const BALANCE_STATIC: U256 = DEFAULT_STATIC;
 pub const BALANCE_COLD_DYNAMIC: U256 = DEFAULT_COLD_DYNAMIC;
@@ -146,11 +165,19 @@ pub const NON_ZERO_VALUE_COST: U256 = U256([9000, 0, 0, 0]);
 pub const BASIC_FALLBACK_FUNCTION_STIPEND: U256 = U256([2300, 0, 0, 0]);
 pub const VALUE_TO_EMPTY_ACCOUNT_COST: U256 = U256([25000, 0, 0, 0]);
 
+// Gas an EXT*CALL callee gets at least and gas its caller keeps at least (EIP-7069)
+pub const MIN_CALLEE_GAS: U256 = U256([2300, 0, 0, 0]);
+pub const MIN_RETAINED_GAS: U256 = U256([5000, 0, 0, 0]);
+
 // Costs in gas for create opcodes (in wei)
 pub const INIT_CODE_WORD_COST: U256 = U256([2, 0, 0, 0]);
 pub const CODE_DEPOSIT_COST: U256 = U256([200, 0, 0, 0]);
 pub const CREATE_BASE_COST: U256 = U256([32000, 0, 0, 0]);
 
+// Costs in gas for the access list of a transaction (in wei)
+pub const ACCESS_LIST_ADDRESS_COST: U256 = U256([2400, 0, 0, 0]);
+pub const ACCESS_LIST_STORAGE_KEY_COST: U256 = U256([1900, 0, 0, 0]);
+
 pub fn exp(exponent_bits: u64) -> Result<U256, OutOfGasError> {
     let exponent_byte_size = (exponent_bits
         .checked_add(7)
@@ -231,6 +258,20 @@ fn copy_behavior(
         .ok_or(OutOfGasError::GasCostOverflow)
 }
 
+pub fn datacopy(
+    current_call_frame: &CallFrame,
+    size: usize,
+    dest_offset: usize,
+) -> Result<U256, OutOfGasError> {
+    copy_behavior(
+        DATACOPY_DYNAMIC_BASE,
+        DATACOPY_STATIC,
+        current_call_frame,
+        size,
+        dest_offset,
+    )
+}
+
 pub fn keccak256(
     current_call_frame: &CallFrame,
     size: usize,
@@ -444,6 +485,27 @@ fn compute_gas_create(
         .ok_or(OutOfGasError::CreationCostIsTooHigh)
 }
 
+/// Cost of EOFCREATE, which hashes the initcontainer to get the new address
+pub fn eofcreate(
+    current_call_frame: &CallFrame,
+    input_offset: usize,
+    input_size: usize,
+    initcontainer_size: usize,
+) -> Result<U256, OutOfGasError> {
+    let memory_expansion_cost = current_call_frame
+        .memory
+        .expansion_cost(input_offset, input_size)?;
+    let hash_cost = EOFCREATE_DYNAMIC_BASE
+        .checked_mul(initcontainer_size.div_ceil(WORD_SIZE).into())
+        .ok_or(OutOfGasError::GasCostOverflow)?;
+
+    EOFCREATE_STATIC
+        .checked_add(memory_expansion_cost)
+        .ok_or(OutOfGasError::CreationCostIsTooHigh)?
+        .checked_add(hash_cost)
+        .ok_or(OutOfGasError::CreationCostIsTooHigh)
+}
+
 pub fn selfdestruct(address_was_cold: bool, account_is_empty: bool) -> Result<U256, OutOfGasError> {
     let mut gas_cost = SELFDESTRUCT_STATIC;
 
@@ -480,21 +542,58 @@ pub fn tx_calldata(calldata: &Bytes) -> Result<u64, OutOfGasError> {
     Ok(calldata_cost)
 }
 
-pub fn tx_creation(code_length: u64, number_of_words: u64) -> Result<u64, OutOfGasError> {
-    let mut creation_cost = code_length
-        .checked_mul(200)
-        .ok_or(OutOfGasError::CreationCostIsTooHigh)?;
-    creation_cost = creation_cost
-        .checked_add(32000)
-        .ok_or(OutOfGasError::CreationCostIsTooHigh)?;
+/// Gas charged before executing a transaction: the base cost, its data, the initcode of a
+/// create transaction and the entries of its access list (EIP-2930)
+pub fn tx_intrinsic(
+    data: &Bytes,
+    is_create: bool,
+    access_list: &[(Address, Vec<H256>)],
+) -> Result<u64, OutOfGasError> {
+    let mut intrinsic_gas = TX_BASE_COST
+        .low_u64()
+        .checked_add(tx_calldata(data)?)
+        .ok_or(OutOfGasError::GasUsedOverflow)?;
+
+    if is_create {
+        let number_of_words: u64 = data
+            .len()
+            .div_ceil(WORD_SIZE)
+            .try_into()
+            .map_err(|_| OutOfGasError::GasCostOverflow)?;
+        let init_code_cost = number_of_words
+            .checked_mul(INIT_CODE_WORD_COST.low_u64())
+            .ok_or(OutOfGasError::GasCostOverflow)?;
+        intrinsic_gas = intrinsic_gas
+            .checked_add(CREATE_BASE_COST.low_u64())
+            .and_then(|gas| gas.checked_add(init_code_cost))
+            .ok_or(OutOfGasError::GasUsedOverflow)?;
+    }
 
-    // GInitCodeword * number_of_words rounded up. GinitCodeWord = 2
-    let words_cost = number_of_words
-        .checked_mul(2)
-        .ok_or(OutOfGasError::GasCostOverflow)?;
-    creation_cost
-        .checked_add(words_cost)
-        .ok_or(OutOfGasError::GasUsedOverflow)
+    for (_, storage_keys) in access_list {
+        let storage_keys: u64 = storage_keys
+            .len()
+            .try_into()
+            .map_err(|_| OutOfGasError::GasCostOverflow)?;
+        let entry_cost = storage_keys
+            .checked_mul(ACCESS_LIST_STORAGE_KEY_COST.low_u64())
+            .and_then(|cost| cost.checked_add(ACCESS_LIST_ADDRESS_COST.low_u64()))
+            .ok_or(OutOfGasError::GasCostOverflow)?;
+        intrinsic_gas = intrinsic_gas
+            .checked_add(entry_cost)
+            .ok_or(OutOfGasError::GasUsedOverflow)?;
+    }
+
+    Ok(intrinsic_gas)
+}
+
+/// Cost of storing the code returned by the initcode of a contract
+pub fn code_deposit(code_length: usize) -> Result<u64, OutOfGasError> {
+    let code_length: u64 = code_length
+        .try_into()
+        .map_err(|_| OutOfGasError::GasCostOverflow)?;
+    code_length
+        .checked_mul(CODE_DEPOSIT_COST.low_u64())
+        .ok_or(OutOfGasError::CreationCostIsTooHigh)
 }
 
 fn address_access_cost(
@@ -720,3 +819,38 @@ pub fn staticcall(
         .checked_add(dynamic_gas)
         .ok_or(OutOfGasError::GasCostOverflow)?)
 }
+
+/// Cost of EXTCALL, EXTDELEGATECALL and EXTSTATICCALL, not including the gas given to the callee
+pub fn ext_call(
+    current_call_frame: &CallFrame,
+    input_offset: usize,
+    input_size: usize,
+    address_was_cold: bool,
+    address_is_empty: bool,
+    value_to_transfer: U256,
+) -> Result<U256, VMError> {
+    let memory_expansion_cost = current_call_frame
+        .memory
+        .expansion_cost(input_offset, input_size)?;
+    let address_access_cost = address_access_cost(
+        address_was_cold,
+        CALL_STATIC,
+        CALL_COLD_DYNAMIC,
+        CALL_WARM_DYNAMIC,
+    )?;
+    let (positive_value_cost, value_to_empty_account) = if value_to_transfer.is_zero() {
+        (U256::zero(), U256::zero())
+    } else if address_is_empty {
+        (CALL_POSITIVE_VALUE, CALL_TO_EMPTY_ACCOUNT)
+    } else {
+        (CALL_POSITIVE_VALUE, U256::zero())
+    };
+
+    Ok(memory_expansion_cost
+        .checked_add(address_access_cost)
+        .ok_or(OutOfGasError::GasCostOverflow)?
+        .checked_add(positive_value_cost)
+        .ok_or(OutOfGasError::GasCostOverflow)?
+        .checked_add(value_to_empty_account)
+        .ok_or(OutOfGasError::GasCostOverflow)?)
+}
@@ -0,0 +1,182 @@
+use crate::{
+    account::{Account, StorageSlot},
+    db::{cache, CacheDB},
+    errors::{InternalError, VMError},
+    vm::{Substate, TransientStorage},
+};
+use bytes::Bytes;
+use ethrex_core::{Address, H256, U256};
+
+/// A change made to the state during a transaction, along with what it replaced so it can be
+/// undone
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JournalEntry {
+    /// An account was created, replacing the cached one if there was any
+    AccountCreated {
+        address: Address,
+        previous: Option<Account>,
+    },
+    BalanceChanged {
+        address: Address,
+        previous: U256,
+    },
+    NonceChanged {
+        address: Address,
+        previous: u64,
+    },
+    CodeChanged {
+        address: Address,
+        previous: Bytes,
+        previous_hash: H256,
+    },
+    /// A storage slot was written, `previous` is `None` if the slot wasn't cached
+    StorageChanged {
+        address: Address,
+        key: H256,
+        previous: Option<StorageSlot>,
+    },
+    TransientStorageChanged {
+        address: Address,
+        key: U256,
+        previous: Option<U256>,
+    },
+    AccountWarmed {
+        address: Address,
+    },
+    StorageKeyWarmed {
+        address: Address,
+        key: H256,
+    },
+    RefundChanged {
+        previous: U256,
+    },
+    LogAdded,
+    SelfDestructed {
+        address: Address,
+    },
+    CreatedAccountAdded {
+        address: Address,
+    },
+}
+
+/// Position in the journal to revert to, taken with [`Journal::checkpoint`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Checkpoint(usize);
+
+/// Changes made to the state during a transaction, in the order they happened.
+///
+/// Every mutation of the cache, the substate or the transient storage made by the VM is recorded
+/// here, so reverting a call frame only undoes the changes it made instead of restoring a copy
+/// of the whole state.
+#[derive(Debug, Clone, Default)]
+pub struct Journal {
+    entries: Vec<JournalEntry>,
+}
+
+impl Journal {
+    pub fn record(&mut self, entry: JournalEntry) {
+        self.entries.push(entry);
+    }
+
+    /// Marks the current position of the journal, the changes recorded after it can be undone
+    /// with [`Journal::revert_to`]
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint(self.entries.len())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Undoes the changes recorded after the checkpoint, the most recent first
+    pub fn revert_to(
+        &mut self,
+        checkpoint: Checkpoint,
+        cache: &mut CacheDB,
+        substate: &mut Substate,
+        transient_storage: &mut TransientStorage,
+    ) -> Result<(), VMError> {
+        let start = checkpoint.0.min(self.entries.len());
+        for entry in self.entries.drain(start..).rev() {
+            match entry {
+                JournalEntry::AccountCreated { address, previous } => match previous {
+                    Some(account) => {
+                        cache::insert_account(cache, address, account);
+                    }
+                    None => {
+                        cache::remove_account(cache, &address);
+                    }
+                },
+                JournalEntry::BalanceChanged { address, previous } => {
+                    cached_account(cache, &address)?.info.balance = previous;
+                }
+                JournalEntry::NonceChanged { address, previous } => {
+                    cached_account(cache, &address)?.info.nonce = previous;
+                }
+                JournalEntry::CodeChanged {
+                    address,
+                    previous,
+                    previous_hash,
+                } => {
+                    let info = &mut cached_account(cache, &address)?.info;
+                    info.bytecode = previous;
+                    info.code_hash = previous_hash;
+                }
+                JournalEntry::StorageChanged {
+                    address,
+                    key,
+                    previous,
+                } => {
+                    let storage = &mut cached_account(cache, &address)?.storage;
+                    match previous {
+                        Some(slot) => storage.insert(key, slot),
+                        None => storage.remove(&key),
+                    };
+                }
+                JournalEntry::TransientStorageChanged {
+                    address,
+                    key,
+                    previous,
+                } => {
+                    match previous {
+                        Some(value) => transient_storage.insert((address, key), value),
+                        None => transient_storage.remove(&(address, key)),
+                    };
+                }
+                JournalEntry::AccountWarmed { address } => {
+                    substate.accessed_addresses.remove(&address);
+                }
+                JournalEntry::StorageKeyWarmed { address, key } => {
+                    if let Some(keys) = substate.accessed_storage_keys.get_mut(&address) {
+                        keys.remove(&key);
+                    }
+                }
+                JournalEntry::RefundChanged { previous } => {
+                    substate.refunded_gas = previous;
+                }
+                JournalEntry::LogAdded => {
+                    substate.logs.pop();
+                }
+                JournalEntry::SelfDestructed { address } => {
+                    substate.selfdestruct_set.remove(&address);
+                }
+                JournalEntry::CreatedAccountAdded { address } => {
+                    substate.created_accounts.remove(&address);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Accounts are cached before being changed, so a journaled account is always in the cache
+fn cached_account<'cache>(
+    cache: &'cache mut CacheDB,
+    address: &Address,
+) -> Result<&'cache mut Account, VMError> {
+    cache::get_account_mut(cache, address).ok_or(VMError::Internal(InternalError::AccountNotFound))
+}
@@ -1,10 +1,13 @@
 pub mod account;
 pub mod call_frame;
+pub mod code;
 pub mod constants;
 pub mod db;
 pub mod environment;
+pub mod eof;
 pub mod errors;
 pub mod gas_cost;
+pub mod journal;
 pub mod memory;
 pub mod opcode_handlers;
 pub mod opcodes;
@@ -39,7 +39,7 @@ impl VM {
             .try_into()
             .map_err(|_err| VMError::VeryLargeNumber)?;
 
-        if let Some(block_hash) = self.db.get_block_hash(block_number) {
+        if let Some(block_hash) = self.db.get_block_hash(block_number)? {
             current_call_frame
                 .stack
                 .push(U256::from_big_endian(block_hash.as_bytes()))?;
@@ -136,7 +136,7 @@ impl VM {
 
         // the current account should have been cached when the contract was called
         let balance = self
-            .get_account(current_call_frame.code_address)
+            .get_account(current_call_frame.code_address)?
             .info
             .balance;
 
@@ -184,7 +184,7 @@ impl VM {
         Ok(OpcodeSuccess::Continue)
     }
 
-    fn get_blob_gasprice(&mut self) -> Result<U256, VMError> {
+    pub(crate) fn get_blob_gasprice(&mut self) -> Result<U256, VMError> {
         fake_exponential(
             MIN_BASE_FEE_PER_BLOB_GAS.into(),
             // Use unwrap because env should have a Some value in excess_blob_gas attribute
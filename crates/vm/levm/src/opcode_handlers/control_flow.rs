@@ -0,0 +1,155 @@
+use crate::{
+    call_frame::CallFrame,
+    constants::STACK_LIMIT,
+    eof::MAX_RETURN_STACK_HEIGHT,
+    errors::{OpcodeSuccess, VMError},
+    gas_cost,
+    vm::VM,
+};
+use ethrex_core::U256;
+
+// Static Control Flow Operations (6)
+// Opcodes: RJUMP, RJUMPI, RJUMPV, CALLF, RETF, JUMPF
+// Only in EOF code, whose validation already checked jump targets and stack heights.
+
+impl VM {
+    // RJUMP operation
+    pub fn op_rjump(
+        &mut self,
+        current_call_frame: &mut CallFrame,
+    ) -> Result<OpcodeSuccess, VMError> {
+        self.increase_consumed_gas(current_call_frame, gas_cost::RJUMP)?;
+
+        let offset = i16::from_be_bytes(current_call_frame.immediate_u16(0)?.to_be_bytes());
+        let next_pc = current_call_frame.pc.saturating_add(2);
+        current_call_frame.relative_jump(next_pc, offset)?;
+
+        Ok(OpcodeSuccess::Continue)
+    }
+
+    // RJUMPI operation
+    pub fn op_rjumpi(
+        &mut self,
+        current_call_frame: &mut CallFrame,
+    ) -> Result<OpcodeSuccess, VMError> {
+        self.increase_consumed_gas(current_call_frame, gas_cost::RJUMPI)?;
+
+        let condition = current_call_frame.stack.pop()?;
+        let offset = if condition.is_zero() {
+            0
+        } else {
+            i16::from_be_bytes(current_call_frame.immediate_u16(0)?.to_be_bytes())
+        };
+        let next_pc = current_call_frame.pc.saturating_add(2);
+        current_call_frame.relative_jump(next_pc, offset)?;
+
+        Ok(OpcodeSuccess::Continue)
+    }
+
+    // RJUMPV operation
+    pub fn op_rjumpv(
+        &mut self,
+        current_call_frame: &mut CallFrame,
+    ) -> Result<OpcodeSuccess, VMError> {
+        self.increase_consumed_gas(current_call_frame, gas_cost::RJUMPV)?;
+
+        let case = current_call_frame.stack.pop()?;
+        let max_index = current_call_frame.immediate_u8(0)?;
+        let table_size = usize::from(max_index).saturating_add(1).saturating_mul(2);
+        let next_pc = current_call_frame
+            .pc
+            .saturating_add(1)
+            .saturating_add(table_size);
+
+        // Cases out of the table fall through to the next instruction
+        let offset = if case <= U256::from(max_index) {
+            let offset_position = usize::try_from(case)
+                .map_err(|_| VMError::VeryLargeNumber)?
+                .saturating_mul(2)
+                .saturating_add(1);
+            i16::from_be_bytes(
+                current_call_frame
+                    .immediate_u16(offset_position)?
+                    .to_be_bytes(),
+            )
+        } else {
+            0
+        };
+        current_call_frame.relative_jump(next_pc, offset)?;
+
+        Ok(OpcodeSuccess::Continue)
+    }
+
+    // CALLF operation
+    pub fn op_callf(
+        &mut self,
+        current_call_frame: &mut CallFrame,
+    ) -> Result<OpcodeSuccess, VMError> {
+        self.increase_consumed_gas(current_call_frame, gas_cost::CALLF)?;
+
+        let section_index = usize::from(current_call_frame.immediate_u16(0)?);
+        check_code_section_stack(current_call_frame, section_index)?;
+        if current_call_frame.return_stack.len() >= MAX_RETURN_STACK_HEIGHT {
+            return Err(VMError::ReturnStackOverflow);
+        }
+
+        let return_pc = current_call_frame.pc.saturating_add(2);
+        current_call_frame
+            .return_stack
+            .push((current_call_frame.code_section, return_pc));
+        current_call_frame.enter_code_section(section_index)?;
+
+        Ok(OpcodeSuccess::Continue)
+    }
+
+    // RETF operation
+    pub fn op_retf(
+        &mut self,
+        current_call_frame: &mut CallFrame,
+    ) -> Result<OpcodeSuccess, VMError> {
+        self.increase_consumed_gas(current_call_frame, gas_cost::RETF)?;
+
+        let (section_index, return_pc) = current_call_frame
+            .return_stack
+            .pop()
+            .ok_or(VMError::ReturnStackUnderflow)?;
+        current_call_frame.code_section = section_index;
+        current_call_frame.pc = return_pc;
+
+        Ok(OpcodeSuccess::Continue)
+    }
+
+    // JUMPF operation
+    pub fn op_jumpf(
+        &mut self,
+        current_call_frame: &mut CallFrame,
+    ) -> Result<OpcodeSuccess, VMError> {
+        self.increase_consumed_gas(current_call_frame, gas_cost::JUMPF)?;
+
+        let section_index = usize::from(current_call_frame.immediate_u16(0)?);
+        check_code_section_stack(current_call_frame, section_index)?;
+        current_call_frame.enter_code_section(section_index)?;
+
+        Ok(OpcodeSuccess::Continue)
+    }
+}
+
+/// The stack must fit the items the code section may push on top of its inputs
+fn check_code_section_stack(
+    current_call_frame: &CallFrame,
+    section_index: usize,
+) -> Result<(), VMError> {
+    let section_type = current_call_frame
+        .eof()?
+        .type_section(section_index)
+        .ok_or(VMError::InvalidJump)?;
+    let max_stack_height = current_call_frame
+        .stack
+        .len()
+        .saturating_add(usize::from(section_type.max_stack_height))
+        .saturating_sub(usize::from(section_type.inputs));
+    if max_stack_height > STACK_LIMIT {
+        return Err(VMError::StackOverflow);
+    }
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use crate::{
+    call_frame::CallFrame,
+    errors::{InternalError, OpcodeSuccess, VMError},
+    gas_cost,
+    vm::VM,
+};
+use ethrex_core::U256;
+
+// Data Section Operations (4)
+// Opcodes: DATALOAD, DATALOADN, DATASIZE, DATACOPY
+
+impl VM {
+    // DATALOAD operation
+    pub fn op_dataload(
+        &mut self,
+        current_call_frame: &mut CallFrame,
+    ) -> Result<OpcodeSuccess, VMError> {
+        self.increase_consumed_gas(current_call_frame, gas_cost::DATALOAD)?;
+
+        // Offsets past the end of the data read zeros, however big they are
+        let offset = usize::try_from(current_call_frame.stack.pop()?).unwrap_or(usize::MAX);
+
+        let result = load_word(current_call_frame.eof()?.data(), offset);
+        current_call_frame.stack.push(result)?;
+
+        Ok(OpcodeSuccess::Continue)
+    }
+
+    // DATALOADN operation
+    pub fn op_dataloadn(
+        &mut self,
+        current_call_frame: &mut CallFrame,
+    ) -> Result<OpcodeSuccess, VMError> {
+        self.increase_consumed_gas(current_call_frame, gas_cost::DATALOADN)?;
+
+        // The validation checked the offset is within the data section
+        let offset = usize::from(current_call_frame.immediate_u16(0)?);
+        current_call_frame.increment_pc_by(2)?;
+
+        let result = load_word(current_call_frame.eof()?.data(), offset);
+        current_call_frame.stack.push(result)?;
+
+        Ok(OpcodeSuccess::Continue)
+    }
+
+    // DATASIZE operation
+    pub fn op_datasize(
+        &mut self,
+        current_call_frame: &mut CallFrame,
+    ) -> Result<OpcodeSuccess, VMError> {
+        self.increase_consumed_gas(current_call_frame, gas_cost::DATASIZE)?;
+
+        let data_size = current_call_frame.eof()?.data().len();
+        current_call_frame.stack.push(U256::from(data_size))?;
+
+        Ok(OpcodeSuccess::Continue)
+    }
+
+    // DATACOPY operation
+    pub fn op_datacopy(
+        &mut self,
+        current_call_frame: &mut CallFrame,
+    ) -> Result<OpcodeSuccess, VMError> {
+        let dest_offset: usize = current_call_frame
+            .stack
+            .pop()?
+            .try_into()
+            .map_err(|_| VMError::VeryLargeNumber)?;
+        let offset = usize::try_from(current_call_frame.stack.pop()?).unwrap_or(usize::MAX);
+        let size: usize = current_call_frame
+            .stack
+            .pop()?
+            .try_into()
+            .map_err(|_| VMError::VeryLargeNumber)?;
+
+        let gas_cost =
+            gas_cost::datacopy(current_call_frame, size, dest_offset).map_err(VMError::OutOfGas)?;
+
+        self.increase_consumed_gas(current_call_frame, gas_cost)?;
+
+        if size == 0 {
+            return Ok(OpcodeSuccess::Continue);
+        }
+
+        // Bytes past the end of the data are copied as zeros
+        let mut data = vec![0u8; size];
+        let container_data = current_call_frame.eof()?.data();
+        for (i, byte) in container_data.iter().skip(offset).take(size).enumerate() {
+            *data
+                .get_mut(i)
+                .ok_or(VMError::Internal(InternalError::SlicingError))? = *byte;
+        }
+        current_call_frame.memory.store_bytes(dest_offset, &data)?;
+
+        Ok(OpcodeSuccess::Continue)
+    }
+}
+
+/// Word of `data` starting at `offset`, with the bytes past the end of the data set to 0
+pub fn load_word(data: &[u8], offset: usize) -> U256 {
+    let mut word = [0u8; 32];
+    for (word_byte, byte) in word.iter_mut().zip(data.iter().skip(offset)) {
+        *word_byte = *byte;
+    }
+    U256::from_big_endian(&word)
+}
@@ -6,8 +6,8 @@ use crate::{
     vm::VM,
 };
 
-// Duplication Operation (16)
-// Opcodes: DUP1 ... DUP16
+// Duplication Operation (17)
+// Opcodes: DUP1 ... DUP16, DUPN
 
 impl VM {
     // DUP operation
@@ -45,4 +45,26 @@ impl VM {
 
         Ok(OpcodeSuccess::Continue)
     }
+
+    // DUPN operation, which only exists in EOF code
+    pub fn op_dupn(
+        &mut self,
+        current_call_frame: &mut CallFrame,
+    ) -> Result<OpcodeSuccess, VMError> {
+        self.increase_consumed_gas(current_call_frame, gas_cost::DUPN)?;
+
+        let depth = usize::from(current_call_frame.immediate_u8(0)?).saturating_add(1);
+        current_call_frame.increment_pc()?;
+
+        let value_at_depth = *current_call_frame.stack.get(
+            current_call_frame
+                .stack
+                .len()
+                .checked_sub(depth)
+                .ok_or(VMError::StackUnderflow)?,
+        )?;
+        current_call_frame.stack.push(value_at_depth)?;
+
+        Ok(OpcodeSuccess::Continue)
+    }
 }
@@ -1,15 +1,18 @@
 use crate::{
     call_frame::CallFrame,
     constants::{WORD_SIZE, WORD_SIZE_IN_BYTES_USIZE},
+    eof::{is_eof, EOF_MAGIC, EOF_MAGIC_HASH},
     errors::{InternalError, OpcodeSuccess, OutOfGasError, VMError},
     gas_cost,
+    opcode_handlers::data::load_word,
     vm::{word_to_address, VM},
 };
+use bytes::Bytes;
 use ethrex_core::U256;
-use keccak_hash::keccak;
 
-// Environmental Information (16)
-// Opcodes: ADDRESS, BALANCE, ORIGIN, CALLER, CALLVALUE, CALLDATALOAD, CALLDATASIZE, CALLDATACOPY, CODESIZE, CODECOPY, GASPRICE, EXTCODESIZE, EXTCODECOPY, RETURNDATASIZE, RETURNDATACOPY, EXTCODEHASH
+// Environmental Information (17)
+// Opcodes: ADDRESS, BALANCE, ORIGIN, CALLER, CALLVALUE, CALLDATALOAD, CALLDATASIZE, CALLDATACOPY, CODESIZE, CODECOPY, GASPRICE, EXTCODESIZE, EXTCODECOPY, RETURNDATASIZE, RETURNDATACOPY, EXTCODEHASH, RETURNDATALOAD
+// Legacy code sees the code of EOF accounts as the EOF magic (EIP-3540)
 
 impl VM {
     // ADDRESS operation
@@ -33,7 +36,7 @@ impl VM {
     ) -> Result<OpcodeSuccess, VMError> {
         let address = word_to_address(current_call_frame.stack.pop()?);
 
-        let (account_info, address_was_cold) = self.access_account(address);
+        let (account_info, address_was_cold) = self.access_account(address)?;
 
         self.increase_consumed_gas(current_call_frame, gas_cost::balance(address_was_cold)?)?;
 
@@ -198,7 +201,7 @@ impl VM {
 
         current_call_frame
             .stack
-            .push(U256::from(current_call_frame.bytecode.len()))?;
+            .push(U256::from(current_call_frame.code.len()))?;
 
         self.increase_consumed_gas(current_call_frame, gas_cost::CODESIZE)?;
 
@@ -255,6 +258,7 @@ impl VM {
             current_call_frame.memory.data.resize(new_memory_size, 0);
         }
 
+        let code = current_call_frame.code.clone();
         for i in 0..size {
             if let Some(memory_byte) =
                 current_call_frame
@@ -264,8 +268,8 @@ impl VM {
                         InternalError::ArithmeticOperationOverflow,
                     ))?)
             {
-                *memory_byte = *current_call_frame
-                    .bytecode
+                *memory_byte = *code
+                    .bytecode()
                     .get(code_offset.checked_add(i).ok_or(VMError::Internal(
                         InternalError::ArithmeticOperationOverflow,
                     ))?)
@@ -295,13 +299,16 @@ impl VM {
     ) -> Result<OpcodeSuccess, VMError> {
         let address = word_to_address(current_call_frame.stack.pop()?);
 
-        let (account_info, address_was_cold) = self.access_account(address);
+        let (account_info, address_was_cold) = self.access_account(address)?;
 
         self.increase_consumed_gas(current_call_frame, gas_cost::extcodesize(address_was_cold)?)?;
 
-        current_call_frame
-            .stack
-            .push(account_info.bytecode.len().into())?;
+        let code_size = if self.is_eof_enabled() && is_eof(&account_info.bytecode) {
+            EOF_MAGIC.len()
+        } else {
+            account_info.bytecode.len()
+        };
+        current_call_frame.stack.push(code_size.into())?;
 
         Ok(OpcodeSuccess::Continue)
     }
@@ -328,7 +335,7 @@ impl VM {
             .try_into()
             .map_err(|_| VMError::VeryLargeNumber)?;
 
-        let (account_info, address_was_cold) = self.access_account(address);
+        let (account_info, address_was_cold) = self.access_account(address)?;
 
         let new_memory_size = dest_offset
             .checked_add(size)
@@ -354,6 +361,12 @@ impl VM {
             return Ok(OpcodeSuccess::Continue);
         }
 
+        let bytecode = if self.is_eof_enabled() && is_eof(&account_info.bytecode) {
+            Bytes::from_static(&EOF_MAGIC)
+        } else {
+            account_info.bytecode
+        };
+
         if current_memory_size < new_memory_size {
             current_call_frame
                 .memory
@@ -375,8 +388,7 @@ impl VM {
                         InternalError::ArithmeticOperationOverflow,
                     ))?)
             {
-                *memory_byte = *account_info
-                    .bytecode
+                *memory_byte = *bytecode
                     .get(offset.checked_add(i).ok_or(VMError::Internal(
                         InternalError::ArithmeticOperationOverflow,
                     ))?)
@@ -458,13 +470,33 @@ impl VM {
     ) -> Result<OpcodeSuccess, VMError> {
         let address = word_to_address(current_call_frame.stack.pop()?);
 
-        let (account_info, address_was_cold) = self.access_account(address);
+        let (account_info, address_was_cold) = self.access_account(address)?;
 
         self.increase_consumed_gas(current_call_frame, gas_cost::extcodehash(address_was_cold)?)?;
 
-        current_call_frame.stack.push(U256::from_big_endian(
-            keccak(account_info.bytecode).as_fixed_bytes(),
-        ))?;
+        let code_hash = if self.is_eof_enabled() && is_eof(&account_info.bytecode) {
+            EOF_MAGIC_HASH
+        } else {
+            account_info.code_hash
+        };
+        current_call_frame
+            .stack
+            .push(U256::from_big_endian(code_hash.as_bytes()))?;
+
+        Ok(OpcodeSuccess::Continue)
+    }
+
+    // RETURNDATALOAD operation, which only exists in EOF code
+    pub fn op_returndataload(
+        &mut self,
+        current_call_frame: &mut CallFrame,
+    ) -> Result<OpcodeSuccess, VMError> {
+        self.increase_consumed_gas(current_call_frame, gas_cost::RETURNDATALOAD)?;
+
+        // Unlike RETURNDATACOPY, reading past the end of the return data gives zeros
+        let offset = usize::try_from(current_call_frame.stack.pop()?).unwrap_or(usize::MAX);
+        let result = load_word(&current_call_frame.sub_return_data, offset);
+        current_call_frame.stack.push(result)?;
 
         Ok(OpcodeSuccess::Continue)
     }
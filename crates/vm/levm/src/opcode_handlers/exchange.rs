@@ -1,13 +1,14 @@
 use crate::{
     call_frame::CallFrame,
+    eof::exchange_positions,
     errors::{OpcodeSuccess, VMError},
     gas_cost,
     opcodes::Opcode,
     vm::VM,
 };
 
-// Exchange Operations (16)
-// Opcodes: SWAP1 ... SWAP16
+// Exchange Operations (18)
+// Opcodes: SWAP1 ... SWAP16, SWAPN, EXCHANGE
 
 impl VM {
     // SWAP operation
@@ -41,4 +42,56 @@ impl VM {
 
         Ok(OpcodeSuccess::Continue)
     }
+
+    // SWAPN operation, which only exists in EOF code
+    pub fn op_swapn(
+        &mut self,
+        current_call_frame: &mut CallFrame,
+    ) -> Result<OpcodeSuccess, VMError> {
+        self.increase_consumed_gas(current_call_frame, gas_cost::SWAPN)?;
+
+        let depth = usize::from(current_call_frame.immediate_u8(0)?).saturating_add(1);
+        current_call_frame.increment_pc()?;
+
+        let stack_top_index = current_call_frame
+            .stack
+            .len()
+            .checked_sub(1)
+            .ok_or(VMError::StackUnderflow)?;
+        let to_swap_index = stack_top_index
+            .checked_sub(depth)
+            .ok_or(VMError::StackUnderflow)?;
+        current_call_frame
+            .stack
+            .swap(stack_top_index, to_swap_index)?;
+
+        Ok(OpcodeSuccess::Continue)
+    }
+
+    // EXCHANGE operation, which only exists in EOF code. Swaps the items `n` and `n + m` deep
+    // below the top, both encoded in its immediate.
+    pub fn op_exchange(
+        &mut self,
+        current_call_frame: &mut CallFrame,
+    ) -> Result<OpcodeSuccess, VMError> {
+        self.increase_consumed_gas(current_call_frame, gas_cost::EXCHANGE)?;
+
+        let (n, m) = exchange_positions(current_call_frame.immediate_u8(0)?);
+        current_call_frame.increment_pc()?;
+
+        let stack_top_index = current_call_frame
+            .stack
+            .len()
+            .checked_sub(1)
+            .ok_or(VMError::StackUnderflow)?;
+        let first_index = stack_top_index
+            .checked_sub(usize::from(n))
+            .ok_or(VMError::StackUnderflow)?;
+        let second_index = first_index
+            .checked_sub(usize::from(m))
+            .ok_or(VMError::StackUnderflow)?;
+        current_call_frame.stack.swap(first_index, second_index)?;
+
+        Ok(OpcodeSuccess::Continue)
+    }
 }
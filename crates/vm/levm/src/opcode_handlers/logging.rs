@@ -55,7 +55,7 @@ impl VM {
             topics,
             data: Bytes::from(data),
         };
-        current_call_frame.logs.push(log);
+        self.add_log(log);
 
         Ok(OpcodeSuccess::Continue)
     }
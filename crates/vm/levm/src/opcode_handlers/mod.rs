@@ -1,6 +1,8 @@
 pub mod arithmetic;
 pub mod bitwise_comparison;
 pub mod block;
+pub mod control_flow;
+pub mod data;
 pub mod dup;
 pub mod environment;
 pub mod exchange;
@@ -1,6 +1,5 @@
 use crate::{
     call_frame::CallFrame,
-    constants::WORD_SIZE,
     errors::{InternalError, OpcodeSuccess, VMError},
     gas_cost,
     opcodes::Opcode,
@@ -26,33 +25,14 @@ impl VM {
             .checked_add(1)
             .ok_or(VMError::InvalidOpcode)?;
 
-        let read_n_bytes: Vec<u8> = current_call_frame
-            .bytecode
-            .get(current_call_frame.pc()..)
-            .unwrap_or_default()
-            .iter()
-            .take(n_bytes)
-            .cloned()
-            .collect();
-
-        let mut value_to_push = [0u8; WORD_SIZE];
-
-        let start_index = WORD_SIZE.checked_sub(n_bytes).ok_or(VMError::Internal(
-            InternalError::ArithmeticOperationUnderflow,
-        ))?;
-
-        // Rellenamos el array `value_to_push` con los bytes leídos
-        for (i, byte) in read_n_bytes.iter().enumerate() {
-            let index = start_index.checked_add(i).ok_or(VMError::Internal(
-                InternalError::ArithmeticOperationOverflow,
-            ))?;
-            if let Some(data_byte) = value_to_push.get_mut(index) {
-                *data_byte = *byte;
-            }
-        }
-
-        let bytes_push: &[u8] = &value_to_push;
-        current_call_frame.stack.push(U256::from(bytes_push))?;
+        // The code is padded, so data cut short by the end of the code is read as zeros
+        let push_data = current_call_frame
+            .code
+            .push_data(current_call_frame.pc(), n_bytes)
+            .ok_or(VMError::Internal(InternalError::PCOverflowed))?;
+        current_call_frame
+            .stack
+            .push(U256::from_big_endian(push_data))?;
 
         current_call_frame.increment_pc_by(n_bytes)?;
 
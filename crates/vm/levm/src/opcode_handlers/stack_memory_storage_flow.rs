@@ -26,11 +26,7 @@ impl VM {
         self.increase_consumed_gas(current_call_frame, gas_cost::TLOAD)?;
 
         let key = current_call_frame.stack.pop()?;
-        let value = current_call_frame
-            .transient_storage
-            .get(&(current_call_frame.msg_sender, key))
-            .cloned()
-            .unwrap_or(U256::zero());
+        let value = self.get_transient_storage(current_call_frame.to, key);
 
         current_call_frame.stack.push(value)?;
         Ok(OpcodeSuccess::Continue)
@@ -41,13 +37,15 @@ impl VM {
         &mut self,
         current_call_frame: &mut CallFrame,
     ) -> Result<OpcodeSuccess, VMError> {
+        if current_call_frame.is_static {
+            return Err(VMError::OpcodeNotAllowedInStaticContext);
+        }
+
         self.increase_consumed_gas(current_call_frame, gas_cost::TSTORE)?;
 
         let key = current_call_frame.stack.pop()?;
         let value = current_call_frame.stack.pop()?;
-        current_call_frame
-            .transient_storage
-            .insert((current_call_frame.msg_sender, key), value);
+        self.update_transient_storage(current_call_frame.to, key, value);
 
         Ok(OpcodeSuccess::Continue)
     }
@@ -139,7 +137,7 @@ impl VM {
         let storage_slot_key = H256::from(bytes);
 
         let (storage_slot, storage_slot_was_cold) =
-            self.access_storage_slot(address, storage_slot_key);
+            self.access_storage_slot(address, storage_slot_key)?;
 
         self.increase_consumed_gas(current_call_frame, gas_cost::sload(storage_slot_was_cold)?)?;
 
@@ -166,51 +164,52 @@ impl VM {
         let key = H256::from(bytes);
 
         let (storage_slot, storage_slot_was_cold) =
-            self.access_storage_slot(current_call_frame.to, key);
+            self.access_storage_slot(current_call_frame.to, key)?;
 
         self.increase_consumed_gas(
             current_call_frame,
             gas_cost::sstore(&storage_slot, new_storage_slot_value, storage_slot_was_cold)?,
         )?;
 
-        // Gas Refunds
-        // TODO: Think about what to do in case of underflow of gas refunds (when we try to substract from it if the value is low)
-        let mut gas_refunds = U256::zero();
+        // Gas refunds (EIP-2200 with the values of EIP-2929 and EIP-3529). The counter can be
+        // decreased, but never below zero, as the removed refund was previously added
         if new_storage_slot_value != storage_slot.current_value {
+            let mut refunds = self.accrued_substate.refunded_gas;
             if storage_slot.current_value == storage_slot.original_value {
-                if storage_slot.original_value.is_zero() && new_storage_slot_value.is_zero() {
-                    gas_refunds = gas_refunds
-                        .checked_add(U256::from(4800))
+                if !storage_slot.original_value.is_zero() && new_storage_slot_value.is_zero() {
+                    refunds = refunds
+                        .checked_add(gas_cost::SSTORE_CLEARS_SCHEDULE)
                         .ok_or(VMError::GasRefundsOverflow)?;
                 }
-            } else if !storage_slot.original_value.is_zero() {
-                if storage_slot.current_value.is_zero() {
-                    gas_refunds = gas_refunds
-                        .checked_sub(U256::from(4800))
-                        .ok_or(VMError::GasRefundsUnderflow)?;
-                } else if new_storage_slot_value.is_zero() {
-                    gas_refunds = gas_refunds
-                        .checked_add(U256::from(4800))
-                        .ok_or(VMError::GasRefundsOverflow)?;
+            } else {
+                if !storage_slot.original_value.is_zero() {
+                    if storage_slot.current_value.is_zero() {
+                        refunds = refunds
+                            .checked_sub(gas_cost::SSTORE_CLEARS_SCHEDULE)
+                            .ok_or(VMError::GasRefundsUnderflow)?;
+                    } else if new_storage_slot_value.is_zero() {
+                        refunds = refunds
+                            .checked_add(gas_cost::SSTORE_CLEARS_SCHEDULE)
+                            .ok_or(VMError::GasRefundsOverflow)?;
+                    }
                 }
-            } else if new_storage_slot_value == storage_slot.original_value {
-                if storage_slot.original_value.is_zero() {
-                    gas_refunds = gas_refunds
-                        .checked_add(U256::from(19900))
-                        .ok_or(VMError::GasRefundsOverflow)?;
-                } else {
-                    gas_refunds = gas_refunds
-                        .checked_add(U256::from(2800))
+                if new_storage_slot_value == storage_slot.original_value {
+                    let restored_refund = if storage_slot.original_value.is_zero() {
+                        gas_cost::SSTORE_STORAGE_CREATION
+                    } else {
+                        gas_cost::SSTORE_STORAGE_MODIFICATION
+                    }
+                    .checked_sub(gas_cost::SLOAD_WARM_DYNAMIC)
+                    .ok_or(VMError::Internal(
+                        InternalError::ArithmeticOperationUnderflow,
+                    ))?;
+                    refunds = refunds
+                        .checked_add(restored_refund)
                         .ok_or(VMError::GasRefundsOverflow)?;
                 }
             }
-        };
-
-        self.env.refunded_gas = self
-            .env
-            .refunded_gas
-            .checked_add(gas_refunds)
-            .ok_or(VMError::GasLimitPriceProductOverflow)?;
+            self.update_refunded_gas(refunds);
+        }
 
         self.update_account_storage(current_call_frame.to, key, new_storage_slot_value)?;
 
@@ -1,14 +1,15 @@
 use crate::{
     call_frame::CallFrame,
     constants::WORD_SIZE_IN_BYTES_USIZE,
+    eof::EofContainer,
     errors::{InternalError, OpcodeSuccess, ResultReason, VMError},
     gas_cost,
     vm::{word_to_address, VM},
 };
-use ethrex_core::{types::TxKind, Address, U256};
+use ethrex_core::{Address, U256};
 
-// System Operations (10)
-// Opcodes: CREATE, CALL, CALLCODE, RETURN, DELEGATECALL, CREATE2, STATICCALL, REVERT, INVALID, SELFDESTRUCT
+// System Operations (15)
+// Opcodes: CREATE, CALL, CALLCODE, RETURN, DELEGATECALL, CREATE2, STATICCALL, REVERT, INVALID, SELFDESTRUCT, EOFCREATE, RETURNCONTRACT, EXTCALL, EXTDELEGATECALL, EXTSTATICCALL
 
 impl VM {
     // CALL operation
@@ -62,7 +63,7 @@ impl VM {
         let new_memory_size = new_memory_size_for_args.max(new_memory_size_for_return_data);
         let current_memory_size = current_call_frame.memory.data.len();
 
-        let (account_info, address_was_cold) = self.access_account(callee);
+        let (account_info, address_was_cold) = self.access_account(callee)?;
 
         self.increase_consumed_gas(
             current_call_frame,
@@ -86,7 +87,7 @@ impl VM {
             msg_sender,
             to,
             callee,
-            false,
+            true,
             is_static,
             args_start_offset,
             args_size,
@@ -142,7 +143,7 @@ impl VM {
         let new_memory_size = new_memory_size_for_args.max(new_memory_size_for_return_data);
         let current_memory_size = current_call_frame.memory.data.len();
 
-        let (_account_info, address_was_cold) = self.access_account(code_address);
+        let (_account_info, address_was_cold) = self.access_account(code_address)?;
 
         self.increase_consumed_gas(
             current_call_frame,
@@ -166,7 +167,7 @@ impl VM {
             msg_sender,
             to,
             code_address,
-            false,
+            true,
             is_static,
             args_start_offset,
             args_size,
@@ -235,7 +236,7 @@ impl VM {
         let to = current_call_frame.to;
         let is_static = current_call_frame.is_static;
 
-        let (_account_info, address_was_cold) = self.access_account(code_address);
+        let (_account_info, address_was_cold) = self.access_account(code_address)?;
 
         let new_memory_size_for_args = (args_start_offset
             .checked_add(args_size)
@@ -308,7 +309,7 @@ impl VM {
             .try_into()
             .map_err(|_err| VMError::VeryLargeNumber)?;
 
-        let (_account_info, address_was_cold) = self.access_account(code_address);
+        let (_account_info, address_was_cold) = self.access_account(code_address)?;
 
         let new_memory_size_for_args = (args_start_offset
             .checked_add(args_size)
@@ -489,7 +490,7 @@ impl VM {
 
         let target_address = word_to_address(current_call_frame.stack.pop()?);
 
-        let (target_account_info, target_account_is_cold) = self.access_account(target_address);
+        let (target_account_info, target_account_is_cold) = self.access_account(target_address)?;
 
         self.increase_consumed_gas(
             current_call_frame,
@@ -498,17 +499,263 @@ impl VM {
         )?;
 
         let (current_account_info, _current_account_is_cold) =
-            self.access_account(current_call_frame.to);
+            self.access_account(current_call_frame.to)?;
 
         self.increase_account_balance(target_address, current_account_info.balance)?;
         self.decrease_account_balance(current_call_frame.to, current_account_info.balance)?;
-
-        if self.tx_kind == TxKind::Create {
-            self.accrued_substate
-                .selfdestrutct_set
-                .insert(current_call_frame.to);
+        self.trace_transfer(
+            current_call_frame.to,
+            target_address,
+            current_account_info.balance,
+        );
+
+        // Since EIP-6780 only the accounts created in the same transaction are destroyed
+        if self
+            .accrued_substate
+            .created_accounts
+            .contains(&current_call_frame.to)
+        {
+            self.add_to_selfdestruct_set(current_call_frame.to);
         }
 
         Ok(OpcodeSuccess::Result(ResultReason::SelfDestruct))
     }
+
+    // EOFCREATE operation
+    pub fn op_eofcreate(
+        &mut self,
+        current_call_frame: &mut CallFrame,
+    ) -> Result<OpcodeSuccess, VMError> {
+        if current_call_frame.is_static {
+            return Err(VMError::OpcodeNotAllowedInStaticContext);
+        }
+
+        let container_index = usize::from(current_call_frame.immediate_u8(0)?);
+        current_call_frame.increment_pc()?;
+
+        let value = current_call_frame.stack.pop()?;
+        let salt = current_call_frame.stack.pop()?;
+        let (input_offset, input_size) = pop_memory_range(current_call_frame)?;
+
+        let initcontainer = current_call_frame
+            .eof()?
+            .container(container_index)
+            .ok_or(VMError::InvalidOpcode)?;
+
+        self.increase_consumed_gas(
+            current_call_frame,
+            gas_cost::eofcreate(
+                current_call_frame,
+                input_offset,
+                input_size,
+                initcontainer.len(),
+            )?,
+        )?;
+
+        let calldata = current_call_frame
+            .memory
+            .load_range(input_offset, input_size)?
+            .into();
+
+        self.eof_create(current_call_frame, initcontainer, value, salt, calldata)
+    }
+
+    // RETURNCONTRACT operation
+    // Returns one of the subcontainers of the initcode as the code to deploy, with the given
+    // memory appended to its data section
+    pub fn op_returncontract(
+        &mut self,
+        current_call_frame: &mut CallFrame,
+    ) -> Result<OpcodeSuccess, VMError> {
+        if !current_call_frame.is_eof_initcode {
+            return Err(VMError::InvalidOpcode);
+        }
+
+        let container_index = usize::from(current_call_frame.immediate_u8(0)?);
+        current_call_frame.increment_pc()?;
+
+        let (aux_data_offset, aux_data_size) = pop_memory_range(current_call_frame)?;
+
+        let gas_cost = current_call_frame
+            .memory
+            .expansion_cost(aux_data_offset, aux_data_size)?;
+        self.increase_consumed_gas(current_call_frame, gas_cost)?;
+
+        let aux_data = current_call_frame
+            .memory
+            .load_range(aux_data_offset, aux_data_size)?;
+        let container = EofContainer::decode(
+            current_call_frame
+                .eof()?
+                .container(container_index)
+                .ok_or(VMError::InvalidOpcode)?,
+        )?;
+
+        // The data section must end up at least as big as the container declares
+        let new_data_size = container.data().len().saturating_add(aux_data.len());
+        let new_data_size = u16::try_from(new_data_size).map_err(|_| VMError::AuxDataTooLarge)?;
+        if new_data_size < container.declared_data_size() {
+            return Err(VMError::AuxDataTooSmall);
+        }
+
+        let mut deployed_code = [container.raw().as_ref(), &aux_data].concat();
+        let data_size_index = container.data_size_index();
+        deployed_code
+            .get_mut(data_size_index..data_size_index.saturating_add(2))
+            .ok_or(VMError::Internal(InternalError::SlicingError))?
+            .copy_from_slice(&new_data_size.to_be_bytes());
+        current_call_frame.returndata = deployed_code.into();
+
+        Ok(OpcodeSuccess::Result(ResultReason::Return))
+    }
+
+    // EXTCALL operation
+    pub fn op_extcall(
+        &mut self,
+        current_call_frame: &mut CallFrame,
+    ) -> Result<OpcodeSuccess, VMError> {
+        let callee = pop_ext_call_target(current_call_frame)?;
+        let (input_offset, input_size) = pop_memory_range(current_call_frame)?;
+        let value = current_call_frame.stack.pop()?;
+
+        if current_call_frame.is_static && !value.is_zero() {
+            return Err(VMError::OpcodeNotAllowedInStaticContext);
+        }
+
+        let (account_info, address_was_cold) = self.access_account(callee)?;
+        self.increase_consumed_gas(
+            current_call_frame,
+            gas_cost::ext_call(
+                current_call_frame,
+                input_offset,
+                input_size,
+                address_was_cold,
+                account_info.is_empty(),
+                value,
+            )?,
+        )?;
+
+        let calldata = current_call_frame
+            .memory
+            .load_range(input_offset, input_size)?
+            .into();
+        let msg_sender = current_call_frame.to;
+        let is_static = current_call_frame.is_static;
+
+        self.generic_ext_call(
+            current_call_frame,
+            value,
+            msg_sender,
+            callee,
+            callee,
+            true,
+            is_static,
+            calldata,
+        )
+    }
+
+    // EXTDELEGATECALL operation
+    pub fn op_extdelegatecall(
+        &mut self,
+        current_call_frame: &mut CallFrame,
+    ) -> Result<OpcodeSuccess, VMError> {
+        let code_address = pop_ext_call_target(current_call_frame)?;
+        let (input_offset, input_size) = pop_memory_range(current_call_frame)?;
+
+        let (account_info, address_was_cold) = self.access_account(code_address)?;
+        self.increase_consumed_gas(
+            current_call_frame,
+            gas_cost::ext_call(
+                current_call_frame,
+                input_offset,
+                input_size,
+                address_was_cold,
+                account_info.is_empty(),
+                U256::zero(),
+            )?,
+        )?;
+
+        let calldata = current_call_frame
+            .memory
+            .load_range(input_offset, input_size)?
+            .into();
+        let msg_sender = current_call_frame.msg_sender;
+        let value = current_call_frame.msg_value;
+        let to = current_call_frame.to;
+        let is_static = current_call_frame.is_static;
+
+        self.generic_ext_call(
+            current_call_frame,
+            value,
+            msg_sender,
+            to,
+            code_address,
+            false,
+            is_static,
+            calldata,
+        )
+    }
+
+    // EXTSTATICCALL operation
+    pub fn op_extstaticcall(
+        &mut self,
+        current_call_frame: &mut CallFrame,
+    ) -> Result<OpcodeSuccess, VMError> {
+        let callee = pop_ext_call_target(current_call_frame)?;
+        let (input_offset, input_size) = pop_memory_range(current_call_frame)?;
+
+        let (account_info, address_was_cold) = self.access_account(callee)?;
+        self.increase_consumed_gas(
+            current_call_frame,
+            gas_cost::ext_call(
+                current_call_frame,
+                input_offset,
+                input_size,
+                address_was_cold,
+                account_info.is_empty(),
+                U256::zero(),
+            )?,
+        )?;
+
+        let calldata = current_call_frame
+            .memory
+            .load_range(input_offset, input_size)?
+            .into();
+        let msg_sender = current_call_frame.to;
+
+        self.generic_ext_call(
+            current_call_frame,
+            U256::zero(),
+            msg_sender,
+            callee,
+            callee,
+            false,
+            true,
+            calldata,
+        )
+    }
+}
+
+/// Pops the target of an EXT*CALL, which must be an address and not any word (EIP-7069)
+fn pop_ext_call_target(current_call_frame: &mut CallFrame) -> Result<Address, VMError> {
+    let target = current_call_frame.stack.pop()?;
+    if target.bits() > 160 {
+        return Err(VMError::InvalidCallTarget);
+    }
+    Ok(word_to_address(target))
+}
+
+/// Pops the offset and size of a memory range, the offset being ignored if the range is empty
+fn pop_memory_range(current_call_frame: &mut CallFrame) -> Result<(usize, usize), VMError> {
+    let offset = current_call_frame.stack.pop()?;
+    let size: usize = current_call_frame
+        .stack
+        .pop()?
+        .try_into()
+        .map_err(|_| VMError::VeryLargeNumber)?;
+    if size == 0 {
+        return Ok((0, 0));
+    }
+    let offset = offset.try_into().map_err(|_| VMError::VeryLargeNumber)?;
+    Ok((offset, size))
 }
@@ -159,14 +159,38 @@ pub enum Opcode {
     LOG2 = 0xA2,
     LOG3 = 0xA3,
     LOG4 = 0xA4,
+
+    // Data Section Operations (EOF only)
+    DATALOAD = 0xD0,
+    DATALOADN = 0xD1,
+    DATASIZE = 0xD2,
+    DATACOPY = 0xD3,
+
+    // Static Flow and Stack Operations (EOF only)
+    RJUMP = 0xE0,
+    RJUMPI = 0xE1,
+    RJUMPV = 0xE2,
+    CALLF = 0xE3,
+    RETF = 0xE4,
+    JUMPF = 0xE5,
+    DUPN = 0xE6,
+    SWAPN = 0xE7,
+    EXCHANGE = 0xE8,
+
     // // System Operations
+    EOFCREATE = 0xEC,
+    RETURNCONTRACT = 0xEE,
     CREATE = 0xF0,
     CALL = 0xF1,
     CALLCODE = 0xF2,
     RETURN = 0xF3,
     DELEGATECALL = 0xF4,
     CREATE2 = 0xF5,
+    RETURNDATALOAD = 0xF7,
+    EXTCALL = 0xF8,
+    EXTDELEGATECALL = 0xF9,
     STATICCALL = 0xFA,
+    EXTSTATICCALL = 0xFB,
     REVERT = 0xFD,
     INVALID = 0xFE,
     SELFDESTRUCT = 0xFF,
@@ -174,6 +198,34 @@ pub enum Opcode {
 
 impl Copy for Opcode {}
 
+impl Opcode {
+    /// Whether the opcode only exists in EOF code (EIP-7692), being invalid in legacy code
+    pub fn is_eof_only(&self) -> bool {
+        matches!(
+            self,
+            Opcode::DATALOAD
+                | Opcode::DATALOADN
+                | Opcode::DATASIZE
+                | Opcode::DATACOPY
+                | Opcode::RJUMP
+                | Opcode::RJUMPI
+                | Opcode::RJUMPV
+                | Opcode::CALLF
+                | Opcode::RETF
+                | Opcode::JUMPF
+                | Opcode::DUPN
+                | Opcode::SWAPN
+                | Opcode::EXCHANGE
+                | Opcode::EOFCREATE
+                | Opcode::RETURNCONTRACT
+                | Opcode::RETURNDATALOAD
+                | Opcode::EXTCALL
+                | Opcode::EXTDELEGATECALL
+                | Opcode::EXTSTATICCALL
+        )
+    }
+}
+
 impl TryFrom<u8> for Opcode {
     type Error = VMError;
 
@@ -318,13 +370,32 @@ impl TryFrom<u8> for Opcode {
             0x5E => Opcode::MCOPY,
             0x5C => Opcode::TLOAD,
             0x5D => Opcode::TSTORE,
+            0xD0 => Opcode::DATALOAD,
+            0xD1 => Opcode::DATALOADN,
+            0xD2 => Opcode::DATASIZE,
+            0xD3 => Opcode::DATACOPY,
+            0xE0 => Opcode::RJUMP,
+            0xE1 => Opcode::RJUMPI,
+            0xE2 => Opcode::RJUMPV,
+            0xE3 => Opcode::CALLF,
+            0xE4 => Opcode::RETF,
+            0xE5 => Opcode::JUMPF,
+            0xE6 => Opcode::DUPN,
+            0xE7 => Opcode::SWAPN,
+            0xE8 => Opcode::EXCHANGE,
+            0xEC => Opcode::EOFCREATE,
+            0xEE => Opcode::RETURNCONTRACT,
             0xF0 => Opcode::CREATE,
             0xF1 => Opcode::CALL,
             0xF2 => Opcode::CALLCODE,
             0xF3 => Opcode::RETURN,
             0xF5 => Opcode::CREATE2,
             0xF4 => Opcode::DELEGATECALL,
+            0xF7 => Opcode::RETURNDATALOAD,
+            0xF8 => Opcode::EXTCALL,
+            0xF9 => Opcode::EXTDELEGATECALL,
             0xFA => Opcode::STATICCALL,
+            0xFB => Opcode::EXTSTATICCALL,
             0xFD => Opcode::REVERT,
             0xFE => Opcode::INVALID,
             0xFF => Opcode::SELFDESTRUCT,
@@ -66,11 +66,7 @@ pub fn new_vm_with_ops_addr_bal_db(
         (
             Address::from_low_u64_be(42),
             Account {
-                info: AccountInfo {
-                    nonce: 0,
-                    balance: U256::MAX,
-                    bytecode: contract_bytecode,
-                },
+                info: AccountInfo::new(U256::MAX, contract_bytecode, 0),
                 storage: HashMap::new(),
             },
         ),
@@ -78,11 +74,7 @@ pub fn new_vm_with_ops_addr_bal_db(
             // This is the sender account
             sender_address,
             Account {
-                info: AccountInfo {
-                    nonce: 0,
-                    balance: sender_balance,
-                    bytecode: Bytes::default(),
-                },
+                info: AccountInfo::new(sender_balance, Bytes::default(), 0),
                 storage: HashMap::new(),
             },
         ),
@@ -1,19 +1,25 @@
 use crate::{
-    account::{Account, StorageSlot},
+    account::{code_hash, Account, StorageSlot},
     call_frame::CallFrame,
+    code::{AnalyzedCode, CodeCache},
     constants::*,
     db::{cache, CacheDB, Database},
     environment::Environment,
+    eof::{is_eof, ContainerKind, EofContainer},
     errors::{
         InternalError, OpcodeSuccess, OutOfGasError, ResultReason, TransactionReport, TxResult,
         TxValidationError, VMError,
     },
     gas_cost::{self},
+    journal::{Checkpoint, Journal, JournalEntry},
     opcodes::Opcode,
     AccountInfo,
 };
 use bytes::Bytes;
-use ethrex_core::{types::TxKind, Address, H256, U256};
+use ethrex_core::{
+    types::{Fork, Log, TxKind},
+    Address, H256, U256,
+};
 use ethrex_rlp;
 use ethrex_rlp::encode::RLPEncode;
 use keccak_hash::keccak;
@@ -24,14 +30,42 @@ use std::{
 };
 
 pub type Storage = HashMap<U256, H256>;
+/// Transient storage of the accounts (EIP-1153), discarded at the end of the transaction
+pub type TransientStorage = HashMap<(Address, U256), U256>;
 
+/// Accrued transaction substate, acted upon once the transaction finishes executing.
+///
+/// Its changes are recorded in the journal, so warm accounts and slots, refunds, logs and
+/// self-destructs of a reverted call frame are discarded.
 #[derive(Debug, Clone, Default)]
-// TODO: https://github.com/lambdaclass/ethrex/issues/604
 pub struct Substate {
-    // accessed addresses and storage keys are considered WARM
-    // pub accessed_addresses: HashSet<Address>,
-    // pub accessed_storage_keys: HashSet<(Address, U256)>,
-    pub selfdestrutct_set: HashSet<Address>,
+    /// Accounts that will be destroyed at the end of the transaction
+    pub selfdestruct_set: HashSet<Address>,
+    /// Accounts created during the transaction, the only ones SELFDESTRUCT can destroy
+    /// (EIP-6780)
+    pub created_accounts: HashSet<Address>,
+    /// Accessed accounts and storage slots, which are WARM for the rest of the transaction
+    /// (EIP-2929)
+    pub accessed_addresses: HashSet<Address>,
+    pub accessed_storage_keys: HashMap<Address, HashSet<H256>>,
+    /// Gas refund counter, applied to the gas used when the transaction ends
+    pub refunded_gas: U256,
+    pub logs: Vec<Log>,
+}
+
+impl Substate {
+    /// Marks an account as accessed, returning whether it was cold
+    pub fn add_accessed_address(&mut self, address: Address) -> bool {
+        self.accessed_addresses.insert(address)
+    }
+
+    /// Marks a storage slot as accessed, returning whether it was cold
+    pub fn add_accessed_storage_key(&mut self, address: Address, key: H256) -> bool {
+        self.accessed_storage_keys
+            .entry(address)
+            .or_default()
+            .insert(key)
+    }
 }
 
 pub struct VM {
@@ -45,9 +79,9 @@ pub struct VM {
     pub db: Arc<dyn Database>,
     pub cache: CacheDB,
     pub tx_kind: TxKind,
-
-    pub touched_accounts: HashSet<Address>,
-    pub touched_storage_slots: HashMap<Address, HashSet<H256>>,
+    pub transient_storage: TransientStorage,
+    /// Changes made to the cache, substate and transient storage, undone when a frame reverts
+    pub journal: Journal,
 }
 
 pub fn address_to_word(address: Address) -> U256 {
@@ -80,28 +114,50 @@ impl VM {
     ) -> Result<Self, VMError> {
         // Maybe this decision should be made in an upper layer
 
-        // Add sender, coinbase and recipient (in the case of a Call) to cache [https://www.evm.codes/about#access_list]
-        let mut default_touched_accounts =
-            HashSet::from_iter([env.origin, env.coinbase].iter().cloned());
+        // The sender, coinbase (EIP-3651), recipient or created contract and precompiles start
+        // warm [https://www.evm.codes/about#access_list]
+        let mut substate = Substate::default();
+        substate.add_accessed_address(env.origin);
+        substate.add_accessed_address(env.coinbase);
+        for precompile in 1..=LAST_PRECOMPILE_ADDRESS {
+            substate.add_accessed_address(Address::from_low_u64_be(precompile));
+        }
+
+        // Addresses and storage keys in the access list start warm as well (EIP-2930)
+        for (address, storage_keys) in &env.tx_access_list {
+            substate.add_accessed_address(*address);
+            for key in storage_keys {
+                substate.add_accessed_storage_key(*address, *key);
+            }
+        }
 
         match to {
             TxKind::Call(address_to) => {
-                default_touched_accounts.insert(address_to);
-
-                // add address_to to cache
-                let recipient_account_info = db.get_account_info(address_to);
-                cache::insert_account(
-                    &mut cache,
-                    address_to,
-                    Account::from(recipient_account_info.clone()),
-                );
+                substate.add_accessed_address(address_to);
+
+                // add address_to to cache, unless a previous transaction of the block already did
+                let recipient_account_info = match cache::get_account(&cache, &address_to) {
+                    Some(account) => account.info.clone(),
+                    None => {
+                        let account_info = db.get_account_info(address_to)?;
+                        cache::insert_account(
+                            &mut cache,
+                            address_to,
+                            Account::from(account_info.clone()),
+                        );
+                        account_info
+                    }
+                };
 
                 // CALL tx
                 let initial_call_frame = CallFrame::new(
                     env.origin,
                     address_to,
                     address_to,
-                    recipient_account_info.bytecode,
+                    CodeCache::shared().get_or_analyze(
+                        recipient_account_info.code_hash,
+                        &recipient_account_info.bytecode,
+                    ),
                     value,
                     calldata.clone(),
                     false,
@@ -114,31 +170,44 @@ impl VM {
                     call_frames: vec![initial_call_frame],
                     db,
                     env,
-                    accrued_substate: Substate::default(),
+                    accrued_substate: substate,
                     cache,
                     tx_kind: to,
-                    touched_accounts: default_touched_accounts,
-                    touched_storage_slots: HashMap::new(),
+                    transient_storage: TransientStorage::new(),
+                    journal: Journal::default(),
                 })
             }
             TxKind::Create => {
                 // CREATE tx
 
                 // (2)
-                let new_contract_address =
-                    VM::calculate_create_address(env.origin, db.get_account_info(env.origin).nonce)
-                        .map_err(|_| {
-                            VMError::Internal(InternalError::CouldNotComputeCreateAddress)
-                        })?;
+                let sender_nonce = match cache::get_account(&cache, &env.origin) {
+                    Some(account) => account.info.nonce,
+                    None => db.get_account_info(env.origin)?.nonce,
+                };
+                let new_contract_address = VM::calculate_create_address(env.origin, sender_nonce)
+                    .map_err(|_| {
+                    VMError::Internal(InternalError::CouldNotComputeCreateAddress)
+                })?;
 
-                default_touched_accounts.insert(new_contract_address);
+                substate.add_accessed_address(new_contract_address);
+                substate.created_accounts.insert(new_contract_address);
 
                 // (3)
-                let created_contract = Account::new(value, calldata.clone(), 1, HashMap::new());
+                // The contract keeps any balance its address already held
+                let previous_balance = match cache::get_account(&cache, &new_contract_address) {
+                    Some(account) => account.info.balance,
+                    None => db.get_account_info(new_contract_address)?.balance,
+                };
+                let balance = previous_balance
+                    .checked_add(value)
+                    .ok_or(VMError::BalanceOverflow)?;
+                let created_contract = Account::new(balance, calldata.clone(), 1, HashMap::new());
                 cache::insert_account(&mut cache, new_contract_address, created_contract);
 
                 // (5)
-                let code: Bytes = calldata.clone();
+                // Initcode is rarely run twice, so it isn't worth caching its analysis
+                let code = Arc::new(AnalyzedCode::new(&calldata, false));
 
                 let initial_call_frame = CallFrame::new(
                     env.origin,
@@ -157,11 +226,11 @@ impl VM {
                     call_frames: vec![initial_call_frame],
                     db,
                     env,
-                    accrued_substate: Substate::default(),
+                    accrued_substate: substate,
                     cache,
                     tx_kind: TxKind::Create,
-                    touched_accounts: default_touched_accounts,
-                    touched_storage_slots: HashMap::new(),
+                    transient_storage: TransientStorage::new(),
+                    journal: Journal::default(),
                 })
             }
         }
@@ -172,17 +241,20 @@ impl VM {
         &mut self,
         current_call_frame: &mut CallFrame,
     ) -> Result<TransactionReport, VMError> {
-        // Backup of Database, Substate and Gas Refunds if sub-context is reverted
-        let (backup_db, backup_substate, backup_refunded_gas) = (
-            self.cache.clone(),
-            self.accrued_substate.clone(),
-            self.env.refunded_gas,
-        );
+        // The changes made by the frame are undone if it reverts
+        let checkpoint = self.journal.checkpoint();
+
+        // EOF code starts at its first code section
+        if self.is_eof_enabled() && current_call_frame.code.eof().is_some() {
+            current_call_frame.is_eof = true;
+            current_call_frame.enter_code_section(0)?;
+        }
 
         loop {
             let opcode = current_call_frame.next_opcode()?.unwrap_or(Opcode::STOP); // This will execute opcode stop if there are no more opcodes, there are other ways of solving this but this is the simplest and doesn't change VM behavior.
 
             let op_result: Result<OpcodeSuccess, VMError> = match opcode {
+                op if op.is_eof_only() && !current_call_frame.is_eof => Err(VMError::InvalidOpcode),
                 Opcode::STOP => Ok(OpcodeSuccess::Result(ResultReason::Stop)),
                 Opcode::ADD => self.op_add(current_call_frame),
                 Opcode::MUL => self.op_mul(current_call_frame),
@@ -278,23 +350,40 @@ impl VM {
                 Opcode::REVERT => self.op_revert(current_call_frame),
                 Opcode::INVALID => self.op_invalid(),
                 Opcode::SELFDESTRUCT => self.op_selfdestruct(current_call_frame),
+                Opcode::DATALOAD => self.op_dataload(current_call_frame),
+                Opcode::DATALOADN => self.op_dataloadn(current_call_frame),
+                Opcode::DATASIZE => self.op_datasize(current_call_frame),
+                Opcode::DATACOPY => self.op_datacopy(current_call_frame),
+                Opcode::RJUMP => self.op_rjump(current_call_frame),
+                Opcode::RJUMPI => self.op_rjumpi(current_call_frame),
+                Opcode::RJUMPV => self.op_rjumpv(current_call_frame),
+                Opcode::CALLF => self.op_callf(current_call_frame),
+                Opcode::RETF => self.op_retf(current_call_frame),
+                Opcode::JUMPF => self.op_jumpf(current_call_frame),
+                Opcode::DUPN => self.op_dupn(current_call_frame),
+                Opcode::SWAPN => self.op_swapn(current_call_frame),
+                Opcode::EXCHANGE => self.op_exchange(current_call_frame),
+                Opcode::EOFCREATE => self.op_eofcreate(current_call_frame),
+                Opcode::RETURNCONTRACT => self.op_returncontract(current_call_frame),
+                Opcode::RETURNDATALOAD => self.op_returndataload(current_call_frame),
+                Opcode::EXTCALL => self.op_extcall(current_call_frame),
+                Opcode::EXTDELEGATECALL => self.op_extdelegatecall(current_call_frame),
+                Opcode::EXTSTATICCALL => self.op_extstaticcall(current_call_frame),
 
                 _ => Err(VMError::OpcodeNotFound),
             };
 
-            // Gas refunds are applied at the end of a transaction. Should it be implemented here?
-
             match op_result {
                 Ok(OpcodeSuccess::Continue) => {}
                 Ok(OpcodeSuccess::Result(_)) => {
                     self.call_frames.push(current_call_frame.clone());
                     return Ok(TransactionReport {
                         result: TxResult::Success,
-                        new_state: self.cache.clone(),
+                        new_state: CacheDB::new(),
                         gas_used: current_call_frame.gas_used.low_u64(),
-                        gas_refunded: self.env.refunded_gas.low_u64(),
+                        gas_refunded: self.accrued_substate.refunded_gas.low_u64(),
                         output: current_call_frame.returndata.clone(),
-                        logs: current_call_frame.logs.clone(),
+                        logs: Vec::new(),
                         created_address: None,
                     });
                 }
@@ -315,15 +404,15 @@ impl VM {
                         self.env.consumed_gas = self.env.consumed_gas.saturating_add(left_gas);
                     }
 
-                    self.restore_state(backup_db, backup_substate, backup_refunded_gas);
+                    self.revert_to_checkpoint(checkpoint)?;
 
                     return Ok(TransactionReport {
                         result: TxResult::Revert(error),
-                        new_state: self.cache.clone(),
+                        new_state: CacheDB::new(),
                         gas_used: current_call_frame.gas_used.low_u64(),
-                        gas_refunded: self.env.refunded_gas.low_u64(),
+                        gas_refunded: self.accrued_substate.refunded_gas.low_u64(),
                         output: current_call_frame.returndata.clone(), // Bytes::new() if error is not RevertOpcode
-                        logs: current_call_frame.logs.clone(),
+                        logs: Vec::new(),
                         created_address: None,
                     });
                 }
@@ -331,86 +420,74 @@ impl VM {
         }
     }
 
-    fn restore_state(
-        &mut self,
-        backup_cache: CacheDB,
-        backup_substate: Substate,
-        backup_refunded_gas: U256,
-    ) {
-        self.cache = backup_cache;
-        self.accrued_substate = backup_substate;
-        self.env.refunded_gas = backup_refunded_gas;
+    /// Undoes the changes made to the state since the checkpoint was taken
+    pub fn revert_to_checkpoint(&mut self, checkpoint: Checkpoint) -> Result<(), VMError> {
+        self.journal.revert_to(
+            checkpoint,
+            &mut self.cache,
+            &mut self.accrued_substate,
+            &mut self.transient_storage,
+        )
     }
 
     fn is_create(&self) -> bool {
         matches!(self.tx_kind, TxKind::Create)
     }
 
-    fn revert_create(&mut self) -> Result<(), VMError> {
-        // Note: currently working with copies
-        let call_frame = self
-            .call_frames
-            .last()
-            .ok_or(VMError::Internal(
-                InternalError::CouldNotAccessLastCallframe,
-            ))?
-            .clone();
-
-        self.decrement_account_nonce(call_frame.msg_sender)?;
-
-        let new_contract_address = call_frame.to;
-        if cache::remove_account(&mut self.cache, &new_contract_address).is_none() {
-            return Err(VMError::AddressDoesNotMatchAnAccount); // Should not be this error
-        }
-
-        Ok(())
+    /// Whether EOF code runs as such, which it does from Osaka on
+    pub fn is_eof_enabled(&self) -> bool {
+        self.env.fork >= Fork::Osaka
     }
 
     /// ## Description
     /// This method performs validations and returns an error if any of the validations fail.
     /// It also makes initial changes alongside the validations:
     /// - It increases sender nonce
-    /// - It substracts up-front-cost from sender balance. (Not doing this for now)
-    /// - It calculates and adds intrinsic gas to the 'gas used' of callframe and environment. (Not doing this for now)
+    /// - It substracts the blob gas fee from the sender balance, as it's paid whatever the outcome
+    /// - It adds the intrinsic gas to the 'gas used' of the initial callframe and environment
     ///   See 'docs' for more information about validations.
     fn validate_transaction(&mut self, initial_call_frame: &mut CallFrame) -> Result<(), VMError> {
-        //TODO: This should revert the transaction, not throw an error. And I don't know if it should be done here...
-        // if self.is_create() {
-        //     // If address is already in db, there's an error
-        //     let new_address_acc = self.db.get_account_info(call_frame.to);
-        //     if !new_address_acc.is_empty() {
-        //         return Err(VMError::AddressAlreadyOccupied);
-        //     }
-        // }
         let sender_address = self.env.origin;
-        let sender_account = self.get_account(sender_address);
+        let sender_account = self.get_account(sender_address)?;
 
         // (1) GASLIMIT_PRICE_PRODUCT_OVERFLOW
+        // The sender must be able to pay the max fee per gas, not only the effective gas price
+        let max_gas_price = self.env.tx_max_fee_per_gas.unwrap_or(self.env.gas_price);
         let gaslimit_price_product =
-            self.env
-                .gas_price
+            max_gas_price
                 .checked_mul(self.env.gas_limit)
                 .ok_or(VMError::TxValidation(
                     TxValidationError::GasLimitPriceProductOverflow,
                 ))?;
 
+        let blob_gas_used = U256::from(self.env.tx_blob_hashes.len())
+            .checked_mul(BLOB_GAS_PER_BLOB)
+            .ok_or(VMError::Internal(
+                InternalError::ArithmeticOperationOverflow,
+            ))?;
+        let max_blob_gas_cost = self
+            .env
+            .tx_max_fee_per_blob_gas
+            .unwrap_or_default()
+            .checked_mul(blob_gas_used)
+            .ok_or(VMError::TxValidation(
+                TxValidationError::GasLimitPriceProductOverflow,
+            ))?;
+
         // Up front cost is the maximum amount of wei that a user is willing to pay for.
         let up_front_cost = gaslimit_price_product
-            .checked_add(initial_call_frame.msg_value)
+            .checked_add(max_blob_gas_cost)
+            .and_then(|cost| cost.checked_add(initial_call_frame.msg_value))
             .ok_or(VMError::TxValidation(
                 TxValidationError::InsufficientAccountFunds,
             ))?;
 
         // (2) INSUFFICIENT_ACCOUNT_FUNDS
-        // NOT CHANGING SENDER BALANCE HERE FOR NOW
-        // This will be increment_account_balance
-        sender_account
-            .info
-            .balance
-            .checked_sub(up_front_cost)
-            .ok_or(VMError::TxValidation(
+        if sender_account.info.balance < up_front_cost {
+            return Err(VMError::TxValidation(
                 TxValidationError::InsufficientAccountFunds,
-            ))?;
+            ));
+        }
 
         // (3) INSUFFICIENT_MAX_FEE_PER_GAS
         if self.env.gas_price < self.env.base_fee_per_gas {
@@ -420,18 +497,28 @@ impl VM {
         }
 
         // (4) INITCODE_SIZE_EXCEEDED
-        if self.is_create() {
-            // INITCODE_SIZE_EXCEEDED
-            if initial_call_frame.calldata.len() >= INIT_CODE_MAX_SIZE {
-                return Err(VMError::TxValidation(
-                    TxValidationError::InitcodeSizeExceeded,
-                ));
-            }
+        if self.is_create() && initial_call_frame.code.len() > INIT_CODE_MAX_SIZE {
+            return Err(VMError::TxValidation(
+                TxValidationError::InitcodeSizeExceeded,
+            ));
         }
 
         // (5) INTRINSIC_GAS_TOO_LOW
-        // TODO: Not doing this for now
-        // self.add_intrinsic_gas(initial_call_frame)?;
+        // The data of a CREATE transaction is the initcode, which is executed as bytecode
+        let tx_data = if self.is_create() {
+            initial_call_frame.bytecode()
+        } else {
+            &initial_call_frame.calldata
+        };
+        let intrinsic_gas: U256 =
+            gas_cost::tx_intrinsic(tx_data, self.is_create(), &self.env.tx_access_list)
+                .map_err(|_| VMError::TxValidation(TxValidationError::IntrinsicGasTooLow))?
+                .into();
+        if intrinsic_gas > self.env.gas_limit {
+            return Err(VMError::TxValidation(TxValidationError::IntrinsicGasTooLow));
+        }
+        initial_call_frame.gas_used = intrinsic_gas;
+        self.env.consumed_gas = intrinsic_gas;
 
         // (6) NONCE_IS_MAX
         self.increment_account_nonce(sender_address)?;
@@ -460,62 +547,96 @@ impl VM {
             ));
         }
 
-        // (10) INSUFFICIENT_MAX_FEE_PER_BLOB_GAS
-        if let Some(tx_max_fee_per_blob_gas) = self.env.tx_max_fee_per_blob_gas {
-            if tx_max_fee_per_blob_gas < self.env.base_fee_per_gas {
-                return Err(VMError::TxValidation(
-                    TxValidationError::InsufficientMaxFeePerGas,
-                ));
-            }
-        }
-
-        //TODO: Implement the rest of the validations (TYPE_3)
-
         // Transaction is type 3 if tx_max_fee_per_blob_gas is Some
-        if self.env.tx_max_fee_per_blob_gas.is_some() {
+        if let Some(tx_max_fee_per_blob_gas) = self.env.tx_max_fee_per_blob_gas {
             let blob_hashes = &self.env.tx_blob_hashes;
 
-            // (11) TYPE_3_TX_ZERO_BLOBS
+            // (10) TYPE_3_TX_ZERO_BLOBS
             if blob_hashes.is_empty() {
                 return Err(VMError::TxValidation(TxValidationError::Type3TxZeroBlobs));
             }
 
-            // (12) TYPE_3_TX_INVALID_BLOB_VERSIONED_HASH
-            for blob_hash in blob_hashes {
-                let blob_hash = blob_hash.as_bytes();
-                if let Some(first_byte) = blob_hash.first() {
-                    if !VALID_BLOB_PREFIXES.contains(first_byte) {
-                        return Err(VMError::TxValidation(
-                            TxValidationError::Type3TxInvalidBlobVersionedHash,
-                        ));
-                    }
-                }
+            // (11) TYPE_3_TX_INVALID_BLOB_VERSIONED_HASH
+            if blob_hashes
+                .iter()
+                .any(|blob_hash| blob_hash.as_bytes().first() != Some(&VERSIONED_HASH_VERSION_KZG))
+            {
+                return Err(VMError::TxValidation(
+                    TxValidationError::Type3TxInvalidBlobVersionedHash,
+                ));
             }
 
-            // (13) TYPE_3_TX_PRE_FORK -> This is not necessary for now because we are not supporting pre-cancun transactions yet. But we should somehow be able to tell the current context.
+            // (12) TYPE_3_TX_PRE_FORK -> This is not necessary for now because we are not supporting pre-cancun transactions yet. But we should somehow be able to tell the current context.
 
-            // (14) TYPE_3_TX_BLOB_COUNT_EXCEEDED
+            // (13) TYPE_3_TX_BLOB_COUNT_EXCEEDED
             if blob_hashes.len() > MAX_BLOB_COUNT {
                 return Err(VMError::TxValidation(
                     TxValidationError::Type3TxBlobCountExceeded,
                 ));
             }
 
-            // (15) TYPE_3_TX_CONTRACT_CREATION
+            // (14) TYPE_3_TX_CONTRACT_CREATION
             if self.is_create() {
                 return Err(VMError::TxValidation(
                     TxValidationError::Type3TxContractCreation,
                 ));
             }
+
+            // (15) INSUFFICIENT_MAX_FEE_PER_BLOB_GAS
+            let blob_gas_price = self.get_blob_gasprice()?;
+            if tx_max_fee_per_blob_gas < blob_gas_price {
+                return Err(VMError::TxValidation(
+                    TxValidationError::InsufficientMaxFeePerBlobGas,
+                ));
+            }
+
+            // The blob gas fee is burned, it isn't refunded if the execution fails
+            let blob_gas_fee = blob_gas_used
+                .checked_mul(blob_gas_price)
+                .ok_or(VMError::BalanceUnderflow)?;
+            self.decrease_account_balance(sender_address, blob_gas_fee)?;
         }
 
         Ok(())
     }
 
-    pub fn transact(&mut self) -> Result<TransactionReport, VMError> {
-        let initial_gas = Default::default();
+    /// Stores the code returned by the initcode of a CREATE transaction as the code of the new
+    /// contract, charging its deposit cost to the gas used by the transaction
+    fn deposit_contract_code(
+        &mut self,
+        initial_call_frame: &CallFrame,
+        report: &mut TransactionReport,
+    ) -> Result<(), VMError> {
+        let contract_code = report.output.clone();
+
+        if contract_code.len() > MAX_CODE_SIZE {
+            return Err(VMError::ContractOutputTooBig);
+        }
+        // EOF initcode returns a validated container with RETURNCONTRACT
+        if contract_code.first() == Some(&INVALID_CONTRACT_PREFIX)
+            && !initial_call_frame.is_eof_initcode
+        {
+            return Err(VMError::InvalidInitialByte);
+        }
+
+        // If the initialization code completes successfully, a final contract-creation cost is paid,
+        // the code-deposit cost, c, proportional to the size of the created contract’s code
+        let code_deposit_cost =
+            gas_cost::code_deposit(contract_code.len()).map_err(VMError::OutOfGas)?;
+        let gas_used = report
+            .gas_used
+            .checked_add(code_deposit_cost)
+            .ok_or(VMError::OutOfGas(OutOfGasError::GasUsedOverflow))?;
+        if U256::from(gas_used) > initial_call_frame.gas_limit {
+            return Err(VMError::OutOfGas(OutOfGasError::MaxGasLimitExceeded));
+        }
+        report.gas_used = gas_used;
 
-        self.env.consumed_gas = initial_gas;
+        self.update_account_bytecode(initial_call_frame.to, contract_code)
+    }
+
+    pub fn transact(&mut self) -> Result<TransactionReport, VMError> {
+        self.env.consumed_gas = U256::zero();
 
         let mut current_call_frame = self
             .call_frames
@@ -524,7 +645,34 @@ impl VM {
 
         self.validate_transaction(&mut current_call_frame)?;
 
-        let mut report = self.execute(&mut current_call_frame)?;
+        // The changes made by the initcode of a failed creation are discarded
+        let checkpoint = self.journal.checkpoint();
+
+        // EOF initcode is followed by the calldata, and the transaction fails using all its gas
+        // if the container isn't valid
+        let eof_initcode =
+            if self.is_create() && self.is_eof_enabled() && is_eof(current_call_frame.bytecode()) {
+                Some(Self::prepare_eof_initcode(&mut current_call_frame))
+            } else {
+                None
+            };
+
+        let mut report = match eof_initcode {
+            Some(Err(error)) => {
+                current_call_frame.gas_used = current_call_frame.gas_limit;
+                self.call_frames.push(current_call_frame.clone());
+                TransactionReport {
+                    result: TxResult::Revert(error),
+                    new_state: CacheDB::new(),
+                    gas_used: current_call_frame.gas_limit.low_u64(),
+                    gas_refunded: 0,
+                    output: Bytes::new(),
+                    logs: Vec::new(),
+                    created_address: None,
+                }
+            }
+            _ => self.execute(&mut current_call_frame)?,
+        };
 
         let initial_call_frame = self
             .call_frames
@@ -536,64 +684,47 @@ impl VM {
 
         let sender = initial_call_frame.msg_sender;
 
-        let calldata_cost =
-            gas_cost::tx_calldata(&initial_call_frame.calldata).map_err(VMError::OutOfGas)?;
-
-        report.gas_used = report
-            .gas_used
-            .checked_add(calldata_cost)
-            .ok_or(VMError::OutOfGas(OutOfGasError::GasUsedOverflow))?;
-
         if self.is_create() {
-            // If create should check if transaction failed. If failed should revert (delete created contract, )
-            if let TxResult::Revert(error) = report.result {
-                self.revert_create()?;
-                return Err(error);
-            }
-            let contract_code = report.clone().output;
-
-            // TODO: Is this the expected behavior?
-            if !contract_code.is_empty() {
-                // (6)
-                if contract_code.len() > MAX_CODE_SIZE {
-                    return Err(VMError::ContractOutputTooBig);
-                }
-                // Supposing contract code has contents
-                if *contract_code
-                    .first()
-                    .ok_or(VMError::Internal(InternalError::TriedToIndexEmptyCode))?
-                    == INVALID_CONTRACT_PREFIX
-                {
-                    return Err(VMError::InvalidInitialByte);
+            let contract_address = initial_call_frame.to;
+            let creation = match &report.result {
+                TxResult::Success => self.deposit_contract_code(&initial_call_frame, &mut report),
+                TxResult::Revert(error) => Err(error.clone()),
+            };
+            match creation {
+                Ok(()) => report.created_address = Some(contract_address),
+                Err(error) if error.is_internal() => return Err(error),
+                Err(error) => {
+                    // The transaction is still valid: the sender pays for the gas and keeps its
+                    // increased nonce, but no contract is created
+                    self.revert_to_checkpoint(checkpoint)?;
+                    cache::remove_account(&mut self.cache, &contract_address);
+                    if error != VMError::RevertOpcode {
+                        report.gas_used = initial_call_frame.gas_limit.low_u64();
+                        report.output = Bytes::new();
+                    }
+                    report.result = TxResult::Revert(error);
                 }
             }
+        }
 
-            // If the initialization code completes successfully, a final contract-creation cost is paid,
-            // the code-deposit cost, c, proportional to the size of the created contract’s code
-            let number_of_words: u64 = initial_call_frame
-                .calldata
-                .chunks(WORD_SIZE)
-                .len()
-                .try_into()
-                .map_err(|_| VMError::Internal(InternalError::ConversionError))?;
-
-            let code_length: u64 = contract_code
-                .len()
-                .try_into()
-                .map_err(|_| VMError::Internal(InternalError::ConversionError))?;
-
-            let creation_cost =
-                gas_cost::tx_creation(code_length, number_of_words).map_err(VMError::OutOfGas)?;
-            report.gas_used = report
+        // Refunds are capped to a fifth of the gas used (EIP-3529), and a reverted transaction
+        // gets none as its substate was discarded
+        let refund_cap = report.gas_used / MAX_REFUND_QUOTIENT;
+        report.gas_refunded = if report.is_success() {
+            self.accrued_substate
+                .refunded_gas
+                .min(U256::from(refund_cap))
+                .low_u64()
+        } else {
+            0
+        };
+        report.gas_used =
+            report
                 .gas_used
-                .checked_add(creation_cost)
-                .ok_or(VMError::OutOfGas(OutOfGasError::GasUsedOverflow))?;
-            // Charge 22100 gas for each storage variable set
-
-            let contract_address = initial_call_frame.to;
-
-            self.update_account_bytecode(contract_address, contract_code)?;
-        }
+                .checked_sub(report.gas_refunded)
+                .ok_or(VMError::Internal(
+                    InternalError::ArithmeticOperationUnderflow,
+                ))?;
 
         let coinbase_address = self.env.coinbase;
 
@@ -609,7 +740,17 @@ impl VM {
         if report.is_success() {
             // Subtract to the caller the gas sent
             self.decrease_account_balance(sender, initial_call_frame.msg_value)?;
-            self.increase_account_balance(receiver_address, initial_call_frame.msg_value)?;
+            // A created contract already holds the value since it was added to the cache
+            if !self.is_create() {
+                self.increase_account_balance(receiver_address, initial_call_frame.msg_value)?;
+            }
+            // The transaction's own transfer comes before the ones made while executing it
+            if self.env.trace_transfers && !initial_call_frame.msg_value.is_zero() {
+                self.accrued_substate.logs.insert(
+                    0,
+                    transfer_log(sender, receiver_address, initial_call_frame.msg_value),
+                );
+            }
         }
 
         // Send coinbase fee
@@ -626,8 +767,49 @@ impl VM {
             self.increase_account_balance(coinbase_address, coinbase_fee)?;
         }
 
+        // Accounts destroyed by the transaction are emptied, so they are removed from the state
+        if report.is_success() {
+            for address in self.accrued_substate.selfdestruct_set.clone() {
+                let account = self.get_account_mut(address)?;
+                account.info = AccountInfo::default();
+                for slot in account.storage.values_mut() {
+                    slot.current_value = U256::zero();
+                }
+            }
+        }
+
         report.new_state.clone_from(&self.cache);
+        report.logs.clone_from(&self.accrued_substate.logs);
+
+        Ok(report)
+    }
+
+    /// Splits the data of a creation transaction into the EOF initcode and its calldata, and
+    /// validates the initcode
+    fn prepare_eof_initcode(current_call_frame: &mut CallFrame) -> Result<(), VMError> {
+        let (container, calldata) =
+            EofContainer::decode_with_trailing_data(current_call_frame.bytecode().clone())?;
+        container.validate(ContainerKind::Initcode)?;
+
+        current_call_frame.code = Arc::new(AnalyzedCode::new(container.raw(), false));
+        current_call_frame.calldata = calldata;
+        current_call_frame.is_eof_initcode = true;
+        Ok(())
+    }
 
+    /// Executes the initial call frame as a system call, like the EIP-4788 beacon root update.
+    ///
+    /// Unlike [`VM::transact`], the transaction isn't validated, the caller's nonce isn't
+    /// increased and no fees are charged.
+    pub fn execute_system_call(&mut self) -> Result<TransactionReport, VMError> {
+        let mut current_call_frame = self
+            .call_frames
+            .pop()
+            .ok_or(VMError::Internal(InternalError::CouldNotPopCallframe))?;
+
+        let mut report = self.execute(&mut current_call_frame)?;
+        report.new_state.clone_from(&self.cache);
+        report.logs.clone_from(&self.accrued_substate.logs);
         Ok(report)
     }
 
@@ -647,30 +829,42 @@ impl VM {
         msg_sender: Address,
         to: Address,
         code_address: Address,
-        _should_transfer_value: bool,
+        should_transfer_value: bool,
         is_static: bool,
         args_offset: usize,
         args_size: usize,
         ret_offset: usize,
         ret_size: usize,
     ) -> Result<OpcodeSuccess, VMError> {
-        let (sender_account_info, _address_was_cold) = self.access_account(msg_sender);
-
-        if sender_account_info.balance < value {
+        let (sender_account_info, _address_was_cold) = self.access_account(msg_sender)?;
+
+        // The call fails without running the callee if the sender can't afford the value or the
+        // maximum call depth is reached. As the callee doesn't run, no gas is consumed by it.
+        // DELEGATECALL keeps the value of the current frame without transferring it.
+        if (should_transfer_value && sender_account_info.balance < value)
+            || current_call_frame.depth >= MAX_CALL_DEPTH
+        {
             current_call_frame.stack.push(U256::from(REVERT_FOR_CALL))?;
             return Ok(OpcodeSuccess::Continue);
         }
 
-        self.decrease_account_balance(msg_sender, value)?;
-        self.increase_account_balance(to, value)?;
+        // The value transfer is undone along with the callee's changes if it reverts
+        let checkpoint = self.journal.checkpoint();
 
-        let (code_account_info, _address_was_cold) = self.access_account(code_address);
+        if should_transfer_value {
+            self.decrease_account_balance(msg_sender, value)?;
+            self.increase_account_balance(to, value)?;
+            self.trace_transfer(msg_sender, to, value);
+        }
+
+        let (code_account_info, _address_was_cold) = self.access_account(code_address)?;
 
+        // Calling an account without code succeeds right away, the caller keeps running
         if code_account_info.bytecode.is_empty() {
             current_call_frame
                 .stack
                 .push(U256::from(SUCCESS_FOR_CALL))?;
-            return Ok(OpcodeSuccess::Result(ResultReason::Stop));
+            return Ok(OpcodeSuccess::Continue);
         }
 
         // self.cache.increment_account_nonce(&code_address); // Internal call doesn't increment account nonce.
@@ -695,13 +889,16 @@ impl VM {
         let new_depth = current_call_frame
             .depth
             .checked_add(1)
-            .ok_or(VMError::StackOverflow)?; // Maybe could be depthOverflow but in concept is quite similar
+            .ok_or(VMError::Internal(
+                InternalError::ArithmeticOperationOverflow,
+            ))?;
 
         let mut new_call_frame = CallFrame::new(
             msg_sender,
             to,
             code_address,
-            code_account_info.bytecode,
+            CodeCache::shared()
+                .get_or_analyze(code_account_info.code_hash, &code_account_info.bytecode),
             value,
             calldata,
             is_static,
@@ -710,13 +907,6 @@ impl VM {
             new_depth,
         );
 
-        // TODO: Increase this to 1024
-        if new_call_frame.depth > 10 {
-            current_call_frame.stack.push(U256::from(REVERT_FOR_CALL))?;
-            // return Ok(OpcodeSuccess::Result(ResultReason::Revert));
-            return Err(VMError::StackOverflow); // This is wrong but it is for testing purposes.
-        }
-
         current_call_frame.sub_return_data_offset = ret_offset;
         current_call_frame.sub_return_data_size = ret_size;
 
@@ -727,7 +917,6 @@ impl VM {
             .gas_used
             .checked_add(tx_report.gas_used.into())
             .ok_or(VMError::OutOfGas(OutOfGasError::ConsumedGasOverflow))?;
-        current_call_frame.logs.extend(tx_report.logs);
         current_call_frame
             .memory
             .store_n_bytes(ret_offset, &tx_report.output, ret_size)?;
@@ -741,6 +930,7 @@ impl VM {
                     .push(U256::from(SUCCESS_FOR_CALL))?;
             }
             TxResult::Revert(_) => {
+                self.revert_to_checkpoint(checkpoint)?;
                 // Push 0 to stack
                 current_call_frame.stack.push(U256::from(REVERT_FOR_CALL))?;
             }
@@ -825,13 +1015,17 @@ impl VM {
         }
 
         let (sender_account_info, _sender_address_was_cold) =
-            self.access_account(current_call_frame.msg_sender);
+            self.access_account(current_call_frame.msg_sender)?;
 
-        if sender_account_info.balance < value_in_wei_to_send {
+        // As with calls, the creation fails without increasing the sender's nonce if it can't
+        // afford the value or the maximum call depth is reached
+        if sender_account_info.balance < value_in_wei_to_send
+            || current_call_frame.depth >= MAX_CALL_DEPTH
+        {
             current_call_frame
                 .stack
                 .push(U256::from(REVERT_FOR_CREATE))?;
-            return Ok(OpcodeSuccess::Result(ResultReason::Revert));
+            return Ok(OpcodeSuccess::Continue);
         }
 
         let new_nonce = match self.increment_account_nonce(current_call_frame.msg_sender) {
@@ -850,6 +1044,14 @@ impl VM {
                 .load_range(code_offset_in_memory, code_size_in_memory)?,
         );
 
+        // EOF initcode can only be run by EOFCREATE and creation transactions
+        if self.is_eof_enabled() && is_eof(&code) {
+            current_call_frame
+                .stack
+                .push(U256::from(REVERT_FOR_CREATE))?;
+            return Ok(OpcodeSuccess::Continue);
+        }
+
         let new_address = match salt {
             Some(salt) => Self::calculate_create2_address(current_call_frame.to, &code, salt)?,
             None => Self::calculate_create_address(current_call_frame.msg_sender, new_nonce)?,
@@ -863,8 +1065,12 @@ impl VM {
             return Ok(OpcodeSuccess::Result(ResultReason::Revert));
         }
 
+        // The created address stays warm even if the creation fails
+        self.add_accessed_address(new_address);
+        let checkpoint = self.journal.checkpoint();
+
         let new_account = Account::new(U256::zero(), code.clone(), 0, Default::default());
-        cache::insert_account(&mut self.cache, new_address, new_account);
+        self.insert_new_account(new_address, new_account);
 
         current_call_frame
             .stack
@@ -886,11 +1092,251 @@ impl VM {
         )?;
 
         // Erases the success value in the stack result of calling generic call, probably this should be refactored soon...
-        current_call_frame
+        let call_result = current_call_frame
             .stack
             .pop()
             .map_err(|_| VMError::StackUnderflow)?;
 
+        // A failed creation leaves no account behind and pushes 0 instead of its address
+        if call_result == U256::from(REVERT_FOR_CALL) {
+            self.revert_to_checkpoint(checkpoint)?;
+            current_call_frame.stack.pop()?;
+            current_call_frame
+                .stack
+                .push(U256::from(REVERT_FOR_CREATE))?;
+        }
+
+        Ok(OpcodeSuccess::Continue)
+    }
+
+    /// Common behavior for EXTCALL, EXTDELEGATECALL and EXTSTATICCALL (EIP-7069)
+    ///
+    /// Unlike legacy calls, the callee's output is only available as return data, and the result
+    /// pushed is 0 on success, 1 on revert or if the callee couldn't run and 2 on failure.
+    #[allow(clippy::too_many_arguments)]
+    pub fn generic_ext_call(
+        &mut self,
+        current_call_frame: &mut CallFrame,
+        value: U256,
+        msg_sender: Address,
+        to: Address,
+        code_address: Address,
+        should_transfer_value: bool,
+        is_static: bool,
+        calldata: Bytes,
+    ) -> Result<OpcodeSuccess, VMError> {
+        current_call_frame.sub_return_data = Bytes::new();
+
+        // The caller keeps at least 1/64 of its gas or MIN_RETAINED_GAS, and the call doesn't
+        // happen if the callee would get less than MIN_CALLEE_GAS
+        let remaining_gas = current_call_frame
+            .gas_limit
+            .checked_sub(current_call_frame.gas_used)
+            .ok_or(VMError::OutOfGas(OutOfGasError::MaxGasLimitExceeded))?;
+        let retained_gas = remaining_gas
+            .checked_div(U256::from(64))
+            .ok_or(VMError::Internal(InternalError::DivisionError))?
+            .max(gas_cost::MIN_RETAINED_GAS);
+        let gas_limit = remaining_gas.saturating_sub(retained_gas);
+
+        let (sender_account_info, _address_was_cold) = self.access_account(msg_sender)?;
+        let (code_account_info, _address_was_cold) = self.access_account(code_address)?;
+        let code = CodeCache::shared()
+            .get_or_analyze(code_account_info.code_hash, &code_account_info.bytecode);
+
+        // EXTDELEGATECALL can't run legacy code in the context of EOF code
+        let is_delegate_to_legacy = to != code_address && code.eof().is_none();
+        if gas_limit < gas_cost::MIN_CALLEE_GAS
+            || (should_transfer_value && sender_account_info.balance < value)
+            || current_call_frame.depth >= MAX_CALL_DEPTH
+            || is_delegate_to_legacy
+        {
+            current_call_frame
+                .stack
+                .push(U256::from(REVERT_FOR_EXT_CALL))?;
+            return Ok(OpcodeSuccess::Continue);
+        }
+
+        // The value transfer is undone along with the callee's changes if it reverts
+        let checkpoint = self.journal.checkpoint();
+
+        if should_transfer_value {
+            self.decrease_account_balance(msg_sender, value)?;
+            self.increase_account_balance(to, value)?;
+            self.trace_transfer(msg_sender, to, value);
+        }
+
+        if code.is_empty() {
+            current_call_frame
+                .stack
+                .push(U256::from(SUCCESS_FOR_EXT_CALL))?;
+            return Ok(OpcodeSuccess::Continue);
+        }
+
+        let new_depth = current_call_frame
+            .depth
+            .checked_add(1)
+            .ok_or(VMError::Internal(
+                InternalError::ArithmeticOperationOverflow,
+            ))?;
+        let mut new_call_frame = CallFrame::new(
+            msg_sender,
+            to,
+            code_address,
+            code,
+            value,
+            calldata,
+            is_static,
+            gas_limit,
+            U256::zero(),
+            new_depth,
+        );
+
+        let tx_report = self.execute(&mut new_call_frame)?;
+
+        current_call_frame.gas_used = current_call_frame
+            .gas_used
+            .checked_add(tx_report.gas_used.into())
+            .ok_or(VMError::OutOfGas(OutOfGasError::ConsumedGasOverflow))?;
+        current_call_frame.sub_return_data = tx_report.output;
+
+        let result = match tx_report.result {
+            TxResult::Success => SUCCESS_FOR_EXT_CALL,
+            TxResult::Revert(error) => {
+                self.revert_to_checkpoint(checkpoint)?;
+                if error == VMError::RevertOpcode {
+                    REVERT_FOR_EXT_CALL
+                } else {
+                    FAILURE_FOR_EXT_CALL
+                }
+            }
+        };
+        current_call_frame.stack.push(U256::from(result))?;
+
+        Ok(OpcodeSuccess::Continue)
+    }
+
+    /// Creates a contract running one of the subcontainers of the current EOF code as its
+    /// initcode (EIP-7620). Its address depends on the creator, the salt and the initcode, like
+    /// with CREATE2, and it's deployed with the container returned by RETURNCONTRACT.
+    pub fn eof_create(
+        &mut self,
+        current_call_frame: &mut CallFrame,
+        initcontainer: Bytes,
+        value: U256,
+        salt: U256,
+        calldata: Bytes,
+    ) -> Result<OpcodeSuccess, VMError> {
+        current_call_frame.sub_return_data = Bytes::new();
+
+        let creator = current_call_frame.to;
+        let new_address = Self::calculate_create2_address(creator, &initcontainer, salt)?;
+
+        // The initcode gets all but 1/64 of the remaining gas
+        let remaining_gas = current_call_frame
+            .gas_limit
+            .checked_sub(current_call_frame.gas_used)
+            .ok_or(VMError::OutOfGas(OutOfGasError::MaxGasLimitExceeded))?;
+        let gas_limit = remaining_gas.saturating_sub(
+            remaining_gas
+                .checked_div(U256::from(64))
+                .ok_or(VMError::Internal(InternalError::DivisionError))?,
+        );
+
+        let (creator_account_info, _address_was_cold) = self.access_account(creator)?;
+        if creator_account_info.balance < value || current_call_frame.depth >= MAX_CALL_DEPTH {
+            current_call_frame
+                .stack
+                .push(U256::from(REVERT_FOR_CREATE))?;
+            return Ok(OpcodeSuccess::Continue);
+        }
+        if self.increment_account_nonce(creator).is_err() {
+            current_call_frame
+                .stack
+                .push(U256::from(REVERT_FOR_CREATE))?;
+            return Ok(OpcodeSuccess::Continue);
+        }
+
+        // A creation into an address that already has code or a nonce uses all the gas it got
+        let (new_account_info, _address_was_cold) = self.access_account(new_address)?;
+        if new_account_info.nonce != 0 || !new_account_info.bytecode.is_empty() {
+            self.increase_consumed_gas(current_call_frame, gas_limit)?;
+            current_call_frame
+                .stack
+                .push(U256::from(REVERT_FOR_CREATE))?;
+            return Ok(OpcodeSuccess::Continue);
+        }
+
+        let checkpoint = self.journal.checkpoint();
+
+        // The new account starts with nonce 1 (EIP-161)
+        let balance = new_account_info.balance;
+        let new_account = Account::new(balance, Bytes::new(), 1, Default::default());
+        self.insert_new_account(new_address, new_account);
+        self.decrease_account_balance(creator, value)?;
+        self.increase_account_balance(new_address, value)?;
+        self.trace_transfer(creator, new_address, value);
+
+        let new_depth = current_call_frame
+            .depth
+            .checked_add(1)
+            .ok_or(VMError::Internal(
+                InternalError::ArithmeticOperationOverflow,
+            ))?;
+        let code = CodeCache::shared().get_or_analyze(code_hash(&initcontainer), &initcontainer);
+        let mut new_call_frame = CallFrame::new(
+            creator,
+            new_address,
+            new_address,
+            code,
+            value,
+            calldata,
+            false,
+            gas_limit,
+            U256::zero(),
+            new_depth,
+        );
+        new_call_frame.is_eof_initcode = true;
+
+        let tx_report = self.execute(&mut new_call_frame)?;
+
+        current_call_frame.gas_used = current_call_frame
+            .gas_used
+            .checked_add(tx_report.gas_used.into())
+            .ok_or(VMError::OutOfGas(OutOfGasError::ConsumedGasOverflow))?;
+
+        if let TxResult::Revert(error) = tx_report.result {
+            self.revert_to_checkpoint(checkpoint)?;
+            if error == VMError::RevertOpcode {
+                current_call_frame.sub_return_data = tx_report.output;
+            }
+            current_call_frame
+                .stack
+                .push(U256::from(REVERT_FOR_CREATE))?;
+            return Ok(OpcodeSuccess::Continue);
+        }
+
+        // The code deposit is paid with the gas the initcode didn't use, the creation failing
+        // and using it all if it's not enough
+        let deployed_code = tx_report.output;
+        let unused_gas = gas_limit.saturating_sub(tx_report.gas_used.into());
+        let code_deposit_cost =
+            U256::from(gas_cost::code_deposit(deployed_code.len()).map_err(VMError::OutOfGas)?);
+        if deployed_code.len() > MAX_CODE_SIZE || code_deposit_cost > unused_gas {
+            self.revert_to_checkpoint(checkpoint)?;
+            self.increase_consumed_gas(current_call_frame, unused_gas)?;
+            current_call_frame
+                .stack
+                .push(U256::from(REVERT_FOR_CREATE))?;
+            return Ok(OpcodeSuccess::Continue);
+        }
+        self.increase_consumed_gas(current_call_frame, code_deposit_cost)?;
+        self.update_account_bytecode(new_address, deployed_code)?;
+
+        current_call_frame
+            .stack
+            .push(address_to_word(new_address))?;
+
         Ok(OpcodeSuccess::Continue)
     }
 
@@ -918,8 +1364,8 @@ impl VM {
         Ok(())
     }
 
-    pub fn cache_from_db(&mut self, address: Address) {
-        let acc_info = self.db.get_account_info(address);
+    pub fn cache_from_db(&mut self, address: Address) -> Result<(), VMError> {
+        let acc_info = self.db.get_account_info(address)?;
         cache::insert_account(
             &mut self.cache,
             address,
@@ -928,38 +1374,37 @@ impl VM {
                 storage: HashMap::new(),
             },
         );
+        Ok(())
     }
 
     /// Accesses to an account's information.
     ///
-    /// Accessed accounts are stored in the `touched_accounts` set.
-    /// Accessed accounts take place in some gas cost computation.
-    #[must_use]
-    pub fn access_account(&mut self, address: Address) -> (AccountInfo, bool) {
-        let address_was_cold = self.touched_accounts.insert(address);
+    /// Accessed accounts are stored in the accessed addresses of the substate, returning whether
+    /// the account was cold, as it takes place in some gas cost computation.
+    pub fn access_account(&mut self, address: Address) -> Result<(AccountInfo, bool), VMError> {
+        let address_was_cold = self.add_accessed_address(address);
         let account = match cache::get_account(&self.cache, &address) {
             Some(account) => account.info.clone(),
-            None => self.db.get_account_info(address),
+            None => self.db.get_account_info(address)?,
         };
-        (account, address_was_cold)
+        Ok((account, address_was_cold))
     }
 
     /// Accesses to an account's storage slot.
     ///
-    /// Accessed storage slots are stored in the `touched_storage_slots` set.
-    /// Accessed storage slots take place in some gas cost computation.
-    #[must_use]
-    pub fn access_storage_slot(&mut self, address: Address, key: H256) -> (StorageSlot, bool) {
-        let storage_slot_was_cold = self
-            .touched_storage_slots
-            .entry(address)
-            .or_default()
-            .insert(key);
+    /// Accessed storage slots are stored in the accessed storage keys of the substate, returning
+    /// whether the slot was cold, as it takes place in some gas cost computation.
+    pub fn access_storage_slot(
+        &mut self,
+        address: Address,
+        key: H256,
+    ) -> Result<(StorageSlot, bool), VMError> {
+        let storage_slot_was_cold = self.add_accessed_storage_key(address, key);
         let storage_slot = match cache::get_account(&self.cache, &address) {
             Some(account) => match account.storage.get(&key) {
                 Some(storage_slot) => storage_slot.clone(),
                 None => {
-                    let value = self.db.get_storage_slot(address, key);
+                    let value = self.db.get_storage_slot(address, key)?;
                     StorageSlot {
                         original_value: value,
                         current_value: value,
@@ -967,14 +1412,44 @@ impl VM {
                 }
             },
             None => {
-                let value = self.db.get_storage_slot(address, key);
+                let value = self.db.get_storage_slot(address, key)?;
                 StorageSlot {
                     original_value: value,
                     current_value: value,
                 }
             }
         };
-        (storage_slot, storage_slot_was_cold)
+        Ok((storage_slot, storage_slot_was_cold))
+    }
+
+    /// Marks an account as accessed, returning whether it was cold
+    pub fn add_accessed_address(&mut self, address: Address) -> bool {
+        let address_was_cold = self.accrued_substate.add_accessed_address(address);
+        if address_was_cold {
+            self.journal.record(JournalEntry::AccountWarmed { address });
+        }
+        address_was_cold
+    }
+
+    /// Marks a storage slot as accessed, returning whether it was cold
+    pub fn add_accessed_storage_key(&mut self, address: Address, key: H256) -> bool {
+        let storage_slot_was_cold = self.accrued_substate.add_accessed_storage_key(address, key);
+        if storage_slot_was_cold {
+            self.journal
+                .record(JournalEntry::StorageKeyWarmed { address, key });
+        }
+        storage_slot_was_cold
+    }
+
+    /// Inserts an account created during the transaction into the cache
+    pub fn insert_new_account(&mut self, address: Address, account: Account) {
+        let previous = cache::insert_account(&mut self.cache, address, account);
+        self.journal
+            .record(JournalEntry::AccountCreated { address, previous });
+        if self.accrued_substate.created_accounts.insert(address) {
+            self.journal
+                .record(JournalEntry::CreatedAccountAdded { address });
+        }
     }
 
     pub fn increase_account_balance(
@@ -983,11 +1458,12 @@ impl VM {
         increase: U256,
     ) -> Result<(), VMError> {
         let account = self.get_account_mut(address)?;
-        account.info.balance = account
-            .info
-            .balance
+        let previous = account.info.balance;
+        account.info.balance = previous
             .checked_add(increase)
             .ok_or(VMError::BalanceOverflow)?;
+        self.journal
+            .record(JournalEntry::BalanceChanged { address, previous });
         Ok(())
     }
 
@@ -997,31 +1473,33 @@ impl VM {
         decrease: U256,
     ) -> Result<(), VMError> {
         let account = self.get_account_mut(address)?;
-        account.info.balance = account
-            .info
-            .balance
+        let previous = account.info.balance;
+        account.info.balance = previous
             .checked_sub(decrease)
             .ok_or(VMError::BalanceUnderflow)?;
+        self.journal
+            .record(JournalEntry::BalanceChanged { address, previous });
         Ok(())
     }
 
     pub fn increment_account_nonce(&mut self, address: Address) -> Result<u64, VMError> {
         let account = self.get_account_mut(address)?;
-        account.info.nonce = account
-            .info
-            .nonce
+        let previous = account.info.nonce;
+        account.info.nonce = previous
             .checked_add(1)
             .ok_or(VMError::TxValidation(TxValidationError::NonceIsMax))?;
-        Ok(account.info.nonce)
+        let new_nonce = account.info.nonce;
+        self.journal
+            .record(JournalEntry::NonceChanged { address, previous });
+        Ok(new_nonce)
     }
 
     pub fn decrement_account_nonce(&mut self, address: Address) -> Result<(), VMError> {
         let account = self.get_account_mut(address)?;
-        account.info.nonce = account
-            .info
-            .nonce
-            .checked_sub(1)
-            .ok_or(VMError::NonceUnderflow)?;
+        let previous = account.info.nonce;
+        account.info.nonce = previous.checked_sub(1).ok_or(VMError::NonceUnderflow)?;
+        self.journal
+            .record(JournalEntry::NonceChanged { address, previous });
         Ok(())
     }
 
@@ -1031,7 +1509,14 @@ impl VM {
         new_bytecode: Bytes,
     ) -> Result<(), VMError> {
         let account = self.get_account_mut(address)?;
-        account.info.bytecode = new_bytecode;
+        let previous = account.info.bytecode.clone();
+        let previous_hash = account.info.code_hash;
+        account.info.set_bytecode(new_bytecode);
+        self.journal.record(JournalEntry::CodeChanged {
+            address,
+            previous,
+            previous_hash,
+        });
         Ok(())
     }
 
@@ -1042,21 +1527,77 @@ impl VM {
         new_value: U256,
     ) -> Result<(), VMError> {
         let account = self.get_account_mut(address)?;
-        let account_original_storage_slot_value = account
-            .storage
-            .get(&key)
-            .map_or(U256::zero(), |slot| slot.original_value);
-        let slot = account.storage.entry(key).or_insert(StorageSlot {
-            original_value: account_original_storage_slot_value,
-            current_value: new_value,
+        let previous = account.storage.get(&key).cloned();
+        let original_value = match &previous {
+            Some(slot) => slot.original_value,
+            None => self.db.get_storage_slot(address, key)?,
+        };
+        let account = self.get_account_mut(address)?;
+        account.storage.insert(
+            key,
+            StorageSlot {
+                original_value,
+                current_value: new_value,
+            },
+        );
+        self.journal.record(JournalEntry::StorageChanged {
+            address,
+            key,
+            previous,
         });
-        slot.current_value = new_value;
         Ok(())
     }
 
-    pub fn get_account_mut(&mut self, address: Address) -> Result<&mut Account, VMError> {
+    pub fn get_transient_storage(&self, address: Address, key: U256) -> U256 {
+        self.transient_storage
+            .get(&(address, key))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn update_transient_storage(&mut self, address: Address, key: U256, value: U256) {
+        let previous = self.transient_storage.insert((address, key), value);
+        self.journal.record(JournalEntry::TransientStorageChanged {
+            address,
+            key,
+            previous,
+        });
+    }
+
+    pub fn add_log(&mut self, log: Log) {
+        self.accrued_substate.logs.push(log);
+        self.journal.record(JournalEntry::LogAdded);
+    }
+
+    /// Records an ether transfer as a log when transfers are traced. Like any other log, it's
+    /// discarded if the frame making the transfer reverts.
+    pub fn trace_transfer(&mut self, from: Address, to: Address, value: U256) {
+        if self.env.trace_transfers && !value.is_zero() {
+            self.add_log(transfer_log(from, to, value));
+        }
+    }
+
+    pub fn update_refunded_gas(&mut self, refunded_gas: U256) {
+        let previous = std::mem::replace(&mut self.accrued_substate.refunded_gas, refunded_gas);
+        if previous != refunded_gas {
+            self.journal
+                .record(JournalEntry::RefundChanged { previous });
+        }
+    }
+
+    /// Registers an account to be destroyed at the end of the transaction
+    pub fn add_to_selfdestruct_set(&mut self, address: Address) {
+        if self.accrued_substate.selfdestruct_set.insert(address) {
+            self.journal
+                .record(JournalEntry::SelfDestructed { address });
+        }
+    }
+
+    // Changes made through the returned account aren't journaled, it's only meant to be used by
+    // the methods that record them
+    fn get_account_mut(&mut self, address: Address) -> Result<&mut Account, VMError> {
         if !cache::is_account_cached(&self.cache, &address) {
-            let account_info = self.db.get_account_info(address);
+            let account_info = self.db.get_account_info(address)?;
             let account = Account {
                 info: account_info,
                 storage: HashMap::new(),
@@ -1068,18 +1609,29 @@ impl VM {
     }
 
     /// Gets account, first checking the cache and then the database (caching in the second case)
-    pub fn get_account(&mut self, address: Address) -> Account {
+    pub fn get_account(&mut self, address: Address) -> Result<Account, VMError> {
         match cache::get_account(&self.cache, &address) {
-            Some(acc) => acc.clone(),
+            Some(acc) => Ok(acc.clone()),
             None => {
-                let account_info = self.db.get_account_info(address);
+                let account_info = self.db.get_account_info(address)?;
                 let account = Account {
                     info: account_info,
                     storage: HashMap::new(),
                 };
                 cache::insert_account(&mut self.cache, address, account.clone());
-                account
+                Ok(account)
             }
         }
     }
 }
+
+/// ERC-20 style Transfer log reporting an ether transfer
+fn transfer_log(from: Address, to: Address, value: U256) -> Log {
+    let mut data = [0u8; 32];
+    value.to_big_endian(&mut data);
+    Log {
+        address: TRANSFER_LOG_EMITTER,
+        topics: vec![TRANSFER_EVENT_TOPIC, H256::from(from), H256::from(to)],
+        data: Bytes::copy_from_slice(&data),
+    }
+}
@@ -274,3 +274,28 @@ fn test_non_compliance_codecopy_memory_resize() {
         &U256::from(14400)
     );
 }
+
+#[test]
+fn test_jump_into_push_data_is_invalid() {
+    // PUSH1 4, JUMP, PUSH1 0x5b: position 4 holds a JUMPDEST byte, but as PUSH data
+    let mut vm =
+        new_vm_with_bytecode(Bytes::copy_from_slice(&[0x60, 4, 0x56, 0x60, 0x5b])).unwrap();
+    let mut current_call_frame = vm.call_frames.pop().unwrap();
+    let report = vm.execute(&mut current_call_frame).unwrap();
+    assert!(matches!(
+        report.result,
+        TxResult::Revert(VMError::InvalidJump)
+    ));
+}
+
+#[test]
+fn test_push_data_cut_short_by_end_of_code() {
+    // PUSH3 0xaa 0xbb, the missing byte is read as zero
+    let mut vm = new_vm_with_bytecode(Bytes::copy_from_slice(&[0x62, 0xaa, 0xbb])).unwrap();
+    let mut current_call_frame = vm.call_frames.pop().unwrap();
+    vm.execute(&mut current_call_frame).unwrap();
+    assert_eq!(
+        current_call_frame.stack.stack.first().unwrap(),
+        &U256::from(0xaabb00)
+    );
+}
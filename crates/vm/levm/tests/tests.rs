@@ -2,16 +2,27 @@
 #![allow(clippy::unwrap_used)]
 
 use bytes::Bytes;
-use ethrex_core::{types::TxKind, Address, H256, U256};
+use ethrex_core::{
+    types::{Fork, Log, TxKind},
+    Address, H256, U256,
+};
 use ethrex_levm::{
-    account::Account,
+    account::{code_hash, Account, AccountInfo, StorageSlot},
+    code::{AnalyzedCode, CodeCache, CODE_PADDING},
     constants::*,
-    db::{cache, CacheDB, Db},
-    errors::{TxResult, VMError},
+    db::{cache, AccountChange, BlockState, CacheDB, Database, Db},
+    eof::{ContainerKind, EofContainer, EofError, EOF_MAGIC_HASH},
+    errors::{
+        DatabaseError, InternalError, TransactionReport, TxResult, TxValidationError, VMError,
+    },
     gas_cost,
+    opcodes::Opcode,
     operations::Operation,
-    utils::{new_vm_with_ops, new_vm_with_ops_addr_bal_db, new_vm_with_ops_db, ops_to_bytecode},
-    vm::{word_to_address, Storage, VM},
+    utils::{
+        new_vm_with_bytecode, new_vm_with_ops, new_vm_with_ops_addr_bal_db, new_vm_with_ops_db,
+        ops_to_bytecode,
+    },
+    vm::{address_to_word, word_to_address, Storage, VM},
     Environment,
 };
 use std::{collections::HashMap, sync::Arc};
@@ -2991,7 +3002,8 @@ fn sstore_op() {
     key.to_big_endian(&mut bytes);
     let key = H256::from(bytes);
 
-    let (storage_slot, _storage_slot_was_cold) = vm.access_storage_slot(sender_address, key);
+    let (storage_slot, _storage_slot_was_cold) =
+        vm.access_storage_slot(sender_address, key).unwrap();
 
     assert_eq!(value, storage_slot.current_value);
 }
@@ -3104,7 +3116,7 @@ fn log0() {
     let mut current_call_frame = vm.call_frames.pop().unwrap();
     vm.execute(&mut current_call_frame).unwrap();
 
-    let logs = &vm.current_call_frame_mut().unwrap().logs;
+    let logs = &vm.accrued_substate.logs;
     let data = [0xff_u8; 32].as_slice();
     assert_eq!(logs.len(), 1);
     assert_eq!(logs[0].data, data.to_vec());
@@ -3134,7 +3146,7 @@ fn log1() {
     let mut current_call_frame = vm.call_frames.pop().unwrap();
     vm.execute(&mut current_call_frame).unwrap();
 
-    let logs = &vm.current_call_frame_mut().unwrap().logs;
+    let logs = &vm.accrued_substate.logs;
     let data = [0xff_u8; 32].as_slice();
     assert_eq!(logs.len(), 1);
     assert_eq!(logs[0].data, data.to_vec());
@@ -3167,7 +3179,7 @@ fn log2() {
     let mut current_call_frame = vm.call_frames.pop().unwrap();
     vm.execute(&mut current_call_frame).unwrap();
 
-    let logs = &vm.current_call_frame_mut().unwrap().logs;
+    let logs = &vm.accrued_substate.logs;
     let data = [0xff_u8; 32].as_slice();
     assert_eq!(logs.len(), 1);
     assert_eq!(logs[0].data, data.to_vec());
@@ -3206,7 +3218,7 @@ fn log3() {
     let mut current_call_frame = vm.call_frames.pop().unwrap();
     vm.execute(&mut current_call_frame).unwrap();
 
-    let logs = &vm.current_call_frame_mut().unwrap().logs;
+    let logs = &vm.accrued_substate.logs;
     let data = [0xff_u8; 32].as_slice();
     assert_eq!(logs.len(), 1);
     assert_eq!(logs[0].data, data.to_vec());
@@ -3252,7 +3264,7 @@ fn log4() {
     let mut current_call_frame = vm.call_frames.pop().unwrap();
     vm.execute(&mut current_call_frame).unwrap();
 
-    let logs = &vm.current_call_frame_mut().unwrap().logs;
+    let logs = &vm.accrued_substate.logs;
     let data = [0xff_u8; 32].as_slice();
     assert_eq!(logs.len(), 1);
     assert_eq!(logs[0].data, data.to_vec());
@@ -3286,7 +3298,7 @@ fn log_with_0_data_size() {
     let mut current_call_frame = vm.call_frames.pop().unwrap();
     vm.execute(&mut current_call_frame).unwrap();
 
-    let logs = &vm.current_call_frame_mut().unwrap().logs;
+    let logs = &vm.accrued_substate.logs;
     assert_eq!(logs.len(), 1);
     assert_eq!(logs[0].data, Vec::new());
     assert_eq!(logs[0].topics.len(), 0);
@@ -3336,7 +3348,7 @@ fn log_with_data_in_memory_smaller_than_size() {
     let mut current_call_frame = vm.call_frames.pop().unwrap();
     vm.execute(&mut current_call_frame).unwrap();
 
-    let logs = &vm.current_call_frame_mut().unwrap().logs;
+    let logs = &vm.accrued_substate.logs;
     let mut data = vec![0_u8; 16];
     data.extend(vec![0xff_u8; 16]);
 
@@ -3371,7 +3383,7 @@ fn multiple_logs_of_different_types() {
     let mut current_call_frame = vm.call_frames.pop().unwrap();
     vm.execute(&mut current_call_frame).unwrap();
 
-    let logs = &vm.current_call_frame_mut().unwrap().logs;
+    let logs = &vm.accrued_substate.logs;
     let data = [0xff_u8; 32].as_slice();
     assert_eq!(logs.len(), 2);
     assert_eq!(logs[0].data, data.to_vec());
@@ -3430,7 +3442,7 @@ fn logs_from_multiple_callers() {
     let mut current_call_frame = vm.call_frames.pop().unwrap();
     vm.execute(&mut current_call_frame).unwrap();
 
-    assert_eq!(current_call_frame.logs.len(), 2)
+    assert_eq!(vm.accrued_substate.logs.len(), 2)
 }
 
 // #[test]
@@ -3689,20 +3701,15 @@ fn transient_store() {
 
     let mut vm = new_vm_with_ops(&operations).unwrap();
 
-    let current_call_frame = vm.current_call_frame_mut().unwrap();
-
-    assert!(current_call_frame.transient_storage.is_empty());
+    assert!(vm.transient_storage.is_empty());
 
     let mut current_call_frame = vm.call_frames.pop().unwrap();
     vm.execute(&mut current_call_frame).unwrap();
 
-    let current_call_frame = vm.current_call_frame_mut().unwrap();
+    let contract_address = vm.current_call_frame_mut().unwrap().to;
 
     assert_eq!(
-        *current_call_frame
-            .transient_storage
-            .get(&(current_call_frame.msg_sender, key))
-            .unwrap(),
+        *vm.transient_storage.get(&(contract_address, key)).unwrap(),
         value
     )
 }
@@ -3712,11 +3719,7 @@ fn transient_store_stack_underflow() {
     let operations = [Operation::Tstore, Operation::Stop];
 
     let mut vm = new_vm_with_ops(&operations).unwrap();
-    assert!(vm
-        .current_call_frame_mut()
-        .unwrap()
-        .transient_storage
-        .is_empty());
+    assert!(vm.transient_storage.is_empty());
 
     let mut current_call_frame = vm.call_frames.pop().unwrap();
     let tx_report = vm.execute(&mut current_call_frame).unwrap();
@@ -3740,12 +3743,9 @@ fn transient_load() {
 
     let mut vm = new_vm_with_ops(&operations).unwrap();
 
-    let caller = vm.current_call_frame_mut().unwrap().msg_sender;
+    let contract_address = vm.current_call_frame_mut().unwrap().to;
 
-    vm.current_call_frame_mut()
-        .unwrap()
-        .transient_storage
-        .insert((caller, key), value);
+    vm.transient_storage.insert((contract_address, key), value);
 
     let mut current_call_frame = vm.call_frames.pop().unwrap();
     vm.execute(&mut current_call_frame).unwrap();
@@ -4712,3 +4712,1220 @@ fn revert_sstore() {
 
     assert_eq!(vm.cache, cache_backup);
 }
+
+fn transfer_env(origin: Address) -> Environment {
+    let mut env = Environment::default_from_address(origin);
+    env.gas_limit = U256::from(21000);
+    env.block_gas_limit = U256::from(30_000_000);
+    env
+}
+
+#[test]
+fn block_state_carries_changes_between_transactions() {
+    let sender = Address::from_low_u64_be(1000);
+    let recipient = Address::from_low_u64_be(2000);
+    let db = Db::new().with_accounts(HashMap::from([(
+        sender,
+        Account::default().with_balance(U256::from(100)),
+    )]));
+    let mut block_state = BlockState::new(Arc::new(db));
+
+    for _ in 0..2 {
+        let mut vm = VM::new(
+            TxKind::Call(recipient),
+            transfer_env(sender),
+            U256::from(10),
+            Bytes::new(),
+            block_state.db(),
+            block_state.transaction_cache(),
+        )
+        .unwrap();
+        let report = vm.transact().unwrap();
+        assert!(report.is_success());
+        block_state.commit(report.new_state).unwrap();
+    }
+
+    let changes: HashMap<Address, AccountChange> = block_state
+        .changes()
+        .into_iter()
+        .map(|change| (change.address, change))
+        .collect();
+    assert_eq!(changes.len(), 2);
+    assert_eq!(changes[&sender].info.nonce, 2);
+    assert_eq!(changes[&sender].info.balance, U256::from(80));
+    assert_eq!(changes[&recipient].info.balance, U256::from(20));
+}
+
+#[test]
+fn block_state_reports_storage_changed_by_the_block() {
+    let contract = Address::from_low_u64_be(42);
+    let key = H256::from_low_u64_be(1);
+    let mut block_state = BlockState::new(Arc::new(Db::new()));
+
+    // The first transaction writes the slot and the second one restores its value
+    for value in [5, 0] {
+        let mut cache = block_state.transaction_cache();
+        let account = cache.entry(contract).or_default();
+        let slot = account.storage.entry(key).or_default();
+        slot.current_value = U256::from(value);
+        block_state.commit(cache).unwrap();
+    }
+    assert!(block_state.changes().is_empty());
+
+    let mut cache = block_state.transaction_cache();
+    cache
+        .get_mut(&contract)
+        .unwrap()
+        .storage
+        .get_mut(&key)
+        .unwrap()
+        .current_value = U256::from(7);
+    block_state.commit(cache).unwrap();
+
+    let changes = block_state.changes();
+    assert_eq!(changes.len(), 1);
+    assert!(!changes[0].info_changed);
+    assert_eq!(changes[0].storage, HashMap::from([(key, U256::from(7))]));
+}
+
+#[test]
+fn block_state_reports_changes_of_a_single_transaction() {
+    let sender = Address::from_low_u64_be(1000);
+    let recipient = Address::from_low_u64_be(2000);
+    let db = Db::new().with_accounts(HashMap::from([(
+        sender,
+        Account::default().with_balance(U256::from(100)),
+    )]));
+    let mut block_state = BlockState::new(Arc::new(db));
+
+    for expected_nonce in 1..=2 {
+        let mut vm = VM::new(
+            TxKind::Call(recipient),
+            transfer_env(sender),
+            U256::from(10),
+            Bytes::new(),
+            block_state.db(),
+            block_state.transaction_cache(),
+        )
+        .unwrap();
+        let report = vm.transact().unwrap();
+
+        // Changes are relative to the state left by the previous transaction
+        let changes: HashMap<Address, AccountChange> = block_state
+            .transaction_changes(&report.new_state)
+            .unwrap()
+            .into_iter()
+            .map(|change| (change.address, change))
+            .collect();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[&sender].info.nonce, expected_nonce);
+        assert_eq!(
+            changes[&recipient].info.balance,
+            U256::from(10 * expected_nonce)
+        );
+        block_state.commit(report.new_state).unwrap();
+    }
+}
+
+#[test]
+fn block_state_withdrawals_increase_balance() {
+    let address = Address::from_low_u64_be(3000);
+    let mut block_state = BlockState::new(Arc::new(Db::new()));
+
+    block_state
+        .increase_account_balance(address, U256::from(5))
+        .unwrap();
+
+    let changes = block_state.changes();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].address, address);
+    assert_eq!(changes[0].info.balance, U256::from(5));
+}
+
+// Database whose storage can't be read, as when a trie node is missing
+struct MissingStorageDb(Db);
+
+impl Database for MissingStorageDb {
+    fn get_account_info(&self, address: Address) -> Result<AccountInfo, DatabaseError> {
+        self.0.get_account_info(address)
+    }
+
+    fn get_storage_slot(&self, _address: Address, _key: H256) -> Result<U256, DatabaseError> {
+        Err(DatabaseError::Custom("missing trie node".to_string()))
+    }
+
+    fn get_block_hash(&self, block_number: u64) -> Result<Option<H256>, DatabaseError> {
+        self.0.get_block_hash(block_number)
+    }
+}
+
+#[test]
+fn database_errors_halt_the_transaction() {
+    let sender = Address::from_low_u64_be(1000);
+    let contract = Address::from_low_u64_be(2000);
+    let bytecode = ops_to_bytecode(&[
+        Operation::Push((1, U256::zero())),
+        Operation::Sload,
+        Operation::Stop,
+    ])
+    .unwrap();
+    let db = Db::new().with_accounts(HashMap::from([
+        (sender, Account::default().with_balance(U256::from(100))),
+        (contract, Account::default().with_bytecode(bytecode)),
+    ]));
+
+    let mut env = transfer_env(sender);
+    env.gas_limit = U256::from(100_000);
+    let mut vm = VM::new(
+        TxKind::Call(contract),
+        env,
+        U256::zero(),
+        Bytes::new(),
+        Arc::new(MissingStorageDb(db)),
+        CacheDB::default(),
+    )
+    .unwrap();
+
+    // The error is returned instead of reverting the transaction as if the slot were empty
+    assert_eq!(
+        vm.transact().unwrap_err(),
+        VMError::Internal(InternalError::Database(DatabaseError::Custom(
+            "missing trie node".to_string()
+        )))
+    );
+}
+
+#[test]
+fn call_fails_at_max_call_depth() {
+    let callee_address = Address::from_low_u64_be(2);
+    let callee_account = Account::default()
+        .with_balance(50000.into())
+        .with_bytecode(callee_return_bytecode(U256::from(0xAAAAAAA)));
+
+    let caller_ops = vec![
+        Operation::Push((32, U256::from(32))),      // ret_size
+        Operation::Push((32, U256::from(0))),       // ret_offset
+        Operation::Push((32, U256::from(0))),       // args_size
+        Operation::Push((32, U256::from(0))),       // args_offset
+        Operation::Push((32, U256::zero())),        // value
+        Operation::Push((32, U256::from(2))),       // address
+        Operation::Push((32, U256::from(100_000))), // gas
+        Operation::Call,
+        Operation::Push((1, U256::from(0x2a))),
+        Operation::Stop,
+    ];
+
+    let mut db = Db::new();
+    db.add_accounts(vec![(callee_address, callee_account)]);
+
+    let mut vm = new_vm_with_ops_addr_bal_db(
+        ops_to_bytecode(&caller_ops).unwrap(),
+        Address::from_low_u64_be(1),
+        U256::zero(),
+        db,
+        CacheDB::default(),
+    )
+    .unwrap();
+
+    let mut current_call_frame = vm.call_frames.pop().unwrap();
+    current_call_frame.depth = MAX_CALL_DEPTH;
+    let report = vm.execute(&mut current_call_frame).unwrap();
+
+    // The call fails instead of halting the caller, which keeps running
+    assert!(report.is_success());
+    let call_frame = vm.current_call_frame_mut().unwrap();
+    assert_eq!(call_frame.stack.pop().unwrap(), U256::from(0x2a));
+    assert_eq!(call_frame.stack.pop().unwrap(), U256::zero());
+    assert_eq!(call_frame.memory.load(0).unwrap(), U256::zero());
+}
+
+#[test]
+fn create_fails_at_max_call_depth() {
+    // Code that returns the value 0xffffffff putting it in memory
+    let initialization_code = hex::decode("63FFFFFFFF6000526004601CF3").unwrap();
+    let operations = [
+        vec![
+            Operation::Push((13, U256::from_big_endian(&initialization_code))),
+            Operation::Push0,
+            Operation::Mstore,
+        ],
+        create_opcodes(13, 19, 0),
+    ]
+    .concat();
+
+    let mut vm = new_vm_with_ops(&operations).unwrap();
+    let sender_address = vm.current_call_frame_mut().unwrap().to;
+    let sender_nonce = vm.get_account(sender_address).unwrap().info.nonce;
+
+    let mut current_call_frame = vm.call_frames.pop().unwrap();
+    current_call_frame.depth = MAX_CALL_DEPTH;
+    vm.execute(&mut current_call_frame).unwrap();
+
+    let call_frame = vm.current_call_frame_mut().unwrap();
+    assert_eq!(call_frame.stack.pop().unwrap(), U256::zero());
+    assert_eq!(
+        vm.get_account(sender_address).unwrap().info.nonce,
+        sender_nonce
+    );
+}
+
+#[test]
+fn transaction_is_charged_intrinsic_gas() {
+    let sender = Address::from_low_u64_be(1000);
+    let recipient = Address::from_low_u64_be(2000);
+    let db = Db::new().with_accounts(HashMap::from([(
+        sender,
+        Account::default().with_balance(U256::from(100)),
+    )]));
+
+    let mut env = transfer_env(sender);
+    env.gas_limit = U256::from(30_000);
+    env.tx_access_list = vec![(recipient, vec![H256::zero(), H256::repeat_byte(1)])];
+    let mut vm = VM::new(
+        TxKind::Call(recipient),
+        env,
+        U256::zero(),
+        Bytes::from(vec![0x01, 0x00, 0x02]),
+        Arc::new(db),
+        CacheDB::default(),
+    )
+    .unwrap();
+    let report = vm.transact().unwrap();
+
+    // Base cost, two non-zero and one zero data bytes, and an access list with two keys
+    assert!(report.is_success());
+    assert_eq!(report.gas_used, 21000 + 2 * 16 + 4 + 2400 + 2 * 1900);
+}
+
+#[test]
+fn transaction_with_gas_limit_below_intrinsic_gas_is_invalid() {
+    let sender = Address::from_low_u64_be(1000);
+    let db = Db::new().with_accounts(HashMap::from([(
+        sender,
+        Account::default().with_balance(U256::from(100)),
+    )]));
+
+    let mut vm = VM::new(
+        TxKind::Call(Address::from_low_u64_be(2000)),
+        transfer_env(sender),
+        U256::zero(),
+        Bytes::from(vec![0x01]),
+        Arc::new(db),
+        CacheDB::default(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        vm.transact().unwrap_err(),
+        VMError::TxValidation(TxValidationError::IntrinsicGasTooLow)
+    );
+}
+
+fn blob_transaction_vm(
+    sender_balance: U256,
+    blob_hashes: Vec<H256>,
+    max_fee_per_blob_gas: U256,
+) -> VM {
+    let sender = Address::from_low_u64_be(1000);
+    let db = Db::new().with_accounts(HashMap::from([(
+        sender,
+        Account::default().with_balance(sender_balance),
+    )]));
+
+    let mut env = transfer_env(sender);
+    env.block_excess_blob_gas = Some(U256::zero());
+    env.tx_blob_hashes = blob_hashes;
+    env.tx_max_fee_per_blob_gas = Some(max_fee_per_blob_gas);
+    VM::new(
+        TxKind::Call(Address::from_low_u64_be(2000)),
+        env,
+        U256::zero(),
+        Bytes::new(),
+        Arc::new(db),
+        CacheDB::default(),
+    )
+    .unwrap()
+}
+
+fn kzg_versioned_hash() -> H256 {
+    let mut hash = H256::repeat_byte(0xaa);
+    hash.0[0] = VERSIONED_HASH_VERSION_KZG;
+    hash
+}
+
+#[test]
+fn blob_transaction_pays_blob_gas_fee() {
+    let sender = Address::from_low_u64_be(1000);
+    let mut vm = blob_transaction_vm(
+        U256::from(1_000_000),
+        vec![kzg_versioned_hash(); 2],
+        U256::from(2),
+    );
+    let report = vm.transact().unwrap();
+
+    // The blob gas price is 1 without excess blob gas, the max fee per blob gas is only checked
+    let blob_gas_fee = BLOB_GAS_PER_BLOB * 2;
+    assert!(report.is_success());
+    assert_eq!(
+        report.new_state[&sender].info.balance,
+        U256::from(1_000_000) - blob_gas_fee
+    );
+}
+
+#[test]
+fn blob_transaction_validation() {
+    let cases = [
+        (vec![], U256::one(), TxValidationError::Type3TxZeroBlobs),
+        (
+            vec![H256::repeat_byte(0x02)],
+            U256::one(),
+            TxValidationError::Type3TxInvalidBlobVersionedHash,
+        ),
+        (
+            vec![kzg_versioned_hash(); MAX_BLOB_COUNT + 1],
+            U256::one(),
+            TxValidationError::Type3TxBlobCountExceeded,
+        ),
+        (
+            vec![kzg_versioned_hash()],
+            U256::zero(),
+            TxValidationError::InsufficientMaxFeePerBlobGas,
+        ),
+    ];
+    for (blob_hashes, max_fee_per_blob_gas, expected_error) in cases {
+        let mut vm = blob_transaction_vm(U256::from(1_000_000), blob_hashes, max_fee_per_blob_gas);
+        assert_eq!(
+            vm.transact().unwrap_err(),
+            VMError::TxValidation(expected_error)
+        );
+    }
+
+    // The sender must be able to pay the max fee of the blobs up front
+    let mut vm = blob_transaction_vm(
+        BLOB_GAS_PER_BLOB * 10 - 1,
+        vec![kzg_versioned_hash()],
+        U256::from(10),
+    );
+    assert_eq!(
+        vm.transact().unwrap_err(),
+        VMError::TxValidation(TxValidationError::InsufficientAccountFunds)
+    );
+}
+
+#[test]
+fn failed_create_transaction_consumes_gas_and_keeps_nonce() {
+    let sender = Address::from_low_u64_be(1000);
+    let db = Db::new().with_accounts(HashMap::from([(
+        sender,
+        Account::default().with_balance(U256::from(1_000_000)),
+    )]));
+    // Initcode that stores a value and then runs into an invalid opcode
+    let initcode = ops_to_bytecode(&[
+        Operation::Push((1, U256::one())),
+        Operation::Push0,
+        Operation::Sstore,
+        Operation::Invalid,
+    ])
+    .unwrap();
+
+    let mut env = transfer_env(sender);
+    env.gas_limit = U256::from(100_000);
+    env.gas_price = U256::one();
+    let mut vm = VM::new(
+        TxKind::Create,
+        env,
+        U256::from(10),
+        initcode,
+        Arc::new(db),
+        CacheDB::default(),
+    )
+    .unwrap();
+    let contract_address = vm.current_call_frame_mut().unwrap().to;
+    let report = vm.transact().unwrap();
+
+    assert!(!report.is_success());
+    assert_eq!(report.gas_used, 100_000);
+    assert_eq!(report.created_address, None);
+    assert!(!report.new_state.contains_key(&contract_address));
+    let sender_account = &report.new_state[&sender];
+    assert_eq!(sender_account.info.nonce, 1);
+    assert_eq!(sender_account.info.balance, U256::from(1_000_000 - 100_000));
+}
+
+/// Code that stores 1 in slot 0 and then self-destructs sending its balance to the beneficiary
+fn store_and_selfdestruct_code(beneficiary: Address) -> Bytes {
+    ops_to_bytecode(&[
+        Operation::Push((1, U256::one())),
+        Operation::Push0,
+        Operation::Sstore,
+        Operation::Push((20, address_to_word(beneficiary))),
+        Operation::SelfDestruct,
+    ])
+    .unwrap()
+}
+
+#[test]
+fn selfdestruct_keeps_accounts_not_created_in_the_transaction() {
+    let sender = Address::from_low_u64_be(1000);
+    let contract = Address::from_low_u64_be(2000);
+    let beneficiary = Address::from_low_u64_be(3000);
+    let contract_code = store_and_selfdestruct_code(beneficiary);
+    let db = Db::new().with_accounts(HashMap::from([
+        (
+            sender,
+            Account::default().with_balance(U256::from(10_000_000)),
+        ),
+        (
+            contract,
+            Account::default()
+                .with_balance(U256::from(50))
+                .with_bytecode(contract_code.clone())
+                .with_nonce(1),
+        ),
+    ]));
+    // A creation transaction whose initcode calls the existing contract
+    let initcode = ops_to_bytecode(&[
+        Operation::Push0,
+        Operation::Push0,
+        Operation::Push0,
+        Operation::Push0,
+        Operation::Push0,
+        Operation::Push((20, address_to_word(contract))),
+        Operation::Gas,
+        Operation::Call,
+        Operation::Stop,
+    ])
+    .unwrap();
+
+    let mut env = transfer_env(sender);
+    env.gas_limit = U256::from(1_000_000);
+    env.gas_price = U256::one();
+    let mut vm = VM::new(
+        TxKind::Create,
+        env,
+        U256::zero(),
+        initcode,
+        Arc::new(db),
+        CacheDB::default(),
+    )
+    .unwrap();
+    let report = vm.transact().unwrap();
+
+    // The contract only sends its balance away, as it existed before the transaction (EIP-6780)
+    assert!(report.is_success());
+    let contract_account = &report.new_state[&contract];
+    assert_eq!(contract_account.info.bytecode, contract_code);
+    assert_eq!(contract_account.info.nonce, 1);
+    assert_eq!(contract_account.info.balance, U256::zero());
+    assert_eq!(
+        contract_account.storage[&H256::zero()].current_value,
+        U256::one()
+    );
+    assert_eq!(report.new_state[&beneficiary].info.balance, U256::from(50));
+}
+
+#[test]
+fn selfdestruct_removes_accounts_created_by_opcodes_in_the_transaction() {
+    let sender = Address::from_low_u64_be(1000);
+    let factory = Address::from_low_u64_be(2000);
+    let beneficiary = Address::from_low_u64_be(3000);
+    // The factory creates a contract whose initcode self-destructs, storing its address in slot 0
+    let initcode = store_and_selfdestruct_code(beneficiary);
+    let initcode_size = initcode.len();
+    let factory_code = ops_to_bytecode(&[
+        Operation::Push((32, U256::from_big_endian(&initcode))),
+        Operation::Push0,
+        Operation::Mstore,
+        Operation::Push((1, U256::from(initcode_size))),
+        Operation::Push((1, U256::from(32 - initcode_size))),
+        Operation::Push0,
+        Operation::Create,
+        Operation::Push0,
+        Operation::Sstore,
+        Operation::Stop,
+    ])
+    .unwrap();
+    let db = Db::new().with_accounts(HashMap::from([
+        (
+            sender,
+            Account::default().with_balance(U256::from(10_000_000)),
+        ),
+        (factory, Account::default().with_bytecode(factory_code)),
+    ]));
+
+    let mut env = transfer_env(sender);
+    env.gas_limit = U256::from(1_000_000);
+    env.gas_price = U256::one();
+    let mut vm = VM::new(
+        TxKind::Call(factory),
+        env,
+        U256::zero(),
+        Bytes::new(),
+        Arc::new(db),
+        CacheDB::default(),
+    )
+    .unwrap();
+    let report = vm.transact().unwrap();
+
+    assert!(report.is_success());
+    let created = word_to_address(report.new_state[&factory].storage[&H256::zero()].current_value);
+    assert_ne!(created, Address::zero());
+    // Created and destroyed in the same transaction, so nothing of it is left
+    let created_account = &report.new_state[&created];
+    assert_eq!(created_account.info, AccountInfo::default());
+    assert!(created_account
+        .storage
+        .values()
+        .all(|slot| slot.current_value.is_zero()));
+}
+
+fn contract_call_vm(mut db: Db, contract: Address, access_list: Vec<(Address, Vec<H256>)>) -> VM {
+    let sender = Address::from_low_u64_be(1000);
+    db.add_accounts(vec![(
+        sender,
+        Account::default().with_balance(U256::from(1_000_000)),
+    )]);
+    let mut env = transfer_env(sender);
+    env.gas_limit = U256::from(100_000);
+    env.tx_access_list = access_list;
+    VM::new(
+        TxKind::Call(contract),
+        env,
+        U256::zero(),
+        Bytes::new(),
+        Arc::new(db),
+        CacheDB::default(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn access_list_and_precompiles_start_warm() {
+    let contract = Address::from_low_u64_be(2000);
+    let code = ops_to_bytecode(&[
+        Operation::Push((1, U256::zero())),
+        Operation::Sload,
+        Operation::Pop,
+        Operation::Push((1, U256::one())),
+        Operation::Balance,
+        Operation::Pop,
+        Operation::Stop,
+    ])
+    .unwrap();
+    let db = Db::new().with_accounts(HashMap::from([(
+        contract,
+        Account::default().with_bytecode(code),
+    )]));
+
+    let mut vm = contract_call_vm(db, contract, vec![(contract, vec![H256::zero()])]);
+    let report = vm.transact().unwrap();
+
+    // Intrinsic gas with the access list, then warm accesses to the slot and the ecrecover precompile
+    let intrinsic_gas = 21000 + 2400 + 1900;
+    assert!(report.is_success());
+    assert_eq!(report.gas_used, intrinsic_gas + 3 + 100 + 2 + 3 + 100 + 2);
+}
+
+#[test]
+fn reverted_call_discards_its_substate() {
+    let callee_address = Address::from_low_u64_be(2);
+    let accessed_address = Address::from_low_u64_be(0xabcd);
+    let callee_ops = [
+        Operation::Push((20, address_to_word(accessed_address))),
+        Operation::Balance,
+        Operation::Pop,
+        Operation::Push0,
+        Operation::Push0,
+        Operation::Log(0),
+        Operation::Push0,
+        Operation::Push0,
+        Operation::Revert,
+    ];
+    let callee_account = Account::default()
+        .with_balance(50000.into())
+        .with_bytecode(ops_to_bytecode(&callee_ops).unwrap());
+
+    let caller_ops = vec![
+        Operation::Push((32, U256::zero())),        // ret_size
+        Operation::Push((32, U256::zero())),        // ret_offset
+        Operation::Push((32, U256::zero())),        // args_size
+        Operation::Push((32, U256::zero())),        // args_offset
+        Operation::Push((32, U256::zero())),        // value
+        Operation::Push((32, U256::from(2))),       // address
+        Operation::Push((32, U256::from(100_000))), // gas
+        Operation::Call,
+        Operation::Stop,
+    ];
+
+    let mut db = Db::new();
+    db.add_accounts(vec![(callee_address, callee_account)]);
+
+    let mut vm = new_vm_with_ops_addr_bal_db(
+        ops_to_bytecode(&caller_ops).unwrap(),
+        Address::from_low_u64_be(1),
+        U256::zero(),
+        db,
+        CacheDB::default(),
+    )
+    .unwrap();
+
+    let mut current_call_frame = vm.call_frames.pop().unwrap();
+    vm.execute(&mut current_call_frame).unwrap();
+
+    assert_eq!(
+        vm.current_call_frame_mut().unwrap().stack.pop().unwrap(),
+        U256::zero()
+    );
+    assert!(vm.accrued_substate.logs.is_empty());
+    assert!(!vm
+        .accrued_substate
+        .accessed_addresses
+        .contains(&accessed_address));
+    // The callee was accessed by the caller, which didn't revert
+    assert!(vm
+        .accrued_substate
+        .accessed_addresses
+        .contains(&callee_address));
+}
+
+#[test]
+fn clearing_a_storage_slot_is_refunded() {
+    let contract = Address::from_low_u64_be(2000);
+    let code = ops_to_bytecode(&[
+        Operation::Push0,
+        Operation::Push0,
+        Operation::Sstore,
+        Operation::Stop,
+    ])
+    .unwrap();
+    let storage = HashMap::from([(
+        H256::zero(),
+        StorageSlot {
+            original_value: U256::one(),
+            current_value: U256::one(),
+        },
+    )]);
+    let db = Db::new().with_accounts(HashMap::from([(
+        contract,
+        Account::default().with_bytecode(code).with_storage(storage),
+    )]));
+
+    let mut vm = contract_call_vm(db, contract, Vec::new());
+    let report = vm.transact().unwrap();
+
+    // A cold reset of the slot costs 2100 + 2900, and clearing it refunds 4800
+    let gas_used_before_refund = 21000 + 2 + 2 + 2100 + 2900;
+    assert!(report.is_success());
+    assert_eq!(report.gas_refunded, 4800);
+    assert_eq!(report.gas_used, gas_used_before_refund - 4800);
+}
+
+#[test]
+fn reverting_to_a_checkpoint_undoes_the_changes_made_after_it() {
+    let mut vm = new_vm_with_ops(&[Operation::Stop]).unwrap();
+    let address = Address::from_low_u64_be(0xabcd);
+    let key = H256::from_low_u64_be(1);
+
+    vm.increase_account_balance(address, U256::from(10))
+        .unwrap();
+    let checkpoint = vm.journal.checkpoint();
+    let journal_length = vm.journal.len();
+
+    vm.increase_account_balance(address, U256::from(5)).unwrap();
+    vm.increment_account_nonce(address).unwrap();
+    vm.update_account_storage(address, key, U256::from(7))
+        .unwrap();
+    vm.update_transient_storage(address, U256::one(), U256::from(3));
+    vm.add_accessed_address(address);
+    vm.add_accessed_storage_key(address, key);
+    vm.update_refunded_gas(U256::from(4800));
+    vm.add_log(Log {
+        address,
+        topics: Vec::new(),
+        data: Bytes::new(),
+    });
+    vm.add_to_selfdestruct_set(address);
+    let created = Address::from_low_u64_be(0xdcba);
+    vm.insert_new_account(created, Account::default());
+
+    vm.revert_to_checkpoint(checkpoint).unwrap();
+
+    let account = vm.get_account(address).unwrap();
+    assert_eq!(account.info.balance, U256::from(10));
+    assert_eq!(account.info.nonce, 0);
+    assert!(account.storage.is_empty());
+    assert!(vm.transient_storage.is_empty());
+    assert!(!vm.accrued_substate.accessed_addresses.contains(&address));
+    assert!(!vm.accrued_substate.accessed_storage_keys[&address].contains(&key));
+    assert_eq!(vm.accrued_substate.refunded_gas, U256::zero());
+    assert!(vm.accrued_substate.logs.is_empty());
+    assert!(vm.accrued_substate.selfdestruct_set.is_empty());
+    assert!(vm.accrued_substate.created_accounts.is_empty());
+    assert!(!cache::is_account_cached(&vm.cache, &created));
+    assert_eq!(vm.journal.len(), journal_length);
+}
+
+#[test]
+fn reverted_call_undoes_its_value_transfer() {
+    let callee_address = Address::from_low_u64_be(2);
+    let callee_account = Account::default().with_bytecode(
+        ops_to_bytecode(&[Operation::Push0, Operation::Push0, Operation::Revert]).unwrap(),
+    );
+
+    let caller_ops = vec![
+        Operation::Push((32, U256::zero())),        // ret_size
+        Operation::Push((32, U256::zero())),        // ret_offset
+        Operation::Push((32, U256::zero())),        // args_size
+        Operation::Push((32, U256::zero())),        // args_offset
+        Operation::Push((32, U256::from(10))),      // value
+        Operation::Push((32, U256::from(2))),       // address
+        Operation::Push((32, U256::from(100_000))), // gas
+        Operation::Call,
+        Operation::Stop,
+    ];
+
+    let mut db = Db::new();
+    db.add_accounts(vec![(callee_address, callee_account)]);
+
+    let mut vm = new_vm_with_ops_addr_bal_db(
+        ops_to_bytecode(&caller_ops).unwrap(),
+        Address::from_low_u64_be(1),
+        U256::zero(),
+        db,
+        CacheDB::default(),
+    )
+    .unwrap();
+
+    let mut current_call_frame = vm.call_frames.pop().unwrap();
+    let caller_address = current_call_frame.to;
+    let caller_balance = vm.get_account(caller_address).unwrap().info.balance;
+    vm.execute(&mut current_call_frame).unwrap();
+
+    assert_eq!(
+        vm.current_call_frame_mut().unwrap().stack.pop().unwrap(),
+        U256::zero()
+    );
+    assert_eq!(
+        vm.get_account(callee_address).unwrap().info.balance,
+        U256::zero()
+    );
+    assert_eq!(
+        vm.get_account(caller_address).unwrap().info.balance,
+        caller_balance
+    );
+}
+
+#[test]
+fn failed_create_pushes_zero_and_leaves_no_account() {
+    // Initcode that reverts right away
+    let initialization_code = hex::decode("60006000fd").unwrap();
+    let operations = [
+        vec![
+            Operation::Push((5, U256::from_big_endian(&initialization_code))),
+            Operation::Push0,
+            Operation::Mstore,
+        ],
+        create_opcodes(5, 27, 0),
+    ]
+    .concat();
+
+    let mut vm = new_vm_with_ops(&operations).unwrap();
+    let mut current_call_frame = vm.call_frames.pop().unwrap();
+    let creator = current_call_frame.msg_sender;
+    vm.execute(&mut current_call_frame).unwrap();
+
+    // The creator's nonce is still increased
+    let creator_nonce = vm.get_account(creator).unwrap().info.nonce;
+    let new_address = VM::calculate_create_address(creator, creator_nonce).unwrap();
+    assert_eq!(
+        vm.current_call_frame_mut().unwrap().stack.pop().unwrap(),
+        U256::zero()
+    );
+    assert!(!vm.cache.contains_key(&new_address));
+}
+
+#[test]
+fn code_analysis_skips_push_data_when_finding_jumpdests() {
+    // JUMPDEST, PUSH2 0x5b5b, JUMPDEST
+    let bytecode = Bytes::from(vec![0x5b, 0x61, 0x5b, 0x5b, 0x5b]);
+    let decoded = AnalyzedCode::new(&bytecode, true);
+    let undecoded = AnalyzedCode::new(&bytecode, false);
+
+    for code in [&decoded, &undecoded] {
+        assert_eq!(code.bytecode(), &bytecode);
+        assert!(code.is_jumpdest(0));
+        assert!(!code.is_jumpdest(2));
+        assert!(!code.is_jumpdest(3));
+        assert!(code.is_jumpdest(4));
+        assert!(!code.is_jumpdest(5));
+        // Past the end of the code there's only padding, which reads as STOP
+        assert_eq!(code.opcode_at(5), Some(Opcode::STOP));
+        assert_eq!(code.push_data(4, 32).unwrap()[1..], [0; 31]);
+    }
+    for pc in 0..bytecode.len() + CODE_PADDING + 1 {
+        assert_eq!(decoded.opcode_at(pc), undecoded.opcode_at(pc));
+    }
+}
+
+#[test]
+fn code_cache_shares_analyses_by_code_hash() {
+    let cache = CodeCache::new(2, true);
+    let first_code = Bytes::from(vec![0x5b]);
+    let first_hash = code_hash(&first_code);
+
+    let analysis = cache.get_or_analyze(first_hash, &first_code);
+    assert!(Arc::ptr_eq(
+        &analysis,
+        &cache.get_or_analyze(first_hash, &first_code)
+    ));
+
+    // Once it's full the cache starts over
+    for code in [vec![0x00], vec![0x01]] {
+        let code = Bytes::from(code);
+        cache.get_or_analyze(code_hash(&code), &code);
+    }
+    assert_eq!(cache.len(), 1);
+    assert!(!cache.contains(&first_hash));
+}
+
+#[test]
+fn transactions_running_the_same_code_share_its_analysis() {
+    let bytecode = ops_to_bytecode(&[Operation::Push0, Operation::Stop]).unwrap();
+    let first_vm = new_vm_with_bytecode(bytecode.clone()).unwrap();
+    let second_vm = new_vm_with_bytecode(bytecode).unwrap();
+
+    assert!(Arc::ptr_eq(
+        &first_vm.call_frames[0].code,
+        &second_vm.call_frames[0].code
+    ));
+}
+
+/// EOF container with the given code sections (inputs, outputs, max stack height, code),
+/// subcontainers and data
+fn eof_container(sections: &[(u8, u8, u16, &[u8])], containers: &[Bytes], data: &[u8]) -> Bytes {
+    let size = |len: usize| u16::try_from(len).unwrap().to_be_bytes();
+    let mut container = vec![0xef, 0x00, 0x01];
+    container.push(0x01);
+    container.extend_from_slice(&size(sections.len().checked_mul(4).unwrap()));
+    container.push(0x02);
+    container.extend_from_slice(&size(sections.len()));
+    for (_, _, _, code) in sections {
+        container.extend_from_slice(&size(code.len()));
+    }
+    if !containers.is_empty() {
+        container.push(0x03);
+        container.extend_from_slice(&size(containers.len()));
+        for subcontainer in containers {
+            container.extend_from_slice(&size(subcontainer.len()));
+        }
+    }
+    container.push(0x04);
+    container.extend_from_slice(&size(data.len()));
+    container.push(0x00);
+    for (inputs, outputs, max_stack_height, _) in sections {
+        container.extend_from_slice(&[*inputs, *outputs]);
+        container.extend_from_slice(&max_stack_height.to_be_bytes());
+    }
+    for (_, _, _, code) in sections {
+        container.extend_from_slice(code);
+    }
+    for subcontainer in containers {
+        container.extend_from_slice(subcontainer);
+    }
+    container.extend_from_slice(data);
+    container.into()
+}
+
+fn osaka_env(origin: Address) -> Environment {
+    let mut env = transfer_env(origin);
+    env.gas_limit = U256::from(1_000_000);
+    env.fork = Fork::Osaka;
+    env
+}
+
+#[test]
+fn traced_transfers_are_reported_as_logs() {
+    let sender = Address::from_low_u64_be(1000);
+    let contract = Address::from_low_u64_be(2000);
+    let recipient = Address::from_low_u64_be(3000);
+    let reverter = Address::from_low_u64_be(4000);
+    // Sends 3 wei to the recipient and 2 wei to a contract that reverts
+    let call_with_value = |value: u8, to: Address| {
+        let mut code = vec![0x60, 0, 0x60, 0, 0x60, 0, 0x60, 0, 0x60, value, 0x73];
+        code.extend_from_slice(to.as_bytes());
+        code.extend_from_slice(&[0x61, 0xff, 0xff, 0xf1, 0x50]);
+        code
+    };
+    let code = [call_with_value(3, recipient), call_with_value(2, reverter)].concat();
+    let db = Db::new().with_accounts(HashMap::from([
+        (sender, Account::default().with_balance(U256::from(100))),
+        (
+            contract,
+            Account::default()
+                .with_balance(U256::from(10))
+                .with_bytecode(Bytes::from(code)),
+        ),
+        (
+            reverter,
+            Account::default().with_bytecode(Bytes::from(vec![0x60, 0, 0x60, 0, 0xfd])),
+        ),
+    ]));
+    let mut env = transfer_env(sender);
+    env.gas_limit = U256::from(1_000_000);
+    env.trace_transfers = true;
+
+    let mut vm = VM::new(
+        TxKind::Call(contract),
+        env,
+        U256::from(5),
+        Bytes::new(),
+        Arc::new(db),
+        CacheDB::default(),
+    )
+    .unwrap();
+    let report = vm.transact().unwrap();
+
+    assert!(report.is_success());
+    let transfers: Vec<(Address, Address, U256)> = report
+        .logs
+        .iter()
+        .map(|log| {
+            assert_eq!(log.address, TRANSFER_LOG_EMITTER);
+            assert_eq!(log.topics[0], TRANSFER_EVENT_TOPIC);
+            (
+                Address::from(log.topics[1]),
+                Address::from(log.topics[2]),
+                U256::from_big_endian(&log.data),
+            )
+        })
+        .collect();
+    assert_eq!(
+        transfers,
+        vec![
+            (sender, contract, U256::from(5)),
+            (contract, recipient, U256::from(3)),
+        ]
+    );
+}
+
+/// Runs a call to `contract` with `code` and returns the report
+fn call_contract_with_code(
+    env: Environment,
+    code: Bytes,
+    other_accounts: Vec<(Address, Account)>,
+) -> (Address, TransactionReport) {
+    let contract = Address::from_low_u64_be(2000);
+    let mut accounts = vec![
+        (
+            env.origin,
+            Account::default().with_balance(U256::from(10_000_000)),
+        ),
+        (contract, Account::default().with_bytecode(code)),
+    ];
+    accounts.extend(other_accounts);
+    let mut db = Db::new();
+    db.add_accounts(accounts);
+    let mut vm = VM::new(
+        TxKind::Call(contract),
+        env,
+        U256::zero(),
+        Bytes::new(),
+        Arc::new(db),
+        CacheDB::default(),
+    )
+    .unwrap();
+    (contract, vm.transact().unwrap())
+}
+
+fn stored_value(report: &TransactionReport, address: Address) -> U256 {
+    report.new_state[&address].storage[&H256::zero()].current_value
+}
+
+#[test]
+fn eof_magic_hash_is_the_hash_of_the_magic() {
+    assert_eq!(
+        EOF_MAGIC_HASH,
+        code_hash(&Bytes::from_static(&[0xef, 0x00]))
+    );
+}
+
+#[test]
+fn eof_container_decodes_and_validates() {
+    let raw = Bytes::from(hex::decode("ef000101000402000100010400000000800000fe").unwrap());
+    let container = EofContainer::decode(raw.clone()).unwrap();
+
+    assert_eq!(container.code_section_count(), 1);
+    assert_eq!(container.type_section(0).unwrap().max_stack_height, 0);
+    assert!(container.type_section(0).unwrap().is_non_returning());
+    assert!(container.data().is_empty());
+    assert!(container.validate(ContainerKind::Runtime).is_ok());
+    assert_eq!(eof_container(&[(0, 0x80, 0, &[0xfe])], &[], &[]), raw);
+
+    // A container with bytes after it is only fine when they're split off as calldata
+    let mut with_trailing_data = raw.to_vec();
+    with_trailing_data.extend_from_slice(&[1, 2, 3]);
+    let with_trailing_data = Bytes::from(with_trailing_data);
+    assert_eq!(
+        EofContainer::decode(with_trailing_data.clone()),
+        Err(EofError::DanglingData)
+    );
+    let (container, trailing_data) =
+        EofContainer::decode_with_trailing_data(with_trailing_data).unwrap();
+    assert_eq!(container.raw(), &raw);
+    assert_eq!(trailing_data, Bytes::from_static(&[1, 2, 3]));
+
+    assert_eq!(
+        EofContainer::decode(raw.slice(..raw.len() - 1)),
+        Err(EofError::MissingBody)
+    );
+}
+
+#[test]
+fn eof_validation_rejects_invalid_code() {
+    // PUSH0, RJUMPI into the immediate of the PUSH1 after it
+    let jump_into_immediate = eof_container(
+        &[(0, 0x80, 1, &[0x5f, 0xe1, 0x00, 0x01, 0x60, 0x00, 0x00])],
+        &[],
+        &[],
+    );
+    // Legacy JUMP isn't allowed in EOF code
+    let disabled_opcode = eof_container(&[(0, 0x80, 1, &[0x5f, 0x56])], &[], &[]);
+    // The code can fall off its end
+    let unterminated = eof_container(&[(0, 0x80, 1, &[0x5f])], &[], &[]);
+    // Declared max stack height doesn't match the code
+    let wrong_max_stack = eof_container(&[(0, 0x80, 2, &[0x5f, 0x50, 0x00])], &[], &[]);
+    // Code section 1 is never called
+    let unreachable_section =
+        eof_container(&[(0, 0x80, 0, &[0x00]), (0, 0x80, 0, &[0x00])], &[], &[]);
+
+    for (code, expected_error) in [
+        (jump_into_immediate, EofError::JumpIntoImmediate),
+        (disabled_opcode, EofError::OpcodeDisabled),
+        (unterminated, EofError::MissingTerminatingInstruction),
+        (wrong_max_stack, EofError::MaxStackHeightMismatch),
+        (unreachable_section, EofError::UnreachableCodeSection),
+    ] {
+        let container = EofContainer::decode(code).unwrap();
+        assert_eq!(
+            container.validate(ContainerKind::Runtime),
+            Err(expected_error)
+        );
+    }
+}
+
+#[test]
+fn eof_code_follows_relative_jumps() {
+    // PUSH1 1, RJUMPI +5, PUSH1 7, RJUMP +2, PUSH1 9, PUSH0, SSTORE, STOP
+    let code = [
+        0x60, 0x01, 0xe1, 0x00, 0x05, 0x60, 0x07, 0xe0, 0x00, 0x02, 0x60, 0x09, 0x5f, 0x55, 0x00,
+    ];
+    let container = eof_container(&[(0, 0x80, 2, &code)], &[], &[]);
+    let (contract, report) =
+        call_contract_with_code(osaka_env(Address::from_low_u64_be(1000)), container, vec![]);
+
+    assert!(report.is_success());
+    assert_eq!(stored_value(&report, contract), U256::from(9));
+}
+
+#[test]
+fn eof_code_calls_functions_and_loads_data() {
+    // CALLF 1, PUSH0, SSTORE, STOP
+    let main = [0xe3, 0x00, 0x01, 0x5f, 0x55, 0x00];
+    // DATALOADN 0, RETF
+    let load_data = [0xd1, 0x00, 0x00, 0xe4];
+    let mut data = [0u8; 32];
+    data[31] = 42;
+    let container = eof_container(&[(0, 0x80, 2, &main), (0, 1, 1, &load_data)], &[], &data);
+    let (contract, report) =
+        call_contract_with_code(osaka_env(Address::from_low_u64_be(1000)), container, vec![]);
+
+    assert!(report.is_success());
+    assert_eq!(stored_value(&report, contract), U256::from(42));
+}
+
+#[test]
+fn eof_opcodes_are_invalid_in_legacy_code() {
+    // RJUMP 0, STOP
+    let (_, report) = call_contract_with_code(
+        osaka_env(Address::from_low_u64_be(1000)),
+        Bytes::from_static(&[0xe0, 0x00, 0x00, 0x00]),
+        vec![],
+    );
+
+    assert_eq!(report.result, TxResult::Revert(VMError::InvalidOpcode));
+}
+
+#[test]
+fn eof_code_is_not_executed_before_osaka() {
+    // PUSH1 1, PUSH0, SSTORE, STOP
+    let container = eof_container(&[(0, 0x80, 2, &[0x60, 0x01, 0x5f, 0x55, 0x00])], &[], &[]);
+    let mut env = osaka_env(Address::from_low_u64_be(1000));
+    env.fork = Fork::Cancun;
+    let (contract, report) = call_contract_with_code(env, container, vec![]);
+
+    assert!(!report.new_state[&contract]
+        .storage
+        .contains_key(&H256::zero()));
+}
+
+#[test]
+fn extcall_pushes_the_callee_result() {
+    let callee = Address::from_low_u64_be(3000);
+    // PUSH0 (value), PUSH0 (input size), PUSH0 (input offset), PUSH20 callee, EXTCALL, PUSH0,
+    // MSTORE, PUSH1 32, PUSH0, RETURN
+    let mut code = vec![0x5f, 0x5f, 0x5f, 0x73];
+    code.extend_from_slice(callee.as_bytes());
+    code.extend_from_slice(&[0xf8, 0x5f, 0x52, 0x60, 0x20, 0x5f, 0xf3]);
+    let container = eof_container(&[(0, 0x80, 4, &code)], &[], &[]);
+
+    for (callee_code, expected_result) in [
+        // PUSH0, PUSH0, REVERT
+        (vec![0x5f, 0x5f, 0xfd], REVERT_FOR_EXT_CALL),
+        // INVALID
+        (vec![0xfe], FAILURE_FOR_EXT_CALL),
+    ] {
+        let (_, report) = call_contract_with_code(
+            osaka_env(Address::from_low_u64_be(1000)),
+            container.clone(),
+            vec![(callee, Account::default().with_bytecode(callee_code.into()))],
+        );
+
+        assert_eq!(report.result, TxResult::Success);
+        assert_eq!(
+            U256::from_big_endian(&report.output),
+            U256::from(expected_result)
+        );
+    }
+}
+
+#[test]
+fn eof_create_transaction_deploys_the_returned_container() {
+    let sender = Address::from_low_u64_be(1000);
+    let runtime = eof_container(&[(0, 0x80, 0, &[0x00])], &[], &[]);
+    // PUSH0 (aux data size), PUSH0 (aux data offset), RETURNCONTRACT 0
+    let initcode = eof_container(
+        &[(0, 0x80, 2, &[0x5f, 0x5f, 0xee, 0x00])],
+        std::slice::from_ref(&runtime),
+        &[],
+    );
+    let mut db = Db::new();
+    db.add_accounts(vec![(
+        sender,
+        Account::default().with_balance(U256::from(10_000_000)),
+    )]);
+    let mut vm = VM::new(
+        TxKind::Create,
+        osaka_env(sender),
+        U256::zero(),
+        initcode,
+        Arc::new(db),
+        CacheDB::default(),
+    )
+    .unwrap();
+    let contract_address = vm.current_call_frame_mut().unwrap().to;
+    let report = vm.transact().unwrap();
+
+    assert!(report.is_success());
+    assert_eq!(report.created_address, Some(contract_address));
+    assert_eq!(report.new_state[&contract_address].info.bytecode, runtime);
+}
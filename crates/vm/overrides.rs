@@ -0,0 +1,244 @@
+use std::{collections::HashMap, sync::Arc};
+
+use bytes::Bytes;
+use ethrex_core::{
+    serde_utils,
+    types::{code_hash, AccountInfo, BlockHeader},
+    Address, H256, U256,
+};
+use ethrex_levm::{
+    account::AccountInfo as LevmAccountInfo,
+    db::{BlockState, Database as LevmDatabase},
+    errors::DatabaseError as LevmDatabaseError,
+};
+use ethrex_storage::AccountUpdate;
+use revm::{
+    db::{AccountState as RevmAccountState, AccountStatus, PlainAccount},
+    primitives::{AccountInfo as RevmAccountInfo, Bytecode as RevmBytecode},
+};
+use revm_primitives::{B256, U256 as RevmU256};
+use serde::Deserialize;
+
+use crate::{errors::EvmError, EvmState, RevmAddress};
+
+/// Account fields replaced while simulating calls, keyed by the account's address.
+///
+/// Follows geth's format for the state override parameter of `eth_call`.
+pub type StateOverride = HashMap<Address, AccountOverride>;
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountOverride {
+    #[serde(default)]
+    pub balance: Option<U256>,
+    #[serde(default, with = "serde_utils::u64::hex_str_opt")]
+    pub nonce: Option<u64>,
+    #[serde(default, with = "serde_utils::bytes::opt")]
+    pub code: Option<Bytes>,
+    /// Replaces the whole storage of the account, every slot not given is empty
+    #[serde(default)]
+    pub state: Option<HashMap<H256, H256>>,
+    /// Replaces only the given storage slots, keeping the rest
+    #[serde(default)]
+    pub state_diff: Option<HashMap<H256, H256>>,
+}
+
+impl AccountOverride {
+    /// Checks the override can be applied to the account with the given address
+    pub fn validate(&self, address: Address) -> Result<(), EvmError> {
+        if self.state.is_some() && self.state_diff.is_some() {
+            return Err(EvmError::Custom(format!(
+                "account {address:#x} has both 'state' and 'stateDiff'"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Account updates leaving the account as overridden, given its info once the override
+    /// is applied
+    pub fn account_updates(&self, address: Address, info: AccountInfo) -> Vec<AccountUpdate> {
+        let mut update = AccountUpdate::new(address);
+        update.info = Some(info);
+        update.code = self.code.clone();
+        update.added_storage = self.storage().collect();
+        if self.state.is_some() {
+            // The previous storage is dropped by removing the account before writing it again
+            vec![AccountUpdate::removed(address), update]
+        } else {
+            vec![update]
+        }
+    }
+
+    /// Slots whose value is overridden, whether the whole storage is replaced or not
+    fn storage(&self) -> impl Iterator<Item = (H256, U256)> + '_ {
+        self.state
+            .iter()
+            .chain(self.state_diff.iter())
+            .flatten()
+            .map(|(key, value)| (*key, U256::from_big_endian(value.as_bytes())))
+    }
+}
+
+/// Block header fields replaced while simulating calls, in geth's format
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockOverrides {
+    #[serde(default, with = "serde_utils::u64::hex_str_opt")]
+    pub number: Option<u64>,
+    #[serde(default, with = "serde_utils::u64::hex_str_opt")]
+    pub time: Option<u64>,
+    #[serde(default, with = "serde_utils::u64::hex_str_opt")]
+    pub gas_limit: Option<u64>,
+    #[serde(default)]
+    pub fee_recipient: Option<Address>,
+    #[serde(default)]
+    pub prev_randao: Option<H256>,
+    #[serde(default, with = "serde_utils::u64::hex_str_opt")]
+    pub base_fee_per_gas: Option<u64>,
+}
+
+impl BlockOverrides {
+    /// Replaces the overridden fields of the header
+    pub fn apply(&self, header: &mut BlockHeader) {
+        if let Some(number) = self.number {
+            header.number = number;
+        }
+        if let Some(time) = self.time {
+            header.timestamp = time;
+        }
+        if let Some(gas_limit) = self.gas_limit {
+            header.gas_limit = gas_limit;
+        }
+        if let Some(fee_recipient) = self.fee_recipient {
+            header.coinbase = fee_recipient;
+        }
+        if let Some(prev_randao) = self.prev_randao {
+            header.prev_randao = prev_randao;
+        }
+        if let Some(base_fee_per_gas) = self.base_fee_per_gas {
+            header.base_fee_per_gas = Some(base_fee_per_gas);
+        }
+    }
+}
+
+/// Applies the overrides to the accounts cached by revm, so they are seen instead of the ones
+/// in the database
+pub fn apply_state_overrides(
+    state: &mut EvmState,
+    overrides: &StateOverride,
+) -> Result<(), EvmError> {
+    for (address, account_override) in overrides {
+        account_override.validate(*address)?;
+        let revm_address = RevmAddress::from_slice(address.as_bytes());
+        let storage = account_override
+            .storage()
+            .map(|(key, value)| {
+                (
+                    RevmU256::from_be_bytes(key.0),
+                    RevmU256::from_limbs(value.0),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        match state {
+            EvmState::Store(db) => {
+                let account = db.load_cache_account(revm_address)?;
+                let plain_account = account.account.get_or_insert_with(PlainAccount::default);
+                override_revm_info(&mut plain_account.info, account_override);
+                if account_override.state.is_some() {
+                    // Slots missing from a changed account are empty instead of read from the db
+                    plain_account.storage.clear();
+                    account.status = AccountStatus::InMemoryChange;
+                }
+                plain_account.storage.extend(storage);
+            }
+            EvmState::Execution(db) => {
+                let account = db.load_account(revm_address)?;
+                override_revm_info(&mut account.info, account_override);
+                if account_override.state.is_some() {
+                    account.storage.clear();
+                    account.account_state = RevmAccountState::StorageCleared;
+                }
+                account.storage.extend(storage);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn override_revm_info(info: &mut RevmAccountInfo, account_override: &AccountOverride) {
+    if let Some(balance) = account_override.balance {
+        info.balance = RevmU256::from_limbs(balance.0);
+    }
+    if let Some(nonce) = account_override.nonce {
+        info.nonce = nonce;
+    }
+    if let Some(code) = &account_override.code {
+        info.code_hash = B256::from(code_hash(code).0);
+        info.code = Some(RevmBytecode::new_raw(code.clone().into()));
+    }
+}
+
+/// Database for LEVM that returns the overridden accounts instead of the ones in the
+/// underlying database
+pub struct OverrideDatabase {
+    db: Arc<dyn LevmDatabase>,
+    overrides: StateOverride,
+}
+
+impl OverrideDatabase {
+    pub fn new(db: Arc<dyn LevmDatabase>, overrides: StateOverride) -> Result<Self, EvmError> {
+        for (address, account_override) in &overrides {
+            account_override.validate(*address)?;
+        }
+        Ok(Self { db, overrides })
+    }
+}
+
+impl LevmDatabase for OverrideDatabase {
+    fn get_account_info(&self, address: Address) -> Result<LevmAccountInfo, LevmDatabaseError> {
+        let mut info = self.db.get_account_info(address)?;
+        if let Some(account_override) = self.overrides.get(&address) {
+            if let Some(balance) = account_override.balance {
+                info.balance = balance;
+            }
+            if let Some(nonce) = account_override.nonce {
+                info.nonce = nonce;
+            }
+            if let Some(code) = &account_override.code {
+                info.set_bytecode(code.clone());
+            }
+        }
+        Ok(info)
+    }
+
+    fn get_storage_slot(&self, address: Address, key: H256) -> Result<U256, LevmDatabaseError> {
+        let Some(account_override) = self.overrides.get(&address) else {
+            return self.db.get_storage_slot(address, key);
+        };
+        let slots = account_override
+            .state
+            .as_ref()
+            .or(account_override.state_diff.as_ref());
+        match slots.and_then(|slots| slots.get(&key)) {
+            Some(value) => Ok(U256::from_big_endian(value.as_bytes())),
+            None if account_override.state.is_some() => Ok(U256::zero()),
+            None => self.db.get_storage_slot(address, key),
+        }
+    }
+
+    fn get_block_hash(&self, block_number: u64) -> Result<Option<H256>, LevmDatabaseError> {
+        self.db.get_block_hash(block_number)
+    }
+}
+
+/// Layers the overrides on top of the current LEVM block state
+// LEVM databases aren't required to be `Send` but block states still hold them behind an `Arc`
+#[allow(clippy::arc_with_non_send_sync)]
+pub fn apply_state_overrides_levm(
+    block_state: &mut BlockState,
+    overrides: &StateOverride,
+) -> Result<(), EvmError> {
+    let db = OverrideDatabase::new(Arc::new(block_state.clone()), overrides.clone())?;
+    *block_state = BlockState::new(Arc::new(db));
+    Ok(())
+}
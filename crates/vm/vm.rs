@@ -1,21 +1,32 @@
 pub mod db;
+pub mod differential;
 pub mod errors;
+mod evm;
 pub mod execution_db;
 mod execution_result;
 #[cfg(feature = "l2")]
 mod mods;
+mod overrides;
 
 use db::StoreWrapper;
 use execution_db::ExecutionDB;
-use std::cmp::min;
+use std::{cmp::min, collections::HashMap};
 
 use ethrex_core::{
     types::{
-        AccountInfo, Block, BlockHash, BlockHeader, ChainConfig, Fork, GenericTransaction,
-        PrivilegedTxType, Receipt, Transaction, TxKind, Withdrawal, GWEI_TO_WEI, INITIAL_BASE_FEE,
+        AccessListEntry, AccountInfo, Block, BlockHash, BlockHeader, ChainConfig, Fork,
+        GenericTransaction, PrivilegedTxType, Receipt, Transaction, TxKind, Withdrawal,
+        GWEI_TO_WEI, INITIAL_BASE_FEE,
     },
     Address, BigEndianHash, H256, U256,
 };
+use ethrex_levm::{
+    constants::LAST_PRECOMPILE_ADDRESS,
+    db::{AccountChange, BlockState},
+    errors::{TransactionReport, TxResult, VMError},
+    vm::VM,
+    Environment,
+};
 use ethrex_storage::{error::StoreError, AccountUpdate, Store};
 use lazy_static::lazy_static;
 use revm::{
@@ -24,9 +35,9 @@ use revm::{
     inspectors::TracerEip3155,
     precompile::{PrecompileSpecId, Precompiles},
     primitives::{BlobExcessGasAndPrice, BlockEnv, TxEnv, B256},
-    Database, DatabaseCommit, Evm,
+    Database, DatabaseCommit, Evm as RevmEvm,
 };
-use revm_inspectors::access_list::AccessListInspector;
+use revm_inspectors::{access_list::AccessListInspector, transfer::TransferInspector};
 // Rename imported types for clarity
 use revm_primitives::{
     ruint::Uint, AccessList as RevmAccessList, AccessListItem, Bytes, FixedBytes,
@@ -34,7 +45,10 @@ use revm_primitives::{
 };
 // Export needed types
 pub use errors::EvmError;
+pub use ethrex_levm::constants::{TRANSFER_EVENT_TOPIC, TRANSFER_LOG_EMITTER};
+pub use evm::{Evm, EvmEngine};
 pub use execution_result::*;
+pub use overrides::{AccountOverride, BlockOverrides, OverrideDatabase, StateOverride};
 pub use revm::primitives::{Address as RevmAddress, SpecId, U256 as RevmU256};
 
 type AccessList = Vec<(Address, Vec<H256>)>;
@@ -77,164 +91,386 @@ impl From<ExecutionDB> for EvmState {
     }
 }
 
-cfg_if::cfg_if! {
-    if #[cfg(feature = "levm")] {
-        use ethrex_levm::{
-            db::{CacheDB, Database as LevmDatabase},
-            errors::{TransactionReport, TxResult, VMError},
-            vm::VM,
-            Environment,
-        };
-        use std::{collections::HashMap, sync::Arc};
-        use ethrex_core::types::code_hash;
-
-        /// Executes all transactions in a block and returns their receipts.
-        pub fn execute_block(
-            block: &Block,
-            state: &mut EvmState,
-        ) -> Result<(Vec<Receipt>, Vec<AccountUpdate>), EvmError> {
-            let block_header = &block.header;
+/// Executes all transactions in a block and returns their receipts.
+pub fn execute_block(block: &Block, state: &mut EvmState) -> Result<Vec<Receipt>, EvmError> {
+    let block_header = &block.header;
+    let spec_id = spec_id(&state.chain_config()?, block_header.timestamp);
+    //eip 4788: execute beacon_root_contract_call before block transactions
+    cfg_if::cfg_if! {
+        if #[cfg(not(feature = "l2"))] {
             //eip 4788: execute beacon_root_contract_call before block transactions
-            cfg_if::cfg_if! {
-                if #[cfg(not(feature = "l2"))] {
-                    let spec_id = spec_id(&state.chain_config()?, block_header.timestamp);
-                    if block_header.parent_beacon_block_root.is_some() && spec_id == SpecId::CANCUN {
-                        beacon_root_contract_call(state, block_header, spec_id)?;
-                    }
-                }
+            if block_header.parent_beacon_block_root.is_some() && spec_id.is_enabled_in(SpecId::CANCUN) {
+                beacon_root_contract_call(state, block_header, spec_id)?;
             }
-            let mut receipts = Vec::new();
-            let mut cumulative_gas_used = 0;
-
-            let store_wrapper = Arc::new(StoreWrapper {
-                store: state.database().unwrap().clone(),
-                block_hash: block.header.parent_hash,
-            });
-
-            let mut account_updates: Vec<AccountUpdate> = vec![];
-
-            for transaction in block.body.transactions.iter() {
-                let result = execute_tx_levm(transaction, block_header, store_wrapper.clone()).unwrap();
-                cumulative_gas_used += result.gas_used;
-                let receipt = Receipt::new(
-                    transaction.tx_type(),
-                    matches!(result.result, TxResult::Success),
-                    cumulative_gas_used,
-                    result.logs,
-                );
-                receipts.push(receipt);
+        }
+    }
+    let mut receipts = Vec::new();
+    let mut cumulative_gas_used = 0;
+
+    for transaction in block.body.transactions.iter() {
+        let result = execute_tx(transaction, block_header, state, spec_id)?;
+        cumulative_gas_used += result.gas_used();
+        let receipt = Receipt::new(
+            transaction.tx_type(),
+            result.is_success(),
+            cumulative_gas_used,
+            result.logs(),
+        );
+        receipts.push(receipt);
+    }
 
-                for (address, account) in result.new_state {
-                    let mut added_storage = HashMap::new();
+    if let Some(withdrawals) = &block.body.withdrawals {
+        process_withdrawals(state, withdrawals)?;
+    }
 
-                    for (key, value) in account.storage {
-                        added_storage.insert(key, value.current_value);
-                    }
+    Ok(receipts)
+}
 
-                    let code = if account.info.bytecode.is_empty() {
-                        None
-                    } else {
-                        Some(account.info.bytecode.clone())
-                    };
+/// Executes all transactions in a block with LEVM and returns their receipts. The changes made
+/// by the block are kept in the block state, see [`get_state_transitions_levm`].
+pub fn execute_block_levm(
+    block: &Block,
+    block_state: &mut BlockState,
+    chain_config: &ChainConfig,
+) -> Result<Vec<Receipt>, EvmError> {
+    let block_header = &block.header;
+    //eip 4788: execute beacon_root_contract_call before block transactions
+    cfg_if::cfg_if! {
+        if #[cfg(not(feature = "l2"))] {
+            let spec_id = spec_id(chain_config, block_header.timestamp);
+            if block_header.parent_beacon_block_root.is_some() && spec_id.is_enabled_in(SpecId::CANCUN) {
+                beacon_root_contract_call_levm(block_state, block_header)?;
+            }
+        }
+    }
+    let mut receipts = Vec::new();
+    let mut cumulative_gas_used = 0;
+
+    for transaction in block.body.transactions.iter() {
+        let result = execute_tx_levm(
+            transaction,
+            block_header,
+            block_state,
+            chain_config.get_fork(block_header.timestamp),
+        )?;
+        cumulative_gas_used += result.gas_used;
+        let receipt = Receipt::new(
+            transaction.tx_type(),
+            matches!(result.result, TxResult::Success),
+            cumulative_gas_used,
+            result.logs,
+        );
+        receipts.push(receipt);
+    }
 
-                    let account_update = AccountUpdate {
-                        address,
-                        removed: false,
-                        info: Some(AccountInfo {
-                            code_hash: code_hash(&account.info.bytecode),
-                            balance: account.info.balance,
-                            nonce: account.info.nonce,
-                        }),
-                        code,
-                        added_storage,
-                    };
+    if let Some(withdrawals) = &block.body.withdrawals {
+        process_withdrawals_levm(block_state, withdrawals)?;
+    }
 
-                    account_updates.push(account_update);
-                }
-            }
+    Ok(receipts)
+}
 
-            if let Some(withdrawals) = &block.body.withdrawals {
-                process_withdrawals(state, withdrawals)?;
-            }
+/// Executes a transaction on top of the changes made by the previous transactions of the
+/// block, and merges its resulting state into the block state
+pub fn execute_tx_levm(
+    tx: &Transaction,
+    block_header: &BlockHeader,
+    block_state: &mut BlockState,
+    fork: Fork,
+) -> Result<TransactionReport, VMError> {
+    let report = run_tx_levm(tx, block_header, block_state, fork)?;
+    block_state.commit(report.new_state.clone())?;
+    Ok(report)
+}
 
-            Ok((receipts, account_updates))
-        }
+/// Executes a transaction on top of the changes made by the previous transactions of the
+/// block, without merging its resulting state into the block state
+pub fn run_tx_levm(
+    tx: &Transaction,
+    block_header: &BlockHeader,
+    block_state: &BlockState,
+    fork: Fork,
+) -> Result<TransactionReport, VMError> {
+    let gas_price: U256 = tx
+        .effective_gas_price(block_header.base_fee_per_gas)
+        .ok_or(VMError::InvalidTransaction)?
+        .into();
+
+    let env = Environment {
+        origin: tx.sender(),
+        consumed_gas: U256::from(21000), // Base gas cost for a transaction
+        gas_limit: tx.gas_limit().into(),
+        block_number: block_header.number.into(),
+        coinbase: block_header.coinbase,
+        timestamp: block_header.timestamp.into(),
+        prev_randao: Some(block_header.prev_randao),
+        chain_id: tx.chain_id().unwrap_or_default().into(),
+        base_fee_per_gas: block_header.base_fee_per_gas.unwrap_or_default().into(),
+        gas_price,
+        block_excess_blob_gas: block_header.excess_blob_gas.map(U256::from),
+        block_blob_gas_used: block_header.blob_gas_used.map(U256::from),
+        tx_blob_hashes: tx.blob_versioned_hashes(),
+        tx_max_priority_fee_per_gas: tx.max_priority_fee().map(U256::from),
+        tx_max_fee_per_gas: tx.max_fee_per_gas().map(U256::from),
+        tx_max_fee_per_blob_gas: tx.max_fee_per_blob_gas().map(U256::from),
+        tx_access_list: tx.access_list(),
+        block_gas_limit: block_header.gas_limit.into(),
+        fork,
+        trace_transfers: false,
+    };
 
-        pub fn execute_tx_levm(
-            tx: &Transaction,
-            block_header: &BlockHeader,
-            db: Arc<dyn LevmDatabase>,
-        ) -> Result<TransactionReport, VMError> {
-            let gas_price : U256 = tx.effective_gas_price(block_header.base_fee_per_gas).ok_or(VMError::InvalidTransaction)?.into();
-
-            let env = Environment {
-                origin: tx.sender(),
-                consumed_gas: U256::from(21000), // Base gas cost for a transaction
-                refunded_gas: U256::zero(),
-                gas_limit: tx.gas_limit().into(),
-                block_number: block_header.number.into(),
-                coinbase: block_header.coinbase,
-                timestamp: block_header.timestamp.into(),
-                prev_randao: Some(block_header.prev_randao),
-                chain_id: tx.chain_id().unwrap().into(),
-                base_fee_per_gas: block_header.base_fee_per_gas.unwrap_or_default().into(),
-                gas_price,
-                block_excess_blob_gas: block_header.excess_blob_gas.map(U256::from),
-                block_blob_gas_used: block_header.blob_gas_used.map(U256::from),
-                tx_blob_hashes: tx.blob_versioned_hashes(),
-                tx_max_priority_fee_per_gas: tx.max_priority_fee().map(U256::from),
-                tx_max_fee_per_gas: tx.max_fee_per_gas().map(U256::from),
-                tx_max_fee_per_blob_gas: tx.max_fee_per_blob_gas().map(U256::from),
-                block_gas_limit: block_header.gas_limit.into(),
-            };
-
-            let mut vm = VM::new(
-                tx.to(),
-                env,
-                tx.value(),
-                tx.data().clone(),
-                db,
-                CacheDB::default(),
-            )?;
-
-            vm.transact()
-        }
-    } else if #[cfg(not(feature = "levm"))] {
-        /// Executes all transactions in a block and returns their receipts.
-        pub fn execute_block(block: &Block, state: &mut EvmState) -> Result<Vec<Receipt>, EvmError> {
-            let block_header = &block.header;
-            let spec_id = spec_id(&state.chain_config()?, block_header.timestamp);
-            //eip 4788: execute beacon_root_contract_call before block transactions
-            cfg_if::cfg_if! {
-                if #[cfg(not(feature = "l2"))] {
-                    //eip 4788: execute beacon_root_contract_call before block transactions
-                    if block_header.parent_beacon_block_root.is_some() && spec_id == SpecId::CANCUN {
-                        beacon_root_contract_call(state, block_header, spec_id)?;
-                    }
-                }
+    let mut vm = VM::new(
+        tx.to(),
+        env,
+        tx.value(),
+        tx.data().clone(),
+        block_state.db(),
+        block_state.transaction_cache(),
+    )?;
+
+    vm.transact()
+}
+
+/// Calls the eip4788 beacon block root system contract with LEVM, storing its changes
+/// in the block state
+pub fn beacon_root_contract_call_levm(
+    block_state: &mut BlockState,
+    header: &BlockHeader,
+) -> Result<TransactionReport, EvmError> {
+    lazy_static! {
+        static ref SYSTEM_ADDRESS: Address =
+            Address::from_slice(&hex::decode("fffffffffffffffffffffffffffffffffffffffe").unwrap());
+        static ref CONTRACT_ADDRESS: Address =
+            Address::from_slice(&hex::decode("000F3df6D732807Ef1319fB7B8bB8522d0Beac02").unwrap(),);
+    };
+    let beacon_root = header
+        .parent_beacon_block_root
+        .ok_or_else(|| EvmError::Header("parent_beacon_block_root field is missing".to_string()))?;
+
+    let env = Environment {
+        origin: *SYSTEM_ADDRESS,
+        gas_limit: U256::from(30_000_000),
+        block_number: header.number.into(),
+        coinbase: header.coinbase,
+        timestamp: header.timestamp.into(),
+        prev_randao: Some(header.prev_randao),
+        block_excess_blob_gas: header.excess_blob_gas.map(U256::from),
+        block_blob_gas_used: header.blob_gas_used.map(U256::from),
+        block_gas_limit: U256::from(30_000_000),
+        ..Environment::default_from_address(*SYSTEM_ADDRESS)
+    };
+
+    let mut vm = VM::new(
+        TxKind::Call(*CONTRACT_ADDRESS),
+        env,
+        U256::zero(),
+        bytes::Bytes::copy_from_slice(beacon_root.as_bytes()),
+        block_state.db(),
+        block_state.transaction_cache(),
+    )?;
+
+    let report = vm.execute_system_call()?;
+    block_state.commit(report.new_state.clone())?;
+    Ok(report)
+}
+
+/// Processes a block's withdrawals, increasing the account balances in the block state
+pub fn process_withdrawals_levm(
+    block_state: &mut BlockState,
+    withdrawals: &[Withdrawal],
+) -> Result<(), EvmError> {
+    for withdrawal in withdrawals
+        .iter()
+        .filter(|withdrawal| withdrawal.amount > 0)
+    {
+        let amount = U256::from(withdrawal.amount) * U256::from(GWEI_TO_WEI);
+        block_state.increase_account_balance(withdrawal.address, amount)?;
+    }
+    Ok(())
+}
+
+/// Returns the account updates of the accounts modified by the block
+/// Doesn't update the DB
+pub fn get_state_transitions_levm(block_state: &BlockState) -> Vec<AccountUpdate> {
+    account_updates_levm(block_state.changes())
+}
+
+/// Turns the account changes reported by LEVM into account updates
+fn account_updates_levm(changes: Vec<AccountChange>) -> Vec<AccountUpdate> {
+    changes
+        .into_iter()
+        .map(|change| {
+            // Accounts left empty, such as destroyed ones, are removed from the database
+            if change.info.is_empty() {
+                return AccountUpdate::removed(change.address);
             }
-            let mut receipts = Vec::new();
-            let mut cumulative_gas_used = 0;
-
-            for transaction in block.body.transactions.iter() {
-                let result = execute_tx(transaction, block_header, state, spec_id)?;
-                cumulative_gas_used += result.gas_used();
-                let receipt = Receipt::new(
-                    transaction.tx_type(),
-                    result.is_success(),
-                    cumulative_gas_used,
-                    result.logs(),
-                );
-                receipts.push(receipt);
+            let mut account_update = AccountUpdate::new(change.address);
+            if change.info_changed {
+                account_update.info = Some(AccountInfo {
+                    code_hash: change.info.code_hash,
+                    balance: change.info.balance,
+                    nonce: change.info.nonce,
+                });
+                if change.code_changed {
+                    account_update.code = Some(change.info.bytecode);
+                }
             }
+            account_update.added_storage = change.storage;
+            account_update
+        })
+        .collect()
+}
 
-            if let Some(withdrawals) = &block.body.withdrawals {
-                process_withdrawals(state, withdrawals)?;
-            }
+/// Executes a GenericTransaction with LEVM on top of the block state, without merging its changes
+/// into it. The VM is returned along with the report so the accessed accounts can be inspected.
+fn run_generic_tx_levm(
+    tx: &GenericTransaction,
+    header: &BlockHeader,
+    block_state: &BlockState,
+    chain_config: &ChainConfig,
+    config: SimulationConfig,
+) -> Result<(TransactionReport, VM), EvmError> {
+    let base_fee = header.base_fee_per_gas.unwrap_or(INITIAL_BASE_FEE);
+    let gas_price = U256::from_little_endian(calculate_gas_price(tx, base_fee).as_le_slice());
+    let gas_limit = tx.gas.unwrap_or(header.gas_limit);
+
+    let env = Environment {
+        origin: tx.from,
+        gas_limit: gas_limit.into(),
+        block_number: header.number.into(),
+        coinbase: header.coinbase,
+        timestamp: header.timestamp.into(),
+        prev_randao: Some(header.prev_randao),
+        chain_id: tx.chain_id.unwrap_or(chain_config.chain_id).into(),
+        // As with revm, the base fee isn't enforced on simulations, so a lower gas price (such
+        // as none at all) is accepted
+        base_fee_per_gas: if config.validation {
+            base_fee.into()
+        } else {
+            gas_price.min(base_fee.into())
+        },
+        gas_price,
+        block_excess_blob_gas: header.excess_blob_gas.map(U256::from),
+        block_blob_gas_used: header.blob_gas_used.map(U256::from),
+        tx_blob_hashes: tx.blob_versioned_hashes.clone(),
+        tx_max_priority_fee_per_gas: tx.max_priority_fee_per_gas.map(U256::from),
+        tx_max_fee_per_gas: tx.max_fee_per_gas.map(U256::from),
+        tx_max_fee_per_blob_gas: tx.max_fee_per_blob_gas,
+        tx_access_list: tx
+            .access_list
+            .iter()
+            .map(|entry| (entry.address, entry.storage_keys.clone()))
+            .collect(),
+        // The block gas limit isn't enforced on simulations
+        block_gas_limit: header.gas_limit.max(gas_limit).into(),
+        fork: chain_config.get_fork(header.timestamp),
+        trace_transfers: config.trace_transfers,
+        ..Environment::default_from_address(tx.from)
+    };
 
-            Ok(receipts)
-        }
+    let mut vm = VM::new(
+        tx.to.clone(),
+        env,
+        tx.value,
+        tx.input.clone(),
+        block_state.db(),
+        block_state.transaction_cache(),
+    )?;
+    let report = vm.transact()?;
+    Ok((report, vm))
+}
+
+/// Executes a single GenericTransaction with LEVM, doesn't commit the result
+pub fn simulate_tx_from_generic_levm(
+    tx: &GenericTransaction,
+    header: &BlockHeader,
+    block_state: &BlockState,
+    chain_config: &ChainConfig,
+) -> Result<ExecutionResult, EvmError> {
+    let (report, _) = run_generic_tx_levm(
+        tx,
+        header,
+        block_state,
+        chain_config,
+        SimulationConfig::default(),
+    )?;
+    Ok(report.into())
+}
+
+/// Executes a GenericTransaction with LEVM and merges its changes into the block state
+pub fn execute_tx_from_generic_levm(
+    tx: &GenericTransaction,
+    header: &BlockHeader,
+    block_state: &mut BlockState,
+    chain_config: &ChainConfig,
+    config: SimulationConfig,
+) -> Result<ExecutionResult, EvmError> {
+    let (report, _) = run_generic_tx_levm(tx, header, block_state, chain_config, config)?;
+    block_state.commit(report.new_state.clone())?;
+    Ok(report.into())
+}
+
+/// Runs the transaction with LEVM and returns the access list along with its execution result
+pub fn create_access_list_levm(
+    tx: &GenericTransaction,
+    header: &BlockHeader,
+    block_state: &BlockState,
+    chain_config: &ChainConfig,
+) -> Result<(ExecutionResult, AccessList), EvmError> {
+    let (report, vm) = run_generic_tx_levm(
+        tx,
+        header,
+        block_state,
+        chain_config,
+        SimulationConfig::default(),
+    )?;
+    // Same exclusions as revm's access list inspector: the sender, the called or created
+    // account and the precompiles. The coinbase is always warm in LEVM, so it's excluded too.
+    let to = match tx.to {
+        TxKind::Call(address) => Some(address),
+        TxKind::Create => report.created_address,
+    };
+    let is_excluded = |address: &Address| {
+        *address == tx.from
+            || Some(*address) == to
+            || *address == header.coinbase
+            || (1..=LAST_PRECOMPILE_ADDRESS).contains(&address.to_low_u64_be())
+                && address[..12] == [0; 12]
+    };
+    let substate = vm.accrued_substate;
+    let mut access_list: HashMap<Address, Vec<H256>> = substate
+        .accessed_addresses
+        .iter()
+        .filter(|address| !is_excluded(address))
+        .map(|address| (*address, Vec::new()))
+        .collect();
+    for (address, keys) in substate.accessed_storage_keys {
+        access_list.entry(address).or_default().extend(keys);
     }
+    // Sorted, as the substate doesn't keep the order in which accounts and slots were accessed
+    let mut access_list: AccessList = access_list.into_iter().collect();
+    for (_, keys) in access_list.iter_mut() {
+        keys.sort();
+    }
+    access_list.sort();
+
+    // Run the tx with the resulting access list to estimate its gas used
+    let report = if report.is_success() {
+        let mut tx = tx.clone();
+        tx.access_list
+            .extend(access_list.iter().map(AccessListEntry::from));
+        run_generic_tx_levm(
+            &tx,
+            header,
+            block_state,
+            chain_config,
+            SimulationConfig::default(),
+        )?
+        .0
+    } else {
+        report
+    };
+    Ok((report.into(), access_list))
 }
 
 // Executes a single tx, doesn't perform state transitions
@@ -249,6 +485,64 @@ pub fn execute_tx(
     run_evm(tx_env, block_env, state, spec_id)
 }
 
+/// How the calls of a simulation spanning several transactions are executed
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SimulationConfig {
+    /// Enforces the base fee and the transaction nonces, as done for real transactions
+    pub validation: bool,
+    /// Reports every ether transfer as a log emitted by [`TRANSFER_LOG_EMITTER`]
+    pub trace_transfers: bool,
+}
+
+/// Executes a GenericTransaction and commits its result, so it's seen by the following ones.
+/// The state transitions are kept until they are collected, as done for block transactions.
+pub fn execute_tx_from_generic(
+    tx: &GenericTransaction,
+    header: &BlockHeader,
+    state: &mut EvmState,
+    spec_id: SpecId,
+    config: SimulationConfig,
+) -> Result<ExecutionResult, EvmError> {
+    let mut block_env = block_env(header);
+    let mut tx_env = tx_env_from_generic(tx, header.base_fee_per_gas.unwrap_or(INITIAL_BASE_FEE));
+    if !config.validation {
+        tx_env.nonce = None;
+        adjust_disabled_base_fee(
+            &mut block_env,
+            tx_env.gas_price,
+            tx_env.max_fee_per_blob_gas,
+        );
+    }
+    let chain_id = state.chain_config()?.chain_id;
+    let mut transfer_inspector = TransferInspector::new(false).with_logs(config.trace_transfers);
+    let evm_builder = RevmEvm::builder()
+        .with_block_env(block_env)
+        .with_tx_env(tx_env)
+        .with_spec_id(spec_id)
+        .modify_cfg_env(|env| {
+            env.disable_base_fee = !config.validation;
+            env.chain_id = chain_id;
+        })
+        .with_external_context(&mut transfer_inspector);
+    let tx_result = match state {
+        EvmState::Store(db) => {
+            let mut evm = evm_builder
+                .with_db(db)
+                .append_handler_register(inspector_handle_register)
+                .build();
+            evm.transact_commit().map_err(EvmError::from)?
+        }
+        EvmState::Execution(db) => {
+            let mut evm = evm_builder
+                .with_db(db)
+                .append_handler_register(inspector_handle_register)
+                .build();
+            evm.transact_commit().map_err(EvmError::from)?
+        }
+    };
+    Ok(tx_result.into())
+}
+
 // Executes a single GenericTransaction, doesn't commit the result or perform state transitions
 pub fn simulate_tx_from_generic(
     tx: &GenericTransaction,
@@ -287,7 +581,7 @@ fn run_evm(
     let tx_result = {
         let chain_spec = state.chain_config()?;
         #[allow(unused_mut)]
-        let mut evm_builder = Evm::builder()
+        let mut evm_builder = RevmEvm::builder()
             .with_block_env(block_env)
             .with_tx_env(tx_env)
             .modify_cfg_env(|cfg| cfg.chain_id = chain_spec.chain_id)
@@ -372,7 +666,7 @@ fn create_access_list_inner(
 ) -> Result<(ExecutionResult, RevmAccessList), EvmError> {
     let mut access_list_inspector = access_list_inspector(&tx_env, state, spec_id)?;
     #[allow(unused_mut)]
-    let mut evm_builder = Evm::builder()
+    let mut evm_builder = RevmEvm::builder()
         .with_block_env(block_env)
         .with_tx_env(tx_env)
         .with_spec_id(spec_id)
@@ -419,7 +713,7 @@ fn run_without_commit(
     );
     let chain_config = state.chain_config()?;
     #[allow(unused_mut)]
-    let mut evm_builder = Evm::builder()
+    let mut evm_builder = RevmEvm::builder()
         .with_block_env(block_env)
         .with_tx_env(tx_env)
         .with_spec_id(spec_id)
@@ -649,7 +943,7 @@ pub fn beacon_root_contract_call(
 
     match state {
         EvmState::Store(db) => {
-            let mut evm = Evm::builder()
+            let mut evm = RevmEvm::builder()
                 .with_db(db)
                 .with_block_env(block_env)
                 .with_tx_env(tx_env)
@@ -666,7 +960,7 @@ pub fn beacon_root_contract_call(
             Ok(transaction_result.result.into())
         }
         EvmState::Execution(db) => {
-            let mut evm = Evm::builder()
+            let mut evm = RevmEvm::builder()
                 .with_db(db)
                 .with_block_env(block_env)
                 .with_tx_env(tx_env)
@@ -853,6 +1147,7 @@ fn access_list_inspector(
 /// WARNING: Assumes at least Merge fork is active
 pub fn spec_id(chain_config: &ChainConfig, block_timestamp: u64) -> SpecId {
     match chain_config.get_fork(block_timestamp) {
+        Fork::Osaka => SpecId::PRAGUE_EOF,
         Fork::Cancun => SpecId::CANCUN,
         Fork::Shanghai => SpecId::SHANGHAI,
         Fork::Paris => SpecId::MERGE,